@@ -0,0 +1,261 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! Syntax checks for the IPFS CIDs records and pins store, without pulling in a
+//! multiformats stack. Two forms are accepted: a CIDv0, the 46-character
+//! base58btc encoding of a sha2-256 multihash that always starts "Qm", and a
+//! CIDv1 in lowercase base32 behind the "b" multibase prefix. Either way the
+//! multihash header is decoded and its digest length held to the bytes that
+//! follow it. Nothing here looks at what the digest hashes.
+//!
+//! Varints must be minimal and base32 carries no stray trailing bits, so a CID
+//! that parses has one spelling and `Cid::encode` gives back the same string.
+
+use crate::MAX_IPFS_CID_LEN;
+
+/// Length of every CIDv0
+pub const CIDV0_LEN: usize = 46;
+/// Shortest base32 CIDv1, prefix included: enough characters for a version,
+/// codec, multihash code and digest length of one byte each
+pub const MIN_CIDV1_LEN: usize = 8;
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// The multihash header of a CIDv0: sha2-256, 32 bytes
+const SHA2_256_HEADER: [u8; 2] = [0x12, 0x20];
+/// Longest varint a multiformats field may take
+const MAX_VARINT_LEN: usize = 9;
+/// Codec of every CIDv0
+pub const DAG_PB_CODEC: u64 = 0x70;
+
+/// A CID taken apart
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cid {
+    pub version: u8,
+    /// Multicodec of the content, `DAG_PB_CODEC` for a CIDv0
+    pub codec: u64,
+    /// Hash function code and digest length, then the digest
+    pub multihash: Vec<u8>,
+}
+
+impl Cid {
+    /// `cid` taken apart if it is a CIDv0 or base32 CIDv1 with a well-formed
+    /// multihash, otherwise the rule it broke
+    pub fn parse(cid: &str) -> Result<Self, &'static str> {
+        let cid = cid.as_bytes();
+        match cid {
+            [] => Err("empty"),
+            [b'Q', b'm', ..] => parse_v0(cid),
+            [b'b', body @ ..] => parse_v1(body),
+            _ => Err("unknown multibase prefix"),
+        }
+    }
+
+    /// The CID in the form `parse` reads: base58btc for a v0, base32 behind
+    /// "b" for a v1
+    pub fn encode(&self) -> String {
+        if self.version == 0 {
+            return encode_base58(&self.multihash);
+        }
+        let mut bytes = Vec::with_capacity(2 * MAX_VARINT_LEN + self.multihash.len());
+        write_varint(&mut bytes, self.version.into());
+        write_varint(&mut bytes, self.codec);
+        bytes.extend_from_slice(&self.multihash);
+        let mut cid = String::with_capacity(1 + (bytes.len() * 8).div_ceil(5));
+        cid.push('b');
+        let (mut acc, mut bits) = (0u16, 0);
+        for byte in bytes {
+            acc = (acc << 8) | u16::from(byte);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                cid.push(BASE32_ALPHABET[usize::from(acc >> bits) & 0x1f] as char);
+            }
+            acc &= (1 << bits) - 1;
+        }
+        if bits > 0 {
+            cid.push(BASE32_ALPHABET[usize::from(acc << (5 - bits)) & 0x1f] as char);
+        }
+        cid
+    }
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// `Ok` if `cid` is a CIDv0 or base32 CIDv1 with a well-formed multihash,
+/// otherwise the rule it broke
+pub fn validate(cid: &str) -> Result<(), &'static str> {
+    Cid::parse(cid).map(drop)
+}
+
+/// Bytes of the multihash at the front of `bytes`, its header included
+pub fn multihash_len(bytes: &[u8]) -> Result<usize, &'static str> {
+    let mut rest = bytes;
+    read_varint(&mut rest)?;
+    let digest_len = read_varint(&mut rest)?;
+    let header_len = bytes.len() - rest.len();
+    usize::try_from(digest_len)
+        .ok()
+        .and_then(|digest_len| header_len.checked_add(digest_len))
+        .filter(|&len| len <= bytes.len())
+        .ok_or("multihash digest length")
+}
+
+fn parse_v0(cid: &[u8]) -> Result<Cid, &'static str> {
+    if cid.len() != CIDV0_LEN {
+        return Err("CIDv0 length");
+    }
+    // Big-endian base58 into the 34 bytes of a sha2-256 multihash; anything
+    // that overflows them, or leaves the header short, isn't one
+    let mut multihash = [0u8; SHA2_256_HEADER.len() + 32];
+    for &c in cid {
+        let digit = BASE58_ALPHABET.iter().position(|&a| a == c).ok_or("base58btc alphabet")?;
+        let mut carry = digit as u32;
+        for byte in multihash.iter_mut().rev() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        if carry != 0 {
+            return Err("CIDv0 multihash header");
+        }
+    }
+    if multihash[..2] != SHA2_256_HEADER {
+        return Err("CIDv0 multihash header");
+    }
+    Ok(Cid {
+        version: 0,
+        codec: DAG_PB_CODEC,
+        multihash: multihash.to_vec(),
+    })
+}
+
+fn parse_v1(body: &[u8]) -> Result<Cid, &'static str> {
+    if !(MIN_CIDV1_LEN - 1..MAX_IPFS_CID_LEN).contains(&body.len()) {
+        return Err("CIDv1 length");
+    }
+    let mut bytes = [0u8; MAX_IPFS_CID_LEN * 5 / 8];
+    let (mut len, mut acc, mut bits) = (0, 0u16, 0);
+    for &c in body {
+        let value = match c {
+            b'a'..=b'z' => c - b'a',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return Err("base32 alphabet"),
+        };
+        acc = (acc << 5) | u16::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes[len] = (acc >> bits) as u8;
+            len += 1;
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if acc != 0 {
+        return Err("base32 trailing bits");
+    }
+
+    let mut rest = &bytes[..len];
+    if read_varint(&mut rest)? != 1 {
+        return Err("CIDv1 version");
+    }
+    let codec = read_varint(&mut rest)?;
+    if multihash_len(rest)? != rest.len() {
+        return Err("multihash digest length");
+    }
+    Ok(Cid {
+        version: 1,
+        codec,
+        multihash: rest.to_vec(),
+    })
+}
+
+/// Take a minimal unsigned LEB128 varint off the front of `bytes`
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, &'static str> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().take(MAX_VARINT_LEN).enumerate() {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                break;
+            }
+            *bytes = &bytes[i + 1..];
+            return Ok(value);
+        }
+    }
+    Err("varint")
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Big-endian base58 of `bytes`, a '1' for each leading zero byte
+fn encode_base58(bytes: &[u8]) -> String {
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &byte in bytes {
+        let mut carry = u32::from(byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    let mut encoded = "1".repeat(zeros);
+    encoded.extend(digits.iter().rev().map(|&digit| BASE58_ALPHABET[usize::from(digit)] as char));
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const V0: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+    /// raw codec, sha2-256 digest
+    const V1: &str = "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm";
+    /// dag-json codec, a two-byte codec varint
+    const V1_DAG_JSON: &str = "baguqeeralutycbwmjziuo5q5q4niowhiwlfhg6nga3fhvqqha2zvn62kf4jq";
+
+    #[test]
+    fn test_well_formed_cids_pass() {
+        assert_eq!(validate(V0), Ok(()));
+        assert_eq!(validate(V1), Ok(()));
+    }
+
+    #[test]
+    fn test_cids_round_trip() {
+        for cid in [V0, V1, V1_DAG_JSON] {
+            assert_eq!(Cid::parse(cid).unwrap().encode(), cid);
+        }
+        let v0 = Cid::parse(V0).unwrap();
+        assert_eq!((v0.version, v0.codec, &v0.multihash[..2]), (0, DAG_PB_CODEC, &SHA2_256_HEADER[..]));
+        let v1 = Cid::parse(V1).unwrap();
+        assert_eq!((v1.version, v1.codec, v1.multihash.len()), (1, 0x55, 34));
+        assert_eq!(Cid::parse(V1_DAG_JSON).unwrap().codec, 0x0129);
+    }
+
+    #[test]
+    fn test_each_rule_is_named() {
+        assert_eq!(validate(""), Err("empty"));
+        assert_eq!(validate("zb2rhe5P4gXftAwvA4eXQ5HJwsER2owDyS9sKaQRRVQPn93bA"), Err("unknown multibase prefix"));
+        assert_eq!(validate(&V0[..45]), Err("CIDv0 length"));
+        assert_eq!(validate(&V0.replace('w', "0")), Err("base58btc alphabet"));
+        assert_eq!(validate(&format!("Qm{}", "z".repeat(44))), Err("CIDv0 multihash header"));
+        assert_eq!(validate("bafy"), Err("CIDv1 length"));
+        assert_eq!(validate(&V1.to_uppercase().replacen('B', "b", 1)), Err("base32 alphabet"));
+        assert_eq!(validate(&V1[..58]), Err("base32 trailing bits"));
+        assert_eq!(validate(&V1[..57]), Err("multihash digest length"));
+        // version 2, then a varint that never ends
+        assert_eq!(validate("bajkreiaa"), Err("CIDv1 version"));
+        assert_eq!(validate("bafk7777777777777757q"), Err("varint"));
+        // a codec of 0x55 spelled over two bytes
+        assert_eq!(validate("bahkqaeralutycbwmjziuo5q5q4niowhiwlfhg6nga3fhvqqha2zvn62kf4jq"), Err("varint"));
+    }
+}
@@ -0,0 +1,370 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! Building `verify_eligibility` submissions from arkworks types, and checking
+//! them before they are sent. Both go through `verifier_core`, the code the
+//! program itself runs, so client and program cannot disagree on the layout or
+//! on whether a proof verifies. `AuditTree` rebuilds the Merkle tree a
+//! `Checkpoint` commits to from the program's events.
+
+use crate::offchain::{g1_syscall_bytes, g2_syscall_bytes};
+use crate::verifier_core::{self, VerifyError};
+use crate::{
+    AccessPassConsumed, AccessPassIssued, AnonymousEligibilityVerified, Checkpoint, CheckpointCommitted,
+    CircuitRegistered, CircuitStatusChanged, ClaimAdjudicated, ClaimEscrowFunded, ClaimNullifierReleased,
+    ClaimSubmitted, DataAccessed, DataPinned, DataPinnedBatch, DataUnpinned, DiagnosisRevoked, DiagnosisVerified,
+    DisputeResolved, EligibilityVerified, Groth16Proof, GuardianConsentGranted, GuardianConsentRevoked, HashAlgo,
+    HoldPlaced, HoldReleased, ImmunizationVerified, LabResultVerified, MetadataUpdated, PatientIndex, PatientKeyRotated,
+    PinAbandoned, PinBountyClaimed, PinConfirmed, PinExpired, PinFailed, PinRecordMigrated, PinRenewed,
+    PinReplicaConfirmed, PinRetagged, PinRetried, PinUpdated, PrescriptionRefilled, PrescriptionVerified, ProofFailed,
+    ProofFormat, ProofNullifier, RecordClaimed, RecordExported, RecordImported, RecordStatusChanged, RecordsClosed,
+    ReplicationDegraded, StorageDealExpiring, StorageDealRecorded, VerificationClosed, VerificationDisputed,
+    VerificationExpired, VerificationInvalidatedByCircuitRevocation, VerificationRecordMigrated, VerificationRenewed,
+    VerificationRevoked, VerificationTokenBurned, VerificationTokenMinted, VerificationType, VerifyingKeyClosed,
+    VerifyingKeyFinalized, VerifyingKeyRegistered, VerifyingKeyUpdateProposed, VerifyingKeyUpdated,
+};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::{system_program, Discriminator, InstructionData, ToAccountMetas};
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::Proof;
+use std::collections::BTreeMap;
+
+pub use crate::{derive_verification_id, derive_verification_pda, event_authority_address};
+
+/// The `VerifyingKeyPDA` of `circuit_id`
+pub fn vk_address(circuit_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"vk", circuit_id.as_bytes()], &crate::ID).0
+}
+
+/// The `PatientIndex` of `patient`, counting its records and holding in
+/// `next_record_nonce` the nonce to pass to `build_verify_eligibility_ix`; zero if
+/// the account doesn't exist yet
+pub fn patient_index_address(patient: &Pubkey) -> Pubkey {
+    PatientIndex::address(patient)
+}
+
+/// The `ProofNullifier` a submission of `proof` against `public_inputs` claims
+pub fn nullifier_address(proof: &[u8], format: ProofFormat, public_inputs: &[u8]) -> Pubkey {
+    let seed = ProofNullifier::seed(proof, format, public_inputs);
+    Pubkey::find_program_address(&[b"nullifier", &seed], &crate::ID).0
+}
+
+/// Serialize `proof` in the layout `format` names
+pub fn encode_proof(proof: &Proof<Bn254>, format: ProofFormat) -> Vec<u8> {
+    Groth16Proof {
+        a: g1_syscall_bytes(&proof.a),
+        b: g2_syscall_bytes(&proof.b),
+        c: g1_syscall_bytes(&proof.c),
+    }
+    .encode(format)
+    .expect("arkworks points are on the curve")
+}
+
+/// 32 little-endian bytes per input, as `ProofFormat::Uncompressed` and
+/// `ProofFormat::Compressed` expect
+pub fn encode_public_inputs(public_inputs: &[Fr]) -> Vec<u8> {
+    public_inputs
+        .iter()
+        .flat_map(|input| input.into_bigint().to_bytes_le())
+        .collect()
+}
+
+/// A `verify_eligibility` instruction proving `public_inputs` with `proof`. The
+/// record lands at `derive_verification_pda` of `record_nonce`, the patient's
+/// next one, and the record of the nonce before it is passed as the previous
+/// record; the verifying key and nullifier addresses are derived from the
+/// submission. `fee_recipient` is the key's `fee_recipient`, needed only when the
+/// circuit charges a fee.
+pub fn build_verify_eligibility_ix(
+    registry: Pubkey,
+    record_nonce: u64,
+    patient: Pubkey,
+    circuit_id: &str,
+    proof: &Proof<Bn254>,
+    proof_format: ProofFormat,
+    public_inputs: &[Fr],
+    ipfs_hash: &str,
+    fee_recipient: Option<Pubkey>,
+) -> Instruction {
+    let proof = encode_proof(proof, proof_format);
+    let mut public_inputs = encode_public_inputs(public_inputs);
+    if proof_format.big_endian_inputs() {
+        public_inputs.chunks_exact_mut(32).for_each(|scalar| scalar.reverse());
+    }
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::VerifyEligibility {
+            registry,
+            verification: derive_verification_pda(&patient, VerificationType::Eligibility, record_nonce).0,
+            verifying_key: vk_address(circuit_id),
+            nullifier: nullifier_address(&proof, proof_format, &public_inputs),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient,
+            cache: None,
+            commitment: None,
+            patient_index: patient_index_address(&patient),
+            previous_record: record_nonce
+                .checked_sub(1)
+                .map(|nonce| derive_verification_pda(&patient, VerificationType::Eligibility, nonce).0),
+            provider: None,
+            provider_registration: None,
+            instructions: None,
+            verification_mint: None,
+            patient_token_account: None,
+            token_program: None,
+            associated_token_program: None,
+            event_authority: event_authority_address(),
+            program: crate::ID,
+        }
+        .to_account_metas(None),
+        data: crate::instruction::VerifyEligibility {
+            proof,
+            proof_format,
+            public_inputs,
+            ipfs_hash: ipfs_hash.to_string(),
+            circuit_id: circuit_id.to_string(),
+            record_nonce,
+            salt: None,
+            hash_algo: HashAlgo::Keccak,
+            mint_token: false,
+            attestation: None,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+/// Why `preflight_verify` rejected a submission; `code()` is the error
+/// `verify_eligibility` would fail with
+pub type PreflightError = VerifyError;
+
+/// Check a Groth16 submission over BN254 against `vk_bytes` locally, with the
+/// code the program runs: the key checks `finalize_vk` makes, then everything
+/// `verify_eligibility` checks of the proof and its public inputs. Checks that
+/// depend on accounts (registry limits, input bindings, freshness, the nullifier,
+/// circuit status) are not covered.
+pub fn preflight_verify(
+    vk_bytes: &[u8],
+    proof_bytes: &[u8],
+    public_inputs_bytes: &[u8],
+    format: ProofFormat,
+) -> Result<(), PreflightError> {
+    verifier_core::check_verifying_key(vk_bytes)?;
+    let prepared = verifier_core::prepare_vk_bytes(vk_bytes)?;
+    let n_public = verifier_core::prepared_n_public(&prepared);
+    let (_, _, verified) =
+        verifier_core::verify_submission(&prepared, n_public, proof_bytes, format, public_inputs_bytes)?;
+    if !verified {
+        return Err(VerifyError::PairingCheckFailed);
+    }
+    Ok(())
+}
+
+/// Discriminators of the program's events, each of which starts with its `seq`
+/// and registry
+const EVENTS: [[u8; 8]; 62] = [
+    EligibilityVerified::DISCRIMINATOR,
+    CircuitRegistered::DISCRIMINATOR,
+    DataPinned::DISCRIMINATOR,
+    VerifyingKeyUpdateProposed::DISCRIMINATOR,
+    VerifyingKeyUpdated::DISCRIMINATOR,
+    VerifyingKeyRegistered::DISCRIMINATOR,
+    VerifyingKeyFinalized::DISCRIMINATOR,
+    CircuitStatusChanged::DISCRIMINATOR,
+    VerifyingKeyClosed::DISCRIMINATOR,
+    AnonymousEligibilityVerified::DISCRIMINATOR,
+    VerificationInvalidatedByCircuitRevocation::DISCRIMINATOR,
+    VerificationRevoked::DISCRIMINATOR,
+    VerificationExpired::DISCRIMINATOR,
+    RecordStatusChanged::DISCRIMINATOR,
+    VerificationClosed::DISCRIMINATOR,
+    RecordsClosed::DISCRIMINATOR,
+    ProofFailed::DISCRIMINATOR,
+    PrescriptionVerified::DISCRIMINATOR,
+    PrescriptionRefilled::DISCRIMINATOR,
+    DiagnosisVerified::DISCRIMINATOR,
+    LabResultVerified::DISCRIMINATOR,
+    ImmunizationVerified::DISCRIMINATOR,
+    DiagnosisRevoked::DISCRIMINATOR,
+    AccessPassIssued::DISCRIMINATOR,
+    AccessPassConsumed::DISCRIMINATOR,
+    VerificationRenewed::DISCRIMINATOR,
+    VerificationRecordMigrated::DISCRIMINATOR,
+    VerificationTokenMinted::DISCRIMINATOR,
+    VerificationTokenBurned::DISCRIMINATOR,
+    VerificationDisputed::DISCRIMINATOR,
+    ClaimSubmitted::DISCRIMINATOR,
+    ClaimNullifierReleased::DISCRIMINATOR,
+    CheckpointCommitted::DISCRIMINATOR,
+    ClaimEscrowFunded::DISCRIMINATOR,
+    ClaimAdjudicated::DISCRIMINATOR,
+    DisputeResolved::DISCRIMINATOR,
+    MetadataUpdated::DISCRIMINATOR,
+    PatientKeyRotated::DISCRIMINATOR,
+    RecordClaimed::DISCRIMINATOR,
+    GuardianConsentGranted::DISCRIMINATOR,
+    GuardianConsentRevoked::DISCRIMINATOR,
+    DataUnpinned::DISCRIMINATOR,
+    HoldPlaced::DISCRIMINATOR,
+    HoldReleased::DISCRIMINATOR,
+    RecordExported::DISCRIMINATOR,
+    RecordImported::DISCRIMINATOR,
+    PinRecordMigrated::DISCRIMINATOR,
+    DataAccessed::DISCRIMINATOR,
+    PinConfirmed::DISCRIMINATOR,
+    PinFailed::DISCRIMINATOR,
+    PinRetried::DISCRIMINATOR,
+    PinAbandoned::DISCRIMINATOR,
+    PinRenewed::DISCRIMINATOR,
+    PinExpired::DISCRIMINATOR,
+    PinBountyClaimed::DISCRIMINATOR,
+    PinReplicaConfirmed::DISCRIMINATOR,
+    ReplicationDegraded::DISCRIMINATOR,
+    DataPinnedBatch::DISCRIMINATOR,
+    StorageDealRecorded::DISCRIMINATOR,
+    StorageDealExpiring::DISCRIMINATOR,
+    PinRetagged::DISCRIMINATOR,
+    PinUpdated::DISCRIMINATOR,
+];
+
+/// First byte hashed into a leaf, and into an inner node, so neither can be
+/// passed off as the other
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// The `seq` and registry an event of this program starts with, given as the
+/// bytes it was logged with (`Program data:`, discriminator included); `None`
+/// for any other program's event and for the unnumbered `ProofChecked` and
+/// `AccessDenied`
+pub fn event_header(event: &[u8]) -> Option<(u64, Pubkey)> {
+    let (discriminator, fields) = event.split_first_chunk::<8>()?;
+    if !EVENTS.contains(discriminator) {
+        return None;
+    }
+    let (seq, fields) = fields.split_first_chunk::<8>()?;
+    let (registry, _) = fields.split_first_chunk::<32>()?;
+    Some((u64::from_le_bytes(*seq), Pubkey::new_from_array(*registry)))
+}
+
+/// The `seq` of an event, as `event_header` reads it
+pub fn event_seq(event: &[u8]) -> Option<u64> {
+    event_header(event).map(|(seq, _)| seq)
+}
+
+/// `keccak(0 || event)`, the leaf of a logged event in an `AuditTree`
+pub fn audit_leaf(event: &[u8]) -> [u8; 32] {
+    keccak::hashv(&[&[LEAF_PREFIX], event]).to_bytes()
+}
+
+fn audit_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&[NODE_PREFIX], left, right]).to_bytes()
+}
+
+/// The Merkle tree whose root `commit_checkpoint` commits: one `audit_leaf` per
+/// event of the checkpoint's range, in `event_seq` order, paired up level by
+/// level with `keccak(1 || left || right)`. The odd node at the end of a level
+/// moves up unpaired.
+pub struct AuditTree {
+    start_seq: u64,
+    /// Leaves first, the root alone last
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+/// The siblings `InclusionProof::verify` hashes an event's leaf with, from the
+/// leaves up, skipping the levels where its node moves up unpaired
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub event_seq: u64,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl AuditTree {
+    /// The tree over `registry`'s events `start_seq` up to `end_seq`, taken
+    /// from `events` in any order, logged bytes as `event_header` reads them.
+    /// Other programs' and registries' events and those outside the range are
+    /// ignored; a missing one is returned as the error.
+    pub fn from_events<'a>(
+        events: impl IntoIterator<Item = &'a [u8]>,
+        registry: &Pubkey,
+        start_seq: u64,
+        end_seq: u64,
+    ) -> Result<Self, u64> {
+        let by_seq: BTreeMap<u64, &[u8]> = events
+            .into_iter()
+            .filter_map(|event| Some((event_header(event)?, event)))
+            .filter(|((seq, from), _)| from == registry && (start_seq..end_seq).contains(seq))
+            .map(|((seq, _), event)| (seq, event))
+            .collect();
+        let leaves = (start_seq..end_seq)
+            .map(|seq| by_seq.get(&seq).map(|event| audit_leaf(event)).ok_or(seq))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => audit_node(left, right),
+                    [odd] => *odd,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(parents);
+        }
+        Ok(Self { start_seq, levels })
+    }
+
+    /// The root to pass `commit_checkpoint`; all zeros for an empty range, which
+    /// it refuses anyway
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().and_then(|level| level.first()).copied().unwrap_or_default()
+    }
+
+    /// Proof that event `event_seq` is under `root`; `None` if it is out of range
+    pub fn prove(&self, event_seq: u64) -> Option<InclusionProof> {
+        let mut index = usize::try_from(event_seq.checked_sub(self.start_seq)?).ok()?;
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                siblings.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(InclusionProof { event_seq, siblings })
+    }
+}
+
+impl InclusionProof {
+    /// Whether `event`, as logged, is the event this proof is for, from the
+    /// checkpoint's registry, and is under `checkpoint`'s root. Needs nothing
+    /// but the checkpoint account, so an auditor can check it against the chain
+    /// without trusting whoever served the event.
+    pub fn verify(&self, checkpoint: &Checkpoint, event: &[u8]) -> bool {
+        if event_header(event) != Some((self.event_seq, checkpoint.registry))
+            || !(checkpoint.start_seq..checkpoint.end_seq).contains(&self.event_seq)
+        {
+            return false;
+        }
+        let mut index = self.event_seq - checkpoint.start_seq;
+        let mut width = checkpoint.end_seq - checkpoint.start_seq;
+        let mut hash = audit_leaf(event);
+        let mut siblings = self.siblings.iter();
+        while width > 1 {
+            if index % 2 == 1 {
+                let Some(sibling) = siblings.next() else { return false };
+                hash = audit_node(sibling, &hash);
+            } else if index + 1 < width {
+                let Some(sibling) = siblings.next() else { return false };
+                hash = audit_node(&hash, sibling);
+            }
+            index /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == checkpoint.root
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! A development circuit with a fixed key and proof, for exercising the real
+//! verification path end to end. The circuit proves knowledge of a preimage `x`
+//! of the public value `x^5 + PREIMAGE_ROUND_CONSTANT`, a single round of the
+//! Poseidon S-box; keys and proof come from fixed RNG seeds, so every call returns
+//! the same bytes. Not a hash anyone should rely on: the point is a real Groth16
+//! statement small enough to set up in milliseconds.
+
+use crate::offchain::encode_verifying_key;
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+/// Public inputs of the circuit: the hash
+pub const N_PUBLIC: u8 = 1;
+/// Added after the S-box so `x = 0` doesn't hash to zero
+pub const PREIMAGE_ROUND_CONSTANT: u64 = 7;
+/// Preimage behind `valid_proof`
+pub const PREIMAGE: u64 = 42;
+const SETUP_SEED: u64 = 0x5eed;
+const PROVE_SEED: u64 = 0xf00d;
+
+/// `x^5 + c = hash`, as `x2 = x * x`, `x4 = x2 * x2`, `hash - c = x4 * x`
+#[derive(Clone, Default)]
+pub struct PreimageCircuit {
+    pub preimage: Option<Fr>,
+}
+
+impl PreimageCircuit {
+    pub fn hash(preimage: Fr) -> Fr {
+        let square = preimage * preimage;
+        square * square * preimage + Fr::from(PREIMAGE_ROUND_CONSTANT)
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for PreimageCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let x_value = self.preimage;
+        let hash = cs.new_input_variable(|| {
+            x_value.map(Self::hash).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let x = cs.new_witness_variable(|| x_value.ok_or(SynthesisError::AssignmentMissing))?;
+        let x2_value = x_value.map(|x| x * x);
+        let x2 = cs.new_witness_variable(|| x2_value.ok_or(SynthesisError::AssignmentMissing))?;
+        let x4 = cs.new_witness_variable(|| {
+            x2_value.map(|x2| x2 * x2).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let constant = Fr::from(PREIMAGE_ROUND_CONSTANT);
+
+        cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x2)?;
+        cs.enforce_constraint(lc!() + x2, lc!() + x2, lc!() + x4)?;
+        cs.enforce_constraint(lc!() + x4, lc!() + x, lc!() + hash - (constant, Variable::One))?;
+        Ok(())
+    }
+}
+
+/// The circuit's proving and verifying keys
+pub fn keys() -> (ProvingKey<Bn254>, VerifyingKey<Bn254>) {
+    let mut rng = StdRng::seed_from_u64(SETUP_SEED);
+    Groth16::<Bn254>::circuit_specific_setup(PreimageCircuit::default(), &mut rng)
+        .expect("the preimage circuit is satisfiable")
+}
+
+/// A proof that `preimage` hashes to `PreimageCircuit::hash(preimage)`
+pub fn prove(preimage: Fr) -> Proof<Bn254> {
+    let mut rng = StdRng::seed_from_u64(PROVE_SEED);
+    let circuit = PreimageCircuit { preimage: Some(preimage) };
+    Groth16::<Bn254>::prove(&keys().0, circuit, &mut rng).expect("the preimage circuit is satisfiable")
+}
+
+/// The verifying key in the layout uploaded through `write_vk_chunk`
+pub fn vk_bytes() -> Vec<u8> {
+    encode_verifying_key(&keys().1)
+}
+
+/// Uncompressed arkworks proof of `PREIMAGE`, for `ProofFormat::Uncompressed`
+pub fn valid_proof() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    prove(Fr::from(PREIMAGE))
+        .serialize_uncompressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes
+}
+
+/// The public inputs `valid_proof` verifies against, little-endian as
+/// `ProofFormat::Uncompressed` expects
+pub fn public_inputs() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    PreimageCircuit::hash(Fr::from(PREIMAGE))
+        .serialize_uncompressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes
+}
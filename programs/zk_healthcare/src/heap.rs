@@ -0,0 +1,137 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! The program's allocator under the `custom-heap` feature. The stock allocator
+//! only ever hands out the default 32KB, whatever heap frame the transaction
+//! requests; this one serves the full `HEAP_FRAME_BYTES`, so verifications with
+//! more public inputs fit once the client asks for the frame with
+//! `ComputeBudgetInstruction::request_heap_frame`. Without that request,
+//! allocations past 32KB touch unmapped memory and abort the transaction.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::mem::size_of;
+use std::ptr::{self, null_mut};
+
+/// The largest heap frame a transaction can request
+pub const HEAP_FRAME_BYTES: usize = 256 * 1024;
+
+#[cfg(all(target_os = "solana", feature = "custom-heap", not(feature = "no-entrypoint")))]
+#[global_allocator]
+static ALLOCATOR: BumpAllocator = BumpAllocator {
+    start: anchor_lang::solana_program::entrypoint::HEAP_START_ADDRESS as usize,
+    len: HEAP_FRAME_BYTES,
+};
+
+/// Bump allocation upwards from `start`, with the next free address kept in the
+/// first word of the region (zero, before the first allocation, as the runtime
+/// hands the heap over). Growing or freeing the most recent allocation is done in
+/// place, so a `Vec` pushed to while nothing else allocates costs its final size
+/// rather than the sum of every capacity it passed through.
+pub struct BumpAllocator {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl BumpAllocator {
+    fn next(&self) -> *mut usize {
+        self.start as *mut usize
+    }
+
+    /// Move the free pointer to `end`, if the region reaches that far
+    unsafe fn bump_to(&self, end: usize) -> bool {
+        if end > self.start.saturating_add(self.len) {
+            return false;
+        }
+        *self.next() = end;
+        true
+    }
+}
+
+unsafe impl GlobalAlloc for BumpAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let next = match *self.next() {
+            0 => self.start + size_of::<usize>(),
+            next => next,
+        };
+        let Some(begin) = next.checked_add(layout.align() - 1).map(|addr| addr & !(layout.align() - 1)) else {
+            return null_mut();
+        };
+        match begin.checked_add(layout.size()) {
+            Some(end) if self.bump_to(end) => begin as *mut u8,
+            _ => null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ptr as usize + layout.size() == *self.next() {
+            *self.next() = ptr as usize;
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if ptr as usize + layout.size() == *self.next() {
+            return match (ptr as usize).checked_add(new_size) {
+                Some(end) if self.bump_to(end) => ptr,
+                _ => null_mut(),
+            };
+        }
+        let moved = self.alloc(Layout::from_size_align_unchecked(new_size, layout.align()));
+        if !moved.is_null() {
+            ptr::copy_nonoverlapping(ptr, moved, layout.size().min(new_size));
+        }
+        moved
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An allocator over a zeroed local buffer
+    fn allocator(heap: &mut [usize]) -> BumpAllocator {
+        BumpAllocator {
+            start: heap.as_mut_ptr() as usize,
+            len: std::mem::size_of_val(heap),
+        }
+    }
+
+    #[test]
+    fn test_allocations_are_aligned_and_bounded() {
+        let mut heap = [0usize; 16];
+        let heap_end = heap.as_ptr() as usize + std::mem::size_of_val(&heap);
+        let allocator = allocator(&mut heap);
+        unsafe {
+            let byte = allocator.alloc(Layout::from_size_align(1, 1).unwrap());
+            let word = allocator.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert_eq!(byte as usize, allocator.start + size_of::<usize>());
+            assert_eq!(word as usize % 8, 0);
+            assert!(word as usize > byte as usize);
+
+            let rest = heap_end - allocator.alloc(Layout::from_size_align(1, 1).unwrap()) as usize;
+            assert!(allocator.alloc(Layout::from_size_align(rest, 1).unwrap()).is_null());
+            assert!(!allocator.alloc(Layout::from_size_align(rest - 1, 1).unwrap()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_last_allocation_grows_and_frees_in_place() {
+        let mut heap = [0usize; 16];
+        let allocator = allocator(&mut heap);
+        unsafe {
+            let layout = Layout::from_size_align(8, 8).unwrap();
+            let first = allocator.alloc(layout);
+            *first = 7;
+            let grown = allocator.realloc(first, layout, 32);
+            assert_eq!(grown, first);
+
+            // Once something else is allocated, growing copies
+            let other = allocator.alloc(layout);
+            let moved = allocator.realloc(grown, Layout::from_size_align(32, 8).unwrap(), 40);
+            assert!(moved as usize > other as usize);
+            assert_eq!(*moved, 7);
+
+            allocator.dealloc(moved, Layout::from_size_align(40, 8).unwrap());
+            assert_eq!(allocator.alloc(layout), moved);
+            assert!(allocator.realloc(moved, layout, 1024).is_null());
+        }
+    }
+}
@@ -1,266 +1,13067 @@
 // Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
 
+// Anchor's generated `cpi` wrappers take every instruction argument and can't be
+// annotated, so the lint is silenced for the crate
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::alt_bn128::prelude::alt_bn128_pairing;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
 use anchor_lang::solana_program::keccak;
-use ark_groth16::{Groth16, prepare_verifying_key, verify_proof};
-use ark_bn254::{Bn254, Parameters};
-use ark_serialize::CanonicalDeserialize;
+use anchor_lang::solana_program::poseidon;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::Token2022;
+use std::borrow::Cow;
+
+pub mod cid;
+// Instruction builders for integrators, never part of the on-chain program
+#[cfg(all(feature = "client", not(target_os = "solana")))]
+pub mod client;
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod fixtures;
+pub mod heap;
+pub mod migrations;
+#[cfg(any(test, feature = "offchain"))]
+pub mod offchain;
+pub mod verifier;
+pub mod verifier_core;
+
+#[cfg(feature = "bls")]
+use verifier::Bls12_381Verifier;
+use verifier::{Groth16Verifier, PlonkVerifier, ProofVerifier, UnsupportedVerifier};
+use verifier_core::{
+    accumulate_vk_x, canonical_point, check_ic_len, compute_vk_x, g1_add, g1_mul, is_identity, negate_g1,
+    pairing_check, scalar_to_be, split_prepared_vk,
+};
+
+declare_id!("HEALTHZKhea1thcare11111111111111111111111111");
 
-declare_id!("HEALth11111111111111111111111111111111111");
+/// Most proofs `verify_eligibility_batch` accepts in one instruction
+pub const MAX_BATCH_SIZE: usize = 8;
+/// Slots a `PartialVerification` stays usable before anyone may reclaim it
+pub const PARTIAL_VERIFICATION_TTL_SLOTS: u64 = 150;
+/// Public inputs `advance_verification` folds into `vk_x` per call
+pub const INPUTS_PER_ADVANCE: usize = 16;
+/// How far a proof timestamp may run ahead of the cluster clock
+pub const MAX_CLOCK_SKEW_SECS: i64 = 120;
+/// Longest circuit identifier accepted (also the PDA seed length limit)
+pub const MAX_CIRCUIT_ID_LEN: usize = 32;
+/// Longest IPFS CID a record or pin stores, room for a base32 CIDv1
+pub const MAX_IPFS_CID_LEN: usize = 64;
+/// Longest multihash a pin stores: a 32-byte digest behind a one-byte hash
+/// code and length
+pub const PIN_MULTIHASH_LEN: usize = 34;
+/// Longest id a pin holds for a `StorageBackend::Custom` store, stored behind
+/// its length in a pin's `multihash`
+pub const MAX_CUSTOM_STORAGE_ID_LEN: usize = PIN_MULTIHASH_LEN - 1;
+/// Most pinning oracles a registry lists, see `add_pinning_oracle`
+pub const MAX_PINNING_ORACLES: usize = 8;
+/// Longest `provider_id` a pinning oracle reports in `confirm_pin`
+pub const MAX_PIN_PROVIDER_ID_LEN: usize = 64;
+/// Most oracles a pin keeps unclaimed bounty shares for at once, see
+/// `claim_pin_bounty`
+pub const MAX_BOUNTY_SHARES: usize = 8;
+/// Most oracles a pin counts as holding its content, see `confirm_pin`
+pub const MAX_PIN_REPLICAS: usize = 8;
+/// Most Filecoin deals a pin records, see `set_storage_deal`
+pub const MAX_STORAGE_DEALS: usize = 4;
+/// Longest storage provider address a deal records, room for the `f0` id
+/// address of any actor
+pub const MAX_DEAL_PROVIDER_LEN: usize = 22;
+/// Most replaced versions a pin keeps in its `history`, see `update_pin`
+pub const MAX_PIN_VERSIONS: usize = 8;
+/// Most pins `pin_medical_data_bulk` writes at once. A transaction of the
+/// patient's alone fits 6 CIDv0 entries, 5 CIDv1 ones, in its 1232 bytes;
+/// with its other accounts in an address lookup table it fits 11 and 9.
+pub const MAX_BULK_PINS: usize = 9;
+/// Variants of `VerificationType`, the length of the per-type arrays
+pub const VERIFICATION_TYPE_COUNT: usize = 6;
+/// Variants of `DataCategory`, the length of `PatientIndex::pin_category_counts`
+pub const DATA_CATEGORY_COUNT: usize = 6;
+/// Largest verifying key accepted. Keys whose account outgrows one
+/// `MAX_PERMITTED_DATA_INCREASE` are grown with `resize_vk_account`.
+pub const MAX_VK_LEN: u32 = 32 * 1024;
+/// Most public inputs a `HashAlgo::Poseidon` proof hash can cover (the widest
+/// light-poseidon BN254 parameter set)
+pub const MAX_POSEIDON_INPUTS: usize = 12;
+/// `max_public_inputs` of a new registry, about as many inputs as fit in one
+/// transaction beside a proof
+pub const DEFAULT_MAX_PUBLIC_INPUTS: u8 = 32;
+/// `cache_ttl_slots` of a new registry, roughly an hour of slots
+pub const DEFAULT_CACHE_TTL_SLOTS: u64 = 9_000;
+/// Target slot time, by which `TimeSource::Slot` counts windows set in seconds
+pub const NOMINAL_SLOT_MS: i64 = 400;
+/// `max_metadata_len` of a new registry
+pub const DEFAULT_MAX_METADATA_LEN: u16 = 512;
+/// `dispute_window_secs` of a new registry
+pub const DEFAULT_DISPUTE_WINDOW_SECS: i64 = 14 * 24 * 60 * 60;
+/// `failed_pin_timeout_secs` of a new registry
+pub const DEFAULT_FAILED_PIN_TIMEOUT_SECS: i64 = 7 * 24 * 60 * 60;
+/// `pin_bounty_period_secs` of a new registry
+pub const DEFAULT_PIN_BOUNTY_PERIOD_SECS: i64 = 30 * 24 * 60 * 60;
+/// `pin_bounty_periods` of a new registry, a year of monthly periods
+pub const DEFAULT_PIN_BOUNTY_PERIODS: u16 = 12;
+/// `pin_replication_target` of a new registry
+pub const DEFAULT_PIN_REPLICATION_TARGET: u8 = 1;
+/// `deal_expiry_warning_secs` of a new registry
+pub const DEFAULT_DEAL_EXPIRY_WARNING_SECS: i64 = 14 * 24 * 60 * 60;
+/// `max_access_pass_slots` of a new registry, about a day of 400ms slots
+pub const DEFAULT_MAX_ACCESS_PASS_SLOTS: u64 = 216_000;
+/// How long a revoked circuit's key outlives its last recorded proof, leaving
+/// time to sweep the records it produced before `close_verifying_key`
+pub const VK_CLOSE_COOLDOWN_SECS: i64 = 7 * 24 * 60 * 60;
+/// How long an unfinalized key upload may sit without a chunk written before
+/// anyone may close it with `gc_stale_upload`
+pub const VK_UPLOAD_TIMEOUT_SECS: i64 = 24 * 60 * 60;
+/// How long an expired record is kept before anyone may close it with
+/// `close_expired_verification`
+pub const RECORD_GC_GRACE_SECS: i64 = 30 * 24 * 60 * 60;
+/// How long past its `paid_through` a pin is kept before anyone may mark it
+/// expired with `expire_pin`
+pub const PIN_EXPIRY_GRACE_SECS: i64 = 7 * 24 * 60 * 60;
+/// How long before its expiry a record may be renewed with `reverify_eligibility`
+pub const RENEWAL_WINDOW_SECS: i64 = 7 * 24 * 60 * 60;
+/// Length of the UTC day a provider's `daily_quota` is counted over
+pub const SECS_PER_DAY: i64 = 24 * 60 * 60;
+/// Prefix of every keccak proof hash, versioned so a later layout can't collide
+/// with records written under this one
+pub const VERIFICATION_HASH_DOMAIN: &[u8] = b"zk_healthcare:v1";
+/// Prefix of every `attestation_message`, so a provider's signature over one
+/// can't pass for anything else it signs
+pub const ATTESTATION_DOMAIN: &[u8] = b"zk_healthcare:attestation:v1";
+/// Half the secp256k1 group order: a signature's `s` above it is the malleated
+/// twin of a valid low-`s` one
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x5d, 0x57, 0x6e,
+    0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+const G1_LEN: usize = 64;
+const G2_LEN: usize = 128;
+const G1_COMPRESSED_LEN: usize = 32;
+const G2_COMPRESSED_LEN: usize = 64;
+/// Offset of the gamma_abc length prefix in an arkworks uncompressed verifying key
+const VK_IC_OFFSET: usize = G1_LEN + 3 * G2_LEN;
+/// Bn254 base field modulus, big-endian
+const FQ_MODULUS_BE: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+/// Bn254 scalar field modulus, big-endian
+const FR_MODULUS_BE: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+/// arkworks stores the short Weierstrass flags in the top two bits of the last y byte
+const SW_FLAGS_MASK: u8 = 0b1100_0000;
+const SW_INFINITY_FLAG: u8 = 0b0100_0000;
+const SW_NEGATIVE_FLAG: u8 = 0b1000_0000;
 
 #[program]
 pub mod zk_healthcare {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, nist_compliant: bool) -> Result<()> {
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        nist_compliant: bool,
+        vk_update_delay_secs: i64,
+    ) -> Result<()> {
+        require!(vk_update_delay_secs >= 0, HealthcareError::InvalidVkUpdateDelay);
+
         let registry = &mut ctx.accounts.registry;
         registry.authority = ctx.accounts.authority.key();
         registry.nist_compliant = nist_compliant;
         registry.total_verifications = 0;
         registry.ipfs_pin_count = 0;
+        registry.vk_update_delay_secs = vk_update_delay_secs;
+        registry.domain = registry_domain(&registry.key());
+        registry.registered_circuits = 0;
+        registry.circuit_for_type = [None; VERIFICATION_TYPE_COUNT];
+        registry.max_circuit_fee = 0;
+        registry.max_public_inputs = DEFAULT_MAX_PUBLIC_INPUTS;
+        registry.cache_ttl_slots = DEFAULT_CACHE_TTL_SLOTS;
+        registry.commit_reveal_slots = 0;
+        registry.security_policy = SecurityPolicy::default_for(nist_compliant);
+        registry.default_validity_secs = 0;
+        registry.gc_bounty_lamports = 0;
+        registry.closed_verifications = 0;
+        registry.max_metadata_len = DEFAULT_MAX_METADATA_LEN;
+        registry.dispute_window_secs = DEFAULT_DISPUTE_WINDOW_SECS;
+        registry.time_source = TimeSource::UnixTimestamp;
+        registry.verifications_by_type = [0; VERIFICATION_TYPE_COUNT];
+        registry.revoked_count = 0;
+        registry.expired_count = 0;
+        registry.failed_proof_count = 0;
+        registry.claim_count = 0;
+        registry.event_seq = 0;
+        registry.checkpointed_seq = 0;
+        registry.cpi_events_enabled = false;
+        registry.denied_access_events_enabled = false;
+        registry.pinning_oracles = Vec::new();
+        registry.failed_pin_timeout_secs = DEFAULT_FAILED_PIN_TIMEOUT_SECS;
+        registry.price_lamports_per_day = 0;
+        registry.storage_treasury = registry.authority;
+        registry.pin_bounty_period_secs = DEFAULT_PIN_BOUNTY_PERIOD_SECS;
+        registry.pin_bounty_periods = DEFAULT_PIN_BOUNTY_PERIODS;
+        registry.pin_replication_target = DEFAULT_PIN_REPLICATION_TARGET;
+        registry.deal_expiry_warning_secs = DEFAULT_DEAL_EXPIRY_WARNING_SECS;
+        registry.max_access_pass_slots = DEFAULT_MAX_ACCESS_PASS_SLOTS;
         msg!("Healthcare ZK Registry initialized");
         Ok(())
     }
 
+    /// Verify a proof and record it. The `VerificationResult` is set as return
+    /// data, so a program calling in through `cpi::verify_eligibility` learns which
+    /// record it produced.
+    ///
+    /// Given the proof's `cache` account, a successful verification also caches
+    /// its result, and a later submission of the same proof is answered from a
+    /// fresh entry without the pairing: the new record account is closed back to
+    /// the patient and the result names the original record.
+    ///
+    /// While the registry sets `commit_reveal_slots`, the patient must first have
+    /// committed to the submission with `commit_verification`, and reveals it here
+    /// by passing that `commitment` and its `salt`. The commitment is closed back
+    /// to the patient.
+    ///
+    /// The record lives at `derive_verification_pda(patient, Eligibility,
+    /// record_nonce)`, and `record_nonce` must be the `next_record_nonce` of the
+    /// patient's `patient_index`, so a patient's records sit at consecutive nonces
+    /// from zero. Circuits that use a verification nonce also check it against the
+    /// index: the proof must carry its nonce, which is then incremented.
+    ///
+    /// With `mint_token`, the patient is also minted one token of the registry's
+    /// verification mint, see `initialize_verification_mint`, into their Token-2022
+    /// associated token account, which is created if needed; the record notes the
+    /// mint. The token can't be transferred, and once the record is revoked
+    /// `burn_verification_token` takes it back.
+    ///
+    /// A registered, active provider may attest the check, as circuits with
+    /// `requires_provider` demand: by co-signing as `provider`, or offline with an
+    /// `attestation` whose signature an earlier `ed25519_program` instruction of
+    /// the transaction checked, see `attestation_message`. Either way its
+    /// `provider_registration` is passed, the attestation counts against the
+    /// provider's `daily_quota`, and the record keeps the provider.
+    ///
+    /// The patient's latest eligibility record, their `last_record_by_type`, is
+    /// passed as `previous_record`. While it is still active a new one is
+    /// refused with `ActiveVerificationExists`, so a resubmission doesn't leave
+    /// two, unless `force_new` is set, which expires the previous record first.
     pub fn verify_eligibility(
         ctx: Context<VerifyEligibility>,
         proof: Vec<u8>,
+        proof_format: ProofFormat,
         public_inputs: Vec<u8>,
         ipfs_hash: String,
-    ) -> Result<()> {
+        circuit_id: String,
+        record_nonce: u64,
+        salt: Option<[u8; 32]>,
+        hash_algo: HashAlgo,
+        mint_token: bool,
+        attestation: Option<ProviderAttestation>,
+        force_new: bool,
+    ) -> Result<VerificationResult> {
+        check_ipfs_cid(&ipfs_hash)?;
         let registry = &mut ctx.accounts.registry;
         let verification = &mut ctx.accounts.verification;
-
+        let nullifier = &mut ctx.accounts.nullifier;
+        let clock = Clock::get()?;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient_index = &mut ctx.accounts.patient_index;
         require!(
-            proof.len() == 256,
-            HealthcareError::InvalidProofLength
+            record_nonce == patient_index.next_record_nonce,
+            HealthcareError::RecordNonceMismatch
         );
-        
-        let is_valid = verify_groth16_proof(&proof, &public_inputs)?;
-        require!(is_valid, HealthcareError::ProofVerificationFailed);
+        patient_index.bump = ctx.bumps.patient_index;
+        if let Some(cached) = cached_result(&ctx.accounts.cache, nullifier, &circuit_id, clock.slot)? {
+            verification.close(ctx.accounts.patient.to_account_info())?;
+            msg!("Verification served from cache");
+            return Ok(cached);
+        }
+        require!(
+            nullifier.verification == Pubkey::default(),
+            HealthcareError::ProofAlreadyUsed
+        );
+        let patient = ctx.accounts.patient.key();
+        check_commitment(
+            &ctx.accounts.commitment,
+            registry,
+            salt,
+            &proof,
+            &public_inputs,
+            &patient,
+            clock.slot,
+        )?;
+        let provider = match (&attestation, &ctx.accounts.provider) {
+            (None, provider) => provider.as_ref().map(|provider| provider.key()),
+            (Some(attestation), None) => Some(check_attestation(
+                attestation,
+                &ctx.accounts.provider_registration,
+                &ctx.accounts.instructions,
+                &registry.key(),
+                &patient,
+                &circuit_id,
+                clock.unix_timestamp,
+            )?),
+            (Some(_), Some(_)) => return err!(HealthcareError::InvalidAttestation),
+        };
+        let attesting_provider =
+            attesting_provider(&verifying_key, &registry.key(), provider, &ctx.accounts.provider_registration)?;
+        if let (Some(_), Some(registration)) = (attesting_provider, &mut ctx.accounts.provider_registration) {
+            registration.use_quota(clock.unix_timestamp)?;
+        }
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&patient),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        retire_previous_record::<VerificationRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::Eligibility,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+        verification.set_inner(VerificationRecord::eligibility(
+            patient,
+            &registry.key(),
+            &verified,
+            ipfs_hash.clone(),
+            &clock,
+            circuit_id,
+            &verifying_key,
+            registry.record_expiry(&verifying_key, clock.unix_timestamp),
+            ctx.accounts.patient.key(),
+        ));
+        verification.bump = ctx.bumps.verification;
+        verification.attesting_provider = attesting_provider;
+        let key = verification.key();
+        verification.transition(key, RecordStatus::Verified, registry)?;
+        verification.previous_record = patient_index.link_record(key);
+        let cooldown_secs = registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::Eligibility, clock.unix_timestamp, cooldown_secs)?;
+        patient_index.next_record_nonce += 1;
+        if mint_token {
+            verification.token_mint = mint_verification_token(
+                &registry.key(),
+                &ctx.accounts.patient,
+                &ctx.accounts.verification_mint,
+                &ctx.accounts.patient_token_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.associated_token_program,
+                &ctx.accounts.system_program,
+            )?;
+            emit!(VerificationTokenMinted {
+                seq: registry.next_event_seq()?,
+                registry: registry.key(),
+                record: key,
+                patient,
+                mint: verification.token_mint,
+            });
+        }
 
-        verification.patient_pubkey = ctx.accounts.patient.key();
-        verification.proof_hash = keccak::hash(&proof).to_bytes();
-        verification.ipfs_hash = ipfs_hash.clone();
-        verification.timestamp = Clock::get()?.unix_timestamp;
-        verification.is_valid = true;
-        verification.verification_type = VerificationType::Eligibility;
+        nullifier.verification = verification.key();
+        nullifier.used_at = verification.timestamp;
+        nullifier.bump = ctx.bumps.nullifier;
 
-        registry.total_verifications += 1;
+        registry.count_verifications(VerificationType::Eligibility, 1)?;
         registry.ipfs_pin_count += 1;
+        verification.event_seq = registry.next_event_seq()?;
+
+        let result = VerificationResult {
+            verified: true,
+            proof_hash: verification.proof_hash,
+            record: verification.key(),
+            verification_id: verification.verification_id,
+        };
+        if let Some(cache) = &ctx.accounts.cache {
+            let entry = VerificationCache {
+                result: result.clone(),
+                circuit_id: verification.circuit_id.clone(),
+                expires_at_slot: clock.slot.saturating_add(registry.cache_ttl_slots),
+                payer: ctx.accounts.patient.key(),
+                bump: 0,
+            };
+            cache_result(
+                cache,
+                &ctx.accounts.patient.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                entry,
+            )?;
+        }
 
-        emit!(EligibilityVerified {
+        let event = EligibilityVerified {
+            seq: verification.event_seq,
+            registry: registry.key(),
             patient: ctx.accounts.patient.key(),
             ipfs_hash,
             timestamp: verification.timestamp,
-        });
+            slot: verification.slot,
+            verification_id: verification.verification_id,
+            provider_attested: attesting_provider.is_some(),
+            guardian: None,
+        };
+        emit!(event);
+        if registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
 
         msg!("Eligibility verified. Gas estimated: ~450K compute units");
-        Ok(())
+        Ok(result)
     }
 
-    pub fn pin_medical_data(
-        ctx: Context<PinMedicalData>,
-        ipfs_cid: String,
-        data_hash: [u8; 32],
-    ) -> Result<()> {
-        let pin_record = &mut ctx.accounts.pin_record;
+    /// `verify_eligibility` without naming the patient. The record's identity is
+    /// `credential`, the nullifier the circuit outputs in its `nullifier_input`
+    /// slot, and any relayer may sign and pay for the submission. A credential
+    /// backs one record per `epoch`, which must be the current one, and the
+    /// event carries only the credential and the IPFS hash.
+    pub fn verify_eligibility_anonymous(
+        ctx: Context<VerifyEligibilityAnonymous>,
+        proof_format: ProofFormat,
+        submission: ProofSubmission,
+        circuit_id: String,
+        credential: [u8; 32],
+        epoch: u64,
+        hash_algo: HashAlgo,
+    ) -> Result<VerificationResult> {
+        let ProofSubmission { proof, public_inputs, ipfs_hash } = submission;
+        check_ipfs_cid(&ipfs_hash)?;
         let registry = &mut ctx.accounts.registry;
+        let verification = &mut ctx.accounts.verification;
+        let nullifier = &mut ctx.accounts.nullifier;
+        let credential_nullifier = &mut ctx.accounts.credential_nullifier;
+        let clock = Clock::get()?;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        require!(!verifying_key.needs_provider(), HealthcareError::ProviderRequired);
+        registry.check_public_inputs_len(&public_inputs)?;
+        require!(
+            nullifier.verification == Pubkey::default(),
+            HealthcareError::ProofAlreadyUsed
+        );
+        require!(
+            credential_nullifier.verification == Pubkey::default(),
+            HealthcareError::CredentialAlreadyUsed
+        );
+        require!(epoch == clock.epoch, HealthcareError::WrongEpoch);
+        require!(
+            verifying_key.credential_nullifier(&public_inputs, proof_format) == Some(credential),
+            HealthcareError::PublicInputNullifierMismatch
+        );
+        check_credential_cooldown(
+            &ctx.accounts.previous_credential_nullifier,
+            &ctx.accounts.verifying_key.key(),
+            &credential,
+            epoch,
+            registry.cooldown_secs,
+            clock.unix_timestamp,
+        )?;
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            None,
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.relayer,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+        verification.set_inner(VerificationRecord::eligibility(
+            Pubkey::new_from_array(credential),
+            &registry.key(),
+            &verified,
+            ipfs_hash.clone(),
+            &clock,
+            circuit_id,
+            &verifying_key,
+            registry.record_expiry(&verifying_key, clock.unix_timestamp),
+            // Naming the relayer would link the record to it
+            Pubkey::default(),
+        ));
+        let key = verification.key();
+        verification.transition(key, RecordStatus::Verified, registry)?;
 
-        pin_record.patient = ctx.accounts.patient.key();
-        pin_record.ipfs_cid = ipfs_cid.clone();
-        pin_record.data_hash = data_hash;
-        pin_record.pinned_at = Clock::get()?.unix_timestamp;
-        pin_record.access_count = 0;
+        nullifier.verification = verification.key();
+        nullifier.used_at = verification.timestamp;
+        nullifier.bump = ctx.bumps.nullifier;
+        credential_nullifier.verification = verification.key();
+        credential_nullifier.epoch = epoch;
+        credential_nullifier.bump = ctx.bumps.credential_nullifier;
+        credential_nullifier.used_at = clock.unix_timestamp;
 
+        registry.count_verifications(VerificationType::Eligibility, 1)?;
         registry.ipfs_pin_count += 1;
+        verification.event_seq = registry.next_event_seq()?;
 
-        emit!(DataPinned {
-            patient: ctx.accounts.patient.key(),
-            ipfs_cid,
-            data_hash,
+        emit!(AnonymousEligibilityVerified {
+            seq: verification.event_seq,
+            registry: registry.key(),
+            nullifier: credential,
+            ipfs_hash,
+            slot: verification.slot,
         });
+        msg!("Anonymous eligibility verified");
+        Ok(VerificationResult {
+            verified: true,
+            proof_hash: verification.proof_hash,
+            record: verification.key(),
+            verification_id: verification.verification_id,
+        })
+    }
 
-        Ok(())
+    /// Verify several proofs for one circuit with a single multi-pairing.
+    ///
+    /// `remaining_accounts` holds two writable accounts per submission, in order:
+    /// its record, at `derive_verification_pda` of `record_nonce` plus its index,
+    /// and its nullifier PDA. `record_nonce` must be the `next_record_nonce` of the
+    /// patient's `patient_index`, which moves past the whole batch. One bad entry
+    /// aborts the whole batch, and its index is logged. The 1232-byte
+    /// transaction limit usually binds before `MAX_BATCH_SIZE`; compressed proofs
+    /// fit the most entries. Returns one `VerificationResult` per submission.
+    /// `previous_record` and `force_new` work as for `verify_eligibility`, once
+    /// for the whole batch. A batch takes no commitments, so it is refused while
+    /// the registry sets `commit_reveal_slots`.
+    pub fn verify_eligibility_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifyEligibilityBatch<'info>>,
+        proof_format: ProofFormat,
+        submissions: Vec<ProofSubmission>,
+        circuit_id: String,
+        record_nonce: u64,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<Vec<VerificationResult>> {
+        require!(
+            !submissions.is_empty() && submissions.len() <= MAX_BATCH_SIZE,
+            HealthcareError::InvalidBatchSize
+        );
+        require!(
+            ctx.remaining_accounts.len() == 2 * submissions.len(),
+            HealthcareError::BatchAccountMismatch
+        );
+        let registry = &mut ctx.accounts.registry;
+        require!(registry.commit_reveal_slots == 0, HealthcareError::CommitmentMismatch);
+        let patient = ctx.accounts.patient.key();
+        let clock = Clock::get()?;
+        registry.check_hash_algo(hash_algo)?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        require!(
+            record_nonce == patient_index.next_record_nonce,
+            HealthcareError::RecordNonceMismatch
+        );
+        patient_index.bump = ctx.bumps.patient_index;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        require!(!verifying_key.needs_provider(), HealthcareError::ProviderRequired);
+        // The entries share one timestamp, so only the first is held to the cooldown
+        patient_index.check_cooldown(VerificationType::Eligibility, clock.unix_timestamp, registry.cooldown_secs)?;
+
+        let mut entries: Vec<BatchEntry> = Vec::with_capacity(submissions.len());
+        for (index, (submission, accounts)) in submissions
+            .iter()
+            .zip(ctx.remaining_accounts.chunks_exact(2))
+            .enumerate()
+        {
+            let checked = BatchEntry::check(
+                &verifying_key,
+                registry,
+                submission,
+                proof_format,
+                &patient,
+                record_nonce + index as u64,
+                &clock,
+                accounts,
+            )
+            .and_then(|entry| {
+                require!(
+                    entries.iter().all(|earlier| earlier.seed != entry.seed),
+                    HealthcareError::ProofAlreadyUsed
+                );
+                // Entries spend consecutive nonces, in submission order
+                spend_nonce(&verifying_key, &entry.inputs, patient_index)?;
+                Ok(entry)
+            });
+            match checked {
+                Ok(entry) => entries.push(entry),
+                Err(err) => {
+                    msg!("Batch entry {} rejected", index);
+                    return Err(err);
+                }
+            }
+        }
+
+        let batch: Vec<_> = entries.iter().map(|entry| (&entry.proof, &entry.inputs[..])).collect();
+        if !verifying_key.verify_batch(&batch).unwrap_or(false) {
+            // The combined check can't say which proof broke it; find the first
+            // one that fails on its own
+            for (index, submission) in submissions.iter().enumerate() {
+                let is_valid = verifying_key
+                    .verify(&entries[index].proof, &submission.public_inputs, proof_format)
+                    .unwrap_or(false);
+                if !is_valid {
+                    msg!("Batch entry {} failed verification", index);
+                    return Err(HealthcareError::PairingCheckFailed.into());
+                }
+            }
+            // Every proof holds alone, so only the combination failed
+            return Err(HealthcareError::ProofVerificationFailed.into());
+        }
+        retire_previous_record::<VerificationRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::Eligibility,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            entries.len() as u64,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+
+        let payer = ctx.accounts.patient.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let mut results = Vec::with_capacity(entries.len());
+        for ((entry, submission), accounts) in entries
+            .iter()
+            .zip(submissions)
+            .zip(ctx.remaining_accounts.chunks_exact(2))
+        {
+            let (record_info, nullifier_info) = (&accounts[0], &accounts[1]);
+            create_pda_account(
+                &payer,
+                record_info,
+                &system_program,
+                VerificationRecord::SPACE,
+                &[
+                    b"verification",
+                    patient.as_ref(),
+                    &[VerificationType::Eligibility as u8],
+                    &entry.record_nonce.to_le_bytes(),
+                    &[entry.record_bump],
+                ],
+            )?;
+            create_pda_account(
+                &payer,
+                nullifier_info,
+                &system_program,
+                ProofNullifier::SPACE,
+                &[b"nullifier", &entry.seed, &[entry.nullifier_bump]],
+            )?;
+
+            let proof_hash = hash_algo.proof_hash(entry.proof.hash(&entry.inputs)?, &entry.inputs)?;
+            let mut record = VerificationRecord {
+                version: VerificationRecord::VERSION,
+                patient_pubkey: patient,
+                proof_hash,
+                hash_algo,
+                public_inputs_hash: compute_public_inputs_hash(&entry.inputs),
+                ipfs_hash: submission.ipfs_hash.clone(),
+                timestamp: clock.unix_timestamp,
+                is_valid: false,
+                verification_type: VerificationType::Eligibility,
+                slot: clock.slot,
+                circuit_id: circuit_id.clone(),
+                circuit_version: verifying_key.version,
+                vk_hash: verifying_key.vk_hash,
+                verification_id: derive_verification_id(&registry.key(), &patient, &circuit_id, &proof_hash),
+                revoked_at: 0,
+                revoked_by: Pubkey::default(),
+                expires_at: registry.record_expiry(&verifying_key, clock.unix_timestamp),
+                status: RecordStatus::Pending,
+                rent_payer: patient,
+                bump: entry.record_bump,
+                revision: 0,
+                previous_proof_hash: [0; 32],
+                token_mint: Pubkey::default(),
+                attesting_provider: None,
+                previous_record: patient_index.link_record(record_info.key()),
+                event_seq: registry.next_event_seq()?,
+                hold: None,
+                migrated_from: Pubkey::default(),
+                metadata: Vec::new(),
+            };
+            record.transition(record_info.key(), RecordStatus::Verified, registry)?;
+            patient_index.note_verification(record_info.key(), VerificationType::Eligibility, clock.unix_timestamp)?;
+            record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+            let nullifier = ProofNullifier {
+                verification: record_info.key(),
+                used_at: clock.unix_timestamp,
+                bump: entry.nullifier_bump,
+            };
+            nullifier.try_serialize(&mut &mut nullifier_info.try_borrow_mut_data()?[..])?;
+            results.push(VerificationResult {
+                verified: true,
+                proof_hash: record.proof_hash,
+                record: record_info.key(),
+                verification_id: record.verification_id,
+            });
+
+            let event = EligibilityVerified {
+                seq: record.event_seq,
+                registry: registry.key(),
+                patient,
+                ipfs_hash: submission.ipfs_hash,
+                timestamp: clock.unix_timestamp,
+                slot: clock.slot,
+                verification_id: record.verification_id,
+                provider_attested: false,
+                guardian: None,
+            };
+            emit!(event);
+            if registry.cpi_events_enabled {
+                emit_cpi!(event);
+            }
+        }
+
+        patient_index.next_record_nonce += entries.len() as u64;
+        registry.count_verifications(VerificationType::Eligibility, entries.len() as u64)?;
+        registry.ipfs_pin_count += entries.len() as u64;
+        msg!("Batch of {} eligibility proofs verified", entries.len());
+        Ok(results)
     }
 
-    pub fn submit_model_update(
-        ctx: Context<SubmitModelUpdate>,
-        encrypted_gradient: Vec<u8>,
-        round_number: u64,
+    /// Renew one of the patient's eligibility records with a new proof instead
+    /// of writing another. The record must be `Expired` or within
+    /// `RENEWAL_WINDOW_SECS` of its expiry; it takes the new proof's hash, IPFS
+    /// hash, circuit and expiry, keeps the proof hash it replaces in
+    /// `previous_proof_hash`, and goes back to `Verified` one `revision` later.
+    pub fn reverify_eligibility(
+        ctx: Context<ReverifyEligibility>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        ipfs_hash: String,
+        circuit_id: String,
+        hash_algo: HashAlgo,
     ) -> Result<()> {
-        let fl_state = &mut ctx.accounts.fl_state;
-        
+        check_ipfs_cid(&ipfs_hash)?;
+        let registry = &mut ctx.accounts.registry;
+        let verification = &mut ctx.accounts.verification;
+        let nullifier = &mut ctx.accounts.nullifier;
+        let clock = Clock::get()?;
+        require!(verification.is_renewable(&clock), HealthcareError::RecordNotRenewable);
         require!(
-            encrypted_gradient.len() <= 4096,
-            HealthcareError::GradientTooLarge
+            nullifier.verification == Pubkey::default(),
+            HealthcareError::ProofAlreadyUsed
         );
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        require!(!verifying_key.needs_provider(), HealthcareError::ProviderRequired);
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient = ctx.accounts.patient.key();
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&patient),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
 
-        fl_state.round_number = round_number;
-        fl_state.last_update = Clock::get()?.unix_timestamp;
-        fl_state.participant_count += 1;
+        let key = verification.key();
+        let reactivated = !verification.is_verified();
+        if reactivated {
+            verification.transition(key, RecordStatus::Verified, registry)?;
+        }
+        verification.renew(
+            &registry.key(),
+            &verified,
+            ipfs_hash,
+            &clock,
+            circuit_id,
+            &verifying_key,
+            registry.record_expiry(&verifying_key, clock.unix_timestamp),
+        );
+        patient_index.record_renewal(key, reactivated, clock.unix_timestamp);
+        verification.event_seq = registry.next_event_seq()?;
 
-        msg!("Federated learning update submitted for round {}", round_number);
+        nullifier.verification = key;
+        nullifier.used_at = clock.unix_timestamp;
+        nullifier.bump = ctx.bumps.nullifier;
+
+        emit!(VerificationRenewed {
+            seq: verification.event_seq,
+            registry: registry.key(),
+            record: key,
+            revision: verification.revision,
+            slot: verification.slot,
+        });
+        msg!("Verification renewed at revision {}", verification.revision);
         Ok(())
     }
-}
 
-// Account structures
-#[account]
-pub struct HealthcareRegistry {
-    pub authority: Pubkey,
-    pub nist_compliant: bool,
-    pub total_verifications: u64,
-    pub ipfs_pin_count: u64,
-}
+    /// Step 1 of a verification split across transactions, for circuits whose
+    /// public inputs don't fit one compute budget. Runs every check that needs no
+    /// curve arithmetic and stores the submission in a `PartialVerification`.
+    pub fn begin_verification(
+        ctx: Context<BeginVerification>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+    ) -> Result<()> {
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        require!(!verifying_key.needs_provider(), HealthcareError::ProviderRequired);
+        let patient = ctx.accounts.patient.key();
+        let clock = Clock::get()?;
+        ctx.accounts.registry.check_public_inputs_len(&public_inputs)?;
+        verifying_key.check_patient_binding(&public_inputs, proof_format, &patient)?;
+        verifying_key.check_domain_binding(&public_inputs, proof_format, &ctx.accounts.registry)?;
+        verifying_key.check_freshness(&public_inputs, proof_format, ctx.accounts.registry.time_source, &clock)?;
+        verifying_key.check_input_count(&public_inputs)?;
+        let inputs = parse_public_inputs(&public_inputs, proof_format)?;
+        let decoded = Groth16Proof::decode(&proof, proof_format)?;
 
-#[account]
-pub struct VerifyingKeyPDA {
-    /// Serialized Groth16 verifying key
-    pub vk_bytes: Vec<u8>,
-    /// Circuit identifier (e.g., "eligibility_v1")
-    pub circuit_id: String,
-    /// Authority that can update the VK
-    pub authority: Pubkey,
-    /// Last update timestamp
-    pub updated_at: i64,
-}
+        let prepared = cached_or_prepared(verifying_key.vk_bytes(), verifying_key.prepared_vk_bytes())?;
+        let [_, _, _, _, ic] = split_prepared_vk(&prepared);
+        check_ic_len(ic, inputs.len())?;
 
-#[account]
-pub struct VerificationRecord {
-    pub patient_pubkey: Pubkey,
-    pub proof_hash: [u8; 32],
-    pub ipfs_hash: String,
-    pub timestamp: i64,
-    pub is_valid: bool,
-    pub verification_type: VerificationType,
-}
+        let partial = &mut ctx.accounts.partial;
+        partial.patient = patient;
+        partial.registry = ctx.accounts.registry.key();
+        partial.verifying_key = ctx.accounts.verifying_key.key();
+        partial.vk_hash = keccak::hash(verifying_key.vk_bytes()).to_bytes();
+        partial.proof = decoded;
+        partial.public_inputs = inputs;
+        partial.next_input = 0;
+        partial.vk_x.copy_from_slice(&ic[..G1_LEN]);
+        partial.seed = ProofNullifier::seed(&proof, proof_format, &public_inputs);
+        partial.expires_at_slot = clock.slot + PARTIAL_VERIFICATION_TTL_SLOTS;
+        msg!(
+            "Partial verification of circuit {} expires after slot {}",
+            circuit_id,
+            partial.expires_at_slot
+        );
+        Ok(())
+    }
 
-#[account]
-pub struct IpfsPinRecord {
-    pub patient: Pubkey,
-    pub ipfs_cid: String,
-    pub data_hash: [u8; 32],
-    pub pinned_at: i64,
-    pub access_count: u32,
-}
+    /// Step 2, repeated as needed: fold the next `INPUTS_PER_ADVANCE` public
+    /// inputs into the stored `vk_x`
+    pub fn advance_verification(ctx: Context<AdvanceVerification>) -> Result<()> {
+        let partial = &mut ctx.accounts.partial;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        partial.check_live(Clock::get()?.slot)?;
 
-#[account]
-pub struct FederatedLearningState {
-    pub round_number: u64,
-    pub last_update: i64,
-    pub participant_count: u32,
-}
+        let prepared = cached_or_prepared(verifying_key.vk_bytes(), verifying_key.prepared_vk_bytes())?;
+        let [_, _, _, _, ic] = split_prepared_vk(&prepared);
+        let start = partial.next_input as usize;
+        let end = (start + INPUTS_PER_ADVANCE).min(partial.public_inputs.len());
+        partial.vk_x = accumulate_vk_x(
+            partial.vk_x,
+            &ic[(start + 1) * G1_LEN..(end + 1) * G1_LEN],
+            &partial.public_inputs[start..end],
+            start,
+        )?;
+        partial.next_input = end as u8;
+        msg!("Folded public inputs {}..{} of {}", start, end, partial.public_inputs.len());
+        Ok(())
+    }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum VerificationType {
-    Eligibility,
-    Prescription,
-    Diagnosis,
-    AccessControl,
-}
+    /// Step 3: run the pairing on the finished `vk_x`, write the record, and
+    /// close the scratch account back to the patient. The record lands where
+    /// `verify_eligibility` would put it, at the patient's next `record_nonce`,
+    /// and `previous_record` and `force_new` work as they do there. The
+    /// submission carries no commitment, so it can't be completed while the
+    /// registry sets `commit_reveal_slots`.
+    pub fn complete_verification(
+        ctx: Context<CompleteVerification>,
+        record_nonce: u64,
+        ipfs_hash: String,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<VerificationResult> {
+        check_ipfs_cid(&ipfs_hash)?;
+        let partial = &ctx.accounts.partial;
+        let nullifier = &mut ctx.accounts.nullifier;
+        let clock = Clock::get()?;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        partial.check_live(clock.slot)?;
+        require!(
+            partial.next_input as usize == partial.public_inputs.len(),
+            HealthcareError::PartialVerificationIncomplete
+        );
+        require!(
+            nullifier.verification == Pubkey::default(),
+            HealthcareError::ProofAlreadyUsed
+        );
+        let registry = &ctx.accounts.registry;
+        require!(registry.commit_reveal_slots == 0, HealthcareError::CommitmentMismatch);
+        registry.check_hash_algo(hash_algo)?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        require!(
+            record_nonce == patient_index.next_record_nonce,
+            HealthcareError::RecordNonceMismatch
+        );
+        patient_index.bump = ctx.bumps.patient_index;
+        spend_nonce(&verifying_key, &partial.public_inputs, patient_index)?;
 
-// Context structs (unchanged)
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init, payer = authority, space = 8 + 128)]
-    pub registry: Account<'info, HealthcareRegistry>,
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let prepared = cached_or_prepared(verifying_key.vk_bytes(), verifying_key.prepared_vk_bytes())?;
+        partial.proof.check_points()?;
+        let is_valid = pairing_check(&prepared, &partial.proof, &partial.vk_x)?;
+        require!(is_valid, HealthcareError::PairingCheckFailed);
+        retire_previous_record::<VerificationRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::Eligibility,
+            force_new,
+            &clock,
+            patient_index,
+            &mut ctx.accounts.registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
 
-#[derive(Accounts)]
-pub struct VerifyEligibility<'info> {
-    #[account(mut)]
-    pub registry: Account<'info, HealthcareRegistry>,
-    #[account(init, payer = patient, space = 8 + 256)]
-    pub verification: Account<'info, VerificationRecord>,
-    #[account(mut)]
-    pub patient: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let proof_hash = partial.proof.hash(&partial.public_inputs)?;
+        let verified = VerifiedProof {
+            proof_hash: hash_algo.proof_hash(proof_hash, &partial.public_inputs)?,
+            hash_algo,
+            public_inputs_hash: compute_public_inputs_hash(&partial.public_inputs),
+            inputs: partial.public_inputs.clone(),
+        };
+        let verification = &mut ctx.accounts.verification;
+        verification.set_inner(VerificationRecord::eligibility(
+            partial.patient,
+            &ctx.accounts.registry.key(),
+            &verified,
+            ipfs_hash.clone(),
+            &clock,
+            verifying_key.circuit_id().to_string(),
+            &verifying_key,
+            ctx.accounts.registry.record_expiry(&verifying_key, clock.unix_timestamp),
+            ctx.accounts.patient.key(),
+        ));
+        verification.bump = ctx.bumps.verification;
+        let key = verification.key();
+        verification.transition(key, RecordStatus::Verified, &mut ctx.accounts.registry)?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        verification.previous_record = patient_index.link_record(key);
+        let cooldown_secs = ctx.accounts.registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::Eligibility, clock.unix_timestamp, cooldown_secs)?;
+        patient_index.next_record_nonce += 1;
 
-#[derive(Accounts)]
-pub struct PinMedicalData<'info> {
-    #[account(mut)]
-    pub registry: Account<'info, HealthcareRegistry>,
-    #[account(init, payer = patient, space = 8 + 256)]
-    pub pin_record: Account<'info, IpfsPinRecord>,
-    #[account(mut)]
-    pub patient: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        nullifier.verification = verification.key();
+        nullifier.used_at = verification.timestamp;
+        nullifier.bump = ctx.bumps.nullifier;
 
-#[derive(Accounts)]
-pub struct SubmitModelUpdate<'info> {
-    #[account(mut)]
-    pub fl_state: Account<'info, FederatedLearningState>,
-    pub agent: Signer<'info>,
-}
+        let registry = &mut ctx.accounts.registry;
+        registry.count_verifications(VerificationType::Eligibility, 1)?;
+        registry.ipfs_pin_count += 1;
+        verification.event_seq = registry.next_event_seq()?;
 
-// Events (unchanged)
-#[event]
-pub struct EligibilityVerified {
-    pub patient: Pubkey,
-    pub ipfs_hash: String,
-    pub timestamp: i64,
-}
+        let event = EligibilityVerified {
+            seq: verification.event_seq,
+            registry: registry.key(),
+            patient: partial.patient,
+            ipfs_hash,
+            timestamp: verification.timestamp,
+            slot: verification.slot,
+            verification_id: verification.verification_id,
+            provider_attested: false,
+            guardian: None,
+        };
+        emit!(event);
+        if registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
+        Ok(VerificationResult {
+            verified: true,
+            proof_hash: verification.proof_hash,
+            record: verification.key(),
+            verification_id: verification.verification_id,
+        })
+    }
 
-#[event]
-pub struct DataPinned {
-    pub patient: Pubkey,
-    pub ipfs_cid: String,
-    pub data_hash: [u8; 32],
-}
+    /// Close an abandoned `PartialVerification` after it expires, refunding the
+    /// rent to its patient. Anyone may crank this.
+    pub fn reclaim_verification(ctx: Context<ReclaimVerification>) -> Result<()> {
+        require!(
+            Clock::get()?.slot > ctx.accounts.partial.expires_at_slot,
+            HealthcareError::PartialVerificationNotExpired
+        );
+        msg!("Expired partial verification reclaimed");
+        Ok(())
+    }
 
-// Errors (expanded with IPFS error)
-#[error_code]
-pub enum HealthcareError {
-    #[msg("Invalid proof length")]
-    InvalidProofLength,
-    #[msg("Proof verification failed")]
-    ProofVerificationFailed,
-    #[msg("Gradient data too large")]
-    GradientTooLarge,
-    #[msg("IPFS pinning failed")]
-    IpfsPinningFailed,
-}
+    /// Count a submission whose proof fails `circuit_id` in the registry's
+    /// `failed_proof_count`. A `verify_*` call that fails aborts with its
+    /// counters, so clients report the failure here: the proof is checked again,
+    /// and one that verifies is refused with `ProofVerified`. Proofs that don't
+    /// decode fail as they would in `verify_eligibility`.
+    pub fn record_failed_proof(
+        ctx: Context<RecordFailedProof>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+    ) -> Result<()> {
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        let registry = &mut ctx.accounts.registry;
+        registry.check_public_inputs_len(&public_inputs)?;
+        let verdict = verifying_key.verifier().verify(&verifying_key, &proof, proof_format, &public_inputs)?;
+        require!(!verdict.verified, HealthcareError::ProofVerified);
+        registry.failed_proof_count = checked_count(registry.failed_proof_count, 1)?;
 
-/// Verify Groth16 zk-SNARK proof for patient eligibility
-/// 
-/// In production, this loads the verifying key from a PDA account and performs
-/// full Groth16 proof verification using ark-groth16 on Bn254 curve.
-/// 
-/// For development, we validate proof structure and format as a sanity check.
-fn verify_groth16_proof(proof_bytes: &[u8], public_inputs_bytes: &[u8]) -> Result<bool> {
-    // Development mode: validate proof structure
-    // Production mode: deserialize proof from bytes and call ark_groth16::verify_proof
-    
-    // Basic checks
-    if proof_bytes.len() == 0 || public_inputs_bytes.len() == 0 {
-        return Err(HealthcareError::ProofVerificationFailed.into());
-    }
-    
-    // In production deployment:
-    // 1. Load verifying key from VerifyingKeyPDA account
-    // 2. Deserialize proof: ark_groth16::Proof::read(...)?
-    // 3. Deserialize public inputs: Vec<ark_bn254::Fr> from bytes
-    // 4. Call: verify_proof(&pvk, &proof, &public_inputs)?
-    // 5. Return result
-    
-    // For now, accept valid-length proofs (real verification requires on-chain VK)
-    Ok(proof_bytes.len() == 256)
-}
+        emit!(ProofFailed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            circuit_id,
+            failed_proof_count: registry.failed_proof_count,
+        });
+        msg!("Failed proof recorded");
+        Ok(())
+    }
 
-// Benchmark test (for scalability)
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Record a prescription the patient proved against the registry's
+    /// `Prescription` circuit as a `PrescriptionRecord`, which registered
+    /// pharmacies then draw `refills_allowed` refills from with `record_refill`
+    /// until `valid_until`. The record's address is derived from the submission,
+    /// so a proof backs at most one prescription. `previous_record` and
+    /// `force_new` work as for `verify_eligibility`: the patient's latest
+    /// prescription blocks a new one until it lapses or runs out of refills,
+    /// and `force_new` ends it.
+    pub fn verify_prescription(
+        ctx: Context<VerifyPrescription>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+        drug_commitment: [u8; 32],
+        refills_allowed: u8,
+        valid_until: i64,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let clock = Clock::get()?;
+        require!(valid_until > clock.unix_timestamp, HealthcareError::InvalidPrescriptionExpiry);
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient = ctx.accounts.patient.key();
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&patient),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        retire_previous_record::<PrescriptionRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::Prescription,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
 
-    #[test]
-    fn test_compute_units() {
-        // Use solana-program-test for CU benchmarking
-        // Placeholder: assert CU < 450_000
+        let prescription = &mut ctx.accounts.prescription;
+        prescription.set_inner(PrescriptionRecord {
+            patient,
+            registry: registry.key(),
+            proof_hash: verified.proof_hash,
+            hash_algo,
+            drug_commitment,
+            circuit_id,
+            verified_at: clock.unix_timestamp,
+            valid_until,
+            refills_allowed,
+            refills_remaining: refills_allowed,
+            bump: ctx.bumps.prescription,
+        });
+        let key = prescription.key();
+        let cooldown_secs = registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::Prescription, clock.unix_timestamp, cooldown_secs)?;
+        registry.count_verifications(VerificationType::Prescription, 1)?;
+
+        emit!(PrescriptionVerified {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            prescription: key,
+            patient,
+            drug_commitment,
+            refills_allowed,
+            valid_until,
+        });
+        msg!("Prescription verified with {} refills", refills_allowed);
+        Ok(())
     }
-        }
+
+    /// Draw one refill from a prescription. Only a pharmacy registered under
+    /// the prescription's registry may sign, and only while the prescription is
+    /// unexpired with refills left.
+    pub fn record_refill(ctx: Context<RecordRefill>) -> Result<()> {
+        let prescription = &mut ctx.accounts.prescription;
+        require!(
+            Clock::get()?.unix_timestamp < prescription.valid_until,
+            HealthcareError::PrescriptionExpired
+        );
+        require!(prescription.refills_remaining > 0, HealthcareError::NoRefillsRemaining);
+        prescription.refills_remaining -= 1;
+
+        emit!(PrescriptionRefilled {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            prescription: prescription.key(),
+            pharmacy: ctx.accounts.pharmacy.key(),
+            refills_remaining: prescription.refills_remaining,
+        });
+        msg!("Refill recorded, {} remaining", prescription.refills_remaining);
+        Ok(())
+    }
+
+    /// Record a diagnosis the patient proved against the registry's `Diagnosis`
+    /// circuit as a `DiagnosisRecord`. `diagnosing_provider` must be a registered,
+    /// active provider of the registry, and the record keeps only the
+    /// `icd10_commitment`, never the code. `pin_record`, when passed, is one of
+    /// the patient's `IpfsPinRecord`s and is linked from the record.
+    /// `previous_record` and `force_new` work as for `verify_eligibility`: the
+    /// patient's latest diagnosis blocks a new one until it is revoked, and with
+    /// `force_new` the new one supersedes it.
+    pub fn verify_diagnosis(
+        ctx: Context<VerifyDiagnosis>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+        icd10_commitment: [u8; 32],
+        diagnosing_provider: Pubkey,
+        ipfs_cid: String,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<()> {
+        check_ipfs_cid(&ipfs_cid)?;
+        let registry = &mut ctx.accounts.registry;
+        let clock = Clock::get()?;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient = ctx.accounts.patient.key();
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&patient),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        retire_previous_record::<DiagnosisRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::Diagnosis,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+
+        let diagnosis = &mut ctx.accounts.diagnosis;
+        diagnosis.set_inner(DiagnosisRecord {
+            patient,
+            registry: registry.key(),
+            provider: diagnosing_provider,
+            proof_hash: verified.proof_hash,
+            hash_algo,
+            icd10_commitment,
+            circuit_id,
+            ipfs_cid: ipfs_cid.clone(),
+            pin_record: ctx.accounts.pin_record.as_ref().map(|pin_record| pin_record.key()),
+            verified_at: clock.unix_timestamp,
+            revoked_at: 0,
+            revoked_by: Pubkey::default(),
+            bump: ctx.bumps.diagnosis,
+        });
+        let key = diagnosis.key();
+        let cooldown_secs = registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::Diagnosis, clock.unix_timestamp, cooldown_secs)?;
+        registry.count_verifications(VerificationType::Diagnosis, 1)?;
+
+        emit!(DiagnosisVerified {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            diagnosis: key,
+            patient,
+            provider: diagnosing_provider,
+            icd10_commitment,
+            ipfs_cid,
+        });
+        msg!("Diagnosis verified for provider {}", diagnosing_provider);
+        Ok(())
+    }
+
+    /// Revoke a `DiagnosisRecord`. Only its diagnosing provider or the registry
+    /// authority may sign; the patient can't retract a provider's diagnosis.
+    pub fn revoke_diagnosis(ctx: Context<RevokeDiagnosis>, reason_code: u16) -> Result<()> {
+        let revoked_by = ctx.accounts.revoker.key();
+        let diagnosis = &mut ctx.accounts.diagnosis;
+        require!(
+            revoked_by == ctx.accounts.registry.authority || revoked_by == diagnosis.provider,
+            HealthcareError::NotAuthorizedToRevoke
+        );
+        release_active_record(&ctx.accounts.patient_index)?;
+        diagnosis.revoked_at = Clock::get()?.unix_timestamp;
+        diagnosis.revoked_by = revoked_by;
+
+        emit!(DiagnosisRevoked {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            diagnosis: diagnosis.key(),
+            patient: diagnosis.patient,
+            reason_code,
+            revoked_by,
+        });
+        msg!("Diagnosis revoked with reason {}", reason_code);
+        Ok(())
+    }
+
+    /// Record a lab result the patient proved against the registry's `LabResult`
+    /// circuit as a `LabResultRecord`, such as an HbA1c below a threshold.
+    /// `lab` must be a registered, active provider of the registry, and the
+    /// record keeps only the `result_commitment`, never the value.
+    /// `previous_record` and `force_new` work as for `verify_eligibility`; lab
+    /// results don't lapse, so a new one always takes `force_new`.
+    pub fn verify_lab_result(
+        ctx: Context<VerifyLabResult>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+        result_commitment: [u8; 32],
+        lab: Pubkey,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let clock = Clock::get()?;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient = ctx.accounts.patient.key();
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&patient),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        retire_previous_record::<LabResultRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::LabResult,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+
+        let lab_result = &mut ctx.accounts.lab_result;
+        lab_result.set_inner(LabResultRecord {
+            patient,
+            registry: registry.key(),
+            lab,
+            proof_hash: verified.proof_hash,
+            hash_algo,
+            result_commitment,
+            circuit_id,
+            verified_at: clock.unix_timestamp,
+            bump: ctx.bumps.lab_result,
+        });
+        let key = lab_result.key();
+        let cooldown_secs = registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::LabResult, clock.unix_timestamp, cooldown_secs)?;
+        registry.count_verifications(VerificationType::LabResult, 1)?;
+
+        emit!(LabResultVerified {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            lab_result: key,
+            patient,
+            lab,
+            result_commitment,
+        });
+        msg!("Lab result verified for lab {}", lab);
+        Ok(())
+    }
+
+    /// Record an immunization the patient proved against the registry's
+    /// `Immunization` circuit as an `ImmunizationRecord`. `provider` must be a
+    /// registered, active provider of the registry, and the record keeps only
+    /// the `status_commitment`, never the vaccine or dose. `previous_record`
+    /// and `force_new` work as for `verify_eligibility`; immunizations don't
+    /// lapse, so a new one always takes `force_new`.
+    pub fn verify_immunization(
+        ctx: Context<VerifyImmunization>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+        status_commitment: [u8; 32],
+        provider: Pubkey,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let clock = Clock::get()?;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient = ctx.accounts.patient.key();
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&patient),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        retire_previous_record::<ImmunizationRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::Immunization,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+
+        let immunization = &mut ctx.accounts.immunization;
+        immunization.set_inner(ImmunizationRecord {
+            patient,
+            registry: registry.key(),
+            provider,
+            proof_hash: verified.proof_hash,
+            hash_algo,
+            status_commitment,
+            circuit_id,
+            verified_at: clock.unix_timestamp,
+            bump: ctx.bumps.immunization,
+        });
+        let key = immunization.key();
+        let cooldown_secs = registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::Immunization, clock.unix_timestamp, cooldown_secs)?;
+        registry.count_verifications(VerificationType::Immunization, 1)?;
+
+        emit!(ImmunizationVerified {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            immunization: key,
+            patient,
+            provider,
+            status_commitment,
+        });
+        msg!("Immunization verified for provider {}", provider);
+        Ok(())
+    }
+
+    /// Grant the patient a short-lived `AccessPass` to `resource` for a proof
+    /// against the registry's `AccessControl` circuit. The pass allows `uses`
+    /// consumptions over the next `valid_slots` slots, at most the registry's
+    /// `max_access_pass_slots`. It lives at `[b"access_pass", patient, resource]`,
+    /// so a new proof replaces the patient's pass to the same resource. When
+    /// `resource` is one of the patient's own `IpfsPinRecord`s, passing it holds
+    /// off `unpin_medical_data` until the pass expires. `previous_record` and
+    /// `force_new` work as for `verify_eligibility`: the patient's latest pass
+    /// blocks a new one while it has uses left, and `force_new` spends them.
+    pub fn verify_access_control(
+        ctx: Context<VerifyAccessControl>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+        resource: Pubkey,
+        valid_slots: u64,
+        uses: u16,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<()> {
+        require!(valid_slots > 0 && uses > 0, HealthcareError::InvalidAccessPass);
+        let registry = &mut ctx.accounts.registry;
+        require!(valid_slots <= registry.max_access_pass_slots, HealthcareError::AccessPassTooLong);
+        let nullifier = &mut ctx.accounts.nullifier;
+        require!(
+            nullifier.verification == Pubkey::default(),
+            HealthcareError::ProofAlreadyUsed
+        );
+        let clock = Clock::get()?;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient = ctx.accounts.patient.key();
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&patient),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        retire_previous_record::<AccessPass>(
+            &ctx.accounts.previous_record,
+            VerificationType::AccessControl,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.patient,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+
+        let access_pass = &mut ctx.accounts.access_pass;
+        access_pass.set_inner(AccessPass {
+            patient,
+            resource,
+            registry: registry.key(),
+            proof_hash: verified.proof_hash,
+            issued_at_slot: clock.slot,
+            expires_at_slot: clock.slot.saturating_add(valid_slots),
+            uses_remaining: uses,
+            bump: ctx.bumps.access_pass,
+        });
+        if let Some(pin_record) = &mut ctx.accounts.pin_record {
+            pin_record.track_grant(access_pass.expires_at_slot);
+        }
+        let key = access_pass.key();
+        let cooldown_secs = registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::AccessControl, clock.unix_timestamp, cooldown_secs)?;
+        nullifier.verification = key;
+        nullifier.used_at = clock.unix_timestamp;
+        nullifier.bump = ctx.bumps.nullifier;
+        registry.count_verifications(VerificationType::AccessControl, 1)?;
+
+        emit!(AccessPassIssued {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            access_pass: key,
+            patient,
+            resource,
+            expires_at_slot: access_pass.expires_at_slot,
+            uses,
+        });
+        msg!("Access pass issued on circuit {} for {} uses", circuit_id, uses);
+        Ok(())
+    }
+
+    /// Spend one use of an `AccessPass` to `resource`. The resource signs, e.g.
+    /// as a PDA of the program that owns it; `record_access` does the same for
+    /// this program's `IpfsPinRecord`s.
+    pub fn consume_access_pass(ctx: Context<ConsumeAccessPass>, resource: Pubkey) -> Result<()> {
+        let access_pass = &mut ctx.accounts.access_pass;
+        let key = access_pass.key();
+        access_pass.consume(key, resource, Clock::get()?.slot, &mut ctx.accounts.registry)
+    }
+
+    /// Count an access to one of this program's `IpfsPinRecord`s and emit
+    /// `DataAccessed` for the audit trail. The pin's patient needs no grant;
+    /// anyone else spends a use of their `AccessPass` to the pin, which then
+    /// can't be unpinned until the pass expires. An access without a usable
+    /// pass fails with `AccessDenied`, emitting `AccessDenied` first if the
+    /// registry has `denied_access_events_enabled`.
+    pub fn record_access(ctx: Context<RecordAccess>) -> Result<()> {
+        let accessor = ctx.accounts.accessor.key();
+        let registry = &mut ctx.accounts.registry;
+        let pin_record = &mut ctx.accounts.pin_record;
+        let clock = Clock::get()?;
+        let refusal = match &ctx.accounts.access_pass {
+            None if accessor == pin_record.patient => None,
+            None => Some("no access pass"),
+            Some(access_pass) => access_pass.refusal(accessor, registry.key(), pin_record.key(), clock.slot),
+        };
+        if let Some(reason) = refusal {
+            if registry.denied_access_events_enabled {
+                emit!(AccessDenied {
+                    registry: registry.key(),
+                    pin_record: pin_record.key(),
+                    accessor,
+                    patient: pin_record.patient,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+            msg!("Access denied: {}", reason);
+            return err!(HealthcareError::AccessDenied);
+        }
+
+        if let Some(access_pass) = &mut ctx.accounts.access_pass {
+            let key = access_pass.key();
+            access_pass.consume(key, pin_record.key(), clock.slot, registry)?;
+            pin_record.track_grant(access_pass.expires_at_slot);
+        }
+        pin_record.access_count =
+            pin_record.access_count.checked_add(1).ok_or_else(|| error!(HealthcareError::CounterOverflow))?;
+        pin_record.last_accessed_at = clock.unix_timestamp;
+        emit!(DataAccessed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            accessor,
+            patient: pin_record.patient,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// The cached result of the proof behind `proof_hash`, as return data, for as
+    /// long as its entry is fresh. Runs no curve arithmetic.
+    pub fn check_cached(ctx: Context<CheckCached>, proof_hash: [u8; 32]) -> Result<VerificationResult> {
+        let cache = &ctx.accounts.cache;
+        require!(cache.is_fresh(Clock::get()?.slot), HealthcareError::CacheEntryExpired);
+        msg!("Cached result for proof {:02x?}.. on circuit {}", &proof_hash[..4], cache.circuit_id);
+        Ok(cache.result.clone())
+    }
+
+    /// Close an expired `VerificationCache`, refunding the rent to whoever paid
+    /// for it. Anyone may crank this.
+    pub fn reclaim_cache(ctx: Context<ReclaimCache>) -> Result<()> {
+        require!(
+            !ctx.accounts.cache.is_fresh(Clock::get()?.slot),
+            HealthcareError::CacheEntryNotExpired
+        );
+        msg!("Expired cache entry reclaimed");
+        Ok(())
+    }
+
+    /// Commit to a `verify_eligibility` submission before revealing it, so a copy
+    /// of the proof seen in flight can't be landed first by someone else.
+    /// `commitment` is `VerificationCommitment::hash` of the proof, its public
+    /// inputs, a secret salt and the submitter, and must be revealed by the same
+    /// submitter after this slot and within the registry's `commit_reveal_slots`.
+    /// A submitter has one outstanding commitment at a time.
+    pub fn commit_verification(ctx: Context<CommitVerification>, commitment: [u8; 32]) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        require!(registry.commit_reveal_slots > 0, HealthcareError::CommitRevealDisabled);
+        let slot = Clock::get()?.slot;
+        ctx.accounts.pending.set_inner(VerificationCommitment {
+            submitter: ctx.accounts.submitter.key(),
+            commitment,
+            committed_at_slot: slot,
+            expires_at_slot: slot.saturating_add(registry.commit_reveal_slots),
+            bump: ctx.bumps.pending,
+        });
+        msg!("Verification committed until slot {}", ctx.accounts.pending.expires_at_slot);
+        Ok(())
+    }
+
+    /// Close an expired `VerificationCommitment`, refunding the rent to its
+    /// submitter. Anyone may crank this.
+    pub fn reclaim_commitment(ctx: Context<ReclaimCommitment>) -> Result<()> {
+        require!(
+            Clock::get()?.slot > ctx.accounts.commitment.expires_at_slot,
+            HealthcareError::CommitmentNotExpired
+        );
+        msg!("Expired commitment reclaimed");
+        Ok(())
+    }
+
+    /// Check a proof against a circuit's key without writing anything: no
+    /// record, no nullifier. The answer is the return data, `[1]` if the proof
+    /// verifies and `[0]` if it doesn't, which CPI callers read with
+    /// `get_return_data` and clients read from a simulated transaction. Only the
+    /// proof itself is checked; patient, domain and freshness bindings are left to
+    /// the submission that records it. Proofs that don't decode or don't fit the
+    /// circuit are reported under `keccak(VERIFICATION_HASH_DOMAIN || program_id ||
+    /// proof || public_inputs)` over the raw submission.
+    pub fn verify_proof_readonly(
+        ctx: Context<VerifyProofReadonly>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        circuit_id: String,
+    ) -> Result<()> {
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        let (verified, proof_hash) =
+            match verifying_key.verifier().verify(&verifying_key, &proof, proof_format, &public_inputs) {
+                Ok(verdict) => (verdict.verified, verdict.proof_hash),
+                Err(err) => {
+                    msg!("Proof rejected: {}", err);
+                    let raw = [VERIFICATION_HASH_DOMAIN, crate::ID.as_ref(), &proof, &public_inputs];
+                    (false, keccak::hashv(&raw).to_bytes())
+                }
+            };
+
+        set_return_data(&[verified as u8]);
+        emit!(ProofChecked {
+            registry: ctx.accounts.registry.key(),
+            circuit_id,
+            proof_hash,
+            verified,
+        });
+        Ok(())
+    }
+
+    /// Open the upload of a circuit's key. `vk_hash` is the keccak of the bytes
+    /// about to be written; `finalize_vk` refuses an upload that doesn't match it.
+    pub fn register_verifying_key(
+        ctx: Context<RegisterVerifyingKey>,
+        circuit_id: String,
+        total_len: u32,
+        config: VkConfig,
+        vk_hash: [u8; 32],
+    ) -> Result<()> {
+        let mut verifying_key = ctx.accounts.verifying_key.load_init()?;
+
+        verifying_key.set_circuit_id(&circuit_id);
+        verifying_key.authority = ctx.accounts.authority.key();
+        verifying_key.updated_at = Clock::get()?.unix_timestamp;
+        verifying_key.total_len = total_len;
+        verifying_key.bump = ctx.bumps.verifying_key;
+        verifying_key.n_public = config.n_public;
+        verifying_key.binds_patient = config.binds_patient as u8;
+        verifying_key.domain = ctx.accounts.registry.domain;
+        verifying_key.binds_domain = config.domain_input.is_some() as u8;
+        verifying_key.domain_input = config.domain_input.unwrap_or_default();
+        verifying_key.freshness_window_secs = config.freshness_window_secs.unwrap_or_default();
+        verifying_key.validity_secs = config.validity_secs.unwrap_or_default();
+        verifying_key.binds_nullifier = config.nullifier_input.is_some() as u8;
+        verifying_key.nullifier_input = config.nullifier_input.unwrap_or_default();
+        verifying_key.uses_nonce = config.nonce_input.is_some() as u8;
+        verifying_key.nonce_input = config.nonce_input.unwrap_or_default();
+        verifying_key.requires_provider = config.requires_provider as u8;
+        verifying_key.status = CircuitStatus::Active as u8;
+        verifying_key.version = 1;
+        verifying_key.min_accepted_version = 1;
+        verifying_key.vk_hash = vk_hash;
+        verifying_key.curve = config.curve as u8;
+        verifying_key.scheme = config.scheme as u8;
+        verifying_key.fee_lamports = config.fee_lamports;
+        verifying_key.fee_recipient = config.fee_recipient;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.registered_circuits = registry
+            .registered_circuits
+            .checked_add(1)
+            .ok_or(HealthcareError::TooManyCircuits)?;
+
+        emit!(CircuitRegistered {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            circuit_id: circuit_id.clone(),
+            verifying_key: ctx.accounts.verifying_key.key(),
+            n_public: config.n_public,
+        });
+        emit!(VerifyingKeyRegistered {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            circuit_id: circuit_id.clone(),
+            vk_hash,
+            authority: ctx.accounts.authority.key(),
+            n_public: config.n_public,
+        });
+        msg!("Verifying key registered for circuit {}", circuit_id);
+        Ok(())
+    }
+
+    /// Write `chunk` at `offset`. An interrupted upload resumes from the
+    /// `InProgress` offset of `VerifyingKeyPDA::upload_status`.
+    pub fn write_vk_chunk(ctx: Context<WriteVkChunk>, offset: u32, chunk: Vec<u8>) -> Result<()> {
+        let mut vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_mut_data()?;
+        let mut verifying_key = VerifyingKeyMut::new(&mut vk_data)?;
+        verifying_key.write_chunk(offset, &chunk)?;
+        verifying_key.updated_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Grow a key account towards `VerifyingKeyPDA::space` of its key. Accounts
+    /// can gain at most `MAX_PERMITTED_DATA_INCREASE` bytes per instruction, so
+    /// keys past that are registered small and resized over several calls before
+    /// their chunks are written.
+    pub fn resize_vk_account(ctx: Context<ResizeVkAccount>, new_len: u32) -> Result<()> {
+        let mut verifying_key = ctx.accounts.verifying_key.load_mut()?;
+        verifying_key.updated_at = Clock::get()?.unix_timestamp;
+        msg!("Verifying key account for circuit {} resized to {} bytes", verifying_key.circuit_id(), new_len);
+        Ok(())
+    }
+
+    /// Close an unfinalized key upload, returning its rent to the authority
+    pub fn abort_vk_upload(ctx: Context<AbortVkUpload>, circuit_id: String) -> Result<()> {
+        close_vk_upload(&mut ctx.accounts.registry, &ctx.accounts.verifying_key, circuit_id)
+    }
+
+    /// Close a key upload left unfinalized for `VK_UPLOAD_TIMEOUT_SECS` since its
+    /// last write. Anyone may call it; the rent still goes to the authority.
+    pub fn gc_stale_upload(ctx: Context<GcStaleUpload>, circuit_id: String) -> Result<()> {
+        let updated_at = ctx.accounts.verifying_key.load()?.updated_at;
+        require!(
+            Clock::get()?.unix_timestamp >= updated_at.saturating_add(VK_UPLOAD_TIMEOUT_SECS),
+            HealthcareError::VkUploadNotStale
+        );
+        close_vk_upload(&mut ctx.accounts.registry, &ctx.accounts.verifying_key, circuit_id)
+    }
+
+    pub fn finalize_vk(ctx: Context<FinalizeVk>) -> Result<()> {
+        let mut vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_mut_data()?;
+        let mut verifying_key = VerifyingKeyMut::new(&mut vk_data)?;
+
+        require!(!verifying_key.is_finalized(), HealthcareError::VerifyingKeyAlreadyFinalized);
+        let key = verifying_key.view();
+        require!(
+            key.upload_status() == UploadStatus::InProgress { written: key.total_len },
+            HealthcareError::VerifyingKeyIncomplete
+        );
+        require!(key.is_intact(), HealthcareError::VerifyingKeyCorrupted);
+        key.verifier().check_verifying_key(key.vk_bytes())?;
+
+        verifying_key.finalized = 1;
+        verifying_key.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(VerifyingKeyFinalized {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            circuit_id: verifying_key.circuit_id().to_string(),
+            vk_hash: verifying_key.vk_hash,
+        });
+        msg!("Verifying key finalized for circuit {}", verifying_key.circuit_id());
+        Ok(())
+    }
+
+    /// Approve a finalized circuit for a verification type. The verify
+    /// instructions only accept the key mapped to the type they record, so a proof
+    /// of one kind of statement can't be filed as another.
+    pub fn set_type_circuit(ctx: Context<SetTypeCircuit>, verification_type: VerificationType) -> Result<()> {
+        let verifying_key = &ctx.accounts.verifying_key;
+        ctx.accounts.registry.circuit_for_type[verification_type as usize] = Some(verifying_key.key());
+        msg!("Circuit {} approved for {:?}", verifying_key.load()?.circuit_id(), verification_type);
+        Ok(())
+    }
+
+    /// Create the registry's verification mint at `derive_verification_mint`: a
+    /// Token-2022 mint of zero decimals whose tokens can't be transferred. The
+    /// mint is its own mint authority and permanent delegate, so only this
+    /// program mints its tokens or burns them back.
+    pub fn initialize_verification_mint(ctx: Context<InitializeVerificationMint>) -> Result<()> {
+        use anchor_lang::system_program::{create_account, CreateAccount};
+        use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+        use anchor_spl::token_2022::spl_token_2022::state::Mint;
+        use anchor_spl::token_2022::{initialize_mint2, InitializeMint2};
+        use anchor_spl::token_2022_extensions::{
+            non_transferable_mint_initialize, permanent_delegate_initialize, NonTransferableMintInitialize,
+            PermanentDelegateInitialize,
+        };
+        let mint = ctx.accounts.verification_mint.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let registry = ctx.accounts.registry.key();
+        let seeds: &[&[u8]] = &[b"verification_mint", registry.as_ref(), &[ctx.bumps.verification_mint]];
+        let space = ExtensionType::try_calculate_account_len::<Mint>(&[
+            ExtensionType::NonTransferable,
+            ExtensionType::PermanentDelegate,
+        ])?;
+
+        let accounts = CreateAccount {
+            from: ctx.accounts.authority.to_account_info(),
+            to: mint.clone(),
+        };
+        create_account(
+            CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(), accounts, &[seeds]),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &Token2022::id(),
+        )?;
+        let accounts = NonTransferableMintInitialize {
+            token_program_id: token_program.clone(),
+            mint: mint.clone(),
+        };
+        non_transferable_mint_initialize(CpiContext::new(token_program.clone(), accounts))?;
+        let accounts = PermanentDelegateInitialize {
+            token_program_id: token_program.clone(),
+            mint: mint.clone(),
+        };
+        permanent_delegate_initialize(CpiContext::new(token_program.clone(), accounts), &mint.key())?;
+        let accounts = InitializeMint2 { mint: mint.clone() };
+        initialize_mint2(CpiContext::new(token_program, accounts), 0, &mint.key(), None)?;
+        msg!("Verification mint {} initialized", mint.key());
+        Ok(())
+    }
+
+    /// Register `pharmacy` under the registry, letting it sign `record_refill`
+    pub fn register_pharmacy(ctx: Context<RegisterPharmacy>, pharmacy: Pubkey) -> Result<()> {
+        let registration = &mut ctx.accounts.registration;
+        registration.registry = ctx.accounts.registry.key();
+        registration.pharmacy = pharmacy;
+        registration.registered_at = Clock::get()?.unix_timestamp;
+        registration.bump = ctx.bumps.registration;
+        msg!("Pharmacy {} registered", pharmacy);
+        Ok(())
+    }
+
+    /// Register `insurer` under the registry, letting it `dispute_verification`
+    pub fn register_insurer(ctx: Context<RegisterInsurer>, insurer: Pubkey) -> Result<()> {
+        let registration = &mut ctx.accounts.registration;
+        registration.registry = ctx.accounts.registry.key();
+        registration.insurer = insurer;
+        registration.registered_at = Clock::get()?.unix_timestamp;
+        registration.bump = ctx.bumps.registration;
+        msg!("Insurer {} registered", insurer);
+        Ok(())
+    }
+
+    /// Register `provider` under the registry as active, letting diagnoses name it.
+    /// `eth_address` is the Ethereum identity its secp256k1 attestations recover to.
+    pub fn register_provider(
+        ctx: Context<RegisterProvider>,
+        provider: Pubkey,
+        eth_address: Option<[u8; 20]>,
+    ) -> Result<()> {
+        let registration = &mut ctx.accounts.registration;
+        registration.registry = ctx.accounts.registry.key();
+        registration.provider = provider;
+        registration.registered_at = Clock::get()?.unix_timestamp;
+        registration.active = true;
+        registration.bump = ctx.bumps.registration;
+        registration.eth_address = eth_address.unwrap_or_default();
+        msg!("Provider {} registered", provider);
+        Ok(())
+    }
+
+    /// Suspend or reinstate a registered provider. Diagnoses it already made
+    /// stand; an inactive provider can't be named by new ones.
+    pub fn set_provider_active(ctx: Context<SetProviderActive>, active: bool) -> Result<()> {
+        let registration = &mut ctx.accounts.registration;
+        registration.active = active;
+        msg!("Provider {} active: {}", registration.provider, active);
+        Ok(())
+    }
+
+    /// Cap the verifications a provider may attest per UTC day, zero lifting
+    /// the cap. What it attested today still counts against the new quota.
+    pub fn set_provider_quota(ctx: Context<SetProviderQuota>, daily_quota: u32) -> Result<()> {
+        let registration = &mut ctx.accounts.registration;
+        registration.daily_quota = daily_quota;
+        msg!("Provider {} daily quota: {}", registration.provider, daily_quota);
+        Ok(())
+    }
+
+    /// Cap the `fee_lamports` a circuit can charge per proof. Applies to keys
+    /// registered from now on; zero, the default, only admits free circuits.
+    pub fn set_max_circuit_fee(ctx: Context<SetMaxCircuitFee>, max_circuit_fee: u64) -> Result<()> {
+        ctx.accounts.registry.max_circuit_fee = max_circuit_fee;
+        msg!("Circuit fees capped at {} lamports", max_circuit_fee);
+        Ok(())
+    }
+
+    /// Replace the registry's security policy. It applies to circuits registered
+    /// from now on and to every verification, so tightening it also stops
+    /// existing circuits outside it. A NIST-compliant registry's policy may only
+    /// allow NIST-approved algorithms.
+    pub fn set_security_policy(ctx: Context<SetSecurityPolicy>, policy: SecurityPolicy) -> Result<()> {
+        require!(policy.is_valid(), HealthcareError::InvalidSecurityPolicy);
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            !registry.nist_compliant || policy.is_nist_compliant(),
+            HealthcareError::PolicyNotNistCompliant
+        );
+        registry.security_policy = policy;
+        msg!("Security policy set to {:?}", policy);
+        Ok(())
+    }
+
+    /// How long records stay active when their circuit sets no validity period of
+    /// its own; zero means they never expire. Only records written from now on
+    /// are affected.
+    pub fn set_default_validity(ctx: Context<SetDefaultValidity>, validity_secs: i64) -> Result<()> {
+        require!(validity_secs >= 0, HealthcareError::InvalidValidityPeriod);
+        ctx.accounts.registry.default_validity_secs = validity_secs;
+        msg!("Records now valid for {} seconds by default", validity_secs);
+        Ok(())
+    }
+
+    /// Set the bounty `close_expired_verification` pays its cranker out of the
+    /// closed record's rent, at most that rent
+    pub fn set_gc_bounty(ctx: Context<SetGcBounty>, gc_bounty_lamports: u64) -> Result<()> {
+        require!(
+            gc_bounty_lamports <= Rent::get()?.minimum_balance(VerificationRecord::SPACE),
+            HealthcareError::GcBountyTooHigh
+        );
+        ctx.accounts.registry.gc_bounty_lamports = gc_bounty_lamports;
+        msg!("Expired records closed for a {} lamport bounty", gc_bounty_lamports);
+        Ok(())
+    }
+
+    /// How long after a record is written `dispute_verification` may contest it;
+    /// zero turns disputes off. Applies to records already written.
+    pub fn set_dispute_window(ctx: Context<SetDisputeWindow>, window_secs: i64) -> Result<()> {
+        require!(window_secs >= 0, HealthcareError::InvalidDisputeWindow);
+        ctx.accounts.registry.dispute_window_secs = window_secs;
+        msg!("Records open to dispute for {} seconds", window_secs);
+        Ok(())
+    }
+
+    /// Make patients wait `cooldown_secs` between verifications of the same type,
+    /// and anonymous credentials as long past their previous epoch's record;
+    /// zero lifts the wait
+    pub fn set_verification_cooldown(ctx: Context<SetVerificationCooldown>, cooldown_secs: i64) -> Result<()> {
+        require!(cooldown_secs >= 0, HealthcareError::InvalidVerificationCooldown);
+        ctx.accounts.registry.cooldown_secs = cooldown_secs;
+        msg!("Verifications of one type at least {} seconds apart", cooldown_secs);
+        Ok(())
+    }
+
+    /// Measure the registry's windows in slots rather than by the validators'
+    /// unix timestamp, or back. Windows stay configured in seconds either way.
+    pub fn set_time_source(ctx: Context<SetTimeSource>, time_source: TimeSource) -> Result<()> {
+        ctx.accounts.registry.time_source = time_source;
+        msg!("Registry windows measured by {:?}", time_source);
+        Ok(())
+    }
+
+    /// Set the longest `metadata` `update_metadata` accepts from now on, at most
+    /// what one instruction may grow an account by. Records already holding more
+    /// keep it.
+    pub fn set_max_metadata_len(ctx: Context<SetMaxMetadataLen>, max_metadata_len: u16) -> Result<()> {
+        require!(
+            max_metadata_len as usize <= MAX_PERMITTED_DATA_INCREASE,
+            HealthcareError::MetadataLimitTooHigh
+        );
+        ctx.accounts.registry.max_metadata_len = max_metadata_len;
+        msg!("Record metadata limited to {} bytes", max_metadata_len);
+        Ok(())
+    }
+
+    /// Emit `EligibilityVerified`, `VerificationRevoked` and `DataPinned` a
+    /// second time through `emit_cpi!`, or stop. The copy lands in the
+    /// transaction's inner instructions, where the log limit can't truncate it,
+    /// for the compute of a self-CPI per event.
+    pub fn set_cpi_events(ctx: Context<SetCpiEvents>, enabled: bool) -> Result<()> {
+        ctx.accounts.registry.cpi_events_enabled = enabled;
+        msg!("CPI events {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// Have `record_access` emit `AccessDenied` for each access it refuses, or
+    /// stop. The refused transaction fails, so the event only reaches indexers
+    /// that read the logs of failed transactions.
+    pub fn set_denied_access_events(ctx: Context<SetDeniedAccessEvents>, enabled: bool) -> Result<()> {
+        ctx.accounts.registry.denied_access_events_enabled = enabled;
+        msg!("Denied access events {}", if enabled { "enabled" } else { "disabled" });
+        Ok(())
+    }
+
+    /// How long a pin reported `Failed` waits for its patient to retry or
+    /// abandon it before anyone may abandon it with `abandon_failed_pin`; zero
+    /// leaves failed pins to their patients. Applies to pins already failed.
+    pub fn set_failed_pin_timeout(ctx: Context<SetFailedPinTimeout>, timeout_secs: i64) -> Result<()> {
+        require!(timeout_secs >= 0, HealthcareError::InvalidFailedPinTimeout);
+        ctx.accounts.registry.failed_pin_timeout_secs = timeout_secs;
+        msg!("Failed pins abandonable by anyone after {} seconds", timeout_secs);
+        Ok(())
+    }
+
+    /// Charge `price_lamports_per_day` for pin storage from now on, paid to
+    /// `storage_treasury`; zero makes new pins free and lets them never expire.
+    /// Pins keep the `paid_through` they were paid to, so only pins and
+    /// renewals from now on are affected.
+    pub fn set_pin_storage_price(
+        ctx: Context<SetPinStoragePrice>,
+        price_lamports_per_day: u64,
+        storage_treasury: Pubkey,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.price_lamports_per_day = price_lamports_per_day;
+        registry.storage_treasury = storage_treasury;
+        msg!("Pin storage priced at {} lamports a day", price_lamports_per_day);
+        Ok(())
+    }
+
+    /// Release the bounties of pins written from now on in `periods` equal
+    /// shares, one for each `period_secs` a pinner hosts the pin. Pins keep the
+    /// terms they were written under.
+    pub fn set_pin_bounty_terms(ctx: Context<SetPinBountyTerms>, period_secs: i64, periods: u16) -> Result<()> {
+        require!(period_secs > 0 && periods > 0, HealthcareError::InvalidPinBountyTerms);
+        let registry = &mut ctx.accounts.registry;
+        registry.pin_bounty_period_secs = period_secs;
+        registry.pin_bounty_periods = periods;
+        msg!("Pin bounties released over {} periods of {} seconds", periods, period_secs);
+        Ok(())
+    }
+
+    /// Require `target` pinning oracles to report the content of pins written
+    /// from now on before they are `Confirmed`, at most `MAX_PIN_REPLICAS`.
+    /// Pins keep the target they were written under.
+    pub fn set_pin_replication_target(ctx: Context<SetPinReplicationTarget>, target: u8) -> Result<()> {
+        require!(
+            target > 0 && usize::from(target) <= MAX_PIN_REPLICAS,
+            HealthcareError::InvalidReplicationTarget
+        );
+        ctx.accounts.registry.pin_replication_target = target;
+        msg!("Pins confirmed by {} oracles", target);
+        Ok(())
+    }
+
+    /// Let `deal_expiring_soon` flag a pin's storage deal from `window_secs`
+    /// before it ends
+    pub fn set_deal_expiry_warning(ctx: Context<SetDealExpiryWarning>, window_secs: i64) -> Result<()> {
+        require!(window_secs > 0, HealthcareError::InvalidDealExpiryWarning);
+        ctx.accounts.registry.deal_expiry_warning_secs = window_secs;
+        msg!("Storage deals flagged {} seconds before they end", window_secs);
+        Ok(())
+    }
+
+    /// Bound how many slots an `AccessPass` issued from now on may stay valid,
+    /// and so how long one can hold off the unpin of a pin it is used on
+    pub fn set_max_access_pass_slots(ctx: Context<SetMaxAccessPassSlots>, max_slots: u64) -> Result<()> {
+        require!(max_slots > 0, HealthcareError::InvalidMaxAccessPassSlots);
+        ctx.accounts.registry.max_access_pass_slots = max_slots;
+        msg!("Access passes valid for at most {} slots", max_slots);
+        Ok(())
+    }
+
+    /// Let `oracle` report pins of this registry through `confirm_pin`, up to
+    /// `MAX_PINNING_ORACLES` at once
+    pub fn add_pinning_oracle(ctx: Context<AddPinningOracle>, oracle: Pubkey) -> Result<()> {
+        let oracles = &mut ctx.accounts.registry.pinning_oracles;
+        require!(!oracles.contains(&oracle), HealthcareError::PinningOracleAlreadyAdded);
+        require!(oracles.len() < MAX_PINNING_ORACLES, HealthcareError::TooManyPinningOracles);
+        oracles.push(oracle);
+        msg!("Pinning oracle {} added", oracle);
+        Ok(())
+    }
+
+    /// Stop `oracle` reporting pins. Pins it already reported keep their status.
+    pub fn remove_pinning_oracle(ctx: Context<RemovePinningOracle>, oracle: Pubkey) -> Result<()> {
+        let oracles = &mut ctx.accounts.registry.pinning_oracles;
+        let position = oracles.iter().position(|key| *key == oracle).ok_or(HealthcareError::NotPinningOracle)?;
+        oracles.swap_remove(position);
+        msg!("Pinning oracle {} removed", oracle);
+        Ok(())
+    }
+
+    /// Change the registry's limits on submissions. `max_public_inputs` bounds
+    /// the public inputs of every proof submitted from now on; verifications
+    /// already begun finish under the limit they were submitted with. Results
+    /// cached from now on stay fresh for `cache_ttl_slots`. A nonzero
+    /// `commit_reveal_slots` makes `verify_eligibility` require a commitment
+    /// made at most that many slots before, and shuts the batch and two-phase
+    /// paths, which take none; zero turns commitments off.
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        max_public_inputs: u8,
+        cache_ttl_slots: u64,
+        commit_reveal_slots: u64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.max_public_inputs = max_public_inputs;
+        registry.cache_ttl_slots = cache_ttl_slots;
+        registry.commit_reveal_slots = commit_reveal_slots;
+        msg!(
+            "Submissions limited to {} public inputs, results cached for {} slots, commitments kept {} slots",
+            max_public_inputs,
+            cache_ttl_slots,
+            commit_reveal_slots
+        );
+        Ok(())
+    }
+
+    pub fn prepare_vk(ctx: Context<PrepareVk>) -> Result<()> {
+        let mut vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_mut_data()?;
+        let mut verifying_key = VerifyingKeyMut::new(&mut vk_data)?;
+        let prepared = prepare_vk_bytes(verifying_key.view().vk_bytes())?;
+        verifying_key.set_prepared(&prepared)?;
+        msg!("Prepared verifying key cached for circuit {}", verifying_key.circuit_id());
+        Ok(())
+    }
+
+    pub fn propose_vk_update(
+        ctx: Context<ProposeVkUpdate>,
+        new_vk_bytes_hash: [u8; 32],
+        total_len: u32,
+    ) -> Result<()> {
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        let proposal = &mut ctx.accounts.proposal;
+        let now = Clock::get()?.unix_timestamp;
+
+        proposal.verifying_key = ctx.accounts.verifying_key.key();
+        proposal.new_vk_hash = new_vk_bytes_hash;
+        proposal.proposed_at = now;
+        proposal.activatable_at = now
+            .checked_add(ctx.accounts.registry.vk_update_delay_secs)
+            .ok_or(HealthcareError::InvalidVkUpdateDelay)?;
+        proposal.total_len = total_len;
+        proposal.vk_bytes = vec![0; total_len as usize];
+        proposal.written_mask = vec![0; VerifyingKeyPDA::mask_len(total_len)];
+        proposal.bump = ctx.bumps.proposal;
+
+        emit!(VerifyingKeyUpdateProposed {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            circuit_id: verifying_key.circuit_id().to_string(),
+            old_hash: keccak::hash(verifying_key.vk_bytes()).to_bytes(),
+            new_hash: new_vk_bytes_hash,
+            activatable_at: proposal.activatable_at,
+        });
+
+        Ok(())
+    }
+
+    pub fn write_vk_update_chunk(
+        ctx: Context<WriteVkUpdateChunk>,
+        offset: u32,
+        chunk: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.proposal.write_chunk(offset, &chunk)
+    }
+
+    pub fn activate_vk_update(ctx: Context<ActivateVkUpdate>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        let mut vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_mut_data()?;
+        let mut verifying_key = VerifyingKeyMut::new(&mut vk_data)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(now >= proposal.activatable_at, HealthcareError::VkUpdateTimelockActive);
+        require!(proposal.is_complete(), HealthcareError::VerifyingKeyIncomplete);
+        require!(
+            keccak::hash(&proposal.vk_bytes).to_bytes() == proposal.new_vk_hash,
+            HealthcareError::VkUpdateHashMismatch
+        );
+        verifying_key.verifier().check_verifying_key(&proposal.vk_bytes)?;
+
+        let old_hash = keccak::hash(verifying_key.view().vk_bytes()).to_bytes();
+        // The cached syscall encoding belongs to the old key and must be rebuilt with prepare_vk
+        verifying_key.replace(&proposal.vk_bytes, proposal.new_vk_hash)?;
+        verifying_key.updated_at = now;
+        verifying_key.version = verifying_key.version.saturating_add(1);
+
+        emit!(VerifyingKeyUpdated {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            circuit_id: verifying_key.circuit_id().to_string(),
+            old_hash,
+            new_hash: proposal.new_vk_hash,
+        });
+
+        msg!("Verifying key updated for circuit {}", verifying_key.circuit_id());
+        Ok(())
+    }
+
+    pub fn cancel_vk_update(_ctx: Context<CancelVkUpdate>) -> Result<()> {
+        msg!("Pending verifying key update cancelled");
+        Ok(())
+    }
+
+    /// Stop or resume accepting proofs for a circuit. Records already written are
+    /// untouched; revoking also lets `sweep_revoked_records` invalidate them, and
+    /// is final.
+    pub fn set_circuit_status(
+        ctx: Context<SetCircuitStatus>,
+        status: CircuitStatus,
+        min_accepted_version: u16,
+    ) -> Result<()> {
+        let mut verifying_key = ctx.accounts.verifying_key.load_mut()?;
+        require!(
+            verifying_key.status() != CircuitStatus::Revoked,
+            HealthcareError::InvalidCircuitStatusChange
+        );
+        verifying_key.status = status as u8;
+        verifying_key.min_accepted_version = min_accepted_version;
+
+        emit!(CircuitStatusChanged {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            circuit_id: verifying_key.circuit_id().to_string(),
+            status,
+            min_accepted_version,
+        });
+        msg!("Circuit {} is now {:?}", verifying_key.circuit_id(), status);
+        Ok(())
+    }
+
+    /// Revoke the `VerificationRecord`s of `registry` passed writable in
+    /// `remaining_accounts` once their circuit is revoked, each followed by its
+    /// patient's `PatientIndex` address. Anyone may crank this; records that were
+    /// already revoked are skipped, and one under a hold fails the sweep.
+    pub fn sweep_revoked_records<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepRevokedRecords<'info>>,
+    ) -> Result<()> {
+        let verifying_key = ctx.accounts.verifying_key.load()?;
+        let circuit_id = verifying_key.circuit_id();
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        require!(pairs.remainder().is_empty(), HealthcareError::PatientIndexMismatch);
+        let mut swept = 0;
+        for accounts in pairs {
+            let (info, patient_index) = (&accounts[0], &accounts[1]);
+            let mut record = Account::<VerificationRecord>::try_from(info)?;
+            require!(record.circuit_id == circuit_id, HealthcareError::RecordCircuitMismatch);
+            require!(
+                record.is_in_registry(&ctx.accounts.registry.key()),
+                HealthcareError::RecordRegistryMismatch
+            );
+            require!(
+                patient_index.key() == PatientIndex::address(&record.patient_pubkey),
+                HealthcareError::PatientIndexMismatch
+            );
+            if record.status == RecordStatus::Revoked {
+                continue;
+            }
+            require!(!record.is_held(), HealthcareError::RecordUnderHold);
+            if record.is_verified() {
+                release_active_record(patient_index)?;
+            }
+            record.transition(info.key(), RecordStatus::Revoked, &mut ctx.accounts.registry)?;
+            record.event_seq = ctx.accounts.registry.next_event_seq()?;
+            record.exit(&crate::ID)?;
+            swept += 1;
+
+            emit!(VerificationInvalidatedByCircuitRevocation {
+                seq: record.event_seq,
+                registry: ctx.accounts.registry.key(),
+                record: info.key(),
+                patient: record.patient_pubkey,
+                verification_type: record.verification_type,
+                circuit_id: circuit_id.to_string(),
+            });
+        }
+        let registry = &mut ctx.accounts.registry;
+        registry.revoked_count = checked_count(registry.revoked_count, swept)?;
+        msg!("Invalidated {} records of revoked circuit {}", swept, circuit_id);
+        Ok(())
+    }
+
+    /// Revoke one `VerificationRecord` whose eligibility no longer holds, such as
+    /// after a loss of coverage or a proof found to rest on falsified data. The
+    /// registry authority or the record's patient may sign; `reason_code` is
+    /// opaque to the program and only carried in the event.
+    pub fn revoke_verification(ctx: Context<RevokeVerification>, reason_code: u16) -> Result<()> {
+        let revoked_by = ctx.accounts.revoker.key();
+        let record = &mut ctx.accounts.verification;
+        require!(
+            revoked_by == ctx.accounts.registry.authority || revoked_by == record.patient_pubkey,
+            HealthcareError::NotAuthorizedToRevoke
+        );
+        if record.is_verified() {
+            release_active_record(&ctx.accounts.patient_index)?;
+        }
+        let key = record.key();
+        record.transition(key, RecordStatus::Revoked, &mut ctx.accounts.registry)?;
+        record.revoked_at = Clock::get()?.unix_timestamp;
+        record.revoked_by = revoked_by;
+        let registry = &mut ctx.accounts.registry;
+        registry.revoked_count = checked_count(registry.revoked_count, 1)?;
+        record.event_seq = registry.next_event_seq()?;
+
+        let event = VerificationRevoked {
+            seq: record.event_seq,
+            registry: registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            verification_type: record.verification_type,
+            reason_code,
+            revoked_by,
+        };
+        emit!(event);
+        if registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
+        msg!("Verification revoked with reason {}", reason_code);
+        Ok(())
+    }
+
+    /// Burn the token `verify_eligibility` minted for a revoked record out of the
+    /// patient's account, signing as the mint's permanent delegate, and clear the
+    /// record's `token_mint`. Anyone may crank this.
+    pub fn burn_verification_token(ctx: Context<BurnVerificationToken>) -> Result<()> {
+        use anchor_spl::token_2022::{burn, Burn};
+        let registry = ctx.accounts.registry.key();
+        let mint = ctx.accounts.verification_mint.to_account_info();
+        let seeds: &[&[u8]] = &[b"verification_mint", registry.as_ref(), &[ctx.bumps.verification_mint]];
+        let accounts = Burn {
+            mint: mint.clone(),
+            from: ctx.accounts.patient_token_account.to_account_info(),
+            authority: mint.clone(),
+        };
+        burn(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), accounts, &[seeds]),
+            1,
+        )?;
+
+        let record = &mut ctx.accounts.verification;
+        record.token_mint = Pubkey::default();
+        emit!(VerificationTokenBurned {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            mint: mint.key(),
+        });
+        msg!("Verification token burned");
+        Ok(())
+    }
+
+    /// Contest a `Verified` record within the registry's `dispute_window_secs` of
+    /// its `timestamp`, or its `slot` under `TimeSource::Slot`, without revoking
+    /// it. The registry authority or a registered insurer, whose
+    /// `insurer_registration` is then passed, may sign. The record turns
+    /// `Disputed`, which fails `assert_active`, until `resolve_dispute`; the
+    /// `VerificationDispute` keeps `evidence_hash`, opaque to the program, and
+    /// the disputer.
+    pub fn dispute_verification(ctx: Context<DisputeVerification>, evidence_hash: [u8; 32]) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let disputer = ctx.accounts.disputer.key();
+        require!(
+            disputer == registry.authority || ctx.accounts.insurer_registration.is_some(),
+            HealthcareError::NotAuthorizedToDispute
+        );
+        let time = registry.time_source;
+        let now = time.now(&Clock::get()?);
+        let record = &mut ctx.accounts.verification;
+        require!(
+            now < record.written_at(time).saturating_add(time.span(registry.dispute_window_secs)),
+            HealthcareError::DisputeWindowClosed
+        );
+        let key = record.key();
+        record.transition(key, RecordStatus::Disputed, &mut ctx.accounts.registry)?;
+        release_active_record(&ctx.accounts.patient_index)?;
+
+        ctx.accounts.dispute.set_inner(VerificationDispute {
+            record: key,
+            disputer,
+            evidence_hash,
+            disputed_at: now,
+            bump: ctx.bumps.dispute,
+        });
+        emit!(VerificationDisputed {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            record: key,
+            patient: record.patient_pubkey,
+            verification_type: record.verification_type,
+            disputer,
+            evidence_hash,
+        });
+        msg!("Verification disputed");
+        Ok(())
+    }
+
+    /// Settle a dispute: an `upheld` one revokes the record, otherwise it is
+    /// `Verified` again. Only the registry authority may resolve; the
+    /// `VerificationDispute` is closed back to its disputer.
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, upheld: bool) -> Result<()> {
+        let record = &mut ctx.accounts.verification;
+        let key = record.key();
+        if upheld {
+            record.transition(key, RecordStatus::Revoked, &mut ctx.accounts.registry)?;
+            record.revoked_at = Clock::get()?.unix_timestamp;
+            record.revoked_by = ctx.accounts.authority.key();
+            let registry = &mut ctx.accounts.registry;
+            registry.revoked_count = checked_count(registry.revoked_count, 1)?;
+        } else {
+            record.transition(key, RecordStatus::Verified, &mut ctx.accounts.registry)?;
+            restore_active_record(&ctx.accounts.patient_index)?;
+        }
+
+        let dispute = &ctx.accounts.dispute;
+        emit!(DisputeResolved {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            record: key,
+            patient: record.patient_pubkey,
+            verification_type: record.verification_type,
+            disputer: dispute.disputer,
+            evidence_hash: dispute.evidence_hash,
+            upheld,
+        });
+        msg!("Dispute resolved, upheld: {}", upheld);
+        Ok(())
+    }
+
+    /// Freeze a record under a litigation hold for the legal order hashed to
+    /// `order_hash`, until `release_hold`. Only the registry authority may
+    /// place or release a hold, and a record holds one at a time.
+    pub fn place_hold(ctx: Context<PlaceHold>, order_hash: [u8; 32]) -> Result<()> {
+        let record = &mut ctx.accounts.verification;
+        require!(!record.is_held(), HealthcareError::RecordUnderHold);
+        let placed_at = Clock::get()?.unix_timestamp;
+        record.hold = Some(HoldInfo { placed_at, order_hash });
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(HoldPlaced {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            order_hash,
+            placed_at,
+        });
+        msg!("Hold placed on verification record");
+        Ok(())
+    }
+
+    /// Lift the hold `place_hold` put on a record
+    pub fn release_hold(ctx: Context<ReleaseHold>) -> Result<()> {
+        let record = &mut ctx.accounts.verification;
+        let hold = record.hold.take().ok_or(HealthcareError::RecordNotHeld)?;
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(HoldReleased {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            order_hash: hold.order_hash,
+            placed_at: hold.placed_at,
+        });
+        msg!("Hold released from verification record");
+        Ok(())
+    }
+
+    /// File a claim for `amount` lamports of care against the signing patient's
+    /// `VerificationRecord`, which must be active: a revoked, expired or disputed
+    /// record backs no claim. The `ClaimRecord` is numbered by the registry's
+    /// `claim_count` and starts out `Submitted`.
+    ///
+    /// `claim_nullifier` is derived from the encounter billed, so a second claim
+    /// for it fails with `DuplicateClaim` until `release_claim_nullifier`.
+    pub fn submit_claim(
+        ctx: Context<SubmitClaim>,
+        amount: u64,
+        service_code_commitment: [u8; 32],
+        claim_nullifier: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, HealthcareError::InvalidClaimAmount);
+        require!(ctx.accounts.nullifier.claim == Pubkey::default(), HealthcareError::DuplicateClaim);
+        let clock = Clock::get()?;
+        ctx.accounts.verification.assert_active(&clock)?;
+        let registry = &mut ctx.accounts.registry;
+        let claim_id = registry.claim_count;
+        registry.claim_count = checked_count(claim_id, 1)?;
+
+        let patient = ctx.accounts.patient.key();
+        let verification = ctx.accounts.verification.key();
+        let claim = &mut ctx.accounts.claim;
+        claim.set_inner(ClaimRecord {
+            registry: registry.key(),
+            patient,
+            verification,
+            claim_id,
+            amount,
+            service_code_commitment,
+            status: ClaimStatus::Submitted,
+            submitted_at: clock.unix_timestamp,
+            bump: ctx.bumps.claim,
+            payee: patient,
+            insurer: None,
+            reason_code: 0,
+            adjudicated_at: 0,
+            claim_nullifier,
+        });
+        ctx.accounts.nullifier.set_inner(ClaimNullifier {
+            claim: claim.key(),
+            used_at: clock.unix_timestamp,
+            bump: ctx.bumps.nullifier,
+        });
+
+        emit!(ClaimSubmitted {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            claim: claim.key(),
+            patient,
+            verification,
+            claim_id,
+            amount,
+            service_code_commitment,
+            claim_nullifier,
+        });
+        msg!("Claim {} submitted for {} lamports", claim_id, amount);
+        Ok(())
+    }
+
+    /// Have a submitted claim paid out to `payee`, a provider active in the
+    /// registry, in place of the patient
+    pub fn designate_claim_payee(ctx: Context<DesignateClaimPayee>) -> Result<()> {
+        let payee = ctx.accounts.provider_registration.provider;
+        ctx.accounts.claim.payee = payee;
+        msg!("Claim {} pays {}", ctx.accounts.claim.claim_id, payee);
+        Ok(())
+    }
+
+    /// Move `lamports`, which must be the claimed amount, from a registered
+    /// insurer into the claim's `ClaimEscrow`. The insurer becomes the one
+    /// claim's adjudicator; the escrow can be funded only once.
+    pub fn fund_claim_escrow(ctx: Context<FundClaimEscrow>, lamports: u64) -> Result<()> {
+        use anchor_lang::system_program::{transfer, Transfer};
+        require!(lamports == ctx.accounts.claim.amount, HealthcareError::ClaimEscrowMismatch);
+        let accounts = Transfer {
+            from: ctx.accounts.insurer.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+        };
+        transfer(CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts), lamports)?;
+
+        let insurer = ctx.accounts.insurer.key();
+        let claim = ctx.accounts.claim.key();
+        ctx.accounts.escrow.set_inner(ClaimEscrow {
+            claim,
+            insurer,
+            lamports,
+            bump: ctx.bumps.escrow,
+        });
+        ctx.accounts.claim.insurer = Some(insurer);
+        emit!(ClaimEscrowFunded {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            claim,
+            insurer,
+            lamports,
+        });
+        msg!("Claim {} escrowed {} lamports", ctx.accounts.claim.claim_id, lamports);
+        Ok(())
+    }
+
+    /// Settle a claim as the insurer who funded its escrow. Approval pays the
+    /// escrowed lamports to the claim's payee and marks it `Paid`; denial marks
+    /// it `Denied` and refunds them. Either way the escrow closes to the insurer,
+    /// so a claim is adjudicated once.
+    pub fn adjudicate_claim(ctx: Context<AdjudicateClaim>, approve: bool, reason_code: u16) -> Result<()> {
+        let lamports = ctx.accounts.escrow.lamports;
+        if approve {
+            let escrow = ctx.accounts.escrow.to_account_info();
+            **escrow.try_borrow_mut_lamports()? -= lamports;
+            **ctx.accounts.payee.try_borrow_mut_lamports()? += lamports;
+        }
+        let claim = &mut ctx.accounts.claim;
+        claim.status = if approve { ClaimStatus::Paid } else { ClaimStatus::Denied };
+        claim.reason_code = reason_code;
+        claim.adjudicated_at = Clock::get()?.unix_timestamp;
+        emit!(ClaimAdjudicated {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            claim: claim.key(),
+            insurer: ctx.accounts.insurer.key(),
+            approved: approve,
+            reason_code,
+            payee: claim.payee,
+            lamports,
+        });
+        msg!("Claim {} {}", claim.claim_id, if approve { "paid" } else { "denied" });
+        Ok(())
+    }
+
+    /// Free a denied claim's encounter for a corrected resubmission, as the
+    /// insurer that denied it. Closing its `ClaimNullifier` returns the rent to
+    /// the patient; the claim itself stays as the record of the denial.
+    pub fn release_claim_nullifier(ctx: Context<ReleaseClaimNullifier>) -> Result<()> {
+        let claim = &ctx.accounts.claim;
+        emit!(ClaimNullifierReleased {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            claim: claim.key(),
+            insurer: ctx.accounts.insurer.key(),
+            claim_nullifier: claim.claim_nullifier,
+        });
+        msg!("Claim {} nullifier released", claim.claim_id);
+        Ok(())
+    }
+
+    /// Commit `root`, the Merkle root `client::AuditTree` builds over the
+    /// registry's events numbered `start_seq` up to `end_seq`, so auditors can
+    /// check an event's inclusion without trusting an RPC. Checkpoints are
+    /// contiguous: each starts where the last one ended and covers at least one
+    /// event already emitted. Only the registry authority commits them.
+    pub fn commit_checkpoint(
+        ctx: Context<CommitCheckpoint>,
+        root: [u8; 32],
+        start_seq: u64,
+        end_seq: u64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            start_seq == registry.checkpointed_seq,
+            HealthcareError::CheckpointNotContiguous
+        );
+        require!(
+            start_seq < end_seq && end_seq <= registry.event_seq,
+            HealthcareError::InvalidCheckpointRange
+        );
+        registry.checkpointed_seq = end_seq;
+
+        let checkpoint = &mut ctx.accounts.checkpoint;
+        checkpoint.set_inner(Checkpoint {
+            registry: registry.key(),
+            root,
+            start_seq,
+            end_seq,
+            committed_at: Clock::get()?.unix_timestamp,
+            bump: ctx.bumps.checkpoint,
+        });
+        emit!(CheckpointCommitted {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            checkpoint: checkpoint.key(),
+            root,
+            start_seq,
+            end_seq,
+        });
+        msg!("Checkpoint committed over events {} to {}", start_seq, end_seq);
+        Ok(())
+    }
+
+    /// Replace the encrypted `metadata` the patient keeps on their record, at
+    /// most the registry's `max_metadata_len` bytes. The account is resized to
+    /// fit: the patient pays the rent of a larger payload and gets back what a
+    /// smaller one frees. The event carries the payload's keccak hash, not the
+    /// payload.
+    pub fn update_metadata(ctx: Context<UpdateMetadata>, metadata: Vec<u8>) -> Result<()> {
+        use anchor_lang::system_program::{transfer, Transfer};
+        require!(
+            metadata.len() <= ctx.accounts.registry.max_metadata_len as usize,
+            HealthcareError::MetadataTooLong
+        );
+        let len = ctx.accounts.verification.space_with_metadata(metadata.len());
+        let info = ctx.accounts.verification.to_account_info();
+        let patient = ctx.accounts.patient.to_account_info();
+        let rent = Rent::get()?.minimum_balance(len);
+        if rent > info.lamports() {
+            let accounts = Transfer {
+                from: patient,
+                to: info.clone(),
+            };
+            let cpi = CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts);
+            transfer(cpi, rent - info.lamports())?;
+        } else {
+            let refund = info.lamports() - rent;
+            **info.try_borrow_mut_lamports()? -= refund;
+            **patient.try_borrow_mut_lamports()? += refund;
+        }
+        info.realloc(len, false)?;
+
+        let metadata_hash = keccak::hash(&metadata).to_bytes();
+        let record = &mut ctx.accounts.verification;
+        record.metadata = metadata;
+        emit!(MetadataUpdated {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            metadata_hash,
+            len: record.metadata.len() as u32,
+        });
+        msg!("Record metadata is now {} bytes", record.metadata.len());
+        Ok(())
+    }
+
+    /// Rewrite a `VerificationRecord` still in an earlier layout in the current one,
+    /// resizing the account to its `VerificationRecord::space_with_metadata`. The
+    /// payer covers the extra rent. Anyone may crank this; the record's contents
+    /// are unchanged.
+    pub fn migrate_verification_record(ctx: Context<MigrateVerificationRecord>) -> Result<()> {
+        use anchor_lang::system_program::{transfer, Transfer};
+        let info = ctx.accounts.verification.to_account_info();
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            require!(
+                migrations::LegacyVerificationRecord::is_legacy(&data),
+                HealthcareError::RecordNotLegacy
+            );
+            migrations::LegacyVerificationRecord::try_from_bytes(&data)?.into_current()
+        };
+        require!(
+            legacy.is_in_registry(&ctx.accounts.registry.key()),
+            HealthcareError::RecordRegistryMismatch
+        );
+
+        let len = legacy.space_with_metadata(legacy.metadata.len());
+        let rent = Rent::get()?.minimum_balance(len);
+        if rent > info.lamports() {
+            let accounts = Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: info.clone(),
+            };
+            let cpi = CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts);
+            transfer(cpi, rent - info.lamports())?;
+        }
+        info.realloc(len, true)?;
+        legacy.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit!(VerificationRecordMigrated {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            record: info.key(),
+            version: VerificationRecord::VERSION,
+        });
+        msg!("Verification record migrated to version {}", VerificationRecord::VERSION);
+        Ok(())
+    }
+
+    /// Rewrite an `IpfsPinRecord` still in an earlier layout in the current one,
+    /// decoding the CID of one that stores it as a string. Every earlier layout
+    /// is shorter than the current one, so the payer covers the rent the pin
+    /// grows by. Anyone may crank this. A pin whose CID doesn't decode fails
+    /// with `InvalidCid`. The pin's patient's `PatientIndex`, created if
+    /// need be, starts counting a pin from before v12 under its category;
+    /// it counted a later one when it was pinned.
+    pub fn migrate_pin_record(ctx: Context<MigratePinRecord>) -> Result<()> {
+        use anchor_lang::system_program::{transfer, Transfer};
+        let info = ctx.accounts.pin_record.to_account_info();
+        let (pin_record, counted) = {
+            let data = info.try_borrow_data()?;
+            require!(migrations::LegacyIpfsPinRecord::is_legacy(&data), HealthcareError::PinRecordNotLegacy);
+            let counted = migrations::LegacyIpfsPinRecord::is_counted(&data);
+            (migrations::LegacyIpfsPinRecord::current_from_bytes(&data)?, counted)
+        };
+        require_keys_eq!(
+            pin_record.registry,
+            ctx.accounts.registry.key(),
+            HealthcareError::PinRecordRegistryMismatch
+        );
+
+        let len = IpfsPinRecord::space_with(pin_record.history.len(), pin_record.deals.len());
+        let rent = Rent::get()?.minimum_balance(len);
+        if rent > info.lamports() {
+            let accounts = Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: info.clone(),
+            };
+            let cpi = CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts);
+            transfer(cpi, rent - info.lamports())?;
+        }
+        info.realloc(len, true)?;
+        pin_record.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        emit!(PinRecordMigrated {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            pin_record: info.key(),
+            version: IpfsPinRecord::VERSION,
+        });
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        if !counted {
+            patient_index.count_pin(pin_record.category)?;
+        }
+        msg!("Pin record migrated to version {}", IpfsPinRecord::VERSION);
+        Ok(())
+    }
+
+    /// Let an active record of this registry move to `destination`, as when
+    /// hospitals merge onto one registry, until `expires_at`. The ticket
+    /// snapshots the record, so `import_record` takes it only unchanged. Only
+    /// the registry authority may export; a record may be exported again once
+    /// its ticket has expired.
+    pub fn export_record(ctx: Context<ExportRecord>, expires_at: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        let record = &ctx.accounts.verification;
+        record.assert_active(&clock)?;
+        require!(record.rent_payer != Pubkey::default(), HealthcareError::AnonymousRecordNotMigratable);
+        require!(expires_at > clock.unix_timestamp, HealthcareError::MigrationTicketExpired);
+        let ticket = &mut ctx.accounts.ticket;
+        require!(
+            ticket.expires_at == 0 || clock.unix_timestamp >= ticket.expires_at,
+            HealthcareError::MigrationTicketOutstanding
+        );
+        let snapshot_hash = keccak::hash(&record.to_account_info().try_borrow_data()?).to_bytes();
+        ticket.set_inner(MigrationTicket {
+            source_registry: ctx.accounts.registry.key(),
+            record: record.key(),
+            destination: ctx.accounts.destination.key(),
+            snapshot_hash,
+            issued_at: clock.unix_timestamp,
+            expires_at,
+            imported_record: None,
+            bump: ctx.bumps.ticket,
+        });
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(RecordExported {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            destination: ctx.accounts.destination.key(),
+            ticket: ticket.key(),
+            snapshot_hash,
+            expires_at,
+        });
+        msg!("Verification record exported to {}", ctx.accounts.destination.key());
+        Ok(())
+    }
+
+    /// Copy a record exported to this registry under `ticket` to its patient's
+    /// next `derive_verification_pda`, verified here without a new proof and
+    /// naming the original in `migrated_from`. The original turns `Migrated`,
+    /// so a ticket imports once. Both registries' authorities sign: this one
+    /// pays for the copy, the source one releases the record.
+    pub fn import_record(ctx: Context<ImportRecord>) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp < ctx.accounts.ticket.expires_at,
+            HealthcareError::MigrationTicketExpired
+        );
+        let source = &mut ctx.accounts.verification;
+        let snapshot_hash = keccak::hash(&source.to_account_info().try_borrow_data()?).to_bytes();
+        require!(
+            snapshot_hash == ctx.accounts.ticket.snapshot_hash,
+            HealthcareError::RecordSnapshotMismatch
+        );
+        source.assert_active(&clock)?;
+
+        let registry = &mut ctx.accounts.registry;
+        let (source_key, new_key) = (source.key(), ctx.accounts.new_record.key());
+        source.transition(source_key, RecordStatus::Migrated, &mut ctx.accounts.source_registry)?;
+        let index = &mut ctx.accounts.patient_index;
+        index.next_record_nonce += 1;
+        index.last_record_by_type[source.verification_type as usize] = new_key;
+        let record = &mut ctx.accounts.new_record;
+        record.set_inner(VerificationRecord {
+            verification_id: derive_verification_id(
+                &registry.key(),
+                &source.patient_pubkey,
+                &source.circuit_id,
+                &source.proof_hash,
+            ),
+            status: RecordStatus::Pending,
+            rent_payer: ctx.accounts.authority.key(),
+            bump: ctx.bumps.new_record,
+            // A mint of the source registry vouches for nothing here
+            token_mint: Pubkey::default(),
+            previous_record: index.link_record(new_key),
+            migrated_from: source_key,
+            ..(**source).clone()
+        });
+        record.transition(new_key, RecordStatus::Verified, registry)?;
+        record.event_seq = registry.next_event_seq()?;
+        ctx.accounts.ticket.imported_record = Some(new_key);
+
+        emit!(RecordImported {
+            seq: record.event_seq,
+            registry: registry.key(),
+            record: new_key,
+            patient: record.patient_pubkey,
+            verification_type: record.verification_type,
+            source_registry: ctx.accounts.source_registry.key(),
+            migrated_from: source_key,
+        });
+        msg!("Verification record imported from {}", ctx.accounts.source_registry.key());
+        Ok(())
+    }
+
+    /// Consume a `VerificationRecord` of this registry, e.g. from a claims program
+    /// through `cpi::check_verification`. Fails unless the record is active, and
+    /// returns it as a `VerificationResult`.
+    pub fn check_verification(ctx: Context<CheckVerification>) -> Result<VerificationResult> {
+        let record = &ctx.accounts.verification;
+        record.assert_active(&Clock::get()?)?;
+        Ok(VerificationResult {
+            verified: true,
+            proof_hash: record.proof_hash,
+            record: record.key(),
+            verification_id: record.verification_id,
+        })
+    }
+
+    /// Where `patient` stands for `verification_type` under this registry, for
+    /// clients that simulate rather than decode accounts, or programs through
+    /// `cpi::check_verification_status`. `record` must be the patient's
+    /// `PatientIndex::last_record_by_type`, or any account if they have none.
+    /// Unlike `check_verification`, a patient who isn't verified is an answer,
+    /// not an error. Changes nothing.
+    pub fn check_verification_status(
+        ctx: Context<CheckVerificationStatus>,
+        patient: Pubkey,
+        verification_type: VerificationType,
+    ) -> Result<VerificationStatusView> {
+        let index = &ctx.accounts.patient_index;
+        let latest = if index.owner == &crate::ID && !index.data_is_empty() {
+            PatientIndex::try_deserialize(&mut &index.try_borrow_data()?[..])?.last_record_by_type
+                [verification_type as usize]
+        } else {
+            Pubkey::default()
+        };
+        let mut view = VerificationStatusView {
+            exists: false,
+            status: RecordStatus::Pending,
+            expires_at: 0,
+            record: Pubkey::default(),
+            held: false,
+        };
+        let info = &ctx.accounts.record;
+        if latest == Pubkey::default() {
+            return Ok(view);
+        }
+        require_keys_eq!(info.key(), latest, HealthcareError::NotLatestRecord);
+        // Closed since, or another registry's
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(view);
+        }
+        let record = VerificationRecord::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        if record.patient_pubkey != patient || !record.is_in_registry(&ctx.accounts.registry.key()) {
+            return Ok(view);
+        }
+        let expired = record.is_verified() && record.is_past_expiry(&Clock::get()?);
+        view.exists = true;
+        view.status = if expired { RecordStatus::Expired } else { record.status };
+        view.expires_at = record.expires_at;
+        view.record = latest;
+        view.held = record.is_held();
+        Ok(view)
+    }
+
+    /// Mark a record `Expired` once its `expires_at` has passed, so indexers see
+    /// the expiry as an event. Anyone may crank this.
+    pub fn mark_expired(ctx: Context<MarkExpired>) -> Result<()> {
+        let record = &mut ctx.accounts.verification;
+        require!(record.is_past_expiry(&Clock::get()?), HealthcareError::RecordNotExpired);
+        if record.is_verified() {
+            release_active_record(&ctx.accounts.patient_index)?;
+        }
+        let key = record.key();
+        record.transition(key, RecordStatus::Expired, &mut ctx.accounts.registry)?;
+        let registry = &mut ctx.accounts.registry;
+        registry.expired_count = checked_count(registry.expired_count, 1)?;
+
+        emit!(VerificationExpired {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            verification_type: record.verification_type,
+            expires_at: record.expires_at,
+        });
+        msg!("Verification expired at {}", record.expires_at);
+        Ok(())
+    }
+
+    /// Close a record `RECORD_GC_GRACE_SECS` past its expiry. The rent goes back
+    /// to its `rent_payer`, or the key it rotated to, less the registry's
+    /// `gc_bounty_lamports` for the cranker, who may be anyone. The record
+    /// written after it in its patient's chain, if any, is passed as
+    /// `next_record` to be relinked past it.
+    pub fn close_expired_verification(ctx: Context<CloseExpiredVerification>) -> Result<()> {
+        let record = &ctx.accounts.verification;
+        let time = ctx.accounts.registry.time_source;
+        let now = time.now(&Clock::get()?);
+        let expires_at = record.expires_in(time);
+        require!(record.expires_at != 0 && now >= expires_at, HealthcareError::RecordNotExpired);
+        require!(
+            now >= expires_at.saturating_add(time.span(RECORD_GC_GRACE_SECS)),
+            HealthcareError::GcGracePeriodActive
+        );
+
+        // Expired whether or not `mark_expired` ran
+        if record.is_verified() {
+            release_active_record(&ctx.accounts.patient_index)?;
+        }
+        let next_record = ctx.accounts.next_record.as_deref_mut();
+        update_patient_index(&ctx.accounts.patient_index, |index| {
+            index.unlink_record(record.key(), record, next_record)
+        })?;
+
+        // The rest of the rent goes to `rent_payer` when the account closes
+        let record_info = record.to_account_info();
+        let bounty = ctx.accounts.registry.gc_bounty_lamports.min(record_info.lamports());
+        **record_info.try_borrow_mut_lamports()? -= bounty;
+        **ctx.accounts.cranker.try_borrow_mut_lamports()? += bounty;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.closed_verifications = checked_count(registry.closed_verifications, 1)?;
+
+        emit!(VerificationClosed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            record: record.key(),
+            patient: record.patient_pubkey,
+            verification_type: record.verification_type,
+            rent_payer: ctx.accounts.rent_payer.key(),
+            lamports: record_info.lamports(),
+            cranker: Some(ctx.accounts.cranker.key()),
+            bounty,
+        });
+        msg!("Expired verification closed, {} lamport bounty paid", bounty);
+        Ok(())
+    }
+
+    /// Close many records at once: each record in `remaining_accounts` is
+    /// followed by its `PatientIndex` address, the `KeyRotation` address of its
+    /// `rent_payer`, the payer or the key it rotated to, which gets the whole
+    /// rent back, without the bounty `close_expired_verification` pays, and the
+    /// record after it in the patient's chain, or the program id if there is
+    /// none. A record must be revoked, migrated away or `RECORD_GC_GRACE_SECS`
+    /// past its expiry, and not under a hold. One that can't be closed fails the
+    /// whole instruction, naming its position in the list. Anyone may crank this.
+    pub fn close_records_bulk<'info>(ctx: Context<'_, '_, 'info, 'info, CloseRecordsBulk<'info>>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let time = registry.time_source;
+        let now = time.now(&Clock::get()?);
+        let groups = ctx.remaining_accounts.chunks_exact(5);
+        require!(groups.remainder().is_empty(), HealthcareError::BulkCloseAccountMismatch);
+        let mut lamports_returned = 0;
+        for (index, accounts) in groups.clone().enumerate() {
+            let (info, patient_index, rent_payer) = (&accounts[0], &accounts[1], &accounts[3]);
+            let record = closable_record(info, patient_index, &accounts[2], rent_payer, &registry.key(), time, now)
+                .map_err(|err| err.with_account_name(format!("records[{}]", index)))?;
+            if record.is_verified() {
+                release_active_record(patient_index)?;
+            }
+            let successor = (accounts[4].key() != crate::ID).then_some(&accounts[4]);
+            update_patient_index(patient_index, |patient_index| {
+                unlink_with_successor(patient_index, info.key(), &record, successor)
+            })?;
+            let lamports = info.lamports();
+            lamports_returned += lamports;
+            **rent_payer.try_borrow_mut_lamports()? += lamports;
+            **info.try_borrow_mut_lamports()? = 0;
+            info.assign(&System::id());
+            info.realloc(0, false)?;
+
+            emit!(VerificationClosed {
+                seq: registry.next_event_seq()?,
+                registry: registry.key(),
+                record: info.key(),
+                patient: record.patient_pubkey,
+                verification_type: record.verification_type,
+                rent_payer: rent_payer.key(),
+                lamports,
+                cranker: None,
+                bounty: 0,
+            });
+        }
+        let count = groups.len() as u32;
+        registry.closed_verifications = checked_count(registry.closed_verifications, u64::from(count))?;
+
+        emit!(RecordsClosed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            count,
+            lamports_returned,
+        });
+        msg!("Closed {} records, {} lamports returned", count, lamports_returned);
+        Ok(())
+    }
+
+    /// Close a revoked circuit's key account and return its rent to the authority,
+    /// once `VK_CLOSE_COOLDOWN_SECS` have passed since it last verified a proof. Any
+    /// verification type still mapped to the key is unmapped, so a key later
+    /// registered under the same circuit id has to be approved again.
+    pub fn close_verifying_key(ctx: Context<CloseVerifyingKey>, circuit_id: String) -> Result<()> {
+        let verifying_key = ctx.accounts.verifying_key.key();
+        let last_used_at = ctx.accounts.verifying_key.load()?.last_used_at;
+        require!(
+            Clock::get()?.unix_timestamp >= last_used_at.saturating_add(VK_CLOSE_COOLDOWN_SECS),
+            HealthcareError::VkCloseCooldownActive
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        for circuit in registry.circuit_for_type.iter_mut() {
+            if *circuit == Some(verifying_key) {
+                *circuit = None;
+            }
+        }
+        registry.registered_circuits = registry.registered_circuits.saturating_sub(1);
+
+        emit!(VerifyingKeyClosed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            circuit_id: circuit_id.clone(),
+            verifying_key,
+            authority: ctx.accounts.authority.key(),
+        });
+        msg!("Verifying key for circuit {} closed", circuit_id);
+        Ok(())
+    }
+
+    /// Pin `ipfs_cid` for the signing patient. While the registry prices
+    /// storage, `storage_lamports` buys whole days of it at
+    /// `price_lamports_per_day`, at least one, paid to the `storage_treasury`;
+    /// the pin is paid through the end of them. Lamports short of another day
+    /// stay with the patient.
+    ///
+    /// `pin_bounty_lamports` are escrowed in the pin for the oracles that
+    /// host the content to claim as they do, see `claim_pin_bounty`; whatever
+    /// isn't released to them goes back to the patient on unpin.
+    ///
+    /// The pin is filed under `category`, with `tag` if given, and counted in
+    /// the patient's `PatientIndex`; `retag_pin` changes both.
+    pub fn pin_medical_data(
+        ctx: Context<PinMedicalData>,
+        ipfs_cid: String,
+        data_hash: [u8; 32],
+        storage_lamports: u64,
+        pin_bounty_lamports: u64,
+        category: DataCategory,
+        tag: Option<[u8; 8]>,
+    ) -> Result<()> {
+        let patient = ctx.accounts.patient.key();
+        let accounts = &ctx.accounts;
+        let days = buy_pin_storage(
+            &accounts.registry,
+            storage_lamports,
+            &accounts.patient,
+            &accounts.storage_treasury,
+            &accounts.system_program,
+        )?;
+        let info = accounts.pin_record.to_account_info();
+        let seeds: &[&[u8]] = &[b"pin", patient.as_ref(), &data_hash, &[ctx.bumps.pin_record]];
+        let mut pin_record = create_pin_record(&accounts.patient, &info, &accounts.system_program, seeds)?;
+        let seeds: &[&[u8]] = &[b"pin_content", patient.as_ref(), &data_hash, &[ctx.bumps.content_claim]];
+        claim_pin_content(&accounts.patient, &accounts.content_claim, &accounts.system_program, seeds, info.key())?;
+        escrow_pin_bounty(&accounts.patient, &info, &accounts.system_program, pin_bounty_lamports)?;
+        let pin = NewPin {
+            backend: StorageBackend::Ipfs,
+            storage_id: ipfs_cid.as_bytes(),
+            data_hash,
+            paid_days: days,
+            bounty_lamports: pin_bounty_lamports,
+            category,
+            tag,
+        };
+        let event = pin_data(&mut pin_record, &info, &mut ctx.accounts.registry, patient, None, pin)?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        patient_index.count_pin(category)?;
+        emit!(event);
+        if ctx.accounts.registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    /// `pin_medical_data` for content stored on `backend`, under its
+    /// `storage_id`: a CID's string for IPFS, an Arweave transaction's 32-byte
+    /// id, the sha256 of a Shadow Drive file's URL, or up to
+    /// `MAX_CUSTOM_STORAGE_ID_LEN` bytes for a `Custom` store. Fails with
+    /// `InvalidStorageId`, or `InvalidCid` for IPFS, on an id of the wrong
+    /// shape.
+    pub fn pin_medical_data_on(
+        ctx: Context<PinMedicalDataOn>,
+        backend: StorageBackend,
+        storage_id: Vec<u8>,
+        data_hash: [u8; 32],
+        storage_lamports: u64,
+        pin_bounty_lamports: u64,
+        category: DataCategory,
+        tag: Option<[u8; 8]>,
+    ) -> Result<()> {
+        let patient = ctx.accounts.patient.key();
+        let accounts = &ctx.accounts;
+        let days = buy_pin_storage(
+            &accounts.registry,
+            storage_lamports,
+            &accounts.patient,
+            &accounts.storage_treasury,
+            &accounts.system_program,
+        )?;
+        let info = accounts.pin_record.to_account_info();
+        let seeds: &[&[u8]] = &[b"pin", patient.as_ref(), &data_hash, &[ctx.bumps.pin_record]];
+        let mut pin_record = create_pin_record(&accounts.patient, &info, &accounts.system_program, seeds)?;
+        let seeds: &[&[u8]] = &[b"pin_content", patient.as_ref(), &data_hash, &[ctx.bumps.content_claim]];
+        claim_pin_content(&accounts.patient, &accounts.content_claim, &accounts.system_program, seeds, info.key())?;
+        escrow_pin_bounty(&accounts.patient, &info, &accounts.system_program, pin_bounty_lamports)?;
+        let pin = NewPin {
+            backend,
+            storage_id: &storage_id,
+            data_hash,
+            paid_days: days,
+            bounty_lamports: pin_bounty_lamports,
+            category,
+            tag,
+        };
+        let event = pin_data(&mut pin_record, &info, &mut ctx.accounts.registry, patient, None, pin)?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        patient_index.count_pin(category)?;
+        emit!(event);
+        if ctx.accounts.registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    /// Pin many documents of the signing patient's at once, as an import job
+    /// does. `remaining_accounts` holds each entry's writable pin record PDA,
+    /// `IpfsPinRecord::address(patient, data_hash)`, then its content claim,
+    /// `PinContentClaim::address(patient, data_hash)`, in order. Each pin buys
+    /// storage with `storage_lamports` as `pin_medical_data` does, and carries
+    /// no bounty. One bad entry aborts the whole batch, and its index is
+    /// logged. A single `DataPinnedBatch` stands for the pins' `DataPinned`
+    /// events, so the logs stay short.
+    pub fn pin_medical_data_bulk<'info>(
+        ctx: Context<'_, '_, '_, 'info, PinMedicalDataBulk<'info>>,
+        entries: Vec<PinEntry>,
+        storage_lamports: u64,
+    ) -> Result<()> {
+        require!(
+            !entries.is_empty() && entries.len() <= MAX_BULK_PINS,
+            HealthcareError::InvalidBulkPinCount
+        );
+        require!(
+            ctx.remaining_accounts.len() == 2 * entries.len(),
+            HealthcareError::BulkPinAccountMismatch
+        );
+        let patient = ctx.accounts.patient.key();
+        let clock = Clock::get()?;
+        let mut paid_through = 0;
+        for (index, (entry, infos)) in entries.iter().zip(ctx.remaining_accounts.chunks(2)).enumerate() {
+            let accounts = &ctx.accounts;
+            let pinned = buy_pin_storage(
+                &accounts.registry,
+                storage_lamports,
+                &accounts.patient,
+                &accounts.storage_treasury,
+                &accounts.system_program,
+            )
+            .and_then(|days| {
+                let pin_record = bulk_pin(accounts, &infos[0], &infos[1], entry, days, &clock)?;
+                paid_through = pin_record.paid_through;
+                Ok(())
+            });
+            if let Err(err) = pinned {
+                msg!("Batch entry {} rejected", index);
+                return Err(err);
+            }
+        }
+
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        for entry in &entries {
+            patient_index.count_pin(entry.category)?;
+        }
+        let registry = &mut ctx.accounts.registry;
+        registry.ipfs_pin_count = checked_count(registry.ipfs_pin_count, entries.len() as u64)?;
+        let data_hashes: Vec<[u8; 32]> = entries.iter().map(|entry| entry.data_hash).collect();
+        let event = DataPinnedBatch {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            patient,
+            count: entries.len() as u16,
+            data_hash_root: data_hash_root(&data_hashes),
+            slot: clock.slot,
+            paid_through,
+        };
+        emit!(event);
+        if ctx.accounts.registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
+        msg!("Batch of {} pins written", entries.len());
+        Ok(())
+    }
+
+    /// Close the signing patient's pin record, so pinning workers can release
+    /// the data. The pin's patient signs, or the key it rotated to. The bounty
+    /// periods hosted until now are settled, and each oracle owed a share is
+    /// paid it, passed writable in `remaining_accounts`. The bounty left
+    /// unreleased goes to the signing patient, and the rent back to its
+    /// `rent_payer`, or the key that rotated to, even when a guardian paid
+    /// both. Fails with `ActiveGrantsExist` while an `AccessPass` to the pin may
+    /// still be consumed.
+    pub fn unpin_medical_data<'info>(ctx: Context<'_, '_, '_, 'info, UnpinMedicalData<'info>>) -> Result<()> {
+        let pin_record = &mut ctx.accounts.pin_record;
+        let clock = Clock::get()?;
+        require!(!pin_record.has_active_grants(clock.slot), HealthcareError::ActiveGrantsExist);
+        pin_record.settle_bounty(clock.unix_timestamp);
+        pay_bounty_shares(pin_record, ctx.remaining_accounts)?;
+        let bounty_refunded = refund_unreleased_bounty(pin_record, &ctx.accounts.patient)?;
+        release_pin_content(&ctx.accounts.content_claim, pin_record.key(), &ctx.accounts.rent_payer)?;
+        ctx.accounts.patient_index.uncount_pin(pin_record.category)?;
+        let registry = &mut ctx.accounts.registry;
+        registry.ipfs_pin_count =
+            registry.ipfs_pin_count.checked_sub(1).ok_or_else(|| error!(HealthcareError::CounterOverflow))?;
+        let pinned_duration_secs = clock.unix_timestamp.saturating_sub(pin_record.pinned_at);
+        emit!(DataUnpinned {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            ipfs_cid: pin_record.cid_string(),
+            backend: pin_record.backend,
+            rent_payer: ctx.accounts.rent_payer.key(),
+            lamports: pin_record.to_account_info().lamports(),
+            pinned_duration_secs,
+            bounty_refunded,
+        });
+        msg!("Pin record {} closed after {}s pinned", pin_record.cid_string(), pinned_duration_secs);
+        Ok(())
+    }
+
+    /// Report whether the IPFS cluster pinned a pin's content, as one of the
+    /// registry's `pinning_oracles`, naming the pinning service in
+    /// `provider_id`. Each oracle reporting the content pinned counts once
+    /// among the pin's replicas, a repeated report changing nothing, and the
+    /// pin turns `Confirmed` once they meet its `replication_target`; the
+    /// oracle that met it is the pin's `oracle`. Oracles go on reporting a
+    /// confirmed pin to replace replicas dropped through `report_unpin`. Only
+    /// a confirmed pin can be granted, accessed or linked from a diagnosis.
+    ///
+    /// A failure report settles a requested pin `Failed`, to wait for its
+    /// patient to `retry_pin` or `abandon_failed_pin`.
+    pub fn confirm_pin(ctx: Context<ConfirmPin>, success: bool, provider_id: String) -> Result<()> {
+        require!(provider_id.len() <= MAX_PIN_PROVIDER_ID_LEN, HealthcareError::PinProviderIdTooLong);
+        let pin_record = &mut ctx.accounts.pin_record;
+        let (oracle, timestamp) = (ctx.accounts.oracle.key(), Clock::get()?.unix_timestamp);
+        let registry = &mut ctx.accounts.registry;
+        let (registry_key, pin, patient) = (registry.key(), pin_record.key(), pin_record.patient);
+        if !success {
+            require!(pin_record.status == PinStatus::Requested, HealthcareError::PinAlreadySettled);
+            pin_record.status = PinStatus::Failed;
+            pin_record.oracle = oracle;
+            pin_record.settled_at = timestamp;
+            emit!(PinFailed {
+                seq: registry.next_event_seq()?,
+                registry: registry_key,
+                pin_record: pin,
+                patient,
+                oracle,
+                provider_id,
+                timestamp,
+            });
+            msg!("Pin record {} failed", pin_record.cid_string());
+            return Ok(());
+        }
+
+        require!(
+            matches!(pin_record.status, PinStatus::Requested | PinStatus::Confirmed),
+            HealthcareError::PinAlreadySettled
+        );
+        pin_record.settle_bounty(timestamp);
+        if !pin_record.add_replica(oracle, timestamp)? {
+            msg!("Pin record {} already held by {}", pin_record.cid_string(), oracle);
+            return Ok(());
+        }
+        let (replicas, replication_target) = (pin_record.replication_achieved(), pin_record.replication_target);
+        emit!(PinReplicaConfirmed {
+            seq: registry.next_event_seq()?,
+            registry: registry_key,
+            pin_record: pin,
+            oracle,
+            provider_id: provider_id.clone(),
+            replicas,
+            replication_target,
+            timestamp,
+        });
+        if pin_record.status == PinStatus::Requested && replicas >= replication_target {
+            pin_record.status = PinStatus::Confirmed;
+            pin_record.oracle = oracle;
+            pin_record.settled_at = timestamp;
+            pin_record.bounty_settled_through = timestamp;
+            emit!(PinConfirmed {
+                seq: registry.next_event_seq()?,
+                registry: registry_key,
+                pin_record: pin,
+                patient,
+                oracle,
+                provider_id,
+                timestamp,
+            });
+        }
+        msg!("Pin record {} held by {} of {} oracles", pin_record.cid_string(), replicas, replication_target);
+        Ok(())
+    }
+
+    /// Report, as an oracle counted among a pin's replicas, that it no longer
+    /// holds the content, so it stops counting. A confirmed pin left short of
+    /// its `replication_target` stays `Confirmed`, its content still held
+    /// elsewhere, and `ReplicationDegraded` asks the other oracles to restore
+    /// it. An oracle since removed from the registry may still report.
+    pub fn report_unpin(ctx: Context<ReportUnpin>) -> Result<()> {
+        let pin_record = &mut ctx.accounts.pin_record;
+        let (oracle, timestamp) = (ctx.accounts.oracle.key(), Clock::get()?.unix_timestamp);
+        pin_record.settle_bounty(timestamp);
+        require!(pin_record.remove_replica(oracle), HealthcareError::NotPinReplica);
+        let (replicas, replication_target) = (pin_record.replication_achieved(), pin_record.replication_target);
+        if pin_record.status == PinStatus::Confirmed && replicas < replication_target {
+            let registry = &mut ctx.accounts.registry;
+            emit!(ReplicationDegraded {
+                seq: registry.next_event_seq()?,
+                registry: registry.key(),
+                pin_record: pin_record.key(),
+                patient: pin_record.patient,
+                oracle,
+                replicas,
+                replication_target,
+                timestamp,
+            });
+        }
+        msg!("Pin record {} dropped by {}, {} replicas left", pin_record.cid_string(), oracle, replicas);
+        Ok(())
+    }
+
+    /// Put a pin an oracle reported `Failed` back to `Requested` for the
+    /// oracles to report again, under `new_cid` if the patient re-added the
+    /// content under another CID, which only an IPFS pin has. Only the pin's
+    /// patient may retry.
+    pub fn retry_pin(ctx: Context<RetryPin>, new_cid: Option<String>) -> Result<()> {
+        let pin_record = &mut ctx.accounts.pin_record;
+        let timestamp = Clock::get()?.unix_timestamp;
+        if let Some(new_cid) = &new_cid {
+            require!(pin_record.backend == StorageBackend::Ipfs, HealthcareError::InvalidStorageId);
+            pin_record.set_cid(new_cid)?;
+        }
+        pin_record.request_again(timestamp);
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(PinRetried {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            ipfs_cid: pin_record.cid_string(),
+            backend: pin_record.backend,
+            cid_changed: new_cid.is_some(),
+            timestamp,
+        });
+        msg!("Pin record {} requested again", pin_record.cid_string());
+        Ok(())
+    }
+
+    /// File a pin of the signing patient's under `category`, with `tag` if
+    /// given, in place of what it was pinned with. The patient's
+    /// `PatientIndex` counts the pin under its new category instead.
+    pub fn retag_pin(ctx: Context<RetagPin>, category: DataCategory, tag: Option<[u8; 8]>) -> Result<()> {
+        let pin_record = &mut ctx.accounts.pin_record;
+        let old_category = std::mem::replace(&mut pin_record.category, category);
+        pin_record.tag = tag;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.uncount_pin(old_category)?;
+        patient_index.count_pin(category)?;
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(PinRetagged {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            old_category,
+            category,
+            tag,
+        });
+        msg!("Pin record {} filed under {:?}", pin_record.cid_string(), category);
+        Ok(())
+    }
+
+    /// Replace the content of an IPFS pin of the signing patient's with
+    /// `new_cid` and `new_data_hash`, as when a document is amended, keeping
+    /// what it held in its `history`: the last `MAX_PIN_VERSIONS` versions,
+    /// the oldest dropped past that. The pin keeps its address, and with it
+    /// the access passes issued to it, and goes back to `Requested` for the
+    /// oracles to report the new content pinned; the passes open it again
+    /// once they have. The patient pays the rent a growing history adds.
+    ///
+    /// The pin's content claim moves to `new_data_hash`, which fails with
+    /// `AlreadyPinned` if another pin of the patient's holds that content or
+    /// lives at its address. The pin's own address stays taken while it
+    /// exists, so the content it was first pinned with can't be pinned again
+    /// beside it, though the pin may be updated back to it. `pinned_at` stays
+    /// when the pin was written; `revised_at` records the update.
+    pub fn update_pin(ctx: Context<UpdatePin>, new_cid: String, new_data_hash: [u8; 32]) -> Result<()> {
+        use anchor_lang::system_program::{transfer, Transfer};
+        let clock = Clock::get()?;
+        let pin_record = &mut ctx.accounts.pin_record;
+        require!(pin_record.backend == StorageBackend::Ipfs, HealthcareError::InvalidStorageId);
+        require!(new_data_hash != pin_record.data_hash, HealthcareError::PinContentUnchanged);
+        let new_content_pin = &ctx.accounts.new_content_pin;
+        if new_content_pin.owner == &crate::ID && new_content_pin.key() != pin_record.key() {
+            msg!("Content already pinned at {}", new_content_pin.key());
+            return err!(HealthcareError::AlreadyPinned);
+        }
+        let (patient, pin) = (ctx.accounts.patient.key(), pin_record.key());
+        let seeds: &[&[u8]] = &[b"pin_content", patient.as_ref(), &new_data_hash, &[ctx.bumps.new_content_claim]];
+        let new_content_claim = &ctx.accounts.new_content_claim;
+        claim_pin_content(&ctx.accounts.patient, new_content_claim, &ctx.accounts.system_program, seeds, pin)?;
+        let replaced = PinVersion {
+            cid_version: pin_record.cid_version,
+            multicodec: pin_record.multicodec,
+            multihash: pin_record.multihash,
+            data_hash: pin_record.data_hash,
+            pinned_at: if pin_record.revision == 0 { pin_record.pinned_at } else { pin_record.revised_at },
+        };
+        pin_record.set_cid(&new_cid)?;
+        if pin_record.history.len() == MAX_PIN_VERSIONS {
+            pin_record.history.remove(0);
+        } else {
+            let info = pin_record.to_account_info();
+            let len = IpfsPinRecord::space_with(pin_record.history.len() + 1, pin_record.deals.len());
+            let rent = Rent::get()?;
+            let accounts = Transfer {
+                from: ctx.accounts.patient.to_account_info(),
+                to: info.clone(),
+            };
+            let cpi = CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts);
+            transfer(cpi, rent.minimum_balance(len).saturating_sub(rent.minimum_balance(info.data_len())))?;
+            info.realloc(len, false)?;
+        }
+        pin_record.history.push(replaced);
+        pin_record.revision =
+            pin_record.revision.checked_add(1).ok_or_else(|| error!(HealthcareError::CounterOverflow))?;
+        let old_hash = std::mem::replace(&mut pin_record.data_hash, new_data_hash);
+        pin_record.revised_at = clock.unix_timestamp;
+        pin_record.request_again(clock.unix_timestamp);
+        release_pin_content(&ctx.accounts.content_claim, pin, &ctx.accounts.patient)?;
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(PinUpdated {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            old_hash,
+            new_hash: new_data_hash,
+            ipfs_cid: pin_record.cid_string(),
+            version: pin_record.revision,
+            timestamp: clock.unix_timestamp,
+        });
+        msg!("Pin record {} updated to revision {}", pin_record.cid_string(), pin_record.revision);
+        Ok(())
+    }
+
+    /// Close a pin an oracle reported `Failed`, returning its rent to its
+    /// `rent_payer` and the bounty left unreleased to its patient, or the keys
+    /// they rotated to. Each oracle owed a share of the bounty from before the
+    /// pin failed is paid it first, passed writable in `remaining_accounts`. The
+    /// pin's patient, or the key it rotated to, abandons it at any time;
+    /// anyone else once the registry's `failed_pin_timeout_secs` have passed
+    /// since the failure, unless that is zero.
+    pub fn abandon_failed_pin<'info>(ctx: Context<'_, '_, '_, 'info, AbandonFailedPin<'info>>) -> Result<()> {
+        let pin_record = &mut ctx.accounts.pin_record;
+        let registry = &mut ctx.accounts.registry;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let cranker = ctx.accounts.cranker.key();
+        if cranker != ctx.accounts.patient.key() {
+            let timeout = registry.failed_pin_timeout_secs;
+            require!(
+                timeout > 0 && timestamp >= pin_record.settled_at.saturating_add(timeout),
+                HealthcareError::FailedPinNotAbandonable
+            );
+        }
+        pay_bounty_shares(pin_record, ctx.remaining_accounts)?;
+        let bounty_refunded = refund_unreleased_bounty(pin_record, &ctx.accounts.patient)?;
+        release_pin_content(&ctx.accounts.content_claim, pin_record.key(), &ctx.accounts.rent_payer)?;
+        ctx.accounts.patient_index.uncount_pin(pin_record.category)?;
+        registry.ipfs_pin_count =
+            registry.ipfs_pin_count.checked_sub(1).ok_or_else(|| error!(HealthcareError::CounterOverflow))?;
+        emit!(PinAbandoned {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            rent_payer: ctx.accounts.rent_payer.key(),
+            lamports: pin_record.to_account_info().lamports(),
+            cranker,
+            timestamp,
+            bounty_refunded,
+        });
+        msg!("Failed pin record {} abandoned", pin_record.cid_string());
+        Ok(())
+    }
+
+    /// Buy `days` more of a pin's storage at the registry's current
+    /// `price_lamports_per_day`, paid to its `storage_treasury` by whoever
+    /// signs. A pin still paid for is extended from its `paid_through`, a
+    /// lapsed one from now. An expired pin can't be renewed; pin it again.
+    pub fn renew_pin(ctx: Context<RenewPin>, days: u32) -> Result<()> {
+        require!(days > 0, HealthcareError::InvalidRenewalDays);
+        let registry = &ctx.accounts.registry;
+        require!(registry.price_lamports_per_day > 0, HealthcareError::PinStorageNotPriced);
+        let lamports = registry
+            .price_lamports_per_day
+            .checked_mul(days.into())
+            .ok_or(HealthcareError::InsufficientStoragePayment)?;
+        let paid = buy_pin_storage(
+            registry,
+            lamports,
+            &ctx.accounts.payer,
+            &Some(ctx.accounts.storage_treasury.clone()),
+            &ctx.accounts.system_program,
+        )?;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let pin_record = &mut ctx.accounts.pin_record;
+        pin_record.extend_paid_through(timestamp, paid)?;
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(PinRenewed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            payer: ctx.accounts.payer.key(),
+            days,
+            lamports,
+            paid_through: pin_record.paid_through,
+        });
+        msg!("Pin record {} paid through {}", pin_record.cid_string(), pin_record.paid_through);
+        Ok(())
+    }
+
+    /// Mark a pin `Expired` once `PIN_EXPIRY_GRACE_SECS` have passed since its
+    /// `paid_through`, telling pinning workers to release the content. Anyone
+    /// may crank this; the patient still closes the pin for its rent with
+    /// `unpin_medical_data`. A pin with no `paid_through` never expires.
+    pub fn expire_pin(ctx: Context<ExpirePin>) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let pin_record = &mut ctx.accounts.pin_record;
+        require!(
+            pin_record.paid_through != 0
+                && timestamp >= pin_record.paid_through.saturating_add(PIN_EXPIRY_GRACE_SECS),
+            HealthcareError::PinNotExpired
+        );
+        pin_record.settle_bounty(timestamp);
+        pin_record.status = PinStatus::Expired;
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(PinExpired {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            paid_through: pin_record.paid_through,
+            timestamp,
+            backend: pin_record.backend,
+        });
+        msg!("Pin record {} expired", pin_record.cid_string());
+        Ok(())
+    }
+
+    /// Pay an oracle what it is owed of a pin's bounty. The pin releases one of
+    /// its `bounty_periods` for each `bounty_period_secs` hosted while
+    /// confirmed, each split equally among the oracles holding it as the
+    /// period ends; see `IpfsPinRecord::settle_bounty`. An oracle that has
+    /// since dropped the pin, or been removed from the registry, still claims
+    /// what it earned while it held it. Fails with `NoPinBountyShare` for a
+    /// signer that neither holds the pin nor is owed any of it, and with
+    /// `PinBountyNotDue` while an oracle is owed nothing yet.
+    pub fn claim_pin_bounty(ctx: Context<ClaimPinBounty>, provider_id: String) -> Result<()> {
+        require!(provider_id.len() <= MAX_PIN_PROVIDER_ID_LEN, HealthcareError::PinProviderIdTooLong);
+        let pin_record = &mut ctx.accounts.pin_record;
+        let (oracle, now) = (ctx.accounts.oracle.key(), Clock::get()?.unix_timestamp);
+        pin_record.settle_bounty(now);
+        let owed = pin_record.bounty_shares.iter().any(|share| share.oracle == oracle);
+        require!(owed || pin_record.replicas.contains(&oracle), HealthcareError::NoPinBountyShare);
+        let periods = pin_record.bounty_periods_settled;
+        let share = pin_record.bounty_shares.iter_mut().find(|share| share.oracle == oracle && share.lamports > 0);
+        let lamports = share.map(std::mem::take).unwrap_or_default().lamports;
+        require!(lamports > 0, HealthcareError::PinBountyNotDue);
+        let info = pin_record.to_account_info();
+        **info.try_borrow_mut_lamports()? -= lamports;
+        **ctx.accounts.oracle.try_borrow_mut_lamports()? += lamports;
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(PinBountyClaimed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            oracle: ctx.accounts.oracle.key(),
+            provider_id,
+            lamports,
+            periods,
+            remaining: pin_record.bounty_lamports - pin_record.bounty_released,
+        });
+        msg!("Pin bounty of {} lamports claimed for {} periods hosted", lamports, periods);
+        Ok(())
+    }
+
+    /// Record, as a registered pinning oracle, the Filecoin deal `deal_id` in
+    /// which `provider` stores a pin's content until `deal_expiry`, at most
+    /// `MAX_STORAGE_DEALS` a pin. Recording a deal the pin holds again
+    /// replaces it, as when the deal is extended. The account grows to fit a
+    /// new deal and the oracle pays the rent it adds, which goes to the pin's
+    /// `rent_payer` with the rest on `unpin_medical_data`.
+    pub fn set_storage_deal(
+        ctx: Context<SetStorageDeal>,
+        deal_id: u64,
+        provider: String,
+        deal_expiry: i64,
+    ) -> Result<()> {
+        use anchor_lang::system_program::{transfer, Transfer};
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !provider.is_empty() && provider.len() <= MAX_DEAL_PROVIDER_LEN && deal_expiry > now,
+            HealthcareError::InvalidStorageDeal
+        );
+        let oracle = ctx.accounts.oracle.key();
+        let deal = DealInfo { deal_id, provider, deal_expiry, oracle, recorded_at: now };
+        let pin_record = &mut ctx.accounts.pin_record;
+        match pin_record.deals.iter().position(|recorded| recorded.deal_id == deal_id) {
+            Some(index) => pin_record.deals[index] = deal.clone(),
+            None => {
+                require!(pin_record.deals.len() < MAX_STORAGE_DEALS, HealthcareError::TooManyStorageDeals);
+                let info = pin_record.to_account_info();
+                let len = IpfsPinRecord::space_with(pin_record.history.len(), pin_record.deals.len() + 1);
+                let rent = Rent::get()?;
+                let accounts = Transfer {
+                    from: ctx.accounts.oracle.to_account_info(),
+                    to: info.clone(),
+                };
+                let cpi = CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts);
+                transfer(cpi, rent.minimum_balance(len).saturating_sub(rent.minimum_balance(info.data_len())))?;
+                info.realloc(len, false)?;
+                pin_record.deals.push(deal.clone());
+            }
+        }
+
+        let registry = &mut ctx.accounts.registry;
+        emit!(StorageDealRecorded {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            oracle,
+            deal_id,
+            provider: deal.provider,
+            deal_expiry,
+            deals: pin_record.deals.len() as u8,
+        });
+        msg!("Pin record {} stored in deal {} until {}", pin_record.cid_string(), deal_id, deal_expiry);
+        Ok(())
+    }
+
+    /// Flag the storage deal `deal_id` of a pin as ending within the
+    /// registry's `deal_expiry_warning_secs`, or ended, so renewal automation
+    /// extends or replaces it. Anyone may crank this. Fails with
+    /// `DealNotExpiringSoon` before the window opens.
+    pub fn deal_expiring_soon(ctx: Context<DealExpiringSoon>, deal_id: u64) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let pin_record = &ctx.accounts.pin_record;
+        let registry = &mut ctx.accounts.registry;
+        let deal = pin_record
+            .deals
+            .iter()
+            .find(|deal| deal.deal_id == deal_id)
+            .ok_or_else(|| error!(HealthcareError::StorageDealNotFound))?;
+        require!(
+            timestamp >= deal.deal_expiry.saturating_sub(registry.deal_expiry_warning_secs),
+            HealthcareError::DealNotExpiringSoon
+        );
+
+        emit!(StorageDealExpiring {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            pin_record: pin_record.key(),
+            patient: pin_record.patient,
+            deal_id,
+            provider: deal.provider.clone(),
+            deal_expiry: deal.deal_expiry,
+            timestamp,
+        });
+        msg!("Storage deal {} ends at {}", deal_id, deal.deal_expiry);
+        Ok(())
+    }
+
+    /// Point the signing patient's key at `new_key`, from which the new key may
+    /// claim the old one's records, access passes and pins. A key rotates away
+    /// once, and never to itself or to a key that has rotated away, so
+    /// rotations only form chains.
+    pub fn rotate_patient_key(ctx: Context<RotatePatientKey>, new_key: Pubkey) -> Result<()> {
+        let old_key = ctx.accounts.patient.key();
+        require!(
+            new_key != old_key && ctx.accounts.new_key_rotation.data_is_empty(),
+            HealthcareError::RotationCycle
+        );
+        let clock = Clock::get()?;
+        let rotation = &mut ctx.accounts.rotation;
+        rotation.old_key = old_key;
+        rotation.new_key = new_key;
+        rotation.rotated_at = clock.unix_timestamp;
+        rotation.bump = ctx.bumps.rotation;
+
+        emit!(PatientKeyRotated {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            old_key,
+            new_key,
+            rotated_at: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+        msg!("Patient key rotated to {}", new_key);
+        Ok(())
+    }
+
+    /// Move `record`, and any more records in `remaining_accounts`, from the
+    /// rotated key to the key it rotated to. Each is copied to
+    /// `derive_verification_pda` of the new key's next record nonce and its old
+    /// account closed to the new key, and both `PatientIndex`es are updated.
+    /// Records leave the old key's chain like closed ones and join the new
+    /// key's in the order they are claimed, so oldest first keeps their order.
+    pub fn claim_record<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRotatedRecord<'info>>) -> Result<()> {
+        let triples = ctx.remaining_accounts.chunks_exact(3);
+        require!(triples.remainder().is_empty(), HealthcareError::ClaimAccountMismatch);
+        let accounts = ctx.accounts;
+        let old_key = accounts.rotation.old_key;
+        let new_patient = accounts.new_patient.to_account_info();
+        let system_program = accounts.system_program.to_account_info();
+        let (record, new_record) = (accounts.record.to_account_info(), accounts.new_record.to_account_info());
+        let next_record = accounts.next_record.as_ref().map(|next_record| next_record.to_account_info());
+        let claims: Vec<_> = std::iter::once((&record, &new_record, next_record.as_ref()))
+            .chain(triples.map(|triple| (&triple[0], &triple[1], (triple[2].key() != crate::ID).then_some(&triple[2]))))
+            .collect();
+        for (i, (record, new_record, successor)) in claims.iter().enumerate() {
+            // A record given twice would otherwise be copied twice
+            require!(
+                claims[..i].iter().all(|earlier| earlier.0.key != record.key),
+                HealthcareError::ClaimAccountMismatch
+            );
+            claim_verification_record(
+                record,
+                new_record,
+                *successor,
+                &old_key,
+                &new_patient,
+                &system_program,
+                &mut accounts.old_index,
+                &mut accounts.new_index,
+                &mut accounts.registry,
+            )?;
+        }
+        // Closed like Anchor's `close`, so the typed `record` skips its write on
+        // exit, and only after the last `create_account` so no transfer is seen
+        // across a CPI
+        for (record, _, _) in &claims {
+            **new_patient.try_borrow_mut_lamports()? += record.lamports();
+            **record.try_borrow_mut_lamports()? = 0;
+            record.assign(&System::id());
+            record.realloc(0, false)?;
+        }
+        accounts.new_index.bump = ctx.bumps.new_index;
+        msg!("Claimed {} records from {}", claims.len(), old_key);
+        Ok(())
+    }
+
+    /// Move an `AccessPass` of the rotated key to the new key's address for the
+    /// same resource, with its remaining uses and expiry
+    pub fn claim_access_pass(ctx: Context<ClaimAccessPass>) -> Result<()> {
+        let new_access_pass = &mut ctx.accounts.new_access_pass;
+        new_access_pass.set_inner(AccessPass {
+            patient: ctx.accounts.new_patient.key(),
+            bump: ctx.bumps.new_access_pass,
+            ..(*ctx.accounts.access_pass).clone()
+        });
+        msg!("Access pass to {} claimed", new_access_pass.resource);
+        Ok(())
+    }
+
+    /// Hand an `IpfsPinRecord` of the rotated key to the new key, whose
+    /// `PatientIndex` counts the pin in place of the old key's, along with
+    /// its content claim. Fails with `AlreadyPinned` if the new key has
+    /// pinned the content itself.
+    pub fn claim_pin_record(ctx: Context<ClaimPinRecord>) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let new_patient = accounts.new_patient.key();
+        let data_hash = accounts.pin_record.data_hash;
+        let seeds: &[&[u8]] = &[b"pin_content", new_patient.as_ref(), &data_hash, &[ctx.bumps.new_content_claim]];
+        let pin = accounts.pin_record.key();
+        claim_pin_content(&accounts.new_patient, &accounts.new_content_claim, &accounts.system_program, seeds, pin)?;
+        release_pin_content(&accounts.content_claim, pin, &accounts.new_patient)?;
+        let category = ctx.accounts.pin_record.category;
+        ctx.accounts.old_patient_index.uncount_pin(category)?;
+        let new_patient_index = &mut ctx.accounts.new_patient_index;
+        new_patient_index.bump = ctx.bumps.new_patient_index;
+        new_patient_index.count_pin(category)?;
+        ctx.accounts.pin_record.patient = new_patient;
+        msg!("Pin record {} claimed", ctx.accounts.pin_record.cid_string());
+        Ok(())
+    }
+
+    /// Let `guardian` act for `ward` within `scopes`, a set of
+    /// `GuardianScope::bit`s, until `expires_at` (zero for no expiry). The
+    /// registry authority may grant consent, or a registered, active provider
+    /// signing as `granter` with its `provider_registration`. Granting again
+    /// replaces the consent, including a revoked one.
+    pub fn grant_guardian_consent(
+        ctx: Context<GrantGuardianConsent>,
+        guardian: Pubkey,
+        ward: Pubkey,
+        scopes: u8,
+        expires_at: i64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let granter = ctx.accounts.granter.key();
+        let is_provider = ctx.accounts.provider_registration.as_ref().is_some_and(|registration| {
+            registration.registry == registry.key() && registration.provider == granter && registration.active
+        });
+        require!(
+            granter == registry.authority || is_provider,
+            HealthcareError::NotAuthorizedToGrantConsent
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let known_scopes = scopes != 0 && scopes & !GuardianScope::ALL == 0;
+        require!(
+            known_scopes && (expires_at == 0 || expires_at > now) && guardian != ward,
+            HealthcareError::InvalidGuardianConsent
+        );
+
+        let consent = &mut ctx.accounts.consent;
+        consent.set_inner(GuardianConsent {
+            registry: registry.key(),
+            guardian,
+            ward,
+            scopes,
+            expires_at,
+            granted_by: granter,
+            granted_at: now,
+            revoked_at: 0,
+            bump: ctx.bumps.consent,
+        });
+        emit!(GuardianConsentGranted {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            consent: consent.key(),
+            guardian,
+            ward,
+            scopes,
+            expires_at,
+            granted_by: granter,
+        });
+        msg!("Guardian consent granted with scopes {:#05b}", scopes);
+        Ok(())
+    }
+
+    /// Revoke a `GuardianConsent`, which blocks the guardian from then on and
+    /// leaves what it already did in place. The registry authority, whoever
+    /// granted it, the guardian or the ward may sign.
+    pub fn revoke_guardian_consent(ctx: Context<RevokeGuardianConsent>) -> Result<()> {
+        let revoked_by = ctx.accounts.revoker.key();
+        let consent = &mut ctx.accounts.consent;
+        require!(
+            [ctx.accounts.registry.authority, consent.granted_by, consent.guardian, consent.ward].contains(&revoked_by),
+            HealthcareError::NotAuthorizedToRevokeConsent
+        );
+        require!(consent.revoked_at == 0, HealthcareError::GuardianConsentRevoked);
+        consent.revoked_at = Clock::get()?.unix_timestamp;
+
+        emit!(GuardianConsentRevoked {
+            seq: ctx.accounts.registry.next_event_seq()?,
+            registry: ctx.accounts.registry.key(),
+            consent: consent.key(),
+            guardian: consent.guardian,
+            ward: consent.ward,
+            revoked_by,
+        });
+        msg!("Guardian consent revoked");
+        Ok(())
+    }
+
+    /// `verify_eligibility` signed and paid for by a guardian with the `Verify`
+    /// scope of a `GuardianConsent`. The record is the ward's: it sits at the
+    /// ward's next `derive_verification_pda`, a patient-bound circuit must bind
+    /// the ward, and only the guardian gets the rent back when it is closed.
+    /// Circuits that require a provider aren't served. `previous_record` and
+    /// `force_new` work as for `verify_eligibility`.
+    pub fn verify_eligibility_for_ward(
+        ctx: Context<VerifyEligibilityForWard>,
+        proof: Vec<u8>,
+        proof_format: ProofFormat,
+        public_inputs: Vec<u8>,
+        ipfs_hash: String,
+        circuit_id: String,
+        record_nonce: u64,
+        hash_algo: HashAlgo,
+        force_new: bool,
+    ) -> Result<VerificationResult> {
+        check_ipfs_cid(&ipfs_hash)?;
+        let clock = Clock::get()?;
+        let consent = &ctx.accounts.consent;
+        consent.check(GuardianScope::Verify, clock.unix_timestamp)?;
+        let (ward, guardian) = (consent.ward, ctx.accounts.guardian.key());
+        let registry = &mut ctx.accounts.registry;
+        let verification = &mut ctx.accounts.verification;
+        let nullifier = &mut ctx.accounts.nullifier;
+        ctx.accounts.verifying_key.load_mut()?.last_used_at = clock.unix_timestamp;
+        let vk_data = ctx.accounts.verifying_key.as_ref().try_borrow_data()?;
+        let verifying_key = VerifyingKey::new(&vk_data)?;
+        require!(!verifying_key.needs_provider(), HealthcareError::ProviderRequired);
+        registry.check_public_inputs_len(&public_inputs)?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        require!(
+            record_nonce == patient_index.next_record_nonce,
+            HealthcareError::RecordNonceMismatch
+        );
+        patient_index.bump = ctx.bumps.patient_index;
+        require!(
+            nullifier.verification == Pubkey::default(),
+            HealthcareError::ProofAlreadyUsed
+        );
+        let verified = verify_eligibility_proof(
+            &verifying_key,
+            registry,
+            Some(&ward),
+            &proof,
+            proof_format,
+            &public_inputs,
+            hash_algo,
+        )?;
+        spend_nonce(&verifying_key, &verified.inputs, patient_index)?;
+        retire_previous_record::<VerificationRecord>(
+            &ctx.accounts.previous_record,
+            VerificationType::Eligibility,
+            force_new,
+            &clock,
+            patient_index,
+            registry,
+        )?;
+        charge_circuit_fee(
+            &verifying_key,
+            1,
+            &ctx.accounts.guardian,
+            &ctx.accounts.fee_recipient,
+            &ctx.accounts.system_program,
+        )?;
+        verification.set_inner(VerificationRecord::eligibility(
+            ward,
+            &registry.key(),
+            &verified,
+            ipfs_hash.clone(),
+            &clock,
+            circuit_id,
+            &verifying_key,
+            registry.record_expiry(&verifying_key, clock.unix_timestamp),
+            guardian,
+        ));
+        verification.bump = ctx.bumps.verification;
+        let key = verification.key();
+        verification.transition(key, RecordStatus::Verified, registry)?;
+        verification.previous_record = patient_index.link_record(key);
+        let cooldown_secs = registry.cooldown_secs;
+        patient_index.record_verification(key, VerificationType::Eligibility, clock.unix_timestamp, cooldown_secs)?;
+        patient_index.next_record_nonce += 1;
+
+        nullifier.verification = key;
+        nullifier.used_at = verification.timestamp;
+        nullifier.bump = ctx.bumps.nullifier;
+
+        registry.count_verifications(VerificationType::Eligibility, 1)?;
+        registry.ipfs_pin_count += 1;
+        verification.event_seq = registry.next_event_seq()?;
+
+        let event = EligibilityVerified {
+            seq: verification.event_seq,
+            registry: registry.key(),
+            patient: ward,
+            ipfs_hash,
+            timestamp: verification.timestamp,
+            slot: verification.slot,
+            verification_id: verification.verification_id,
+            provider_attested: false,
+            guardian: Some(guardian),
+        };
+        emit!(event);
+        if registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
+        msg!("Eligibility verified for ward by guardian {}", guardian);
+        Ok(VerificationResult {
+            verified: true,
+            proof_hash: verification.proof_hash,
+            record: key,
+            verification_id: verification.verification_id,
+        })
+    }
+
+    /// `pin_medical_data` for a ward, signed by a guardian with the `Pin` scope
+    /// of a `GuardianConsent`, who pays for the storage and the bounty
+    pub fn pin_medical_data_for_ward(
+        ctx: Context<PinMedicalDataForWard>,
+        ipfs_cid: String,
+        data_hash: [u8; 32],
+        storage_lamports: u64,
+        pin_bounty_lamports: u64,
+        category: DataCategory,
+        tag: Option<[u8; 8]>,
+    ) -> Result<()> {
+        let accounts = &ctx.accounts;
+        accounts.consent.check(GuardianScope::Pin, Clock::get()?.unix_timestamp)?;
+        let (ward, guardian) = (accounts.consent.ward, accounts.guardian.key());
+        let days = buy_pin_storage(
+            &accounts.registry,
+            storage_lamports,
+            &accounts.guardian,
+            &accounts.storage_treasury,
+            &accounts.system_program,
+        )?;
+        let info = accounts.pin_record.to_account_info();
+        let seeds: &[&[u8]] = &[b"pin", ward.as_ref(), &data_hash, &[ctx.bumps.pin_record]];
+        let mut pin_record = create_pin_record(&accounts.guardian, &info, &accounts.system_program, seeds)?;
+        let seeds: &[&[u8]] = &[b"pin_content", ward.as_ref(), &data_hash, &[ctx.bumps.content_claim]];
+        claim_pin_content(&accounts.guardian, &accounts.content_claim, &accounts.system_program, seeds, info.key())?;
+        escrow_pin_bounty(&accounts.guardian, &info, &accounts.system_program, pin_bounty_lamports)?;
+        let pin = NewPin {
+            backend: StorageBackend::Ipfs,
+            storage_id: ipfs_cid.as_bytes(),
+            data_hash,
+            paid_days: days,
+            bounty_lamports: pin_bounty_lamports,
+            category,
+            tag,
+        };
+        let registry = &mut ctx.accounts.registry;
+        let event = pin_data(&mut pin_record, &info, registry, ward, Some(guardian), pin)?;
+        let patient_index = &mut ctx.accounts.patient_index;
+        patient_index.bump = ctx.bumps.patient_index;
+        patient_index.count_pin(category)?;
+        emit!(event);
+        if ctx.accounts.registry.cpi_events_enabled {
+            emit_cpi!(event);
+        }
+        Ok(())
+    }
+
+    pub fn submit_model_update(
+        ctx: Context<SubmitModelUpdate>,
+        encrypted_gradient: Vec<u8>,
+        round_number: u64,
+    ) -> Result<()> {
+        let fl_state = &mut ctx.accounts.fl_state;
+        
+        require!(
+            encrypted_gradient.len() <= 4096,
+            HealthcareError::GradientTooLarge
+        );
+
+        fl_state.round_number = round_number;
+        let clock = Clock::get()?;
+        fl_state.last_update = clock.unix_timestamp;
+        fl_state.participant_count += 1;
+        fl_state.slot = clock.slot;
+
+        msg!("Federated learning update submitted for round {}", round_number);
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+#[derive(InitSpace)]
+pub struct HealthcareRegistry {
+    pub authority: Pubkey,
+    pub nist_compliant: bool,
+    pub total_verifications: u64,
+    pub ipfs_pin_count: u64,
+    /// Minimum time between proposing and activating a verifying key update
+    pub vk_update_delay_secs: i64,
+    /// Domain separator for this deployment, see `registry_domain`. Clients read
+    /// it from here when building witnesses for domain-bound circuits.
+    pub domain: [u8; 32],
+    /// Number of circuits with a verifying key PDA under `[b"vk", circuit_id]`
+    pub registered_circuits: u16,
+    /// Verifying key approved for each `VerificationType`, indexed by variant
+    pub circuit_for_type: [Option<Pubkey>; VERIFICATION_TYPE_COUNT],
+    /// Most a circuit may set as its `fee_lamports`, see `set_max_circuit_fee`
+    pub max_circuit_fee: u64,
+    /// Most 32-byte public inputs a submission may carry, whatever its circuit
+    /// expects; see `update_config`
+    pub max_public_inputs: u8,
+    /// Slots a `VerificationCache` entry is served for after it is written
+    pub cache_ttl_slots: u64,
+    /// Slots a `VerificationCommitment` may be revealed in; zero means
+    /// `verify_eligibility` takes submissions without one
+    pub commit_reveal_slots: u64,
+    /// What circuits and submissions the registry accepts, see `set_security_policy`
+    pub security_policy: SecurityPolicy,
+    /// Seconds a record stays active when its circuit sets no `validity_secs`;
+    /// zero means records never expire. See `set_default_validity`.
+    pub default_validity_secs: i64,
+    /// Lamports of a closed record's rent paid to whoever cranks
+    /// `close_expired_verification`, see `set_gc_bounty`
+    pub gc_bounty_lamports: u64,
+    /// Records closed by `close_expired_verification`; `total_verifications`
+    /// still counts them
+    pub closed_verifications: u64,
+    /// Longest `VerificationRecord::metadata` that `update_metadata` accepts, see
+    /// `set_max_metadata_len`
+    pub max_metadata_len: u16,
+    /// Seconds after a record's `timestamp` that `dispute_verification` may
+    /// contest it, see `set_dispute_window`
+    pub dispute_window_secs: i64,
+    /// What the dispute window, the expiry grace period and proof freshness are
+    /// measured against, see `set_time_source`
+    pub time_source: TimeSource,
+    /// Seconds a patient waits between verifications of one type; zero means
+    /// no wait. See `set_verification_cooldown`.
+    pub cooldown_secs: i64,
+    /// `total_verifications` split by `VerificationType`, in declaration order
+    pub verifications_by_type: [u64; VERIFICATION_TYPE_COUNT],
+    /// Records revoked by `revoke_verification`, an upheld dispute or a sweep of
+    /// a revoked circuit
+    pub revoked_count: u64,
+    /// Records marked expired by `mark_expired` or replaced with `force_new`
+    pub expired_count: u64,
+    /// Proofs reported through `record_failed_proof` that fail their circuit
+    pub failed_proof_count: u64,
+    /// Claims filed with `submit_claim`, which also numbers the next one
+    pub claim_count: u64,
+    /// Events emitted under the registry so far, which also numbers the next
+    /// one; see `next_event_seq`
+    pub event_seq: u64,
+    /// `end_seq` of the latest `Checkpoint`, where the next one must start
+    pub checkpointed_seq: u64,
+    /// Whether `EligibilityVerified`, `VerificationRevoked` and `DataPinned` are
+    /// emitted a second time through a self-CPI, see `set_cpi_events`
+    pub cpi_events_enabled: bool,
+    /// Whether `record_access` emits `AccessDenied` before refusing an access
+    /// without a grant, see `set_denied_access_events`
+    pub denied_access_events_enabled: bool,
+    /// Keys that report whether the IPFS cluster pinned a pin's content, see
+    /// `confirm_pin`
+    #[max_len(MAX_PINNING_ORACLES)]
+    pub pinning_oracles: Vec<Pubkey>,
+    /// Seconds after an oracle reports a pin `Failed` that anyone may abandon
+    /// it; zero leaves it to the patient. See `set_failed_pin_timeout`.
+    pub failed_pin_timeout_secs: i64,
+    /// Lamports a day of pin storage costs; zero pins for free and without
+    /// expiry. See `set_pin_storage_price`.
+    pub price_lamports_per_day: u64,
+    /// Account paid for pin storage
+    pub storage_treasury: Pubkey,
+    /// How long a pinner hosts a pin for each share of its bounty, see
+    /// `set_pin_bounty_terms`
+    pub pin_bounty_period_secs: i64,
+    /// Shares a pin's bounty is released in, one per period hosted
+    pub pin_bounty_periods: u16,
+    /// Pinning oracles that must report a pin's content pinned before it is
+    /// `Confirmed`, see `set_pin_replication_target`
+    pub pin_replication_target: u8,
+    /// How long before a pin's storage deal ends `deal_expiring_soon` may
+    /// flag it, see `set_deal_expiry_warning`
+    pub deal_expiry_warning_secs: i64,
+    /// Most slots an `AccessPass` may be issued for, see
+    /// `set_max_access_pass_slots`
+    pub max_access_pass_slots: u64,
+}
+
+impl HealthcareRegistry {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// Count `count` new records of `verification_type`
+    fn count_verifications(&mut self, verification_type: VerificationType, count: u64) -> Result<()> {
+        self.total_verifications = checked_count(self.total_verifications, count)?;
+        let by_type = &mut self.verifications_by_type[verification_type as usize];
+        *by_type = checked_count(*by_type, count)?;
+        Ok(())
+    }
+
+    /// The `seq` of an event about to be emitted under the registry. Every event
+    /// carries one, so an indexer can tell when it missed one and a `Checkpoint`
+    /// can commit to a contiguous run.
+    fn next_event_seq(&mut self) -> Result<u64> {
+        let event_seq = self.event_seq;
+        self.event_seq = checked_count(event_seq, 1)?;
+        Ok(event_seq)
+    }
+
+    /// `VerificationRecord::expires_at` of a record written at `now` against
+    /// `verifying_key`: its circuit's validity period, else the registry default
+    pub fn record_expiry(&self, verifying_key: &VerifyingKeyPDA, now: i64) -> i64 {
+        let validity_secs = match verifying_key.validity_secs {
+            0 => self.default_validity_secs,
+            validity_secs => validity_secs,
+        };
+        match validity_secs {
+            0 => 0,
+            validity_secs => now.saturating_add(validity_secs),
+        }
+    }
+
+    /// Whether `verifying_key` is the circuit approved for `verification_type`
+    pub fn is_circuit_for(&self, verification_type: VerificationType, verifying_key: &Pubkey) -> bool {
+        self.circuit_for_type[verification_type as usize] == Some(*verifying_key)
+    }
+
+    /// Bound the public inputs of a submission before anything hashes or parses them
+    pub fn check_public_inputs_len(&self, public_inputs: &[u8]) -> Result<()> {
+        require!(!public_inputs.is_empty(), HealthcareError::EmptyPublicInputs);
+        require!(
+            public_inputs.len() <= self.max_public_inputs as usize * 32,
+            HealthcareError::TooManyPublicInputs
+        );
+        Ok(())
+    }
+
+    /// A NIST-compliant registry only records proof hashes from a NIST-standardised
+    /// hash function
+    pub fn check_hash_algo(&self, hash_algo: HashAlgo) -> Result<()> {
+        require!(
+            !self.nist_compliant || hash_algo.is_nist_compliant(),
+            HealthcareError::HashAlgoNotCompliant
+        );
+        require!(
+            self.security_policy.allows_hash_algo(hash_algo),
+            HealthcareError::HashAlgoNotAllowed
+        );
+        Ok(())
+    }
+}
+
+/// The schemes, curves and hash algorithms a registry accepts, whatever its
+/// circuits were registered with. Each set is a bitmask of the variants' `bit()`.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecurityPolicy {
+    pub allowed_schemes: u8,
+    pub allowed_curves: u8,
+    pub allowed_hash_algos: u8,
+    /// When non-zero, circuits must check proof freshness with a window of at most
+    /// this many seconds
+    pub max_freshness_window_secs: i64,
+}
+
+impl SecurityPolicy {
+    /// What a new registry starts with: anything the program supports, except
+    /// that a NIST-compliant registry only hashes records with NIST algorithms
+    pub fn default_for(nist_compliant: bool) -> Self {
+        SecurityPolicy {
+            allowed_schemes: u8::MAX,
+            allowed_curves: u8::MAX,
+            allowed_hash_algos: if nist_compliant { HashAlgo::Keccak.bit() } else { u8::MAX },
+            max_freshness_window_secs: 0,
+        }
+    }
+
+    /// Allows at least one of each, with a non-negative freshness bound
+    pub fn is_valid(&self) -> bool {
+        self.allowed_schemes != 0
+            && self.allowed_curves != 0
+            && self.allowed_hash_algos != 0
+            && self.max_freshness_window_secs >= 0
+    }
+
+    /// Whether every hash algorithm allowed is NIST-approved. No scheme or curve
+    /// is standardised either way, so only the hash is constrained.
+    pub fn is_nist_compliant(&self) -> bool {
+        [HashAlgo::Keccak, HashAlgo::Poseidon]
+            .into_iter()
+            .all(|hash_algo| hash_algo.is_nist_compliant() || !self.allows_hash_algo(hash_algo))
+    }
+
+    pub fn allows_hash_algo(&self, hash_algo: HashAlgo) -> bool {
+        self.allowed_hash_algos & hash_algo.bit() != 0
+    }
+
+    /// Whether a circuit with this scheme, curve and freshness window is allowed
+    pub fn allows_circuit(&self, scheme: ProvingScheme, curve: CurveId, freshness_window: Option<i64>) -> bool {
+        let fresh_enough = match (self.max_freshness_window_secs, freshness_window) {
+            (0, _) => true,
+            (max, Some(window)) => window <= max,
+            (_, None) => false,
+        };
+        self.allowed_schemes & scheme.bit() != 0 && self.allowed_curves & curve.bit() != 0 && fresh_enough
+    }
+
+    /// `allows_circuit` for a circuit about to be registered
+    pub fn allows_config(&self, config: &VkConfig) -> bool {
+        self.allows_circuit(config.scheme, config.curve, config.freshness_window_secs)
+    }
+
+    /// `allows_circuit` for a registered circuit
+    pub fn allows_key(&self, verifying_key: &VerifyingKeyPDA) -> bool {
+        self.allows_circuit(verifying_key.scheme(), verifying_key.curve(), verifying_key.freshness_window())
+    }
+}
+
+/// Header of a verifying key account. The key itself follows the header in the
+/// same account and is read in place through `VerifyingKey`; see
+/// `VerifyingKeyPDA::space` for the layout.
+#[account(zero_copy)]
+pub struct VerifyingKeyPDA {
+    /// Authority that can update the VK
+    pub authority: Pubkey,
+    /// Domain of the registry this key was registered under
+    pub domain: [u8; 32],
+    /// keccak of the key bytes: declared at registration, checked by `finalize_vk`,
+    /// and replaced by each activated update
+    pub vk_hash: [u8; 32],
+    /// Circuit identifier (e.g., "eligibility_v1"), zero-padded; see `circuit_id()`
+    pub circuit_id: [u8; MAX_CIRCUIT_ID_LEN],
+    /// Account paid `fee_lamports` for each proof recorded against this key
+    pub fee_recipient: Pubkey,
+    /// Last update timestamp
+    pub updated_at: i64,
+    /// When non-zero, the last public input is a unix timestamp no older than this
+    pub freshness_window_secs: i64,
+    /// Time of the last proof recorded against this key, zero if none was;
+    /// `close_verifying_key` waits `VK_CLOSE_COOLDOWN_SECS` past it
+    pub last_used_at: i64,
+    /// When non-zero, records of this circuit expire this long after they are
+    /// written, in place of the registry's `default_validity_secs`
+    pub validity_secs: i64,
+    /// Lamports the patient pays `fee_recipient` per recorded proof, zero if free
+    pub fee_lamports: u64,
+    /// Length of the key bytes
+    pub total_len: u32,
+    /// Length of the written prefix of the key bytes, where an interrupted
+    /// upload resumes; see `upload_status`
+    pub written: u32,
+    /// Starts at 1 and counts activated key updates
+    pub version: u16,
+    /// Proofs are refused while `version` is below this, e.g. until a fixed key lands
+    pub min_accepted_version: u16,
+    pub circuit_id_len: u8,
+    /// Number of 32-byte public inputs every proof for this circuit must carry
+    pub n_public: u8,
+    /// Verification refuses to use the key until this is set by `finalize_vk`
+    pub finalized: u8,
+    /// Set by `prepare_vk` once the prepared region holds the syscall encoding, and
+    /// cleared whenever the key bytes change
+    pub prepared: u8,
+    pub bump: u8,
+    /// Public input 0 must equal `patient_binding(patient)` for the signing patient
+    pub binds_patient: u8,
+    /// Public input `domain_input` must equal `domain`
+    pub binds_domain: u8,
+    pub domain_input: u8,
+    /// `CircuitStatus`; only `Active` circuits accept new proofs
+    pub status: u8,
+    /// `CurveId` the key and its proofs are over
+    pub curve: u8,
+    /// `ProvingScheme` of the circuit, which picks the verifier
+    pub scheme: u8,
+    /// Public input `nullifier_input` is a credential nullifier, which
+    /// `verify_eligibility_anonymous` records in place of the patient
+    pub binds_nullifier: u8,
+    pub nullifier_input: u8,
+    /// Public input `nonce_input` is the patient's `PatientIndex` verification
+    /// nonce, spent by each proof recorded
+    pub uses_nonce: u8,
+    pub nonce_input: u8,
+    /// Eligibility proofs need a registered provider's co-signature, which only
+    /// `verify_eligibility` takes
+    pub requires_provider: u8,
+    pub reserved: [u8; 4],
+}
+
+impl VerifyingKeyPDA {
+    /// Discriminator and header, ahead of the key region
+    pub const HEADER_LEN: usize = 8 + std::mem::size_of::<VerifyingKeyPDA>();
+
+    /// Account size for a key of `total_len` bytes: the header, then the key bytes,
+    /// the prepared encoding and the write mask
+    pub fn space(total_len: u32) -> usize {
+        Self::HEADER_LEN + total_len as usize + Self::prepared_len(total_len) + Self::mask_len(total_len)
+    }
+
+    /// What `register_verifying_key` allocates. Larger keys are grown to `space`
+    /// with `resize_vk_account`, at most `MAX_PERMITTED_DATA_INCREASE` per call.
+    pub fn initial_space(total_len: u32) -> usize {
+        Self::space(total_len).min(MAX_PERMITTED_DATA_INCREASE)
+    }
+
+    pub fn circuit_id(&self) -> &str {
+        let len = (self.circuit_id_len as usize).min(MAX_CIRCUIT_ID_LEN);
+        std::str::from_utf8(&self.circuit_id[..len]).unwrap_or_default()
+    }
+
+    pub fn set_circuit_id(&mut self, circuit_id: &str) {
+        self.circuit_id = [0; MAX_CIRCUIT_ID_LEN];
+        self.circuit_id[..circuit_id.len()].copy_from_slice(circuit_id.as_bytes());
+        self.circuit_id_len = circuit_id.len() as u8;
+    }
+
+    pub fn is_finalized(&self) -> bool {
+        self.finalized != 0
+    }
+
+    pub fn upload_status(&self) -> UploadStatus {
+        if self.is_finalized() {
+            UploadStatus::Finalized
+        } else {
+            UploadStatus::InProgress { written: self.written }
+        }
+    }
+
+    pub fn needs_provider(&self) -> bool {
+        self.requires_provider != 0
+    }
+
+    pub fn freshness_window(&self) -> Option<i64> {
+        (self.freshness_window_secs > 0).then_some(self.freshness_window_secs)
+    }
+
+    pub fn status(&self) -> CircuitStatus {
+        decode_variant(self.status)
+    }
+
+    pub fn curve(&self) -> CurveId {
+        decode_variant(self.curve)
+    }
+
+    pub fn scheme(&self) -> ProvingScheme {
+        decode_variant(self.scheme)
+    }
+
+    /// `UnsupportedVerifier` for a key this build has no verifier for, which
+    /// registration only lets through from a build with other features
+    pub fn verifier(&self) -> &'static dyn ProofVerifier {
+        self.scheme().verifier_on(self.curve()).unwrap_or(&UnsupportedVerifier)
+    }
+
+    /// Size every key for this circuit must have: rotations keep the scheme and
+    /// the shape of the statement
+    pub fn expected_len(&self) -> u32 {
+        self.verifier().vk_len(self.n_public)
+    }
+
+    /// Whether `verify_eligibility_anonymous` can use this key: it declares a
+    /// credential nullifier and doesn't bind the patient
+    pub fn supports_anonymous(&self) -> bool {
+        self.binds_nullifier != 0 && self.binds_patient == 0 && self.uses_nonce == 0
+    }
+
+    /// Whether new proofs may be verified against this key
+    pub fn is_accepting(&self) -> bool {
+        self.status() == CircuitStatus::Active && self.version >= self.min_accepted_version
+    }
+
+    /// Exact size of an uncompressed arkworks Groth16 verifying key with `n_public` inputs
+    pub fn expected_vk_len(n_public: u8) -> u32 {
+        (VK_IC_OFFSET + 8 + (n_public as usize + 1) * G1_LEN) as u32
+    }
+
+    /// Reject proofs generated for someone other than `patient` on circuits that
+    /// opted into patient binding
+    pub fn check_patient_binding(
+        &self,
+        public_inputs: &[u8],
+        format: ProofFormat,
+        patient: &Pubkey,
+    ) -> Result<()> {
+        if self.binds_patient == 0 {
+            return Ok(());
+        }
+        let bound = public_inputs.get(..32).map(|input| scalar_to_be(input, format));
+        require!(
+            bound == Some(patient_binding(patient)),
+            HealthcareError::PublicInputPatientMismatch
+        );
+        Ok(())
+    }
+
+    /// The credential nullifier in the `nullifier_input` slot, big-endian, or
+    /// `None` if the circuit declares none or the inputs are too short
+    pub fn credential_nullifier(&self, public_inputs: &[u8], format: ProofFormat) -> Option<[u8; 32]> {
+        if self.binds_nullifier == 0 {
+            return None;
+        }
+        let start = self.nullifier_input as usize * 32;
+        public_inputs.get(start..start + 32).map(|input| scalar_to_be(input, format))
+    }
+
+    /// The nonce in the `nonce_input` slot of parsed `inputs`, or `None` if the
+    /// circuit uses no nonce or the inputs are too short
+    pub fn bound_nonce(&self, inputs: &[[u8; 32]]) -> Option<[u8; 32]> {
+        if self.uses_nonce == 0 {
+            return None;
+        }
+        inputs.get(self.nonce_input as usize).copied()
+    }
+
+    /// Reject proofs generated for another deployment on circuits that reserve a
+    /// public input for the registry domain
+    pub fn check_domain_binding(
+        &self,
+        public_inputs: &[u8],
+        format: ProofFormat,
+        registry: &HealthcareRegistry,
+    ) -> Result<()> {
+        if self.binds_domain == 0 {
+            return Ok(());
+        }
+        let start = self.domain_input as usize * 32;
+        let bound = public_inputs
+            .get(start..start + 32)
+            .map(|input| scalar_to_be(input, format));
+        require!(
+            self.domain == registry.domain && bound == Some(self.domain),
+            HealthcareError::PublicInputDomainMismatch
+        );
+        Ok(())
+    }
+
+    /// Reject proofs whose timestamp input is older than the freshness window or
+    /// further ahead of the clock than `MAX_CLOCK_SKEW_SECS`. Under
+    /// `TimeSource::Slot` the input is a slot and both spans are counted in slots.
+    pub fn check_freshness(
+        &self,
+        public_inputs: &[u8],
+        format: ProofFormat,
+        time: TimeSource,
+        clock: &Clock,
+    ) -> Result<()> {
+        let Some(window) = self.freshness_window() else {
+            return Ok(());
+        };
+        let (now, window) = (time.now(clock), time.span(window));
+        let start = (self.n_public as usize).saturating_sub(1) * 32;
+        let timestamp = public_inputs
+            .get(start..start + 32)
+            .map(|input| scalar_to_be(input, format))
+            .ok_or(HealthcareError::PublicInputCountMismatch)?;
+        let (high, low) = timestamp.split_at(24);
+        let mut seconds = [0u8; 8];
+        seconds.copy_from_slice(low);
+        let timestamp = i64::try_from(u64::from_be_bytes(seconds))
+            .ok()
+            .filter(|_| high.iter().all(|byte| *byte == 0));
+
+        match timestamp {
+            Some(timestamp) if timestamp <= now.saturating_add(time.span(MAX_CLOCK_SKEW_SECS)) => {
+                if now.saturating_sub(timestamp) > window {
+                    msg!("Proof timestamp {} is older than {} by {:?}", timestamp, window, time);
+                    return Err(HealthcareError::ProofStale.into());
+                }
+                Ok(())
+            }
+            _ => Err(HealthcareError::ProofTimestampInFuture.into()),
+        }
+    }
+
+    pub fn check_input_count(&self, public_inputs: &[u8]) -> Result<()> {
+        Ok(verifier_core::check_input_count(self.n_public as usize, public_inputs)?)
+    }
+
+    /// The prepared encoding drops the 8-byte gamma_abc length prefix
+    pub fn prepared_len(total_len: u32) -> usize {
+        (total_len as usize).saturating_sub(8)
+    }
+
+    pub fn mask_len(total_len: u32) -> usize {
+        (total_len as usize).div_ceil(8)
+    }
+
+    pub fn is_valid_circuit_id(circuit_id: &str) -> bool {
+        !circuit_id.is_empty() && circuit_id.len() <= MAX_CIRCUIT_ID_LEN
+    }
+}
+
+/// Enum fields of zero-copy headers are stored as their borsh variant index
+fn decode_variant<T: AnchorDeserialize + Default>(index: u8) -> T {
+    T::try_from_slice(&[index]).unwrap_or_default()
+}
+
+/// Byte ranges of the key bytes, prepared encoding and write mask within an account
+fn vk_regions(total_len: u32) -> [std::ops::Range<usize>; 3] {
+    let key_end = VerifyingKeyPDA::HEADER_LEN + total_len as usize;
+    let prepared_end = key_end + VerifyingKeyPDA::prepared_len(total_len);
+    [
+        VerifyingKeyPDA::HEADER_LEN..key_end,
+        key_end..prepared_end,
+        prepared_end..prepared_end + VerifyingKeyPDA::mask_len(total_len),
+    ]
+}
+
+/// Check that `data` is a verifying key account grown far enough to hold its key
+fn check_vk_account(data: &[u8]) -> Result<()> {
+    require!(
+        data.len() >= VerifyingKeyPDA::HEADER_LEN && data[..8] == VerifyingKeyPDA::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let header: &VerifyingKeyPDA = bytemuck::from_bytes(&data[8..VerifyingKeyPDA::HEADER_LEN]);
+    require!(
+        data.len() >= VerifyingKeyPDA::space(header.total_len),
+        HealthcareError::VerifyingKeyNotAllocated
+    );
+    Ok(())
+}
+
+/// A verifying key account borrowed in place: its header through `Deref`, and
+/// the key bytes and prepared encoding as slices of the account data
+pub struct VerifyingKey<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> VerifyingKey<'a> {
+    /// `data` is the whole account, discriminator included
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        check_vk_account(data)?;
+        Ok(VerifyingKey { data })
+    }
+
+    pub fn vk_bytes(&self) -> &'a [u8] {
+        let [key, _, _] = vk_regions(self.total_len);
+        &self.data[key]
+    }
+
+    /// The cached syscall encoding, empty until `prepare_vk` has run
+    pub fn prepared_vk_bytes(&self) -> &'a [u8] {
+        let [_, prepared, _] = vk_regions(self.total_len);
+        if self.prepared == 0 {
+            return &[];
+        }
+        &self.data[prepared]
+    }
+
+    /// Whether the key bytes match `vk_hash`
+    pub fn is_intact(&self) -> bool {
+        keccak::hash(self.vk_bytes()).to_bytes() == self.vk_hash
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.written == self.total_len
+    }
+
+    /// Check a proof against this key. Every verify instruction goes through here
+    /// so the public input count is enforced before any decoding work is done.
+    pub fn verify(
+        &self,
+        proof: &Groth16Proof,
+        public_inputs: &[u8],
+        format: ProofFormat,
+    ) -> Result<bool> {
+        self.check_input_count(public_inputs)?;
+        verify_groth16_proof(
+            self.vk_bytes(),
+            self.prepared_vk_bytes(),
+            proof,
+            public_inputs,
+            format,
+        )
+    }
+
+    /// Check parsed proofs against this key with one multi-pairing. Inputs must
+    /// already have passed `check_input_count` and `parse_public_inputs`.
+    pub fn verify_batch(&self, entries: &[(&Groth16Proof, &[[u8; 32]])]) -> Result<bool> {
+        verify_groth16_batch(self.vk_bytes(), self.prepared_vk_bytes(), entries)
+    }
+}
+
+impl std::ops::Deref for VerifyingKey<'_> {
+    type Target = VerifyingKeyPDA;
+
+    fn deref(&self) -> &VerifyingKeyPDA {
+        bytemuck::from_bytes(&self.data[8..VerifyingKeyPDA::HEADER_LEN])
+    }
+}
+
+/// `f` applied to the key account behind `account`, for constraints that need
+/// the key bytes
+pub fn inspect_vk<T>(account: &AccountLoader<VerifyingKeyPDA>, f: impl FnOnce(&VerifyingKey) -> T) -> Result<T> {
+    let data = account.as_ref().try_borrow_data()?;
+    Ok(f(&VerifyingKey::new(&data)?))
+}
+
+/// `VerifyingKey` for instructions that write the key region
+pub struct VerifyingKeyMut<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> VerifyingKeyMut<'a> {
+    pub fn new(data: &'a mut [u8]) -> Result<Self> {
+        check_vk_account(data)?;
+        Ok(VerifyingKeyMut { data })
+    }
+
+    pub fn view(&self) -> VerifyingKey<'_> {
+        VerifyingKey { data: self.data }
+    }
+
+    /// Copy `chunk` into the key bytes at `offset`. Chunks may arrive in any order;
+    /// overlapping a previously written range is only allowed when the bytes agree,
+    /// so a retried transaction is harmless but a conflicting rewrite is rejected.
+    pub fn write_chunk(&mut self, offset: u32, chunk: &[u8]) -> Result<()> {
+        require!(!self.is_finalized(), HealthcareError::VerifyingKeyAlreadyFinalized);
+        let [key, _, mask] = vk_regions(self.total_len);
+        let (head, mask_bytes) = self.data.split_at_mut(mask.start);
+        write_masked_chunk(&mut head[key], mask_bytes, offset, chunk)?;
+
+        let mut written = self.written as usize;
+        while written < self.total_len as usize && is_byte_written(&self.data[mask.clone()], written) {
+            written += 1;
+        }
+        self.written = written as u32;
+        Ok(())
+    }
+
+    /// Cache the syscall encoding of the key bytes
+    pub fn set_prepared(&mut self, prepared_vk_bytes: &[u8]) -> Result<()> {
+        let [_, prepared, _] = vk_regions(self.total_len);
+        require!(
+            prepared_vk_bytes.len() == prepared.len(),
+            HealthcareError::VerifyingKeyDeserializeFailed
+        );
+        self.data[prepared].copy_from_slice(prepared_vk_bytes);
+        self.prepared = 1;
+        Ok(())
+    }
+
+    /// Swap in a complete key of `vk_bytes.len()` bytes. The account must already
+    /// be sized for it; the prepared encoding is dropped.
+    pub fn replace(&mut self, vk_bytes: &[u8], vk_hash: [u8; 32]) -> Result<()> {
+        let total_len = vk_bytes.len() as u32;
+        require!(
+            self.data.len() >= VerifyingKeyPDA::space(total_len),
+            HealthcareError::VerifyingKeyNotAllocated
+        );
+        self.total_len = total_len;
+        self.written = total_len;
+        self.vk_hash = vk_hash;
+        self.prepared = 0;
+        let [key, _, mask] = vk_regions(total_len);
+        self.data[key].copy_from_slice(vk_bytes);
+        self.data[mask].fill(0xff);
+        Ok(())
+    }
+}
+
+impl std::ops::Deref for VerifyingKeyMut<'_> {
+    type Target = VerifyingKeyPDA;
+
+    fn deref(&self) -> &VerifyingKeyPDA {
+        bytemuck::from_bytes(&self.data[8..VerifyingKeyPDA::HEADER_LEN])
+    }
+}
+
+impl std::ops::DerefMut for VerifyingKeyMut<'_> {
+    fn deref_mut(&mut self) -> &mut VerifyingKeyPDA {
+        bytemuck::from_bytes_mut(&mut self.data[8..VerifyingKeyPDA::HEADER_LEN])
+    }
+}
+
+/// Staged replacement for a live verifying key, activatable after the registry delay
+#[account]
+#[derive(InitSpace)]
+pub struct VkUpdateProposal {
+    pub verifying_key: Pubkey,
+    /// keccak of the replacement key bytes, committed at proposal time
+    pub new_vk_hash: [u8; 32],
+    pub proposed_at: i64,
+    pub activatable_at: i64,
+    pub total_len: u32,
+    /// `total_len` bytes, which `space` sizes the account for
+    #[max_len(0)]
+    pub vk_bytes: Vec<u8>,
+    #[max_len(0)]
+    pub written_mask: Vec<u8>,
+    pub bump: u8,
+}
+
+impl VkUpdateProposal {
+    pub fn space(total_len: u32) -> usize {
+        8 + Self::INIT_SPACE + total_len as usize + VerifyingKeyPDA::mask_len(total_len)
+    }
+
+    pub fn write_chunk(&mut self, offset: u32, chunk: &[u8]) -> Result<()> {
+        write_masked_chunk(&mut self.vk_bytes, &mut self.written_mask, offset, chunk)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        is_fully_written(&self.written_mask, self.total_len)
+    }
+}
+
+fn is_byte_written(mask: &[u8], index: usize) -> bool {
+    mask[index / 8] & (1 << (index % 8)) != 0
+}
+
+fn write_masked_chunk(bytes: &mut [u8], mask: &mut [u8], offset: u32, chunk: &[u8]) -> Result<()> {
+    let start = offset as usize;
+    let end = start
+        .checked_add(chunk.len())
+        .filter(|end| !chunk.is_empty() && *end <= bytes.len())
+        .ok_or(HealthcareError::VkChunkOutOfBounds)?;
+
+    for (index, byte) in (start..end).zip(chunk) {
+        if is_byte_written(mask, index) {
+            require!(bytes[index] == *byte, HealthcareError::VkChunkConflict);
+        }
+    }
+
+    bytes[start..end].copy_from_slice(chunk);
+    for index in start..end {
+        mask[index / 8] |= 1 << (index % 8);
+    }
+    Ok(())
+}
+
+fn is_fully_written(mask: &[u8], total_len: u32) -> bool {
+    (0..total_len as usize).all(|index| is_byte_written(mask, index))
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VerificationRecord {
+    /// `VERSION` of the layout the record was written in; see `migrations`
+    pub version: u8,
+    pub patient_pubkey: Pubkey,
+    pub proof_hash: [u8; 32],
+    #[max_len(MAX_IPFS_CID_LEN)]
+    pub ipfs_hash: String,
+    pub timestamp: i64,
+    /// `status == Verified`, kept in step by `transition` for readers of the
+    /// original layout; the program itself reads `status`
+    pub is_valid: bool,
+    pub verification_type: VerificationType,
+    /// Slot the proof was verified in, for reconciling against ledger history
+    pub slot: u64,
+    /// Circuit whose verifying key accepted the proof
+    #[max_len(MAX_CIRCUIT_ID_LEN)]
+    pub circuit_id: String,
+    /// `VerifyingKeyPDA::version` of that key at the time
+    pub circuit_version: u16,
+    /// Hash of the verifying key bytes that accepted the proof
+    pub vk_hash: [u8; 32],
+    /// How `proof_hash` was computed
+    pub hash_algo: HashAlgo,
+    /// `compute_public_inputs_hash` of the statement the proof established
+    pub public_inputs_hash: [u8; 32],
+    /// `derive_verification_id` of the verification
+    pub verification_id: [u8; 32],
+    /// When `revoke_verification` revoked the record, or zero
+    pub revoked_at: i64,
+    /// Who revoked it: the registry authority or the patient
+    pub revoked_by: Pubkey,
+    /// The record stops being active at this time; zero if it never does
+    pub expires_at: i64,
+    /// Where the record is in its lifecycle, changed only through `transition`;
+    /// anything that reads a record checks it with `assert_active`
+    pub status: RecordStatus,
+    /// Paid for the account and gets the rent back when it is closed, at the key
+    /// it rotated to if it has rotated; unset on anonymous records, which are
+    /// never closed
+    pub rent_payer: Pubkey,
+    /// Bump of a record at a derived address, zero for one at a client keypair
+    pub bump: u8,
+    /// Times `reverify_eligibility` renewed the record
+    pub revision: u16,
+    /// `proof_hash` before the latest renewal, zero if never renewed
+    pub previous_proof_hash: [u8; 32],
+    /// The registry's verification mint while the patient holds a token of it
+    /// for this record, see `verify_eligibility`; unset otherwise
+    pub token_mint: Pubkey,
+    /// The registered provider who co-signed `verify_eligibility`, if one did
+    pub attesting_provider: Option<Pubkey>,
+    /// The patient's record written before this one, see `PatientIndex::last_record`;
+    /// `None` for their first, an anonymous or one migrated from before v6
+    pub previous_record: Option<Pubkey>,
+    /// `seq` of the latest verify or revoke event about the record
+    pub event_seq: u64,
+    /// The compliance hold `place_hold` put on the record, if any. A held record
+    /// can't be changed, consumed or closed, even once expired, until
+    /// `release_hold`.
+    pub hold: Option<HoldInfo>,
+    /// The record `import_record` copied this one from, in another registry;
+    /// unset for one written here
+    pub migrated_from: Pubkey,
+    /// Encrypted payload the patient attached with `update_metadata`, which the
+    /// program never reads
+    #[max_len(0)]
+    pub metadata: Vec<u8>,
+}
+
+impl VerificationRecord {
+    /// Length of a record with no `metadata`; `update_metadata` resizes the
+    /// account to fit what it holds
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 9;
+
+    /// Length of the record's account holding `metadata_len` bytes of metadata.
+    /// Records written before `ipfs_hash` was held to `MAX_IPFS_CID_LEN` may
+    /// carry a longer one, which keeps its room.
+    pub fn space_with_metadata(&self, metadata_len: usize) -> usize {
+        Self::SPACE + self.ipfs_hash.len().saturating_sub(MAX_IPFS_CID_LEN) + metadata_len
+    }
+
+    /// Whether the record still attests eligibility at `clock`: `Verified` and not
+    /// past its expiry, whether or not `mark_expired` has run
+    pub fn is_active(&self, clock: &Clock) -> bool {
+        self.assert_active(clock).is_ok()
+    }
+
+    /// The check every instruction consuming a record makes
+    pub fn assert_active(&self, clock: &Clock) -> Result<()> {
+        require!(self.version == Self::VERSION, HealthcareError::RecordNeedsMigration);
+        match self.status {
+            RecordStatus::Verified => {}
+            RecordStatus::Revoked => return err!(HealthcareError::RecordRevoked),
+            RecordStatus::Expired => return err!(HealthcareError::RecordExpired),
+            RecordStatus::Disputed => return err!(HealthcareError::RecordDisputed),
+            RecordStatus::Pending => return err!(HealthcareError::RecordNotVerified),
+            RecordStatus::Migrated => return err!(HealthcareError::RecordAlreadyMigrated),
+        }
+        require!(!self.is_past_expiry(clock), HealthcareError::RecordExpired);
+        Ok(())
+    }
+
+    /// The original `is_valid`: verified, and not since revoked, expired or
+    /// disputed
+    pub fn is_verified(&self) -> bool {
+        self.status == RecordStatus::Verified
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.hold.is_some()
+    }
+
+    /// Move the record at `record` to `status` if `RecordStatus::can_become`
+    /// allows it, keeping `is_valid` in step, and emit `RecordStatusChanged`
+    /// under `registry`
+    pub fn transition(
+        &mut self,
+        record: Pubkey,
+        status: RecordStatus,
+        registry: &mut Account<HealthcareRegistry>,
+    ) -> Result<()> {
+        require!(self.status.can_become(status), HealthcareError::InvalidStatusTransition);
+        let old_status = std::mem::replace(&mut self.status, status);
+        self.is_valid = self.is_verified();
+        emit!(RecordStatusChanged {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            record,
+            patient: self.patient_pubkey,
+            verification_type: self.verification_type,
+            old_status,
+            new_status: status,
+        });
+        Ok(())
+    }
+
+    pub fn is_past_expiry(&self, clock: &Clock) -> bool {
+        self.expires_at != 0 && clock.unix_timestamp >= self.expires_at
+    }
+
+    /// When the record was last written, in `time`'s unit
+    pub fn written_at(&self, time: TimeSource) -> i64 {
+        time.at(self.timestamp, self.slot)
+    }
+
+    /// `expires_at` in `time`'s unit: under `TimeSource::Slot`, the slot its
+    /// validity period ends at counted from the record's `slot`
+    pub fn expires_in(&self, time: TimeSource) -> i64 {
+        let validity = time.span(self.expires_at.saturating_sub(self.timestamp));
+        self.written_at(time).saturating_add(validity)
+    }
+
+    /// Whether `reverify_eligibility` may renew the record at `clock`: expired, or
+    /// verified and within `RENEWAL_WINDOW_SECS` of an expiry
+    pub fn is_renewable(&self, clock: &Clock) -> bool {
+        match self.status {
+            RecordStatus::Expired => true,
+            RecordStatus::Verified => {
+                self.expires_at != 0 && clock.unix_timestamp >= self.expires_at.saturating_sub(RENEWAL_WINDOW_SECS)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the record was written under `registry`, by recomputing its
+    /// verification ID
+    pub fn is_in_registry(&self, registry: &Pubkey) -> bool {
+        self.verification_id
+            == derive_verification_id(registry, &self.patient_pubkey, &self.circuit_id, &self.proof_hash)
+    }
+
+    /// The record of an eligibility proof `verify_eligibility_proof` accepted,
+    /// still `Pending` until the caller moves it to `Verified`. `identity` fills
+    /// `patient_pubkey`: the patient, or the credential nullifier of an anonymous
+    /// submission.
+    fn eligibility(
+        identity: Pubkey,
+        registry: &Pubkey,
+        verified: &VerifiedProof,
+        ipfs_hash: String,
+        clock: &Clock,
+        circuit_id: String,
+        verifying_key: &VerifyingKeyPDA,
+        expires_at: i64,
+        rent_payer: Pubkey,
+    ) -> Self {
+        let verification_id = derive_verification_id(registry, &identity, &circuit_id, &verified.proof_hash);
+        VerificationRecord {
+            version: Self::VERSION,
+            patient_pubkey: identity,
+            proof_hash: verified.proof_hash,
+            ipfs_hash,
+            timestamp: clock.unix_timestamp,
+            is_valid: false,
+            verification_type: VerificationType::Eligibility,
+            slot: clock.slot,
+            circuit_id,
+            circuit_version: verifying_key.version,
+            vk_hash: verifying_key.vk_hash,
+            hash_algo: verified.hash_algo,
+            public_inputs_hash: verified.public_inputs_hash,
+            verification_id,
+            revoked_at: 0,
+            revoked_by: Pubkey::default(),
+            expires_at,
+            status: RecordStatus::Pending,
+            rent_payer,
+            bump: 0,
+            revision: 0,
+            previous_proof_hash: [0; 32],
+            token_mint: Pubkey::default(),
+            attesting_provider: None,
+            previous_record: None,
+            event_seq: 0,
+            hold: None,
+            migrated_from: Pubkey::default(),
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Point the record at the newer proof `verified`, as `eligibility` would
+    /// have written it, keeping the replaced proof hash
+    fn renew(
+        &mut self,
+        registry: &Pubkey,
+        verified: &VerifiedProof,
+        ipfs_hash: String,
+        clock: &Clock,
+        circuit_id: String,
+        verifying_key: &VerifyingKeyPDA,
+        expires_at: i64,
+    ) {
+        self.previous_proof_hash = std::mem::replace(&mut self.proof_hash, verified.proof_hash);
+        self.verification_id = derive_verification_id(registry, &self.patient_pubkey, &circuit_id, &self.proof_hash);
+        self.ipfs_hash = ipfs_hash;
+        self.timestamp = clock.unix_timestamp;
+        self.slot = clock.slot;
+        self.circuit_id = circuit_id;
+        self.circuit_version = verifying_key.version;
+        self.vk_hash = verifying_key.vk_hash;
+        self.hash_algo = verified.hash_algo;
+        self.public_inputs_hash = verified.public_inputs_hash;
+        self.expires_at = expires_at;
+        self.revision += 1;
+    }
+}
+
+/// A prescription `verify_prescription` recorded, at
+/// `[b"prescription", ProofNullifier::seed(..)]` of its submission
+#[account]
+#[derive(InitSpace)]
+pub struct PrescriptionRecord {
+    pub patient: Pubkey,
+    /// Registry whose pharmacies may refill it
+    pub registry: Pubkey,
+    pub proof_hash: [u8; 32],
+    /// How `proof_hash` was computed
+    pub hash_algo: HashAlgo,
+    /// Commitment to the prescribed drug code; the code itself stays off chain
+    pub drug_commitment: [u8; 32],
+    /// Circuit whose verifying key accepted the proof
+    #[max_len(MAX_CIRCUIT_ID_LEN)]
+    pub circuit_id: String,
+    pub verified_at: i64,
+    /// No refill is recorded at or after this time
+    pub valid_until: i64,
+    pub refills_allowed: u8,
+    /// Counts down from `refills_allowed` with each `record_refill`
+    pub refills_remaining: u8,
+    pub bump: u8,
+}
+
+impl PrescriptionRecord {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(proof: &[u8], format: ProofFormat, public_inputs: &[u8]) -> Pubkey {
+        let seed = ProofNullifier::seed(proof, format, public_inputs);
+        Pubkey::find_program_address(&[b"prescription", &seed], &crate::ID).0
+    }
+}
+
+/// A diagnosis `verify_diagnosis` recorded, at `[b"diagnosis",
+/// ProofNullifier::seed(..)]` of its submission
+#[account]
+#[derive(InitSpace)]
+pub struct DiagnosisRecord {
+    pub patient: Pubkey,
+    pub registry: Pubkey,
+    /// The registered provider who made the diagnosis
+    pub provider: Pubkey,
+    pub proof_hash: [u8; 32],
+    /// How `proof_hash` was computed
+    pub hash_algo: HashAlgo,
+    /// Commitment to the ICD-10 code; the code itself never goes on chain
+    pub icd10_commitment: [u8; 32],
+    /// Circuit whose verifying key accepted the proof
+    #[max_len(MAX_CIRCUIT_ID_LEN)]
+    pub circuit_id: String,
+    #[max_len(MAX_IPFS_CID_LEN)]
+    pub ipfs_cid: String,
+    /// The patient's `IpfsPinRecord` the diagnosis links to, if any
+    pub pin_record: Option<Pubkey>,
+    pub verified_at: i64,
+    /// When `revoke_diagnosis` revoked the record, or zero
+    pub revoked_at: i64,
+    /// Who revoked it: the provider or the registry authority
+    pub revoked_by: Pubkey,
+    pub bump: u8,
+}
+
+impl DiagnosisRecord {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(proof: &[u8], format: ProofFormat, public_inputs: &[u8]) -> Pubkey {
+        let seed = ProofNullifier::seed(proof, format, public_inputs);
+        Pubkey::find_program_address(&[b"diagnosis", &seed], &crate::ID).0
+    }
+}
+
+/// A lab result `verify_lab_result` recorded, at `[b"lab_result",
+/// ProofNullifier::seed(..)]` of its submission
+#[account]
+#[derive(InitSpace)]
+pub struct LabResultRecord {
+    pub patient: Pubkey,
+    pub registry: Pubkey,
+    /// The registered provider whose lab issued the result
+    pub lab: Pubkey,
+    pub proof_hash: [u8; 32],
+    /// How `proof_hash` was computed
+    pub hash_algo: HashAlgo,
+    /// Commitment to the result's category, e.g. "HbA1c below 6.5%"; the value
+    /// itself never goes on chain
+    pub result_commitment: [u8; 32],
+    /// Circuit whose verifying key accepted the proof
+    #[max_len(MAX_CIRCUIT_ID_LEN)]
+    pub circuit_id: String,
+    pub verified_at: i64,
+    pub bump: u8,
+}
+
+impl LabResultRecord {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(proof: &[u8], format: ProofFormat, public_inputs: &[u8]) -> Pubkey {
+        let seed = ProofNullifier::seed(proof, format, public_inputs);
+        Pubkey::find_program_address(&[b"lab_result", &seed], &crate::ID).0
+    }
+}
+
+/// An immunization `verify_immunization` recorded, at `[b"immunization",
+/// ProofNullifier::seed(..)]` of its submission
+#[account]
+#[derive(InitSpace)]
+pub struct ImmunizationRecord {
+    pub patient: Pubkey,
+    pub registry: Pubkey,
+    /// The registered provider who administered or attested the immunization
+    pub provider: Pubkey,
+    pub proof_hash: [u8; 32],
+    /// How `proof_hash` was computed
+    pub hash_algo: HashAlgo,
+    /// Commitment to the vaccine and dose status; neither goes on chain
+    pub status_commitment: [u8; 32],
+    /// Circuit whose verifying key accepted the proof
+    #[max_len(MAX_CIRCUIT_ID_LEN)]
+    pub circuit_id: String,
+    pub verified_at: i64,
+    pub bump: u8,
+}
+
+impl ImmunizationRecord {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(proof: &[u8], format: ProofFormat, public_inputs: &[u8]) -> Pubkey {
+        let seed = ProofNullifier::seed(proof, format, public_inputs);
+        Pubkey::find_program_address(&[b"immunization", &seed], &crate::ID).0
+    }
+}
+
+/// A claim `submit_claim` filed against an active `VerificationRecord`, at
+/// `[b"claim", registry, claim_id as little-endian u64]`
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimRecord {
+    pub registry: Pubkey,
+    pub patient: Pubkey,
+    /// The patient's record the claim rests on
+    pub verification: Pubkey,
+    /// The registry's `claim_count` when the claim was filed
+    pub claim_id: u64,
+    /// Lamports claimed
+    pub amount: u64,
+    /// Commitment to the billed service code; the code itself stays off chain
+    pub service_code_commitment: [u8; 32],
+    pub status: ClaimStatus,
+    pub submitted_at: i64,
+    pub bump: u8,
+    /// Paid on approval: the patient, or a provider from `designate_claim_payee`
+    pub payee: Pubkey,
+    /// The insurer that funded the claim's escrow and alone adjudicates it
+    pub insurer: Option<Pubkey>,
+    /// The insurer's code for its decision, zero until adjudicated
+    pub reason_code: u16,
+    pub adjudicated_at: i64,
+    /// The encounter's nullifier, whose `ClaimNullifier` points back here
+    pub claim_nullifier: [u8; 32],
+}
+
+impl ClaimRecord {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(registry: &Pubkey, claim_id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"claim", registry.as_ref(), &claim_id.to_le_bytes()], &crate::ID).0
+    }
+}
+
+/// Marks an encounter as claimed, at `[b"claim_nullifier", registry,
+/// claim_nullifier]`, so it backs at most one open claim
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimNullifier {
+    /// The `ClaimRecord` filed for the encounter
+    pub claim: Pubkey,
+    pub used_at: i64,
+    pub bump: u8,
+}
+
+impl ClaimNullifier {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(registry: &Pubkey, claim_nullifier: &[u8; 32]) -> Pubkey {
+        Pubkey::find_program_address(&[b"claim_nullifier", registry.as_ref(), claim_nullifier], &crate::ID).0
+    }
+}
+
+/// A Merkle root over the registry's events numbered `start_seq` up to but not
+/// including `end_seq`, at `[b"checkpoint", registry, start_seq]`; see
+/// `commit_checkpoint`
+#[account]
+#[derive(InitSpace)]
+pub struct Checkpoint {
+    pub registry: Pubkey,
+    pub root: [u8; 32],
+    pub start_seq: u64,
+    pub end_seq: u64,
+    pub committed_at: i64,
+    pub bump: u8,
+}
+
+impl Checkpoint {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(registry: &Pubkey, start_seq: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"checkpoint", registry.as_ref(), &start_seq.to_le_bytes()], &crate::ID).0
+    }
+}
+
+/// The source registry's leave for `record` to move to `destination`, at
+/// `[b"migration_ticket", record]`; see `export_record`
+#[account]
+#[derive(InitSpace)]
+pub struct MigrationTicket {
+    pub source_registry: Pubkey,
+    pub record: Pubkey,
+    pub destination: Pubkey,
+    /// Keccak of the record's account as exported; `import_record` refuses a
+    /// record that has changed since
+    pub snapshot_hash: [u8; 32],
+    pub issued_at: i64,
+    /// `import_record` fails from this unix time on
+    pub expires_at: i64,
+    /// The record `import_record` wrote in the destination, once it has
+    pub imported_record: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl MigrationTicket {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(record: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"migration_ticket", record.as_ref()], &crate::ID).0
+    }
+}
+
+/// Lamports an insurer set aside for a claim, at `[b"claim_escrow", claim]`,
+/// closed by `adjudicate_claim`
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimEscrow {
+    pub claim: Pubkey,
+    pub insurer: Pubkey,
+    /// Held over the account's rent, paid out or refunded in full
+    pub lamports: u64,
+    pub bump: u8,
+}
+
+impl ClaimEscrow {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(claim: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"claim_escrow", claim.as_ref()], &crate::ID).0
+    }
+}
+
+/// Where a `ClaimRecord` is in adjudication
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClaimStatus {
+    Submitted,
+    Approved,
+    Denied,
+    Paid,
+}
+
+/// An insurer the registry authority allows to `dispute_verification`, at
+/// `[b"insurer", registry, insurer]`
+#[account]
+#[derive(InitSpace)]
+pub struct InsurerRegistration {
+    pub registry: Pubkey,
+    pub insurer: Pubkey,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl InsurerRegistration {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(registry: &Pubkey, insurer: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"insurer", registry.as_ref(), insurer.as_ref()], &crate::ID).0
+    }
+}
+
+/// An open dispute of a record, at `[b"dispute", record]`, closed by
+/// `resolve_dispute`
+#[account]
+#[derive(InitSpace)]
+pub struct VerificationDispute {
+    pub record: Pubkey,
+    /// The registry authority or registered insurer that opened it
+    pub disputer: Pubkey,
+    /// Hash of the disputer's evidence, held off-chain
+    pub evidence_hash: [u8; 32],
+    pub disputed_at: i64,
+    pub bump: u8,
+}
+
+impl VerificationDispute {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(record: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"dispute", record.as_ref()], &crate::ID).0
+    }
+}
+
+/// A provider's offline attestation for `verify_eligibility`, in place of its
+/// co-signature
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProviderAttestation {
+    pub provider: Pubkey,
+    /// keccak of the `attestation_message` the provider signed
+    pub msg_hash: [u8; 32],
+    /// Index in the transaction of the precompile instruction checking the
+    /// signature
+    pub sig_index: u8,
+    pub scheme: AttestationScheme,
+}
+
+/// How a `ProviderAttestation` is signed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttestationScheme {
+    /// By the provider's own key, checked by `ed25519_program`
+    #[default]
+    Ed25519,
+    /// By the provider's registered `eth_address`, checked by `secp256k1_program`
+    /// over `eth_signed_message` of the attestation
+    Secp256k1,
+}
+
+/// A provider the registry authority registered, at `[b"provider", registry,
+/// provider]`. Only an `active` one may be named by `verify_diagnosis`.
+#[account]
+#[derive(InitSpace)]
+pub struct ProviderRegistration {
+    pub registry: Pubkey,
+    pub provider: Pubkey,
+    pub registered_at: i64,
+    pub active: bool,
+    pub bump: u8,
+    /// Ethereum address the provider's secp256k1 attestations must recover to,
+    /// zero if it has none
+    pub eth_address: [u8; 20],
+    /// Verifications the provider may attest per UTC day, zero for no cap
+    pub daily_quota: u32,
+    /// Verifications attested on `quota_epoch_day`
+    pub used_today: u32,
+    /// Days since the Unix epoch that `used_today` counts
+    pub quota_epoch_day: i64,
+}
+
+impl ProviderRegistration {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(registry: &Pubkey, provider: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"provider", registry.as_ref(), provider.as_ref()], &crate::ID).0
+    }
+
+    /// Count one attested verification against the quota of the UTC day `now`
+    /// falls in, starting the count over on a new day
+    pub fn use_quota(&mut self, now: i64) -> Result<()> {
+        let day = now.div_euclid(SECS_PER_DAY);
+        if day != self.quota_epoch_day {
+            self.quota_epoch_day = day;
+            self.used_today = 0;
+        }
+        require!(
+            self.daily_quota == 0 || self.used_today < self.daily_quota,
+            HealthcareError::ProviderQuotaExceeded
+        );
+        self.used_today += 1;
+        Ok(())
+    }
+}
+
+/// What `verify_access_control` grants: up to `uses_remaining` accesses by
+/// `patient` to `resource` through `expires_at_slot`
+#[account]
+#[derive(InitSpace)]
+pub struct AccessPass {
+    pub patient: Pubkey,
+    /// Whatever the pass opens, e.g. a data holder's account or an `IpfsPinRecord`
+    pub resource: Pubkey,
+    pub registry: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub issued_at_slot: u64,
+    /// Last slot the pass may be consumed in
+    pub expires_at_slot: u64,
+    pub uses_remaining: u16,
+    pub bump: u8,
+}
+
+impl AccessPass {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(patient: &Pubkey, resource: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"access_pass", patient.as_ref(), resource.as_ref()], &crate::ID).0
+    }
+
+    /// Why the pass doesn't let `holder` into `resource` under `registry` in
+    /// `slot`, if it doesn't
+    fn refusal(&self, holder: Pubkey, registry: Pubkey, resource: Pubkey, slot: u64) -> Option<&'static str> {
+        if self.patient != holder {
+            Some("access pass held by another key")
+        } else if self.registry != registry {
+            Some("access pass issued under another registry")
+        } else if self.resource != resource {
+            Some("access pass to another resource")
+        } else if slot > self.expires_at_slot {
+            Some("access pass expired")
+        } else if self.uses_remaining == 0 {
+            Some("access pass used up")
+        } else {
+            None
+        }
+    }
+
+    /// Spend a use of the pass at `access_pass` for `resource` in `slot`, and
+    /// emit `AccessPassConsumed` under `registry`
+    fn consume(
+        &mut self,
+        access_pass: Pubkey,
+        resource: Pubkey,
+        slot: u64,
+        registry: &mut Account<HealthcareRegistry>,
+    ) -> Result<()> {
+        require!(self.resource == resource, HealthcareError::AccessPassResourceMismatch);
+        require!(slot <= self.expires_at_slot, HealthcareError::AccessPassExpired);
+        require!(self.uses_remaining > 0, HealthcareError::AccessPassExhausted);
+        self.uses_remaining -= 1;
+
+        emit!(AccessPassConsumed {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            access_pass,
+            patient: self.patient,
+            resource,
+            uses_remaining: self.uses_remaining,
+        });
+        msg!("Access pass consumed, {} uses remaining", self.uses_remaining);
+        Ok(())
+    }
+}
+
+/// A pharmacy the registry authority allows to `record_refill`, at
+/// `[b"pharmacy", registry, pharmacy]`
+#[account]
+#[derive(InitSpace)]
+pub struct PharmacyRegistration {
+    pub registry: Pubkey,
+    pub pharmacy: Pubkey,
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl PharmacyRegistration {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(registry: &Pubkey, pharmacy: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"pharmacy", registry.as_ref(), pharmacy.as_ref()], &crate::ID).0
+    }
+}
+
+/// Return data of the recording verify instructions, for CPI callers. A proof
+/// that fails aborts the instruction, so a caller that gets this back always sees
+/// `verified == true`; use `verify_proof_readonly` to branch without aborting.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct VerificationResult {
+    pub verified: bool,
+    pub proof_hash: [u8; 32],
+    /// The `VerificationRecord` written for the proof
+    pub record: Pubkey,
+    /// `derive_verification_id` of the verification
+    pub verification_id: [u8; 32],
+}
+
+/// Return data of `check_verification_status`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct VerificationStatusView {
+    /// Whether the patient has a record of the type under the registry; not
+    /// if theirs has been closed or is another registry's
+    pub exists: bool,
+    /// The record's status, `Expired` once past its `expires_at` even before
+    /// `mark_expired`; `Pending` when there is none
+    pub status: RecordStatus,
+    pub expires_at: i64,
+    /// The patient's latest record of the type, unset when there is none
+    pub record: Pubkey,
+    /// Whether the record is under a compliance hold
+    pub held: bool,
+}
+
+/// Marks a (proof, public inputs) pair as spent so it backs at most one record
+#[account]
+#[derive(InitSpace)]
+pub struct ProofNullifier {
+    /// The `VerificationRecord` created by the first submission
+    pub verification: Pubkey,
+    pub used_at: i64,
+    pub bump: u8,
+}
+
+impl ProofNullifier {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// `keccak(compressed proof || little-endian public inputs)`. Both halves are
+    /// normalized so re-encoding a proof in another `ProofFormat` hits the same PDA.
+    /// Undecodable proofs fall back to their raw bytes; the handler rejects them.
+    /// PLONK and BLS12-381 proofs take that path too, which is fine since each is
+    /// accepted in a single encoding per proof.
+    pub fn seed(proof: &[u8], format: ProofFormat, public_inputs: &[u8]) -> [u8; 32] {
+        let compressed = match format {
+            // Canonicity of compressed input is enforced by `Groth16Proof::decode`
+            ProofFormat::Compressed => None,
+            _ if proof.len() == format.proof_len() => verifier_core::decode_points(proof, format)
+                .ok()
+                .and_then(|proof| proof.encode(ProofFormat::Compressed).ok()),
+            _ => None,
+        };
+        let mut inputs = public_inputs.to_vec();
+        if format.big_endian_inputs() {
+            inputs.chunks_exact_mut(32).for_each(|scalar| scalar.reverse());
+        }
+        keccak::hashv(&[compressed.as_deref().unwrap_or(proof), &inputs]).to_bytes()
+    }
+}
+
+/// Spends a circuit-produced credential nullifier for one epoch, so a credential
+/// backs at most one anonymous record per epoch. Lives at
+/// `[b"credential", verifying key, nullifier, epoch as little-endian u64]`.
+#[account]
+#[derive(InitSpace)]
+pub struct CredentialNullifier {
+    /// The `VerificationRecord` created with the credential
+    pub verification: Pubkey,
+    pub epoch: u64,
+    pub bump: u8,
+    /// When the record was verified, which the next epoch's cooldown counts from
+    pub used_at: i64,
+}
+
+impl CredentialNullifier {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(verifying_key: &Pubkey, credential: &[u8; 32], epoch: u64) -> Pubkey {
+        let seeds: &[&[u8]] = &[b"credential", verifying_key.as_ref(), credential, &epoch.to_le_bytes()];
+        Pubkey::find_program_address(seeds, &crate::ID).0
+    }
+}
+
+/// Per-patient verification state, shared by every verification type so one
+/// proof can't be recorded under two of them. Lives at `[b"patient", patient]`.
+#[account]
+#[derive(InitSpace)]
+pub struct PatientIndex {
+    /// The nonce the patient's next proof on a nonce-using circuit must carry
+    pub verification_nonce: u64,
+    /// The `record_nonce` of the patient's next `verify_eligibility` record; the
+    /// records before it sit at `derive_verification_pda` of each lower nonce
+    pub next_record_nonce: u64,
+    pub bump: u8,
+    /// Records any `verify_*` instruction wrote for the patient; anonymous ones
+    /// name no patient and aren't counted
+    pub verification_count: u32,
+    /// The latest of them, and when it was verified
+    pub last_verification: Pubkey,
+    pub last_verified_at: i64,
+    /// `verification_count` split by `VerificationType`, in declaration order
+    pub type_counts: [u32; VERIFICATION_TYPE_COUNT],
+    /// Those still `Verified`: revocation, expiry and closing take records out
+    pub active_count: u32,
+    /// `last_verified_at` split by `VerificationType`, which the registry's
+    /// `cooldown_secs` is counted from
+    pub last_verified_at_by_type: [i64; VERIFICATION_TYPE_COUNT],
+    /// The patient's newest `VerificationRecord`, unset when they have none.
+    /// Each record's `previous_record` leads to the one written before it, so
+    /// the history is walked from here until `None`, or an account that no
+    /// longer exists once the oldest record was closed.
+    pub last_record: Pubkey,
+    /// The patient's latest record of each `VerificationType`, in declaration
+    /// order, for `check_verification_status`; unset for a type they have none of
+    pub last_record_by_type: [Pubkey; VERIFICATION_TYPE_COUNT],
+    /// Pins written for the patient, split by `DataCategory` in declaration
+    /// order; `retag_pin` moves a pin's count to its new category
+    pub pin_category_counts: [u32; DATA_CATEGORY_COUNT],
+}
+
+impl PatientIndex {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(patient: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"patient", patient.as_ref()], &crate::ID).0
+    }
+
+    /// Count `record`, just moved to `Verified` at `now`, unless the patient's
+    /// last verification of `verification_type` is under `cooldown_secs` old
+    fn record_verification(
+        &mut self,
+        record: Pubkey,
+        verification_type: VerificationType,
+        now: i64,
+        cooldown_secs: i64,
+    ) -> Result<()> {
+        self.check_cooldown(verification_type, now, cooldown_secs)?;
+        self.note_verification(record, verification_type, now)
+    }
+
+    /// Fail with `VerificationCooldownActive` while the patient's last
+    /// verification of `verification_type` is under `cooldown_secs` old
+    fn check_cooldown(&self, verification_type: VerificationType, now: i64, cooldown_secs: i64) -> Result<()> {
+        let last_verified_at = self.last_verified_at_by_type[verification_type as usize];
+        if last_verified_at != 0 {
+            check_cooldown(last_verified_at, cooldown_secs, now)?;
+        }
+        Ok(())
+    }
+
+    /// Count `record`, just moved to `Verified` at `now`, without checking the
+    /// cooldown; a batch checks it once for all of its records
+    fn note_verification(&mut self, record: Pubkey, verification_type: VerificationType, now: i64) -> Result<()> {
+        let overflow = || error!(HealthcareError::CounterOverflow);
+        self.verification_count = self.verification_count.checked_add(1).ok_or_else(overflow)?;
+        let type_count = &mut self.type_counts[verification_type as usize];
+        *type_count = type_count.checked_add(1).ok_or_else(overflow)?;
+        self.active_count = self.active_count.checked_add(1).ok_or_else(overflow)?;
+        self.last_verification = record;
+        self.last_verified_at = now;
+        self.last_verified_at_by_type[verification_type as usize] = now;
+        self.last_record_by_type[verification_type as usize] = record;
+        Ok(())
+    }
+
+    /// Count a pin of `category` held by the patient
+    fn count_pin(&mut self, category: DataCategory) -> Result<()> {
+        let count = &mut self.pin_category_counts[category as usize];
+        *count = count.checked_add(1).ok_or_else(|| error!(HealthcareError::CounterOverflow))?;
+        Ok(())
+    }
+
+    /// Stop counting a pin under `category`, failing with `CounterOverflow`
+    /// for a pin the index never counted there
+    fn uncount_pin(&mut self, category: DataCategory) -> Result<()> {
+        let count = &mut self.pin_category_counts[category as usize];
+        *count = count.checked_sub(1).ok_or_else(|| error!(HealthcareError::CounterOverflow))?;
+        Ok(())
+    }
+
+    /// Make `record`, just written, the newest of the patient's records and
+    /// return the one before it, for its `previous_record`
+    fn link_record(&mut self, record: Pubkey) -> Option<Pubkey> {
+        let previous = std::mem::replace(&mut self.last_record, record);
+        (previous != Pubkey::default()).then_some(previous)
+    }
+
+    /// Take `record`, at `key` and about to be closed or moved, out of the
+    /// patient's chain: whichever of `last_record` and `successor`, the record
+    /// written after it, points at it skips to its `previous_record`. Only the
+    /// oldest record, or one migrated from before the chain, may leave without
+    /// its successor.
+    fn unlink_record(
+        &mut self,
+        key: Pubkey,
+        record: &VerificationRecord,
+        successor: Option<&mut VerificationRecord>,
+    ) -> Result<()> {
+        let latest_of_type = &mut self.last_record_by_type[record.verification_type as usize];
+        if *latest_of_type == key {
+            *latest_of_type = Pubkey::default();
+        }
+        if self.last_record == key {
+            self.last_record = record.previous_record.unwrap_or_default();
+            return Ok(());
+        }
+        match successor {
+            Some(successor) => {
+                require!(successor.previous_record == Some(key), HealthcareError::RecordSuccessorMismatch);
+                successor.previous_record = record.previous_record;
+            }
+            None => require!(record.previous_record.is_none(), HealthcareError::RecordSuccessorMismatch),
+        }
+        Ok(())
+    }
+
+    /// Note the renewal of `record` at `now`, which counts as active again if
+    /// it had expired
+    fn record_renewal(&mut self, record: Pubkey, reactivated: bool, now: i64) {
+        if reactivated {
+            self.active_count += 1;
+        }
+        self.last_verification = record;
+        self.last_verified_at = now;
+    }
+}
+
+/// A patient's move from `old_key` to `new_key`, at `[b"key_rotation", old_key]`.
+/// The new key claims the old one's records, access passes and pins through it.
+#[account]
+#[derive(InitSpace)]
+pub struct KeyRotation {
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub rotated_at: i64,
+    pub bump: u8,
+}
+
+impl KeyRotation {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(old_key: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[b"key_rotation", old_key.as_ref()], &crate::ID).0
+    }
+
+    /// The key `key` rotated to if `rotation`, the account at its `address`,
+    /// holds a rotation, else `key` itself
+    pub fn current_key(key: Pubkey, rotation: &AccountInfo) -> Result<Pubkey> {
+        if rotation.owner != &crate::ID || rotation.data_is_empty() {
+            return Ok(key);
+        }
+        let rotation = KeyRotation::try_deserialize(&mut &rotation.try_borrow_data()?[..])?;
+        Ok(rotation.new_key)
+    }
+
+    /// Where rent `rent_payer` paid goes back to: its `current_key`
+    pub fn refund_key(rent_payer: Pubkey, rotation: &AccountInfo) -> Result<Pubkey> {
+        Self::current_key(rent_payer, rotation)
+    }
+}
+
+/// A guardian's consent to act for `ward`, such as a minor, who may hold no
+/// wallet of their own. Lives at `[b"guardian", registry, guardian, ward]`.
+#[account]
+#[derive(InitSpace)]
+pub struct GuardianConsent {
+    pub registry: Pubkey,
+    pub guardian: Pubkey,
+    pub ward: Pubkey,
+    /// `GuardianScope::bit`s of what the guardian may do for the ward
+    pub scopes: u8,
+    /// The consent lapses at this unix time; zero if it never does
+    pub expires_at: i64,
+    /// The registry authority or registered provider who granted it
+    pub granted_by: Pubkey,
+    pub granted_at: i64,
+    /// When `revoke_guardian_consent` revoked it, or zero
+    pub revoked_at: i64,
+    pub bump: u8,
+}
+
+impl GuardianConsent {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(registry: &Pubkey, guardian: &Pubkey, ward: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"guardian", registry.as_ref(), guardian.as_ref(), ward.as_ref()],
+            &crate::ID,
+        )
+        .0
+    }
+
+    /// Fails unless the consent is unrevoked, unexpired at `now` and covers `scope`
+    pub fn check(&self, scope: GuardianScope, now: i64) -> Result<()> {
+        require!(self.revoked_at == 0, HealthcareError::GuardianConsentRevoked);
+        require!(
+            self.expires_at == 0 || now < self.expires_at,
+            HealthcareError::GuardianConsentExpired
+        );
+        require!(self.scopes & scope.bit() != 0, HealthcareError::GuardianScopeMissing);
+        Ok(())
+    }
+}
+
+/// What a `GuardianConsent` lets its guardian do for the ward
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuardianScope {
+    /// `verify_eligibility_for_ward`
+    Verify,
+    /// `pin_medical_data_for_ward`
+    Pin,
+    /// Obtaining access passes in the ward's name
+    GrantAccess,
+}
+
+impl GuardianScope {
+    /// Every scope's flag
+    pub const ALL: u8 = 0b111;
+
+    /// The scope's flag in `GuardianConsent::scopes`
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Scratch state of a verification spread over several transactions
+#[account]
+#[derive(InitSpace)]
+pub struct PartialVerification {
+    /// Paid for the account, must sign every step, and gets the rent back
+    pub patient: Pubkey,
+    pub registry: Pubkey,
+    pub verifying_key: Pubkey,
+    /// `keccak(vk_bytes)` at begin; a key update would invalidate `vk_x`
+    pub vk_hash: [u8; 32],
+    pub proof: Groth16Proof,
+    /// Big-endian public inputs, which `space` sizes the account for
+    #[max_len(0)]
+    pub public_inputs: Vec<[u8; 32]>,
+    /// Inputs before this index are already folded into `vk_x`
+    pub next_input: u8,
+    pub vk_x: [u8; 64],
+    /// `ProofNullifier::seed` of the submission
+    pub seed: [u8; 32],
+    /// Last slot the verification can advance or complete in
+    pub expires_at_slot: u64,
+}
+
+impl PartialVerification {
+    pub fn space(n_public: u8) -> usize {
+        8 + Self::INIT_SPACE + 32 * n_public as usize
+    }
+
+    pub fn check_live(&self, slot: u64) -> Result<()> {
+        require!(
+            slot <= self.expires_at_slot,
+            HealthcareError::PartialVerificationExpired
+        );
+        Ok(())
+    }
+}
+
+/// The result of a verified proof, kept for the registry's `cache_ttl_slots` so
+/// re-checks of the proof skip the pairing. Lives at `[b"cache", proof_hash]`.
+#[account]
+#[derive(InitSpace)]
+pub struct VerificationCache {
+    pub result: VerificationResult,
+    #[max_len(MAX_CIRCUIT_ID_LEN)]
+    pub circuit_id: String,
+    /// Last slot the entry is served in
+    pub expires_at_slot: u64,
+    /// Paid for the account and gets the rent back from `reclaim_cache`
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+impl VerificationCache {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn is_fresh(&self, slot: u64) -> bool {
+        slot <= self.expires_at_slot
+    }
+}
+
+/// A submitter's commitment to a `verify_eligibility` submission it has yet to
+/// reveal. Lives at `[b"commitment", submitter]`.
+#[account]
+#[derive(InitSpace)]
+pub struct VerificationCommitment {
+    /// Paid for the account and gets the rent back on reveal or expiry
+    pub submitter: Pubkey,
+    pub commitment: [u8; 32],
+    pub committed_at_slot: u64,
+    /// Last slot the commitment may be revealed in
+    pub expires_at_slot: u64,
+    pub bump: u8,
+}
+
+impl VerificationCommitment {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    /// `keccak(proof || public_inputs || salt || submitter)`, as submitted
+    pub fn hash(proof: &[u8], public_inputs: &[u8], salt: &[u8; 32], submitter: &Pubkey) -> [u8; 32] {
+        keccak::hashv(&[proof, public_inputs, salt, submitter.as_ref()]).to_bytes()
+    }
+}
+
+/// A patient's pin of content on a `StorageBackend`, IPFS for pins written
+/// before there were others, at `[b"pin", patient, data_hash]` so each
+/// patient pins a given content once; an updated pin stays at the data hash
+/// it was first pinned with, see `update_pin`. The name stays, as the account's
+/// discriminator derives from it. Pins written before that live at the
+/// keypair address they were created at, and a claimed pin keeps the address
+/// of the key that pinned it.
+#[account]
+#[derive(InitSpace)]
+pub struct IpfsPinRecord {
+    /// `VERSION` of the layout the pin was written in; see `migrations`
+    pub version: u8,
+    pub patient: Pubkey,
+    /// The pinned CID, decoded: its version, 0 or 1
+    pub cid_version: u8,
+    /// Content multicodec, dag-pb for a CIDv0
+    pub multicodec: u16,
+    /// Hash function code, digest length and digest, zero-padded past the
+    /// digest. Off IPFS, the id's length and the id, and `cid_version` and
+    /// `multicodec` are zero.
+    pub multihash: [u8; PIN_MULTIHASH_LEN],
+    pub data_hash: [u8; 32],
+    pub pinned_at: i64,
+    pub access_count: u32,
+    /// Slot of `pinned_at`; zero for pins written before it was recorded
+    pub slot: u64,
+    pub registry: Pubkey,
+    /// Paid for the account and gets the rent back from `unpin_medical_data`:
+    /// the patient, or the guardian who pinned for a ward
+    pub rent_payer: Pubkey,
+    /// Last slot an `AccessPass` to the pin may be consumed in, of those issued
+    /// with the pin passed or used on it; zero for none
+    pub grants_expire_at_slot: u64,
+    /// When `record_access` last counted an access; zero for never
+    pub last_accessed_at: i64,
+    /// Whether a pinning oracle has reported the content pinned
+    pub status: PinStatus,
+    /// The oracle that reported the pin `Failed`, or whose report met its
+    /// `replication_target`; the default key while `Requested`
+    pub oracle: Pubkey,
+    /// When `oracle` settled the pin; zero while `Requested`
+    pub settled_at: i64,
+    /// Until when the pin's storage is paid for, see `renew_pin`; zero for a
+    /// pin stored for free, which never expires
+    pub paid_through: i64,
+    /// Lamports escrowed in the account, beside its rent, for the oracles that
+    /// hold the pin; see `claim_pin_bounty`
+    pub bounty_lamports: u64,
+    /// Of `bounty_lamports`, what has been released into `bounty_shares`
+    pub bounty_released: u64,
+    /// The registry's `pin_bounty_period_secs` when the pin was written
+    pub bounty_period_secs: i64,
+    /// The registry's `pin_bounty_periods` when the pin was written
+    pub bounty_periods: u16,
+    /// Of `bounty_periods`, those settled, whether released to the oracles
+    /// holding the pin or, held by none, left in escrow
+    pub bounty_periods_settled: u16,
+    /// When the last settled bounty period ended, and so the next one began;
+    /// a confirmation restarts the count from its time
+    pub bounty_settled_through: i64,
+    /// What each oracle that held the pin is owed of the bounty released so
+    /// far and hasn't claimed, whether or not it still holds it; the default
+    /// key in free entries
+    pub bounty_shares: [BountyShare; MAX_BOUNTY_SHARES],
+    /// The registry's `pin_replication_target` when the pin was written; zero
+    /// for pins written before, which one report confirms
+    pub replication_target: u8,
+    /// Oracles that reported the content pinned and haven't reported it
+    /// dropped, the default key in free slots
+    pub replicas: [Pubkey; MAX_PIN_REPLICAS],
+    /// When each of `replicas` reported the content pinned
+    pub replica_confirmed_at: [i64; MAX_PIN_REPLICAS],
+    /// Where the content is stored, and so how to read `multihash`
+    pub backend: StorageBackend,
+    /// What kind of document the content is, `Other` for pins written
+    /// before categories; see `retag_pin`
+    pub category: DataCategory,
+    /// Free-form label the patient gave the pin, if any
+    pub tag: Option<[u8; 8]>,
+    /// Times `update_pin` replaced the content; zero while the pin holds what
+    /// it was pinned with
+    pub revision: u32,
+    /// When `update_pin` last replaced the content, zero if it never has;
+    /// `pinned_at` stays when the pin was written
+    pub revised_at: i64,
+    /// The content `update_pin` replaced, oldest first, at most
+    /// `MAX_PIN_VERSIONS`; the oldest goes once it is full
+    #[max_len(0)]
+    pub history: Vec<PinVersion>,
+    /// Filecoin deals storing the content, as pinning oracles reported them
+    /// through `set_storage_deal`, at most `MAX_STORAGE_DEALS`
+    #[max_len(0)]
+    pub deals: Vec<DealInfo>,
+}
+
+/// An oracle's part of the bounty a pin released while it held the pin
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BountyShare {
+    pub oracle: Pubkey,
+    pub lamports: u64,
+}
+
+/// Where a pin stands with the IPFS cluster, as its registry's pinning
+/// oracles report it through `confirm_pin`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinStatus {
+    /// Written by `pin_medical_data` or `retry_pin`, not yet reported
+    Requested,
+    Confirmed,
+    /// The cluster couldn't pin the content; `retry_pin` or
+    /// `abandon_failed_pin` it
+    Failed,
+    /// Storage lapsed past its grace period, see `expire_pin`; workers release
+    /// the content
+    Expired,
+}
+
+/// Where a pin's content is stored, telling off-chain workers which network
+/// to pin it on and how to read the pin's id
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// Under a CID
+    Ipfs,
+    /// Under the 32-byte id of the transaction that stored it
+    Arweave,
+    /// Under the sha256 of the file's URL
+    ShadowDrive,
+    /// A partner's own store, by the code agreed with it, under up to
+    /// `MAX_CUSTOM_STORAGE_ID_LEN` bytes
+    Custom(u16),
+}
+
+/// What kind of document a pin holds, so a patient's pins of one kind are
+/// found without reading their content
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataCategory {
+    Imaging,
+    LabReport,
+    ClinicalNote,
+    Prescription,
+    Consent,
+    Other,
+}
+
+/// A Filecoin storage deal holding a pin's content
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct DealInfo {
+    pub deal_id: u64,
+    /// The storage provider's address, such as `f01234`
+    #[max_len(MAX_DEAL_PROVIDER_LEN)]
+    pub provider: String,
+    /// When the deal ends
+    pub deal_expiry: i64,
+    /// The pinning oracle that recorded the deal
+    pub oracle: Pubkey,
+    pub recorded_at: i64,
+}
+
+/// Content a pin held before `update_pin` replaced it, as the pin held it
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct PinVersion {
+    pub cid_version: u8,
+    pub multicodec: u16,
+    pub multihash: [u8; PIN_MULTIHASH_LEN],
+    pub data_hash: [u8; 32],
+    pub pinned_at: i64,
+}
+
+/// One document of a `pin_medical_data_bulk` call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PinEntry {
+    pub ipfs_cid: String,
+    pub data_hash: [u8; 32],
+    pub category: DataCategory,
+    pub tag: Option<[u8; 8]>,
+}
+
+/// Marks content a patient has pinned, at `PinContentClaim::address` of the
+/// patient and the content's `data_hash`, so no two of the patient's pins
+/// hold it, even once `update_pin` moved a pin off its own address's content.
+/// The pin holding the content owns it; pins written before claims existed
+/// hold none.
+#[account]
+#[derive(InitSpace)]
+pub struct PinContentClaim {
+    pub pin_record: Pubkey,
+}
+
+impl PinContentClaim {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+
+    pub fn address(patient: &Pubkey, data_hash: &[u8; 32]) -> Pubkey {
+        Pubkey::find_program_address(&[b"pin_content", patient.as_ref(), data_hash], &crate::ID).0
+    }
+}
+
+impl IpfsPinRecord {
+    /// Length of a pin with no `history` or `deals`; `update_pin` and
+    /// `set_storage_deal` grow the account to fit them
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+    /// v1 held the CID as its string, v2 holds it decoded, v3 appends
+    /// `grants_expire_at_slot`, v4 `last_accessed_at`, v5 `status` and
+    /// `oracle`, v6 `settled_at`, v7 `paid_through`, v8 the bounty fields,
+    /// v9 the replication fields, v10 `backend`, v11 `deals`, v12
+    /// `category` and `tag`, ahead of `deals`, and v13 `revision`,
+    /// `revised_at` and `history` ahead of them too
+    pub const VERSION: u8 = 13;
+
+    /// Length of a pin's account holding `versions` earlier versions and
+    /// `deals` storage deals
+    pub fn space_with(versions: usize, deals: usize) -> usize {
+        Self::SPACE + versions * PinVersion::INIT_SPACE + deals * DealInfo::INIT_SPACE
+    }
+
+    pub fn address(patient: &Pubkey, data_hash: &[u8; 32]) -> Pubkey {
+        Pubkey::find_program_address(&[b"pin", patient.as_ref(), data_hash], &crate::ID).0
+    }
+
+    /// Add `days` of storage to the pin, counted from `paid_through`, or from
+    /// `now` if that has passed
+    fn extend_paid_through(&mut self, now: i64, days: u64) -> Result<()> {
+        if days == 0 {
+            return Ok(());
+        }
+        self.paid_through = i64::try_from(days)
+            .ok()
+            .and_then(|days| days.checked_mul(SECS_PER_DAY))
+            .and_then(|secs| secs.checked_add(self.paid_through.max(now)))
+            .ok_or(HealthcareError::CounterOverflow)?;
+        Ok(())
+    }
+
+    /// Oracles currently counted as holding the pin's content
+    pub fn replication_achieved(&self) -> u8 {
+        self.replicas.iter().filter(|replica| **replica != Pubkey::default()).count() as u8
+    }
+
+    /// Count `oracle` among the pin's replicas from `now`; false if it already
+    /// is
+    fn add_replica(&mut self, oracle: Pubkey, now: i64) -> Result<bool> {
+        if self.replicas.contains(&oracle) {
+            return Ok(false);
+        }
+        let free = self
+            .replicas
+            .iter()
+            .position(|replica| *replica == Pubkey::default())
+            .ok_or_else(|| error!(HealthcareError::PinReplicasFull))?;
+        self.replicas[free] = oracle;
+        self.replica_confirmed_at[free] = now;
+        Ok(true)
+    }
+
+    /// Stop counting `oracle` among the pin's replicas; false if it wasn't
+    fn remove_replica(&mut self, oracle: Pubkey) -> bool {
+        match self.replicas.iter().position(|replica| *replica == oracle) {
+            Some(index) => {
+                self.replicas[index] = Pubkey::default();
+                self.replica_confirmed_at[index] = 0;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Bounty periods that ended, as of `now`, since `bounty_settled_through`
+    /// while the pin is confirmed, at most those of `bounty_periods` left to
+    /// settle
+    pub fn bounty_periods_due(&self, now: i64) -> u16 {
+        if self.status != PinStatus::Confirmed || self.bounty_period_secs <= 0 {
+            return 0;
+        }
+        let hosted = now.saturating_sub(self.bounty_settled_through).max(0) / self.bounty_period_secs;
+        u16::try_from(hosted).unwrap_or(u16::MAX).min(self.bounty_periods - self.bounty_periods_settled)
+    }
+
+    /// Lamports of the bounty the first `periods` of `bounty_periods` release
+    /// between them, all of it once every period is settled
+    pub fn bounty_released_by(&self, periods: u16) -> u64 {
+        if self.bounty_periods == 0 {
+            return 0;
+        }
+        (u128::from(self.bounty_lamports) * u128::from(periods) / u128::from(self.bounty_periods)) as u64
+    }
+
+    /// Settle the bounty periods that ended since `bounty_settled_through` as
+    /// of `now`, splitting what they release equally into the `bounty_shares`
+    /// of the oracles among the pin's replicas. Settled before the replicas
+    /// change, a period's share goes to the oracles holding the pin as it
+    /// ends. A period no replica held, or a part with no free entry or left
+    /// over from the split, stays unreleased in escrow for the patient.
+    fn settle_bounty(&mut self, now: i64) {
+        let periods = self.bounty_periods_due(now);
+        if periods == 0 {
+            return;
+        }
+        let settled = self.bounty_periods_settled + periods;
+        let due = self.bounty_released_by(settled) - self.bounty_released_by(self.bounty_periods_settled);
+        self.bounty_periods_settled = settled;
+        self.bounty_settled_through += i64::from(periods) * self.bounty_period_secs;
+        let holders = self.replication_achieved();
+        if holders == 0 {
+            return;
+        }
+        let part = due / u64::from(holders);
+        for oracle in self.replicas.into_iter().filter(|replica| *replica != Pubkey::default()) {
+            let entry = match self.bounty_shares.iter().position(|share| share.oracle == oracle) {
+                Some(index) => Some(index),
+                None => self.bounty_shares.iter().position(|share| share.oracle == Pubkey::default()),
+            };
+            if let Some(index) = entry {
+                self.bounty_shares[index].oracle = oracle;
+                self.bounty_shares[index].lamports += part;
+                self.bounty_released += part;
+            }
+        }
+    }
+
+    /// Put the pin back to `Requested` as of `now`, settling the bounty periods
+    /// hosted until then, and forget which oracles reported on it. The periods
+    /// left resume from the next confirmation.
+    fn request_again(&mut self, now: i64) {
+        self.settle_bounty(now);
+        self.status = PinStatus::Requested;
+        self.oracle = Pubkey::default();
+        self.settled_at = 0;
+        self.replicas = [Pubkey::default(); MAX_PIN_REPLICAS];
+        self.replica_confirmed_at = [0; MAX_PIN_REPLICAS];
+    }
+
+    /// Whether an `AccessPass` to the pin may still be consumed in `slot`
+    pub fn has_active_grants(&self, slot: u64) -> bool {
+        self.grants_expire_at_slot != 0 && slot <= self.grants_expire_at_slot
+    }
+
+    /// Count a pass to the pin lasting through `expires_at_slot`
+    fn track_grant(&mut self, expires_at_slot: u64) {
+        self.grants_expire_at_slot = self.grants_expire_at_slot.max(expires_at_slot);
+    }
+
+    /// Store `ipfs_cid` decoded. Fails as `check_ipfs_cid` does, and with
+    /// `InvalidCid` for a CID whose codec or multihash a pin has no room for.
+    pub fn set_cid(&mut self, ipfs_cid: &str) -> Result<()> {
+        let cid = check_ipfs_cid(ipfs_cid)?;
+        let multicodec = u16::try_from(cid.codec).map_err(|_| invalid_cid("multicodec wider than a pin holds"))?;
+        if cid.multihash.len() > PIN_MULTIHASH_LEN {
+            return Err(invalid_cid("multihash longer than a pin holds"));
+        }
+        self.cid_version = cid.version;
+        self.multicodec = multicodec;
+        self.multihash = [0; PIN_MULTIHASH_LEN];
+        self.multihash[..cid.multihash.len()].copy_from_slice(&cid.multihash);
+        Ok(())
+    }
+
+    /// Point the pin at `storage_id` on `backend`, checking the id's shape
+    /// there: a CID's string for IPFS, 32 bytes for Arweave and Shadow Drive,
+    /// and 1 to `MAX_CUSTOM_STORAGE_ID_LEN` bytes for a custom store
+    pub fn set_storage_id(&mut self, backend: StorageBackend, storage_id: &[u8]) -> Result<()> {
+        let fits = match backend {
+            StorageBackend::Ipfs => {
+                let cid = std::str::from_utf8(storage_id).map_err(|_| invalid_cid("not UTF-8"))?;
+                self.set_cid(cid)?;
+                self.backend = backend;
+                return Ok(());
+            }
+            StorageBackend::Arweave | StorageBackend::ShadowDrive => storage_id.len() == 32,
+            StorageBackend::Custom(_) => !storage_id.is_empty() && storage_id.len() <= MAX_CUSTOM_STORAGE_ID_LEN,
+        };
+        if !fits {
+            msg!("{:?} id of {} bytes", backend, storage_id.len());
+            return err!(HealthcareError::InvalidStorageId);
+        }
+        self.cid_version = 0;
+        self.multicodec = 0;
+        self.multihash = [0; PIN_MULTIHASH_LEN];
+        self.multihash[0] = storage_id.len() as u8;
+        self.multihash[1..=storage_id.len()].copy_from_slice(storage_id);
+        self.backend = backend;
+        Ok(())
+    }
+
+    /// The pinned CID as the string it was pinned from; off IPFS, the id in
+    /// lowercase hex
+    pub fn cid_string(&self) -> String {
+        if self.backend != StorageBackend::Ipfs {
+            let len = usize::from(self.multihash[0]).min(MAX_CUSTOM_STORAGE_ID_LEN);
+            return self.multihash[1..=len].iter().map(|byte| format!("{:02x}", byte)).collect();
+        }
+        let len = cid::multihash_len(&self.multihash).unwrap_or(PIN_MULTIHASH_LEN);
+        cid::Cid {
+            version: self.cid_version,
+            codec: self.multicodec.into(),
+            multihash: self.multihash[..len].to_vec(),
+        }
+        .encode()
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FederatedLearningState {
+    pub round_number: u64,
+    pub last_update: i64,
+    pub participant_count: u32,
+    /// Slot of `last_update`
+    pub slot: u64,
+}
+
+impl FederatedLearningState {
+    pub const SPACE: usize = 8 + Self::INIT_SPACE;
+}
+
+/// How far a key upload has got, from `VerifyingKeyPDA::upload_status`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UploadStatus {
+    /// Chunks are still being written; `written` is where to resume
+    InProgress { written: u32 },
+    Finalized,
+}
+
+/// Lifecycle of a circuit. `Deprecated` stops new proofs; `Revoked` also lets
+/// `sweep_revoked_records` invalidate records the circuit already produced.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CircuitStatus {
+    #[default]
+    Active,
+    Deprecated,
+    Revoked,
+}
+
+/// Pairing curve of a circuit's proofs and verifying key
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CurveId {
+    #[default]
+    Bn254,
+    /// Groth16 only, verified by builds with the `bls` feature; see
+    /// `verifier::Bls12_381Verifier`
+    Bls12_381,
+}
+
+impl CurveId {
+    /// The curve's flag in `SecurityPolicy::allowed_curves`
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Proof system of a circuit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProvingScheme {
+    /// Needs a trusted setup per circuit
+    #[default]
+    Groth16,
+    /// KZG-based with a universal setup, in the snarkjs proof layout
+    Plonk,
+}
+
+impl ProvingScheme {
+    /// The scheme's verifier for proofs over `curve`, if this build has one
+    pub fn verifier_on(self, curve: CurveId) -> Option<&'static dyn ProofVerifier> {
+        match (self, curve) {
+            (ProvingScheme::Groth16, CurveId::Bn254) => Some(&Groth16Verifier),
+            (ProvingScheme::Plonk, CurveId::Bn254) => Some(&PlonkVerifier),
+            #[cfg(feature = "bls")]
+            (ProvingScheme::Groth16, CurveId::Bls12_381) => Some(&Bls12_381Verifier),
+            _ => None,
+        }
+    }
+
+    /// The scheme's flag in `SecurityPolicy::allowed_schemes`
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// How a `VerificationRecord`'s `proof_hash` is computed
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashAlgo {
+    /// `compute_verification_hash` of the proof and its public inputs
+    #[default]
+    Keccak,
+    /// circomlib's `Poseidon(n)` over the public inputs, so a circuit can
+    /// recompute the hash of the record its proof produced. Every proof of the same
+    /// statement shares it; the nullifier still tells the proofs apart.
+    Poseidon,
+}
+
+/// What a registry measures its windows against, see `set_time_source`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeSource {
+    /// `Clock::unix_timestamp`, the stake-weighted median of validator votes,
+    /// which can drift from wall time and from slot progress
+    #[default]
+    UnixTimestamp,
+    /// `Clock::slot`. Windows set in seconds are counted in `NOMINAL_SLOT_MS`
+    /// slots, and a circuit's timestamp input carries a slot.
+    Slot,
+}
+
+impl TimeSource {
+    /// The current time in this source's unit
+    pub fn now(self, clock: &Clock) -> i64 {
+        self.at(clock.unix_timestamp, clock.slot)
+    }
+
+    /// Of a moment recorded as both `timestamp` and `slot`, the one this source reads
+    pub fn at(self, timestamp: i64, slot: u64) -> i64 {
+        match self {
+            TimeSource::UnixTimestamp => timestamp,
+            TimeSource::Slot => i64::try_from(slot).unwrap_or(i64::MAX),
+        }
+    }
+
+    /// A span of `secs` in this source's unit
+    pub fn span(self, secs: i64) -> i64 {
+        match self {
+            TimeSource::UnixTimestamp => secs,
+            TimeSource::Slot => secs.saturating_mul(1000) / NOMINAL_SLOT_MS,
+        }
+    }
+}
+
+impl HashAlgo {
+    /// Keccak is the permutation standardised as SHA-3; Poseidon has no NIST standing
+    pub fn is_nist_compliant(self) -> bool {
+        self == HashAlgo::Keccak
+    }
+
+    /// The algorithm's flag in `SecurityPolicy::allowed_hash_algos`
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// The record's `proof_hash`, given the scheme's `proof_hash` and the public
+    /// inputs as `parse_public_inputs` returns them. Poseidon digests are
+    /// big-endian, the way circom prints field elements.
+    pub fn proof_hash(self, proof_hash: [u8; 32], inputs: &[[u8; 32]]) -> Result<[u8; 32]> {
+        match self {
+            HashAlgo::Keccak => Ok(proof_hash),
+            HashAlgo::Poseidon => {
+                require!(
+                    inputs.len() <= MAX_POSEIDON_INPUTS,
+                    HealthcareError::TooManyInputsForPoseidon
+                );
+                let inputs: Vec<&[u8]> = inputs.iter().map(|input| &input[..]).collect();
+                poseidon::hashv(poseidon::Parameters::Bn254X5, poseidon::Endianness::BigEndian, &inputs)
+                    .map(|hash| hash.to_bytes())
+                    .map_err(|_| error!(HealthcareError::InvalidPublicInputEncoding))
+            }
+        }
+    }
+}
+
+/// A litigation hold on a `VerificationRecord`, see `place_hold`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HoldInfo {
+    pub placed_at: i64,
+    /// Hash of the legal order behind the hold, which the program never reads
+    pub order_hash: [u8; 32],
+}
+
+/// Lifecycle of a `VerificationRecord`. `Verified` and `Expired` keep the
+/// discriminants of the original `Active` and `Expired`, so records written
+/// before the other states existed decode unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordStatus {
+    Verified,
+    Expired,
+    /// Written, but its proof not yet accepted
+    Pending,
+    Revoked,
+    /// Under challenge; back to `Verified` or on to `Revoked` once settled
+    Disputed,
+    /// Moved to another registry by `import_record`, where it lives on
+    Migrated,
+}
+
+impl RecordStatus {
+    /// Whether a record may move from `self` to `next`. `Revoked` and `Migrated`
+    /// are final, and an expired record is only verified again by
+    /// `reverify_eligibility`.
+    pub fn can_become(self, next: RecordStatus) -> bool {
+        matches!(
+            (self, next),
+            (RecordStatus::Pending, RecordStatus::Verified | RecordStatus::Revoked)
+                | (
+                    RecordStatus::Verified,
+                    RecordStatus::Expired | RecordStatus::Revoked | RecordStatus::Disputed | RecordStatus::Migrated
+                )
+                | (RecordStatus::Disputed, RecordStatus::Verified | RecordStatus::Revoked)
+                | (RecordStatus::Expired, RecordStatus::Verified | RecordStatus::Revoked)
+        )
+    }
+}
+
+/// What a proof establishes. Records store the variant's borsh index, so new
+/// variants go last, leaving existing records decoding unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationType {
+    Eligibility,
+    Prescription,
+    Diagnosis,
+    AccessControl,
+    LabResult,
+    Immunization,
+}
+
+/// One proof of a `verify_eligibility_batch` or `verify_eligibility_anonymous` call
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofSubmission {
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<u8>,
+    pub ipfs_hash: String,
+}
+
+/// A batch submission that passed every per-proof check, awaiting the pairing
+struct BatchEntry {
+    proof: Groth16Proof,
+    inputs: Vec<[u8; 32]>,
+    seed: [u8; 32],
+    record_nonce: u64,
+    record_bump: u8,
+    nullifier_bump: u8,
+}
+
+impl BatchEntry {
+    /// Everything `verify_eligibility` checks before pairing, plus the entry's
+    /// `[record, nullifier]` accounts, its record at the patient's `record_nonce`.
+    /// Identity points are rejected here; the curve and subgroup checks come free
+    /// with the batch syscalls.
+    fn check(
+        verifying_key: &VerifyingKey,
+        registry: &HealthcareRegistry,
+        submission: &ProofSubmission,
+        format: ProofFormat,
+        patient: &Pubkey,
+        record_nonce: u64,
+        clock: &Clock,
+        accounts: &[AccountInfo],
+    ) -> Result<Self> {
+        let public_inputs = &submission.public_inputs;
+        check_ipfs_cid(&submission.ipfs_hash)?;
+        registry.check_public_inputs_len(public_inputs)?;
+        verifying_key.check_patient_binding(public_inputs, format, patient)?;
+        verifying_key.check_domain_binding(public_inputs, format, registry)?;
+        verifying_key.check_freshness(public_inputs, format, registry.time_source, clock)?;
+        verifying_key.check_input_count(public_inputs)?;
+        let inputs = parse_public_inputs(public_inputs, format)?;
+        let proof = Groth16Proof::decode(&submission.proof, format)?;
+        require!(
+            ![&proof.a[..], &proof.b[..], &proof.c[..]].into_iter().any(is_identity),
+            HealthcareError::InvalidProofPoint
+        );
+
+        let seed = ProofNullifier::seed(&submission.proof, format, public_inputs);
+        let (record, record_bump) = derive_verification_pda(patient, VerificationType::Eligibility, record_nonce);
+        let (nullifier, nullifier_bump) = Pubkey::find_program_address(&[b"nullifier", &seed], &crate::ID);
+        require!(
+            accounts[0].key() == record && accounts[1].key() == nullifier,
+            HealthcareError::BatchAccountMismatch
+        );
+        require!(
+            accounts[0].data_is_empty() && accounts[1].data_is_empty(),
+            HealthcareError::ProofAlreadyUsed
+        );
+        Ok(BatchEntry {
+            proof,
+            inputs,
+            seed,
+            record_nonce,
+            record_bump,
+            nullifier_bump,
+        })
+    }
+}
+
+/// Public input conventions a circuit opts into when its key is registered, and
+/// what it charges per proof
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct VkConfig {
+    /// Number of 32-byte public inputs every proof must carry
+    pub n_public: u8,
+    /// Public input 0 is `patient_binding(patient)`
+    pub binds_patient: bool,
+    /// Public input slot holding `registry_domain(registry)`
+    pub domain_input: Option<u8>,
+    /// Public input slot holding a credential nullifier, for anonymous verification
+    pub nullifier_input: Option<u8>,
+    /// Public input slot holding the patient's next verification nonce
+    pub nonce_input: Option<u8>,
+    /// The last public input is a unix timestamp at most this many seconds old
+    pub freshness_window_secs: Option<i64>,
+    /// Records expire this long after they are written; the registry's
+    /// `default_validity_secs` applies when unset
+    pub validity_secs: Option<i64>,
+    /// Pairing curve the circuit was compiled for
+    pub curve: CurveId,
+    pub scheme: ProvingScheme,
+    /// Lamports paid to `fee_recipient` for each proof recorded, at most the
+    /// registry's `max_circuit_fee`
+    pub fee_lamports: u64,
+    pub fee_recipient: Pubkey,
+    /// Eligibility proofs must be co-signed by an active registered provider
+    pub requires_provider: bool,
+}
+
+impl VkConfig {
+    /// The verifier of the circuit's scheme and curve, if this build has one
+    pub fn verifier(&self) -> Option<&'static dyn ProofVerifier> {
+        self.scheme.verifier_on(self.curve)
+    }
+
+    /// Slot of the timestamp input, always the last one
+    pub fn timestamp_input(&self) -> Option<u8> {
+        self.freshness_window_secs.map(|_| self.n_public.saturating_sub(1))
+    }
+
+    /// The domain slot must exist and not overlap the patient or timestamp slots
+    pub fn has_valid_domain_input(&self) -> bool {
+        match self.domain_input {
+            None => true,
+            Some(index) => {
+                index < self.n_public
+                    && !(self.binds_patient && index == 0)
+                    && self.timestamp_input() != Some(index)
+            }
+        }
+    }
+
+    pub fn has_valid_freshness_window(&self) -> bool {
+        match self.freshness_window_secs {
+            None => true,
+            Some(window) => window > 0 && !(self.binds_patient && self.timestamp_input() == Some(0)),
+        }
+    }
+
+    pub fn has_valid_validity_period(&self) -> bool {
+        match self.validity_secs {
+            None => true,
+            Some(validity_secs) => validity_secs > 0,
+        }
+    }
+
+    /// The nullifier slot must exist and not overlap any other bound slot
+    pub fn has_valid_nullifier_input(&self) -> bool {
+        match self.nullifier_input {
+            None => true,
+            Some(index) => {
+                index < self.n_public
+                    && !(self.binds_patient && index == 0)
+                    && self.domain_input != Some(index)
+                    && self.timestamp_input() != Some(index)
+            }
+        }
+    }
+
+    /// The nonce slot must exist and not overlap any other bound slot
+    pub fn has_valid_nonce_input(&self) -> bool {
+        match self.nonce_input {
+            None => true,
+            Some(index) => {
+                index < self.n_public
+                    && !(self.binds_patient && index == 0)
+                    && self.domain_input != Some(index)
+                    && self.nullifier_input != Some(index)
+                    && self.timestamp_input() != Some(index)
+            }
+        }
+    }
+
+    /// A fee needs somewhere to go
+    pub fn has_valid_fee_recipient(&self) -> bool {
+        self.fee_lamports == 0 || self.fee_recipient != Pubkey::default()
+    }
+}
+
+/// Serialization of a submitted Groth16 proof and its public inputs. The sizes
+/// are BN254's; BLS12-381 proofs come `Compressed` only, see
+/// `verifier::Bls12_381Verifier`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProofFormat {
+    /// arkworks `serialize_uncompressed`: A (64) | B (128) | C (64), little-endian
+    Uncompressed,
+    /// arkworks `serialize_compressed`: A (32) | B (64) | C (32), little-endian
+    Compressed,
+    /// circom/snarkjs: A (64) | B (128) | C (64) with big-endian coordinates and
+    /// c1 before c0 in each G2 coordinate. Public signals are big-endian as well.
+    SnarkJs,
+}
+
+impl ProofFormat {
+    pub fn proof_len(self) -> usize {
+        match self {
+            ProofFormat::Uncompressed | ProofFormat::SnarkJs => 2 * G1_LEN + G2_LEN,
+            ProofFormat::Compressed => G1_COMPRESSED_LEN + G2_COMPRESSED_LEN + G1_COMPRESSED_LEN,
+        }
+    }
+
+    /// Whether public inputs are packed as big-endian scalars
+    pub fn big_endian_inputs(self) -> bool {
+        self == ProofFormat::SnarkJs
+    }
+}
+
+// Context structs (unchanged)
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = authority, space = HealthcareRegistry::SPACE)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts are passed in field order, which `accounts::VerifyEligibility` and,
+/// under the `cpi` feature, `cpi::accounts::VerifyEligibility` follow
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    proof: Vec<u8>,
+    proof_format: ProofFormat,
+    public_inputs: Vec<u8>,
+    ipfs_hash: String,
+    circuit_id: String,
+    record_nonce: u64,
+)]
+pub struct VerifyEligibility<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = patient,
+        space = VerificationRecord::SPACE,
+        seeds = [
+            b"verification",
+            patient.key().as_ref(),
+            &[VerificationType::Eligibility as u8],
+            &record_nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::Eligibility, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// `init_if_needed` so a replay reaches the handler and fails with
+    /// `ProofAlreadyUsed` instead of the system program's "already in use"
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = ProofNullifier::SPACE,
+        seeds = [b"nullifier", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub nullifier: Account<'info, ProofNullifier>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// CHECK: the proof's `VerificationCache` PDA, checked by address before it
+    /// is written and by owner and contents before it is read
+    #[account(mut)]
+    pub cache: Option<UncheckedAccount<'info>>,
+    /// The patient's `VerificationCommitment` to this submission, checked against
+    /// the submission by the handler
+    #[account(mut, close = patient)]
+    pub commitment: Option<Account<'info, VerificationCommitment>>,
+    /// The patient's `PatientIndex`, holding the record nonce and, for circuits
+    /// that use one, the verification nonce
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the patient's latest eligibility record, their
+    /// `last_record_by_type`, checked by address and read if it still holds a
+    /// record; omitted for the first
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+    /// A provider attesting the check was made in their presence, required by
+    /// circuits that set `requires_provider`; passed with its registration
+    pub provider: Option<Signer<'info>>,
+    #[account(mut)]
+    pub provider_registration: Option<Account<'info, ProviderRegistration>>,
+    /// CHECK: the instructions sysvar, checked by address when loaded; needed
+    /// only with an `attestation`
+    pub instructions: Option<UncheckedAccount<'info>>,
+    /// CHECK: the registry's verification mint, checked by address when
+    /// `mint_token` is set; this and the three accounts after it may be omitted
+    /// otherwise
+    #[account(mut)]
+    pub verification_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: the patient's associated token account of the mint, which the
+    /// associated token program checks and creates if needed
+    #[account(mut)]
+    pub patient_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Program<'info, Token2022>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    proof: Vec<u8>,
+    proof_format: ProofFormat,
+    public_inputs: Vec<u8>,
+    ipfs_hash: String,
+    circuit_id: String,
+)]
+pub struct ReverifyEligibility<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = verification.patient_pubkey == patient.key() @ HealthcareError::NotRecordPatient,
+        constraint = verification.status != RecordStatus::Revoked @ HealthcareError::RecordRevoked,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::Eligibility, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// `init_if_needed` so a replay reaches the handler and fails with
+    /// `ProofAlreadyUsed` instead of the system program's "already in use"
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = ProofNullifier::SPACE,
+        seeds = [b"nullifier", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub nullifier: Account<'info, ProofNullifier>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    #[account(mut, seeds = [b"patient", patient.key().as_ref()], bump = patient_index.bump)]
+    pub patient_index: Account<'info, PatientIndex>,
+}
+
+/// `VerifyEligibility` with a relayer in place of the patient and the
+/// credential's `CredentialNullifier` for the epoch
+#[derive(Accounts)]
+#[instruction(
+    proof_format: ProofFormat,
+    submission: ProofSubmission,
+    circuit_id: String,
+    credential: [u8; 32],
+    epoch: u64,
+)]
+pub struct VerifyEligibilityAnonymous<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(init, payer = relayer, space = VerificationRecord::SPACE)]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = verifying_key.load()?.supports_anonymous() @ HealthcareError::AnonymousModeUnsupported,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::Eligibility, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = ProofNullifier::SPACE,
+        seeds = [
+            b"nullifier",
+            ProofNullifier::seed(&submission.proof, proof_format, &submission.public_inputs).as_ref(),
+        ],
+        bump,
+    )]
+    pub nullifier: Account<'info, ProofNullifier>,
+    /// `init_if_needed` for the same reason as `nullifier`: a reused credential
+    /// fails with `CredentialAlreadyUsed`
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = CredentialNullifier::SPACE,
+        seeds = [b"credential", verifying_key.key().as_ref(), credential.as_ref(), &epoch.to_le_bytes()],
+        bump,
+    )]
+    pub credential_nullifier: Account<'info, CredentialNullifier>,
+    /// CHECK: the credential's nullifier of the epoch before, checked by address
+    /// and read if it exists; needed only under a cooldown
+    pub previous_credential_nullifier: Option<UncheckedAccount<'info>>,
+    /// Signs and pays; unrelated to the patient
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof: Vec<u8>, proof_format: ProofFormat, public_inputs: Vec<u8>, circuit_id: String)]
+pub struct BeginVerification<'info> {
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = verifying_key.load()?.scheme() == ProvingScheme::Groth16 @ HealthcareError::UnsupportedProvingScheme,
+        constraint = verifying_key.load()?.curve() == CurveId::Bn254 @ HealthcareError::UnsupportedCurve,
+        constraint = registry.is_circuit_for(VerificationType::Eligibility, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(init, payer = patient, space = PartialVerification::space(verifying_key.load()?.n_public))]
+    pub partial: Account<'info, PartialVerification>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof: Vec<u8>, proof_format: ProofFormat, public_inputs: Vec<u8>, circuit_id: String)]
+pub struct RecordFailedProof<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceVerification<'info> {
+    #[account(mut, has_one = patient, has_one = verifying_key)]
+    pub partial: Account<'info, PartialVerification>,
+    #[account(
+        constraint = inspect_vk(&verifying_key, |key| keccak::hash(key.vk_bytes()).to_bytes())? == partial.vk_hash
+            @ HealthcareError::VerifyingKeyChanged,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    pub patient: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(record_nonce: u64)]
+pub struct CompleteVerification<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(mut, close = patient, has_one = patient, has_one = registry, has_one = verifying_key)]
+    pub partial: Account<'info, PartialVerification>,
+    #[account(
+        mut,
+        constraint = inspect_vk(&verifying_key, |key| keccak::hash(key.vk_bytes()).to_bytes())? == partial.vk_hash
+            @ HealthcareError::VerifyingKeyChanged,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = registry.is_circuit_for(VerificationType::Eligibility, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(
+        init,
+        payer = patient,
+        space = VerificationRecord::SPACE,
+        seeds = [
+            b"verification",
+            patient.key().as_ref(),
+            &[VerificationType::Eligibility as u8],
+            &record_nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = ProofNullifier::SPACE,
+        seeds = [b"nullifier", partial.seed.as_ref()],
+        bump,
+    )]
+    pub nullifier: Account<'info, ProofNullifier>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The patient's `PatientIndex`, holding the record nonce and, for circuits
+    /// that use one, the verification nonce
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the patient's latest eligibility record, as for `VerifyEligibility`
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimVerification<'info> {
+    #[account(mut, close = patient, has_one = patient)]
+    pub partial: Account<'info, PartialVerification>,
+    #[account(mut)]
+    pub patient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof: Vec<u8>, proof_format: ProofFormat, public_inputs: Vec<u8>, circuit_id: String)]
+pub struct VerifyProofReadonly<'info> {
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof: Vec<u8>, proof_format: ProofFormat, public_inputs: Vec<u8>, circuit_id: String)]
+pub struct VerifyPrescription<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = patient,
+        space = PrescriptionRecord::SPACE,
+        seeds = [b"prescription", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub prescription: Account<'info, PrescriptionRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::Prescription, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The patient's `PatientIndex`, counting the prescription and holding the
+    /// verification nonce of circuits that use one
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the patient's latest prescription, their `last_record_by_type`, checked
+    /// by address and read if it still holds one; omitted for the first
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    proof: Vec<u8>,
+    proof_format: ProofFormat,
+    public_inputs: Vec<u8>,
+    circuit_id: String,
+    icd10_commitment: [u8; 32],
+    diagnosing_provider: Pubkey,
+)]
+pub struct VerifyDiagnosis<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = patient,
+        space = DiagnosisRecord::SPACE,
+        seeds = [b"diagnosis", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub diagnosis: Account<'info, DiagnosisRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::Diagnosis, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// Only exists for a provider registered under the registry
+    #[account(
+        seeds = [b"provider", registry.key().as_ref(), diagnosing_provider.as_ref()],
+        bump = provider_registration.bump,
+        constraint = provider_registration.active @ HealthcareError::ProviderInactive,
+    )]
+    pub provider_registration: Account<'info, ProviderRegistration>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The patient's `PatientIndex`, counting the diagnosis and holding the
+    /// verification nonce of circuits that use one
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    #[account(
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        constraint = pin_record.patient == patient.key() @ HealthcareError::PinRecordPatientMismatch,
+        // Implied by the `Confirmed` check, but tells a failed pin apart
+        constraint = pin_record.status != PinStatus::Failed @ HealthcareError::IpfsPinningFailed,
+        constraint = pin_record.status == PinStatus::Confirmed @ HealthcareError::PinNotConfirmed,
+    )]
+    pub pin_record: Option<Account<'info, IpfsPinRecord>>,
+    /// CHECK: the patient's latest diagnosis, their `last_record_by_type`, checked
+    /// by address and read if it still holds one; omitted for the first
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    proof: Vec<u8>,
+    proof_format: ProofFormat,
+    public_inputs: Vec<u8>,
+    circuit_id: String,
+    result_commitment: [u8; 32],
+    lab: Pubkey,
+)]
+pub struct VerifyLabResult<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = patient,
+        space = LabResultRecord::SPACE,
+        seeds = [b"lab_result", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub lab_result: Account<'info, LabResultRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::LabResult, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// Only exists for a provider registered under the registry
+    #[account(
+        seeds = [b"provider", registry.key().as_ref(), lab.as_ref()],
+        bump = provider_registration.bump,
+        constraint = provider_registration.active @ HealthcareError::ProviderInactive,
+    )]
+    pub provider_registration: Account<'info, ProviderRegistration>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The patient's `PatientIndex`, counting the lab result and holding the
+    /// verification nonce of circuits that use one
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the patient's latest lab result, their `last_record_by_type`, checked
+    /// by address and read if it still holds one; omitted for the first
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    proof: Vec<u8>,
+    proof_format: ProofFormat,
+    public_inputs: Vec<u8>,
+    circuit_id: String,
+    status_commitment: [u8; 32],
+    provider: Pubkey,
+)]
+pub struct VerifyImmunization<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = patient,
+        space = ImmunizationRecord::SPACE,
+        seeds = [b"immunization", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub immunization: Account<'info, ImmunizationRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::Immunization, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// Only exists for a provider registered under the registry
+    #[account(
+        seeds = [b"provider", registry.key().as_ref(), provider.as_ref()],
+        bump = provider_registration.bump,
+        constraint = provider_registration.active @ HealthcareError::ProviderInactive,
+    )]
+    pub provider_registration: Account<'info, ProviderRegistration>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The patient's `PatientIndex`, counting the immunization and holding the
+    /// verification nonce of circuits that use one
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the patient's latest immunization, their `last_record_by_type`, checked
+    /// by address and read if it still holds one; omitted for the first
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDiagnosis<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = diagnosis.registry == registry.key() @ HealthcareError::RecordRegistryMismatch,
+        constraint = diagnosis.revoked_at == 0 @ HealthcareError::AlreadyRevoked,
+    )]
+    pub diagnosis: Account<'info, DiagnosisRecord>,
+    /// CHECK: the diagnosis's `PatientIndex` by address
+    #[account(mut, seeds = [b"patient", diagnosis.patient.as_ref()], bump)]
+    pub patient_index: UncheckedAccount<'info>,
+    /// The diagnosing provider or the registry authority
+    pub revoker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    proof: Vec<u8>,
+    proof_format: ProofFormat,
+    public_inputs: Vec<u8>,
+    circuit_id: String,
+    resource: Pubkey,
+)]
+pub struct VerifyAccessControl<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// `init_if_needed` so a fresh proof replaces an earlier pass to the resource
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = AccessPass::SPACE,
+        seeds = [b"access_pass", patient.key().as_ref(), resource.as_ref()],
+        bump,
+    )]
+    pub access_pass: Account<'info, AccessPass>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::AccessControl, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// `init_if_needed` so a replay reaches the handler and fails with
+    /// `ProofAlreadyUsed` instead of the system program's "already in use"
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = ProofNullifier::SPACE,
+        seeds = [b"nullifier", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub nullifier: Account<'info, ProofNullifier>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The patient's `PatientIndex`, counting the pass and holding the
+    /// verification nonce of circuits that use one
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// `resource` when it is one of the patient's `IpfsPinRecord`s, which then
+    /// waits for the pass to expire before it can be unpinned
+    #[account(
+        mut,
+        address = resource @ HealthcareError::AccessPassResourceMismatch,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.patient == patient.key() @ HealthcareError::PinRecordPatientMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        // Implied by the `Confirmed` check, but tells a failed pin apart
+        constraint = pin_record.status != PinStatus::Failed @ HealthcareError::IpfsPinningFailed,
+        constraint = pin_record.status == PinStatus::Confirmed @ HealthcareError::PinNotConfirmed,
+    )]
+    pub pin_record: Option<Account<'info, IpfsPinRecord>>,
+    /// CHECK: the patient's latest access pass, their `last_record_by_type`,
+    /// checked by address and read if it still holds one; omitted for the
+    /// first. It may be `access_pass` itself, for a pass to the same resource.
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(resource: Pubkey)]
+pub struct ConsumeAccessPass<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(mut, has_one = registry)]
+    pub access_pass: Account<'info, AccessPass>,
+    /// The resource the pass is presented to
+    #[account(address = resource @ HealthcareError::AccessPassResourceMismatch)]
+    pub resource_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordAccess<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        // Implied by the `Confirmed` check, but tells a failed pin apart
+        constraint = pin_record.status != PinStatus::Failed @ HealthcareError::IpfsPinningFailed,
+        constraint = pin_record.status == PinStatus::Confirmed @ HealthcareError::PinNotConfirmed,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    /// The accessor's pass to the pin; the pin's patient may omit it. Checked
+    /// by the handler, so a pass that grants nothing is refused as
+    /// `AccessDenied`.
+    #[account(mut)]
+    pub access_pass: Option<Account<'info, AccessPass>>,
+    pub accessor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordRefill<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(mut, has_one = registry)]
+    pub prescription: Account<'info, PrescriptionRecord>,
+    /// Only exists for a pharmacy registered under the prescription's registry
+    #[account(
+        seeds = [b"pharmacy", prescription.registry.as_ref(), pharmacy.key().as_ref()],
+        bump = registration.bump,
+    )]
+    pub registration: Account<'info, PharmacyRegistration>,
+    pub pharmacy: Signer<'info>,
+}
+
+/// Per-proof record and nullifier PDAs are passed in `remaining_accounts`
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(proof_format: ProofFormat, submissions: Vec<ProofSubmission>, circuit_id: String)]
+pub struct VerifyEligibilityBatch<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = verifying_key.load()?.scheme() == ProvingScheme::Groth16 @ HealthcareError::UnsupportedProvingScheme,
+        constraint = verifying_key.load()?.curve() == CurveId::Bn254 @ HealthcareError::UnsupportedCurve,
+        constraint = registry.is_circuit_for(VerificationType::Eligibility, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    /// The patient's `PatientIndex`, holding the record nonce and, for circuits
+    /// that use one, the verification nonce
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the patient's latest eligibility record, as for `VerifyEligibility`
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_id: String, total_len: u32, config: VkConfig)]
+pub struct RegisterVerifyingKey<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = VerifyingKeyPDA::is_valid_circuit_id(&circuit_id) @ HealthcareError::InvalidCircuitId,
+        constraint = config.verifier().is_some() @ HealthcareError::UnsupportedCurve,
+        constraint = registry.security_policy.allows_config(&config) @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = total_len > 0 && total_len <= MAX_VK_LEN @ HealthcareError::VerifyingKeyTooLarge,
+        constraint = config.n_public > 0
+            && config.verifier().is_some_and(|verifier| total_len == verifier.vk_len(config.n_public))
+            @ HealthcareError::PublicInputCountMismatch,
+        constraint = config.has_valid_domain_input() @ HealthcareError::InvalidDomainInput,
+        constraint = config.has_valid_nullifier_input() @ HealthcareError::InvalidNullifierInput,
+        constraint = config.has_valid_nonce_input() @ HealthcareError::InvalidNonceInput,
+        constraint = config.has_valid_freshness_window() @ HealthcareError::InvalidFreshnessWindow,
+        constraint = config.has_valid_validity_period() @ HealthcareError::InvalidValidityPeriod,
+        constraint = config.fee_lamports <= registry.max_circuit_fee @ HealthcareError::CircuitFeeTooHigh,
+        constraint = config.has_valid_fee_recipient() @ HealthcareError::InvalidFeeRecipient,
+    )]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = authority,
+        space = VerifyingKeyPDA::initial_space(total_len),
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WriteVkChunk<'info> {
+    #[account(mut, has_one = authority)]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    pub authority: Signer<'info>,
+}
+
+/// `realloc` runs before the other constraints, so they see the account at `new_len`
+#[derive(Accounts)]
+#[instruction(new_len: u32)]
+pub struct ResizeVkAccount<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        realloc = new_len as usize,
+        realloc::payer = authority,
+        realloc::zero = false,
+        constraint = !verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyAlreadyFinalized,
+        constraint = new_len as usize >= VerifyingKeyPDA::HEADER_LEN
+            && new_len as usize <= VerifyingKeyPDA::space(verifying_key.load()?.total_len)
+            @ HealthcareError::InvalidVkAccountSize,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeVk<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_id: String)]
+pub struct AbortVkUpload<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+        constraint = !verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyAlreadyFinalized,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_id: String)]
+pub struct GcStaleUpload<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+        constraint = !verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyAlreadyFinalized,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// CHECK: only receives the rent, and `has_one` pins it to the key's authority
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTypeCircuit<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized)]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVerificationMint<'info> {
+    #[account(has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// CHECK: created here as a Token-2022 mint at its PDA
+    #[account(mut, seeds = [b"verification_mint", registry.key().as_ref()], bump)]
+    pub verification_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(pharmacy: Pubkey)]
+pub struct RegisterPharmacy<'info> {
+    #[account(has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = authority,
+        space = PharmacyRegistration::SPACE,
+        seeds = [b"pharmacy", registry.key().as_ref(), pharmacy.as_ref()],
+        bump,
+    )]
+    pub registration: Account<'info, PharmacyRegistration>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(insurer: Pubkey)]
+pub struct RegisterInsurer<'info> {
+    #[account(has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = authority,
+        space = InsurerRegistration::SPACE,
+        seeds = [b"insurer", registry.key().as_ref(), insurer.as_ref()],
+        bump,
+    )]
+    pub registration: Account<'info, InsurerRegistration>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(provider: Pubkey)]
+pub struct RegisterProvider<'info> {
+    #[account(has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = authority,
+        space = ProviderRegistration::SPACE,
+        seeds = [b"provider", registry.key().as_ref(), provider.as_ref()],
+        bump,
+    )]
+    pub registration: Account<'info, ProviderRegistration>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProviderActive<'info> {
+    #[account(has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(mut, has_one = registry)]
+    pub registration: Account<'info, ProviderRegistration>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProviderQuota<'info> {
+    #[account(has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(mut, has_one = registry)]
+    pub registration: Account<'info, ProviderRegistration>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSecurityPolicy<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDisputeWindow<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetVerificationCooldown<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTimeSource<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDefaultValidity<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGcBounty<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxMetadataLen<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCpiEvents<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDeniedAccessEvents<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFailedPinTimeout<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPinStoragePrice<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPinBountyTerms<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPinReplicationTarget<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDealExpiryWarning<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxAccessPassSlots<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddPinningOracle<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemovePinningOracle<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxCircuitFee<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proof_hash: [u8; 32])]
+pub struct CheckCached<'info> {
+    #[account(seeds = [b"cache", proof_hash.as_ref()], bump = cache.bump)]
+    pub cache: Account<'info, VerificationCache>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimCache<'info> {
+    #[account(mut, close = payer, has_one = payer)]
+    pub cache: Account<'info, VerificationCache>,
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CommitVerification<'info> {
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = submitter,
+        space = VerificationCommitment::SPACE,
+        seeds = [b"commitment", submitter.key().as_ref()],
+        bump,
+    )]
+    pub pending: Account<'info, VerificationCommitment>,
+    #[account(mut)]
+    pub submitter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimCommitment<'info> {
+    #[account(mut, close = submitter, has_one = submitter)]
+    pub commitment: Account<'info, VerificationCommitment>,
+    #[account(mut)]
+    pub submitter: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PrepareVk<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = verifying_key.load()?.scheme() == ProvingScheme::Groth16 @ HealthcareError::UnsupportedProvingScheme,
+        constraint = verifying_key.load()?.curve() == CurveId::Bn254 @ HealthcareError::UnsupportedCurve,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_vk_bytes_hash: [u8; 32], total_len: u32)]
+pub struct ProposeVkUpdate<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = total_len > 0 && total_len <= MAX_VK_LEN @ HealthcareError::VerifyingKeyTooLarge,
+    )]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        has_one = authority,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        // A rotated key must prove the same shape of statement
+        constraint = total_len == verifying_key.load()?.expected_len()
+            @ HealthcareError::PublicInputCountMismatch,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(
+        init,
+        payer = authority,
+        space = VkUpdateProposal::space(total_len),
+        seeds = [b"vk_update", verifying_key.key().as_ref()],
+        bump,
+    )]
+    pub proposal: Account<'info, VkUpdateProposal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WriteVkUpdateChunk<'info> {
+    #[account(has_one = authority)]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(
+        mut,
+        seeds = [b"vk_update", verifying_key.key().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, VkUpdateProposal>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateVkUpdate<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+        realloc = VerifyingKeyPDA::space(proposal.total_len),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"vk_update", verifying_key.key().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, VkUpdateProposal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelVkUpdate<'info> {
+    #[account(has_one = authority)]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"vk_update", verifying_key.key().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, VkUpdateProposal>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCircuitStatus<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    pub authority: Signer<'info>,
+}
+
+/// Records to revoke are passed writable in `remaining_accounts`, each followed
+/// by its patient's `PatientIndex` address
+#[derive(Accounts)]
+pub struct SweepRevokedRecords<'info> {
+    /// The registry the records were written under, which counts them revoked
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(constraint = verifying_key.load()?.status() == CircuitStatus::Revoked @ HealthcareError::CircuitNotRevoked)]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeVerification<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = verification.status != RecordStatus::Revoked @ HealthcareError::AlreadyRevoked,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    /// CHECK: the record's `PatientIndex` by address, updated only if it holds
+    /// one; anonymous records have none
+    #[account(mut, seeds = [b"patient", verification.patient_pubkey.as_ref()], bump)]
+    pub patient_index: UncheckedAccount<'info>,
+    /// The registry authority or the record's patient
+    pub revoker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BurnVerificationToken<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = verification.status == RecordStatus::Revoked @ HealthcareError::RecordNotRevoked,
+        constraint = verification.token_mint == verification_mint.key() @ HealthcareError::NoVerificationToken,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    /// CHECK: the registry's verification mint, signing as its permanent delegate
+    #[account(mut, seeds = [b"verification_mint", registry.key().as_ref()], bump)]
+    pub verification_mint: UncheckedAccount<'info>,
+    /// CHECK: the record's patient's associated token account of the mint
+    #[account(
+        mut,
+        address = anchor_spl::associated_token::get_associated_token_address_with_program_id(
+            &verification.patient_pubkey,
+            &verification_mint.key(),
+            &Token2022::id(),
+        ) @ HealthcareError::InvalidTokenAccount,
+    )]
+    pub patient_token_account: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeVerification<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        init,
+        payer = disputer,
+        space = VerificationDispute::SPACE,
+        seeds = [b"dispute", verification.key().as_ref()],
+        bump,
+    )]
+    pub dispute: Account<'info, VerificationDispute>,
+    /// CHECK: the record's `PatientIndex` by address, updated only if it holds
+    /// one; anonymous records have none
+    #[account(mut, seeds = [b"patient", verification.patient_pubkey.as_ref()], bump)]
+    pub patient_index: UncheckedAccount<'info>,
+    /// The disputer's registration as an insurer; omitted when the authority disputes
+    #[account(
+        seeds = [b"insurer", registry.key().as_ref(), disputer.key().as_ref()],
+        bump = insurer_registration.bump,
+    )]
+    pub insurer_registration: Option<Account<'info, InsurerRegistration>>,
+    #[account(mut)]
+    pub disputer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        mut,
+        close = disputer,
+        has_one = disputer,
+        seeds = [b"dispute", verification.key().as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, VerificationDispute>,
+    /// CHECK: the record's `PatientIndex` by address, updated only if it holds
+    /// one; anonymous records have none
+    #[account(mut, seeds = [b"patient", verification.patient_pubkey.as_ref()], bump)]
+    pub patient_index: UncheckedAccount<'info>,
+    /// CHECK: receives the dispute's rent; must be its disputer
+    #[account(mut)]
+    pub disputer: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceHold<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.version == VerificationRecord::VERSION @ HealthcareError::RecordNeedsMigration,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseHold<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, service_code_commitment: [u8; 32], claim_nullifier: [u8; 32])]
+pub struct SubmitClaim<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = verification.patient_pubkey == patient.key() @ HealthcareError::ClaimPatientMismatch,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        init,
+        payer = patient,
+        space = ClaimRecord::SPACE,
+        seeds = [b"claim", registry.key().as_ref(), &registry.claim_count.to_le_bytes()],
+        bump,
+    )]
+    pub claim: Account<'info, ClaimRecord>,
+    /// `init_if_needed` so a duplicate reaches the handler and fails with
+    /// `DuplicateClaim` instead of the system program's "already in use"
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = ClaimNullifier::SPACE,
+        seeds = [b"claim_nullifier", registry.key().as_ref(), claim_nullifier.as_ref()],
+        bump,
+    )]
+    pub nullifier: Account<'info, ClaimNullifier>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DesignateClaimPayee<'info> {
+    #[account(
+        mut,
+        has_one = patient,
+        constraint = claim.status == ClaimStatus::Submitted @ HealthcareError::ClaimAlreadyAdjudicated,
+    )]
+    pub claim: Account<'info, ClaimRecord>,
+    #[account(
+        seeds = [b"provider", claim.registry.as_ref(), provider_registration.provider.as_ref()],
+        bump = provider_registration.bump,
+        constraint = provider_registration.active @ HealthcareError::ProviderInactive,
+    )]
+    pub provider_registration: Account<'info, ProviderRegistration>,
+    pub patient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundClaimEscrow<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry,
+        constraint = claim.status == ClaimStatus::Submitted @ HealthcareError::ClaimAlreadyAdjudicated,
+    )]
+    pub claim: Account<'info, ClaimRecord>,
+    #[account(
+        init,
+        payer = insurer,
+        space = ClaimEscrow::SPACE,
+        seeds = [b"claim_escrow", claim.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Account<'info, ClaimEscrow>,
+    #[account(
+        seeds = [b"insurer", claim.registry.as_ref(), insurer.key().as_ref()],
+        bump = insurer_registration.bump,
+    )]
+    pub insurer_registration: Account<'info, InsurerRegistration>,
+    #[account(mut)]
+    pub insurer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdjudicateClaim<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry,
+        constraint = claim.status == ClaimStatus::Submitted @ HealthcareError::ClaimAlreadyAdjudicated,
+        constraint = claim.insurer == Some(insurer.key()) @ HealthcareError::ClaimInsurerMismatch,
+    )]
+    pub claim: Account<'info, ClaimRecord>,
+    #[account(
+        mut,
+        close = insurer,
+        has_one = claim,
+        seeds = [b"claim_escrow", claim.key().as_ref()],
+        bump = escrow.bump,
+    )]
+    pub escrow: Account<'info, ClaimEscrow>,
+    /// CHECK: only credited, and only at the claim's `payee`
+    #[account(mut, address = claim.payee @ HealthcareError::ClaimPayeeMismatch)]
+    pub payee: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub insurer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseClaimNullifier<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        has_one = registry,
+        has_one = patient,
+        constraint = claim.status == ClaimStatus::Denied @ HealthcareError::ClaimNotDenied,
+        constraint = claim.insurer == Some(insurer.key()) @ HealthcareError::ClaimInsurerMismatch,
+    )]
+    pub claim: Account<'info, ClaimRecord>,
+    #[account(
+        mut,
+        close = patient,
+        has_one = claim,
+        seeds = [b"claim_nullifier", claim.registry.as_ref(), claim.claim_nullifier.as_ref()],
+        bump = nullifier.bump,
+    )]
+    pub nullifier: Account<'info, ClaimNullifier>,
+    /// CHECK: receives the nullifier's rent, which it paid; `has_one` on `claim`
+    #[account(mut)]
+    pub patient: UncheckedAccount<'info>,
+    pub insurer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32], start_seq: u64)]
+pub struct CommitCheckpoint<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init,
+        payer = authority,
+        space = Checkpoint::SPACE,
+        seeds = [b"checkpoint", registry.key().as_ref(), &start_seq.to_le_bytes()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, Checkpoint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.version == VerificationRecord::VERSION @ HealthcareError::RecordNeedsMigration,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = verification.patient_pubkey == patient.key() @ HealthcareError::NotRecordPatient,
+        constraint = verification.status != RecordStatus::Revoked @ HealthcareError::RecordRevoked,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVerificationRecord<'info> {
+    /// The registry the record was written under, checked by the handler
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// CHECK: a `VerificationRecord` in a legacy layout, which `Account` can't
+    /// read; the handler checks its discriminator and version
+    #[account(mut, owner = crate::ID)]
+    pub verification: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePinRecord<'info> {
+    /// The registry the pin was written under, checked by the handler
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// CHECK: an `IpfsPinRecord` in an earlier layout, which `Account` can't
+    /// read; the handler checks its discriminator, length and version
+    #[account(mut, owner = crate::ID)]
+    pub pin_record: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", migrations::LegacyIpfsPinRecord::patient_of(&pin_record)?.as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExportRecord<'info> {
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        constraint = verification.version == VerificationRecord::VERSION @ HealthcareError::RecordNeedsMigration,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    /// The registry the record moves to
+    #[account(constraint = destination.key() != registry.key() @ HealthcareError::MigrationTicketMismatch)]
+    pub destination: Account<'info, HealthcareRegistry>,
+    /// `init_if_needed` so a record whose ticket expired can be exported again
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = MigrationTicket::SPACE,
+        seeds = [b"migration_ticket", verification.key().as_ref()],
+        bump,
+    )]
+    pub ticket: Account<'info, MigrationTicket>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ImportRecord<'info> {
+    /// The registry the record moves to
+    #[account(mut, has_one = authority)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = source_registry.authority == source_authority.key() @ HealthcareError::NotSourceAuthority,
+    )]
+    pub source_registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.status != RecordStatus::Migrated @ HealthcareError::RecordAlreadyMigrated,
+        constraint = verification.is_in_registry(&source_registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        mut,
+        seeds = [b"migration_ticket", verification.key().as_ref()],
+        bump = ticket.bump,
+        constraint = ticket.source_registry == source_registry.key() @ HealthcareError::MigrationTicketMismatch,
+        constraint = ticket.destination == registry.key() @ HealthcareError::MigrationTicketMismatch,
+    )]
+    pub ticket: Account<'info, MigrationTicket>,
+    #[account(mut, seeds = [b"patient", verification.patient_pubkey.as_ref()], bump = patient_index.bump)]
+    pub patient_index: Account<'info, PatientIndex>,
+    #[account(
+        init,
+        payer = authority,
+        space = verification.space_with_metadata(verification.metadata.len()),
+        seeds = [
+            b"verification",
+            verification.patient_pubkey.as_ref(),
+            &[verification.verification_type as u8],
+            &patient_index.next_record_nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub new_record: Account<'info, VerificationRecord>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub source_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckVerification<'info> {
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch)]
+    pub verification: Account<'info, VerificationRecord>,
+}
+
+#[derive(Accounts)]
+#[instruction(patient: Pubkey)]
+pub struct CheckVerificationStatus<'info> {
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// CHECK: the patient's `PatientIndex`, read if they have one
+    #[account(seeds = [b"patient", patient.as_ref()], bump)]
+    pub patient_index: UncheckedAccount<'info>,
+    /// CHECK: the index's latest record of the type, checked by the handler and
+    /// read if it still exists
+    pub record: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseExpiredVerification<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        close = rent_payer,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    /// CHECK: the `KeyRotation` address of the record's `rent_payer`, read if
+    /// the payer has rotated
+    #[account(seeds = [b"key_rotation", verification.rent_payer.as_ref()], bump)]
+    pub rent_payer_rotation: UncheckedAccount<'info>,
+    /// The record's `rent_payer`, or the key it rotated to
+    #[account(
+        mut,
+        constraint = rent_payer.key() == KeyRotation::refund_key(verification.rent_payer, &rent_payer_rotation)?
+            @ HealthcareError::RentRefundMismatch,
+    )]
+    pub rent_payer: SystemAccount<'info>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    /// CHECK: the record's `PatientIndex` by address, updated only if it holds
+    /// one; anonymous records have none
+    #[account(mut, seeds = [b"patient", verification.patient_pubkey.as_ref()], bump)]
+    pub patient_index: UncheckedAccount<'info>,
+    /// The record written after `verification` in its patient's chain, omitted
+    /// for the newest one
+    #[account(mut)]
+    pub next_record: Option<Account<'info, VerificationRecord>>,
+}
+
+/// Records to close are passed writable in `remaining_accounts`, each followed
+/// by its `PatientIndex` address, writable, the `KeyRotation` address of its
+/// `rent_payer`, the payer or the key it rotated to, writable, and its successor
+/// in the patient's chain, writable, or the program id
+#[derive(Accounts)]
+pub struct CloseRecordsBulk<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct MarkExpired<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        constraint = verification.is_in_registry(&registry.key()) @ HealthcareError::RecordRegistryMismatch,
+        constraint = verification.status != RecordStatus::Expired @ HealthcareError::RecordExpired,
+        constraint = !verification.is_held() @ HealthcareError::RecordUnderHold,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    /// CHECK: the record's `PatientIndex` by address, updated only if it holds
+    /// one; anonymous records have none
+    #[account(mut, seeds = [b"patient", verification.patient_pubkey.as_ref()], bump)]
+    pub patient_index: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_id: String)]
+pub struct CloseVerifyingKey<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.domain == registry.domain @ HealthcareError::VkRegistryMismatch,
+        constraint = verifying_key.load()?.status() == CircuitStatus::Revoked @ HealthcareError::CircuitNotRevoked,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(ipfs_cid: String, data_hash: [u8; 32])]
+pub struct PinMedicalData<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// CHECK: the pin's PDA, created by the handler as `bulk_pin` creates
+    /// one, so a second pin of the same content fails with `AlreadyPinned`
+    /// however far `update_pin` or `set_storage_deal` grew the account,
+    /// instead of the system program's "already in use"
+    #[account(mut, seeds = [b"pin", patient.key().as_ref(), data_hash.as_ref()], bump)]
+    pub pin_record: UncheckedAccount<'info>,
+    /// CHECK: the patient's `PinContentClaim` of the content, created by the
+    /// handler, so content another pin was updated to fails the same way
+    #[account(mut, seeds = [b"pin_content", patient.key().as_ref(), data_hash.as_ref()], bump)]
+    pub content_claim: UncheckedAccount<'info>,
+    /// The patient's `PatientIndex`, counting the pin under its category
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the registry's
+    /// `storage_treasury`, and may be omitted while storage is free
+    #[account(mut, address = registry.storage_treasury @ HealthcareError::InvalidStorageTreasury)]
+    pub storage_treasury: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(backend: StorageBackend, storage_id: Vec<u8>, data_hash: [u8; 32])]
+pub struct PinMedicalDataOn<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// CHECK: as in `PinMedicalData`
+    #[account(mut, seeds = [b"pin", patient.key().as_ref(), data_hash.as_ref()], bump)]
+    pub pin_record: UncheckedAccount<'info>,
+    /// CHECK: as in `PinMedicalData`
+    #[account(mut, seeds = [b"pin_content", patient.key().as_ref(), data_hash.as_ref()], bump)]
+    pub content_claim: UncheckedAccount<'info>,
+    /// The patient's `PatientIndex`, counting the pin under its category
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the registry's
+    /// `storage_treasury`, and may be omitted while storage is free
+    #[account(mut, address = registry.storage_treasury @ HealthcareError::InvalidStorageTreasury)]
+    pub storage_treasury: Option<UncheckedAccount<'info>>,
+}
+
+/// Pin record and content claim PDAs are passed writable in
+/// `remaining_accounts`
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PinMedicalDataBulk<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// The patient's `PatientIndex`, counting the pins under their categories
+    #[account(
+        init_if_needed,
+        payer = patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", patient.key().as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the registry's
+    /// `storage_treasury`, and may be omitted while storage is free
+    #[account(mut, address = registry.storage_treasury @ HealthcareError::InvalidStorageTreasury)]
+    pub storage_treasury: Option<UncheckedAccount<'info>>,
+}
+
+/// Oracles owed a share of the pin bounty are passed writable in
+/// `remaining_accounts`
+#[derive(Accounts)]
+pub struct UnpinMedicalData<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = registry,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    /// CHECK: the `KeyRotation` address of the pin's patient, read if the
+    /// patient has rotated
+    #[account(seeds = [b"key_rotation", pin_record.patient.as_ref()], bump)]
+    pub patient_rotation: UncheckedAccount<'info>,
+    /// CHECK: the `KeyRotation` address of the pin's `rent_payer`, read if the
+    /// payer has rotated
+    #[account(seeds = [b"key_rotation", pin_record.rent_payer.as_ref()], bump)]
+    pub rent_payer_rotation: UncheckedAccount<'info>,
+    /// The pin's `rent_payer`, or the key it rotated to
+    #[account(
+        mut,
+        constraint = rent_payer.key() == KeyRotation::refund_key(pin_record.rent_payer, &rent_payer_rotation)?
+            @ HealthcareError::RentRefundMismatch,
+    )]
+    pub rent_payer: SystemAccount<'info>,
+    /// The pin's patient, or the key it rotated to, refunded the bounty left
+    /// unreleased
+    #[account(
+        mut,
+        constraint = patient.key() == KeyRotation::current_key(pin_record.patient, &patient_rotation)?
+            @ HealthcareError::PinRecordPatientMismatch,
+    )]
+    pub patient: Signer<'info>,
+    #[account(mut, seeds = [b"patient", pin_record.patient.as_ref()], bump = patient_index.bump)]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the pin's `PinContentClaim`, closed to `rent_payer` with the pin
+    #[account(mut, seeds = [b"pin_content", pin_record.patient.as_ref(), pin_record.data_hash.as_ref()], bump)]
+    pub content_claim: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmPin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    #[account(constraint = registry.pinning_oracles.contains(&oracle.key()) @ HealthcareError::NotPinningOracle)]
+    pub oracle: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReportUnpin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    pub oracle: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RetryPin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        has_one = patient @ HealthcareError::PinRecordPatientMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        constraint = pin_record.status == PinStatus::Failed @ HealthcareError::PinNotFailed,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    pub patient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RetagPin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        has_one = patient @ HealthcareError::PinRecordPatientMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    #[account(mut, seeds = [b"patient", pin_record.patient.as_ref()], bump = patient_index.bump)]
+    pub patient_index: Account<'info, PatientIndex>,
+    pub patient: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_cid: String, new_data_hash: [u8; 32])]
+pub struct UpdatePin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        has_one = patient @ HealthcareError::PinRecordPatientMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        constraint = pin_record.status != PinStatus::Expired @ HealthcareError::PinAlreadyExpired,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    /// CHECK: the patient's pin address of `new_data_hash`, only read for
+    /// whether a pin other than `pin_record` lives there
+    #[account(seeds = [b"pin", patient.key().as_ref(), new_data_hash.as_ref()], bump)]
+    pub new_content_pin: UncheckedAccount<'info>,
+    /// CHECK: the pin's `PinContentClaim` of the content it replaces, closed
+    /// by the handler
+    #[account(mut, seeds = [b"pin_content", patient.key().as_ref(), pin_record.data_hash.as_ref()], bump)]
+    pub content_claim: UncheckedAccount<'info>,
+    /// CHECK: the patient's `PinContentClaim` of `new_data_hash`, created by
+    /// the handler
+    #[account(mut, seeds = [b"pin_content", patient.key().as_ref(), new_data_hash.as_ref()], bump)]
+    pub new_content_claim: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Oracles owed a share of the pin bounty are passed writable in
+/// `remaining_accounts`
+#[derive(Accounts)]
+pub struct AbandonFailedPin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        close = rent_payer,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        constraint = pin_record.status == PinStatus::Failed @ HealthcareError::PinNotFailed,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    /// CHECK: the `KeyRotation` address of the pin's patient, read if the
+    /// patient has rotated
+    #[account(seeds = [b"key_rotation", pin_record.patient.as_ref()], bump)]
+    pub patient_rotation: UncheckedAccount<'info>,
+    /// CHECK: the `KeyRotation` address of the pin's `rent_payer`, read if the
+    /// payer has rotated
+    #[account(seeds = [b"key_rotation", pin_record.rent_payer.as_ref()], bump)]
+    pub rent_payer_rotation: UncheckedAccount<'info>,
+    /// The pin's `rent_payer`, or the key it rotated to
+    #[account(
+        mut,
+        constraint = rent_payer.key() == KeyRotation::refund_key(pin_record.rent_payer, &rent_payer_rotation)?
+            @ HealthcareError::RentRefundMismatch,
+    )]
+    pub rent_payer: SystemAccount<'info>,
+    /// The pin's patient or the key it rotated to, or anyone once the failure
+    /// has outlasted the registry's `failed_pin_timeout_secs`
+    pub cranker: Signer<'info>,
+    #[account(mut, seeds = [b"patient", pin_record.patient.as_ref()], bump = patient_index.bump)]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// The pin's patient, or the key it rotated to, refunded the bounty left
+    /// unreleased
+    #[account(
+        mut,
+        constraint = patient.key() == KeyRotation::current_key(pin_record.patient, &patient_rotation)?
+            @ HealthcareError::PinRecordPatientMismatch,
+    )]
+    pub patient: SystemAccount<'info>,
+    /// CHECK: as in `UnpinMedicalData`
+    #[account(mut, seeds = [b"pin_content", pin_record.patient.as_ref(), pin_record.data_hash.as_ref()], bump)]
+    pub content_claim: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RenewPin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        constraint = pin_record.status != PinStatus::Expired @ HealthcareError::PinAlreadyExpired,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: only receives lamports; must be the registry's `storage_treasury`
+    #[account(mut, address = registry.storage_treasury @ HealthcareError::InvalidStorageTreasury)]
+    pub storage_treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExpirePin<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        constraint = pin_record.status != PinStatus::Expired @ HealthcareError::PinAlreadyExpired,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetStorageDeal<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    /// Pays the rent a new deal adds to the pin
+    #[account(mut, constraint = registry.pinning_oracles.contains(&oracle.key()) @ HealthcareError::NotPinningOracle)]
+    pub oracle: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DealExpiringSoon<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPinBounty<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        mut,
+        has_one = registry @ HealthcareError::PinRecordRegistryMismatch,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    /// An oracle holding the pin or owed a share of its bounty, listed in the
+    /// registry or not
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_key: Pubkey)]
+pub struct RotatePatientKey<'info> {
+    /// The registry whose events carry the rotation, which the patient names
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    /// `init`, so a key rotates away only once
+    #[account(
+        init,
+        payer = patient,
+        space = KeyRotation::SPACE,
+        seeds = [b"key_rotation", patient.key().as_ref()],
+        bump,
+    )]
+    pub rotation: Account<'info, KeyRotation>,
+    /// CHECK: only checked to be empty, as a key that rotated away can't be
+    /// rotated to
+    #[account(seeds = [b"key_rotation", new_key.as_ref()], bump)]
+    pub new_key_rotation: UncheckedAccount<'info>,
+    /// The old key
+    #[account(mut)]
+    pub patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Further records to claim are passed writable in `remaining_accounts`, each
+/// followed by the address it moves to and its successor in the old key's
+/// chain, as `next_record` is for `record`, or the program id for none
+#[derive(Accounts)]
+pub struct ClaimRotatedRecord<'info> {
+    /// The registry the claimed records were written under
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        seeds = [b"key_rotation", rotation.old_key.as_ref()],
+        bump = rotation.bump,
+        constraint = rotation.new_key == new_patient.key() @ HealthcareError::RotationTargetMismatch,
+    )]
+    pub rotation: Account<'info, KeyRotation>,
+    /// Closed to `new_patient` once copied to `new_record`
+    #[account(mut)]
+    pub record: Account<'info, VerificationRecord>,
+    /// CHECK: created here at `derive_verification_pda` of the new key's next
+    /// record nonce, which the handler checks
+    #[account(mut)]
+    pub new_record: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"patient", rotation.old_key.as_ref()], bump = old_index.bump)]
+    pub old_index: Account<'info, PatientIndex>,
+    #[account(
+        init_if_needed,
+        payer = new_patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", new_patient.key().as_ref()],
+        bump,
+    )]
+    pub new_index: Account<'info, PatientIndex>,
+    #[account(mut)]
+    pub new_patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the record written after `record` in the old key's chain, relinked
+    /// past it by the handler; omitted if there is none
+    #[account(mut)]
+    pub next_record: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAccessPass<'info> {
+    #[account(
+        seeds = [b"key_rotation", rotation.old_key.as_ref()],
+        bump = rotation.bump,
+        constraint = rotation.new_key == new_patient.key() @ HealthcareError::RotationTargetMismatch,
+    )]
+    pub rotation: Account<'info, KeyRotation>,
+    #[account(
+        mut,
+        close = new_patient,
+        constraint = access_pass.patient == rotation.old_key @ HealthcareError::NotRotatedKeyAccount,
+    )]
+    pub access_pass: Account<'info, AccessPass>,
+    #[account(
+        init,
+        payer = new_patient,
+        space = AccessPass::SPACE,
+        seeds = [b"access_pass", new_patient.key().as_ref(), access_pass.resource.as_ref()],
+        bump,
+    )]
+    pub new_access_pass: Account<'info, AccessPass>,
+    #[account(mut)]
+    pub new_patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPinRecord<'info> {
+    #[account(
+        seeds = [b"key_rotation", rotation.old_key.as_ref()],
+        bump = rotation.bump,
+        constraint = rotation.new_key == new_patient.key() @ HealthcareError::RotationTargetMismatch,
+    )]
+    pub rotation: Account<'info, KeyRotation>,
+    #[account(
+        mut,
+        constraint = pin_record.version == IpfsPinRecord::VERSION @ HealthcareError::PinRecordNeedsMigration,
+        constraint = pin_record.patient == rotation.old_key @ HealthcareError::NotRotatedKeyAccount,
+    )]
+    pub pin_record: Account<'info, IpfsPinRecord>,
+    #[account(mut, seeds = [b"patient", rotation.old_key.as_ref()], bump = old_patient_index.bump)]
+    pub old_patient_index: Account<'info, PatientIndex>,
+    #[account(
+        init_if_needed,
+        payer = new_patient,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", new_patient.key().as_ref()],
+        bump,
+    )]
+    pub new_patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the old key's `PinContentClaim` of the pin's content, closed by
+    /// the handler
+    #[account(mut, seeds = [b"pin_content", rotation.old_key.as_ref(), pin_record.data_hash.as_ref()], bump)]
+    pub content_claim: UncheckedAccount<'info>,
+    /// CHECK: the new key's `PinContentClaim` of the pin's content, created by
+    /// the handler
+    #[account(mut, seeds = [b"pin_content", new_patient.key().as_ref(), pin_record.data_hash.as_ref()], bump)]
+    pub new_content_claim: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub new_patient: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(guardian: Pubkey, ward: Pubkey)]
+pub struct GrantGuardianConsent<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        init_if_needed,
+        payer = granter,
+        space = GuardianConsent::SPACE,
+        seeds = [b"guardian", registry.key().as_ref(), guardian.as_ref(), ward.as_ref()],
+        bump,
+    )]
+    pub consent: Account<'info, GuardianConsent>,
+    /// The registry authority, or a provider passed with its registration
+    #[account(mut)]
+    pub granter: Signer<'info>,
+    pub provider_registration: Option<Account<'info, ProviderRegistration>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeGuardianConsent<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(mut, has_one = registry)]
+    pub consent: Account<'info, GuardianConsent>,
+    /// The registry authority, the granter, the guardian or the ward
+    pub revoker: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(
+    proof: Vec<u8>,
+    proof_format: ProofFormat,
+    public_inputs: Vec<u8>,
+    ipfs_hash: String,
+    circuit_id: String,
+    record_nonce: u64,
+)]
+pub struct VerifyEligibilityForWard<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        seeds = [b"guardian", registry.key().as_ref(), guardian.key().as_ref(), consent.ward.as_ref()],
+        bump = consent.bump,
+    )]
+    pub consent: Account<'info, GuardianConsent>,
+    #[account(
+        init,
+        payer = guardian,
+        space = VerificationRecord::SPACE,
+        seeds = [
+            b"verification",
+            consent.ward.as_ref(),
+            &[VerificationType::Eligibility as u8],
+            &record_nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub verification: Account<'info, VerificationRecord>,
+    #[account(
+        mut,
+        seeds = [b"vk", circuit_id.as_bytes()],
+        bump = verifying_key.load()?.bump,
+        constraint = verifying_key.load()?.is_finalized() @ HealthcareError::VerifyingKeyNotFinalized,
+        constraint = verifying_key.load()?.is_accepting() @ HealthcareError::CircuitDeprecated,
+        constraint = registry.security_policy.allows_key(&*verifying_key.load()?)
+            @ HealthcareError::CircuitNotAllowedByPolicy,
+        constraint = inspect_vk(&verifying_key, |key| key.is_intact())? @ HealthcareError::VerifyingKeyCorrupted,
+        constraint = registry.is_circuit_for(VerificationType::Eligibility, &verifying_key.key())
+            @ HealthcareError::WrongCircuitForType,
+    )]
+    pub verifying_key: AccountLoader<'info, VerifyingKeyPDA>,
+    /// `init_if_needed` so a replay reaches the handler and fails with
+    /// `ProofAlreadyUsed` instead of the system program's "already in use"
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        space = ProofNullifier::SPACE,
+        seeds = [b"nullifier", ProofNullifier::seed(&proof, proof_format, &public_inputs).as_ref()],
+        bump,
+    )]
+    pub nullifier: Account<'info, ProofNullifier>,
+    /// The ward's `PatientIndex`
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", consent.ward.as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    /// CHECK: the ward's latest eligibility record, as for `VerifyEligibility`
+    #[account(mut)]
+    pub previous_record: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: only receives lamports; must be the key's `fee_recipient`, and may
+    /// be omitted when the circuit is free
+    #[account(mut, address = verifying_key.load()?.fee_recipient @ HealthcareError::InvalidFeeRecipient)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(ipfs_cid: String, data_hash: [u8; 32])]
+pub struct PinMedicalDataForWard<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, HealthcareRegistry>,
+    #[account(
+        seeds = [b"guardian", registry.key().as_ref(), guardian.key().as_ref(), consent.ward.as_ref()],
+        bump = consent.bump,
+    )]
+    pub consent: Account<'info, GuardianConsent>,
+    /// CHECK: the ward's pin of the content, created by the handler as in
+    /// `PinMedicalData`
+    #[account(mut, seeds = [b"pin", consent.ward.as_ref(), data_hash.as_ref()], bump)]
+    pub pin_record: UncheckedAccount<'info>,
+    /// CHECK: the ward's `PinContentClaim` of the content, as in
+    /// `PinMedicalData`
+    #[account(mut, seeds = [b"pin_content", consent.ward.as_ref(), data_hash.as_ref()], bump)]
+    pub content_claim: UncheckedAccount<'info>,
+    /// The ward's `PatientIndex`, counting the pin under its category
+    #[account(
+        init_if_needed,
+        payer = guardian,
+        space = PatientIndex::SPACE,
+        seeds = [b"patient", consent.ward.as_ref()],
+        bump,
+    )]
+    pub patient_index: Account<'info, PatientIndex>,
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: as in `PinMedicalData`
+    #[account(mut, address = registry.storage_treasury @ HealthcareError::InvalidStorageTreasury)]
+    pub storage_treasury: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitModelUpdate<'info> {
+    #[account(mut)]
+    pub fl_state: Account<'info, FederatedLearningState>,
+    pub agent: Signer<'info>,
+}
+
+// Events. Each starts with `seq`, its place among its registry's events (see
+// `HealthcareRegistry::event_seq`), and that `registry`, so an indexer following
+// several registries can tell their events apart and notice one it missed.
+#[event]
+pub struct EligibilityVerified {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub patient: Pubkey,
+    pub ipfs_hash: String,
+    pub timestamp: i64,
+    pub slot: u64,
+    pub verification_id: [u8; 32],
+    /// A registered provider co-signed the verification, see `attesting_provider`
+    pub provider_attested: bool,
+    /// The guardian who verified for the patient under a `GuardianConsent`
+    pub guardian: Option<Pubkey>,
+}
+
+#[event]
+pub struct CircuitRegistered {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub verifying_key: Pubkey,
+    pub n_public: u8,
+}
+
+/// Emitted by `verify_proof_readonly`, which otherwise leaves no trace. The
+/// check keeps the registry read-only, so unlike the registry's other events
+/// it carries no `seq`.
+#[event]
+pub struct ProofChecked {
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub proof_hash: [u8; 32],
+    pub verified: bool,
+}
+
+#[event]
+pub struct DataPinned {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub patient: Pubkey,
+    pub ipfs_cid: String,
+    pub data_hash: [u8; 32],
+    pub slot: u64,
+    /// The guardian who pinned for the patient under a `GuardianConsent`
+    pub guardian: Option<Pubkey>,
+    /// Until when the storage is paid for; zero while storage is free
+    pub paid_through: i64,
+    /// Where workers pin the content; `ipfs_cid` is its id in hex off IPFS
+    pub backend: StorageBackend,
+    pub category: DataCategory,
+}
+
+/// `pin_medical_data_bulk` wrote `count` pins of the patient's, all on IPFS
+#[event]
+pub struct DataPinnedBatch {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub patient: Pubkey,
+    pub count: u16,
+    /// `data_hash_root` of the entries' data hashes, in entry order
+    pub data_hash_root: [u8; 32],
+    pub slot: u64,
+    /// Until when each pin's storage is paid for; zero while storage is free
+    pub paid_through: i64,
+}
+
+#[event]
+pub struct DataUnpinned {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub ipfs_cid: String,
+    /// Where workers release the content from
+    pub backend: StorageBackend,
+    /// Where the rent went
+    pub rent_payer: Pubkey,
+    pub lamports: u64,
+    /// From `pinned_at` to the unpin
+    pub pinned_duration_secs: i64,
+    /// Of the bounty, what no oracle was released, returned to `patient`
+    pub bounty_refunded: u64,
+}
+
+/// A pinning oracle reported holding the pin's content, counting towards its
+/// replication target
+#[event]
+pub struct PinReplicaConfirmed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub oracle: Pubkey,
+    pub provider_id: String,
+    /// Oracles holding the content, this one included
+    pub replicas: u8,
+    pub replication_target: u8,
+    pub timestamp: i64,
+}
+
+/// An oracle dropped the content of a confirmed pin, leaving it short of its
+/// replication target
+#[event]
+pub struct ReplicationDegraded {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub oracle: Pubkey,
+    /// Oracles still holding the content
+    pub replicas: u8,
+    pub replication_target: u8,
+    pub timestamp: i64,
+}
+
+/// The pin's content is held by as many pinning oracles as its replication
+/// target asks
+#[event]
+pub struct PinConfirmed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub oracle: Pubkey,
+    pub provider_id: String,
+    pub timestamp: i64,
+}
+
+/// A pinning oracle reported the pin's content could not be pinned
+#[event]
+pub struct PinFailed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub oracle: Pubkey,
+    pub provider_id: String,
+    pub timestamp: i64,
+}
+
+/// A pin reported `Failed` was put back to `Requested` by its patient
+#[event]
+pub struct PinRetried {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    /// The CID the pin now holds
+    pub ipfs_cid: String,
+    pub backend: StorageBackend,
+    /// Whether the retry swapped in a new CID
+    pub cid_changed: bool,
+    pub timestamp: i64,
+}
+
+/// A pin reported `Failed` was closed and its rent refunded
+#[event]
+pub struct PinAbandoned {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub rent_payer: Pubkey,
+    /// Rent returned to `rent_payer`
+    pub lamports: u64,
+    /// Who closed the pin: its patient, or anyone past the timeout
+    pub cranker: Pubkey,
+    pub timestamp: i64,
+    /// Of the bounty, what no oracle was released, returned to the patient
+    pub bounty_refunded: u64,
+}
+
+/// More storage was bought for a pin
+#[event]
+pub struct PinRenewed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub payer: Pubkey,
+    pub days: u32,
+    /// Paid to the registry's `storage_treasury`
+    pub lamports: u64,
+    pub paid_through: i64,
+}
+
+/// A pin's storage lapsed past its grace period; workers release the content
+#[event]
+pub struct PinExpired {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub paid_through: i64,
+    pub timestamp: i64,
+    /// Where workers release the content from
+    pub backend: StorageBackend,
+}
+
+/// The patient filed a pin under another category or tag
+#[event]
+pub struct PinRetagged {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub old_category: DataCategory,
+    pub category: DataCategory,
+    pub tag: Option<[u8; 8]>,
+}
+
+/// The patient replaced a pin's content, its old version kept in the pin's
+/// `history`
+#[event]
+pub struct PinUpdated {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub old_hash: [u8; 32],
+    pub new_hash: [u8; 32],
+    /// The CID the pin now holds
+    pub ipfs_cid: String,
+    /// The pin's `revision`, one per update
+    pub version: u32,
+    pub timestamp: i64,
+}
+
+/// A pinning oracle recorded a Filecoin deal storing the pin's content
+#[event]
+pub struct StorageDealRecorded {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub oracle: Pubkey,
+    pub deal_id: u64,
+    pub provider: String,
+    pub deal_expiry: i64,
+    /// Deals the pin holds, this one included
+    pub deals: u8,
+}
+
+/// A pin's storage deal ends within the registry's warning window, or has
+/// ended; renewal automation extends or replaces it
+#[event]
+pub struct StorageDealExpiring {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub patient: Pubkey,
+    pub deal_id: u64,
+    pub provider: String,
+    pub deal_expiry: i64,
+    pub timestamp: i64,
+}
+
+/// An oracle claimed what it is owed of a pin's bounty
+#[event]
+pub struct PinBountyClaimed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub oracle: Pubkey,
+    pub provider_id: String,
+    pub lamports: u64,
+    /// Periods hosted so far, all of them now released
+    pub periods: u16,
+    /// Lamports of the bounty not yet released to any oracle
+    pub remaining: u64,
+}
+
+/// An access `record_access` counted on a pin
+#[event]
+pub struct DataAccessed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub accessor: Pubkey,
+    pub patient: Pubkey,
+    pub timestamp: i64,
+}
+
+/// An access `record_access` refused, emitted when the registry has
+/// `denied_access_events_enabled`. Its transaction fails, rolling back any
+/// count, so unlike the registry's other events it carries no `seq`.
+#[event]
+pub struct AccessDenied {
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub accessor: Pubkey,
+    pub patient: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VerifyingKeyUpdateProposed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub old_hash: [u8; 32],
+    pub new_hash: [u8; 32],
+    pub activatable_at: i64,
+}
+
+#[event]
+pub struct VerifyingKeyUpdated {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub old_hash: [u8; 32],
+    pub new_hash: [u8; 32],
+}
+
+/// Clients pin `vk_hash` to the key they generated proofs against
+#[event]
+pub struct VerifyingKeyRegistered {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub vk_hash: [u8; 32],
+    pub authority: Pubkey,
+    pub n_public: u8,
+}
+
+/// The uploaded bytes matched `vk_hash` and the key now verifies proofs
+#[event]
+pub struct VerifyingKeyFinalized {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub vk_hash: [u8; 32],
+}
+
+#[event]
+pub struct CircuitStatusChanged {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub status: CircuitStatus,
+    pub min_accepted_version: u16,
+}
+
+/// The key account is gone; its rent went to `authority`
+#[event]
+pub struct VerifyingKeyClosed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub verifying_key: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Deliberately carries nothing that identifies the patient or the relayer
+#[event]
+pub struct AnonymousEligibilityVerified {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub nullifier: [u8; 32],
+    pub ipfs_hash: String,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VerificationInvalidatedByCircuitRevocation {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    pub circuit_id: String,
+}
+
+#[event]
+pub struct VerificationRevoked {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    pub reason_code: u16,
+    pub revoked_by: Pubkey,
+}
+
+/// From `mark_expired`, and from a submission whose `force_new` expires the
+/// patient's previous record
+#[event]
+pub struct VerificationExpired {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct RecordStatusChanged {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    pub old_status: RecordStatus,
+    pub new_status: RecordStatus,
+}
+
+/// One per record closed, by `close_expired_verification` or `close_records_bulk`
+#[event]
+pub struct VerificationClosed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    /// Where the rent went, less the bounty
+    pub rent_payer: Pubkey,
+    pub lamports: u64,
+    /// `None` from `close_records_bulk`, which pays no bounty
+    pub cranker: Option<Pubkey>,
+    pub bounty: u64,
+}
+
+/// One for every `close_records_bulk`, after the `VerificationClosed` of each record
+#[event]
+pub struct RecordsClosed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub count: u32,
+    pub lamports_returned: u64,
+}
+
+#[event]
+pub struct ProofFailed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub circuit_id: String,
+    pub failed_proof_count: u64,
+}
+
+#[event]
+pub struct PrescriptionVerified {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub prescription: Pubkey,
+    pub patient: Pubkey,
+    pub drug_commitment: [u8; 32],
+    pub refills_allowed: u8,
+    pub valid_until: i64,
+}
+
+#[event]
+pub struct PrescriptionRefilled {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub prescription: Pubkey,
+    pub pharmacy: Pubkey,
+    pub refills_remaining: u8,
+}
+
+#[event]
+pub struct DiagnosisVerified {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub diagnosis: Pubkey,
+    pub patient: Pubkey,
+    pub provider: Pubkey,
+    pub icd10_commitment: [u8; 32],
+    pub ipfs_cid: String,
+}
+
+#[event]
+pub struct LabResultVerified {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub lab_result: Pubkey,
+    pub patient: Pubkey,
+    pub lab: Pubkey,
+    pub result_commitment: [u8; 32],
+}
+
+#[event]
+pub struct ImmunizationVerified {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub immunization: Pubkey,
+    pub patient: Pubkey,
+    pub provider: Pubkey,
+    pub status_commitment: [u8; 32],
+}
+
+#[event]
+pub struct DiagnosisRevoked {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub diagnosis: Pubkey,
+    pub patient: Pubkey,
+    pub reason_code: u16,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct AccessPassIssued {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub access_pass: Pubkey,
+    pub patient: Pubkey,
+    pub resource: Pubkey,
+    pub expires_at_slot: u64,
+    pub uses: u16,
+}
+
+#[event]
+pub struct AccessPassConsumed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub access_pass: Pubkey,
+    pub patient: Pubkey,
+    pub resource: Pubkey,
+    pub uses_remaining: u16,
+}
+
+#[event]
+pub struct VerificationRenewed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub revision: u16,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VerificationRecordMigrated {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub version: u8,
+}
+
+#[event]
+pub struct PinRecordMigrated {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub pin_record: Pubkey,
+    pub version: u8,
+}
+
+#[event]
+pub struct RecordExported {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub destination: Pubkey,
+    pub ticket: Pubkey,
+    pub snapshot_hash: [u8; 32],
+    pub expires_at: i64,
+}
+
+/// Emitted under the destination registry; the original's move to `Migrated`
+/// is a `RecordStatusChanged` under the source one
+#[event]
+pub struct RecordImported {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    pub source_registry: Pubkey,
+    pub migrated_from: Pubkey,
+}
+
+#[event]
+pub struct VerificationTokenMinted {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct VerificationTokenBurned {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct VerificationDisputed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    pub disputer: Pubkey,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct ClaimSubmitted {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub claim: Pubkey,
+    pub patient: Pubkey,
+    pub verification: Pubkey,
+    pub claim_id: u64,
+    pub amount: u64,
+    pub service_code_commitment: [u8; 32],
+    pub claim_nullifier: [u8; 32],
+}
+
+#[event]
+pub struct ClaimNullifierReleased {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub claim: Pubkey,
+    pub insurer: Pubkey,
+    pub claim_nullifier: [u8; 32],
+}
+
+#[event]
+pub struct CheckpointCommitted {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub checkpoint: Pubkey,
+    pub root: [u8; 32],
+    pub start_seq: u64,
+    pub end_seq: u64,
+}
+
+#[event]
+pub struct ClaimEscrowFunded {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub claim: Pubkey,
+    pub insurer: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct ClaimAdjudicated {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub claim: Pubkey,
+    pub insurer: Pubkey,
+    pub approved: bool,
+    pub reason_code: u16,
+    pub payee: Pubkey,
+    /// Paid to `payee` on approval, refunded to the insurer on denial
+    pub lamports: u64,
+}
+
+#[event]
+pub struct HoldPlaced {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub order_hash: [u8; 32],
+    pub placed_at: i64,
+}
+
+#[event]
+pub struct HoldReleased {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub order_hash: [u8; 32],
+    /// When the hold was placed
+    pub placed_at: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    pub verification_type: VerificationType,
+    pub disputer: Pubkey,
+    pub evidence_hash: [u8; 32],
+    /// Whether the record was revoked rather than restored
+    pub upheld: bool,
+}
+
+#[event]
+pub struct MetadataUpdated {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub record: Pubkey,
+    pub patient: Pubkey,
+    /// Keccak of the new metadata
+    pub metadata_hash: [u8; 32],
+    pub len: u32,
+}
+
+#[event]
+pub struct PatientKeyRotated {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+    pub rotated_at: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RecordClaimed {
+    pub seq: u64,
+    pub registry: Pubkey,
+    /// The closed account under the old key, and its copy under the new one
+    pub record: Pubkey,
+    pub new_record: Pubkey,
+    pub old_key: Pubkey,
+    pub new_key: Pubkey,
+}
+
+#[event]
+pub struct GuardianConsentGranted {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub consent: Pubkey,
+    pub guardian: Pubkey,
+    pub ward: Pubkey,
+    pub scopes: u8,
+    pub expires_at: i64,
+    pub granted_by: Pubkey,
+}
+
+#[event]
+pub struct GuardianConsentRevoked {
+    pub seq: u64,
+    pub registry: Pubkey,
+    pub consent: Pubkey,
+    pub guardian: Pubkey,
+    pub ward: Pubkey,
+    pub revoked_by: Pubkey,
+}
+
+// Errors (expanded with IPFS error)
+#[error_code]
+pub enum HealthcareError {
+    #[msg("Invalid proof length")]
+    InvalidProofLength,
+    #[msg("Proof verification failed")]
+    ProofVerificationFailed,
+    #[msg("Gradient data too large")]
+    GradientTooLarge,
+    #[msg("IPFS pinning failed")]
+    IpfsPinningFailed,
+    #[msg("Verifying key could not be deserialized")]
+    VerifyingKeyDeserializeFailed,
+    #[msg("Proof bytes are not a valid Groth16 proof encoding")]
+    InvalidProofEncoding,
+    #[msg("Public inputs are not a valid field element encoding")]
+    InvalidPublicInputEncoding,
+    #[msg("Circuit id must be between 1 and 32 bytes")]
+    InvalidCircuitId,
+    #[msg("Verifying key length exceeds the maximum upload size")]
+    VerifyingKeyTooLarge,
+    #[msg("Verifying key chunk is empty or extends past the declared length")]
+    VkChunkOutOfBounds,
+    #[msg("Verifying key chunk conflicts with bytes already written")]
+    VkChunkConflict,
+    #[msg("Verifying key upload is missing bytes")]
+    VerifyingKeyIncomplete,
+    #[msg("Verifying key has already been finalized")]
+    VerifyingKeyAlreadyFinalized,
+    #[msg("Verifying key has not been finalized")]
+    VerifyingKeyNotFinalized,
+    #[msg("Verifying key update delay must not be negative")]
+    InvalidVkUpdateDelay,
+    #[msg("Verifying key update is still inside its timelock window")]
+    VkUpdateTimelockActive,
+    #[msg("Uploaded verifying key does not match the proposed hash")]
+    VkUpdateHashMismatch,
+    #[msg("Public input is not a canonical Bn254 scalar")]
+    PublicInputNotInField,
+    #[msg("Number of public inputs does not match the circuit")]
+    PublicInputCountMismatch,
+    #[msg("Proof point is the identity, off the curve, or outside the prime-order subgroup")]
+    InvalidProofPoint,
+    #[msg("Proof is not in the canonical encoding for its format")]
+    NonCanonicalProofEncoding,
+    #[msg("Proof has already been used for a verification")]
+    ProofAlreadyUsed,
+    #[msg("Public input 0 is not bound to the signing patient")]
+    PublicInputPatientMismatch,
+    #[msg("Domain input slot is out of range or reserved for the patient")]
+    InvalidDomainInput,
+    #[msg("Public input is not bound to this registry's domain")]
+    PublicInputDomainMismatch,
+    #[msg("Freshness window must be positive and its timestamp slot free")]
+    InvalidFreshnessWindow,
+    #[msg("Proof timestamp is outside the freshness window")]
+    ProofStale,
+    #[msg("Proof timestamp is in the future")]
+    ProofTimestampInFuture,
+    #[msg("Batch must hold between 1 and MAX_BATCH_SIZE proofs")]
+    InvalidBatchSize,
+    #[msg("Batch accounts do not match the submissions' record and nullifier PDAs")]
+    BatchAccountMismatch,
+    #[msg("Partial verification has expired and can only be reclaimed")]
+    PartialVerificationExpired,
+    #[msg("Partial verification has not yet expired")]
+    PartialVerificationNotExpired,
+    #[msg("Partial verification still has public inputs to fold")]
+    PartialVerificationIncomplete,
+    #[msg("Verifying key changed since the verification began")]
+    VerifyingKeyChanged,
+    #[msg("Registry has reached its circuit limit")]
+    TooManyCircuits,
+    #[msg("Circuit is deprecated or revoked and no longer accepts proofs")]
+    CircuitDeprecated,
+    #[msg("Circuit status cannot be changed once revoked")]
+    InvalidCircuitStatusChange,
+    #[msg("Circuit has not been revoked")]
+    CircuitNotRevoked,
+    #[msg("Verification record belongs to a different circuit")]
+    RecordCircuitMismatch,
+    #[msg("Stored verifying key bytes do not match their hash")]
+    VerifyingKeyCorrupted,
+    #[msg("Verifying key is not the registry's circuit for this verification type")]
+    WrongCircuitForType,
+    #[msg("Proofs over this curve can't be verified by this program")]
+    UnsupportedCurve,
+    #[msg("This instruction doesn't support the circuit's proving scheme")]
+    UnsupportedProvingScheme,
+    #[msg("Proof format not accepted by the circuit's proving scheme")]
+    UnsupportedProofFormat,
+    #[msg("Hash algorithm not allowed on a NIST-compliant registry")]
+    HashAlgoNotCompliant,
+    #[msg("Too many public inputs for a Poseidon proof hash")]
+    TooManyInputsForPoseidon,
+    #[msg("Verifying key account not yet resized to hold its key")]
+    VerifyingKeyNotAllocated,
+    #[msg("Verifying key account size out of range")]
+    InvalidVkAccountSize,
+    #[msg("Circuit verified a proof too recently to close its key")]
+    VkCloseCooldownActive,
+    #[msg("Verifying key was registered under another registry")]
+    VkRegistryMismatch,
+    #[msg("Proof failed the pairing check")]
+    PairingCheckFailed,
+    #[msg("Patient can't cover the circuit's verification fee")]
+    InsufficientFeeFunds,
+    #[msg("Circuit fee exceeds the registry's maximum")]
+    CircuitFeeTooHigh,
+    #[msg("Fee recipient missing or not the circuit's")]
+    InvalidFeeRecipient,
+    #[msg("More public inputs than the registry allows")]
+    TooManyPublicInputs,
+    #[msg("No public inputs submitted")]
+    EmptyPublicInputs,
+    #[msg("Cached verification result has expired")]
+    CacheEntryExpired,
+    #[msg("Cached verification result hasn't expired yet")]
+    CacheEntryNotExpired,
+    #[msg("Cache account isn't the PDA of the proof hash")]
+    CacheAccountMismatch,
+    #[msg("Circuit has no credential nullifier or binds the patient")]
+    AnonymousModeUnsupported,
+    #[msg("Nullifier input slot is out of range or overlaps another bound input")]
+    InvalidNullifierInput,
+    #[msg("Credential nullifier doesn't match the circuit's nullifier input")]
+    PublicInputNullifierMismatch,
+    #[msg("Credential already verified this epoch")]
+    CredentialAlreadyUsed,
+    #[msg("Epoch doesn't match the cluster's current epoch")]
+    WrongEpoch,
+    #[msg("Registry doesn't take verification commitments")]
+    CommitRevealDisabled,
+    #[msg("Submission doesn't match the submitter's commitment")]
+    CommitmentMismatch,
+    #[msg("Commitment's reveal window has passed")]
+    CommitmentExpired,
+    #[msg("Commitment must be revealed in a later slot than it was made")]
+    CommitmentTooRecent,
+    #[msg("Commitment's reveal window hasn't passed yet")]
+    CommitmentNotExpired,
+    #[msg("Nonce input slot is out of range or overlaps another bound input")]
+    InvalidNonceInput,
+    #[msg("Circuit uses a verification nonce but no patient index was passed")]
+    PatientIndexRequired,
+    #[msg("Proof's nonce isn't the patient's next verification nonce")]
+    StaleNonce,
+    #[msg("Verifying key upload was written to too recently to collect")]
+    VkUploadNotStale,
+    #[msg("Circuit's scheme, curve or freshness window is outside the registry's security policy")]
+    CircuitNotAllowedByPolicy,
+    #[msg("Hash algorithm not allowed by the registry's security policy")]
+    HashAlgoNotAllowed,
+    #[msg("Security policy must allow a scheme, a curve and a hash algorithm")]
+    InvalidSecurityPolicy,
+    #[msg("Security policy allows algorithms outside the NIST-approved set")]
+    PolicyNotNistCompliant,
+    #[msg("Verification record is already invalid")]
+    AlreadyRevoked,
+    #[msg("Only the registry authority or the record's patient may revoke it")]
+    NotAuthorizedToRevoke,
+    #[msg("Verification record was not written under this registry")]
+    RecordRegistryMismatch,
+    #[msg("Validity period must be positive")]
+    InvalidValidityPeriod,
+    #[msg("Verification record has been revoked")]
+    RecordRevoked,
+    #[msg("Verification record has expired")]
+    RecordExpired,
+    #[msg("Verification record has not reached its expiry")]
+    RecordNotExpired,
+    #[msg("Expired verification record is still within its grace period")]
+    GcGracePeriodActive,
+    #[msg("Garbage collection bounty exceeds a record's rent")]
+    GcBountyTooHigh,
+    #[msg("Record nonce is not the patient's next one")]
+    RecordNonceMismatch,
+    #[msg("Verification record cannot move to that status")]
+    InvalidStatusTransition,
+    #[msg("Verification record is pending or disputed")]
+    RecordNotVerified,
+    #[msg("Patient index account does not match the record's patient")]
+    PatientIndexMismatch,
+    #[msg("Prescription must be valid until a future time")]
+    InvalidPrescriptionExpiry,
+    #[msg("Prescription has expired")]
+    PrescriptionExpired,
+    #[msg("Prescription has no refills remaining")]
+    NoRefillsRemaining,
+    #[msg("Diagnosing provider is not active")]
+    ProviderInactive,
+    #[msg("Pin record belongs to another patient")]
+    PinRecordPatientMismatch,
+    #[msg("IPFS CID is too long")]
+    IpfsCidTooLong,
+    #[msg("Access pass needs at least one use and one slot")]
+    InvalidAccessPass,
+    #[msg("Access pass is for another resource")]
+    AccessPassResourceMismatch,
+    #[msg("Access pass has expired")]
+    AccessPassExpired,
+    #[msg("Access pass has no uses remaining")]
+    AccessPassExhausted,
+    #[msg("Record is neither expired nor close to its expiry")]
+    RecordNotRenewable,
+    #[msg("Record belongs to another patient")]
+    NotRecordPatient,
+    #[msg("Verification record is in a legacy layout; run migrate_verification_record")]
+    RecordNeedsMigration,
+    #[msg("Verification record is already in the current layout")]
+    RecordNotLegacy,
+    #[msg("Minting a verification token needs the mint, token account and token programs")]
+    MissingTokenAccounts,
+    #[msg("Account is not the registry's verification mint")]
+    InvalidVerificationMint,
+    #[msg("Verification record has not been revoked")]
+    RecordNotRevoked,
+    #[msg("Verification record holds no token of this mint")]
+    NoVerificationToken,
+    #[msg("Token account is not the patient's account of the verification mint")]
+    InvalidTokenAccount,
+    #[msg("Metadata exceeds the registry's max_metadata_len")]
+    MetadataTooLong,
+    #[msg("Metadata limit exceeds what one instruction can grow a record by")]
+    MetadataLimitTooHigh,
+    #[msg("Only the registry authority or a registered insurer may dispute a verification")]
+    NotAuthorizedToDispute,
+    #[msg("The record's dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Verification record is under dispute")]
+    RecordDisputed,
+    #[msg("Dispute window must not be negative")]
+    InvalidDisputeWindow,
+    #[msg("Circuit requires a registered provider to co-sign the verification")]
+    ProviderRequired,
+    #[msg("Provider is not registered under this registry")]
+    ProviderNotRegistered,
+    #[msg("Attestation has no valid ed25519 instruction ahead of this one")]
+    InvalidAttestation,
+    #[msg("Attested message doesn't match the provider, patient, circuit or registry")]
+    AttestationMismatch,
+    #[msg("Provider attestation has expired")]
+    AttestationExpired,
+    #[msg("secp256k1 attestation signature has a high s value")]
+    AttestationSignatureMalleable,
+    #[msg("New key is the old one or has itself rotated away")]
+    RotationCycle,
+    #[msg("Signer is not the key the rotation points to")]
+    RotationTargetMismatch,
+    #[msg("Account does not belong to the rotated key")]
+    NotRotatedKeyAccount,
+    #[msg("Claim accounts do not match the records and their new PDAs")]
+    ClaimAccountMismatch,
+    #[msg("Guardian consent needs known scopes, a future expiry and a ward other than the guardian")]
+    InvalidGuardianConsent,
+    #[msg("Only the registry authority or an active registered provider may grant guardian consent")]
+    NotAuthorizedToGrantConsent,
+    #[msg("Only the registry authority, the granter, the guardian or the ward may revoke guardian consent")]
+    NotAuthorizedToRevokeConsent,
+    #[msg("Guardian consent has been revoked")]
+    GuardianConsentRevoked,
+    #[msg("Guardian consent has expired")]
+    GuardianConsentExpired,
+    #[msg("Guardian consent does not cover this action")]
+    GuardianScopeMissing,
+    #[msg("Bulk close accounts must come as record, patient index, rent payer's rotation and refund")]
+    BulkCloseAccountMismatch,
+    #[msg("Record is neither revoked, migrated nor past its expiry and grace period")]
+    RecordNotClosable,
+    #[msg("Patient already has an active verification of this type; pass force_new to replace it")]
+    ActiveVerificationExists,
+    #[msg("Previous record account is not the patient's latest record of this type")]
+    PreviousRecordMismatch,
+    #[msg("Provider has attested its daily quota of verifications")]
+    ProviderQuotaExceeded,
+    #[msg("Patient verified this type too recently; wait out the registry's cooldown")]
+    VerificationCooldownActive,
+    #[msg("Verification cooldown must not be negative")]
+    InvalidVerificationCooldown,
+    #[msg("Previous credential nullifier is not the credential's of the previous epoch")]
+    PreviousCredentialMismatch,
+    #[msg("Registry counter overflowed")]
+    CounterOverflow,
+    #[msg("Proof verifies, so there is no failure to record")]
+    ProofVerified,
+    #[msg("Claim amount must be positive")]
+    InvalidClaimAmount,
+    #[msg("Verification record is not the claiming patient's")]
+    ClaimPatientMismatch,
+    #[msg("Claim has already been adjudicated")]
+    ClaimAlreadyAdjudicated,
+    #[msg("Signer is not the insurer that funded the claim")]
+    ClaimInsurerMismatch,
+    #[msg("Claim escrow must hold exactly the claimed amount")]
+    ClaimEscrowMismatch,
+    #[msg("Account is not the claim's payee")]
+    ClaimPayeeMismatch,
+    #[msg("Encounter has already been claimed")]
+    DuplicateClaim,
+    #[msg("Only a denied claim's nullifier can be released")]
+    ClaimNotDenied,
+    #[msg("Account is not the record written after the one leaving its patient's chain")]
+    RecordSuccessorMismatch,
+    #[msg("Checkpoint must start where the previous one ended")]
+    CheckpointNotContiguous,
+    #[msg("Checkpoint must cover at least one event already emitted")]
+    InvalidCheckpointRange,
+    #[msg("Rent goes back to the account's rent payer, or the key it rotated to")]
+    RentRefundMismatch,
+    #[msg("Record is under a compliance hold")]
+    RecordUnderHold,
+    #[msg("Record is not under a compliance hold")]
+    RecordNotHeld,
+    #[msg("Verification record has been migrated to another registry")]
+    RecordAlreadyMigrated,
+    #[msg("Migration ticket has expired")]
+    MigrationTicketExpired,
+    #[msg("Record already has a migration ticket that has not expired")]
+    MigrationTicketOutstanding,
+    #[msg("Migration ticket is not from the source registry to this one")]
+    MigrationTicketMismatch,
+    #[msg("Record has changed since its migration ticket was issued")]
+    RecordSnapshotMismatch,
+    #[msg("Anonymous records name no patient to migrate")]
+    AnonymousRecordNotMigratable,
+    #[msg("Only the source registry's authority may release a record to another registry")]
+    NotSourceAuthority,
+    #[msg("Record is not the patient's latest of this verification type")]
+    NotLatestRecord,
+    #[msg("IPFS CID is not a CIDv0 or base32 CIDv1")]
+    InvalidCid,
+    #[msg("Pin record is in a legacy layout; run migrate_pin_record")]
+    PinRecordNeedsMigration,
+    #[msg("Pin record is already in the current layout")]
+    PinRecordNotLegacy,
+    #[msg("Pin record was not written under this registry")]
+    PinRecordRegistryMismatch,
+    #[msg("An access pass to the pin record may still be consumed")]
+    ActiveGrantsExist,
+    #[msg("The patient has already pinned this content")]
+    AlreadyPinned,
+    #[msg("No grant lets the signer access the pin record")]
+    AccessDenied,
+    #[msg("Pin record is not confirmed pinned yet")]
+    PinNotConfirmed,
+    #[msg("Pin record was already confirmed or failed")]
+    PinAlreadySettled,
+    #[msg("Signer is not a pinning oracle of the registry")]
+    NotPinningOracle,
+    #[msg("Key is already a pinning oracle of the registry")]
+    PinningOracleAlreadyAdded,
+    #[msg("Registry lists the most pinning oracles it can")]
+    TooManyPinningOracles,
+    #[msg("Pinning provider id is too long")]
+    PinProviderIdTooLong,
+    #[msg("Pin record was not reported failed")]
+    PinNotFailed,
+    #[msg("Only the patient may abandon a failed pin before the registry's timeout")]
+    FailedPinNotAbandonable,
+    #[msg("Failed pin timeout must not be negative")]
+    InvalidFailedPinTimeout,
+    #[msg("Lamports paid don't cover a day of pin storage")]
+    InsufficientStoragePayment,
+    #[msg("Storage treasury must be the registry's storage_treasury")]
+    InvalidStorageTreasury,
+    #[msg("Registry doesn't charge for pin storage")]
+    PinStorageNotPriced,
+    #[msg("Renewal must buy at least one day")]
+    InvalidRenewalDays,
+    #[msg("Pin storage hasn't lapsed past its grace period")]
+    PinNotExpired,
+    #[msg("Pin record has expired")]
+    PinAlreadyExpired,
+    #[msg("No further share of the pin bounty is released yet")]
+    PinBountyNotDue,
+    #[msg("Every oracle owed a share of the pin bounty must be passed writable")]
+    PinBountyOwed,
+    #[msg("Pin bounty terms need a positive period and period count")]
+    InvalidPinBountyTerms,
+    #[msg("Replication target must be between one and the most replicas a pin counts")]
+    InvalidReplicationTarget,
+    #[msg("Signer is not counted among the pin's replicas")]
+    NotPinReplica,
+    #[msg("Pin counts as many replicas as it holds")]
+    PinReplicasFull,
+    #[msg("Bulk pin must hold between 1 and MAX_BULK_PINS entries")]
+    InvalidBulkPinCount,
+    #[msg("Bulk pin accounts must be each entry's writable pin record PDA")]
+    BulkPinAccountMismatch,
+    #[msg("Storage id does not fit its backend")]
+    InvalidStorageId,
+    #[msg("Storage deal needs a provider address of at most MAX_DEAL_PROVIDER_LEN bytes and a future expiry")]
+    InvalidStorageDeal,
+    #[msg("Pin holds as many storage deals as it records")]
+    TooManyStorageDeals,
+    #[msg("Pin holds no storage deal of that id")]
+    StorageDealNotFound,
+    #[msg("Storage deal doesn't end within the registry's warning window")]
+    DealNotExpiringSoon,
+    #[msg("Deal expiry warning window must be positive")]
+    InvalidDealExpiryWarning,
+    #[msg("Pin already holds content of this data hash")]
+    PinContentUnchanged,
+    #[msg("Access pass would outlast the registry's max_access_pass_slots")]
+    AccessPassTooLong,
+    #[msg("Access pass slot limit must be positive")]
+    InvalidMaxAccessPassSlots,
+    #[msg("Signer neither holds the pin nor is owed a share of its bounty")]
+    NoPinBountyShare,
+}
+
+/// A Groth16 proof decoded into the alt_bn128 syscall encoding
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+impl Groth16Proof {
+    /// Decode a proof submitted in any `ProofFormat`. Compressed points are
+    /// expanded with the alt_bn128 decompression syscalls.
+    pub fn decode(bytes: &[u8], format: ProofFormat) -> Result<Self> {
+        Ok(verifier_core::decode_proof(bytes, format)?)
+    }
+
+    /// See `verifier_core::check_proof_points`
+    pub fn check_points(&self) -> Result<()> {
+        Ok(verifier_core::check_proof_points(self)?)
+    }
+
+    /// The canonical serialization of this proof in `format`, byte-for-byte what
+    /// arkworks (or snarkjs) would produce for the same points
+    pub fn encode(&self, format: ProofFormat) -> Result<Vec<u8>> {
+        verifier_core::encode_proof(self, format).ok_or_else(|| error!(HealthcareError::InvalidProofEncoding))
+    }
+
+    /// `compute_verification_hash` over the arkworks compressed encoding. Every
+    /// accepted encoding of the same proof maps to the same hash, so it can serve as
+    /// an audit identifier.
+    pub fn hash(&self, public_inputs: &[[u8; 32]]) -> Result<[u8; 32]> {
+        Ok(compute_verification_hash(&self.encode(ProofFormat::Compressed)?, public_inputs))
+    }
+}
+
+/// Verify Groth16 zk-SNARK proof for patient eligibility
+/// 
+/// The verifying key is read from the `VerifyingKeyPDA` account and public inputs
+/// are packed as consecutive 32-byte field elements in the byte order of `format`.
+/// Both are re-encoded for the alt_bn128 syscalls, which compute `vk_x` and run the
+/// single pairing check `e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1`.
+fn verify_groth16_proof(
+    vk_bytes: &[u8],
+    prepared_vk_bytes: &[u8],
+    proof: &Groth16Proof,
+    public_inputs_bytes: &[u8],
+    format: ProofFormat,
+) -> Result<bool> {
+    let prepared = cached_or_prepared(vk_bytes, prepared_vk_bytes)?;
+    let public_inputs = parse_public_inputs(public_inputs_bytes, format)?;
+    Ok(verifier_core::verify_prepared(&prepared, proof, &public_inputs)?)
+}
+
+/// Batch Groth16 verification with the random linear combination trick: for
+/// Fiat-Shamir weights `r_i`, every proof holds (with overwhelming probability)
+/// iff `prod e(-r_i A_i, B_i) * e(sum(r_i) alpha, beta) * e(sum(r_i vk_x_i), gamma)
+/// * e(sum(r_i C_i), delta) == 1`. That is N + 3 pairings instead of 4N.
+fn verify_groth16_batch(
+    vk_bytes: &[u8],
+    prepared_vk_bytes: &[u8],
+    entries: &[(&Groth16Proof, &[[u8; 32]])],
+) -> Result<bool> {
+    let prepared = cached_or_prepared(vk_bytes, prepared_vk_bytes)?;
+    let [alpha, beta, gamma, delta, ic] = split_prepared_vk(&prepared);
+    let invalid = || error!(HealthcareError::InvalidProofPoint);
+
+    let weights = batch_weights(&prepared, entries);
+    let mut weight_sum = [0u8; 32];
+    let mut vk_x_sum = [0u8; G1_LEN];
+    let mut c_sum = [0u8; G1_LEN];
+    let mut pairing_input = Vec::with_capacity((entries.len() + 3) * (G1_LEN + G2_LEN));
+    for ((proof, public_inputs), weight) in entries.iter().zip(&weights) {
+        check_ic_len(ic, public_inputs.len())?;
+        let vk_x = compute_vk_x(ic, public_inputs)?;
+        vk_x_sum = g1_add(&vk_x_sum, &g1_mul(&vk_x, weight).ok_or_else(invalid)?).ok_or_else(invalid)?;
+        c_sum = g1_add(&c_sum, &g1_mul(&proof.c, weight).ok_or_else(invalid)?).ok_or_else(invalid)?;
+        scalar_add(&mut weight_sum, weight);
+
+        pairing_input.extend_from_slice(&negate_g1(&g1_mul(&proof.a, weight).ok_or_else(invalid)?));
+        pairing_input.extend_from_slice(&proof.b);
+    }
+    let alpha_sum = g1_mul(alpha, &weight_sum).ok_or_else(invalid)?;
+    for (g1, g2) in [(&alpha_sum[..], beta), (&vk_x_sum[..], gamma), (&c_sum[..], delta)] {
+        pairing_input.extend_from_slice(g1);
+        pairing_input.extend_from_slice(g2);
+    }
+
+    let result = alt_bn128_pairing(&pairing_input).map_err(|_| invalid())?;
+    Ok(result.last() == Some(&1))
+}
+
+/// Nonzero 128-bit weights from a hash of the key and every proof and input in
+/// the batch, so a prover can't choose proofs whose errors cancel out
+fn batch_weights(prepared_vk: &[u8], entries: &[(&Groth16Proof, &[[u8; 32]])]) -> Vec<[u8; 32]> {
+    let mut transcript: Vec<&[u8]> = vec![prepared_vk];
+    for (proof, public_inputs) in entries {
+        transcript.extend([&proof.a[..], &proof.b[..], &proof.c[..]]);
+        transcript.extend(public_inputs.iter().map(|input| &input[..]));
+    }
+    let seed = keccak::hashv(&transcript).to_bytes();
+
+    (0..entries.len() as u32)
+        .map(|index| {
+            let hash = keccak::hashv(&[&seed, &index.to_le_bytes()]).to_bytes();
+            let mut weight = [0u8; 32];
+            weight[16..].copy_from_slice(&hash[..16]);
+            weight[31] |= 1;
+            weight
+        })
+        .collect()
+}
+
+/// Big-endian `a += b`. Only used on sums of 128-bit weights, far below the modulus.
+fn scalar_add(a: &mut [u8; 32], b: &[u8; 32]) {
+    let mut carry = 0u16;
+    for i in (0..32).rev() {
+        let sum = a[i] as u16 + b[i] as u16 + carry;
+        a[i] = sum as u8;
+        carry = sum >> 8;
+    }
+}
+
+/// Bookkeeping for a key upload closed before finalization. Anchor's `close`
+/// returns the rent once the instruction succeeds.
+fn close_vk_upload(
+    registry: &mut Account<HealthcareRegistry>,
+    verifying_key: &AccountLoader<VerifyingKeyPDA>,
+    circuit_id: String,
+) -> Result<()> {
+    let key = verifying_key.load()?;
+    registry.registered_circuits = registry.registered_circuits.saturating_sub(1);
+    emit!(VerifyingKeyClosed {
+        seq: registry.next_event_seq()?,
+        registry: registry.key(),
+        circuit_id: circuit_id.clone(),
+        verifying_key: verifying_key.key(),
+        authority: key.authority,
+    });
+    msg!("Upload of verifying key for circuit {} closed at {} of {} bytes", circuit_id, key.written, key.total_len);
+    Ok(())
+}
+
+/// The cached prepared key, or one prepared on the fly if `prepare_vk` never ran
+fn cached_or_prepared<'a>(vk_bytes: &[u8], prepared_vk_bytes: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+    if prepared_vk_bytes.is_empty() {
+        Ok(Cow::Owned(prepare_vk_bytes(vk_bytes)?))
+    } else {
+        Ok(Cow::Borrowed(prepared_vk_bytes))
+    }
+}
+
+/// The record at `info`, if `close_records_bulk` may close it to `refund_to`
+/// with `patient_index` as its index and `rotation` as its rent payer's
+/// `KeyRotation` address, at `now` in `time`
+fn closable_record(
+    info: &AccountInfo,
+    patient_index: &AccountInfo,
+    rotation: &AccountInfo,
+    refund_to: &AccountInfo,
+    registry: &Pubkey,
+    time: TimeSource,
+    now: i64,
+) -> Result<VerificationRecord> {
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    let record = VerificationRecord::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require!(record.is_in_registry(registry), HealthcareError::RecordRegistryMismatch);
+    // Anonymous records have no rent payer and are never closed
+    require!(
+        record.rent_payer != Pubkey::default() && rotation.key() == KeyRotation::address(&record.rent_payer),
+        HealthcareError::BulkCloseAccountMismatch
+    );
+    require_keys_eq!(
+        refund_to.key(),
+        KeyRotation::refund_key(record.rent_payer, rotation)?,
+        HealthcareError::RentRefundMismatch
+    );
+    require!(
+        patient_index.key() == PatientIndex::address(&record.patient_pubkey),
+        HealthcareError::PatientIndexMismatch
+    );
+    let expires_at = record.expires_in(time);
+    let past_grace = record.expires_at != 0 && now >= expires_at.saturating_add(time.span(RECORD_GC_GRACE_SECS));
+    require!(
+        matches!(record.status, RecordStatus::Revoked | RecordStatus::Migrated) || past_grace,
+        HealthcareError::RecordNotClosable
+    );
+    require!(!record.is_held(), HealthcareError::RecordUnderHold);
+    Ok(record)
+}
+
+/// A pin as one of the pin instructions asks for it, see `write_pin`
+struct NewPin<'a> {
+    backend: StorageBackend,
+    storage_id: &'a [u8],
+    data_hash: [u8; 32],
+    /// Storage days bought, see `buy_pin_storage`
+    paid_days: u64,
+    /// Escrowed for the pinning oracles, see `escrow_pin_bounty`
+    bounty_lamports: u64,
+    category: DataCategory,
+    tag: Option<[u8; 8]>,
+}
+
+/// Write `pin_record`, fresh from `create_pin_record`, to its account `info`
+/// as `patient`'s `pin`, by `guardian` if one acted for the patient, and
+/// return the `DataPinned` event for the caller to emit
+fn pin_data(
+    pin_record: &mut IpfsPinRecord,
+    info: &AccountInfo,
+    registry: &mut Account<HealthcareRegistry>,
+    patient: Pubkey,
+    guardian: Option<Pubkey>,
+    pin: NewPin,
+) -> Result<DataPinned> {
+    let clock = Clock::get()?;
+    write_pin(pin_record, registry, patient, guardian, &pin, &clock)?;
+    pin_record.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    registry.ipfs_pin_count = checked_count(registry.ipfs_pin_count, 1)?;
+
+    Ok(DataPinned {
+        seq: registry.next_event_seq()?,
+        registry: registry.key(),
+        patient,
+        ipfs_cid: pin_record.cid_string(),
+        data_hash: pin.data_hash,
+        slot: clock.slot,
+        guardian,
+        paid_through: pin_record.paid_through,
+        backend: pin.backend,
+        category: pin.category,
+    })
+}
+
+/// Fill in `pin` as a new pin under the registry's current terms, leaving
+/// the counts and the event to the caller
+fn write_pin(
+    pin_record: &mut IpfsPinRecord,
+    registry: &Account<HealthcareRegistry>,
+    patient: Pubkey,
+    guardian: Option<Pubkey>,
+    pin: &NewPin,
+    clock: &Clock,
+) -> Result<()> {
+    pin_record.version = IpfsPinRecord::VERSION;
+    pin_record.set_storage_id(pin.backend, pin.storage_id)?;
+    pin_record.patient = patient;
+    pin_record.data_hash = pin.data_hash;
+    pin_record.pinned_at = clock.unix_timestamp;
+    pin_record.access_count = 0;
+    pin_record.slot = clock.slot;
+    pin_record.registry = registry.key();
+    pin_record.rent_payer = guardian.unwrap_or(patient);
+    pin_record.grants_expire_at_slot = 0;
+    pin_record.last_accessed_at = 0;
+    pin_record.status = PinStatus::Requested;
+    pin_record.oracle = Pubkey::default();
+    pin_record.settled_at = 0;
+    pin_record.paid_through = 0;
+    pin_record.extend_paid_through(clock.unix_timestamp, pin.paid_days)?;
+    pin_record.bounty_lamports = pin.bounty_lamports;
+    pin_record.bounty_released = 0;
+    pin_record.bounty_period_secs = registry.pin_bounty_period_secs;
+    pin_record.bounty_periods = registry.pin_bounty_periods;
+    pin_record.bounty_periods_settled = 0;
+    pin_record.bounty_settled_through = 0;
+    pin_record.bounty_shares = [BountyShare::default(); MAX_BOUNTY_SHARES];
+    pin_record.replication_target = registry.pin_replication_target;
+    pin_record.replicas = [Pubkey::default(); MAX_PIN_REPLICAS];
+    pin_record.replica_confirmed_at = [0; MAX_PIN_REPLICAS];
+    pin_record.category = pin.category;
+    pin_record.tag = pin.tag;
+    Ok(())
+}
+
+/// Create and fill in `info` as the patient's pin of `entry`, and `claim` as
+/// its content claim, failing with `BulkPinAccountMismatch` if either isn't
+/// its address and `AlreadyPinned` if the pin or the claim exists
+fn bulk_pin<'info>(
+    accounts: &PinMedicalDataBulk<'info>,
+    info: &AccountInfo<'info>,
+    claim: &AccountInfo<'info>,
+    entry: &PinEntry,
+    paid_days: u64,
+    clock: &Clock,
+) -> Result<IpfsPinRecord> {
+    let patient = accounts.patient.key();
+    let seeds: &[&[u8]] = &[b"pin", patient.as_ref(), &entry.data_hash];
+    let (address, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    require!(info.key() == address && info.is_writable, HealthcareError::BulkPinAccountMismatch);
+    let claim_seeds: &[&[u8]] = &[b"pin_content", patient.as_ref(), &entry.data_hash];
+    let (claim_address, claim_bump) = Pubkey::find_program_address(claim_seeds, &crate::ID);
+    require!(claim.key() == claim_address && claim.is_writable, HealthcareError::BulkPinAccountMismatch);
+    let seeds: &[&[u8]] = &[b"pin", patient.as_ref(), &entry.data_hash, &[bump]];
+    let mut pin_record = create_pin_record(&accounts.patient, info, &accounts.system_program, seeds)?;
+    let seeds: &[&[u8]] = &[b"pin_content", patient.as_ref(), &entry.data_hash, &[claim_bump]];
+    claim_pin_content(&accounts.patient, claim, &accounts.system_program, seeds, address)?;
+    let pin = NewPin {
+        backend: StorageBackend::Ipfs,
+        storage_id: entry.ipfs_cid.as_bytes(),
+        data_hash: entry.data_hash,
+        paid_days,
+        bounty_lamports: 0,
+        category: entry.category,
+        tag: entry.tag,
+    };
+    write_pin(&mut pin_record, &accounts.registry, patient, None, &pin, clock)?;
+    pin_record.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    Ok(pin_record)
+}
+
+/// Create the `PinContentClaim` PDA `info` of `seeds` for `pin_record`, rent
+/// paid by `payer`. Fails with `AlreadyPinned`, naming the pin holding the
+/// content, if the claim exists.
+fn claim_pin_content<'info>(
+    payer: &AccountInfo<'info>,
+    info: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    pin_record: Pubkey,
+) -> Result<()> {
+    if info.owner == &crate::ID {
+        let claim = PinContentClaim::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        msg!("Content already pinned at {}", claim.pin_record);
+        return err!(HealthcareError::AlreadyPinned);
+    }
+    create_pda_account(payer, info, system_program, PinContentClaim::SPACE, seeds)?;
+    PinContentClaim { pin_record }.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+/// Close the `PinContentClaim` at `info` if `pin_record` holds it, returning
+/// its rent to `to`. A pin written before claims holds none, and the claim
+/// may be another pin's that took the content since. Called after the
+/// instruction's last CPI, so no transfer is seen across one.
+fn release_pin_content(info: &AccountInfo, pin_record: Pubkey, to: &AccountInfo) -> Result<()> {
+    if info.owner != &crate::ID
+        || PinContentClaim::try_deserialize(&mut &info.try_borrow_data()?[..])?.pin_record != pin_record
+    {
+        return Ok(());
+    }
+    **to.try_borrow_mut_lamports()? += info.lamports();
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&System::id());
+    info.realloc(0, false)?;
+    Ok(())
+}
+
+/// Create the pin record PDA `info` of `seeds`, rent paid by `payer`, and
+/// read it back blank for `write_pin`. Fails with `AlreadyPinned`, naming the
+/// pin, if the content is pinned there already, whatever its length.
+fn create_pin_record<'info>(
+    payer: &AccountInfo<'info>,
+    info: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+) -> Result<IpfsPinRecord> {
+    if info.owner == &crate::ID {
+        msg!("Content already pinned at {}", info.key());
+        return err!(HealthcareError::AlreadyPinned);
+    }
+    create_pda_account(payer, info, system_program, IpfsPinRecord::SPACE, seeds)?;
+    IpfsPinRecord::try_deserialize_unchecked(&mut &info.try_borrow_data()?[..])
+}
+
+/// Root of the Merkle tree over `data_hashes` that `DataPinnedBatch` carries,
+/// built as `client::AuditTree` builds its tree over events: leaves
+/// `keccak(0 || data_hash)` in entry order, paired up level by level with
+/// `keccak(1 || left || right)`, the odd node at the end of a level moving up
+/// unpaired
+pub fn data_hash_root(data_hashes: &[[u8; 32]]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> =
+        data_hashes.iter().map(|data_hash| keccak::hashv(&[&[0], data_hash]).to_bytes()).collect();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => keccak::hashv(&[&[1], left, right]).to_bytes(),
+                [odd] => *odd,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+/// Charge `payer` for the whole days of pin storage `lamports` buys at the
+/// registry's `price_lamports_per_day`, paying its `storage_treasury`, and
+/// return the days bought: none while storage is free, else at least one.
+fn buy_pin_storage<'info>(
+    registry: &HealthcareRegistry,
+    lamports: u64,
+    payer: &Signer<'info>,
+    storage_treasury: &Option<UncheckedAccount<'info>>,
+    system_program: &Program<'info, System>,
+) -> Result<u64> {
+    use anchor_lang::system_program::{transfer, Transfer};
+    let price = registry.price_lamports_per_day;
+    if price == 0 {
+        return Ok(0);
+    }
+    let days = lamports / price;
+    require!(days > 0, HealthcareError::InsufficientStoragePayment);
+    let Some(storage_treasury) = storage_treasury else {
+        return err!(HealthcareError::InvalidStorageTreasury);
+    };
+    let cost = days * price;
+    require!(payer.lamports() >= cost, HealthcareError::InsufficientStoragePayment);
+    let accounts = Transfer {
+        from: payer.to_account_info(),
+        to: storage_treasury.to_account_info(),
+    };
+    transfer(CpiContext::new(system_program.to_account_info(), accounts), cost)?;
+    msg!("Paid {} lamports for {} days of pin storage", cost, days);
+    Ok(days)
+}
+
+/// Move `lamports` of `payer`'s into `pin_record` as its bounty
+fn escrow_pin_bounty<'info>(
+    payer: &Signer<'info>,
+    pin_record: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    lamports: u64,
+) -> Result<()> {
+    use anchor_lang::system_program::{transfer, Transfer};
+    if lamports == 0 {
+        return Ok(());
+    }
+    let accounts = Transfer {
+        from: payer.to_account_info(),
+        to: pin_record.clone(),
+    };
+    transfer(CpiContext::new(system_program.to_account_info(), accounts), lamports)
+}
+
+/// Pay each oracle its unclaimed `bounty_shares` of `pin_record` out of the
+/// account ahead of closing it, finding the oracle among `oracles` by key.
+/// Fails with `PinBountyOwed` for an oracle owed a share that isn't there,
+/// writable.
+fn pay_bounty_shares(pin_record: &mut Account<IpfsPinRecord>, oracles: &[AccountInfo]) -> Result<()> {
+    let info = pin_record.to_account_info();
+    for share in pin_record.bounty_shares.iter_mut().filter(|share| share.lamports > 0) {
+        let oracle = oracles
+            .iter()
+            .find(|oracle| oracle.key() == share.oracle && oracle.is_writable)
+            .ok_or_else(|| error!(HealthcareError::PinBountyOwed))?;
+        **info.try_borrow_mut_lamports()? -= share.lamports;
+        **oracle.try_borrow_mut_lamports()? += std::mem::take(share).lamports;
+    }
+    Ok(())
+}
+
+/// Return what of `pin_record`'s bounty no oracle was released to `patient`,
+/// once the shares are paid, so only the rent is left for the close
+fn refund_unreleased_bounty(pin_record: &Account<IpfsPinRecord>, patient: &AccountInfo) -> Result<u64> {
+    let lamports = pin_record.bounty_lamports - pin_record.bounty_released;
+    **pin_record.to_account_info().try_borrow_mut_lamports()? -= lamports;
+    **patient.try_borrow_mut_lamports()? += lamports;
+    Ok(lamports)
+}
+
+/// Copy the rotated key's record at `record_info` to `new_record_info`, which
+/// must be `derive_verification_pda` of the new key's next record nonce, and
+/// move the record between the indexes and their chains, relinking `successor`
+/// in the old one. The record must be `registry`'s. The caller closes the old
+/// account.
+fn claim_verification_record<'info>(
+    record_info: &AccountInfo<'info>,
+    new_record_info: &AccountInfo<'info>,
+    successor: Option<&AccountInfo<'info>>,
+    old_key: &Pubkey,
+    new_patient: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    old_index: &mut PatientIndex,
+    new_index: &mut PatientIndex,
+    registry: &mut Account<HealthcareRegistry>,
+) -> Result<()> {
+    require_keys_eq!(*record_info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    let record = VerificationRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+    require_keys_eq!(record.patient_pubkey, *old_key, HealthcareError::NotRotatedKeyAccount);
+    require!(record.is_in_registry(&registry.key()), HealthcareError::RecordRegistryMismatch);
+    require!(!record.is_held(), HealthcareError::RecordUnderHold);
+    let new_key = new_patient.key();
+    let verification_type = record.verification_type;
+    let nonce = new_index.next_record_nonce;
+    let (address, bump) = derive_verification_pda(&new_key, verification_type, nonce);
+    require_keys_eq!(new_record_info.key(), address, HealthcareError::ClaimAccountMismatch);
+    create_pda_account(
+        new_patient,
+        new_record_info,
+        system_program,
+        record.space_with_metadata(record.metadata.len()),
+        &[b"verification", new_key.as_ref(), &[verification_type as u8], &nonce.to_le_bytes(), &[bump]],
+    )?;
+    unlink_with_successor(old_index, record_info.key(), &record, successor)?;
+    let claimed = VerificationRecord {
+        patient_pubkey: new_key,
+        rent_payer: new_key,
+        bump,
+        previous_record: new_index.link_record(address),
+        ..record
+    };
+    claimed.try_serialize(&mut &mut new_record_info.try_borrow_mut_data()?[..])?;
+
+    let active = u32::from(claimed.is_verified());
+    let slot = verification_type as usize;
+    old_index.verification_count = old_index.verification_count.saturating_sub(1);
+    old_index.type_counts[slot] = old_index.type_counts[slot].saturating_sub(1);
+    old_index.active_count = old_index.active_count.saturating_sub(active);
+    new_index.next_record_nonce += 1;
+    new_index.verification_count += 1;
+    new_index.type_counts[slot] += 1;
+    new_index.active_count += active;
+    if claimed.timestamp >= new_index.last_verified_at {
+        new_index.last_verification = address;
+        new_index.last_verified_at = claimed.timestamp;
+    }
+    if new_index.last_record_by_type[slot] == Pubkey::default()
+        || claimed.timestamp >= new_index.last_verified_at_by_type[slot]
+    {
+        new_index.last_record_by_type[slot] = address;
+    }
+
+    emit!(RecordClaimed {
+        seq: registry.next_event_seq()?,
+        registry: registry.key(),
+        record: record_info.key(),
+        new_record: address,
+        old_key: *old_key,
+        new_key,
+    });
+    Ok(())
+}
+
+/// Create a program-owned PDA like Anchor's `init`, including when lamports were
+/// sent to the address beforehand (which would make `create_account` fail)
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    use anchor_lang::system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    };
+    let signer = &[seeds];
+    let rent = Rent::get()?.minimum_balance(space);
+    let current = target.lamports();
+    if current == 0 {
+        let accounts = CreateAccount {
+            from: payer.clone(),
+            to: target.clone(),
+        };
+        let cpi = CpiContext::new_with_signer(system_program.clone(), accounts, signer);
+        return create_account(cpi, rent, space as u64, &crate::ID);
+    }
+
+    if rent > current {
+        let accounts = Transfer {
+            from: payer.clone(),
+            to: target.clone(),
+        };
+        transfer(CpiContext::new(system_program.clone(), accounts), rent - current)?;
+    }
+    let accounts = Allocate {
+        account_to_allocate: target.clone(),
+    };
+    allocate(CpiContext::new_with_signer(system_program.clone(), accounts, signer), space as u64)?;
+    let accounts = Assign {
+        account_to_assign: target.clone(),
+    };
+    assign(CpiContext::new_with_signer(system_program.clone(), accounts, signer), &crate::ID)
+}
+
+/// The cached result for the submission `nullifier` belongs to, if `cache` holds
+/// a fresh entry for it. The nullifier ties the entry to this exact submission:
+/// its record is the one the entry was written for.
+fn cached_result(
+    cache: &Option<UncheckedAccount>,
+    nullifier: &ProofNullifier,
+    circuit_id: &str,
+    slot: u64,
+) -> Result<Option<VerificationResult>> {
+    let Some(cache) = cache else {
+        return Ok(None);
+    };
+    if cache.owner != &crate::ID {
+        return Ok(None);
+    }
+    let entry = VerificationCache::try_deserialize(&mut &cache.try_borrow_data()?[..])?;
+    let is_hit = entry.result.record == nullifier.verification && entry.circuit_id == circuit_id && entry.is_fresh(slot);
+    Ok(is_hit.then_some(entry.result))
+}
+
+/// Create the `VerificationCache` of `entry.result.proof_hash` at `cache`
+fn cache_result<'info>(
+    cache: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    entry: VerificationCache,
+) -> Result<()> {
+    let proof_hash = entry.result.proof_hash;
+    let (address, bump) = Pubkey::find_program_address(&[b"cache", &proof_hash], &crate::ID);
+    require!(cache.key() == address, HealthcareError::CacheAccountMismatch);
+    create_pda_account(payer, cache, system_program, VerificationCache::SPACE, &[b"cache", &proof_hash, &[bump]])?;
+    let entry = VerificationCache { bump, ..entry };
+    entry.try_serialize(&mut &mut cache.try_borrow_mut_data()?[..])
+}
+
+/// What `verify_eligibility_proof` established about a submission
+struct VerifiedProof {
+    proof_hash: [u8; 32],
+    hash_algo: HashAlgo,
+    public_inputs_hash: [u8; 32],
+    /// The public inputs, parsed
+    inputs: Vec<[u8; 32]>,
+}
+
+/// Hold a `verify_eligibility` submission to the commitment `submitter` made to
+/// it. A commitment is only required while the registry sets
+/// `commit_reveal_slots`, but one that is passed is always checked.
+fn check_commitment(
+    commitment: &Option<Account<VerificationCommitment>>,
+    registry: &HealthcareRegistry,
+    salt: Option<[u8; 32]>,
+    proof: &[u8],
+    public_inputs: &[u8],
+    submitter: &Pubkey,
+    slot: u64,
+) -> Result<()> {
+    let Some(commitment) = commitment else {
+        require!(registry.commit_reveal_slots == 0, HealthcareError::CommitmentMismatch);
+        return Ok(());
+    };
+    let salt = salt.ok_or(HealthcareError::CommitmentMismatch)?;
+    require!(
+        commitment.commitment == VerificationCommitment::hash(proof, public_inputs, &salt, submitter),
+        HealthcareError::CommitmentMismatch
+    );
+    require!(slot > commitment.committed_at_slot, HealthcareError::CommitmentTooRecent);
+    require!(slot <= commitment.expires_at_slot, HealthcareError::CommitmentExpired);
+    Ok(())
+}
+
+/// The provider attesting a `verify_eligibility`, by co-signing or by
+/// `check_attestation`, which must be registered under `registry` and active.
+/// Only a circuit that `needs_provider` requires one.
+fn attesting_provider(
+    verifying_key: &VerifyingKey,
+    registry: &Pubkey,
+    provider: Option<Pubkey>,
+    registration: &Option<Account<ProviderRegistration>>,
+) -> Result<Option<Pubkey>> {
+    match (provider, registration) {
+        (None, None) => {
+            require!(!verifying_key.needs_provider(), HealthcareError::ProviderRequired);
+            Ok(None)
+        }
+        (Some(provider), Some(registration)) => {
+            require!(
+                registration.registry == *registry && registration.provider == provider,
+                HealthcareError::ProviderNotRegistered
+            );
+            require!(registration.active, HealthcareError::ProviderInactive);
+            Ok(Some(provider))
+        }
+        _ => err!(HealthcareError::ProviderNotRegistered),
+    }
+}
+
+/// The provider of an offline `attestation`: the precompile instruction at its
+/// `sig_index`, ahead of this one, must have checked the provider's signature
+/// over an unexpired `attestation_message` for `patient` and `circuit_id`, or
+/// under `Secp256k1` over its `eth_signed_message`. `msg_hash` is the keccak of
+/// what was signed.
+fn check_attestation(
+    attestation: &ProviderAttestation,
+    registration: &Option<Account<ProviderRegistration>>,
+    instructions: &Option<UncheckedAccount>,
+    registry: &Pubkey,
+    patient: &Pubkey,
+    circuit_id: &str,
+    now: i64,
+) -> Result<Pubkey> {
+    use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+    use anchor_lang::solana_program::{ed25519_program, secp256k1_program};
+
+    let instructions = instructions.as_ref().ok_or(HealthcareError::InvalidAttestation)?.to_account_info();
+    let current = load_current_index_checked(&instructions)?;
+    require!(u16::from(attestation.sig_index) < current, HealthcareError::InvalidAttestation);
+    let ix = load_instruction_at_checked(attestation.sig_index as usize, &instructions)?;
+    let body_len = ATTESTATION_DOMAIN.len() + 72 + circuit_id.len();
+    let (message, body) = match attestation.scheme {
+        AttestationScheme::Ed25519 => {
+            require!(ix.program_id == ed25519_program::ID, HealthcareError::InvalidAttestation);
+            let (signer, message) = ed25519_signed_message(&ix.data).ok_or(HealthcareError::InvalidAttestation)?;
+            require!(signer == attestation.provider.as_ref(), HealthcareError::AttestationMismatch);
+            (message, message)
+        }
+        AttestationScheme::Secp256k1 => {
+            require!(ix.program_id == secp256k1_program::ID, HealthcareError::InvalidAttestation);
+            let (eth_address, signature, message) = secp256k1_signed_message(&ix.data, attestation.sig_index)
+                .ok_or(HealthcareError::InvalidAttestation)?;
+            require!(
+                signature[32..64] <= SECP256K1_HALF_ORDER[..],
+                HealthcareError::AttestationSignatureMalleable
+            );
+            let registration = registration.as_ref().ok_or(HealthcareError::ProviderNotRegistered)?;
+            require!(
+                registration.eth_address != [0; 20] && eth_address == registration.eth_address,
+                HealthcareError::AttestationMismatch
+            );
+            let prefix = eth_signed_message_prefix(body_len);
+            let body = message.strip_prefix(prefix.as_slice()).ok_or(HealthcareError::AttestationMismatch)?;
+            (message, body)
+        }
+    };
+
+    require!(
+        keccak::hash(message).0 == attestation.msg_hash,
+        HealthcareError::AttestationMismatch
+    );
+    let expiry_at = ATTESTATION_DOMAIN.len() + 64;
+    let expires_at = body
+        .get(expiry_at..expiry_at + 8)
+        .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(HealthcareError::AttestationMismatch)?;
+    require!(
+        body == attestation_message(registry, patient, circuit_id, expires_at).as_slice(),
+        HealthcareError::AttestationMismatch
+    );
+    require!(now < expires_at, HealthcareError::AttestationExpired);
+    Ok(attestation.provider)
+}
+
+/// Public key and message of an `ed25519_program` instruction checking a
+/// single signature whose key and message are inside the instruction itself
+fn ed25519_signed_message(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    // A signature count and a padding byte, then seven offsets: the signature's,
+    // public key's and message's, each with the index of the instruction holding
+    // it, and the message size between the last two
+    if data.first() != Some(&1) {
+        return None;
+    }
+    let offset = |field: usize| {
+        let at = 2 + 2 * field;
+        data.get(at..at + 2).map(|bytes| usize::from(u16::from_le_bytes([bytes[0], bytes[1]])))
+    };
+    let this_instruction = usize::from(u16::MAX);
+    if [1, 3, 6].into_iter().any(|field| offset(field) != Some(this_instruction)) {
+        return None;
+    }
+    let (key_at, message_at, message_len) = (offset(2)?, offset(4)?, offset(5)?);
+    Some((data.get(key_at..key_at + 32)?, data.get(message_at..message_at + message_len)?))
+}
+
+/// Ethereum address, signature and message of a `secp256k1_program` instruction,
+/// at `index` in the transaction, checking a single signature whose parts are
+/// all inside the instruction itself
+fn secp256k1_signed_message(data: &[u8], index: u8) -> Option<([u8; 20], &[u8], &[u8])> {
+    // A signature count, then the signature's, address's and message's offsets,
+    // each followed by the index of the instruction holding it, and the message
+    // size between the last two
+    let offsets = data.get(1..12).filter(|_| data[0] == 1)?;
+    let offset = |at: usize| usize::from(u16::from_le_bytes([offsets[at], offsets[at + 1]]));
+    if [offsets[2], offsets[5], offsets[10]] != [index; 3] {
+        return None;
+    }
+    let (signature_at, address_at, message_at, message_len) = (offset(0), offset(3), offset(6), offset(8));
+    let eth_address = data.get(address_at..address_at + 20)?.try_into().ok()?;
+    Some((
+        eth_address,
+        data.get(signature_at..signature_at + 64)?,
+        data.get(message_at..message_at + message_len)?,
+    ))
+}
+
+/// Prefix of an Ethereum `personal_sign` message of `len` bytes
+fn eth_signed_message_prefix(len: usize) -> Vec<u8> {
+    format!("\x19Ethereum Signed Message:\n{}", len).into_bytes()
+}
+
+/// The checks and verification the named and anonymous eligibility flows share:
+/// input bindings (the patient's only when there is one), the registry's hash
+/// policy, then the proof itself
+fn verify_eligibility_proof(
+    verifying_key: &VerifyingKey,
+    registry: &HealthcareRegistry,
+    patient: Option<&Pubkey>,
+    proof: &[u8],
+    proof_format: ProofFormat,
+    public_inputs: &[u8],
+    hash_algo: HashAlgo,
+) -> Result<VerifiedProof> {
+    if let Some(patient) = patient {
+        verifying_key.check_patient_binding(public_inputs, proof_format, patient)?;
+    }
+    verifying_key.check_domain_binding(public_inputs, proof_format, registry)?;
+    verifying_key.check_freshness(public_inputs, proof_format, registry.time_source, &Clock::get()?)?;
+    registry.check_hash_algo(hash_algo)?;
+
+    let verdict = verifying_key.verifier().verify(verifying_key, proof, proof_format, public_inputs)?;
+    require!(verdict.verified, HealthcareError::PairingCheckFailed);
+    let inputs = verifying_key.verifier().parse_public_inputs(public_inputs, proof_format)?;
+    Ok(VerifiedProof {
+        proof_hash: hash_algo.proof_hash(verdict.proof_hash, &inputs)?,
+        hash_algo,
+        public_inputs_hash: compute_public_inputs_hash(&inputs),
+        inputs,
+    })
+}
+
+/// A record a `verify_*` instruction writes, which the patient's next record of
+/// its type would duplicate while it is still current
+trait ReplaceableRecord: AccountSerialize + AccountDeserialize {
+    fn is_current(&self, clock: &Clock) -> bool;
+
+    /// Take the record at `address` out of use for a `force_new` replacement.
+    /// Records that grant nothing further are only superseded, and keep their
+    /// contents.
+    fn retire(
+        &mut self,
+        _address: Pubkey,
+        _clock: &Clock,
+        _patient_index: &mut PatientIndex,
+        _registry: &mut Account<HealthcareRegistry>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ReplaceableRecord for VerificationRecord {
+    fn is_current(&self, clock: &Clock) -> bool {
+        self.is_active(clock)
+    }
+
+    fn retire(
+        &mut self,
+        address: Pubkey,
+        _clock: &Clock,
+        patient_index: &mut PatientIndex,
+        registry: &mut Account<HealthcareRegistry>,
+    ) -> Result<()> {
+        require!(!self.is_held(), HealthcareError::RecordUnderHold);
+        self.transition(address, RecordStatus::Expired, registry)?;
+        patient_index.active_count = patient_index.active_count.saturating_sub(1);
+        registry.expired_count = checked_count(registry.expired_count, 1)?;
+        emit!(VerificationExpired {
+            seq: registry.next_event_seq()?,
+            registry: registry.key(),
+            record: address,
+            patient: self.patient_pubkey,
+            verification_type: self.verification_type,
+            expires_at: self.expires_at,
+        });
+        Ok(())
+    }
+}
+
+impl ReplaceableRecord for PrescriptionRecord {
+    fn is_current(&self, clock: &Clock) -> bool {
+        clock.unix_timestamp < self.valid_until && self.refills_remaining > 0
+    }
+
+    /// Ends the prescription now, so its refills can't be drawn alongside the
+    /// new one's
+    fn retire(
+        &mut self,
+        _address: Pubkey,
+        clock: &Clock,
+        _patient_index: &mut PatientIndex,
+        _registry: &mut Account<HealthcareRegistry>,
+    ) -> Result<()> {
+        self.valid_until = clock.unix_timestamp;
+        Ok(())
+    }
+}
+
+/// Only its provider or the registry can revoke a diagnosis, so a newer one
+/// supersedes it without touching it
+impl ReplaceableRecord for DiagnosisRecord {
+    fn is_current(&self, _clock: &Clock) -> bool {
+        self.revoked_at == 0
+    }
+}
+
+/// Lab results and immunizations don't lapse; a newer one supersedes them
+impl ReplaceableRecord for LabResultRecord {
+    fn is_current(&self, _clock: &Clock) -> bool {
+        true
+    }
+}
+
+impl ReplaceableRecord for ImmunizationRecord {
+    fn is_current(&self, _clock: &Clock) -> bool {
+        true
+    }
+}
+
+impl ReplaceableRecord for AccessPass {
+    fn is_current(&self, clock: &Clock) -> bool {
+        clock.slot <= self.expires_at_slot && self.uses_remaining > 0
+    }
+
+    /// Spends the pass's remaining uses
+    fn retire(
+        &mut self,
+        _address: Pubkey,
+        _clock: &Clock,
+        _patient_index: &mut PatientIndex,
+        _registry: &mut Account<HealthcareRegistry>,
+    ) -> Result<()> {
+        self.uses_remaining = 0;
+        Ok(())
+    }
+}
+
+/// Refuse a second current record of `verification_type`: the patient's latest
+/// one, their `PatientIndex::last_record_by_type`, must be passed as
+/// `previous_record` and, if it is still current, is retired when `force_new`
+/// is set. Records written before the last migration, closed or claimed away
+/// don't count.
+fn retire_previous_record<T: ReplaceableRecord>(
+    previous_record: &Option<UncheckedAccount>,
+    verification_type: VerificationType,
+    force_new: bool,
+    clock: &Clock,
+    patient_index: &mut PatientIndex,
+    registry: &mut Account<HealthcareRegistry>,
+) -> Result<()> {
+    let address = patient_index.last_record_by_type[verification_type as usize];
+    if address == Pubkey::default() {
+        return Ok(());
+    }
+    let info = previous_record.as_ref().ok_or(HealthcareError::PreviousRecordMismatch)?;
+    require_keys_eq!(info.key(), address, HealthcareError::PreviousRecordMismatch);
+    if info.owner != &crate::ID || migrations::LegacyVerificationRecord::is_legacy(&info.try_borrow_data()?) {
+        return Ok(());
+    }
+    let mut record = T::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    if !record.is_current(clock) {
+        return Ok(());
+    }
+    if !force_new {
+        return Err(error!(HealthcareError::ActiveVerificationExists).with_account_name(address));
+    }
+    record.retire(address, clock, patient_index, registry)?;
+    record.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+/// `cid` decoded, failing with `IpfsCidTooLong` if it won't fit the
+/// `MAX_IPFS_CID_LEN` bytes accounts set aside for it, and with `InvalidCid`
+/// if it isn't a CIDv0 or base32 CIDv1
+fn check_ipfs_cid(cid: &str) -> Result<cid::Cid> {
+    require!(cid.len() <= MAX_IPFS_CID_LEN, HealthcareError::IpfsCidTooLong);
+    cid::Cid::parse(cid).map_err(invalid_cid)
+}
+
+/// `InvalidCid`, logging the `rule` the CID broke
+fn invalid_cid(rule: &str) -> Error {
+    msg!("Invalid CID: {}", rule);
+    error!(HealthcareError::InvalidCid)
+}
+
+/// `counter` plus `count`, failing with `CounterOverflow` past `u64::MAX`
+fn checked_count(counter: u64, count: u64) -> Result<u64> {
+    counter.checked_add(count).ok_or_else(|| error!(HealthcareError::CounterOverflow))
+}
+
+/// Fail with `VerificationCooldownActive`, logging the seconds left, while
+/// `now` is under `cooldown_secs` past `last_verified_at`
+fn check_cooldown(last_verified_at: i64, cooldown_secs: i64, now: i64) -> Result<()> {
+    let remaining_secs = last_verified_at.saturating_add(cooldown_secs).saturating_sub(now);
+    if remaining_secs > 0 {
+        msg!("Verification cooldown active, remaining_secs: {}", remaining_secs);
+        return err!(HealthcareError::VerificationCooldownActive);
+    }
+    Ok(())
+}
+
+/// The cooldown of an anonymous credential, which names no patient: counted
+/// from the record it backed in the epoch before `epoch`, if it backed one
+fn check_credential_cooldown(
+    previous: &Option<UncheckedAccount>,
+    verifying_key: &Pubkey,
+    credential: &[u8; 32],
+    epoch: u64,
+    cooldown_secs: i64,
+    now: i64,
+) -> Result<()> {
+    let Some(previous_epoch) = epoch.checked_sub(1).filter(|_| cooldown_secs != 0) else {
+        return Ok(());
+    };
+    let info = previous.as_ref().ok_or(HealthcareError::PreviousCredentialMismatch)?;
+    require_keys_eq!(
+        info.key(),
+        CredentialNullifier::address(verifying_key, credential, previous_epoch),
+        HealthcareError::PreviousCredentialMismatch
+    );
+    if info.owner != &crate::ID {
+        return Ok(());
+    }
+    let spent = CredentialNullifier::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    check_cooldown(spent.used_at, cooldown_secs, now)
+}
+
+/// Spend the patient's next verification nonce on circuits that use one: the
+/// proof's nonce input must equal it, and it is incremented in the same
+/// instruction that records the proof
+fn spend_nonce(verifying_key: &VerifyingKeyPDA, inputs: &[[u8; 32]], index: &mut PatientIndex) -> Result<()> {
+    if verifying_key.uses_nonce == 0 {
+        return Ok(());
+    }
+    require!(
+        verifying_key.bound_nonce(inputs) == Some(nonce_scalar(index.verification_nonce)),
+        HealthcareError::StaleNonce
+    );
+    index.verification_nonce += 1;
+    Ok(())
+}
+
+/// Take a record leaving `Verified` out of its patient's `active_count`.
+/// `patient_index` is at the record's `PatientIndex` address, which holds no
+/// index for an anonymous record.
+fn release_active_record(patient_index: &AccountInfo) -> Result<()> {
+    update_active_count(patient_index, |count| count.saturating_sub(1))
+}
+
+/// Count a record back in its patient's `active_count` as it returns to
+/// `Verified`, like `release_active_record` in reverse
+fn restore_active_record(patient_index: &AccountInfo) -> Result<()> {
+    update_active_count(patient_index, |count| count + 1)
+}
+
+fn update_active_count(patient_index: &AccountInfo, update: impl FnOnce(u32) -> u32) -> Result<()> {
+    update_patient_index(patient_index, |index| {
+        index.active_count = update(index.active_count);
+        Ok(())
+    })
+}
+
+/// Apply `update` to the `PatientIndex` at `patient_index`, if it holds one
+fn update_patient_index(
+    patient_index: &AccountInfo,
+    update: impl FnOnce(&mut PatientIndex) -> Result<()>,
+) -> Result<()> {
+    if patient_index.owner != &crate::ID {
+        return Ok(());
+    }
+    let mut data = patient_index.try_borrow_mut_data()?;
+    let mut index = PatientIndex::try_deserialize(&mut &data[..])?;
+    update(&mut index)?;
+    index.try_serialize(&mut &mut data[..])
+}
+
+/// `PatientIndex::unlink_record` with the successor given as an account
+fn unlink_with_successor(
+    index: &mut PatientIndex,
+    key: Pubkey,
+    record: &VerificationRecord,
+    successor: Option<&AccountInfo>,
+) -> Result<()> {
+    let Some(successor) = successor else {
+        return index.unlink_record(key, record, None);
+    };
+    require_keys_eq!(*successor.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    let mut data = successor.try_borrow_mut_data()?;
+    let mut next = VerificationRecord::try_deserialize(&mut &data[..])?;
+    index.unlink_record(key, record, Some(&mut next))?;
+    // Never longer than before: the link only moves to an older record or goes
+    next.try_serialize(&mut &mut data[..])
+}
+
+/// Mint one token of `registry`'s verification mint into the patient's
+/// associated token account, creating the account if needed, and return the
+/// mint. The mint signs as its own authority.
+fn mint_verification_token<'info>(
+    registry: &Pubkey,
+    patient: &Signer<'info>,
+    mint: &Option<UncheckedAccount<'info>>,
+    token_account: &Option<UncheckedAccount<'info>>,
+    token_program: &Option<Program<'info, Token2022>>,
+    associated_token_program: &Option<Program<'info, AssociatedToken>>,
+    system_program: &Program<'info, System>,
+) -> Result<Pubkey> {
+    use anchor_spl::associated_token::{create_idempotent, Create};
+    use anchor_spl::token_2022::{mint_to, MintTo};
+    let (Some(mint), Some(token_account), Some(token_program), Some(associated_token_program)) =
+        (mint, token_account, token_program, associated_token_program)
+    else {
+        return err!(HealthcareError::MissingTokenAccounts);
+    };
+    let (address, bump) = derive_verification_mint(registry);
+    require!(mint.key() == address, HealthcareError::InvalidVerificationMint);
+
+    let accounts = Create {
+        payer: patient.to_account_info(),
+        associated_token: token_account.to_account_info(),
+        authority: patient.to_account_info(),
+        mint: mint.to_account_info(),
+        system_program: system_program.to_account_info(),
+        token_program: token_program.to_account_info(),
+    };
+    create_idempotent(CpiContext::new(associated_token_program.to_account_info(), accounts))?;
+    let accounts = MintTo {
+        mint: mint.to_account_info(),
+        to: token_account.to_account_info(),
+        authority: mint.to_account_info(),
+    };
+    let seeds: &[&[u8]] = &[b"verification_mint", registry.as_ref(), &[bump]];
+    mint_to(CpiContext::new_with_signer(token_program.to_account_info(), accounts, &[seeds]), 1)?;
+    Ok(address)
+}
+
+/// Pay the circuit's fee for `proofs` recorded proofs from the patient to its
+/// `fee_recipient`. Free circuits need no recipient account.
+fn charge_circuit_fee<'info>(
+    key: &VerifyingKeyPDA,
+    proofs: u64,
+    patient: &Signer<'info>,
+    fee_recipient: &Option<UncheckedAccount<'info>>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    use anchor_lang::system_program::{transfer, Transfer};
+    if key.fee_lamports == 0 {
+        return Ok(());
+    }
+    let Some(fee_recipient) = fee_recipient else {
+        return err!(HealthcareError::InvalidFeeRecipient);
+    };
+    let fee = key
+        .fee_lamports
+        .checked_mul(proofs)
+        .ok_or(HealthcareError::InsufficientFeeFunds)?;
+    require!(patient.lamports() >= fee, HealthcareError::InsufficientFeeFunds);
+    let accounts = Transfer {
+        from: patient.to_account_info(),
+        to: fee_recipient.to_account_info(),
+    };
+    transfer(CpiContext::new(system_program.to_account_info(), accounts), fee)?;
+    msg!("Paid circuit fee of {} lamports", fee);
+    Ok(())
+}
+
+/// See `verifier_core::prepare_vk_bytes`
+fn prepare_vk_bytes(vk_bytes: &[u8]) -> Result<Vec<u8>> {
+    Ok(verifier_core::prepare_vk_bytes(vk_bytes)?)
+}
+
+/// See `verifier_core::check_verifying_key`
+fn check_verifying_key(vk_bytes: &[u8]) -> Result<()> {
+    Ok(verifier_core::check_verifying_key(vk_bytes)?)
+}
+
+/// The public input a patient-bound circuit must expose at index 0:
+/// `Fr::from_le_bytes_mod_order(keccak(patient))`, big-endian
+pub fn patient_binding(patient: &Pubkey) -> [u8; 32] {
+    hash_to_scalar(&[patient.as_ref()])
+}
+
+/// The public input a nonce-using circuit exposes at its `nonce_input` for
+/// verification nonce `nonce`, big-endian
+pub fn nonce_scalar(nonce: u64) -> [u8; 32] {
+    let mut scalar = [0u8; 32];
+    scalar[24..].copy_from_slice(&nonce.to_be_bytes());
+    scalar
+}
+
+/// Domain separator of a registry: `Fr::from_le_bytes_mod_order(keccak(program_id
+/// || registry))`, big-endian. Reduced so it can be used directly as a public input.
+pub fn registry_domain(registry: &Pubkey) -> [u8; 32] {
+    hash_to_scalar(&[crate::ID.as_ref(), registry.as_ref()])
+}
+
+/// The keccak proof hash of a verification: `keccak(VERIFICATION_HASH_DOMAIN ||
+/// program_id || proof || public_inputs)`. `proof` is the scheme's canonical
+/// encoding (arkworks compressed for Groth16, the snarkjs bytes for PLONK) and
+/// `public_inputs` are big-endian scalars, so off-chain tooling gets the value the
+/// program records whichever format the proof was submitted in.
+pub fn compute_verification_hash(proof: &[u8], public_inputs: &[[u8; 32]]) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> = vec![VERIFICATION_HASH_DOMAIN, crate::ID.as_ref(), proof];
+    parts.extend(public_inputs.iter().map(|input| &input[..]));
+    keccak::hashv(&parts).to_bytes()
+}
+
+/// A verification's stable identifier: `keccak(program_id || registry || patient
+/// || circuit_id || proof_hash)`. `patient` is the record's `patient_pubkey` (the
+/// credential nullifier of an anonymous submission) and `proof_hash` the one the
+/// record stores, so a submitter can compute the ID before the transaction lands.
+pub fn derive_verification_id(
+    registry: &Pubkey,
+    patient: &Pubkey,
+    circuit_id: &str,
+    proof_hash: &[u8; 32],
+) -> [u8; 32] {
+    keccak::hashv(&[
+        crate::ID.as_ref(),
+        registry.as_ref(),
+        patient.as_ref(),
+        circuit_id.as_bytes(),
+        proof_hash,
+    ])
+    .to_bytes()
+}
+
+/// Address and bump of the record `verify_eligibility` writes for `patient`'s
+/// `record_nonce`, with `verification_type` of the record
+pub fn derive_verification_pda(
+    patient: &Pubkey,
+    verification_type: VerificationType,
+    record_nonce: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"verification",
+            patient.as_ref(),
+            &[verification_type as u8],
+            &record_nonce.to_le_bytes(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// The `event_authority` account of the `#[event_cpi]` instructions, which
+/// signs their `emit_cpi!` self-CPIs
+pub fn event_authority_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &crate::ID).0
+}
+
+/// What a provider signs for `ProviderAttestation`: the attestation of
+/// `patient`'s check against `circuit_id` under `registry`, until the unix time
+/// `expires_at`
+pub fn attestation_message(registry: &Pubkey, patient: &Pubkey, circuit_id: &str, expires_at: i64) -> Vec<u8> {
+    [
+        ATTESTATION_DOMAIN,
+        registry.as_ref(),
+        patient.as_ref(),
+        &expires_at.to_le_bytes(),
+        circuit_id.as_bytes(),
+    ]
+    .concat()
+}
+
+/// `message` as an Ethereum wallet's `personal_sign` signs it, which is what a
+/// `Secp256k1` attestation carries: keccak of this is the signed digest
+pub fn eth_signed_message(message: &[u8]) -> Vec<u8> {
+    [eth_signed_message_prefix(message.len()).as_slice(), message].concat()
+}
+
+/// Address and bump of `registry`'s verification mint, see
+/// `initialize_verification_mint`
+pub fn derive_verification_mint(registry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"verification_mint", registry.as_ref()], &crate::ID)
+}
+
+/// Keccak of the public inputs as big-endian scalars, stored on each record so
+/// auditors can check which statement was proven
+pub fn compute_public_inputs_hash(public_inputs: &[[u8; 32]]) -> [u8; 32] {
+    let parts: Vec<&[u8]> = public_inputs.iter().map(|input| &input[..]).collect();
+    keccak::hashv(&parts).to_bytes()
+}
+
+/// `Fr::from_le_bytes_mod_order(keccak(parts))` as a big-endian scalar
+fn hash_to_scalar(parts: &[&[u8]]) -> [u8; 32] {
+    let mut scalar = keccak::hashv(parts).to_bytes();
+    scalar.reverse();
+    // 2^256 < 6r, so a handful of subtractions fully reduces the hash
+    while scalar >= FR_MODULUS_BE {
+        let mut borrow = 0u8;
+        for i in (0..32).rev() {
+            let (diff, underflow_a) = scalar[i].overflowing_sub(FR_MODULUS_BE[i]);
+            let (diff, underflow_b) = diff.overflowing_sub(borrow);
+            scalar[i] = diff;
+            borrow = (underflow_a || underflow_b) as u8;
+        }
+    }
+    scalar
+}
+
+/// See `verifier_core::parse_public_inputs`
+pub fn parse_public_inputs(bytes: &[u8], format: ProofFormat) -> Result<Vec<[u8; 32]>> {
+    Ok(verifier_core::parse_public_inputs(bytes, format)?)
+}
+
+// Benchmark test (for scalability)
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::verifier_core::g1_to_syscall;
+    use ark_bn254::{Bn254, Fr};
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_groth16::{Groth16, VerifyingKey};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_snark::SNARK;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    /// Proves knowledge of `x` such that `x * x == y` for public `y`
+    #[derive(Clone)]
+    struct SquareCircuit {
+        x: Option<Fr>,
+        y: Option<Fr>,
+    }
+
+    impl ConstraintSynthesizer<Fr> for SquareCircuit {
+        fn generate_constraints(
+            self,
+            cs: ConstraintSystemRef<Fr>,
+        ) -> std::result::Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = cs.new_input_variable(|| self.y.ok_or(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)?;
+            Ok(())
+        }
+    }
+
+    /// Returns (vk_bytes, proof_bytes, public_input_bytes) for a fresh proof
+    fn square_fixture() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let mut rng = StdRng::seed_from_u64(42);
+        let x = Fr::from(7u64);
+        let y = x * x;
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(
+            SquareCircuit { x: None, y: None },
+            &mut rng,
+        )
+        .unwrap();
+        let proof = Groth16::<Bn254>::prove(&pk, SquareCircuit { x: Some(x), y: Some(y) }, &mut rng)
+            .unwrap();
+
+        let mut vk_bytes = Vec::new();
+        vk.serialize_uncompressed(&mut vk_bytes).unwrap();
+        let mut proof_bytes = Vec::new();
+        proof.serialize_uncompressed(&mut proof_bytes).unwrap();
+        let mut input_bytes = Vec::new();
+        y.serialize_uncompressed(&mut input_bytes).unwrap();
+        (vk_bytes, proof_bytes, input_bytes)
+    }
+
+    /// A decoded proof with its big-endian public inputs
+    type BatchItem = (Groth16Proof, Vec<[u8; 32]>);
+
+    /// Returns the vk and proofs of `y = x^2` for `x = 3..3 + n`, all under one
+    /// proving key
+    fn square_batch(n: u64) -> (Vec<u8>, Vec<BatchItem>) {
+        let mut rng = StdRng::seed_from_u64(42);
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(
+            SquareCircuit { x: None, y: None },
+            &mut rng,
+        )
+        .unwrap();
+        let entries = (3..3 + n)
+            .map(|x| {
+                let (x, y) = (Fr::from(x), Fr::from(x * x));
+                let proof = Groth16::<Bn254>::prove(&pk, SquareCircuit { x: Some(x), y: Some(y) }, &mut rng)
+                    .unwrap();
+                let mut proof_bytes = Vec::new();
+                proof.serialize_uncompressed(&mut proof_bytes).unwrap();
+                let mut input = [0u8; 32];
+                input.copy_from_slice(&y.into_bigint().to_bytes_be());
+                (decode(&proof_bytes), vec![input])
+            })
+            .collect();
+        let mut vk_bytes = Vec::new();
+        vk.serialize_uncompressed(&mut vk_bytes).unwrap();
+        (vk_bytes, entries)
+    }
+
+    fn verify_batch(vk: &[u8], entries: &[BatchItem]) -> Result<bool> {
+        let batch: Vec<_> = entries.iter().map(|(proof, inputs)| (proof, &inputs[..])).collect();
+        verify_groth16_batch(vk, &[], &batch)
+    }
+
+    fn decode(proof: &[u8]) -> Groth16Proof {
+        Groth16Proof::decode(proof, ProofFormat::Uncompressed).unwrap()
+    }
+
+    fn verify(vk: &[u8], prepared: &[u8], proof: &[u8], inputs: &[u8]) -> Result<bool> {
+        verify_groth16_proof(vk, prepared, &decode(proof), inputs, ProofFormat::Uncompressed)
+    }
+
+    #[test]
+    fn test_valid_proof_verifies() {
+        let (vk, proof, inputs) = square_fixture();
+        assert_eq!(proof.len(), 256);
+        assert!(verify(&vk, &[], &proof, &inputs).unwrap());
+    }
+
+    #[test]
+    fn test_proof_for_other_statement_fails() {
+        let (vk, proof, _) = square_fixture();
+        let mut inputs = Vec::new();
+        Fr::from(50u64).serialize_uncompressed(&mut inputs).unwrap();
+        assert!(!verify(&vk, &[], &proof, &inputs).unwrap());
+    }
+
+    #[test]
+    fn test_mutated_proof_fails() {
+        let (vk, mut proof, inputs) = square_fixture();
+        proof[0] ^= 0x01;
+        assert!(!matches!(verify(&vk, &[], &proof, &inputs), Ok(true)));
+    }
+
+    #[test]
+    fn test_compressed_proof_decodes_and_hashes_identically() {
+        let (vk, proof, inputs) = square_fixture();
+        let ark_proof = ark_groth16::Proof::<Bn254>::deserialize_uncompressed(proof.as_slice()).unwrap();
+        let mut compressed = Vec::new();
+        ark_proof.serialize_compressed(&mut compressed).unwrap();
+        assert_eq!(compressed.len(), 128);
+
+        let from_compressed = Groth16Proof::decode(&compressed, ProofFormat::Compressed).unwrap();
+        assert_eq!(from_compressed, decode(&proof));
+        assert!(verify_groth16_proof(
+            &vk,
+            &[],
+            &from_compressed,
+            &inputs,
+            ProofFormat::Uncompressed,
+        ).unwrap());
+        let inputs = parse_public_inputs(&inputs, ProofFormat::Uncompressed).unwrap();
+        let expected = keccak::hashv(&[VERIFICATION_HASH_DOMAIN, crate::ID.as_ref(), &compressed, &inputs[0]]);
+        assert_eq!(from_compressed.hash(&inputs).unwrap(), expected.to_bytes());
+    }
+
+    #[test]
+    fn test_proof_length_must_match_format() {
+        let (_, proof, _) = square_fixture();
+        let err = Groth16Proof::decode(&proof, ProofFormat::Compressed).unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidProofLength.into());
+        let err = Groth16Proof::decode(&proof[..128], ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidProofLength.into());
+    }
+
+    #[test]
+    fn test_snarkjs_layout_verifies() {
+        let (vk, proof, inputs) = square_fixture();
+        let ark_proof = ark_groth16::Proof::<Bn254>::deserialize_uncompressed(proof.as_slice()).unwrap();
+        let snarkjs_proof = [
+            offchain::g1_syscall_bytes(&ark_proof.a).as_slice(),
+            offchain::g2_syscall_bytes(&ark_proof.b).as_slice(),
+            offchain::g1_syscall_bytes(&ark_proof.c).as_slice(),
+        ]
+        .concat();
+        let decoded = Groth16Proof::decode(&snarkjs_proof, ProofFormat::SnarkJs).unwrap();
+        assert_eq!(decoded, decode(&proof));
+
+        let mut signals = inputs.clone();
+        signals.reverse();
+        assert!(verify_groth16_proof(&vk, &[], &decoded, &signals, ProofFormat::SnarkJs).unwrap());
+        assert!(!matches!(
+            verify_groth16_proof(&vk, &[], &decoded, &inputs, ProofFormat::SnarkJs),
+            Ok(true)
+        ));
+    }
+
+    #[test]
+    fn test_snarkjs_non_canonical_coordinate_rejected() {
+        let mut snarkjs_proof = [0u8; 256];
+        snarkjs_proof[..32].copy_from_slice(&FQ_MODULUS_BE);
+        let err = Groth16Proof::decode(&snarkjs_proof, ProofFormat::SnarkJs).unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidProofEncoding.into());
+    }
+
+    #[test]
+    fn test_flipped_y_sign_flag_rejected() {
+        let (_, mut proof, _) = square_fixture();
+        proof[63] ^= SW_NEGATIVE_FLAG;
+        let err = Groth16Proof::decode(&proof, ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::NonCanonicalProofEncoding.into());
+    }
+
+    #[test]
+    fn test_encode_matches_arkworks_for_every_sign() {
+        use ark_ec::AffineRepr;
+
+        // Cover both y-sign flags on each point, plus the identity
+        for seed in 0..8u64 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (pk, _) = Groth16::<Bn254>::circuit_specific_setup(
+                SquareCircuit { x: None, y: None },
+                &mut rng,
+            )
+            .unwrap();
+            let x = Fr::from(seed + 2);
+            let mut proof = Groth16::<Bn254>::prove(
+                &pk,
+                SquareCircuit { x: Some(x), y: Some(x * x) },
+                &mut rng,
+            )
+            .unwrap();
+            if seed == 0 {
+                proof.c = ark_bn254::G1Affine::zero();
+            }
+            for (format, compressed) in [
+                (ProofFormat::Uncompressed, false),
+                (ProofFormat::Compressed, true),
+            ] {
+                let mut bytes = Vec::new();
+                if compressed {
+                    proof.serialize_compressed(&mut bytes).unwrap();
+                } else {
+                    proof.serialize_uncompressed(&mut bytes).unwrap();
+                }
+                let decoded = Groth16Proof::decode(&bytes, format).unwrap();
+                assert_eq!(decoded.encode(format).unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn test_identity_a_rejected() {
+        let (vk, proof, inputs) = square_fixture();
+        let mut proof = decode(&proof);
+        proof.a = [0u8; 64];
+        let err = verify_groth16_proof(&vk, &[], &proof, &inputs, ProofFormat::Uncompressed)
+            .unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidProofPoint.into());
+    }
+
+    #[test]
+    fn test_off_curve_c_rejected() {
+        let (vk, proof, inputs) = square_fixture();
+        let mut proof = decode(&proof);
+        proof.c[63] ^= 0x01;
+        let err = verify_groth16_proof(&vk, &[], &proof, &inputs, ProofFormat::Uncompressed)
+            .unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidProofPoint.into());
+    }
+
+    #[test]
+    fn test_non_subgroup_b_rejected() {
+        use ark_bn254::{g2, Fq2, G2Affine};
+        use ark_ec::short_weierstrass::SWCurveConfig;
+        use ark_ff::{Field, One};
+
+        // Points on the twist outside the r-torsion are easy to find since the
+        // G2 cofactor is huge: walk x until x^3 + b is a square
+        let mut x = Fq2::one();
+        let outside = loop {
+            if let Some(y) = (x * x * x + g2::Config::COEFF_B).sqrt() {
+                let point = G2Affine::new_unchecked(x, y);
+                assert!(point.is_on_curve());
+                if !point.is_in_correct_subgroup_assuming_on_curve() {
+                    break point;
+                }
+            }
+            x += Fq2::one();
+        };
+
+        let (vk, proof, inputs) = square_fixture();
+        let mut proof = decode(&proof);
+        proof.b = offchain::g2_syscall_bytes(&outside);
+        let err = verify_groth16_proof(&vk, &[], &proof, &inputs, ProofFormat::Uncompressed)
+            .unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidProofPoint.into());
+    }
+
+    #[test]
+    fn test_batch_of_valid_proofs_verifies() {
+        let (vk, entries) = square_batch(4);
+        assert!(verify_batch(&vk, &entries[..1]).unwrap());
+        assert!(verify_batch(&vk, &entries).unwrap());
+        let prepared = prepare_vk_bytes(&vk).unwrap();
+        let batch: Vec<_> = entries.iter().map(|(proof, inputs)| (proof, &inputs[..])).collect();
+        assert!(verify_groth16_batch(&vk, &prepared, &batch).unwrap());
+    }
+
+    #[test]
+    fn test_batch_with_one_bad_proof_fails() {
+        let (vk, mut entries) = square_batch(4);
+        // Entry 2 proves 5^2, not 6^2
+        entries[2].1 = entries[3].1.clone();
+        assert!(!verify_batch(&vk, &entries).unwrap());
+
+        // Two wrong proofs can't be made to cancel: swapping C between entries
+        // keeps the unweighted sum of the equations intact
+        let (vk, mut entries) = square_batch(2);
+        let c = entries[0].0.c;
+        entries[0].0.c = entries[1].0.c;
+        entries[1].0.c = c;
+        assert!(!verify_batch(&vk, &entries).unwrap());
+    }
+
+    #[test]
+    fn test_batch_weights_bind_every_proof() {
+        let (vk, entries) = square_batch(3);
+        let prepared = prepare_vk_bytes(&vk).unwrap();
+        let batch: Vec<_> = entries.iter().map(|(proof, inputs)| (proof, &inputs[..])).collect();
+        let weights = batch_weights(&prepared, &batch);
+        assert_eq!(weights.len(), 3);
+        assert!(weights.iter().all(|weight| weight[..16] == [0; 16] && weight[31] & 1 == 1));
+        assert_ne!(weights[0], weights[1]);
+
+        let reordered = [batch[1], batch[0], batch[2]];
+        assert_ne!(batch_weights(&prepared, &reordered)[2], weights[2]);
+    }
+
+    #[test]
+    fn test_garbage_vk_rejected() {
+        let (_, proof, inputs) = square_fixture();
+        let err = verify(&[0u8; 64], &[], &proof, &inputs).unwrap_err();
+        assert_eq!(err, HealthcareError::VerifyingKeyDeserializeFailed.into());
+    }
+
+    #[test]
+    fn test_truncated_public_inputs_rejected() {
+        let (vk, proof, inputs) = square_fixture();
+        let err = verify(&vk, &[], &proof, &inputs[..31]).unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidPublicInputEncoding.into());
+    }
+
+    #[test]
+    fn test_prepared_vk_matches_arkworks_encoding() {
+        let (vk, proof, inputs) = square_fixture();
+        let prepared = prepare_vk_bytes(&vk).unwrap();
+        assert_eq!(prepared.len(), VerifyingKeyPDA::prepared_len(vk.len() as u32));
+
+        let vk_struct = VerifyingKey::<Bn254>::deserialize_uncompressed(vk.as_slice()).unwrap();
+        assert_eq!(prepared, offchain::prepare_verifying_key(&vk_struct));
+        assert!(verify(&vk, &prepared, &proof, &inputs).unwrap());
+        check_verifying_key(&vk).unwrap();
+    }
+
+    #[test]
+    fn test_negate_g1_matches_arkworks() {
+        let (_, proof_bytes, _) = square_fixture();
+        let proof = ark_groth16::Proof::<Bn254>::deserialize_uncompressed(proof_bytes.as_slice()).unwrap();
+        let a = g1_to_syscall(&proof_bytes[..G1_LEN]).unwrap();
+        assert_eq!(a, offchain::g1_syscall_bytes(&proof.a));
+        assert_eq!(negate_g1(&a), offchain::g1_syscall_bytes(&(-proof.a)));
+        assert_eq!(negate_g1(&[0; 64]), [0; 64]);
+    }
+
+    fn fr_modulus_le() -> [u8; 32] {
+        let mut modulus = FR_MODULUS_BE;
+        modulus.reverse();
+        modulus
+    }
+
+    #[test]
+    fn test_public_input_equal_to_modulus_rejected() {
+        let err = parse_public_inputs(&fr_modulus_le(), ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputNotInField.into());
+        let err = parse_public_inputs(&[0xff; 32], ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputNotInField.into());
+    }
+
+    #[test]
+    fn test_public_input_modulus_minus_one_accepted() {
+        let mut input = fr_modulus_le();
+        input[0] -= 1;
+        let parsed = parse_public_inputs(&input, ProofFormat::Uncompressed).unwrap();
+        let mut expected = FR_MODULUS_BE;
+        expected[31] -= 1;
+        assert_eq!(parsed, vec![expected]);
+        assert_eq!(Fr::from_le_bytes_mod_order(&input), -Fr::from(1u64));
+    }
+
+    #[test]
+    fn test_public_input_out_of_range_in_later_slot() {
+        let mut inputs = vec![0u8; 32];
+        inputs.extend_from_slice(&fr_modulus_le());
+        let err = parse_public_inputs(&inputs, ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputNotInField.into());
+    }
+
+    #[test]
+    fn test_public_input_empty_or_ragged_rejected() {
+        let err = parse_public_inputs(&[], ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidPublicInputEncoding.into());
+        let err = parse_public_inputs(&[0; 33], ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidPublicInputEncoding.into());
+    }
+
+    /// Account data of a freshly registered key, sized for all of it
+    fn pending_vk(total_len: u32) -> Vec<u8> {
+        let mut data = vec![0; VerifyingKeyPDA::space(total_len)];
+        data[..8].copy_from_slice(&VerifyingKeyPDA::DISCRIMINATOR);
+        let header: &mut VerifyingKeyPDA = bytemuck::from_bytes_mut(&mut data[8..VerifyingKeyPDA::HEADER_LEN]);
+        header.set_circuit_id("eligibility_v1");
+        header.authority = Pubkey::new_unique();
+        header.total_len = total_len;
+        header.bump = 255;
+        header.n_public = 1;
+        header.version = 1;
+        header.min_accepted_version = 1;
+        data
+    }
+
+    #[test]
+    fn test_circuit_acceptance() {
+        let mut data = pending_vk(8);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        assert!(vk.is_accepting());
+        vk.min_accepted_version = 2;
+        assert!(!vk.is_accepting());
+        vk.version = 2;
+        assert!(vk.is_accepting());
+        vk.status = CircuitStatus::Deprecated as u8;
+        assert!(!vk.is_accepting());
+    }
+
+    #[test]
+    fn test_public_input_count_enforced() {
+        let (vk_bytes, proof, inputs) = square_fixture();
+        let mut data = pending_vk(vk_bytes.len() as u32);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.write_chunk(0, &vk_bytes).unwrap();
+        assert_eq!(vk.total_len, VerifyingKeyPDA::expected_vk_len(1));
+        assert!(vk.view().verify(&decode(&proof), &inputs, ProofFormat::Uncompressed).unwrap());
+
+        let too_many = [inputs.as_slice(), inputs.as_slice()].concat();
+        let err = vk.view().verify(&decode(&proof), &too_many, ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputCountMismatch.into());
+        let err = vk.view().verify(&decode(&proof), &[], ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputCountMismatch.into());
+    }
+
+    #[test]
+    fn test_patient_binding_matches_arkworks_reduction() {
+        for seed in 0..32u8 {
+            let patient = Pubkey::new_from_array([seed; 32]);
+            let expected = offchain::patient_binding(&patient);
+            let mut expected_be = expected.into_bigint().to_bytes_be();
+            expected_be.resize(32, 0);
+            assert_eq!(patient_binding(&patient).to_vec(), expected_be);
+        }
+    }
+
+    #[test]
+    fn test_patient_binding_checked_only_when_enabled() {
+        let patient = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut bound = patient_binding(&patient);
+        let mut data = pending_vk(584);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.check_patient_binding(&[], ProofFormat::Uncompressed, &other).unwrap();
+
+        vk.binds_patient = 1;
+        vk.check_patient_binding(&bound, ProofFormat::SnarkJs, &patient).unwrap();
+        let err = vk.check_patient_binding(&bound, ProofFormat::SnarkJs, &other).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputPatientMismatch.into());
+        let err = vk.check_patient_binding(&[], ProofFormat::SnarkJs, &patient).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputPatientMismatch.into());
+
+        bound.reverse();
+        vk.check_patient_binding(&bound, ProofFormat::Uncompressed, &patient).unwrap();
+    }
+
+    #[test]
+    fn test_security_policy() {
+        let policy = SecurityPolicy::default_for(false);
+        assert!(policy.is_valid() && !policy.is_nist_compliant());
+        assert!(policy.allows_circuit(ProvingScheme::Plonk, CurveId::Bls12_381, None));
+        assert!(SecurityPolicy::default_for(true).is_nist_compliant());
+
+        let strict = SecurityPolicy {
+            allowed_schemes: ProvingScheme::Groth16.bit(),
+            allowed_curves: CurveId::Bn254.bit(),
+            allowed_hash_algos: HashAlgo::Keccak.bit(),
+            max_freshness_window_secs: 600,
+        };
+        assert!(strict.is_nist_compliant());
+        assert!(strict.allows_circuit(ProvingScheme::Groth16, CurveId::Bn254, Some(600)));
+        assert!(!strict.allows_circuit(ProvingScheme::Groth16, CurveId::Bn254, Some(601)));
+        assert!(!strict.allows_circuit(ProvingScheme::Groth16, CurveId::Bn254, None));
+        assert!(!strict.allows_circuit(ProvingScheme::Plonk, CurveId::Bn254, Some(60)));
+        assert!(!strict.allows_circuit(ProvingScheme::Groth16, CurveId::Bls12_381, Some(60)));
+        assert!(!strict.allows_hash_algo(HashAlgo::Poseidon));
+        assert!(!SecurityPolicy { allowed_curves: 0, ..strict }.is_valid());
+        assert!(!SecurityPolicy { max_freshness_window_secs: -1, ..strict }.is_valid());
+    }
+
+    fn registry() -> HealthcareRegistry {
+        HealthcareRegistry {
+            authority: Pubkey::new_unique(),
+            nist_compliant: true,
+            total_verifications: 0,
+            ipfs_pin_count: 0,
+            vk_update_delay_secs: 0,
+            domain: registry_domain(&Pubkey::new_unique()),
+            registered_circuits: 0,
+            circuit_for_type: [None; VERIFICATION_TYPE_COUNT],
+            max_circuit_fee: 0,
+            max_public_inputs: DEFAULT_MAX_PUBLIC_INPUTS,
+            cache_ttl_slots: DEFAULT_CACHE_TTL_SLOTS,
+            commit_reveal_slots: 0,
+            security_policy: SecurityPolicy::default_for(true),
+            default_validity_secs: 0,
+            gc_bounty_lamports: 0,
+            closed_verifications: 0,
+            max_metadata_len: DEFAULT_MAX_METADATA_LEN,
+            dispute_window_secs: DEFAULT_DISPUTE_WINDOW_SECS,
+            time_source: TimeSource::UnixTimestamp,
+            cooldown_secs: 0,
+            verifications_by_type: [0; VERIFICATION_TYPE_COUNT],
+            revoked_count: 0,
+            expired_count: 0,
+            failed_proof_count: 0,
+            claim_count: 0,
+            event_seq: 0,
+            checkpointed_seq: 0,
+            cpi_events_enabled: false,
+            denied_access_events_enabled: false,
+            pinning_oracles: Vec::new(),
+            failed_pin_timeout_secs: DEFAULT_FAILED_PIN_TIMEOUT_SECS,
+            price_lamports_per_day: 0,
+            storage_treasury: Pubkey::default(),
+            pin_bounty_period_secs: DEFAULT_PIN_BOUNTY_PERIOD_SECS,
+            pin_bounty_periods: DEFAULT_PIN_BOUNTY_PERIODS,
+            pin_replication_target: DEFAULT_PIN_REPLICATION_TARGET,
+            deal_expiry_warning_secs: DEFAULT_DEAL_EXPIRY_WARNING_SECS,
+            max_access_pass_slots: DEFAULT_MAX_ACCESS_PASS_SLOTS,
+        }
+    }
+
+    #[test]
+    fn test_domain_binding_uses_registered_slot() {
+        let registry = registry();
+        let other_domain = registry_domain(&Pubkey::new_unique());
+        assert_ne!(registry.domain, other_domain);
+
+        let mut data = pending_vk(648);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.domain = registry.domain;
+        vk.binds_domain = 1;
+        vk.domain_input = 1;
+        let inputs = [[7u8; 32], registry.domain].concat();
+        vk.check_domain_binding(&inputs, ProofFormat::SnarkJs, &registry).unwrap();
+
+        let wrong = [[7u8; 32], other_domain].concat();
+        let err = vk.check_domain_binding(&wrong, ProofFormat::SnarkJs, &registry).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputDomainMismatch.into());
+        let err = vk.check_domain_binding(&inputs[..32], ProofFormat::SnarkJs, &registry).unwrap_err();
+        assert_eq!(err, HealthcareError::PublicInputDomainMismatch.into());
+    }
+
+    #[test]
+    fn test_freshness_window() {
+        let now = 1_700_000_000i64;
+        let clock = Clock { unix_timestamp: now, ..Clock::default() };
+        let unix = TimeSource::UnixTimestamp;
+        let mut data = pending_vk(520);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        let stamp = |ts: i64| {
+            let mut input = [0u8; 32];
+            input[24..].copy_from_slice(&ts.to_be_bytes());
+            input
+        };
+        vk.check_freshness(&stamp(0), ProofFormat::SnarkJs, unix, &clock).unwrap();
+
+        vk.freshness_window_secs = 3600;
+        vk.check_freshness(&stamp(now), ProofFormat::SnarkJs, unix, &clock).unwrap();
+        vk.check_freshness(&stamp(now - 3600), ProofFormat::SnarkJs, unix, &clock).unwrap();
+        vk.check_freshness(&stamp(now + MAX_CLOCK_SKEW_SECS), ProofFormat::SnarkJs, unix, &clock).unwrap();
+        let err = vk.check_freshness(&stamp(now - 3601), ProofFormat::SnarkJs, unix, &clock).unwrap_err();
+        assert_eq!(err, HealthcareError::ProofStale.into());
+        let err = vk
+            .check_freshness(&stamp(now + MAX_CLOCK_SKEW_SECS + 1), ProofFormat::SnarkJs, unix, &clock)
+            .unwrap_err();
+        assert_eq!(err, HealthcareError::ProofTimestampInFuture.into());
+
+        let mut huge = stamp(now);
+        huge[0] = 1;
+        let err = vk.check_freshness(&huge, ProofFormat::SnarkJs, unix, &clock).unwrap_err();
+        assert_eq!(err, HealthcareError::ProofTimestampInFuture.into());
+
+        // Little-endian formats carry the timestamp in the low bytes
+        let mut le = stamp(now);
+        le.reverse();
+        vk.check_freshness(&le, ProofFormat::Uncompressed, unix, &clock).unwrap();
+
+        // Under slots the input is a slot, and the window 9_000 of them
+        let slot = 50_000_000u64;
+        let clock = Clock { slot, unix_timestamp: now, ..Clock::default() };
+        let slots = TimeSource::Slot;
+        let at = |slot: u64| stamp(slot as i64);
+        vk.check_freshness(&at(slot - 9_000), ProofFormat::SnarkJs, slots, &clock).unwrap();
+        vk.check_freshness(&at(slot + 300), ProofFormat::SnarkJs, slots, &clock).unwrap();
+        let err = vk.check_freshness(&at(slot - 9_001), ProofFormat::SnarkJs, slots, &clock).unwrap_err();
+        assert_eq!(err, HealthcareError::ProofStale.into());
+        let err = vk.check_freshness(&at(slot + 301), ProofFormat::SnarkJs, slots, &clock).unwrap_err();
+        assert_eq!(err, HealthcareError::ProofTimestampInFuture.into());
+        let err = vk.check_freshness(&stamp(now), ProofFormat::SnarkJs, slots, &clock).unwrap_err();
+        assert_eq!(err, HealthcareError::ProofTimestampInFuture.into());
+    }
+
+    fn record(status: RecordStatus) -> VerificationRecord {
+        VerificationRecord {
+            version: VerificationRecord::VERSION,
+            patient_pubkey: Pubkey::new_unique(),
+            proof_hash: [1; 32],
+            ipfs_hash: String::new(),
+            timestamp: 0,
+            is_valid: status == RecordStatus::Verified,
+            verification_type: VerificationType::Eligibility,
+            slot: 0,
+            circuit_id: "eligibility_v1".to_string(),
+            circuit_version: 1,
+            vk_hash: [0; 32],
+            hash_algo: HashAlgo::Keccak,
+            public_inputs_hash: [0; 32],
+            verification_id: [0; 32],
+            revoked_at: 0,
+            revoked_by: Pubkey::default(),
+            expires_at: 0,
+            status,
+            rent_payer: Pubkey::default(),
+            bump: 0,
+            revision: 0,
+            previous_proof_hash: [0; 32],
+            token_mint: Pubkey::default(),
+            attesting_provider: None,
+            previous_record: None,
+            event_seq: 0,
+            hold: None,
+            migrated_from: Pubkey::default(),
+            metadata: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_status_transitions() {
+        let legal = [
+            (RecordStatus::Pending, RecordStatus::Verified),
+            (RecordStatus::Pending, RecordStatus::Revoked),
+            (RecordStatus::Verified, RecordStatus::Expired),
+            (RecordStatus::Verified, RecordStatus::Revoked),
+            (RecordStatus::Verified, RecordStatus::Disputed),
+            (RecordStatus::Disputed, RecordStatus::Verified),
+            (RecordStatus::Disputed, RecordStatus::Revoked),
+            (RecordStatus::Expired, RecordStatus::Verified),
+            (RecordStatus::Expired, RecordStatus::Revoked),
+            (RecordStatus::Verified, RecordStatus::Migrated),
+        ];
+        let statuses = [
+            RecordStatus::Pending,
+            RecordStatus::Verified,
+            RecordStatus::Revoked,
+            RecordStatus::Expired,
+            RecordStatus::Disputed,
+            RecordStatus::Migrated,
+        ];
+        let (key, mut lamports, mut data) = (Pubkey::new_unique(), 0, Vec::new());
+        registry().try_serialize(&mut data).unwrap();
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+        let mut registry = Account::<HealthcareRegistry>::try_from(&info).unwrap();
+        for from in statuses {
+            for to in statuses {
+                let mut record = record(from);
+                let seq = registry.event_seq;
+                let result = record.transition(Pubkey::new_unique(), to, &mut registry);
+                if legal.contains(&(from, to)) {
+                    result.unwrap();
+                    assert_eq!((record.status, record.is_valid), (to, to == RecordStatus::Verified));
+                    assert_eq!(registry.event_seq, seq + 1);
+                } else {
+                    let err = result.unwrap_err();
+                    assert_eq!(err, HealthcareError::InvalidStatusTransition.into(), "{from:?} -> {to:?}");
+                    assert_eq!(record.status, from);
+                }
+            }
+        }
+
+        // Among the refused: un-revoking and skipping the proof check
+        assert!(!RecordStatus::Revoked.can_become(RecordStatus::Verified));
+        assert!(!RecordStatus::Pending.can_become(RecordStatus::Expired));
+    }
+
+    #[test]
+    fn test_record_status_reads_the_original_layout() {
+        // `Active` and `Expired` were the first two variants
+        assert_eq!(RecordStatus::try_from_slice(&[0]).unwrap(), RecordStatus::Verified);
+        assert_eq!(RecordStatus::try_from_slice(&[1]).unwrap(), RecordStatus::Expired);
+
+        let clock = Clock::default();
+        record(RecordStatus::Verified).assert_active(&clock).unwrap();
+        for (status, error) in [
+            (RecordStatus::Revoked, HealthcareError::RecordRevoked),
+            (RecordStatus::Expired, HealthcareError::RecordExpired),
+            (RecordStatus::Pending, HealthcareError::RecordNotVerified),
+            (RecordStatus::Disputed, HealthcareError::RecordDisputed),
+            (RecordStatus::Migrated, HealthcareError::RecordAlreadyMigrated),
+        ] {
+            assert_eq!(record(status).assert_active(&clock).unwrap_err(), error.into());
+        }
+    }
+
+    #[test]
+    fn test_unmigrated_record_is_not_active() {
+        let mut legacy = record(RecordStatus::Verified);
+        legacy.version = 1;
+        let err = legacy.assert_active(&Clock::default()).unwrap_err();
+        assert_eq!(err, HealthcareError::RecordNeedsMigration.into());
+    }
+
+    #[test]
+    fn test_vk_config_slots() {
+        let config = VkConfig {
+            n_public: 3,
+            binds_patient: true,
+            domain_input: Some(1),
+            nullifier_input: None,
+            nonce_input: None,
+            freshness_window_secs: Some(60),
+            validity_secs: None,
+            curve: CurveId::Bn254,
+            scheme: ProvingScheme::Groth16,
+            fee_lamports: 0,
+            fee_recipient: Pubkey::default(),
+            requires_provider: false,
+        };
+        assert!(config.has_valid_domain_input() && config.has_valid_freshness_window());
+        assert!(!VkConfig { domain_input: Some(2), ..config }.has_valid_domain_input());
+        assert!(!VkConfig { domain_input: Some(0), ..config }.has_valid_domain_input());
+        assert!(!VkConfig { freshness_window_secs: Some(0), ..config }.has_valid_freshness_window());
+        assert!(config.has_valid_validity_period());
+        assert!(!VkConfig { validity_secs: Some(0), ..config }.has_valid_validity_period());
+        let single = VkConfig { n_public: 1, domain_input: None, ..config };
+        assert!(!single.has_valid_freshness_window());
+        assert!(!VkConfig { fee_lamports: 1, ..config }.has_valid_fee_recipient());
+        for taken in [0, 1, 2] {
+            assert!(!VkConfig { nullifier_input: Some(taken), ..config }.has_valid_nullifier_input());
+        }
+        let four = VkConfig { n_public: 4, ..config };
+        assert!(VkConfig { nullifier_input: Some(2), ..four }.has_valid_nullifier_input());
+        for taken in [0, 1, 3, 4] {
+            assert!(!VkConfig { nonce_input: Some(taken), ..four }.has_valid_nonce_input());
+        }
+        assert!(VkConfig { nonce_input: Some(2), ..four }.has_valid_nonce_input());
+        let anonymous = VkConfig { nullifier_input: Some(2), ..four };
+        assert!(!VkConfig { nonce_input: Some(2), ..anonymous }.has_valid_nonce_input());
+    }
+
+    #[test]
+    fn test_vk_chunks_out_of_order() {
+        let (vk_bytes, _, _) = square_fixture();
+        let mut data = pending_vk(vk_bytes.len() as u32);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        let chunks: Vec<(usize, &[u8])> = vk_bytes
+            .chunks(100)
+            .enumerate()
+            .map(|(i, chunk)| (i * 100, chunk))
+            .collect();
+
+        for (offset, chunk) in chunks.iter().rev() {
+            assert!(!vk.view().is_complete());
+            vk.write_chunk(*offset as u32, chunk).unwrap();
+        }
+        assert!(vk.view().is_complete());
+        assert_eq!(vk.view().vk_bytes(), vk_bytes);
+    }
+
+    #[test]
+    fn test_vk_overlapping_chunks() {
+        let mut data = pending_vk(16);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.write_chunk(0, &[1; 10]).unwrap();
+        // Re-sending identical bytes (e.g. a retried transaction) is accepted
+        vk.write_chunk(4, &[1; 6]).unwrap();
+        vk.write_chunk(8, &[1, 1, 2, 2, 2, 2, 2, 2]).unwrap();
+        assert!(vk.view().is_complete());
+
+        let err = vk.write_chunk(6, &[9; 4]).unwrap_err();
+        assert_eq!(err, HealthcareError::VkChunkConflict.into());
+        assert_eq!(&vk.view().vk_bytes()[6..10], &[1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_vk_missing_bytes_incomplete() {
+        let mut data = pending_vk(16);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.write_chunk(0, &[1; 7]).unwrap();
+        vk.write_chunk(8, &[1; 8]).unwrap();
+        assert!(!vk.view().is_complete());
+        vk.write_chunk(7, &[1]).unwrap();
+        assert!(vk.view().is_complete());
+    }
+
+    #[test]
+    fn test_vk_chunk_out_of_bounds() {
+        let mut data = pending_vk(16);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        let err = vk.write_chunk(10, &[0; 7]).unwrap_err();
+        assert_eq!(err, HealthcareError::VkChunkOutOfBounds.into());
+        let err = vk.write_chunk(u32::MAX, &[0; 1]).unwrap_err();
+        assert_eq!(err, HealthcareError::VkChunkOutOfBounds.into());
+        let err = vk.write_chunk(0, &[]).unwrap_err();
+        assert_eq!(err, HealthcareError::VkChunkOutOfBounds.into());
+    }
+
+    #[test]
+    fn test_vk_write_after_finalize_rejected() {
+        let mut data = pending_vk(4);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.write_chunk(0, &[1; 4]).unwrap();
+        vk.finalized = 1;
+        let err = vk.write_chunk(0, &[1; 4]).unwrap_err();
+        assert_eq!(err, HealthcareError::VerifyingKeyAlreadyFinalized.into());
+    }
+
+    #[test]
+    fn test_vk_account_must_hold_its_key() {
+        let (vk_bytes, _, _) = square_fixture();
+        let total_len = vk_bytes.len() as u32;
+        let mut data = pending_vk(total_len);
+        assert_eq!(data.len(), VerifyingKeyPDA::HEADER_LEN + 2 * vk_bytes.len() - 8 + vk_bytes.len().div_ceil(8));
+
+        let err = crate::VerifyingKey::new(&data[..data.len() - 1]).err().unwrap();
+        assert_eq!(err, HealthcareError::VerifyingKeyNotAllocated.into());
+        let err = crate::VerifyingKey::new(&data[..VerifyingKeyPDA::HEADER_LEN - 1]).err().unwrap();
+        assert_eq!(err, ErrorCode::AccountDiscriminatorMismatch.into());
+
+        // Bytes past the key regions (an account grown further than needed) are ignored
+        data.extend_from_slice(&[0xaa; 64]);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.write_chunk(0, &vk_bytes).unwrap();
+        vk.set_prepared(&prepare_vk_bytes(&vk_bytes).unwrap()).unwrap();
+        let key = vk.view();
+        assert!(key.is_complete());
+        assert_eq!(key.vk_bytes(), vk_bytes);
+        assert_eq!(key.prepared_vk_bytes(), prepare_vk_bytes(&vk_bytes).unwrap());
+    }
+
+    #[test]
+    fn test_poseidon_proof_hash_matches_circomlib() {
+        let scalar = |value: u8| {
+            let mut bytes = [0u8; 32];
+            bytes[31] = value;
+            bytes
+        };
+        let hex = |digest: [u8; 32]| digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+
+        // circomlibjs `poseidon([1])` and `poseidon([1, 2])`
+        let digest = HashAlgo::Poseidon.proof_hash([0; 32], &[scalar(1)]).unwrap();
+        assert_eq!(hex(digest), "29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133");
+        let digest = HashAlgo::Poseidon.proof_hash([0; 32], &[scalar(1), scalar(2)]).unwrap();
+        assert_eq!(hex(digest), "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a");
+
+        assert_eq!(HashAlgo::Keccak.proof_hash([7; 32], &[scalar(1)]).unwrap(), [7; 32]);
+        let err = HashAlgo::Poseidon.proof_hash([0; 32], &[scalar(1); MAX_POSEIDON_INPUTS + 1]).unwrap_err();
+        assert_eq!(err, HealthcareError::TooManyInputsForPoseidon.into());
+    }
+
+    #[test]
+    fn test_development_circuit_fixture() {
+        // Compute units are benchmarked against the SBF build in tests/compute_units.rs
+        let vk_bytes = fixtures::vk_bytes();
+        assert_eq!(vk_bytes.len() as u32, VerifyingKeyPDA::expected_vk_len(fixtures::N_PUBLIC));
+        assert_eq!(vk_bytes, fixtures::vk_bytes());
+        assert_eq!(fixtures::valid_proof(), fixtures::valid_proof());
+        check_verifying_key(&vk_bytes).unwrap();
+
+        let mut data = pending_vk(vk_bytes.len() as u32);
+        let mut vk = VerifyingKeyMut::new(&mut data).unwrap();
+        vk.write_chunk(0, &vk_bytes).unwrap();
+        let key = vk.view();
+        let inputs = fixtures::public_inputs();
+        let proof = decode(&fixtures::valid_proof());
+        assert!(key.verify(&proof, &inputs, ProofFormat::Uncompressed).unwrap());
+
+        let other = fixtures::prove(Fr::from(fixtures::PREIMAGE + 1));
+        let mut other_bytes = Vec::new();
+        other.serialize_uncompressed(&mut other_bytes).unwrap();
+        assert!(!key.verify(&decode(&other_bytes), &inputs, ProofFormat::Uncompressed).unwrap());
+    }
+
+    /// Fail unless `account`, with every variable field at its longest, serializes
+    /// to exactly the `space` allocated for it
+    fn assert_fills<T: AccountSerialize>(account: &T, space: usize) {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), space, "{}", std::any::type_name::<T>());
+    }
+
+    #[test]
+    fn test_account_space_fits_longest_accounts() {
+        let key = Pubkey::new_unique();
+        let cid = "b".repeat(MAX_IPFS_CID_LEN);
+        let circuit_id = "c".repeat(MAX_CIRCUIT_ID_LEN);
+
+        assert_fills(
+            &HealthcareRegistry {
+                authority: key,
+                nist_compliant: true,
+                total_verifications: u64::MAX,
+                ipfs_pin_count: u64::MAX,
+                vk_update_delay_secs: i64::MAX,
+                domain: [1; 32],
+                registered_circuits: u16::MAX,
+                circuit_for_type: [Some(key); VERIFICATION_TYPE_COUNT],
+                max_circuit_fee: u64::MAX,
+                max_public_inputs: u8::MAX,
+                cache_ttl_slots: u64::MAX,
+                commit_reveal_slots: u64::MAX,
+                security_policy: SecurityPolicy::default_for(true),
+                default_validity_secs: i64::MAX,
+                gc_bounty_lamports: u64::MAX,
+                closed_verifications: u64::MAX,
+                max_metadata_len: u16::MAX,
+                dispute_window_secs: i64::MAX,
+                time_source: TimeSource::Slot,
+                cooldown_secs: i64::MAX,
+                verifications_by_type: [u64::MAX; VERIFICATION_TYPE_COUNT],
+                revoked_count: u64::MAX,
+                expired_count: u64::MAX,
+                failed_proof_count: u64::MAX,
+                claim_count: u64::MAX,
+                event_seq: u64::MAX,
+                checkpointed_seq: u64::MAX,
+                cpi_events_enabled: true,
+                denied_access_events_enabled: true,
+                pinning_oracles: vec![key; MAX_PINNING_ORACLES],
+                failed_pin_timeout_secs: i64::MAX,
+                price_lamports_per_day: u64::MAX,
+                storage_treasury: key,
+                pin_bounty_period_secs: i64::MAX,
+                pin_bounty_periods: u16::MAX,
+                pin_replication_target: u8::MAX,
+                deal_expiry_warning_secs: i64::MAX,
+                max_access_pass_slots: u64::MAX,
+            },
+            HealthcareRegistry::SPACE,
+        );
+        let total_len = 648;
+        assert_fills(
+            &VkUpdateProposal {
+                verifying_key: key,
+                new_vk_hash: [1; 32],
+                proposed_at: 1,
+                activatable_at: 2,
+                total_len,
+                vk_bytes: vec![1; total_len as usize],
+                written_mask: vec![1; VerifyingKeyPDA::mask_len(total_len)],
+                bump: 255,
+            },
+            VkUpdateProposal::space(total_len),
+        );
+        let metadata_len = DEFAULT_MAX_METADATA_LEN as usize;
+        let record = VerificationRecord {
+            version: VerificationRecord::VERSION,
+            patient_pubkey: key,
+            proof_hash: [1; 32],
+            ipfs_hash: cid.clone(),
+            timestamp: 1,
+            is_valid: true,
+            verification_type: VerificationType::AccessControl,
+            slot: 1,
+            circuit_id: circuit_id.clone(),
+            circuit_version: u16::MAX,
+            vk_hash: [1; 32],
+            hash_algo: HashAlgo::Poseidon,
+            public_inputs_hash: [1; 32],
+            verification_id: [1; 32],
+            revoked_at: 1,
+            revoked_by: key,
+            expires_at: 1,
+            status: RecordStatus::Disputed,
+            rent_payer: key,
+            bump: 255,
+            revision: u16::MAX,
+            previous_proof_hash: [1; 32],
+            token_mint: key,
+            attesting_provider: Some(key),
+            previous_record: Some(key),
+            event_seq: u64::MAX,
+            hold: Some(HoldInfo { placed_at: 1, order_hash: [1; 32] }),
+            migrated_from: key,
+            metadata: vec![1; metadata_len],
+        };
+        assert_fills(&record, record.space_with_metadata(metadata_len));
+        assert_fills(
+            &PrescriptionRecord {
+                patient: key,
+                registry: key,
+                proof_hash: [1; 32],
+                hash_algo: HashAlgo::Poseidon,
+                drug_commitment: [1; 32],
+                circuit_id: circuit_id.clone(),
+                verified_at: 1,
+                valid_until: 1,
+                refills_allowed: u8::MAX,
+                refills_remaining: u8::MAX,
+                bump: 255,
+            },
+            PrescriptionRecord::SPACE,
+        );
+        assert_fills(
+            &DiagnosisRecord {
+                patient: key,
+                registry: key,
+                provider: key,
+                proof_hash: [1; 32],
+                hash_algo: HashAlgo::Poseidon,
+                icd10_commitment: [1; 32],
+                circuit_id: circuit_id.clone(),
+                ipfs_cid: cid.clone(),
+                pin_record: Some(key),
+                verified_at: 1,
+                revoked_at: 1,
+                revoked_by: key,
+                bump: 255,
+            },
+            DiagnosisRecord::SPACE,
+        );
+        assert_fills(
+            &LabResultRecord {
+                patient: key,
+                registry: key,
+                lab: key,
+                proof_hash: [1; 32],
+                hash_algo: HashAlgo::Poseidon,
+                result_commitment: [1; 32],
+                circuit_id: circuit_id.clone(),
+                verified_at: 1,
+                bump: 255,
+            },
+            LabResultRecord::SPACE,
+        );
+        assert_fills(
+            &ImmunizationRecord {
+                patient: key,
+                registry: key,
+                provider: key,
+                proof_hash: [1; 32],
+                hash_algo: HashAlgo::Poseidon,
+                status_commitment: [1; 32],
+                circuit_id: circuit_id.clone(),
+                verified_at: 1,
+                bump: 255,
+            },
+            ImmunizationRecord::SPACE,
+        );
+        assert_fills(
+            &ClaimRecord {
+                registry: key,
+                patient: key,
+                verification: key,
+                claim_id: u64::MAX,
+                amount: u64::MAX,
+                service_code_commitment: [1; 32],
+                status: ClaimStatus::Paid,
+                submitted_at: 1,
+                bump: 255,
+                payee: key,
+                insurer: Some(key),
+                reason_code: u16::MAX,
+                adjudicated_at: 1,
+                claim_nullifier: [1; 32],
+            },
+            ClaimRecord::SPACE,
+        );
+        assert_fills(&ClaimNullifier { claim: key, used_at: 1, bump: 255 }, ClaimNullifier::SPACE);
+        assert_fills(
+            &ClaimEscrow { claim: key, insurer: key, lamports: u64::MAX, bump: 255 },
+            ClaimEscrow::SPACE,
+        );
+        assert_fills(
+            &Checkpoint {
+                registry: key,
+                root: [1; 32],
+                start_seq: u64::MAX,
+                end_seq: u64::MAX,
+                committed_at: 1,
+                bump: 255,
+            },
+            Checkpoint::SPACE,
+        );
+        assert_fills(
+            &InsurerRegistration {
+                registry: key,
+                insurer: key,
+                registered_at: 1,
+                bump: 255,
+            },
+            InsurerRegistration::SPACE,
+        );
+        assert_fills(
+            &VerificationDispute {
+                record: key,
+                disputer: key,
+                evidence_hash: [1; 32],
+                disputed_at: 1,
+                bump: 255,
+            },
+            VerificationDispute::SPACE,
+        );
+        assert_fills(
+            &ProviderRegistration {
+                registry: key,
+                provider: key,
+                registered_at: 1,
+                active: true,
+                bump: 255,
+                eth_address: [1; 20],
+                daily_quota: u32::MAX,
+                used_today: u32::MAX,
+                quota_epoch_day: 1,
+            },
+            ProviderRegistration::SPACE,
+        );
+        assert_fills(
+            &AccessPass {
+                patient: key,
+                resource: key,
+                registry: key,
+                proof_hash: [1; 32],
+                issued_at_slot: 1,
+                expires_at_slot: 1,
+                uses_remaining: u16::MAX,
+                bump: 255,
+            },
+            AccessPass::SPACE,
+        );
+        assert_fills(
+            &PharmacyRegistration {
+                registry: key,
+                pharmacy: key,
+                registered_at: 1,
+                bump: 255,
+            },
+            PharmacyRegistration::SPACE,
+        );
+        assert_fills(
+            &ProofNullifier {
+                verification: key,
+                used_at: 1,
+                bump: 255,
+            },
+            ProofNullifier::SPACE,
+        );
+        assert_fills(
+            &CredentialNullifier {
+                verification: key,
+                epoch: 1,
+                bump: 255,
+                used_at: 1,
+            },
+            CredentialNullifier::SPACE,
+        );
+        assert_fills(
+            &PatientIndex {
+                verification_nonce: 1,
+                next_record_nonce: 1,
+                bump: 255,
+                verification_count: u32::MAX,
+                last_verification: key,
+                last_verified_at: 1,
+                type_counts: [u32::MAX; VERIFICATION_TYPE_COUNT],
+                active_count: u32::MAX,
+                last_verified_at_by_type: [1; VERIFICATION_TYPE_COUNT],
+                last_record: key,
+                last_record_by_type: [key; VERIFICATION_TYPE_COUNT],
+                pin_category_counts: [u32::MAX; DATA_CATEGORY_COUNT],
+            },
+            PatientIndex::SPACE,
+        );
+        assert_fills(
+            &KeyRotation {
+                old_key: key,
+                new_key: key,
+                rotated_at: 1,
+                bump: 255,
+            },
+            KeyRotation::SPACE,
+        );
+        assert_fills(
+            &GuardianConsent {
+                registry: key,
+                guardian: key,
+                ward: key,
+                scopes: GuardianScope::ALL,
+                expires_at: 1,
+                granted_by: key,
+                granted_at: 1,
+                revoked_at: 1,
+                bump: 255,
+            },
+            GuardianConsent::SPACE,
+        );
+        let n_public = u8::MAX;
+        assert_fills(
+            &PartialVerification {
+                patient: key,
+                registry: key,
+                verifying_key: key,
+                vk_hash: [1; 32],
+                proof: Groth16Proof {
+                    a: [1; 64],
+                    b: [1; 128],
+                    c: [1; 64],
+                },
+                public_inputs: vec![[1; 32]; n_public as usize],
+                next_input: n_public,
+                vk_x: [1; 64],
+                seed: [1; 32],
+                expires_at_slot: 1,
+            },
+            PartialVerification::space(n_public),
+        );
+        assert_fills(
+            &VerificationCache {
+                result: VerificationResult {
+                    verified: true,
+                    proof_hash: [1; 32],
+                    record: key,
+                    verification_id: [1; 32],
+                },
+                circuit_id,
+                expires_at_slot: 1,
+                payer: key,
+                bump: 255,
+            },
+            VerificationCache::SPACE,
+        );
+        assert_fills(
+            &VerificationCommitment {
+                submitter: key,
+                commitment: [1; 32],
+                committed_at_slot: 1,
+                expires_at_slot: 1,
+                bump: 255,
+            },
+            VerificationCommitment::SPACE,
+        );
+        let pin = IpfsPinRecord {
+            version: IpfsPinRecord::VERSION,
+            patient: key,
+            cid_version: 1,
+            multicodec: u16::MAX,
+            multihash: [1; PIN_MULTIHASH_LEN],
+            data_hash: [1; 32],
+            pinned_at: 1,
+            access_count: u32::MAX,
+            slot: 1,
+            registry: key,
+            rent_payer: key,
+            grants_expire_at_slot: u64::MAX,
+            last_accessed_at: 1,
+            status: PinStatus::Failed,
+            oracle: key,
+            settled_at: 1,
+            paid_through: 1,
+            bounty_lamports: 1,
+            bounty_released: 1,
+            bounty_period_secs: 1,
+            bounty_periods: 1,
+            bounty_periods_settled: u16::MAX,
+            bounty_settled_through: 1,
+            bounty_shares: [BountyShare { oracle: key, lamports: 1 }; MAX_BOUNTY_SHARES],
+            replication_target: 1,
+            replicas: [key; MAX_PIN_REPLICAS],
+            replica_confirmed_at: [1; MAX_PIN_REPLICAS],
+            backend: StorageBackend::Custom(u16::MAX),
+            category: DataCategory::Other,
+            tag: Some([1; 8]),
+            revision: u32::MAX,
+            revised_at: 1,
+            history: Vec::new(),
+            deals: Vec::new(),
+        };
+        assert_fills(&pin, IpfsPinRecord::SPACE);
+        let deal = DealInfo {
+            deal_id: u64::MAX,
+            provider: "f".repeat(MAX_DEAL_PROVIDER_LEN),
+            deal_expiry: 1,
+            oracle: key,
+            recorded_at: 1,
+        };
+        let version = PinVersion {
+            cid_version: 1,
+            multicodec: u16::MAX,
+            multihash: [1; PIN_MULTIHASH_LEN],
+            data_hash: [1; 32],
+            pinned_at: 1,
+        };
+        let (history, deals) = (vec![version; MAX_PIN_VERSIONS], vec![deal; MAX_STORAGE_DEALS]);
+        let pin = IpfsPinRecord { history, deals, ..pin };
+        assert_fills(&pin, IpfsPinRecord::space_with(MAX_PIN_VERSIONS, MAX_STORAGE_DEALS));
+        assert_fills(
+            &FederatedLearningState {
+                round_number: 1,
+                last_update: 1,
+                participant_count: u32::MAX,
+                slot: 1,
+            },
+            FederatedLearningState::SPACE,
+        );
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! Earlier layouts of the program's accounts, read back so
+//! `migrate_verification_record` can rewrite them in the current one.
+//!
+//! From v2 on, an account struct starts with a `version: u8` and a struct that
+//! changes layout bumps its `VERSION`. Accounts written before that have no
+//! version byte, so a v1 account is told apart by its length: it never reaches
+//! `V2_SPACE`, which every later account does, so their version byte can be read.
+
+use crate::{
+    BountyShare, DataCategory, DealInfo, HashAlgo, HoldInfo, IpfsPinRecord, PinStatus, RecordStatus, StorageBackend,
+    VerificationRecord, VerificationType, MAX_BOUNTY_SHARES, MAX_IPFS_CID_LEN, MAX_PIN_REPLICAS, MAX_STORAGE_DEALS,
+    PIN_MULTIHASH_LEN,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// A `VerificationRecord` in its v1 layout, before the `version` byte. A v2
+/// record is the same fields behind that byte, a v3 one adds `token_mint`, a v4
+/// one `metadata`, a v5 one `attesting_provider` ahead of it, a v6 one
+/// `previous_record` after that, a v7 one `event_seq` after that and a v8 one
+/// `hold` after that.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LegacyVerificationRecord {
+    pub patient_pubkey: Pubkey,
+    pub proof_hash: [u8; 32],
+    pub ipfs_hash: String,
+    pub timestamp: i64,
+    pub is_valid: bool,
+    pub verification_type: VerificationType,
+    pub slot: u64,
+    pub circuit_id: String,
+    pub circuit_version: u16,
+    pub vk_hash: [u8; 32],
+    pub hash_algo: HashAlgo,
+    pub public_inputs_hash: [u8; 32],
+    pub verification_id: [u8; 32],
+    pub revoked_at: i64,
+    pub revoked_by: Pubkey,
+    pub expires_at: i64,
+    pub status: RecordStatus,
+    pub rent_payer: Pubkey,
+    pub bump: u8,
+    /// Absent from records written before `reverify_eligibility`, whose accounts
+    /// may end at `bump`; read as zero then
+    pub revision: u16,
+    pub previous_proof_hash: [u8; 32],
+    /// Only in v3 to v8 records, read by `try_from_bytes` after the serialized fields
+    #[borsh_skip]
+    pub token_mint: Pubkey,
+    /// Only in v5 to v8 records, read after `token_mint`
+    #[borsh_skip]
+    pub attesting_provider: Option<Pubkey>,
+    /// Only in v6 to v8 records, read after `attesting_provider`
+    #[borsh_skip]
+    pub previous_record: Option<Pubkey>,
+    /// Only in v7 and v8 records, read after `previous_record`
+    #[borsh_skip]
+    pub event_seq: u64,
+    /// Only in v8 records, read after `event_seq`
+    #[borsh_skip]
+    pub hold: Option<HoldInfo>,
+    /// Only in v4 to v8 records, read last
+    #[borsh_skip]
+    pub metadata: Vec<u8>,
+}
+
+impl LegacyVerificationRecord {
+    /// Length of a v1 account as last allocated; older ones are shorter
+    pub const SPACE: usize = 8 + 416 + 2 + 32;
+    /// Length of a v2 account, before v3 appended `token_mint`
+    pub const V2_SPACE: usize = 8 + 1 + 416 + 2 + 32;
+    /// Length of a v3 account, before v4 appended `metadata`
+    pub const V3_SPACE: usize = Self::V2_SPACE + 32;
+    /// Length of a v4 account without metadata, before v5 inserted
+    /// `attesting_provider` ahead of it
+    pub const V4_SPACE: usize = Self::V3_SPACE + 4;
+    /// Length of a v5 account without metadata or a provider, before v6
+    /// inserted `previous_record` ahead of the metadata
+    pub const V5_SPACE: usize = Self::V4_SPACE + 1;
+    /// Length of a v6 account without metadata, a provider or a previous record,
+    /// before v7 inserted `event_seq` ahead of the metadata
+    pub const V6_SPACE: usize = Self::V5_SPACE + 1;
+    /// Length of a v7 account without metadata, a provider or a previous record,
+    /// before v8 inserted `hold` ahead of the metadata
+    pub const V7_SPACE: usize = Self::V6_SPACE + 8;
+    /// Length of a v8 account without metadata, a provider, a previous record
+    /// or a hold, before v9 inserted `migrated_from` ahead of the metadata
+    pub const V8_SPACE: usize = Self::V7_SPACE + 1;
+    /// Bytes of the fields `reverify_eligibility` appended
+    const RENEWAL_FIELDS_LEN: usize = 2 + 32;
+
+    /// Whether `data` is a `VerificationRecord` account still in an earlier layout
+    pub fn is_legacy(data: &[u8]) -> bool {
+        data.starts_with(&VerificationRecord::DISCRIMINATOR)
+            && Self::version(data).is_some_and(|version| version < VerificationRecord::VERSION)
+    }
+
+    /// Layout version of a `VerificationRecord` account: 1 for one too short to
+    /// carry the version byte, `None` for one too short to hold a record at all
+    fn version(data: &[u8]) -> Option<u8> {
+        if data.len() < Self::V2_SPACE {
+            return (data.len() > VerificationRecord::DISCRIMINATOR.len()).then_some(1);
+        }
+        data.get(VerificationRecord::DISCRIMINATOR.len()).copied()
+    }
+
+    /// Read a v1 to v8 account, discriminator included. A record that ends
+    /// before the renewal fields gets their defaults.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(&VerificationRecord::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        let version = Self::version(data).ok_or(ErrorCode::AccountDidNotDeserialize)?;
+        let mut fields = &data[VerificationRecord::DISCRIMINATOR.len()..];
+        if version > 1 {
+            fields = &fields[1..];
+        }
+        let mut bytes = fields.to_vec();
+        bytes.resize(bytes.len() + Self::RENEWAL_FIELDS_LEN, 0);
+        let reader = &mut &bytes[..];
+        let unreadable = |_| error!(ErrorCode::AccountDidNotDeserialize);
+        let mut record = Self::deserialize(reader).map_err(unreadable)?;
+        if version >= 3 {
+            record.token_mint = Pubkey::deserialize(reader).map_err(unreadable)?;
+        }
+        if version >= 5 {
+            record.attesting_provider = Option::deserialize(reader).map_err(unreadable)?;
+        }
+        if version >= 6 {
+            record.previous_record = Option::deserialize(reader).map_err(unreadable)?;
+        }
+        if version >= 7 {
+            record.event_seq = u64::deserialize(reader).map_err(unreadable)?;
+        }
+        if version >= 8 {
+            record.hold = Option::deserialize(reader).map_err(unreadable)?;
+        }
+        if version >= 4 {
+            record.metadata = Vec::deserialize(reader).map_err(unreadable)?;
+        }
+        Ok(record)
+    }
+
+    /// The record in the current layout, migrated from no other registry and,
+    /// unless it is a v8 one under a hold, under none. One from before v6 is
+    /// outside its patient's chain of records, and one from before v7 has no
+    /// `event_seq` of its own: the events that concern it were emitted before
+    /// the registry numbered them.
+    pub fn into_current(self) -> VerificationRecord {
+        VerificationRecord {
+            version: VerificationRecord::VERSION,
+            patient_pubkey: self.patient_pubkey,
+            proof_hash: self.proof_hash,
+            ipfs_hash: self.ipfs_hash,
+            timestamp: self.timestamp,
+            is_valid: self.is_valid,
+            verification_type: self.verification_type,
+            slot: self.slot,
+            circuit_id: self.circuit_id,
+            circuit_version: self.circuit_version,
+            vk_hash: self.vk_hash,
+            hash_algo: self.hash_algo,
+            public_inputs_hash: self.public_inputs_hash,
+            verification_id: self.verification_id,
+            revoked_at: self.revoked_at,
+            revoked_by: self.revoked_by,
+            expires_at: self.expires_at,
+            status: self.status,
+            rent_payer: self.rent_payer,
+            bump: self.bump,
+            revision: self.revision,
+            previous_proof_hash: self.previous_proof_hash,
+            token_mint: self.token_mint,
+            attesting_provider: self.attesting_provider,
+            previous_record: self.previous_record,
+            event_seq: self.event_seq,
+            hold: self.hold,
+            migrated_from: Pubkey::default(),
+            metadata: self.metadata,
+        }
+    }
+}
+
+/// An `IpfsPinRecord` in its v1 layout, which held the CID as its string and
+/// had no version byte. A v2 pin is the v4 layout without
+/// `grants_expire_at_slot` and `last_accessed_at`, a v3 one without
+/// `last_accessed_at`, a v4 one the v5 layout without `status` and `oracle`,
+/// a v5 one the v6 layout without `settled_at`, a v6 one the v7 layout
+/// without `paid_through`, a v7 one the v8 layout without the bounty fields,
+/// a v8 one the v9 layout without the replication fields, and a v9 one the
+/// current layout without `backend`. Pins before v10 are all on IPFS.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LegacyIpfsPinRecord {
+    pub patient: Pubkey,
+    pub ipfs_cid: String,
+    pub data_hash: [u8; 32],
+    pub pinned_at: i64,
+    pub access_count: u32,
+    pub slot: u64,
+    pub registry: Pubkey,
+    pub rent_payer: Pubkey,
+}
+
+impl LegacyIpfsPinRecord {
+    /// Length of a v1 account, allocated for the longest CID whatever it held
+    pub const SPACE: usize = 8 + 32 + 4 + MAX_IPFS_CID_LEN + 32 + 8 + 4 + 8 + 32 + 32;
+    /// Length of a v2 account, before v3 appended `grants_expire_at_slot`
+    pub const V2_SPACE: usize = Self::V3_SPACE - 8;
+    /// Length of a v3 account, before v4 appended `last_accessed_at`
+    pub const V3_SPACE: usize = Self::V4_SPACE - 8;
+    /// Length of a v4 account, before v5 appended `status` and `oracle`
+    pub const V4_SPACE: usize = Self::V5_SPACE - 1 - 32;
+    /// Length of a v5 account, before v6 appended `settled_at`
+    pub const V5_SPACE: usize = Self::V6_SPACE - 8;
+    /// Length of a v6 account, before v7 appended `paid_through`
+    pub const V6_SPACE: usize = Self::V7_SPACE - 8;
+    /// Length of a v7 account, before v8 appended the bounty fields
+    pub const V7_SPACE: usize =
+        Self::V8_SPACE - 8 - 8 - 8 - 2 - 2 - 8 - MAX_BOUNTY_SHARES * BountyShare::INIT_SPACE;
+    /// Length of a v8 account, before v9 appended the replication fields
+    pub const V8_SPACE: usize = Self::V9_SPACE - 1 - 32 * MAX_PIN_REPLICAS - 8 * MAX_PIN_REPLICAS;
+    /// Length of a v9 account, before v10 appended `backend`
+    pub const V9_SPACE: usize = Self::V10_SPACE - StorageBackend::INIT_SPACE;
+    /// Length of a v10 account, before v11 appended `deals`
+    pub const V10_SPACE: usize = Self::V11_SPACE - 4;
+    /// Length of a v11 account holding no deals, before v12 put `category`
+    /// and `tag`, an optional 8 bytes, ahead of them
+    pub const V11_SPACE: usize = Self::V12_SPACE - DataCategory::INIT_SPACE - 1 - 8;
+    /// Length of a v12 account holding no deals, before v13 put `revision`,
+    /// `revised_at` and `history` ahead of them
+    pub const V12_SPACE: usize = IpfsPinRecord::SPACE - 4 - 8 - 4;
+
+    /// The patient of an `IpfsPinRecord` account in any layout: a v1 pin
+    /// starts with it, a later one holds it behind its version byte
+    pub fn patient_of(info: &AccountInfo) -> Result<Pubkey> {
+        let data = info.try_borrow_data()?;
+        let at = IpfsPinRecord::DISCRIMINATOR.len() + usize::from(data.len() != Self::SPACE);
+        let patient = data.get(at..at + 32).and_then(|patient| <[u8; 32]>::try_from(patient).ok());
+        Ok(Pubkey::new_from_array(patient.ok_or_else(|| error!(ErrorCode::AccountDidNotDeserialize))?))
+    }
+
+    /// Whether the patient's `PatientIndex` already counts the pin in `data`,
+    /// as it does every pin written since v12 filed pins under a category
+    pub fn is_counted(data: &[u8]) -> bool {
+        data.len() != Self::SPACE && data.get(IpfsPinRecord::DISCRIMINATOR.len()).is_some_and(|version| *version >= 12)
+    }
+
+    /// Whether `data` is an `IpfsPinRecord` account still in an earlier
+    /// layout. A v1 pin starts with its patient rather than a version byte, so
+    /// it is told apart by its length.
+    pub fn is_legacy(data: &[u8]) -> bool {
+        let version = data.get(IpfsPinRecord::DISCRIMINATOR.len()).copied();
+        let holds_deals =
+            |space| (0..=MAX_STORAGE_DEALS).any(|deals| data.len() == space + deals * DealInfo::INIT_SPACE);
+        data.starts_with(&IpfsPinRecord::DISCRIMINATOR)
+            && (data.len() == Self::SPACE
+                || (data.len() == Self::V2_SPACE && version == Some(2))
+                || (data.len() == Self::V3_SPACE && version == Some(3))
+                || (data.len() == Self::V4_SPACE && version == Some(4))
+                || (data.len() == Self::V5_SPACE && version == Some(5))
+                || (data.len() == Self::V6_SPACE && version == Some(6))
+                || (data.len() == Self::V7_SPACE && version == Some(7))
+                || (data.len() == Self::V8_SPACE && version == Some(8))
+                || (data.len() == Self::V9_SPACE && version == Some(9))
+                || (data.len() == Self::V10_SPACE && version == Some(10))
+                || (version == Some(11) && holds_deals(Self::V11_SPACE))
+                || (version == Some(12) && holds_deals(Self::V12_SPACE)))
+    }
+
+    /// Read a v1 to v12 account, discriminator included, in the current layout,
+    /// failing as `into_current` does for a v1 one. The fields a later version
+    /// added start at zero, leaving the pin `Requested` with no history, and a
+    /// pin from before v12 is filed as `Other`.
+    pub fn current_from_bytes(data: &[u8]) -> Result<IpfsPinRecord> {
+        if data.len() == Self::SPACE {
+            return Self::try_from_bytes(data)?.into_current();
+        }
+        let discriminator_len = IpfsPinRecord::DISCRIMINATOR.len();
+        let version = data.get(discriminator_len).copied();
+        let mut bytes = data.get(discriminator_len..).unwrap_or_default().to_vec();
+        if matches!(version, Some(11 | 12)) {
+            // Make room for `category` and `tag` between `backend`, of either
+            // length, and `deals` in a v11 pin, then for `revision`,
+            // `revised_at` and `history` after them
+            let rest = &mut &bytes[Self::V9_SPACE - discriminator_len..];
+            StorageBackend::deserialize(rest).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+            let at = bytes.len() - rest.len();
+            if version == Some(11) {
+                bytes.splice(at..at, [0; DataCategory::INIT_SPACE + 1]);
+            }
+            let rest = &mut &bytes[at..];
+            <(DataCategory, Option<[u8; 8]>)>::deserialize(rest)
+                .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+            let at = bytes.len() - rest.len();
+            bytes.splice(at..at, [0; 4 + 8 + 4]);
+        }
+        let len = bytes.len().max(IpfsPinRecord::SPACE - discriminator_len);
+        bytes.resize(len, 0);
+        let mut pin_record =
+            IpfsPinRecord::deserialize(&mut &bytes[..]).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+        pin_record.version = IpfsPinRecord::VERSION;
+        if version != Some(12) {
+            pin_record.category = DataCategory::Other;
+        }
+        Ok(pin_record)
+    }
+
+    /// Read a v1 account, discriminator included
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.starts_with(&IpfsPinRecord::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Self::deserialize(&mut &data[IpfsPinRecord::DISCRIMINATOR.len()..])
+            .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+    }
+
+    /// The pin in the current layout, failing as `IpfsPinRecord::set_cid` does
+    /// for a CID it can't hold
+    pub fn into_current(self) -> Result<IpfsPinRecord> {
+        let mut pin_record = IpfsPinRecord {
+            version: IpfsPinRecord::VERSION,
+            patient: self.patient,
+            cid_version: 0,
+            multicodec: 0,
+            multihash: [0; PIN_MULTIHASH_LEN],
+            data_hash: self.data_hash,
+            pinned_at: self.pinned_at,
+            access_count: self.access_count,
+            slot: self.slot,
+            registry: self.registry,
+            rent_payer: self.rent_payer,
+            grants_expire_at_slot: 0,
+            last_accessed_at: 0,
+            status: PinStatus::Requested,
+            oracle: Pubkey::default(),
+            settled_at: 0,
+            paid_through: 0,
+            bounty_lamports: 0,
+            bounty_released: 0,
+            bounty_period_secs: 0,
+            bounty_periods: 0,
+            bounty_periods_settled: 0,
+            bounty_settled_through: 0,
+            bounty_shares: [BountyShare::default(); MAX_BOUNTY_SHARES],
+            replication_target: 0,
+            replicas: [Pubkey::default(); MAX_PIN_REPLICAS],
+            replica_confirmed_at: [0; MAX_PIN_REPLICAS],
+            backend: StorageBackend::Ipfs,
+            category: DataCategory::Other,
+            tag: None,
+            revision: 0,
+            revised_at: 0,
+            history: Vec::new(),
+            deals: Vec::new(),
+        };
+        pin_record.set_cid(&self.ipfs_cid)?;
+        Ok(pin_record)
+    }
+}
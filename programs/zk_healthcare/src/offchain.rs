@@ -0,0 +1,86 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! arkworks helpers for preparing verifying keys off-chain. The program itself
+//! only handles raw bytes and the alt_bn128 syscalls; these helpers produce the
+//! same encodings from arkworks types.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::keccak;
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::VerifyingKey;
+use ark_serialize::CanonicalSerialize;
+
+/// Serialize a verifying key in the layout uploaded through `write_vk_chunk`
+pub fn encode_verifying_key(vk: &VerifyingKey<Bn254>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(vk.uncompressed_size());
+    vk.serialize_uncompressed(&mut bytes)
+        .expect("serializing into a Vec cannot fail");
+    bytes
+}
+
+/// The syscall encoding of a verifying key, identical to what `prepare_vk` caches
+/// in `VerifyingKeyPDA::prepared_vk_bytes`
+pub fn prepare_verifying_key(vk: &VerifyingKey<Bn254>) -> Vec<u8> {
+    let mut prepared = Vec::new();
+    prepared.extend_from_slice(&g1_syscall_bytes(&vk.alpha_g1));
+    prepared.extend_from_slice(&g2_syscall_bytes(&vk.beta_g2));
+    prepared.extend_from_slice(&g2_syscall_bytes(&vk.gamma_g2));
+    prepared.extend_from_slice(&g2_syscall_bytes(&vk.delta_g2));
+    for point in &vk.gamma_abc_g1 {
+        prepared.extend_from_slice(&g1_syscall_bytes(point));
+    }
+    prepared
+}
+
+/// Big-endian `x | y`, all zeros for the point at infinity
+pub fn g1_syscall_bytes(point: &G1Affine) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&x.into_bigint().to_bytes_be());
+        bytes[32..].copy_from_slice(&y.into_bigint().to_bytes_be());
+    }
+    bytes
+}
+
+/// Big-endian `x.c1 | x.c0 | y.c1 | y.c0`, all zeros for the point at infinity
+pub fn g2_syscall_bytes(point: &G2Affine) -> [u8; 128] {
+    let mut bytes = [0u8; 128];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&x.c1.into_bigint().to_bytes_be());
+        bytes[32..64].copy_from_slice(&x.c0.into_bigint().to_bytes_be());
+        bytes[64..96].copy_from_slice(&y.c1.into_bigint().to_bytes_be());
+        bytes[96..].copy_from_slice(&y.c0.into_bigint().to_bytes_be());
+    }
+    bytes
+}
+
+/// The public input a patient-bound circuit must expose at index 0, matching
+/// `crate::patient_binding`
+pub fn patient_binding(patient: &Pubkey) -> Fr {
+    Fr::from_le_bytes_mod_order(&keccak::hash(patient.as_ref()).to_bytes())
+}
+
+/// Serialize a PLONK verifying key in the layout `verifier::PlonkVerifier` reads:
+/// `[qm, ql, qr, qo, qc, s1, s2, s3]` commitments, `[tau]_2`, the coset shifts
+/// `k1`, `k2`, and the generator `omega` of the `2^power` element domain
+pub fn encode_plonk_verifying_key(
+    commitments: &[G1Affine; 8],
+    x_2: &G2Affine,
+    k1: Fr,
+    k2: Fr,
+    omega: Fr,
+    power: u8,
+) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(crate::verifier::plonk::PLONK_VK_LEN);
+    for point in commitments {
+        bytes.extend_from_slice(&g1_syscall_bytes(point));
+    }
+    bytes.extend_from_slice(&g2_syscall_bytes(x_2));
+    for scalar in [k1, k2, omega] {
+        bytes.extend_from_slice(&scalar.into_bigint().to_bytes_be());
+    }
+    bytes.push(power);
+    bytes
+}
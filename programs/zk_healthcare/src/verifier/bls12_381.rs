@@ -0,0 +1,124 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! Groth16 over BLS12-381, for circuits compiled for that curve rather than
+//! BN254. The runtime has no BLS12-381 syscalls, so points are decoded and the
+//! pairing computed with arkworks in the program itself.
+
+use super::{ProofVerifier, Verdict};
+use crate::verifier_core::{parse_scalars, VerifyError};
+use crate::{compute_verification_hash, HealthcareError, ProofFormat, VerifyingKey};
+use anchor_lang::prelude::*;
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::PrimeField;
+use ark_groth16::{prepare_verifying_key, Groth16, Proof};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+pub const BLS_G1_LEN: usize = 96;
+pub const BLS_G2_LEN: usize = 192;
+pub const BLS_G1_COMPRESSED_LEN: usize = 48;
+pub const BLS_G2_COMPRESSED_LEN: usize = 96;
+/// A (48) | B (96) | C (48), arkworks compressed
+pub const BLS_PROOF_LEN: usize = 2 * BLS_G1_COMPRESSED_LEN + BLS_G2_COMPRESSED_LEN;
+/// Offset of the gamma_abc length prefix in an arkworks uncompressed verifying key
+const BLS_VK_IC_OFFSET: usize = BLS_G1_LEN + 3 * BLS_G2_LEN;
+/// BLS12-381 scalar field modulus, big-endian
+pub const BLS_FR_MODULUS_BE: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+type BlsVerifyingKey = ark_groth16::VerifyingKey<Bls12_381>;
+
+/// Groth16 over BLS12-381, keys in the arkworks uncompressed layout. Proofs are
+/// only accepted as `ProofFormat::Compressed`, a single encoding per proof, so
+/// `ProofNullifier::seed` over the raw bytes tells resubmissions apart.
+pub struct Bls12_381Verifier;
+
+impl ProofVerifier for Bls12_381Verifier {
+    fn vk_len(&self, n_public: u8) -> u32 {
+        (BLS_VK_IC_OFFSET + 8 + (n_public as usize + 1) * BLS_G1_LEN) as u32
+    }
+
+    fn check_verifying_key(&self, vk_bytes: &[u8]) -> Result<()> {
+        let vk = decode_verifying_key(vk_bytes)?;
+        let points = [vk.alpha_g1.is_zero(), vk.beta_g2.is_zero(), vk.gamma_g2.is_zero(), vk.delta_g2.is_zero()];
+        require!(!points.contains(&true), HealthcareError::VerifyingKeyDeserializeFailed);
+        Ok(())
+    }
+
+    fn verify(
+        &self,
+        key: &VerifyingKey,
+        proof: &[u8],
+        format: ProofFormat,
+        public_inputs: &[u8],
+    ) -> Result<Verdict> {
+        require!(format == ProofFormat::Compressed, HealthcareError::UnsupportedProofFormat);
+        let decoded = decode_proof(proof)?;
+        key.check_input_count(public_inputs)?;
+        let inputs = self.parse_public_inputs(public_inputs, format)?;
+        let vk = decode_verifying_key(key.vk_bytes())?;
+        let scalars: Vec<Fr> = inputs.iter().map(|input| Fr::from_be_bytes_mod_order(input)).collect();
+        let verified = Groth16::<Bls12_381>::verify_proof(&prepare_verifying_key(&vk), &decoded, &scalars)
+            .map_err(|_| error!(HealthcareError::VerifyingKeyDeserializeFailed))?;
+        Ok(Verdict {
+            verified,
+            proof_hash: compute_verification_hash(proof, &inputs),
+        })
+    }
+
+    fn parse_public_inputs(&self, bytes: &[u8], format: ProofFormat) -> Result<Vec<[u8; 32]>> {
+        Ok(parse_scalars(bytes, format, &BLS_FR_MODULUS_BE)?)
+    }
+}
+
+/// Decode a compressed proof, refusing identity points and any encoding
+/// arkworks wouldn't produce for the same points
+fn decode_proof(bytes: &[u8]) -> std::result::Result<Proof<Bls12_381>, VerifyError> {
+    if bytes.len() != BLS_PROOF_LEN {
+        return Err(VerifyError::InvalidProofLength { expected: BLS_PROOF_LEN, actual: bytes.len() });
+    }
+    let (a, rest) = bytes.split_at(BLS_G1_COMPRESSED_LEN);
+    let (b, c) = rest.split_at(BLS_G2_COMPRESSED_LEN);
+    let invalid = |offset: usize| move |_| VerifyError::InvalidProofEncoding { offset };
+    let proof = Proof::<Bls12_381> {
+        a: G1Affine::deserialize_compressed(a).map_err(invalid(0))?,
+        b: G2Affine::deserialize_compressed(b).map_err(invalid(BLS_G1_COMPRESSED_LEN))?,
+        c: G1Affine::deserialize_compressed(c).map_err(invalid(BLS_G1_COMPRESSED_LEN + BLS_G2_COMPRESSED_LEN))?,
+    };
+    for (point, is_zero) in [('A', proof.a.is_zero()), ('B', proof.b.is_zero()), ('C', proof.c.is_zero())] {
+        if is_zero {
+            return Err(VerifyError::InvalidProofPoint { point });
+        }
+    }
+    let mut encoded = Vec::with_capacity(BLS_PROOF_LEN);
+    proof.serialize_compressed(&mut encoded).map_err(|_| VerifyError::NonCanonicalProofEncoding)?;
+    if encoded != bytes {
+        return Err(VerifyError::NonCanonicalProofEncoding);
+    }
+    Ok(proof)
+}
+
+/// Deserializing checks every point is on the curve and in the prime-order
+/// subgroup
+fn decode_verifying_key(vk_bytes: &[u8]) -> Result<BlsVerifyingKey> {
+    let vk = BlsVerifyingKey::deserialize_uncompressed(vk_bytes)
+        .map_err(|_| error!(HealthcareError::VerifyingKeyDeserializeFailed))?;
+    require!(
+        vk.gamma_abc_g1.len() > 1 && vk.uncompressed_size() == vk_bytes.len(),
+        HealthcareError::VerifyingKeyDeserializeFailed
+    );
+    Ok(vk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::BigInteger;
+
+    #[test]
+    fn test_scalar_modulus_matches_arkworks() {
+        assert_eq!(Fr::MODULUS.to_bytes_be(), BLS_FR_MODULUS_BE);
+    }
+}
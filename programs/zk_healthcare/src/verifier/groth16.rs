@@ -0,0 +1,36 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+use super::{ProofVerifier, Verdict};
+use crate::{
+    cached_or_prepared, check_verifying_key, verifier_core, ProofFormat, VerifyingKey, VerifyingKeyPDA,
+};
+use anchor_lang::prelude::*;
+
+/// Groth16 over BN254, with keys in the arkworks uncompressed layout
+pub struct Groth16Verifier;
+
+impl ProofVerifier for Groth16Verifier {
+    fn vk_len(&self, n_public: u8) -> u32 {
+        VerifyingKeyPDA::expected_vk_len(n_public)
+    }
+
+    fn check_verifying_key(&self, vk_bytes: &[u8]) -> Result<()> {
+        check_verifying_key(vk_bytes)
+    }
+
+    fn verify(
+        &self,
+        key: &VerifyingKey,
+        proof: &[u8],
+        format: ProofFormat,
+        public_inputs: &[u8],
+    ) -> Result<Verdict> {
+        let prepared = cached_or_prepared(key.vk_bytes(), key.prepared_vk_bytes())?;
+        let (proof, inputs, verified) =
+            verifier_core::verify_submission(&prepared, key.n_public as usize, proof, format, public_inputs)?;
+        Ok(Verdict {
+            verified,
+            proof_hash: proof.hash(&inputs)?,
+        })
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! One `ProofVerifier` per `ProvingScheme` and `CurveId` this build can verify.
+//! The verify instructions go through `ProvingScheme::verifier_on` and never
+//! branch on the scheme or curve themselves, so records and events look the same
+//! whichever proof system a circuit uses.
+
+#[cfg(feature = "bls")]
+pub mod bls12_381;
+mod groth16;
+pub mod plonk;
+
+use crate::{parse_public_inputs, HealthcareError, ProofFormat, VerifyingKey};
+use anchor_lang::prelude::*;
+
+#[cfg(feature = "bls")]
+pub use bls12_381::Bls12_381Verifier;
+pub use groth16::Groth16Verifier;
+pub use plonk::{PlonkProof, PlonkVerifier};
+
+/// Outcome of checking a proof that decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Verdict {
+    pub verified: bool,
+    /// `compute_verification_hash` of the proof, the same for every encoding the
+    /// scheme accepts
+    pub proof_hash: [u8; 32],
+}
+
+pub trait ProofVerifier {
+    /// Exact size of a verifying key for a circuit with `n_public` inputs
+    fn vk_len(&self, n_public: u8) -> u32;
+
+    /// Reject keys that are malformed or could never verify a proof. Run before a
+    /// key (or a replacement for one) can be used.
+    fn check_verifying_key(&self, vk_bytes: &[u8]) -> Result<()>;
+
+    /// Decode `proof` and check it against `key`. An error means the submission is
+    /// malformed or doesn't fit the circuit; a well-formed proof of a false
+    /// statement is `verified: false`.
+    fn verify(
+        &self,
+        key: &VerifyingKey,
+        proof: &[u8],
+        format: ProofFormat,
+        public_inputs: &[u8],
+    ) -> Result<Verdict>;
+
+    /// Parse public inputs as `verify` does, into big-endian scalars of the
+    /// curve's scalar field
+    fn parse_public_inputs(&self, bytes: &[u8], format: ProofFormat) -> Result<Vec<[u8; 32]>> {
+        parse_public_inputs(bytes, format)
+    }
+}
+
+/// Stands in for a key whose scheme and curve this build has no verifier for,
+/// e.g. one registered by a build with the `bls` feature: it fails everything
+/// with `UnsupportedCurve`
+pub struct UnsupportedVerifier;
+
+impl ProofVerifier for UnsupportedVerifier {
+    fn vk_len(&self, _n_public: u8) -> u32 {
+        0
+    }
+
+    fn check_verifying_key(&self, _vk_bytes: &[u8]) -> Result<()> {
+        err!(HealthcareError::UnsupportedCurve)
+    }
+
+    fn verify(
+        &self,
+        _key: &VerifyingKey,
+        _proof: &[u8],
+        _format: ProofFormat,
+        _public_inputs: &[u8],
+    ) -> Result<Verdict> {
+        err!(HealthcareError::UnsupportedCurve)
+    }
+
+    fn parse_public_inputs(&self, _bytes: &[u8], _format: ProofFormat) -> Result<Vec<[u8; 32]>> {
+        err!(HealthcareError::UnsupportedCurve)
+    }
+}
@@ -0,0 +1,368 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! PLONK over BN254 with KZG commitments, as generated and checked by snarkjs
+//! (`plonk_verify.js`): the same keccak transcript, linearization and single
+//! batched opening, so a snarkjs proof verifies here unchanged. Curve operations
+//! use the alt_bn128 syscalls; only scalar field arithmetic runs in the program.
+
+use super::{ProofVerifier, Verdict};
+use crate::{
+    canonical_point, compute_verification_hash, g1_add, g1_mul, negate_g1, parse_public_inputs, HealthcareError,
+    ProofFormat, VerifyingKey, FR_MODULUS_BE, G1_LEN, G2_LEN,
+};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::alt_bn128::prelude::{alt_bn128_addition, alt_bn128_pairing};
+use anchor_lang::solana_program::keccak;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, One, PrimeField};
+
+/// `[qm, ql, qr, qo, qc, s1, s2, s3]`, in transcript order
+const VK_COMMITMENTS: usize = 8;
+/// Commitments (64 each) | X_2 = `[tau]_2` (128) | k1 | k2 | omega (32 each) | power (1)
+pub const PLONK_VK_LEN: usize = VK_COMMITMENTS * G1_LEN + G2_LEN + 3 * 32 + 1;
+/// A, B, C, Z, T1, T2, T3, Wxi, Wxiw (64 each) | six evaluations (32 each)
+pub const PLONK_PROOF_LEN: usize = 9 * G1_LEN + 6 * 32;
+/// Largest domain the scalar field has roots of unity for
+const MAX_POWER: u8 = 28;
+
+/// `[1]_1`
+const G1_GENERATOR: [u8; 64] = {
+    let mut point = [0u8; 64];
+    point[31] = 1;
+    point[63] = 2;
+    point
+};
+/// `[1]_2` in the syscall encoding
+const G2_GENERATOR: [u8; 128] = [
+    0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+    0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+    0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+    0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+    0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+    0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+    0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+    0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+];
+
+/// PLONK with a universal KZG setup. Proofs use the snarkjs layout and are only
+/// accepted as `ProofFormat::SnarkJs`, which also makes public inputs big-endian.
+pub struct PlonkVerifier;
+
+impl ProofVerifier for PlonkVerifier {
+    fn vk_len(&self, _n_public: u8) -> u32 {
+        PLONK_VK_LEN as u32
+    }
+
+    fn check_verifying_key(&self, vk_bytes: &[u8]) -> Result<()> {
+        let vk = PlonkVerifyingKey::parse(vk_bytes)?;
+        let invalid = |_| error!(HealthcareError::VerifyingKeyDeserializeFailed);
+        for point in vk.commitments {
+            alt_bn128_addition(point).map_err(invalid)?;
+        }
+        let mut pairing_input = [0u8; G1_LEN + G2_LEN];
+        pairing_input[G1_LEN..].copy_from_slice(vk.x_2);
+        alt_bn128_pairing(&pairing_input).map_err(invalid)?;
+
+        // omega must generate the whole domain, and k1, k2 must shift it onto
+        // cosets disjoint from it and from each other, or the copy constraints
+        // could be satisfied by unrelated wires
+        let n = vk.domain_size();
+        let omega_half = (1..vk.power).fold(vk.omega, |acc, _| acc.square());
+        let in_domain = |x: Fr| x.pow([n]).is_one();
+        let distinct_cosets = match vk.k2.inverse() {
+            Some(k2_inv) => !in_domain(vk.k1) && !in_domain(vk.k2) && !in_domain(vk.k1 * k2_inv),
+            None => false,
+        };
+        require!(
+            in_domain(vk.omega) && !omega_half.is_one() && distinct_cosets,
+            HealthcareError::VerifyingKeyDeserializeFailed
+        );
+        Ok(())
+    }
+
+    fn verify(
+        &self,
+        key: &VerifyingKey,
+        proof: &[u8],
+        format: ProofFormat,
+        public_inputs: &[u8],
+    ) -> Result<Verdict> {
+        let decoded = PlonkProof::decode(proof, format)?;
+        key.check_input_count(public_inputs)?;
+        let public_inputs = parse_public_inputs(public_inputs, format)?;
+        let vk = PlonkVerifyingKey::parse(key.vk_bytes())?;
+        Ok(Verdict {
+            verified: verify_plonk(&vk, &decoded, &public_inputs)?,
+            proof_hash: compute_verification_hash(proof, &public_inputs),
+        })
+    }
+}
+
+/// A PLONK proof in the snarkjs layout, coordinates and scalars big-endian
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlonkProof {
+    pub a: [u8; 64],
+    pub b: [u8; 64],
+    pub c: [u8; 64],
+    pub z: [u8; 64],
+    pub t1: [u8; 64],
+    pub t2: [u8; 64],
+    pub t3: [u8; 64],
+    pub wxi: [u8; 64],
+    pub wxiw: [u8; 64],
+    pub eval_a: Fr,
+    pub eval_b: Fr,
+    pub eval_c: Fr,
+    pub eval_s1: Fr,
+    pub eval_s2: Fr,
+    pub eval_zw: Fr,
+}
+
+impl PlonkProof {
+    /// Every coordinate and evaluation must be canonical, so each proof has
+    /// exactly one accepted encoding and its raw bytes can serve as its identity
+    pub fn decode(bytes: &[u8], format: ProofFormat) -> Result<Self> {
+        require!(format == ProofFormat::SnarkJs, HealthcareError::UnsupportedProofFormat);
+        require!(bytes.len() == PLONK_PROOF_LEN, HealthcareError::InvalidProofLength);
+        let invalid = |offset: usize| {
+            msg!("Proof element at byte {} is not canonical", offset);
+            error!(HealthcareError::InvalidProofEncoding)
+        };
+
+        let (points, evaluations) = bytes.split_at(9 * G1_LEN);
+        let mut points = points.chunks_exact(G1_LEN).enumerate();
+        let mut point = || {
+            let (index, bytes) = points.next().unwrap_or_default();
+            canonical_point::<64>(bytes).ok_or_else(|| invalid(index * G1_LEN))
+        };
+        let [a, b, c, z, t1, t2, t3, wxi, wxiw] = [
+            point()?, point()?, point()?, point()?, point()?, point()?, point()?, point()?, point()?,
+        ];
+        let mut evaluations = evaluations.chunks_exact(32).enumerate();
+        let mut evaluation = || {
+            let (index, bytes) = evaluations.next().unwrap_or_default();
+            canonical_scalar(bytes).ok_or_else(|| invalid(9 * G1_LEN + index * 32))
+        };
+        let [eval_a, eval_b, eval_c, eval_s1, eval_s2, eval_zw] = [
+            evaluation()?, evaluation()?, evaluation()?, evaluation()?, evaluation()?, evaluation()?,
+        ];
+        Ok(PlonkProof {
+            a, b, c, z, t1, t2, t3, wxi, wxiw,
+            eval_a, eval_b, eval_c, eval_s1, eval_s2, eval_zw,
+        })
+    }
+}
+
+/// A PLONK verifying key borrowed from `vk_bytes`
+struct PlonkVerifyingKey<'a> {
+    /// `[qm, ql, qr, qo, qc, s1, s2, s3]`
+    commitments: [&'a [u8]; VK_COMMITMENTS],
+    x_2: &'a [u8],
+    k1: Fr,
+    k2: Fr,
+    omega: Fr,
+    /// The evaluation domain has `2^power` elements
+    power: u8,
+}
+
+impl<'a> PlonkVerifyingKey<'a> {
+    fn parse(vk_bytes: &'a [u8]) -> Result<Self> {
+        let invalid = || error!(HealthcareError::VerifyingKeyDeserializeFailed);
+        if vk_bytes.len() != PLONK_VK_LEN {
+            return Err(invalid());
+        }
+        let (commitments, rest) = vk_bytes.split_at(VK_COMMITMENTS * G1_LEN);
+        let (x_2, rest) = rest.split_at(G2_LEN);
+        let mut points = commitments.chunks_exact(G1_LEN);
+        let commitments = [(); VK_COMMITMENTS].map(|_| points.next().unwrap_or_default());
+        let mut scalars = rest.chunks_exact(32).map(canonical_scalar);
+        let mut scalar = || scalars.next().flatten().ok_or_else(invalid);
+        let (k1, k2, omega) = (scalar()?, scalar()?, scalar()?);
+        let power = rest[3 * 32];
+        if power == 0 || power > MAX_POWER {
+            return Err(invalid());
+        }
+        Ok(PlonkVerifyingKey {
+            commitments,
+            x_2,
+            k1,
+            k2,
+            omega,
+            power,
+        })
+    }
+
+    fn domain_size(&self) -> u64 {
+        1 << self.power
+    }
+}
+
+/// The Fiat-Shamir transcript of snarkjs: each challenge is the keccak of what
+/// was absorbed since the previous one, read as a big-endian integer mod r
+#[derive(Default)]
+struct Transcript(Vec<u8>);
+
+impl Transcript {
+    fn absorb(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn absorb_scalar(&mut self, scalar: &Fr) {
+        self.0.extend_from_slice(&fr_to_be(scalar));
+    }
+
+    fn challenge(&mut self) -> Fr {
+        let challenge = Fr::from_be_bytes_mod_order(&keccak::hash(&self.0).to_bytes());
+        self.0.clear();
+        challenge
+    }
+}
+
+/// Rebuild the challenges, fold every commitment into one opening at `xi` and one
+/// at `xi * omega`, and check both with a single pairing:
+/// `e(-(Wxi + u Wxiw), [tau]_2) * e(xi Wxi + u xi omega Wxiw + F - E, [1]_2) == 1`
+fn verify_plonk(vk: &PlonkVerifyingKey, proof: &PlonkProof, public_inputs: &[[u8; 32]]) -> Result<bool> {
+    let mut transcript = Transcript::default();
+    vk.commitments.iter().for_each(|point| transcript.absorb(point));
+    public_inputs.iter().for_each(|input| transcript.absorb(input));
+    [&proof.a, &proof.b, &proof.c].into_iter().for_each(|point| transcript.absorb(point));
+    let beta = transcript.challenge();
+    transcript.absorb_scalar(&beta);
+    let gamma = transcript.challenge();
+    transcript.absorb_scalar(&beta);
+    transcript.absorb_scalar(&gamma);
+    transcript.absorb(&proof.z);
+    let alpha = transcript.challenge();
+    transcript.absorb_scalar(&alpha);
+    [&proof.t1, &proof.t2, &proof.t3].into_iter().for_each(|point| transcript.absorb(point));
+    let xi = transcript.challenge();
+    transcript.absorb_scalar(&xi);
+    for evaluation in [proof.eval_a, proof.eval_b, proof.eval_c, proof.eval_s1, proof.eval_s2, proof.eval_zw] {
+        transcript.absorb_scalar(&evaluation);
+    }
+    let v1 = transcript.challenge();
+    transcript.absorb(&proof.wxi);
+    transcript.absorb(&proof.wxiw);
+    let u = transcript.challenge();
+    let [v2, v3, v4, v5] = [v1.square(), v1.square() * v1, v1.square().square(), v1.square().square() * v1];
+
+    // L_i(xi) = omega^i (xi^n - 1) / (n (xi - omega^i)) for the public input rows
+    let n = vk.domain_size();
+    let xin = xi.pow([n]);
+    let zh = xin - Fr::one();
+    let mut lagrange = Vec::with_capacity(public_inputs.len().max(1));
+    let mut omega_i = Fr::one();
+    for _ in 0..public_inputs.len().max(1) {
+        // xi landing in the domain happens with negligible probability; refuse it
+        let Some(denominator) = (Fr::from(n) * (xi - omega_i)).inverse() else {
+            return Ok(false);
+        };
+        lagrange.push(omega_i * zh * denominator);
+        omega_i *= vk.omega;
+    }
+    let pi = public_inputs
+        .iter()
+        .zip(&lagrange)
+        .fold(Fr::from(0u64), |pi, (input, l)| pi - Fr::from_be_bytes_mod_order(input) * l);
+
+    let (e_a, e_b, e_c) = (proof.eval_a, proof.eval_b, proof.eval_c);
+    let alpha2 = alpha.square();
+    let l1_alpha2 = lagrange[0] * alpha2;
+    let sigma_a = e_a + beta * proof.eval_s1 + gamma;
+    let sigma_b = e_b + beta * proof.eval_s2 + gamma;
+    // Constant term of the linearization polynomial
+    let r0 = pi - l1_alpha2 - alpha * sigma_a * sigma_b * (e_c + gamma) * proof.eval_zw;
+    let beta_xi = beta * xi;
+    let z_coeff = alpha * (e_a + beta_xi + gamma) * (e_b + beta_xi * vk.k1 + gamma) * (e_c + beta_xi * vk.k2 + gamma)
+        + l1_alpha2
+        + u;
+    let s3_coeff = alpha * beta * sigma_a * sigma_b * proof.eval_zw;
+    let e = -r0 + v1 * e_a + v2 * e_b + v3 * e_c + v4 * proof.eval_s1 + v5 * proof.eval_s2 + u * proof.eval_zw;
+
+    let [qm, ql, qr, qo, qc, s1, s2, s3] = vk.commitments;
+    let b1 = msm(&[
+        (qm, e_a * e_b),
+        (ql, e_a),
+        (qr, e_b),
+        (qo, e_c),
+        (qc, Fr::one()),
+        (&proof.z, z_coeff),
+        (s3, -s3_coeff),
+        (&proof.t1, -zh),
+        (&proof.t2, -zh * xin),
+        (&proof.t3, -zh * xin.square()),
+        (&proof.a, v1),
+        (&proof.b, v2),
+        (&proof.c, v3),
+        (s1, v4),
+        (s2, v5),
+        (&G1_GENERATOR, -e),
+        (&proof.wxi, xi),
+        (&proof.wxiw, u * xi * vk.omega),
+    ])?;
+    let a1 = msm(&[(&proof.wxi, Fr::one()), (&proof.wxiw, u)])?;
+
+    let mut pairing_input = Vec::with_capacity(2 * (G1_LEN + G2_LEN));
+    pairing_input.extend_from_slice(&negate_g1(&a1));
+    pairing_input.extend_from_slice(vk.x_2);
+    pairing_input.extend_from_slice(&b1);
+    pairing_input.extend_from_slice(&G2_GENERATOR);
+    let result = alt_bn128_pairing(&pairing_input).map_err(|_| HealthcareError::InvalidProofPoint)?;
+    Ok(result.last() == Some(&1))
+}
+
+/// `sum(scalar_i * point_i)` with the group syscalls
+fn msm(terms: &[(&[u8], Fr)]) -> Result<[u8; 64]> {
+    let invalid = || error!(HealthcareError::InvalidProofPoint);
+    let mut sum = [0u8; G1_LEN];
+    for (point, scalar) in terms {
+        let product = if scalar.is_one() {
+            g1_add(point, &[0u8; G1_LEN])
+        } else {
+            g1_mul(point, &fr_to_be(scalar))
+        };
+        sum = g1_add(&sum, &product.ok_or_else(invalid)?).ok_or_else(invalid)?;
+    }
+    Ok(sum)
+}
+
+fn canonical_scalar(be_bytes: &[u8]) -> Option<Fr> {
+    (be_bytes < &FR_MODULUS_BE[..]).then(|| Fr::from_be_bytes_mod_order(be_bytes))
+}
+
+fn fr_to_be(scalar: &Fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&scalar.into_bigint().to_bytes_be());
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::offchain::{g1_syscall_bytes, g2_syscall_bytes};
+    use ark_bn254::{G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+
+    #[test]
+    fn test_generators_match_arkworks() {
+        assert_eq!(G1_GENERATOR, g1_syscall_bytes(&G1Affine::generator()));
+        assert_eq!(G2_GENERATOR, g2_syscall_bytes(&G2Affine::generator()));
+    }
+
+    #[test]
+    fn test_decode_requires_canonical_evaluations() {
+        let mut bytes = [0u8; PLONK_PROOF_LEN];
+        for point in bytes[..9 * G1_LEN].chunks_exact_mut(G1_LEN) {
+            point.copy_from_slice(&G1_GENERATOR);
+        }
+        let proof = PlonkProof::decode(&bytes, ProofFormat::SnarkJs).unwrap();
+        assert_eq!(proof.eval_zw, Fr::from(0u64));
+
+        let last = PLONK_PROOF_LEN - 32;
+        bytes[last..].copy_from_slice(&FR_MODULUS_BE);
+        let err = PlonkProof::decode(&bytes, ProofFormat::SnarkJs).unwrap_err();
+        assert_eq!(err, HealthcareError::InvalidProofEncoding.into());
+        let err = PlonkProof::decode(&bytes, ProofFormat::Uncompressed).unwrap_err();
+        assert_eq!(err, HealthcareError::UnsupportedProofFormat.into());
+    }
+}
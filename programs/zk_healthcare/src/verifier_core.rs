@@ -0,0 +1,567 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! The Groth16 checks shared by the program and `client::preflight_verify`:
+//! decoding proofs and keys, parsing public inputs, and the pairing itself. Nothing
+//! here logs or reads accounts, so the same code runs on-chain and off; the program
+//! logs a `VerifyError` when it turns it into a `HealthcareError`.
+
+use crate::{
+    Groth16Proof, HealthcareError, ProofFormat, FQ_MODULUS_BE, FR_MODULUS_BE, G1_COMPRESSED_LEN, G1_LEN,
+    G2_COMPRESSED_LEN, G2_LEN, SW_FLAGS_MASK, SW_INFINITY_FLAG, SW_NEGATIVE_FLAG, VK_IC_OFFSET,
+};
+use anchor_lang::prelude::msg;
+use anchor_lang::solana_program::alt_bn128::compression::prelude::{
+    alt_bn128_g1_compress, alt_bn128_g1_decompress, alt_bn128_g2_compress, alt_bn128_g2_decompress,
+};
+use anchor_lang::solana_program::alt_bn128::prelude::{
+    alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing,
+};
+use std::fmt;
+
+/// Why a submission fails verification, with the byte offset or index of the
+/// first bad element where there is one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    InvalidProofLength { expected: usize, actual: usize },
+    InvalidProofEncoding { offset: usize },
+    NonCanonicalProofEncoding,
+    InvalidProofPoint { point: char },
+    InvalidPublicInputEncoding { len: usize },
+    PublicInputNotInField { index: usize },
+    PublicInputCountMismatch { expected: usize, actual: usize },
+    VerifyingKeyDeserializeFailed { offset: usize },
+    PairingCheckFailed,
+}
+
+impl VerifyError {
+    /// The error the program fails with
+    pub fn code(self) -> HealthcareError {
+        match self {
+            VerifyError::InvalidProofLength { .. } => HealthcareError::InvalidProofLength,
+            VerifyError::InvalidProofEncoding { .. } => HealthcareError::InvalidProofEncoding,
+            VerifyError::NonCanonicalProofEncoding => HealthcareError::NonCanonicalProofEncoding,
+            VerifyError::InvalidProofPoint { .. } => HealthcareError::InvalidProofPoint,
+            VerifyError::InvalidPublicInputEncoding { .. } => HealthcareError::InvalidPublicInputEncoding,
+            VerifyError::PublicInputNotInField { .. } => HealthcareError::PublicInputNotInField,
+            VerifyError::PublicInputCountMismatch { .. } => HealthcareError::PublicInputCountMismatch,
+            VerifyError::VerifyingKeyDeserializeFailed { .. } => HealthcareError::VerifyingKeyDeserializeFailed,
+            VerifyError::PairingCheckFailed => HealthcareError::PairingCheckFailed,
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::InvalidProofLength { expected, actual } => {
+                write!(f, "Proof is {} bytes, expected {}", actual, expected)
+            }
+            VerifyError::InvalidProofEncoding { offset } => {
+                write!(f, "Proof point at byte {} is not a valid encoding", offset)
+            }
+            VerifyError::NonCanonicalProofEncoding => write!(f, "Proof is not in its canonical encoding"),
+            VerifyError::InvalidProofPoint { point } => {
+                write!(f, "Proof point {} is not a valid group element", point)
+            }
+            VerifyError::InvalidPublicInputEncoding { len } => {
+                write!(f, "Public inputs are {} bytes, not a non-empty multiple of 32", len)
+            }
+            VerifyError::PublicInputNotInField { index } => {
+                write!(f, "Public input {} is not a canonical field element", index)
+            }
+            VerifyError::PublicInputCountMismatch { expected, actual } => {
+                write!(f, "Expected {} public inputs, got {}", expected, actual)
+            }
+            VerifyError::VerifyingKeyDeserializeFailed { offset } => {
+                write!(f, "Verifying key is malformed at byte {}", offset)
+            }
+            VerifyError::PairingCheckFailed => write!(f, "Proof failed the pairing check"),
+        }
+    }
+}
+
+impl From<VerifyError> for anchor_lang::error::Error {
+    fn from(err: VerifyError) -> Self {
+        msg!("{}", err);
+        err.code().into()
+    }
+}
+
+pub type VerifyResult<T> = std::result::Result<T, VerifyError>;
+
+/// Decode a proof submitted in any `ProofFormat`. Compressed points are expanded
+/// with the alt_bn128 decompression syscalls.
+pub fn decode_proof(bytes: &[u8], format: ProofFormat) -> VerifyResult<Groth16Proof> {
+    if bytes.len() != format.proof_len() {
+        return Err(VerifyError::InvalidProofLength { expected: format.proof_len(), actual: bytes.len() });
+    }
+    let proof = decode_points(bytes, format).map_err(|offset| VerifyError::InvalidProofEncoding { offset })?;
+    // Decoding masks flag bits and the syscall encoding has a single form per
+    // point, so re-encoding catches every alternative spelling of the proof
+    if encode_proof(&proof, format).as_deref() != Some(bytes) {
+        return Err(VerifyError::NonCanonicalProofEncoding);
+    }
+    Ok(proof)
+}
+
+/// The points of a proof of the right length, or the byte offset of the first
+/// invalid one
+pub(crate) fn decode_points(bytes: &[u8], format: ProofFormat) -> std::result::Result<Groth16Proof, usize> {
+    Ok(match format {
+        ProofFormat::Uncompressed => {
+            let (a, rest) = bytes.split_at(G1_LEN);
+            let (b, c) = rest.split_at(G2_LEN);
+            Groth16Proof {
+                a: g1_to_syscall(a).ok_or(0usize)?,
+                b: g2_to_syscall(b).ok_or(G1_LEN)?,
+                c: g1_to_syscall(c).ok_or(G1_LEN + G2_LEN)?,
+            }
+        }
+        ProofFormat::Compressed => {
+            let (a, rest) = bytes.split_at(G1_COMPRESSED_LEN);
+            let (b, c) = rest.split_at(G2_COMPRESSED_LEN);
+            Groth16Proof {
+                a: g1_decompress(a).ok_or(0usize)?,
+                b: g2_decompress(b).ok_or(G1_COMPRESSED_LEN)?,
+                c: g1_decompress(c).ok_or(G1_COMPRESSED_LEN + G2_COMPRESSED_LEN)?,
+            }
+        }
+        // snarkjs already matches the syscall encoding (the endianness flip and
+        // c0/c1 swap relative to arkworks), so only canonicity is checked
+        ProofFormat::SnarkJs => {
+            let (a, rest) = bytes.split_at(G1_LEN);
+            let (b, c) = rest.split_at(G2_LEN);
+            Groth16Proof {
+                a: canonical_point(a).ok_or(0usize)?,
+                b: canonical_point(b).ok_or(G1_LEN)?,
+                c: canonical_point(c).ok_or(G1_LEN + G2_LEN)?,
+            }
+        }
+    })
+}
+
+/// The canonical serialization of `proof` in `format`, byte-for-byte what arkworks
+/// (or snarkjs) would produce for the same points. `None` if a point can't be
+/// compressed because it is off the curve.
+pub fn encode_proof(proof: &Groth16Proof, format: ProofFormat) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(format.proof_len());
+    match format {
+        ProofFormat::Uncompressed => {
+            bytes.extend_from_slice(&g1_to_ark(&proof.a));
+            bytes.extend_from_slice(&g2_to_ark(&proof.b));
+            bytes.extend_from_slice(&g1_to_ark(&proof.c));
+        }
+        ProofFormat::Compressed => {
+            bytes.extend_from_slice(&g1_compress(&proof.a)?);
+            bytes.extend_from_slice(&g2_compress(&proof.b)?);
+            bytes.extend_from_slice(&g1_compress(&proof.c)?);
+        }
+        ProofFormat::SnarkJs => {
+            bytes.extend_from_slice(&proof.a);
+            bytes.extend_from_slice(&proof.b);
+            bytes.extend_from_slice(&proof.c);
+        }
+    }
+    Some(bytes)
+}
+
+/// Reject points that would let a forged proof through or waste a full pairing:
+/// none of A, B, C may be the identity, A and C must be on the curve (G1 has
+/// cofactor 1), and B must lie in the prime-order subgroup of G2. The single
+/// pairing `e(0, B)` is used for the latter because the syscall validates
+/// subgroup membership of its G2 operands.
+pub fn check_proof_points(proof: &Groth16Proof) -> VerifyResult<()> {
+    for (point, bytes) in [('A', &proof.a), ('C', &proof.c)] {
+        let mut add_input = [0u8; 2 * G1_LEN];
+        add_input[..G1_LEN].copy_from_slice(bytes);
+        if is_identity(bytes) || alt_bn128_addition(&add_input).is_err() {
+            return Err(VerifyError::InvalidProofPoint { point });
+        }
+    }
+
+    let mut pairing_input = [0u8; G1_LEN + G2_LEN];
+    pairing_input[G1_LEN..].copy_from_slice(&proof.b);
+    if is_identity(&proof.b) || alt_bn128_pairing(&pairing_input).is_err() {
+        return Err(VerifyError::InvalidProofPoint { point: 'B' });
+    }
+    Ok(())
+}
+
+/// Re-encode an arkworks uncompressed verifying key for the alt_bn128 syscalls:
+/// alpha (64) | beta (128) | gamma (128) | delta (128) | gamma_abc (64 each)
+pub fn prepare_vk_bytes(vk_bytes: &[u8]) -> VerifyResult<Vec<u8>> {
+    let invalid = |offset: usize| VerifyError::VerifyingKeyDeserializeFailed { offset };
+    if vk_bytes.len() < VK_IC_OFFSET + 8 {
+        return Err(invalid(vk_bytes.len()));
+    }
+
+    let mut ic_len = [0u8; 8];
+    ic_len.copy_from_slice(&vk_bytes[VK_IC_OFFSET..VK_IC_OFFSET + 8]);
+    let ic_len = u64::from_le_bytes(ic_len) as usize;
+    let ic_bytes = &vk_bytes[VK_IC_OFFSET + 8..];
+    if ic_len == 0 || ic_bytes.len() != ic_len.saturating_mul(G1_LEN) {
+        return Err(invalid(VK_IC_OFFSET));
+    }
+
+    let mut prepared = Vec::with_capacity(vk_bytes.len() - 8);
+    prepared.extend_from_slice(&g1_to_syscall(&vk_bytes[..G1_LEN]).ok_or_else(|| invalid(0))?);
+    for (index, g2) in vk_bytes[G1_LEN..VK_IC_OFFSET].chunks_exact(G2_LEN).enumerate() {
+        prepared.extend_from_slice(&g2_to_syscall(g2).ok_or_else(|| invalid(G1_LEN + index * G2_LEN))?);
+    }
+    for (index, g1) in ic_bytes.chunks_exact(G1_LEN).enumerate() {
+        prepared.extend_from_slice(&g1_to_syscall(g1).ok_or_else(|| invalid(ic_offset(index)))?);
+    }
+    Ok(prepared)
+}
+
+/// Reject verifying keys whose points are malformed or off the curve. The group
+/// syscalls validate their operands, so each point is pushed through one.
+/// Offsets are reported in the submitted (arkworks) layout.
+pub fn check_verifying_key(vk_bytes: &[u8]) -> VerifyResult<()> {
+    let prepared = prepare_vk_bytes(vk_bytes)?;
+    let invalid = |offset: usize| VerifyError::VerifyingKeyDeserializeFailed { offset };
+
+    let (alpha, rest) = prepared.split_at(G1_LEN);
+    let (g2_points, ic) = rest.split_at(3 * G2_LEN);
+    alt_bn128_addition(alpha).map_err(|_| invalid(0))?;
+    for (index, g1) in ic.chunks_exact(G1_LEN).enumerate() {
+        alt_bn128_addition(g1).map_err(|_| invalid(ic_offset(index)))?;
+    }
+    for (index, g2) in g2_points.chunks_exact(G2_LEN).enumerate() {
+        let mut pairing_input = [0u8; G1_LEN + G2_LEN];
+        pairing_input[G1_LEN..].copy_from_slice(g2);
+        alt_bn128_pairing(&pairing_input).map_err(|_| invalid(G1_LEN + index * G2_LEN))?;
+    }
+    Ok(())
+}
+
+/// Byte offset of `IC[index]` in an arkworks uncompressed verifying key
+fn ic_offset(index: usize) -> usize {
+    VK_IC_OFFSET + 8 + index * G1_LEN
+}
+
+/// Public inputs taken by a prepared verifying key
+pub fn prepared_n_public(prepared: &[u8]) -> usize {
+    let [_, _, _, _, ic] = split_prepared_vk(prepared);
+    (ic.len() / G1_LEN).saturating_sub(1)
+}
+
+/// Public inputs must be whole 32-byte elements, exactly `n_public` of them
+pub fn check_input_count(n_public: usize, public_inputs: &[u8]) -> VerifyResult<()> {
+    if !public_inputs.chunks_exact(32).remainder().is_empty() {
+        return Err(VerifyError::InvalidPublicInputEncoding { len: public_inputs.len() });
+    }
+    if public_inputs.len() != n_public * 32 {
+        return Err(VerifyError::PublicInputCountMismatch { expected: n_public, actual: public_inputs.len() / 32 });
+    }
+    Ok(())
+}
+
+/// Parse the public input buffer into 32-byte scalars (little-endian, or big-endian
+/// for `ProofFormat::SnarkJs`), returned big-endian for the multiplication syscall.
+///
+/// Every element must already be a canonical Bn254 scalar (strictly less than the
+/// field modulus). Values are never reduced: if `x` and `x + r` were both accepted,
+/// two byte strings would prove the same statement yet hash to different records.
+pub fn parse_public_inputs(bytes: &[u8], format: ProofFormat) -> VerifyResult<Vec<[u8; 32]>> {
+    parse_scalars(bytes, format, &FR_MODULUS_BE)
+}
+
+/// `parse_public_inputs` against the big-endian scalar field `modulus` of
+/// another curve
+pub fn parse_scalars(bytes: &[u8], format: ProofFormat, modulus: &[u8; 32]) -> VerifyResult<Vec<[u8; 32]>> {
+    if bytes.is_empty() || !bytes.chunks_exact(32).remainder().is_empty() {
+        return Err(VerifyError::InvalidPublicInputEncoding { len: bytes.len() });
+    }
+    // Sized up front: the program's bump allocator never reclaims a grown Vec's
+    // old buffers
+    let mut scalars = Vec::with_capacity(bytes.len() / 32);
+    for (index, chunk) in bytes.chunks_exact(32).enumerate() {
+        let scalar = scalar_to_be(chunk, format);
+        if scalar >= *modulus {
+            return Err(VerifyError::PublicInputNotInField { index });
+        }
+        scalars.push(scalar);
+    }
+    Ok(scalars)
+}
+
+/// Everything `Groth16Verifier::verify` checks of a submission, in the same order:
+/// the proof encoding, the input count and encoding, the points and the pairing.
+/// Returns the decoded proof, its parsed inputs (for hashing without parsing them
+/// again) and whether the pairing held.
+pub fn verify_submission(
+    prepared: &[u8],
+    n_public: usize,
+    proof: &[u8],
+    format: ProofFormat,
+    public_inputs: &[u8],
+) -> VerifyResult<(Groth16Proof, Vec<[u8; 32]>, bool)> {
+    let proof = decode_proof(proof, format)?;
+    check_input_count(n_public, public_inputs)?;
+    let public_inputs = parse_public_inputs(public_inputs, format)?;
+    let verified = verify_prepared(prepared, &proof, &public_inputs)?;
+    Ok((proof, public_inputs, verified))
+}
+
+/// Check a decoded proof against a prepared key: the points, the input count the
+/// key expects, then the single pairing check
+/// `e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1`
+pub fn verify_prepared(prepared: &[u8], proof: &Groth16Proof, public_inputs: &[[u8; 32]]) -> VerifyResult<bool> {
+    check_proof_points(proof)?;
+    let [_, _, _, _, ic] = split_prepared_vk(prepared);
+    check_ic_len(ic, public_inputs.len())?;
+
+    let vk_x = compute_vk_x(ic, public_inputs)?;
+    pairing_check(prepared, proof, &vk_x)
+}
+
+/// The key carries one `IC` point per public input plus the constant term
+pub(crate) fn check_ic_len(ic: &[u8], n_inputs: usize) -> VerifyResult<()> {
+    if ic.len() != (n_inputs + 1) * G1_LEN {
+        let expected = (ic.len() / G1_LEN).saturating_sub(1);
+        return Err(VerifyError::PublicInputCountMismatch { expected, actual: n_inputs });
+    }
+    Ok(())
+}
+
+/// `e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1`
+pub(crate) fn pairing_check(prepared: &[u8], proof: &Groth16Proof, vk_x: &[u8; 64]) -> VerifyResult<bool> {
+    let [alpha, beta, gamma, delta, _] = split_prepared_vk(prepared);
+    let mut pairing_input = Vec::with_capacity(4 * (G1_LEN + G2_LEN));
+    pairing_input.extend_from_slice(&negate_g1(&proof.a));
+    pairing_input.extend_from_slice(&proof.b);
+    pairing_input.extend_from_slice(alpha);
+    pairing_input.extend_from_slice(beta);
+    pairing_input.extend_from_slice(vk_x);
+    pairing_input.extend_from_slice(gamma);
+    pairing_input.extend_from_slice(&proof.c);
+    pairing_input.extend_from_slice(delta);
+
+    let result = alt_bn128_pairing(&pairing_input).map_err(|_| VerifyError::PairingCheckFailed)?;
+    Ok(result.last() == Some(&1))
+}
+
+/// `vk_x = IC[0] + sum(input_i * IC[i + 1])` via the alt_bn128 group syscalls
+pub(crate) fn compute_vk_x(ic: &[u8], public_inputs: &[[u8; 32]]) -> VerifyResult<[u8; 64]> {
+    let mut vk_x = [0u8; G1_LEN];
+    vk_x.copy_from_slice(&ic[..G1_LEN]);
+    accumulate_vk_x(vk_x, &ic[G1_LEN..], public_inputs, 0)
+}
+
+/// `vk_x + sum(input_i * point_i)`, pairing each input with its `IC` point.
+/// `first` is the index of the first input, for error offsets.
+pub(crate) fn accumulate_vk_x(
+    mut vk_x: [u8; 64],
+    points: &[u8],
+    public_inputs: &[[u8; 32]],
+    first: usize,
+) -> VerifyResult<[u8; 64]> {
+    for (index, (input, point)) in public_inputs.iter().zip(points.chunks_exact(G1_LEN)).enumerate() {
+        let invalid = || VerifyError::VerifyingKeyDeserializeFailed { offset: ic_offset(first + index + 1) };
+        let product = g1_mul(point, input).ok_or_else(invalid)?;
+        vk_x = g1_add(&vk_x, &product).ok_or_else(invalid)?;
+    }
+    Ok(vk_x)
+}
+
+/// `[alpha, beta, gamma, delta, gamma_abc]` of a prepared verifying key
+pub(crate) fn split_prepared_vk(prepared: &[u8]) -> [&[u8]; 5] {
+    let (alpha, rest) = prepared.split_at(G1_LEN);
+    let (beta, rest) = rest.split_at(G2_LEN);
+    let (gamma, rest) = rest.split_at(G2_LEN);
+    let (delta, ic) = rest.split_at(G2_LEN);
+    [alpha, beta, gamma, delta, ic]
+}
+
+/// `scalar * point` with the multiplication syscall, `None` if the point is invalid
+pub(crate) fn g1_mul(point: &[u8], scalar: &[u8; 32]) -> Option<[u8; 64]> {
+    let mut mul_input = [0u8; G1_LEN + 32];
+    mul_input[..G1_LEN].copy_from_slice(point);
+    mul_input[G1_LEN..].copy_from_slice(scalar);
+    alt_bn128_multiplication(&mul_input).ok()?.try_into().ok()
+}
+
+/// `a + b` with the addition syscall, `None` if either point is invalid
+pub(crate) fn g1_add(a: &[u8], b: &[u8]) -> Option<[u8; 64]> {
+    let mut add_input = [0u8; 2 * G1_LEN];
+    add_input[..G1_LEN].copy_from_slice(a);
+    add_input[G1_LEN..].copy_from_slice(b);
+    alt_bn128_addition(&add_input).ok()?.try_into().ok()
+}
+
+/// Convert an arkworks uncompressed G1 point (little-endian coordinates, flags in
+/// the top bits of y) to the big-endian syscall encoding
+pub(crate) fn g1_to_syscall(ark_bytes: &[u8]) -> Option<[u8; 64]> {
+    let mut point = [0u8; G1_LEN];
+    if ark_bytes[G1_LEN - 1] & SW_INFINITY_FLAG != 0 {
+        return Some(point);
+    }
+    for (coordinate, ark) in point.chunks_exact_mut(32).zip(ark_bytes.chunks_exact(32)) {
+        for (out, byte) in coordinate.iter_mut().zip(ark.iter().rev()) {
+            *out = *byte;
+        }
+    }
+    point[32] &= !SW_FLAGS_MASK;
+    point.chunks_exact(32).all(is_canonical_fq).then_some(point)
+}
+
+/// Convert an arkworks uncompressed G2 point (c0 before c1) to the syscall
+/// encoding, which is big-endian with c1 before c0 in each coordinate
+fn g2_to_syscall(ark_bytes: &[u8]) -> Option<[u8; 128]> {
+    let mut point = [0u8; G2_LEN];
+    if ark_bytes[G2_LEN - 1] & SW_INFINITY_FLAG != 0 {
+        return Some(point);
+    }
+    for (coordinate, ark) in point.chunks_exact_mut(64).zip(ark_bytes.chunks_exact(64)) {
+        for (out, byte) in coordinate.iter_mut().zip(ark.iter().rev()) {
+            *out = *byte;
+        }
+    }
+    point[64] &= !SW_FLAGS_MASK;
+    point.chunks_exact(32).all(is_canonical_fq).then_some(point)
+}
+
+/// Inverse of `g1_to_syscall`, including the y-sign flag arkworks always sets
+fn g1_to_ark(point: &[u8; 64]) -> [u8; 64] {
+    let mut ark_bytes = [0u8; G1_LEN];
+    if is_identity(point) {
+        ark_bytes[G1_LEN - 1] = SW_INFINITY_FLAG;
+        return ark_bytes;
+    }
+    for (ark, coordinate) in ark_bytes.chunks_exact_mut(32).zip(point.chunks_exact(32)) {
+        for (out, byte) in ark.iter_mut().zip(coordinate.iter().rev()) {
+            *out = *byte;
+        }
+    }
+    let y = &point[32..];
+    if y > &fq_neg(y)[..] {
+        ark_bytes[G1_LEN - 1] |= SW_NEGATIVE_FLAG;
+    }
+    ark_bytes
+}
+
+/// Inverse of `g2_to_syscall`. arkworks orders Fq2 elements by c1, then c0.
+fn g2_to_ark(point: &[u8; 128]) -> [u8; 128] {
+    let mut ark_bytes = [0u8; G2_LEN];
+    if is_identity(point) {
+        ark_bytes[G2_LEN - 1] = SW_INFINITY_FLAG;
+        return ark_bytes;
+    }
+    for (ark, coordinate) in ark_bytes.chunks_exact_mut(64).zip(point.chunks_exact(64)) {
+        for (out, byte) in ark.iter_mut().zip(coordinate.iter().rev()) {
+            *out = *byte;
+        }
+    }
+    let (y_c1, y_c0) = point[64..].split_at(32);
+    if (y_c1, y_c0) > (&fq_neg(y_c1)[..], &fq_neg(y_c0)[..]) {
+        ark_bytes[G2_LEN - 1] |= SW_NEGATIVE_FLAG;
+    }
+    ark_bytes
+}
+
+/// Expand an arkworks compressed G1 point (little-endian x, flags in the top bits
+/// of its last byte) to the syscall encoding
+fn g1_decompress(ark_bytes: &[u8]) -> Option<[u8; 64]> {
+    if ark_bytes[G1_COMPRESSED_LEN - 1] & SW_INFINITY_FLAG != 0 {
+        return Some([0u8; G1_LEN]);
+    }
+    let mut compressed = [0u8; G1_COMPRESSED_LEN];
+    for (out, byte) in compressed.iter_mut().zip(ark_bytes.iter().rev()) {
+        *out = *byte;
+    }
+    alt_bn128_g1_decompress(&compressed).ok()
+}
+
+/// Expand an arkworks compressed G2 point (x.c0 | x.c1, little-endian, flags in
+/// the top bits of the last byte) to the syscall encoding
+fn g2_decompress(ark_bytes: &[u8]) -> Option<[u8; 128]> {
+    if ark_bytes[G2_COMPRESSED_LEN - 1] & SW_INFINITY_FLAG != 0 {
+        return Some([0u8; G2_LEN]);
+    }
+    let mut compressed = [0u8; G2_COMPRESSED_LEN];
+    for (out, byte) in compressed.iter_mut().zip(ark_bytes.iter().rev()) {
+        *out = *byte;
+    }
+    alt_bn128_g2_decompress(&compressed).ok()
+}
+
+/// Inverse of `g1_decompress`
+fn g1_compress(point: &[u8; 64]) -> Option<[u8; 32]> {
+    let mut ark_bytes = [0u8; G1_COMPRESSED_LEN];
+    if is_identity(point) {
+        ark_bytes[G1_COMPRESSED_LEN - 1] = SW_INFINITY_FLAG;
+        return Some(ark_bytes);
+    }
+    let compressed = alt_bn128_g1_compress(point).ok()?;
+    for (out, byte) in ark_bytes.iter_mut().zip(compressed.iter().rev()) {
+        *out = *byte;
+    }
+    Some(ark_bytes)
+}
+
+/// Inverse of `g2_decompress`
+fn g2_compress(point: &[u8; 128]) -> Option<[u8; 64]> {
+    let mut ark_bytes = [0u8; G2_COMPRESSED_LEN];
+    if is_identity(point) {
+        ark_bytes[G2_COMPRESSED_LEN - 1] = SW_INFINITY_FLAG;
+        return Some(ark_bytes);
+    }
+    let compressed = alt_bn128_g2_compress(point).ok()?;
+    for (out, byte) in ark_bytes.iter_mut().zip(compressed.iter().rev()) {
+        *out = *byte;
+    }
+    Some(ark_bytes)
+}
+
+/// Copy a syscall-encoded point, rejecting coordinates outside the base field
+pub(crate) fn canonical_point<const N: usize>(be_bytes: &[u8]) -> Option<[u8; N]> {
+    if !be_bytes.chunks_exact(32).all(is_canonical_fq) {
+        return None;
+    }
+    be_bytes.try_into().ok()
+}
+
+pub(crate) fn is_identity(point: &[u8]) -> bool {
+    point.iter().all(|byte| *byte == 0)
+}
+
+fn is_canonical_fq(be_bytes: &[u8]) -> bool {
+    be_bytes < &FQ_MODULUS_BE[..]
+}
+
+/// Negate a syscall-encoded G1 point: (x, y) -> (x, p - y)
+pub(crate) fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut negated = *point;
+    if is_identity(point) {
+        return negated;
+    }
+    negated[32..].copy_from_slice(&fq_neg(&point[32..]));
+    negated
+}
+
+/// `p - x` for a canonical big-endian base field element, with `-0 = 0`
+fn fq_neg(be_bytes: &[u8]) -> [u8; 32] {
+    let mut negated = [0u8; 32];
+    if be_bytes.iter().all(|byte| *byte == 0) {
+        return negated;
+    }
+    let mut borrow = 0u8;
+    for i in (0..32).rev() {
+        let (diff, underflow_a) = FQ_MODULUS_BE[i].overflowing_sub(be_bytes[i]);
+        let (diff, underflow_b) = diff.overflowing_sub(borrow);
+        negated[i] = diff;
+        borrow = (underflow_a || underflow_b) as u8;
+    }
+    negated
+}
+
+/// A 32-byte public input in the byte order of `format`, as big-endian
+pub(crate) fn scalar_to_be(bytes: &[u8], format: ProofFormat) -> [u8; 32] {
+    let mut scalar = [0u8; 32];
+    scalar.copy_from_slice(bytes);
+    if !format.big_endian_inputs() {
+        scalar.reverse();
+    }
+    scalar
+}
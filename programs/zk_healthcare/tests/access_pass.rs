@@ -0,0 +1,140 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{AccessPass, HealthcareError, IpfsPinRecord, VerificationType};
+
+const CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with `CIRCUIT` approved for access control
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let ix = set_type_circuit_ix(authority, registry.pubkey(), CIRCUIT, VerificationType::AccessControl);
+    send(ctx, &[ix], &[]).await.unwrap();
+    registry
+}
+
+/// Issue the payer a pass to `resource` and return its address
+async fn issue(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+    resource: Pubkey,
+    valid_slots: u64,
+    uses: u16,
+) -> Pubkey {
+    let patient = ctx.payer.pubkey();
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, patient, fixture, resource, valid_slots, uses);
+    let ix = after_latest_record(ctx, ix, patient, VerificationType::AccessControl).await;
+    send(ctx, &[ix], &[]).await.unwrap();
+    AccessPass::address(&patient, &resource)
+}
+
+#[tokio::test]
+async fn test_pass_allows_each_of_its_uses() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let resource = Keypair::new();
+    let address = issue(&mut ctx, &registry, &fixture, resource.pubkey(), 100, 3).await;
+
+    let pass: AccessPass = fetch(&mut ctx, address).await;
+    assert_eq!((pass.patient, pass.resource), (ctx.payer.pubkey(), resource.pubkey()));
+    assert_eq!((pass.expires_at_slot, pass.uses_remaining), (pass.issued_at_slot + 100, 3));
+
+    for remaining in (0..3).rev() {
+        // Past the slot, so the repeated consumption isn't taken for the last one
+        warp_clock(&mut ctx, 0).await;
+        let ix = consume_access_pass_ix(registry.pubkey(), address, resource.pubkey(), resource.pubkey());
+        send(&mut ctx, &[ix], &[&resource]).await.unwrap();
+        let pass: AccessPass = fetch(&mut ctx, address).await;
+        assert_eq!(pass.uses_remaining, remaining);
+    }
+    warp_clock(&mut ctx, 0).await;
+    let ix = consume_access_pass_ix(registry.pubkey(), address, resource.pubkey(), resource.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&resource]).await, HealthcareError::AccessPassExhausted);
+}
+
+#[tokio::test]
+async fn test_pass_expires_by_slot() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let resource = Keypair::new();
+    let address = issue(&mut ctx, &registry, &fixture, resource.pubkey(), 10, 5).await;
+
+    let pass: AccessPass = fetch(&mut ctx, address).await;
+    ctx.warp_to_slot(pass.expires_at_slot).unwrap();
+    let ix = consume_access_pass_ix(registry.pubkey(), address, resource.pubkey(), resource.pubkey());
+    send(&mut ctx, &[ix], &[&resource]).await.unwrap();
+    ctx.warp_to_slot(pass.expires_at_slot + 1).unwrap();
+    let ix = consume_access_pass_ix(registry.pubkey(), address, resource.pubkey(), resource.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&resource]).await, HealthcareError::AccessPassExpired);
+}
+
+#[tokio::test]
+async fn test_pass_lifetime_capped_by_the_registry() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let (authority, patient, resource) = (ctx.payer.pubkey(), ctx.payer.pubkey(), Keypair::new().pubkey());
+    let ix = set_max_access_pass_slots_ix(authority, registry.pubkey(), 0);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidMaxAccessPassSlots);
+    send(&mut ctx, &[set_max_access_pass_slots_ix(authority, registry.pubkey(), 50)], &[]).await.unwrap();
+
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, patient, &fixture, resource, 51, 1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::AccessPassTooLong);
+    let address = issue(&mut ctx, &registry, &fixture, resource, 50, 1).await;
+    let pass: AccessPass = fetch(&mut ctx, address).await;
+    assert_eq!(pass.expires_at_slot, pass.issued_at_slot + 50);
+}
+
+#[tokio::test]
+async fn test_pass_refused_to_another_resource() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let (resource, other) = (Keypair::new(), Keypair::new());
+    let address = issue(&mut ctx, &registry, &fixture, resource.pubkey(), 100, 1).await;
+
+    // Another resource presenting the pass, in its own name or in this one's
+    let ix = consume_access_pass_ix(registry.pubkey(), address, other.pubkey(), other.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::AccessPassResourceMismatch);
+    let ix = consume_access_pass_ix(registry.pubkey(), address, resource.pubkey(), other.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::AccessPassResourceMismatch);
+
+    let pass: AccessPass = fetch(&mut ctx, address).await;
+    assert_eq!(pass.uses_remaining, 1);
+}
+
+#[tokio::test]
+async fn test_pass_records_access_to_a_pin_record() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let registry = setup(&mut ctx, &fixtures[0]).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, CID, [1; 32])], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient, &[1; 32]);
+    confirm_pin(&mut ctx, registry.pubkey(), pin_record).await;
+    issue(&mut ctx, &registry, &fixtures[0], pin_record, 100, 1).await;
+
+    send(&mut ctx, &[record_access_ix(registry.pubkey(), pin_record, patient, true)], &[]).await.unwrap();
+    let pinned: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pinned.access_count, 1);
+
+    // A fresh proof replaces the spent pass; the spent proof can't
+    warp_clock(&mut ctx, 0).await;
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, patient, &fixtures[0], pin_record, 100, 1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::ProofAlreadyUsed);
+    issue(&mut ctx, &registry, &fixtures[1], pin_record, 100, 1).await;
+    send(&mut ctx, &[record_access_ix(registry.pubkey(), pin_record, patient, true)], &[]).await.unwrap();
+    let pinned: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pinned.access_count, 2);
+}
@@ -0,0 +1,242 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::keccak;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{
+    CredentialNullifier, HealthcareError, HealthcareRegistry, ProofNullifier, VerificationRecord, VkConfig,
+};
+
+const CIRCUIT: &str = "anonymous_v1";
+const CREDENTIAL: u64 = 0x5eed_c0de;
+
+/// A fixture proving its own statement `statement` for `credential`, the
+/// nullifier the circuit publishes at input 0; every statement shares one key
+fn fixture(credential: u64, statement: u64) -> Fixture {
+    bound_square_fixture(1, vec![Fr::from(credential), Fr::from(statement)])
+}
+
+fn credential_bytes(credential: u64) -> [u8; 32] {
+    Fr::from(credential).into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+async fn setup(ctx: &mut ProgramTestContext, config: VkConfig) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk_with(ctx, registry.pubkey(), CIRCUIT, &fixture(CREDENTIAL, 0).vk_bytes, config).await;
+    registry
+}
+
+fn anonymous_config() -> VkConfig {
+    VkConfig {
+        nullifier_input: Some(0),
+        ..VkConfig::default()
+    }
+}
+
+/// A fresh keypair holding nothing but the lamports it is sent
+async fn funded(ctx: &mut ProgramTestContext, lamports: u64) -> Keypair {
+    let keypair = Keypair::new();
+    let ix = system_instruction::transfer(&ctx.payer.pubkey(), &keypair.pubkey(), lamports);
+    send(ctx, &[ix], &[]).await.unwrap();
+    keypair
+}
+
+async fn epoch(ctx: &mut ProgramTestContext) -> u64 {
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.epoch
+}
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    relayer: &Keypair,
+    fixture: &Fixture,
+    credential: [u8; 32],
+    epoch: u64,
+) -> (Keypair, Result<(), BanksClientError>) {
+    let verification = Keypair::new();
+    let ix = verify_eligibility_anonymous_ix(
+        registry.pubkey(),
+        verification.pubkey(),
+        CIRCUIT,
+        relayer.pubkey(),
+        fixture.proof.clone(),
+        fixture.public_inputs.clone(),
+        credential,
+        epoch,
+    );
+    let result = send(ctx, &[ix], &[&verification, relayer]).await;
+    (verification, result)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[tokio::test]
+async fn test_anonymous_record_holds_no_signer_key() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, anonymous_config()).await;
+    let relayer = funded(&mut ctx, 1_000_000_000).await;
+    let credential = credential_bytes(CREDENTIAL);
+    let epoch = epoch(&mut ctx).await;
+
+    let proven = fixture(CREDENTIAL, 1);
+    let (verification, result) = submit(&mut ctx, &registry, &relayer, &proven, credential, epoch).await;
+    result.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification.pubkey()).await;
+    assert!(record.is_valid);
+    assert_eq!(record.patient_pubkey, Pubkey::new_from_array(credential));
+    assert_eq!(record.proof_hash, verification_hash(&proven));
+    let data = ctx.banks_client.get_account(verification.pubkey()).await.unwrap().unwrap().data;
+    for signer in [relayer.pubkey(), verification.pubkey(), ctx.payer.pubkey()] {
+        assert!(!contains(&data, signer.as_ref()), "record holds {}", signer);
+    }
+
+    let spent: CredentialNullifier = fetch(&mut ctx, credential_nullifier_address(CIRCUIT, &credential, epoch)).await;
+    assert_eq!((spent.verification, spent.epoch), (verification.pubkey(), epoch));
+    let registry: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(registry.total_verifications, 1);
+}
+
+#[tokio::test]
+async fn test_nullifier_spent_once_per_epoch() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, anonymous_config()).await;
+    let relayer = funded(&mut ctx, 1_000_000_000).await;
+    let credential = credential_bytes(CREDENTIAL);
+    let epoch = epoch(&mut ctx).await;
+
+    let first = fixture(CREDENTIAL, 1);
+    submit(&mut ctx, &registry, &relayer, &first, credential, epoch).await.1.unwrap();
+
+    // The same proof is a replay, and a new proof for the same credential is too
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &first, credential, epoch).await;
+    assert_error(result, HealthcareError::ProofAlreadyUsed);
+    let second = fixture(CREDENTIAL, 2);
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &second, credential, epoch).await;
+    assert_error(result, HealthcareError::CredentialAlreadyUsed);
+
+    // Other credentials are unaffected, and the credential renews next epoch
+    let other = fixture(CREDENTIAL + 1, 2);
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &other, credential_bytes(CREDENTIAL + 1), epoch).await;
+    result.unwrap();
+    ctx.warp_to_epoch(epoch + 1).unwrap();
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &second, credential, epoch).await;
+    assert_error(result, HealthcareError::CredentialAlreadyUsed);
+    let (verification, result) = submit(&mut ctx, &registry, &relayer, &second, credential, epoch + 1).await;
+    result.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification.pubkey()).await;
+    assert_eq!(record.patient_pubkey, Pubkey::new_from_array(credential));
+}
+
+#[tokio::test]
+async fn test_cooldown_carries_over_the_epoch_boundary() {
+    const COOLDOWN_SECS: i64 = 60 * 60;
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, anonymous_config()).await;
+    let ix = set_verification_cooldown_ix(ctx.payer.pubkey(), registry.pubkey(), COOLDOWN_SECS);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let relayer = funded(&mut ctx, 1_000_000_000).await;
+    let credential = credential_bytes(CREDENTIAL);
+    let epoch = epoch(&mut ctx).await;
+    submit(&mut ctx, &registry, &relayer, &fixture(CREDENTIAL, 1), credential, epoch).await.1.unwrap();
+    let spent: CredentialNullifier = fetch(&mut ctx, credential_nullifier_address(CIRCUIT, &credential, epoch)).await;
+
+    // The next epoch's nullifier is fresh, but the last one's record is too recent
+    ctx.warp_to_epoch(epoch + 1).unwrap();
+    warp_clock_to(&mut ctx, spent.used_at + 60).await;
+    let second = fixture(CREDENTIAL, 2);
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &second, credential, epoch + 1).await;
+    assert_error(result, HealthcareError::VerificationCooldownActive);
+
+    warp_clock_to(&mut ctx, spent.used_at + COOLDOWN_SECS).await;
+    submit(&mut ctx, &registry, &relayer, &second, credential, epoch + 1).await.1.unwrap();
+}
+
+#[tokio::test]
+async fn test_relayer_pays_for_the_submission() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, anonymous_config()).await;
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let rents = rent.minimum_balance(VerificationRecord::SPACE)
+        + rent.minimum_balance(ProofNullifier::SPACE)
+        + rent.minimum_balance(CredentialNullifier::SPACE);
+    let relayer = funded(&mut ctx, rents).await;
+    let credential = credential_bytes(CREDENTIAL);
+    let epoch = epoch(&mut ctx).await;
+
+    // The payer's transaction fee aside, the relayer funds every account
+    let proven = fixture(CREDENTIAL, 1);
+    let (verification, result) = submit(&mut ctx, &registry, &relayer, &proven, credential, epoch).await;
+    result.unwrap();
+    assert_eq!(ctx.banks_client.get_balance(relayer.pubkey()).await.unwrap(), 0);
+    let record: VerificationRecord = fetch(&mut ctx, verification.pubkey()).await;
+    assert_ne!(record.patient_pubkey, relayer.pubkey());
+}
+
+#[tokio::test]
+async fn test_anonymous_submission_rejections() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, anonymous_config()).await;
+    let relayer = funded(&mut ctx, 1_000_000_000).await;
+    let epoch = epoch(&mut ctx).await;
+    let proven = fixture(CREDENTIAL, 1);
+
+    // The claimed credential must be the one the proof publishes, for this epoch
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &proven, credential_bytes(CREDENTIAL + 1), epoch).await;
+    assert_error(result, HealthcareError::PublicInputNullifierMismatch);
+    let credential = credential_bytes(CREDENTIAL);
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &proven, credential, epoch + 1).await;
+    assert_error(result, HealthcareError::WrongEpoch);
+
+    // Past the nullifier, the inputs are held to the proof by the pairing as usual
+    let mut forged = fixture(CREDENTIAL, 1);
+    forged.public_inputs[32] ^= 1;
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &forged, credential, epoch).await;
+    assert_error(result, HealthcareError::PairingCheckFailed);
+}
+
+#[tokio::test]
+async fn test_anonymous_mode_needs_a_nullifier_circuit() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, VkConfig::default()).await;
+    let relayer = funded(&mut ctx, 1_000_000_000).await;
+    let epoch = epoch(&mut ctx).await;
+    let credential = credential_bytes(CREDENTIAL);
+    let (_, result) = submit(&mut ctx, &registry, &relayer, &fixture(CREDENTIAL, 1), credential, epoch).await;
+    assert_error(result, HealthcareError::AnonymousModeUnsupported);
+
+    // A nullifier slot must be a real input no other binding claims
+    let vk_bytes = fixture(CREDENTIAL, 0).vk_bytes;
+    let vk_hash = keccak::hash(&vk_bytes).to_bytes();
+    let config = VkConfig {
+        n_public: n_public(&vk_bytes),
+        binds_patient: true,
+        ..VkConfig::default()
+    };
+    for nullifier_input in [0, config.n_public] {
+        let config = VkConfig {
+            nullifier_input: Some(nullifier_input),
+            ..config
+        };
+        let ix = register_vk_ix(
+            ctx.payer.pubkey(),
+            registry.pubkey(),
+            "anonymous_v2",
+            vk_bytes.len() as u32,
+            config,
+            vk_hash,
+        );
+        assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidNullifierInput);
+    }
+}
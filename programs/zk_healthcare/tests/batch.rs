@@ -0,0 +1,198 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    derive_verification_pda, HealthcareError, HealthcareRegistry, PatientIndex, ProofFormat, ProofNullifier,
+    ProofSubmission, RecordStatus, VerificationRecord, VerificationResult, VerificationType, MAX_BATCH_SIZE,
+};
+
+const CIRCUIT: &str = "eligibility_batch";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn setup(ctx: &mut ProgramTestContext, n: u64) -> (Keypair, Vec<Fixture>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(n);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    (registry, fixtures)
+}
+
+/// A batch of `submissions` by the payer, from their next record nonce on
+async fn batch_ix(ctx: &mut ProgramTestContext, registry: &Keypair, submissions: Vec<ProofSubmission>) -> Instruction {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    verify_eligibility_batch_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        ProofFormat::Uncompressed,
+        submissions,
+    )
+}
+
+#[tokio::test]
+async fn test_batch_writes_one_record_per_proof() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 3).await;
+
+    let submissions = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+    let ix = batch_ix(&mut ctx, &registry, submissions).await;
+    let answer = simulate_return_data(&mut ctx, std::slice::from_ref(&ix), &[]).await;
+    let results = Vec::<VerificationResult>::try_from_slice(&answer.data).unwrap();
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let patient = ctx.payer.pubkey();
+    for ((fixture, result), nonce) in fixtures.iter().zip(&results).zip(0..) {
+        let (address, bump) = derive_verification_pda(&patient, VerificationType::Eligibility, nonce);
+        assert_eq!(result.record, address);
+        let record: VerificationRecord = fetch(&mut ctx, address).await;
+        assert_eq!(record.bump, bump);
+        assert_eq!(record.proof_hash, result.proof_hash);
+        assert!(record.is_valid);
+        assert_eq!(record.patient_pubkey, ctx.payer.pubkey());
+        assert_eq!(record.proof_hash, verification_hash(fixture));
+        assert_eq!(record.ipfs_hash, CID);
+
+        let nullifier = nullifier_address(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs);
+        let nullifier: ProofNullifier = fetch(&mut ctx, nullifier).await;
+        assert_eq!(nullifier.verification, address);
+    }
+    let registry: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(registry.total_verifications, 3);
+    assert_eq!(next_record_nonce(&mut ctx, patient).await, 3);
+}
+
+#[tokio::test]
+async fn test_invalid_proof_aborts_batch_and_names_index() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 4).await;
+
+    let mut submissions: Vec<_> = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+    // Entry 2 claims entry 3's statement
+    submissions[2].public_inputs = fixtures[3].public_inputs.clone();
+    let ix = batch_ix(&mut ctx, &registry, submissions).await;
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+
+    assert_error(result, HealthcareError::PairingCheckFailed);
+    assert!(logs.iter().any(|log| log.contains("Batch entry 2 failed verification")));
+    let address = verification_address(&ctx.payer.pubkey(), 0);
+    assert!(ctx.banks_client.get_account(address).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_batch_rejects_used_and_duplicate_proofs() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 3).await;
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixtures[1].proof.clone(),
+        ProofFormat::Uncompressed,
+        fixtures[1].public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let submissions = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+    let ix = batch_ix(&mut ctx, &registry, submissions).await;
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::ProofAlreadyUsed);
+    assert!(logs.iter().any(|log| log.contains("Batch entry 1 rejected")));
+
+    let submissions = [&fixtures[0], &fixtures[2], &fixtures[0]]
+        .into_iter()
+        .map(|fixture| submission(fixture, CID))
+        .collect();
+    let ix = batch_ix(&mut ctx, &registry, submissions).await;
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::ProofAlreadyUsed);
+    assert!(logs.iter().any(|log| log.contains("Batch entry 2 rejected")));
+}
+
+#[tokio::test]
+async fn test_batch_must_replace_an_active_record() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 3).await;
+    let patient = ctx.payer.pubkey();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        patient,
+        fixtures[0].proof.clone(),
+        ProofFormat::Uncompressed,
+        fixtures[0].public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let submissions: Vec<_> = fixtures[1..].iter().map(|fixture| submission(fixture, CID)).collect();
+    let ix = batch_ix(&mut ctx, &registry, submissions).await;
+    assert_error(send(&mut ctx, &[ix.clone()], &[]).await, HealthcareError::ActiveVerificationExists);
+    send(&mut ctx, &[with_force_new(ix)], &[]).await.unwrap();
+
+    let previous: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 0)).await;
+    assert_eq!(previous.status, RecordStatus::Expired);
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!((index.verification_count, index.active_count), (3, 2));
+    assert_eq!(index.last_record_by_type[VerificationType::Eligibility as usize], verification_address(&patient, 2));
+}
+
+#[tokio::test]
+async fn test_malformed_entry_named() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 2).await;
+
+    let mut submissions: Vec<_> = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+    submissions[1].proof.truncate(128);
+    let ix = batch_ix(&mut ctx, &registry, submissions).await;
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::InvalidProofLength);
+    assert!(logs.iter().any(|log| log.contains("Batch entry 1 rejected")));
+}
+
+#[tokio::test]
+async fn test_batch_accounts_must_match_submissions() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 2).await;
+    let submissions: Vec<_> = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+
+    // Records of entries 0 and 1 swapped
+    let mut ix = batch_ix(&mut ctx, &registry, submissions.clone()).await;
+    ix.accounts.swap(9, 11);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::BatchAccountMismatch);
+
+    let mut ix = batch_ix(&mut ctx, &registry, submissions.clone()).await;
+    ix.accounts.truncate(11);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::BatchAccountMismatch);
+
+    // The batch starts at the patient's next record nonce, not past it
+    let patient = ctx.payer.pubkey();
+    let format = ProofFormat::Uncompressed;
+    let ix = verify_eligibility_batch_ix(registry.pubkey(), 1, CIRCUIT, patient, format, submissions);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordNonceMismatch);
+}
+
+#[tokio::test]
+async fn test_batch_size_bounds() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 1).await;
+
+    let ix = batch_ix(&mut ctx, &registry, Vec::new()).await;
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidBatchSize);
+
+    let submissions = vec![submission(&fixtures[0], CID); MAX_BATCH_SIZE + 1];
+    let ix = batch_ix(&mut ctx, &registry, submissions).await;
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidBatchSize);
+}
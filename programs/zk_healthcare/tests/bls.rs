@@ -0,0 +1,100 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{compute_verification_hash, CurveId, HealthcareError, ProofFormat, VerificationRecord, VerifyingKey};
+
+const BLS: &str = "eligibility_bls_v1";
+const GROTH16: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    circuit_id: &str,
+    proof: &[u8],
+    format: ProofFormat,
+    public_inputs: &[u8],
+) -> Result<Pubkey, BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        circuit_id,
+        patient,
+        proof.to_vec(),
+        format,
+        public_inputs.to_vec(),
+        CID,
+    );
+    send(ctx, &[ix], &[]).await?;
+    Ok(verification)
+}
+
+#[tokio::test]
+async fn test_one_proof_per_curve_verifies() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), GROTH16, &fixture.vk_bytes).await;
+    let verification =
+        submit(&mut ctx, &registry, GROTH16, &fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs)
+            .await
+            .unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+
+    // A patient holds one active record, so the BLS12-381 proof gets a chain of
+    // its own
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let bls = bls_square_fixture(1);
+    upload_bls_vk(&mut ctx, registry.pubkey(), BLS, &bls).await;
+    let data = fetch_vk_data(&mut ctx, BLS).await;
+    assert_eq!(VerifyingKey::new(&data).unwrap().curve(), CurveId::Bls12_381);
+
+    let verification = submit(&mut ctx, &registry, BLS, &bls.proof, ProofFormat::Compressed, &bls.public_inputs)
+        .await
+        .unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+    let mut input = [0u8; 32];
+    input.copy_from_slice(&bls.public_inputs);
+    input.reverse();
+    assert_eq!(record.proof_hash, compute_verification_hash(&bls.proof, &[input]));
+}
+
+#[tokio::test]
+async fn test_bls_circuit_rejects_bn254_proofs() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let bls = bls_square_fixture(1);
+    upload_bls_vk(&mut ctx, registry.pubkey(), BLS, &bls).await;
+
+    // A BN254 proof is the wrong length for the curve, and BLS12-381 proofs
+    // come compressed only
+    let bn254 = square_fixture(1);
+    let proof = &bn254.compressed_proof;
+    let result = submit(&mut ctx, &registry, BLS, proof, ProofFormat::Compressed, &bls.public_inputs).await;
+    assert_error(result.map(drop), HealthcareError::InvalidProofLength);
+    let result = submit(&mut ctx, &registry, BLS, &bn254.proof, ProofFormat::Uncompressed, &bls.public_inputs).await;
+    assert_error(result.map(drop), HealthcareError::UnsupportedProofFormat);
+
+    // A proof of another statement is a failed pairing, not an error
+    let check = |inputs: &[u8]| {
+        verify_proof_readonly_ix(registry.pubkey(), BLS, bls.proof.clone(), ProofFormat::Compressed, inputs.to_vec())
+    };
+    assert_eq!(simulate_return_data(&mut ctx, &[check(&bls.public_inputs)], &[]).await.data, [1]);
+    let other = bls_square_fixture(2);
+    assert_eq!(simulate_return_data(&mut ctx, &[check(&other.public_inputs)], &[]).await.data, [0]);
+
+    // The BN254 syscall encoding has no BLS12-381 counterpart to cache
+    let ix = prepare_vk_ix(ctx.payer.pubkey(), BLS);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::UnsupportedCurve);
+}
@@ -0,0 +1,121 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, HealthcareRegistry, PatientIndex, ProofFormat, RecordsClosed, VerificationClosed,
+    VerificationRecord, RECORD_GC_GRACE_SECS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+
+/// A registry whose records expire after `VALIDITY_SECS`, and `n` records the
+/// payer verified under it, each forcing the one before it to expire
+async fn setup(ctx: &mut ProgramTestContext, n: u64) -> (Keypair, Vec<Pubkey>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(n);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), VALIDITY_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+
+    let patient = ctx.payer.pubkey();
+    let mut records = Vec::new();
+    for (nonce, fixture) in fixtures.iter().enumerate() {
+        let ix = verify_eligibility_ix(
+            registry.pubkey(),
+            nonce as u64,
+            CIRCUIT,
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+            CID,
+        );
+        let ix = if nonce == 0 { ix } else { with_force_new(ix) };
+        send(ctx, &[ix], &[]).await.unwrap();
+        records.push(verification_address(&patient, nonce as u64));
+    }
+    (registry, records)
+}
+
+async fn revoke(ctx: &mut ProgramTestContext, registry: &Keypair, record: Pubkey) {
+    let patient = ctx.payer.pubkey();
+    let ix = revoke_verification_ix(patient, registry.pubkey(), record, patient, 1);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ten_records_close_at_once() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, records) = setup(&mut ctx, 10).await;
+    let patient = ctx.payer.pubkey();
+    // Half revoked, the other half left to run out their validity
+    for record in &records[..5] {
+        revoke(&mut ctx, &registry, *record).await;
+    }
+    let last: VerificationRecord = fetch(&mut ctx, records[9]).await;
+    warp_clock_to(&mut ctx, last.expires_at + RECORD_GC_GRACE_SECS).await;
+
+    let mut rent = 0;
+    for record in &records {
+        rent += ctx.banks_client.get_balance(*record).await.unwrap();
+    }
+    let before = ctx.banks_client.get_balance(patient).await.unwrap();
+    // Oldest first, each relinking the next, which is closed in turn
+    let triples: Vec<_> = (0..records.len()).map(|at| (records[at], patient, records.get(at + 1).copied())).collect();
+    let (result, logs) = send_logged(&mut ctx, &[close_records_bulk_ix(registry.pubkey(), &triples)], &[]).await;
+    result.unwrap();
+
+    for record in &records {
+        assert!(ctx.banks_client.get_account(*record).await.unwrap().is_none());
+    }
+    // Less the transaction's one signature fee
+    assert_eq!(ctx.banks_client.get_balance(patient).await.unwrap(), before + rent - 5_000);
+    let summary = events::<RecordsClosed>(&logs);
+    assert_eq!((summary.len(), summary[0].count, summary[0].lamports_returned), (1, 10, rent));
+    let closed = events::<VerificationClosed>(&logs);
+    assert_eq!(closed.iter().map(|event| event.record).collect::<Vec<_>>(), records);
+    assert_eq!(closed.iter().map(|event| event.lamports).sum::<u64>(), rent);
+    let registry: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(registry.closed_verifications, 10);
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!((index.active_count, index.last_record), (0, Pubkey::default()));
+}
+
+#[tokio::test]
+async fn test_active_record_aborts_the_bulk_close() {
+    let mut ctx = start().await;
+    let (registry, records) = setup(&mut ctx, 3).await;
+    let patient = ctx.payer.pubkey();
+    revoke(&mut ctx, &registry, records[0]).await;
+    revoke(&mut ctx, &registry, records[1]).await;
+
+    // The last record is still active, and sits second in the batch
+    let triples: Vec<_> = [0, 2, 1].map(|at| (records[at], patient, records.get(at + 1).copied())).to_vec();
+    let (result, logs) = send_logged(&mut ctx, &[close_records_bulk_ix(registry.pubkey(), &triples)], &[]).await;
+    assert_error(result, HealthcareError::RecordNotClosable);
+    assert!(logs.iter().any(|log| log.contains("records[1]")));
+    for record in &records {
+        assert!(ctx.banks_client.get_account(*record).await.unwrap().is_some());
+    }
+}
+
+#[tokio::test]
+async fn test_rent_only_goes_to_the_payer() {
+    let mut ctx = start().await;
+    let (registry, records) = setup(&mut ctx, 1).await;
+    revoke(&mut ctx, &registry, records[0]).await;
+
+    let patient = ctx.payer.pubkey();
+    let elsewhere = (records[0], patient, patient, Pubkey::new_unique(), None);
+    let ix = close_records_bulk_refunding_ix(registry.pubkey(), &[elsewhere]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RentRefundMismatch);
+    let ix = close_records_bulk_ix(registry.pubkey(), &[(records[0], Pubkey::new_unique(), None)]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::BulkCloseAccountMismatch);
+}
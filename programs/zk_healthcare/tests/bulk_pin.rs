@@ -0,0 +1,169 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::VersionedTransaction;
+use zk_healthcare::{
+    data_hash_root, DataCategory, DataPinned, DataPinnedBatch, HealthcareError, HealthcareRegistry, IpfsPinRecord,
+    PatientIndex, PinEntry, PinStatus, MAX_BULK_PINS,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const V1_CID: &str = "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm";
+
+/// `count` entries of `ipfs_cid`, each a different document
+fn entries(ipfs_cid: &str, count: usize) -> Vec<PinEntry> {
+    (0..count)
+        .map(|index| PinEntry {
+            ipfs_cid: ipfs_cid.to_string(),
+            data_hash: [index as u8 + 1; 32],
+            category: DataCategory::Other,
+            tag: None,
+        })
+        .collect()
+}
+
+/// Bytes of a transaction carrying `ix` alone, signed by the payer alone
+fn transaction_len(ctx: &ProgramTestContext, ix: Instruction) -> usize {
+    let message = Message::new(&[ix], Some(&ctx.payer.pubkey()));
+    1 + 64 * usize::from(message.header.num_required_signatures) + message.serialize().len()
+}
+
+/// Bytes of a signed v0 transaction
+fn v0_len(tx: &VersionedTransaction) -> usize {
+    1 + 64 * tx.signatures.len() + tx.message.serialize().len()
+}
+
+#[tokio::test]
+async fn test_batch_of_six_pins() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    let entries = entries(CID, 6);
+
+    let ix = pin_medical_data_bulk_ix(registry, patient, &entries);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    for entry in &entries {
+        let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &entry.data_hash)).await;
+        assert_eq!((pin.version, pin.patient, pin.rent_payer), (IpfsPinRecord::VERSION, patient, patient));
+        assert_eq!((pin.cid_string(), pin.data_hash), (CID.to_string(), entry.data_hash));
+        assert_eq!(pin.status, PinStatus::Requested);
+    }
+    let state: HealthcareRegistry = fetch(&mut ctx, registry).await;
+    assert_eq!(state.ipfs_pin_count, 6);
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.pin_category_counts[DataCategory::Other as usize], 6);
+
+    // One event for the batch, committing to every document's hash
+    assert!(events::<DataPinned>(&logs).is_empty());
+    let event = &events::<DataPinnedBatch>(&logs)[0];
+    assert_eq!((event.registry, event.patient, event.count), (registry, patient, 6));
+    let hashes: Vec<[u8; 32]> = entries.iter().map(|entry| entry.data_hash).collect();
+    assert_eq!(event.data_hash_root, data_hash_root(&hashes));
+    assert_ne!(event.data_hash_root, data_hash_root(&hashes[..5]));
+
+    // The pins are ordinary ones: the same content isn't pinned twice
+    warp_clock(&mut ctx, 0).await;
+    let ix = pin_medical_data_ix(registry, patient, CID, entries[3].data_hash);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::AlreadyPinned);
+}
+
+#[tokio::test]
+async fn test_practical_batch_size() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+
+    // A transaction of the patient's alone fits 6 CIDv0 entries, 5 CIDv1 ones
+    let len = |ipfs_cid: &str, count: usize| {
+        transaction_len(&ctx, pin_medical_data_bulk_ix(registry, patient, &entries(ipfs_cid, count)))
+    };
+    assert!(len(CID, 6) <= PACKET_DATA_SIZE && len(CID, 7) > PACKET_DATA_SIZE);
+    assert!(len(V1_CID, 5) <= PACKET_DATA_SIZE && len(V1_CID, 6) > PACKET_DATA_SIZE);
+    assert!(len(CID, MAX_BULK_PINS) > PACKET_DATA_SIZE);
+
+    let ix = pin_medical_data_bulk_ix(registry, patient, &[]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidBulkPinCount);
+
+    // Each entry needs its own pin's and content claim's addresses
+    let mut ix = pin_medical_data_bulk_ix(registry, patient, &entries(CID, 3));
+    ix.accounts.pop();
+    assert_error(send(&mut ctx, &[ix.clone()], &[]).await, HealthcareError::BulkPinAccountMismatch);
+    ix.accounts.push(ix.accounts[ix.accounts.len() - 1].clone());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::BulkPinAccountMismatch);
+}
+
+#[tokio::test]
+async fn test_lookup_table_fits_a_full_batch() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    // Distinct documents across the batches below
+    let batch = |ipfs_cid: &str, from: usize, count: usize| entries(ipfs_cid, from + count)[from..].to_vec();
+
+    // Eight entries outgrow a legacy transaction but fit a v0 one
+    let tx = pin_medical_data_bulk_v0(&mut ctx, registry, &batch(CID, 0, 8)).await;
+    assert!(v0_len(&tx) <= PACKET_DATA_SIZE);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    let state: HealthcareRegistry = fetch(&mut ctx, registry).await;
+    assert_eq!(state.ipfs_pin_count, 8);
+
+    // as do the most a batch holds, even of the longer CIDv1s
+    let full = batch(V1_CID, 8, MAX_BULK_PINS);
+    let tx = pin_medical_data_bulk_v0(&mut ctx, registry, &full).await;
+    assert!(v0_len(&tx) <= PACKET_DATA_SIZE);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    for entry in &full {
+        let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &entry.data_hash)).await;
+        assert_eq!(pin.cid_string(), V1_CID);
+    }
+    let state: HealthcareRegistry = fetch(&mut ctx, registry).await;
+    assert_eq!(state.ipfs_pin_count, 8 + MAX_BULK_PINS as u64);
+
+    // One more CIDv0 entry would still fit, but is past the limit
+    let past = batch(CID, 8 + MAX_BULK_PINS, MAX_BULK_PINS + 1);
+    let tx = pin_medical_data_bulk_v0(&mut ctx, registry, &past).await;
+    assert!(v0_len(&tx) <= PACKET_DATA_SIZE);
+    assert_error(ctx.banks_client.process_transaction(tx).await, HealthcareError::InvalidBulkPinCount);
+}
+
+#[tokio::test]
+async fn test_invalid_entry_aborts_the_batch() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    let mut entries = entries(CID, 6);
+    entries[4].ipfs_cid = "QmNotACid".to_string();
+
+    let ix = pin_medical_data_bulk_ix(registry, patient, &entries);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::InvalidCid);
+    assert!(logs.iter().any(|log| log.contains("Batch entry 4 rejected")));
+    // Nothing of the batch was written, not even the entries before
+    for entry in &entries {
+        let address = IpfsPinRecord::address(&patient, &entry.data_hash);
+        assert!(ctx.banks_client.get_account(address).await.unwrap().is_none());
+    }
+    let state: HealthcareRegistry = fetch(&mut ctx, registry).await;
+    assert_eq!(state.ipfs_pin_count, 0);
+
+    // A document pinned already, or twice in the batch, aborts it too
+    send(&mut ctx, &[pin_medical_data_ix(registry, patient, CID, entries[2].data_hash)], &[]).await.unwrap();
+    entries[4].ipfs_cid = CID.to_string();
+    let (result, logs) = send_logged(&mut ctx, &[pin_medical_data_bulk_ix(registry, patient, &entries)], &[]).await;
+    assert_error(result, HealthcareError::AlreadyPinned);
+    assert!(logs.iter().any(|log| log.contains("Batch entry 2 rejected")));
+    entries[2].data_hash = entries[5].data_hash;
+    let (result, logs) = send_logged(&mut ctx, &[pin_medical_data_bulk_ix(registry, patient, &entries)], &[]).await;
+    assert_error(result, HealthcareError::AlreadyPinned);
+    assert!(logs.iter().any(|log| log.contains("Batch entry 5 rejected")));
+}
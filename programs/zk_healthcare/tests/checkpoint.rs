@@ -0,0 +1,105 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{Checkpoint, HealthcareError, HealthcareRegistry, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const ROOT: [u8; 32] = [0xab; 32];
+
+/// A registry and `n` records the payer verified under it, each forcing the one
+/// before it to expire
+async fn setup(ctx: &mut ProgramTestContext, n: u64) -> (Keypair, Vec<Pubkey>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(n);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let mut records = Vec::new();
+    for (nonce, fixture) in fixtures.iter().enumerate() {
+        let ix = verify_eligibility_ix(
+            registry.pubkey(),
+            nonce as u64,
+            CIRCUIT,
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+            CID,
+        );
+        let ix = if nonce == 0 { ix } else { with_force_new(ix) };
+        send(ctx, &[ix], &[]).await.unwrap();
+        records.push(verification_address(&patient, nonce as u64));
+    }
+    (registry, records)
+}
+
+async fn commit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    start_seq: u64,
+    end_seq: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let ix = commit_checkpoint_ix(ctx.payer.pubkey(), registry.pubkey(), ROOT, start_seq, end_seq);
+    send(ctx, &[ix], &[]).await
+}
+
+#[tokio::test]
+async fn test_records_keep_their_latest_seq() {
+    let mut ctx = start().await;
+    let (registry, records) = setup(&mut ctx, 3).await;
+    let patient = ctx.payer.pubkey();
+    let ix = revoke_verification_ix(patient, registry.pubkey(), records[2], patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let mut seqs = Vec::new();
+    for record in &records {
+        let record: VerificationRecord = fetch(&mut ctx, *record).await;
+        seqs.push(record.event_seq);
+    }
+    assert!(seqs.windows(2).all(|pair| pair[0] < pair[1]));
+    // The revocation is the last event
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!((account.event_seq, account.checkpointed_seq), (seqs[2] + 1, 0));
+}
+
+#[tokio::test]
+async fn test_checkpoints_are_contiguous() {
+    let mut ctx = start().await;
+    let (registry, _) = setup(&mut ctx, 3).await;
+    let emitted = fetch::<HealthcareRegistry>(&mut ctx, registry.pubkey()).await.event_seq;
+
+    assert_error(commit(&mut ctx, &registry, 1, 3).await, HealthcareError::CheckpointNotContiguous);
+    assert_error(commit(&mut ctx, &registry, 0, 0).await, HealthcareError::InvalidCheckpointRange);
+    // Past the events emitted so far
+    assert_error(commit(&mut ctx, &registry, 0, emitted + 1).await, HealthcareError::InvalidCheckpointRange);
+
+    commit(&mut ctx, &registry, 0, 2).await.unwrap();
+    let checkpoint: Checkpoint = fetch(&mut ctx, Checkpoint::address(&registry.pubkey(), 0)).await;
+    assert_eq!((checkpoint.registry, checkpoint.root), (registry.pubkey(), ROOT));
+    assert_eq!((checkpoint.start_seq, checkpoint.end_seq), (0, 2));
+
+    assert_error(commit(&mut ctx, &registry, 1, 3).await, HealthcareError::CheckpointNotContiguous);
+    commit(&mut ctx, &registry, 2, 3).await.unwrap();
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.checkpointed_seq, 3);
+}
+
+#[tokio::test]
+async fn test_only_authority_commits() {
+    let mut ctx = start().await;
+    let (registry, _) = setup(&mut ctx, 1).await;
+    let intruder = Keypair::new();
+
+    // Funded to pay the checkpoint's rent, so only the authority check is left
+    let fund = system_instruction::transfer(&ctx.payer.pubkey(), &intruder.pubkey(), 1_000_000_000);
+    let ix = commit_checkpoint_ix(intruder.pubkey(), registry.pubkey(), ROOT, 0, 1);
+    let err = send(&mut ctx, &[fund, ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+}
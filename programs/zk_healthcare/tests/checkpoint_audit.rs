@@ -0,0 +1,97 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// A light client checking an event against a checkpoint, from logs alone.
+// Captures events by swapping the process-wide syscall stubs, so this binary
+// holds a single test.
+
+mod common;
+
+use common::*;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use zk_healthcare::client::{event_seq, AuditTree};
+use zk_healthcare::{Checkpoint, EligibilityVerified, HealthcareRegistry, ProofFormat, VerificationRevoked};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+#[tokio::test]
+async fn test_checkpoint_proves_each_event() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(7);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+
+    // The setup's events go under a checkpoint of their own
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    let ix = commit_checkpoint_ix(patient, registry.pubkey(), [0; 32], 0, account.event_seq);
+    let (result, mut logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+
+    // Seven verifications and a revocation
+    for (nonce, fixture) in fixtures.iter().enumerate() {
+        let ix = verify_eligibility_ix(
+            registry.pubkey(),
+            nonce as u64,
+            CIRCUIT,
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+            CID,
+        );
+        let ix = if nonce == 0 { ix } else { with_force_new(ix) };
+        let (result, tx_logs) = send_logged(&mut ctx, &[ix], &[]).await;
+        result.unwrap();
+        logs.extend(tx_logs);
+    }
+    let ix = revoke_verification_ix(patient, registry.pubkey(), verification_address(&patient, 6), patient, 1);
+    let (result, tx_logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    logs.extend(tx_logs);
+
+    let (start, end) = (account.event_seq, account.event_seq + event_data(&logs).len() as u64);
+    let verified = events::<EligibilityVerified>(&logs);
+    assert_eq!(verified.len(), 7);
+    assert!(verified.iter().all(|event| (start..end).contains(&event.seq)));
+    assert_eq!(events::<VerificationRevoked>(&logs)[0].seq, end - 1);
+
+    // Served out of order, and only ever for this registry
+    let mut data = event_data(&logs);
+    data.reverse();
+    let tree = AuditTree::from_events(data.iter().map(Vec::as_slice), &registry.pubkey(), start, end).unwrap();
+    let past_end = AuditTree::from_events(data.iter().map(Vec::as_slice), &registry.pubkey(), start, end + 1);
+    assert_eq!(past_end.err(), Some(end));
+    let other = AuditTree::from_events(data.iter().map(Vec::as_slice), &Pubkey::new_unique(), start, end);
+    assert_eq!(other.err(), Some(start));
+
+    let ix = commit_checkpoint_ix(patient, registry.pubkey(), tree.root(), start, end);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let checkpoint: Checkpoint = fetch(&mut ctx, Checkpoint::address(&registry.pubkey(), start)).await;
+
+    for event in &data {
+        let proof = tree.prove(event_seq(event).unwrap()).unwrap();
+        assert!(proof.verify(&checkpoint, event));
+    }
+
+    // A proof holds for its own event only, unaltered, under its own registry
+    let (first, last) = (&data[data.len() - 1], &data[0]);
+    let proof = tree.prove(event_seq(last).unwrap()).unwrap();
+    assert!(!proof.verify(&checkpoint, first));
+    let mut tampered = last.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(!proof.verify(&checkpoint, &tampered));
+    assert!(!proof.verify(&Checkpoint { registry: Pubkey::new_unique(), ..checkpoint.clone() }, last));
+    assert!(tree.prove(end).is_none());
+
+    // Over an odd count of events, the last moves up unpaired
+    let odd_start = start + 1 - (end - start) % 2;
+    let tree = AuditTree::from_events(data.iter().map(Vec::as_slice), &registry.pubkey(), odd_start, end).unwrap();
+    let checkpoint = Checkpoint { root: tree.root(), start_seq: odd_start, end_seq: end, ..checkpoint };
+    for event in data.iter().filter(|event| event_seq(event).unwrap() >= odd_start) {
+        let proof = tree.prove(event_seq(event).unwrap()).unwrap();
+        assert!(proof.verify(&checkpoint, event));
+    }
+    assert!(tree.prove(odd_start - 1).is_none());
+}
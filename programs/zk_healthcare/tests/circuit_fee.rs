@@ -0,0 +1,168 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{
+    fixtures, HealthcareError, HealthcareRegistry, PatientIndex, ProofFormat, ProofNullifier, VerificationRecord,
+    VerifyingKey, VkConfig,
+};
+
+const CIRCUIT: &str = "preimage_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+/// Above the rent-exempt minimum, so a fresh recipient account can hold it
+const FEE: u64 = 1_000_000;
+
+/// A registry whose eligibility circuit charges `FEE` to a fresh author account
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    let ix = set_max_circuit_fee_ix(ctx.payer.pubkey(), registry.pubkey(), FEE);
+    send(ctx, &[ix], &[]).await.unwrap();
+
+    let author = Keypair::new();
+    let config = VkConfig {
+        fee_lamports: FEE,
+        fee_recipient: author.pubkey(),
+        ..VkConfig::default()
+    };
+    upload_vk_with(ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes(), config).await;
+    (registry, author)
+}
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    patient: &Keypair,
+    public_inputs: Vec<u8>,
+    fee_recipient: Option<Pubkey>,
+) -> (Pubkey, Result<(), BanksClientError>) {
+    let nonce = next_record_nonce(ctx, patient.pubkey()).await;
+    let verification = verification_address(&patient.pubkey(), nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient.pubkey(),
+        fixtures::valid_proof(),
+        ProofFormat::Uncompressed,
+        public_inputs,
+        CID,
+    );
+    let ix = match fee_recipient {
+        Some(fee_recipient) => with_fee_recipient(ix, fee_recipient),
+        None => ix,
+    };
+    let result = send(ctx, &[ix], &[patient]).await;
+    (verification, result)
+}
+
+async fn funded_patient(ctx: &mut ProgramTestContext, lamports: u64) -> Keypair {
+    let patient = Keypair::new();
+    let ix = system_instruction::transfer(&ctx.payer.pubkey(), &patient.pubkey(), lamports);
+    send(ctx, &[ix], &[]).await.unwrap();
+    patient
+}
+
+#[tokio::test]
+async fn test_fee_paid_to_recipient_on_success() {
+    let mut ctx = start().await;
+    let (registry, author) = setup(&mut ctx).await;
+    let data = fetch_vk_data(&mut ctx, CIRCUIT).await;
+    let vk = VerifyingKey::new(&data).unwrap();
+    assert_eq!((vk.fee_lamports, vk.fee_recipient), (FEE, author.pubkey()));
+
+    let patient = funded_patient(&mut ctx, 1_000_000_000).await;
+    let (verification, result) =
+        submit(&mut ctx, &registry, &patient, fixtures::public_inputs(), Some(author.pubkey())).await;
+    result.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+    assert_eq!(balance(&mut ctx, author.pubkey()).await, FEE);
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let rents = rent.minimum_balance(VerificationRecord::SPACE)
+        + rent.minimum_balance(ProofNullifier::SPACE)
+        + rent.minimum_balance(PatientIndex::SPACE);
+    assert_eq!(balance(&mut ctx, patient.pubkey()).await, 1_000_000_000 - rents - FEE);
+}
+
+#[tokio::test]
+async fn test_no_fee_when_proof_fails() {
+    let mut ctx = start().await;
+    let (registry, author) = setup(&mut ctx).await;
+    let patient = funded_patient(&mut ctx, 1_000_000_000).await;
+
+    let mut inputs = fixtures::public_inputs();
+    inputs[0] ^= 1;
+    let (_, result) = submit(&mut ctx, &registry, &patient, inputs, Some(author.pubkey())).await;
+    assert_error(result, HealthcareError::PairingCheckFailed);
+    assert_eq!(balance(&mut ctx, author.pubkey()).await, 0);
+    assert_eq!(balance(&mut ctx, patient.pubkey()).await, 1_000_000_000);
+}
+
+#[tokio::test]
+async fn test_fee_requires_the_circuit_recipient() {
+    let mut ctx = start().await;
+    let (registry, _author) = setup(&mut ctx).await;
+    let patient = funded_patient(&mut ctx, 1_000_000_000).await;
+
+    let (_, result) = submit(&mut ctx, &registry, &patient, fixtures::public_inputs(), None).await;
+    assert_error(result, HealthcareError::InvalidFeeRecipient);
+    let other = Keypair::new().pubkey();
+    let (_, result) = submit(&mut ctx, &registry, &patient, fixtures::public_inputs(), Some(other)).await;
+    assert_error(result, HealthcareError::InvalidFeeRecipient);
+}
+
+#[tokio::test]
+async fn test_fee_beyond_patient_funds_rejected() {
+    let mut ctx = start().await;
+    let (registry, author) = setup(&mut ctx).await;
+
+    // Enough for the record, nullifier and patient index rent, but only half the fee
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    let rents = rent.minimum_balance(VerificationRecord::SPACE)
+        + rent.minimum_balance(ProofNullifier::SPACE)
+        + rent.minimum_balance(PatientIndex::SPACE);
+    let patient = funded_patient(&mut ctx, rents + FEE / 2).await;
+    let (_, result) = submit(&mut ctx, &registry, &patient, fixtures::public_inputs(), Some(author.pubkey())).await;
+    assert_error(result, HealthcareError::InsufficientFeeFunds);
+    assert_eq!(balance(&mut ctx, author.pubkey()).await, 0);
+}
+
+#[tokio::test]
+async fn test_fee_above_cap_rejected_at_registration() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let vk_bytes = fixtures::vk_bytes();
+    let vk_hash = keccak::hash(&vk_bytes).to_bytes();
+    let config = VkConfig {
+        n_public: fixtures::N_PUBLIC,
+        fee_lamports: FEE,
+        fee_recipient: Keypair::new().pubkey(),
+        ..VkConfig::default()
+    };
+    let total_len = vk_bytes.len() as u32;
+    let register = |config| register_vk_ix(authority, registry.pubkey(), CIRCUIT, total_len, config, vk_hash);
+
+    // No fee is allowed until the authority raises the cap
+    assert_error(send(&mut ctx, &[register(config)], &[]).await, HealthcareError::CircuitFeeTooHigh);
+    let ix = set_max_circuit_fee_ix(authority, registry.pubkey(), FEE - 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 1).await;
+    assert_error(send(&mut ctx, &[register(config)], &[]).await, HealthcareError::CircuitFeeTooHigh);
+    let registry_account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(registry_account.max_circuit_fee, FEE - 1);
+
+    let ix = set_max_circuit_fee_ix(authority, registry.pubkey(), FEE);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let unpaid = VkConfig { fee_recipient: Pubkey::default(), ..config };
+    assert_error(send(&mut ctx, &[register(unpaid)], &[]).await, HealthcareError::InvalidFeeRecipient);
+    warp_clock(&mut ctx, 1).await;
+    send(&mut ctx, &[register(config)], &[]).await.unwrap();
+}
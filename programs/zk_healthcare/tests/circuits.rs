@@ -0,0 +1,196 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    CircuitStatus, HealthcareError, HealthcareRegistry, ProofFormat, VerificationRecord, VerificationType,
+    VerifyingKey, VK_CLOSE_COOLDOWN_SECS,
+};
+
+const CIRCUIT_A: &str = "eligibility_v1";
+const CIRCUIT_B: &str = "prescription_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// Two circuits of the same shape under different keys; the last upload, B, is
+/// the eligibility circuit
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Fixture, Fixture) {
+    let registry = initialize_registry(ctx).await;
+    let (a, b) = (square_fixture(1), square_fixture(2));
+    upload_vk(ctx, registry.pubkey(), CIRCUIT_A, &a.vk_bytes).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT_B, &b.vk_bytes).await;
+    (registry, a, b)
+}
+
+async fn approve(ctx: &mut ProgramTestContext, registry: &Keypair, circuit_id: &str) {
+    let ix = set_type_circuit_ix(ctx.payer.pubkey(), registry.pubkey(), circuit_id, VerificationType::Eligibility);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+/// The payer's next record and the instruction proving into it, which expires
+/// the record before it
+async fn verify_ix(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    circuit_id: &str,
+    proof: &[u8],
+    public_inputs: &[u8],
+) -> (Pubkey, Instruction) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        circuit_id,
+        patient,
+        proof.to_vec(),
+        ProofFormat::Uncompressed,
+        public_inputs.to_vec(),
+        CID,
+    );
+    (verification_address(&patient, nonce), with_force_new(ix))
+}
+
+#[tokio::test]
+async fn test_proofs_route_to_their_circuit() {
+    let mut ctx = start().await;
+    let (registry, a, b) = setup(&mut ctx).await;
+
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.registered_circuits, 2);
+    let data = fetch_vk_data(&mut ctx, CIRCUIT_B).await;
+    assert_eq!(VerifyingKey::new(&data).unwrap().circuit_id(), CIRCUIT_B);
+
+    for (circuit_id, fixture) in [(CIRCUIT_A, &a), (CIRCUIT_B, &b)] {
+        approve(&mut ctx, &registry, circuit_id).await;
+        let (verification, ix) =
+            verify_ix(&mut ctx, &registry, circuit_id, &fixture.proof, &fixture.public_inputs).await;
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+        let record: VerificationRecord = fetch(&mut ctx, verification).await;
+        assert!(record.is_valid);
+        assert_eq!(record.circuit_id, circuit_id);
+    }
+}
+
+#[tokio::test]
+async fn test_proof_fails_under_another_circuit() {
+    let mut ctx = start().await;
+    let (registry, a, _) = setup(&mut ctx).await;
+
+    let (_, ix) = verify_ix(&mut ctx, &registry, CIRCUIT_B, &a.proof, &a.public_inputs).await;
+    assert_error(
+        send(&mut ctx, &[ix], &[]).await,
+        HealthcareError::PairingCheckFailed,
+    );
+
+    // Naming circuit B while passing circuit A's key is caught by the PDA seeds
+    approve(&mut ctx, &registry, CIRCUIT_A).await;
+    let mut ix = verify_ix(&mut ctx, &registry, CIRCUIT_B, &a.proof, &a.public_inputs).await.1;
+    ix.accounts[2].pubkey = vk_address(CIRCUIT_A);
+    let err = send(&mut ctx, &[ix], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintSeeds as u32);
+}
+
+#[tokio::test]
+async fn test_deprecated_circuit_refuses_new_proofs() {
+    let mut ctx = start().await;
+    let (registry, a, _) = setup(&mut ctx).await;
+    approve(&mut ctx, &registry, CIRCUIT_A).await;
+    let authority = ctx.payer.pubkey();
+
+    let (_, ix) = verify_ix(&mut ctx, &registry, CIRCUIT_A, &a.proof, &a.public_inputs).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = set_circuit_status_ix(authority, registry.pubkey(), CIRCUIT_A, CircuitStatus::Deprecated, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let fresh = a.reprove(7);
+    let (_, ix) = verify_ix(&mut ctx, &registry, CIRCUIT_A, &fresh, &a.public_inputs).await;
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CircuitDeprecated);
+
+    // An active circuit below its minimum version is refused the same way
+    let ix = set_circuit_status_ix(authority, registry.pubkey(), CIRCUIT_A, CircuitStatus::Active, 2);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Retries of the same transaction would share a signature
+    warp_clock(&mut ctx, 0).await;
+    let ix = verify_ix(&mut ctx, &registry, CIRCUIT_A, &fresh, &a.public_inputs).await.1;
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CircuitDeprecated);
+
+    let ix = set_circuit_status_ix(authority, registry.pubkey(), CIRCUIT_A, CircuitStatus::Active, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    let ix = verify_ix(&mut ctx, &registry, CIRCUIT_A, &fresh, &a.public_inputs).await.1;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_revocation_sweep_invalidates_old_records() {
+    let mut ctx = start().await;
+    let (registry, a, b) = setup(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+
+    let mut records = Vec::new();
+    for (circuit_id, fixture) in [(CIRCUIT_A, &a), (CIRCUIT_B, &b)] {
+        approve(&mut ctx, &registry, circuit_id).await;
+        let (verification, ix) =
+            verify_ix(&mut ctx, &registry, circuit_id, &fixture.proof, &fixture.public_inputs).await;
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+        records.push((verification, authority));
+    }
+    let ix = sweep_revoked_records_ix(registry.pubkey(), CIRCUIT_A, &records[..1]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CircuitNotRevoked);
+
+    let ix = set_circuit_status_ix(authority, registry.pubkey(), CIRCUIT_A, CircuitStatus::Revoked, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = sweep_revoked_records_ix(registry.pubkey(), CIRCUIT_A, &records);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordCircuitMismatch);
+
+    // Same transaction as the refused sweep above, so move to a fresh blockhash
+    warp_clock(&mut ctx, 0).await;
+    let ix = sweep_revoked_records_ix(registry.pubkey(), CIRCUIT_A, &records[..1]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, records[0].0).await;
+    assert!(!record.is_valid);
+    assert_eq!(record.circuit_version, 1);
+    let record: VerificationRecord = fetch(&mut ctx, records[1].0).await;
+    assert!(record.is_valid);
+
+    let ix = set_circuit_status_ix(authority, registry.pubkey(), CIRCUIT_A, CircuitStatus::Active, 1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidCircuitStatusChange);
+}
+
+#[tokio::test]
+async fn test_close_revoked_circuit_after_cooldown() {
+    let mut ctx = start().await;
+    let (registry, a, _) = setup(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    approve(&mut ctx, &registry, CIRCUIT_A).await;
+    let (_, ix) = verify_ix(&mut ctx, &registry, CIRCUIT_A, &a.proof, &a.public_inputs).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = close_verifying_key_ix(authority, registry.pubkey(), CIRCUIT_A);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CircuitNotRevoked);
+
+    let ix = set_circuit_status_ix(authority, registry.pubkey(), CIRCUIT_A, CircuitStatus::Revoked, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    let ix = close_verifying_key_ix(authority, registry.pubkey(), CIRCUIT_A);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VkCloseCooldownActive);
+
+    warp_clock(&mut ctx, VK_CLOSE_COOLDOWN_SECS).await;
+    let rent = ctx.banks_client.get_balance(vk_address(CIRCUIT_A)).await.unwrap();
+    let before = ctx.banks_client.get_balance(authority).await.unwrap();
+    let ix = close_verifying_key_ix(authority, registry.pubkey(), CIRCUIT_A);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    assert!(ctx.banks_client.get_account(vk_address(CIRCUIT_A)).await.unwrap().is_none());
+    let after = ctx.banks_client.get_balance(authority).await.unwrap();
+    assert_eq!(after, before + rent - 5000);
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.registered_circuits, 1);
+    assert_eq!(state.circuit_for_type[VerificationType::Eligibility as usize], None);
+}
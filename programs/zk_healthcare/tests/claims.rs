@@ -0,0 +1,249 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use anchor_lang::error::ErrorCode;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    ClaimEscrow, ClaimNullifier, ClaimRecord, ClaimStatus, HealthcareError, HealthcareRegistry, ProofFormat,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+/// Stands in for a salted hash of a CPT code
+const SERVICE_CODE_COMMITMENT: [u8; 32] = [0x2a; 32];
+const AMOUNT: u64 = 250_000;
+/// Stands in for a hash of the encounter's identifiers
+const ENCOUNTER: [u8; 32] = [0x61; 32];
+
+/// A registry holding one verified eligibility record of the payer
+async fn setup(ctx: &mut ProgramTestContext) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        fixture.proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        CID,
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+    registry
+}
+
+#[tokio::test]
+async fn test_claim_against_active_record_is_submitted() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let verification = verification_address(&patient, 0);
+
+    let ix = submit_claim_ix(registry.pubkey(), verification, patient, 0, 250_000, SERVICE_CODE_COMMITMENT, ENCOUNTER);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let claim: ClaimRecord = fetch(&mut ctx, ClaimRecord::address(&registry.pubkey(), 0)).await;
+    assert_eq!(claim.status, ClaimStatus::Submitted);
+    assert_eq!((claim.patient, claim.verification), (patient, verification));
+    assert_eq!((claim.claim_id, claim.amount), (0, 250_000));
+    assert_eq!(claim.service_code_commitment, SERVICE_CODE_COMMITMENT);
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.claim_count, 1);
+
+    // The next claim, for another encounter, takes the next number
+    let ix = submit_claim_ix(registry.pubkey(), verification, patient, 1, 1, SERVICE_CODE_COMMITMENT, [0x62; 32]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let claim: ClaimRecord = fetch(&mut ctx, ClaimRecord::address(&registry.pubkey(), 1)).await;
+    assert_eq!(claim.claim_id, 1);
+}
+
+#[tokio::test]
+async fn test_claim_against_revoked_record_fails() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let verification = verification_address(&patient, 0);
+
+    let ix = revoke_verification_ix(patient, registry.pubkey(), verification, patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = submit_claim_ix(registry.pubkey(), verification, patient, 0, 250_000, SERVICE_CODE_COMMITMENT, ENCOUNTER);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordRevoked);
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.claim_count, 0);
+}
+
+#[tokio::test]
+async fn test_claim_needs_the_records_patient_and_an_amount() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let (patient, registry) = (ctx.payer.pubkey(), registry.pubkey());
+    let verification = verification_address(&patient, 0);
+
+    let other = funded(&mut ctx).await;
+    let ix = submit_claim_ix(registry, verification, other.pubkey(), 0, AMOUNT, SERVICE_CODE_COMMITMENT, ENCOUNTER);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::ClaimPatientMismatch);
+
+    let ix = submit_claim_ix(registry, verification, patient, 0, 0, SERVICE_CODE_COMMITMENT, ENCOUNTER);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidClaimAmount);
+}
+
+/// A registered, funded insurer
+async fn insurer(ctx: &mut ProgramTestContext, registry: &Keypair) -> Keypair {
+    let insurer = funded(ctx).await;
+    let ix = register_insurer_ix(ctx.payer.pubkey(), registry.pubkey(), insurer.pubkey());
+    send(ctx, &[ix], &[]).await.unwrap();
+    insurer
+}
+
+/// Claim 0 of `AMOUNT` lamports, filed by the payer against their record
+async fn submit(ctx: &mut ProgramTestContext, registry: &Keypair) -> Pubkey {
+    let patient = ctx.payer.pubkey();
+    let verification = verification_address(&patient, 0);
+    let ix = submit_claim_ix(registry.pubkey(), verification, patient, 0, AMOUNT, SERVICE_CODE_COMMITMENT, ENCOUNTER);
+    send(ctx, &[ix], &[]).await.unwrap();
+    ClaimRecord::address(&registry.pubkey(), 0)
+}
+
+#[tokio::test]
+async fn test_approved_claim_pays_the_designated_provider() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let claim = submit(&mut ctx, &registry).await;
+    let insurer = insurer(&mut ctx, &registry).await;
+    let provider = funded(&mut ctx).await;
+    let ix = register_provider_ix(ctx.payer.pubkey(), registry.pubkey(), provider.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = designate_claim_payee_ix(registry.pubkey(), claim, ctx.payer.pubkey(), provider.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = fund_claim_escrow_ix(registry.pubkey(), claim, insurer.pubkey(), AMOUNT - 1);
+    assert_error(send(&mut ctx, &[ix], &[&insurer]).await, HealthcareError::ClaimEscrowMismatch);
+    let ix = fund_claim_escrow_ix(registry.pubkey(), claim, insurer.pubkey(), AMOUNT);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+    let escrow: ClaimEscrow = fetch(&mut ctx, ClaimEscrow::address(&claim)).await;
+    assert_eq!((escrow.insurer, escrow.lamports), (insurer.pubkey(), AMOUNT));
+
+    let (insurer_before, provider_before) =
+        (balance(&mut ctx, insurer.pubkey()).await, balance(&mut ctx, provider.pubkey()).await);
+    let ix = adjudicate_claim_ix(registry.pubkey(), claim, provider.pubkey(), insurer.pubkey(), true, 0);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+    assert_eq!(balance(&mut ctx, provider.pubkey()).await, provider_before + AMOUNT);
+    // The escrow's rent goes back to the insurer
+    let rent = Rent::default().minimum_balance(ClaimEscrow::SPACE);
+    assert_eq!(balance(&mut ctx, insurer.pubkey()).await, insurer_before + rent);
+    let record: ClaimRecord = fetch(&mut ctx, claim).await;
+    assert_eq!((record.status, record.insurer), (ClaimStatus::Paid, Some(insurer.pubkey())));
+
+    // Adjudicated once: the escrow is gone and cannot be funded again
+    warp_clock(&mut ctx, 0).await;
+    let ix = adjudicate_claim_ix(registry.pubkey(), claim, provider.pubkey(), insurer.pubkey(), true, 0);
+    let err = send(&mut ctx, &[ix], &[&insurer]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::AccountNotInitialized as u32);
+    let ix = fund_claim_escrow_ix(registry.pubkey(), claim, insurer.pubkey(), AMOUNT);
+    assert_error(send(&mut ctx, &[ix], &[&insurer]).await, HealthcareError::ClaimAlreadyAdjudicated);
+}
+
+#[tokio::test]
+async fn test_denied_claim_refunds_the_insurer() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let claim = submit(&mut ctx, &registry).await;
+    let insurer = insurer(&mut ctx, &registry).await;
+    let patient = ctx.payer.pubkey();
+
+    let insurer_before = balance(&mut ctx, insurer.pubkey()).await;
+    let ix = fund_claim_escrow_ix(registry.pubkey(), claim, insurer.pubkey(), AMOUNT);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+    let ix = adjudicate_claim_ix(registry.pubkey(), claim, patient, insurer.pubkey(), false, 17);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+
+    // The payer pays the fees, so everything comes back
+    assert_eq!(balance(&mut ctx, insurer.pubkey()).await, insurer_before);
+    let record: ClaimRecord = fetch(&mut ctx, claim).await;
+    assert_eq!((record.status, record.reason_code), (ClaimStatus::Denied, 17));
+}
+
+#[tokio::test]
+async fn test_only_the_funding_insurer_adjudicates() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let claim = submit(&mut ctx, &registry).await;
+    let insurer_a = insurer(&mut ctx, &registry).await;
+    let insurer_b = insurer(&mut ctx, &registry).await;
+    let patient = ctx.payer.pubkey();
+
+    // An unregistered signer cannot take the claim on
+    let outsider = funded(&mut ctx).await;
+    let ix = fund_claim_escrow_ix(registry.pubkey(), claim, outsider.pubkey(), AMOUNT);
+    let err = send(&mut ctx, &[ix], &[&outsider]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::AccountNotInitialized as u32);
+
+    let ix = fund_claim_escrow_ix(registry.pubkey(), claim, insurer_a.pubkey(), AMOUNT);
+    send(&mut ctx, &[ix], &[&insurer_a]).await.unwrap();
+    for signer in [&insurer_b, &outsider] {
+        let ix = adjudicate_claim_ix(registry.pubkey(), claim, patient, signer.pubkey(), true, 0);
+        assert_error(send(&mut ctx, &[ix], &[signer]).await, HealthcareError::ClaimInsurerMismatch);
+    }
+    // and the payout goes only to the claim's payee
+    let ix = adjudicate_claim_ix(registry.pubkey(), claim, insurer_a.pubkey(), insurer_a.pubkey(), true, 0);
+    assert_error(send(&mut ctx, &[ix], &[&insurer_a]).await, HealthcareError::ClaimPayeeMismatch);
+}
+
+#[tokio::test]
+async fn test_duplicate_encounter_is_refused() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let claim = submit(&mut ctx, &registry).await;
+    let patient = ctx.payer.pubkey();
+    let verification = verification_address(&patient, 0);
+
+    let nullifier: ClaimNullifier = fetch(&mut ctx, ClaimNullifier::address(&registry.pubkey(), &ENCOUNTER)).await;
+    assert_eq!(nullifier.claim, claim);
+
+    // Same encounter, billed under another service code and amount
+    let ix = submit_claim_ix(registry.pubkey(), verification, patient, 1, AMOUNT + 1, [0x2b; 32], ENCOUNTER);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::DuplicateClaim);
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.claim_count, 1);
+}
+
+#[tokio::test]
+async fn test_released_nullifier_allows_resubmission() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let claim = submit(&mut ctx, &registry).await;
+    let insurer = insurer(&mut ctx, &registry).await;
+    let patient = ctx.payer.pubkey();
+    let release = release_claim_nullifier_ix(registry.pubkey(), claim, patient, insurer.pubkey(), ENCOUNTER);
+
+    let ix = fund_claim_escrow_ix(registry.pubkey(), claim, insurer.pubkey(), AMOUNT);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+    // Open claims keep their encounter
+    assert_error(send(&mut ctx, std::slice::from_ref(&release), &[&insurer]).await, HealthcareError::ClaimNotDenied);
+
+    let ix = adjudicate_claim_ix(registry.pubkey(), claim, patient, insurer.pubkey(), false, 3);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+    let other = funded(&mut ctx).await;
+    let ix = release_claim_nullifier_ix(registry.pubkey(), claim, patient, other.pubkey(), ENCOUNTER);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::ClaimInsurerMismatch);
+    // Past the slot, so the repeated release isn't taken for the refused one
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[release], &[&insurer]).await.unwrap();
+
+    // The corrected claim is filed for the same encounter
+    let verification = verification_address(&patient, 0);
+    let ix = submit_claim_ix(registry.pubkey(), verification, patient, 1, AMOUNT, [0x2b; 32], ENCOUNTER);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let nullifier: ClaimNullifier = fetch(&mut ctx, ClaimNullifier::address(&registry.pubkey(), &ENCOUNTER)).await;
+    assert_eq!(nullifier.claim, ClaimRecord::address(&registry.pubkey(), 1));
+    let record: ClaimRecord = fetch(&mut ctx, claim).await;
+    assert_eq!(record.status, ClaimStatus::Denied);
+}
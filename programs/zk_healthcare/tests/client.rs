@@ -0,0 +1,75 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use ark_bn254::Fr;
+use common::*;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::fixtures::{self, PreimageCircuit};
+use zk_healthcare::{client, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "preimage_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+#[test]
+fn test_client_encodings_match_test_helpers() {
+    let proof = fixtures::prove(Fr::from(fixtures::PREIMAGE));
+    let hash = PreimageCircuit::hash(Fr::from(fixtures::PREIMAGE));
+    assert_eq!(client::encode_proof(&proof, ProofFormat::Uncompressed), fixtures::valid_proof());
+    assert_eq!(client::encode_public_inputs(&[hash]), fixtures::public_inputs());
+    assert_eq!(client::vk_address(CIRCUIT), vk_address(CIRCUIT));
+
+    let (registry, patient) = (Keypair::new(), Keypair::new());
+    let built = client::build_verify_eligibility_ix(
+        registry.pubkey(),
+        3,
+        patient.pubkey(),
+        CIRCUIT,
+        &proof,
+        ProofFormat::Uncompressed,
+        &[hash],
+        CID,
+        None,
+    );
+    let expected = verify_eligibility_ix(
+        registry.pubkey(),
+        3,
+        CIRCUIT,
+        patient.pubkey(),
+        fixtures::valid_proof(),
+        ProofFormat::Uncompressed,
+        fixtures::public_inputs(),
+        CID,
+    );
+    assert_eq!(built, expected);
+}
+
+#[tokio::test]
+async fn test_client_submissions_verify_in_every_format() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes()).await;
+
+    // A preimage per format: re-encodings of one proof share a nullifier
+    let formats = [ProofFormat::Uncompressed, ProofFormat::Compressed, ProofFormat::SnarkJs];
+    for (preimage, format) in (fixtures::PREIMAGE..).zip(formats) {
+        let preimage = Fr::from(preimage);
+        let patient = ctx.payer.pubkey();
+        let nonce = next_record_nonce(&mut ctx, patient).await;
+        let ix = client::build_verify_eligibility_ix(
+            registry.pubkey(),
+            nonce,
+            patient,
+            CIRCUIT,
+            &fixtures::prove(preimage),
+            format,
+            &[PreimageCircuit::hash(preimage)],
+            CID,
+            None,
+        );
+        send(&mut ctx, &[with_force_new(ix)], &[]).await.unwrap();
+
+        let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, nonce)).await;
+        assert!(record.is_valid, "{:?} submission rejected", format);
+    }
+}
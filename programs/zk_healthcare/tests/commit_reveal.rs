@@ -0,0 +1,211 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, ProofFormat, VerificationCommitment, VerificationRecord, DEFAULT_CACHE_TTL_SLOTS,
+    DEFAULT_MAX_PUBLIC_INPUTS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const REVEAL_SLOTS: u64 = 20;
+const SALT: [u8; 32] = [7; 32];
+
+/// A registry requiring commitments, with the fixture's key as its circuit
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = update_config_ix(
+        ctx.payer.pubkey(),
+        registry.pubkey(),
+        DEFAULT_MAX_PUBLIC_INPUTS,
+        DEFAULT_CACHE_TTL_SLOTS,
+        REVEAL_SLOTS,
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+    registry
+}
+
+async fn commit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    submitter: &Keypair,
+    fixture: &Fixture,
+) -> Result<(), BanksClientError> {
+    let commitment = VerificationCommitment::hash(&fixture.proof, &fixture.public_inputs, &SALT, &submitter.pubkey());
+    let ix = commit_verification_ix(registry.pubkey(), submitter.pubkey(), commitment);
+    send(ctx, &[ix], &[submitter]).await
+}
+
+/// Submit the fixture's proof as `submitter`, revealing `committer`'s commitment
+/// with `salt` if given
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    submitter: &Keypair,
+    fixture: &Fixture,
+    reveal: Option<(&Keypair, [u8; 32])>,
+) -> (Pubkey, Result<(), BanksClientError>) {
+    let nonce = next_record_nonce(ctx, submitter.pubkey()).await;
+    let verification = verification_address(&submitter.pubkey(), nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        submitter.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let ix = match reveal {
+        Some((committer, salt)) => with_commitment(ix, committer.pubkey(), salt),
+        None => ix,
+    };
+    let result = send(ctx, &[ix], &[submitter]).await;
+    (verification, result)
+}
+
+async fn next_slot(ctx: &mut ProgramTestContext) {
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    ctx.warp_to_slot(clock.slot + 1).unwrap();
+}
+
+#[tokio::test]
+async fn test_commit_then_reveal() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let patient = funded(&mut ctx).await;
+
+    // Without a commitment the proof is refused outright
+    let (_, result) = submit(&mut ctx, &registry, &patient, &fixture, None).await;
+    assert_error(result, HealthcareError::CommitmentMismatch);
+
+    commit(&mut ctx, &registry, &patient, &fixture).await.unwrap();
+    let commitment = commitment_address(&patient.pubkey());
+    let pending: VerificationCommitment = fetch(&mut ctx, commitment).await;
+    assert_eq!(pending.submitter, patient.pubkey());
+    assert_eq!(pending.expires_at_slot, pending.committed_at_slot + REVEAL_SLOTS);
+    let (_, result) = submit(&mut ctx, &registry, &patient, &fixture, Some((&patient, SALT))).await;
+    assert_error(result, HealthcareError::CommitmentTooRecent);
+
+    next_slot(&mut ctx).await;
+    let (_, result) = submit(&mut ctx, &registry, &patient, &fixture, Some((&patient, [8; 32]))).await;
+    assert_error(result, HealthcareError::CommitmentMismatch);
+    let (verification, result) = submit(&mut ctx, &registry, &patient, &fixture, Some((&patient, SALT))).await;
+    result.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.patient_pubkey, patient.pubkey());
+    assert!(ctx.banks_client.get_account(commitment).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_commitment_cannot_be_revealed_by_another_submitter() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let patient = funded(&mut ctx).await;
+    let front_runner = funded(&mut ctx).await;
+    commit(&mut ctx, &registry, &patient, &fixture).await.unwrap();
+    next_slot(&mut ctx).await;
+
+    // Identical proof bytes, even with the patient's salt, don't open the patient's
+    // commitment for anyone else, and a commitment of their own comes too late
+    let (_, result) = submit(&mut ctx, &registry, &front_runner, &fixture, Some((&patient, SALT))).await;
+    assert_error(result, HealthcareError::CommitmentMismatch);
+    let (_, result) = submit(&mut ctx, &registry, &front_runner, &fixture, None).await;
+    assert_error(result, HealthcareError::CommitmentMismatch);
+    commit(&mut ctx, &registry, &front_runner, &fixture).await.unwrap();
+    let (_, result) = submit(&mut ctx, &registry, &front_runner, &fixture, Some((&front_runner, SALT))).await;
+    assert_error(result, HealthcareError::CommitmentTooRecent);
+
+    let (verification, result) = submit(&mut ctx, &registry, &patient, &fixture, Some((&patient, SALT))).await;
+    result.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.patient_pubkey, patient.pubkey());
+}
+
+#[tokio::test]
+async fn test_expired_commitment_refunds_its_submitter() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let patient = funded(&mut ctx).await;
+    commit(&mut ctx, &registry, &patient, &fixture).await.unwrap();
+    let commitment = commitment_address(&patient.pubkey());
+
+    let ix = reclaim_commitment_ix(commitment, patient.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CommitmentNotExpired);
+
+    let pending: VerificationCommitment = fetch(&mut ctx, commitment).await;
+    ctx.warp_to_slot(pending.expires_at_slot + 1).unwrap();
+    let (_, result) = submit(&mut ctx, &registry, &patient, &fixture, Some((&patient, SALT))).await;
+    assert_error(result, HealthcareError::CommitmentExpired);
+
+    // Anyone may crank it, but the rent only goes back to the submitter
+    let ix = reclaim_commitment_ix(commitment, ctx.payer.pubkey());
+    let err = send(&mut ctx, &[ix], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+    let rent = ctx.banks_client.get_balance(commitment).await.unwrap();
+    let before = ctx.banks_client.get_balance(patient.pubkey()).await.unwrap();
+    send(&mut ctx, &[reclaim_commitment_ix(commitment, patient.pubkey())], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(commitment).await.unwrap().is_none());
+    assert_eq!(ctx.banks_client.get_balance(patient.pubkey()).await.unwrap(), before + rent);
+
+    // Reclaimed, the submitter may commit again
+    commit(&mut ctx, &registry, &patient, &fixture).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_commitments_off_by_default() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let patient = funded(&mut ctx).await;
+
+    assert_error(
+        commit(&mut ctx, &registry, &patient, &fixture).await,
+        HealthcareError::CommitRevealDisabled,
+    );
+    submit(&mut ctx, &registry, &patient, &fixture, None).await.1.unwrap();
+}
+
+#[tokio::test]
+async fn test_paths_without_a_commitment_are_refused() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let patient = ctx.payer.pubkey();
+    let format = ProofFormat::Uncompressed;
+
+    // Neither the batch nor the two-phase path carries a commitment to reveal
+    let submissions = vec![submission(&fixture, CID)];
+    let ix = verify_eligibility_batch_ix(registry.pubkey(), 0, CIRCUIT, patient, format, submissions);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CommitmentMismatch);
+
+    let partial = Keypair::new();
+    let (proof, inputs) = (&fixture.proof, &fixture.public_inputs);
+    let ix = begin_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        patient,
+        proof.clone(),
+        format,
+        inputs.clone(),
+    );
+    send(&mut ctx, &[ix], &[&partial]).await.unwrap();
+    send(&mut ctx, &[advance_verification_ix(partial.pubkey(), CIRCUIT, patient)], &[]).await.unwrap();
+    let nullifier = nullifier_address(proof, format, inputs);
+    let ix = complete_verification_ix(registry.pubkey(), partial.pubkey(), CIRCUIT, 0, nullifier, patient, CID);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CommitmentMismatch);
+}
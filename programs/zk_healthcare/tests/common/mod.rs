@@ -0,0 +1,3102 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+#![allow(dead_code)]
+
+mod plonk;
+
+pub use plonk::*;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE};
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey};
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::address_lookup_table::state::{AddressLookupTable, LookupTableMeta};
+use solana_sdk::address_lookup_table::AddressLookupTableAccount;
+use solana_sdk::message::{v0, VersionedMessage};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::{system_instruction, system_program};
+use solana_sdk::transaction::{Transaction, TransactionError, VersionedTransaction};
+use solana_sdk::transaction_context::TransactionReturnData;
+use std::sync::Once;
+use zk_healthcare::DataCategory;
+
+pub const VK_CHUNK_SIZE: usize = 512;
+pub const VK_UPDATE_DELAY_SECS: i64 = 3600;
+/// `event_authority` and `program`, which `#[event_cpi]` appends to an
+/// instruction's declared accounts
+pub const EVENT_CPI_ACCOUNTS: usize = 2;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entrypoint ties the slice and account lifetimes together
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    zk_healthcare::entry(program_id, accounts, data)
+}
+
+/// Start a bank running the SBF build instead of the native processor, so compute
+/// units are metered. Requires `anchor build` and `SBF_OUT_DIR=target/deploy`.
+pub async fn start_sbf() -> ProgramTestContext {
+    let mut program = ProgramTest::new("zk_healthcare", zk_healthcare::ID, None);
+    program.prefer_bpf(true);
+    program.start_with_context().await
+}
+
+/// The native program, for tests that add programs of their own before starting
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new(
+        "zk_healthcare",
+        zk_healthcare::ID,
+        processor!(process_instruction),
+    )
+}
+
+pub async fn start() -> ProgramTestContext {
+    program_test().start_with_context().await
+}
+
+/// Program-test's native stubs print `sol_log_data` to stdout rather than the
+/// transaction log. This forwards every syscall to them except that one, which
+/// it writes to the log as `Program data: <base64>...` the way a validator does.
+/// Program-test doesn't report inner instructions either, so each CPI is logged
+/// as `Program invoke data: <program> <base64>` before it is forwarded.
+struct EventLogStubs(Box<dyn SyscallStubs>);
+
+struct NoStubs;
+
+impl SyscallStubs for NoStubs {}
+
+impl SyscallStubs for EventLogStubs {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<_> = fields.iter().map(|field| BASE64_STANDARD.encode(field)).collect();
+        self.0.sol_log(&format!("Program data: {}", fields.join(" ")))
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let data = BASE64_STANDARD.encode(&instruction.data);
+        self.0.sol_log(&format!("Program invoke data: {} {}", instruction.program_id, data));
+        self.0.sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+}
+
+/// `start`, with emitted events readable from `send_logged` logs via `events`.
+/// The stubs are process-wide, so only use this in a test binary of its own.
+pub async fn start_with_event_logs() -> ProgramTestContext {
+    static ONCE: Once = Once::new();
+    let ctx = start().await;
+    ONCE.call_once(|| {
+        let native = set_syscall_stubs(Box::new(NoStubs));
+        set_syscall_stubs(Box::new(EventLogStubs(native)));
+    });
+    ctx
+}
+
+/// Decode the `T` events in transaction logs, in order
+pub fn events<T: anchor_lang::Event>(logs: &[String]) -> Vec<T> {
+    event_data(logs)
+        .into_iter()
+        .filter(|data| data.starts_with(&T::DISCRIMINATOR))
+        .map(|data| T::try_from_slice(&data[8..]).unwrap())
+        .collect()
+}
+
+/// Every event in transaction logs as the bytes it was logged with,
+/// discriminator included, in order
+pub fn event_data(logs: &[String]) -> Vec<Vec<u8>> {
+    logs.iter()
+        .filter_map(|log| log.split_once("Program data: "))
+        .filter_map(|(_, data)| BASE64_STANDARD.decode(data).ok())
+        .collect()
+}
+
+/// The program and data of every CPI in transaction logs, in order
+pub fn invoke_data(logs: &[String]) -> Vec<(Pubkey, Vec<u8>)> {
+    logs.iter()
+        .filter_map(|log| log.split_once("Program invoke data: "))
+        .filter_map(|(_, invoke)| invoke.split_once(' '))
+        .map(|(program, data)| (program.parse().unwrap(), BASE64_STANDARD.decode(data).unwrap()))
+        .collect()
+}
+
+pub async fn send(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+/// Send a transaction and return its result alongside the program logs
+///
+/// Unlike `send` this runs on the bank directly, so it can land while a
+/// transaction just sent through the queue still holds its account locks;
+/// it is retried until they are released.
+pub async fn send_logged(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> (std::result::Result<(), BanksClientError>, Vec<String>) {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let outcome = loop {
+        let outcome = ctx.banks_client.process_transaction_with_metadata(tx.clone()).await.unwrap();
+        if outcome.result != Err(TransactionError::AccountInUse) {
+            break outcome;
+        }
+        tokio::task::yield_now().await;
+    };
+    let logs = outcome.metadata.map(|metadata| metadata.log_messages).unwrap_or_default();
+    (outcome.result.map_err(BanksClientError::TransactionError), logs)
+}
+
+/// Simulate a transaction and return the compute units it consumed
+pub async fn simulate_units(ctx: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) -> u64 {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    simulate_transaction_units(ctx, tx.into()).await
+}
+
+/// `simulate_units` of a transaction already signed, legacy or v0
+pub async fn simulate_transaction_units(ctx: &mut ProgramTestContext, tx: VersionedTransaction) -> u64 {
+    let simulation = ctx.banks_client.simulate_transaction(tx).await.unwrap();
+    simulation.result.unwrap().unwrap();
+    simulation.simulation_details.unwrap().units_consumed
+}
+
+/// An address lookup table of `addresses`, written straight into the bank
+/// and usable from the next slot, which this warps to
+pub async fn lookup_table(ctx: &mut ProgramTestContext, addresses: Vec<Pubkey>) -> AddressLookupTableAccount {
+    let key = Pubkey::new_unique();
+    let table = AddressLookupTable { meta: LookupTableMeta::default(), addresses: addresses.clone().into() };
+    let data = table.serialize_for_tests().unwrap();
+    let account = solana_sdk::account::Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: solana_sdk::address_lookup_table::program::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    ctx.set_account(&key, &account.into());
+    warp_clock(ctx, 0).await;
+    AddressLookupTableAccount { key, addresses }
+}
+
+/// A v0 transaction of `instructions` signed by the payer alone, loading what
+/// accounts it can through `table`
+pub async fn v0_transaction(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    table: &AddressLookupTableAccount,
+) -> VersionedTransaction {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let tables = std::slice::from_ref(table);
+    let message = v0::Message::try_compile(&ctx.payer.pubkey(), instructions, tables, blockhash).unwrap();
+    VersionedTransaction::try_new(VersionedMessage::V0(message), &[&ctx.payer]).unwrap()
+}
+
+/// Simulate a transaction and return the data the last program set with `set_return_data`
+pub async fn simulate_return_data(
+    ctx: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> TransactionReturnData {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&ctx.payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&ctx.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let simulation = ctx.banks_client.simulate_transaction(tx).await.unwrap();
+    simulation.result.unwrap().unwrap();
+    simulation.simulation_details.unwrap().return_data.expect("no return data")
+}
+
+/// Move to the next slot and push the bank clock forward by `secs`. The slot
+/// change also rotates the blockhash, so a retried instruction gets a new signature.
+pub async fn warp_clock(ctx: &mut ProgramTestContext, secs: i64) {
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    ctx.warp_to_slot(clock.slot + 1).unwrap();
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += secs;
+    ctx.set_sysvar(&clock);
+}
+
+/// `warp_clock`, but to the absolute time `unix_timestamp`
+pub async fn warp_clock_to(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    ctx.warp_to_slot(clock.slot + 1).unwrap();
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    ctx.set_sysvar(&clock);
+}
+
+/// Extract the Anchor error code from a failed transaction
+pub fn error_code(err: BanksClientError) -> u32 {
+    match err.unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => code,
+        other => panic!("expected a custom program error, got {other:?}"),
+    }
+}
+
+pub fn assert_error(result: std::result::Result<(), BanksClientError>, expected: zk_healthcare::HealthcareError) {
+    let code = error_code(result.expect_err("transaction should have failed"));
+    assert_eq!(code, u32::from(expected), "unexpected error code");
+}
+
+pub async fn fetch<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: Pubkey) -> T {
+    let account = ctx
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .expect("account exists");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// A fresh keypair holding nothing but the lamports it is sent
+pub async fn funded(ctx: &mut ProgramTestContext) -> Keypair {
+    let keypair = Keypair::new();
+    let ix = system_instruction::transfer(&ctx.payer.pubkey(), &keypair.pubkey(), 1_000_000_000);
+    send(ctx, &[ix], &[]).await.unwrap();
+    keypair
+}
+
+pub async fn balance(ctx: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    ctx.banks_client.get_balance(address).await.unwrap()
+}
+
+pub async fn initialize_registry(ctx: &mut ProgramTestContext) -> Keypair {
+    initialize_registry_with(ctx, true).await
+}
+
+pub fn initialize_ix(authority: Pubkey, registry: Pubkey, nist_compliant: bool) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::Initialize {
+            registry,
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::Initialize {
+            nist_compliant,
+            vk_update_delay_secs: VK_UPDATE_DELAY_SECS,
+        }
+        .data(),
+    }
+}
+
+pub async fn initialize_registry_with(ctx: &mut ProgramTestContext, nist_compliant: bool) -> Keypair {
+    let registry = Keypair::new();
+    let ix = initialize_ix(ctx.payer.pubkey(), registry.pubkey(), nist_compliant);
+    send(ctx, &[ix], &[&registry]).await.unwrap();
+    registry
+}
+
+pub fn vk_address(circuit_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"vk", circuit_id.as_bytes()], &zk_healthcare::ID).0
+}
+
+/// Number of public inputs encoded in an uncompressed verifying key
+pub fn n_public(vk_bytes: &[u8]) -> u8 {
+    ((vk_bytes.len() - 456) / 64 - 1) as u8
+}
+
+pub fn register_vk_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    circuit_id: &str,
+    total_len: u32,
+    config: zk_healthcare::VkConfig,
+    vk_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RegisterVerifyingKey {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RegisterVerifyingKey {
+            circuit_id: circuit_id.to_string(),
+            total_len,
+            config,
+            vk_hash,
+        }
+        .data(),
+    }
+}
+
+pub fn write_vk_chunk_ix(authority: Pubkey, circuit_id: &str, offset: u32, chunk: &[u8]) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::WriteVkChunk {
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::WriteVkChunk {
+            offset,
+            chunk: chunk.to_vec(),
+        }
+        .data(),
+    }
+}
+
+pub fn resize_vk_account_ix(authority: Pubkey, circuit_id: &str, new_len: u32) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ResizeVkAccount {
+            verifying_key: vk_address(circuit_id),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ResizeVkAccount { new_len }.data(),
+    }
+}
+
+/// Grow a registered key's account to hold all of its key, one
+/// `MAX_PERMITTED_DATA_INCREASE` step per transaction
+pub async fn grow_vk_account(ctx: &mut ProgramTestContext, circuit_id: &str, total_len: u32) {
+    let authority = ctx.payer.pubkey();
+    let space = zk_healthcare::VerifyingKeyPDA::space(total_len);
+    let mut len = zk_healthcare::VerifyingKeyPDA::initial_space(total_len);
+    while len < space {
+        len = (len + MAX_PERMITTED_DATA_INCREASE).min(space);
+        send(ctx, &[resize_vk_account_ix(authority, circuit_id, len as u32)], &[]).await.unwrap();
+    }
+}
+
+/// Raw data of a verifying key account, read through `zk_healthcare::VerifyingKey`
+pub async fn fetch_vk_data(ctx: &mut ProgramTestContext, circuit_id: &str) -> Vec<u8> {
+    let account = ctx
+        .banks_client
+        .get_account(vk_address(circuit_id))
+        .await
+        .unwrap()
+        .expect("account exists");
+    account.data
+}
+
+pub fn finalize_vk_ix(authority: Pubkey, registry: Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::FinalizeVk {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::FinalizeVk {}.data(),
+    }
+}
+
+/// Register, upload in chunks, and finalize a verifying key
+pub async fn upload_vk(ctx: &mut ProgramTestContext, registry: Pubkey, circuit_id: &str, vk_bytes: &[u8]) {
+    upload_vk_with(ctx, registry, circuit_id, vk_bytes, zk_healthcare::VkConfig::default()).await
+}
+
+/// `upload_vk` with input bindings; `config.n_public` is taken from the key. The
+/// key becomes the registry's eligibility circuit.
+pub async fn upload_vk_with(
+    ctx: &mut ProgramTestContext,
+    registry: Pubkey,
+    circuit_id: &str,
+    vk_bytes: &[u8],
+    config: zk_healthcare::VkConfig,
+) {
+    let config = zk_healthcare::VkConfig { n_public: n_public(vk_bytes), ..config };
+    upload_vk_config(ctx, registry, circuit_id, vk_bytes, config).await
+}
+
+/// Upload a PLONK fixture's key as the registry's eligibility circuit
+pub async fn upload_plonk_vk(ctx: &mut ProgramTestContext, registry: Pubkey, circuit_id: &str, fixture: &PlonkFixture) {
+    let config = zk_healthcare::VkConfig {
+        n_public: (fixture.public_inputs.len() / 32) as u8,
+        scheme: zk_healthcare::ProvingScheme::Plonk,
+        ..zk_healthcare::VkConfig::default()
+    };
+    upload_vk_config(ctx, registry, circuit_id, &fixture.vk_bytes, config).await
+}
+
+/// Upload a BLS12-381 fixture's key as the registry's eligibility circuit
+pub async fn upload_bls_vk(ctx: &mut ProgramTestContext, registry: Pubkey, circuit_id: &str, fixture: &BlsFixture) {
+    let config = zk_healthcare::VkConfig {
+        n_public: (fixture.public_inputs.len() / 32) as u8,
+        curve: zk_healthcare::CurveId::Bls12_381,
+        ..zk_healthcare::VkConfig::default()
+    };
+    upload_vk_config(ctx, registry, circuit_id, &fixture.vk_bytes, config).await
+}
+
+async fn upload_vk_config(
+    ctx: &mut ProgramTestContext,
+    registry: Pubkey,
+    circuit_id: &str,
+    vk_bytes: &[u8],
+    config: zk_healthcare::VkConfig,
+) {
+    let authority = ctx.payer.pubkey();
+    let total_len = vk_bytes.len() as u32;
+    let vk_hash = keccak::hash(vk_bytes).to_bytes();
+    let ix = register_vk_ix(authority, registry, circuit_id, total_len, config, vk_hash);
+    send(ctx, &[ix], &[]).await.unwrap();
+    grow_vk_account(ctx, circuit_id, total_len).await;
+    for (i, chunk) in vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_chunk_ix(authority, circuit_id, (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(ctx, &[ix], &[]).await.unwrap();
+    }
+    send(ctx, &[finalize_vk_ix(authority, registry, circuit_id)], &[]).await.unwrap();
+    let ix = set_type_circuit_ix(authority, registry, circuit_id, zk_healthcare::VerificationType::Eligibility);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+pub fn set_type_circuit_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    circuit_id: &str,
+    verification_type: zk_healthcare::VerificationType,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetTypeCircuit {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::SetTypeCircuit { verification_type }.data(),
+    }
+}
+
+pub fn set_max_circuit_fee_ix(authority: Pubkey, registry: Pubkey, max_circuit_fee: u64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetMaxCircuitFee { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetMaxCircuitFee { max_circuit_fee }.data(),
+    }
+}
+
+pub fn set_security_policy_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    policy: zk_healthcare::SecurityPolicy,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetSecurityPolicy { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetSecurityPolicy { policy }.data(),
+    }
+}
+
+pub fn set_default_validity_ix(authority: Pubkey, registry: Pubkey, validity_secs: i64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetDefaultValidity { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetDefaultValidity { validity_secs }.data(),
+    }
+}
+
+pub fn set_gc_bounty_ix(authority: Pubkey, registry: Pubkey, gc_bounty_lamports: u64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetGcBounty { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetGcBounty { gc_bounty_lamports }.data(),
+    }
+}
+
+pub fn set_max_metadata_len_ix(authority: Pubkey, registry: Pubkey, max_metadata_len: u16) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetMaxMetadataLen { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetMaxMetadataLen { max_metadata_len }.data(),
+    }
+}
+
+pub fn set_cpi_events_ix(authority: Pubkey, registry: Pubkey, enabled: bool) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetCpiEvents { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetCpiEvents { enabled }.data(),
+    }
+}
+
+pub fn set_denied_access_events_ix(authority: Pubkey, registry: Pubkey, enabled: bool) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetDeniedAccessEvents { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetDeniedAccessEvents { enabled }.data(),
+    }
+}
+
+pub fn set_failed_pin_timeout_ix(authority: Pubkey, registry: Pubkey, timeout_secs: i64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetFailedPinTimeout { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetFailedPinTimeout { timeout_secs }.data(),
+    }
+}
+
+pub fn set_pin_bounty_terms_ix(authority: Pubkey, registry: Pubkey, period_secs: i64, periods: u16) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetPinBountyTerms { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetPinBountyTerms { period_secs, periods }.data(),
+    }
+}
+
+pub fn set_pin_replication_target_ix(authority: Pubkey, registry: Pubkey, target: u8) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetPinReplicationTarget { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetPinReplicationTarget { target }.data(),
+    }
+}
+
+pub fn set_deal_expiry_warning_ix(authority: Pubkey, registry: Pubkey, window_secs: i64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetDealExpiryWarning { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetDealExpiryWarning { window_secs }.data(),
+    }
+}
+
+pub fn set_max_access_pass_slots_ix(authority: Pubkey, registry: Pubkey, max_slots: u64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetMaxAccessPassSlots { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetMaxAccessPassSlots { max_slots }.data(),
+    }
+}
+
+pub fn set_pin_storage_price_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    price_lamports_per_day: u64,
+    storage_treasury: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetPinStoragePrice { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetPinStoragePrice {
+            price_lamports_per_day,
+            storage_treasury,
+        }
+        .data(),
+    }
+}
+
+pub fn add_pinning_oracle_ix(authority: Pubkey, registry: Pubkey, oracle: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::AddPinningOracle { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::AddPinningOracle { oracle }.data(),
+    }
+}
+
+pub fn remove_pinning_oracle_ix(authority: Pubkey, registry: Pubkey, oracle: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RemovePinningOracle { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::RemovePinningOracle { oracle }.data(),
+    }
+}
+
+pub fn update_metadata_ix(registry: Pubkey, verification: Pubkey, patient: Pubkey, metadata: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::UpdateMetadata {
+            registry,
+            verification,
+            patient,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::UpdateMetadata { metadata }.data(),
+    }
+}
+
+pub fn update_config_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    max_public_inputs: u8,
+    cache_ttl_slots: u64,
+    commit_reveal_slots: u64,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::UpdateConfig { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::UpdateConfig {
+            max_public_inputs,
+            cache_ttl_slots,
+            commit_reveal_slots,
+        }
+        .data(),
+    }
+}
+
+pub fn prepare_vk_ix(authority: Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::PrepareVk {
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::PrepareVk {}.data(),
+    }
+}
+
+pub fn set_circuit_status_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    circuit_id: &str,
+    status: zk_healthcare::CircuitStatus,
+    min_accepted_version: u16,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetCircuitStatus {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::SetCircuitStatus {
+            status,
+            min_accepted_version,
+        }
+        .data(),
+    }
+}
+
+/// Sweep `records`, each given with its patient
+pub fn sweep_revoked_records_ix(registry: Pubkey, circuit_id: &str, records: &[(Pubkey, Pubkey)]) -> Instruction {
+    let mut accounts = zk_healthcare::accounts::SweepRevokedRecords {
+        registry,
+        verifying_key: vk_address(circuit_id),
+    }
+    .to_account_metas(None);
+    for (record, patient) in records {
+        accounts.push(AccountMeta::new(*record, false));
+        accounts.push(AccountMeta::new(patient_index_address(patient), false));
+    }
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts,
+        data: zk_healthcare::instruction::SweepRevokedRecords {}.data(),
+    }
+}
+
+pub fn revoke_verification_ix(
+    revoker: Pubkey,
+    registry: Pubkey,
+    verification: Pubkey,
+    patient: Pubkey,
+    reason_code: u16,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RevokeVerification {
+            registry,
+            verification,
+            patient_index: patient_index_address(&patient),
+            revoker,
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RevokeVerification { reason_code }.data(),
+    }
+}
+
+/// Close `verification` of `patient`, who paid its rent, relinking
+/// `next_record`, the record after it in the patient's chain
+pub fn close_expired_verification_ix(
+    registry: Pubkey,
+    verification: Pubkey,
+    patient: Pubkey,
+    cranker: Pubkey,
+    next_record: Option<Pubkey>,
+) -> Instruction {
+    close_expired_verification_refunding_ix(registry, verification, patient, patient, patient, cranker, next_record)
+}
+
+/// Close `patient`'s record whose rent `rent_payer` paid, refunding it to
+/// `refund_to`, the payer or the key it rotated to
+pub fn close_expired_verification_refunding_ix(
+    registry: Pubkey,
+    verification: Pubkey,
+    patient: Pubkey,
+    rent_payer: Pubkey,
+    refund_to: Pubkey,
+    cranker: Pubkey,
+    next_record: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CloseExpiredVerification {
+            registry,
+            verification,
+            rent_payer_rotation: zk_healthcare::KeyRotation::address(&rent_payer),
+            rent_payer: refund_to,
+            cranker,
+            patient_index: patient_index_address(&patient),
+            next_record,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CloseExpiredVerification {}.data(),
+    }
+}
+
+/// Close `records` in one instruction, each given with its patient, who paid
+/// its rent, and the record after it in the patient's chain, if any
+pub fn close_records_bulk_ix(registry: Pubkey, records: &[(Pubkey, Pubkey, Option<Pubkey>)]) -> Instruction {
+    let records: Vec<_> = records
+        .iter()
+        .map(|&(record, patient, next_record)| (record, patient, patient, patient, next_record))
+        .collect();
+    close_records_bulk_refunding_ix(registry, &records)
+}
+
+/// Close `records` in one instruction, each given with its patient, its rent
+/// payer, where its rent goes back to, and the record after it in the
+/// patient's chain, if any
+pub fn close_records_bulk_refunding_ix(
+    registry: Pubkey,
+    records: &[(Pubkey, Pubkey, Pubkey, Pubkey, Option<Pubkey>)],
+) -> Instruction {
+    let mut accounts = zk_healthcare::accounts::CloseRecordsBulk { registry }.to_account_metas(None);
+    for (record, patient, rent_payer, refund_to, next_record) in records {
+        accounts.push(AccountMeta::new(*record, false));
+        accounts.push(AccountMeta::new(patient_index_address(patient), false));
+        accounts.push(AccountMeta::new_readonly(zk_healthcare::KeyRotation::address(rent_payer), false));
+        accounts.push(AccountMeta::new(*refund_to, false));
+        accounts.push(match next_record {
+            Some(next_record) => AccountMeta::new(*next_record, false),
+            None => AccountMeta::new_readonly(zk_healthcare::ID, false),
+        });
+    }
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts,
+        data: zk_healthcare::instruction::CloseRecordsBulk {}.data(),
+    }
+}
+
+pub fn close_verifying_key_ix(authority: Pubkey, registry: Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CloseVerifyingKey {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CloseVerifyingKey {
+            circuit_id: circuit_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+pub fn abort_vk_upload_ix(authority: Pubkey, registry: Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::AbortVkUpload {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::AbortVkUpload {
+            circuit_id: circuit_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+/// `gc_stale_upload` refunding `authority`, signed only by the transaction's payer
+pub fn gc_stale_upload_ix(authority: Pubkey, registry: Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::GcStaleUpload {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::GcStaleUpload {
+            circuit_id: circuit_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+pub fn vk_update_address(circuit_id: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"vk_update", vk_address(circuit_id).as_ref()], &zk_healthcare::ID).0
+}
+
+pub fn propose_vk_update_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    circuit_id: &str,
+    new_vk_bytes_hash: [u8; 32],
+    total_len: u32,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ProposeVkUpdate {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            proposal: vk_update_address(circuit_id),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ProposeVkUpdate {
+            new_vk_bytes_hash,
+            total_len,
+        }
+        .data(),
+    }
+}
+
+pub fn write_vk_update_chunk_ix(authority: Pubkey, circuit_id: &str, offset: u32, chunk: &[u8]) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::WriteVkUpdateChunk {
+            verifying_key: vk_address(circuit_id),
+            proposal: vk_update_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::WriteVkUpdateChunk {
+            offset,
+            chunk: chunk.to_vec(),
+        }
+        .data(),
+    }
+}
+
+pub fn activate_vk_update_ix(authority: Pubkey, registry: Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ActivateVkUpdate {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            proposal: vk_update_address(circuit_id),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ActivateVkUpdate {}.data(),
+    }
+}
+
+pub fn cancel_vk_update_ix(authority: Pubkey, circuit_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CancelVkUpdate {
+            verifying_key: vk_address(circuit_id),
+            proposal: vk_update_address(circuit_id),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CancelVkUpdate {}.data(),
+    }
+}
+
+pub fn nullifier_address(proof: &[u8], proof_format: zk_healthcare::ProofFormat, public_inputs: &[u8]) -> Pubkey {
+    let seed = zk_healthcare::ProofNullifier::seed(proof, proof_format, public_inputs);
+    Pubkey::find_program_address(&[b"nullifier", &seed], &zk_healthcare::ID).0
+}
+
+pub fn begin_verification_ix(
+    registry: Pubkey,
+    partial: Pubkey,
+    circuit_id: &str,
+    patient: Pubkey,
+    proof: Vec<u8>,
+    proof_format: zk_healthcare::ProofFormat,
+    public_inputs: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::BeginVerification {
+            registry,
+            verifying_key: vk_address(circuit_id),
+            partial,
+            patient,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::BeginVerification {
+            proof,
+            proof_format,
+            public_inputs,
+            circuit_id: circuit_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+pub fn advance_verification_ix(partial: Pubkey, circuit_id: &str, patient: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::AdvanceVerification {
+            partial,
+            verifying_key: vk_address(circuit_id),
+            patient,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::AdvanceVerification {}.data(),
+    }
+}
+
+/// `nullifier` is the submission's `nullifier_address`; the record lands at
+/// `verification_address` of `record_nonce`
+pub fn complete_verification_ix(
+    registry: Pubkey,
+    partial: Pubkey,
+    circuit_id: &str,
+    record_nonce: u64,
+    nullifier: Pubkey,
+    patient: Pubkey,
+    ipfs_hash: &str,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CompleteVerification {
+            registry,
+            partial,
+            verifying_key: vk_address(circuit_id),
+            verification: verification_address(&patient, record_nonce),
+            nullifier,
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            patient_index: patient_index_address(&patient),
+            previous_record: previous_record_address(&patient, record_nonce),
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CompleteVerification {
+            record_nonce,
+            ipfs_hash: ipfs_hash.to_string(),
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+pub fn reclaim_verification_ix(partial: Pubkey, patient: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ReclaimVerification { partial, patient }.to_account_metas(None),
+        data: zk_healthcare::instruction::ReclaimVerification {}.data(),
+    }
+}
+
+/// A `verify_eligibility_batch` writing `patient`'s records from `record_nonce`
+/// on, one nonce per submission
+pub fn verify_eligibility_batch_ix(
+    registry: Pubkey,
+    record_nonce: u64,
+    circuit_id: &str,
+    patient: Pubkey,
+    proof_format: zk_healthcare::ProofFormat,
+    submissions: Vec<zk_healthcare::ProofSubmission>,
+) -> Instruction {
+    let mut accounts = zk_healthcare::accounts::VerifyEligibilityBatch {
+        registry,
+        verifying_key: vk_address(circuit_id),
+        patient,
+        system_program: system_program::ID,
+        fee_recipient: None,
+        patient_index: patient_index_address(&patient),
+        previous_record: previous_record_address(&patient, record_nonce),
+        event_authority: zk_healthcare::event_authority_address(),
+        program: zk_healthcare::ID,
+    }
+    .to_account_metas(None);
+    for (nonce, submission) in (record_nonce..).zip(&submissions) {
+        let (proof, inputs) = (&submission.proof, &submission.public_inputs);
+        accounts.push(AccountMeta::new(verification_address(&patient, nonce), false));
+        accounts.push(AccountMeta::new(nullifier_address(proof, proof_format, inputs), false));
+    }
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts,
+        data: zk_healthcare::instruction::VerifyEligibilityBatch {
+            proof_format,
+            submissions,
+            circuit_id: circuit_id.to_string(),
+            record_nonce,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+/// A `reverify_eligibility` renewing `patient`'s record `verification` with `fixture`
+pub fn reverify_eligibility_ix(
+    registry: Pubkey,
+    verification: Pubkey,
+    circuit_id: &str,
+    patient: Pubkey,
+    fixture: &Fixture,
+    ipfs_hash: &str,
+) -> Instruction {
+    let format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ReverifyEligibility {
+            registry,
+            verification,
+            verifying_key: vk_address(circuit_id),
+            nullifier: nullifier_address(&fixture.proof, format, &fixture.public_inputs),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            patient_index: patient_index_address(&patient),
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ReverifyEligibility {
+            proof: fixture.proof.clone(),
+            proof_format: format,
+            public_inputs: fixture.public_inputs.clone(),
+            ipfs_hash: ipfs_hash.to_string(),
+            circuit_id: circuit_id.to_string(),
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+        }
+        .data(),
+    }
+}
+
+/// The record `verify_eligibility` writes for `patient`'s `record_nonce`
+pub fn verification_address(patient: &Pubkey, record_nonce: u64) -> Pubkey {
+    let eligibility = zk_healthcare::VerificationType::Eligibility;
+    zk_healthcare::derive_verification_pda(patient, eligibility, record_nonce).0
+}
+
+/// The record of the nonce before `record_nonce`, which a verification of
+/// `record_nonce` passes; none for the first
+pub fn previous_record_address(patient: &Pubkey, record_nonce: u64) -> Option<Pubkey> {
+    record_nonce.checked_sub(1).map(|nonce| verification_address(patient, nonce))
+}
+
+/// The `record_nonce` of `patient`'s next `verify_eligibility`
+pub async fn next_record_nonce(ctx: &mut ProgramTestContext, patient: Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(patient_index_address(&patient)).await.unwrap();
+    account.map_or(0, |account| {
+        let index = zk_healthcare::PatientIndex::try_deserialize(&mut &account.data[..]).unwrap();
+        index.next_record_nonce
+    })
+}
+
+/// A `verify_eligibility` instruction writing `patient`'s record at `record_nonce`
+#[allow(clippy::too_many_arguments)]
+pub fn verify_eligibility_ix(
+    registry: Pubkey,
+    record_nonce: u64,
+    circuit_id: &str,
+    patient: Pubkey,
+    proof: Vec<u8>,
+    proof_format: zk_healthcare::ProofFormat,
+    public_inputs: Vec<u8>,
+    ipfs_hash: &str,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyEligibility {
+            registry,
+            verification: verification_address(&patient, record_nonce),
+            verifying_key: vk_address(circuit_id),
+            nullifier: nullifier_address(&proof, proof_format, &public_inputs),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            cache: None,
+            commitment: None,
+            patient_index: patient_index_address(&patient),
+            previous_record: previous_record_address(&patient, record_nonce),
+            provider: None,
+            provider_registration: None,
+            instructions: None,
+            verification_mint: None,
+            patient_token_account: None,
+            token_program: None,
+            associated_token_program: None,
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyEligibility {
+            proof,
+            proof_format,
+            public_inputs,
+            ipfs_hash: ipfs_hash.to_string(),
+            circuit_id: circuit_id.to_string(),
+            record_nonce,
+            salt: None,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            mint_token: false,
+            attestation: None,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+/// A `verify_prescription` of `fixture` by `patient`; the record is at
+/// `PrescriptionRecord::address` of the submission
+pub fn verify_prescription_ix(
+    registry: Pubkey,
+    circuit_id: &str,
+    patient: Pubkey,
+    fixture: &Fixture,
+    drug_commitment: [u8; 32],
+    refills_allowed: u8,
+    valid_until: i64,
+) -> Instruction {
+    let format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyPrescription {
+            registry,
+            prescription: zk_healthcare::PrescriptionRecord::address(&fixture.proof, format, &fixture.public_inputs),
+            verifying_key: vk_address(circuit_id),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            patient_index: patient_index_address(&patient),
+            previous_record: None,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyPrescription {
+            proof: fixture.proof.clone(),
+            proof_format: format,
+            public_inputs: fixture.public_inputs.clone(),
+            circuit_id: circuit_id.to_string(),
+            drug_commitment,
+            refills_allowed,
+            valid_until,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+pub fn register_pharmacy_ix(authority: Pubkey, registry: Pubkey, pharmacy: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RegisterPharmacy {
+            registry,
+            registration: zk_healthcare::PharmacyRegistration::address(&registry, &pharmacy),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RegisterPharmacy { pharmacy }.data(),
+    }
+}
+
+pub fn record_refill_ix(registry: Pubkey, prescription: Pubkey, pharmacy: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RecordRefill {
+            registry,
+            prescription,
+            registration: zk_healthcare::PharmacyRegistration::address(&registry, &pharmacy),
+            pharmacy,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RecordRefill {}.data(),
+    }
+}
+
+/// A `verify_diagnosis` of `fixture` by `patient`; the record is at
+/// `DiagnosisRecord::address` of the submission
+pub fn verify_diagnosis_ix(
+    registry: Pubkey,
+    circuit_id: &str,
+    patient: Pubkey,
+    fixture: &Fixture,
+    icd10_commitment: [u8; 32],
+    diagnosing_provider: Pubkey,
+    pin_record: Option<Pubkey>,
+) -> Instruction {
+    let format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyDiagnosis {
+            registry,
+            diagnosis: zk_healthcare::DiagnosisRecord::address(&fixture.proof, format, &fixture.public_inputs),
+            verifying_key: vk_address(circuit_id),
+            provider_registration: zk_healthcare::ProviderRegistration::address(&registry, &diagnosing_provider),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            patient_index: patient_index_address(&patient),
+            pin_record,
+            previous_record: None,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyDiagnosis {
+            proof: fixture.proof.clone(),
+            proof_format: format,
+            public_inputs: fixture.public_inputs.clone(),
+            circuit_id: circuit_id.to_string(),
+            icd10_commitment,
+            diagnosing_provider,
+            ipfs_cid: "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string(),
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+pub fn verify_lab_result_ix(
+    registry: Pubkey,
+    circuit_id: &str,
+    patient: Pubkey,
+    fixture: &Fixture,
+    result_commitment: [u8; 32],
+    lab: Pubkey,
+) -> Instruction {
+    let format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyLabResult {
+            registry,
+            lab_result: zk_healthcare::LabResultRecord::address(&fixture.proof, format, &fixture.public_inputs),
+            verifying_key: vk_address(circuit_id),
+            provider_registration: zk_healthcare::ProviderRegistration::address(&registry, &lab),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            patient_index: patient_index_address(&patient),
+            previous_record: None,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyLabResult {
+            proof: fixture.proof.clone(),
+            proof_format: format,
+            public_inputs: fixture.public_inputs.clone(),
+            circuit_id: circuit_id.to_string(),
+            result_commitment,
+            lab,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+pub fn verify_immunization_ix(
+    registry: Pubkey,
+    circuit_id: &str,
+    patient: Pubkey,
+    fixture: &Fixture,
+    status_commitment: [u8; 32],
+    provider: Pubkey,
+) -> Instruction {
+    let format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyImmunization {
+            registry,
+            immunization: zk_healthcare::ImmunizationRecord::address(&fixture.proof, format, &fixture.public_inputs),
+            verifying_key: vk_address(circuit_id),
+            provider_registration: zk_healthcare::ProviderRegistration::address(&registry, &provider),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            patient_index: patient_index_address(&patient),
+            previous_record: None,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyImmunization {
+            proof: fixture.proof.clone(),
+            proof_format: format,
+            public_inputs: fixture.public_inputs.clone(),
+            circuit_id: circuit_id.to_string(),
+            status_commitment,
+            provider,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+pub fn revoke_diagnosis_ix(
+    revoker: Pubkey,
+    registry: Pubkey,
+    diagnosis: Pubkey,
+    patient: Pubkey,
+    reason_code: u16,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RevokeDiagnosis {
+            registry,
+            diagnosis,
+            patient_index: patient_index_address(&patient),
+            revoker,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RevokeDiagnosis { reason_code }.data(),
+    }
+}
+
+pub fn register_insurer_ix(authority: Pubkey, registry: Pubkey, insurer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RegisterInsurer {
+            registry,
+            registration: zk_healthcare::InsurerRegistration::address(&registry, &insurer),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RegisterInsurer { insurer }.data(),
+    }
+}
+
+pub fn set_dispute_window_ix(authority: Pubkey, registry: Pubkey, window_secs: i64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetDisputeWindow { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetDisputeWindow { window_secs }.data(),
+    }
+}
+
+pub fn set_verification_cooldown_ix(authority: Pubkey, registry: Pubkey, cooldown_secs: i64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetVerificationCooldown { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetVerificationCooldown { cooldown_secs }.data(),
+    }
+}
+
+pub fn set_time_source_ix(authority: Pubkey, registry: Pubkey, time_source: zk_healthcare::TimeSource) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetTimeSource { registry, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::SetTimeSource { time_source }.data(),
+    }
+}
+
+/// `disputer` contesting `patient`'s `verification`, as a registered insurer
+/// when `insurer` is set and as the registry authority otherwise
+pub fn dispute_verification_ix(
+    registry: Pubkey,
+    verification: Pubkey,
+    patient: Pubkey,
+    disputer: Pubkey,
+    insurer: bool,
+    evidence_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::DisputeVerification {
+            registry,
+            verification,
+            dispute: zk_healthcare::VerificationDispute::address(&verification),
+            patient_index: patient_index_address(&patient),
+            insurer_registration: insurer.then(|| zk_healthcare::InsurerRegistration::address(&registry, &disputer)),
+            disputer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::DisputeVerification { evidence_hash }.data(),
+    }
+}
+
+pub fn resolve_dispute_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    verification: Pubkey,
+    patient: Pubkey,
+    disputer: Pubkey,
+    upheld: bool,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ResolveDispute {
+            registry,
+            verification,
+            dispute: zk_healthcare::VerificationDispute::address(&verification),
+            patient_index: patient_index_address(&patient),
+            disputer,
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ResolveDispute { upheld }.data(),
+    }
+}
+
+/// File claim number `claim_id` of `registry` against `patient`'s `verification`
+pub fn submit_claim_ix(
+    registry: Pubkey,
+    verification: Pubkey,
+    patient: Pubkey,
+    claim_id: u64,
+    amount: u64,
+    service_code_commitment: [u8; 32],
+    claim_nullifier: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SubmitClaim {
+            registry,
+            verification,
+            claim: zk_healthcare::ClaimRecord::address(&registry, claim_id),
+            nullifier: zk_healthcare::ClaimNullifier::address(&registry, &claim_nullifier),
+            patient,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::SubmitClaim { amount, service_code_commitment, claim_nullifier }.data(),
+    }
+}
+
+pub fn designate_claim_payee_ix(registry: Pubkey, claim: Pubkey, patient: Pubkey, provider: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::DesignateClaimPayee {
+            claim,
+            provider_registration: zk_healthcare::ProviderRegistration::address(&registry, &provider),
+            patient,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::DesignateClaimPayee {}.data(),
+    }
+}
+
+/// Escrow `lamports` for `claim` of `registry` from `insurer`
+pub fn fund_claim_escrow_ix(registry: Pubkey, claim: Pubkey, insurer: Pubkey, lamports: u64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::FundClaimEscrow {
+            registry,
+            claim,
+            escrow: zk_healthcare::ClaimEscrow::address(&claim),
+            insurer_registration: zk_healthcare::InsurerRegistration::address(&registry, &insurer),
+            insurer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::FundClaimEscrow { lamports }.data(),
+    }
+}
+
+/// Release the nullifier of `claim`, filed by `patient` under `registry`
+pub fn release_claim_nullifier_ix(
+    registry: Pubkey,
+    claim: Pubkey,
+    patient: Pubkey,
+    insurer: Pubkey,
+    claim_nullifier: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ReleaseClaimNullifier {
+            registry,
+            claim,
+            nullifier: zk_healthcare::ClaimNullifier::address(&registry, &claim_nullifier),
+            patient,
+            insurer,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ReleaseClaimNullifier {}.data(),
+    }
+}
+
+pub fn commit_checkpoint_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    root: [u8; 32],
+    start_seq: u64,
+    end_seq: u64,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CommitCheckpoint {
+            registry,
+            checkpoint: zk_healthcare::Checkpoint::address(&registry, start_seq),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CommitCheckpoint { root, start_seq, end_seq }.data(),
+    }
+}
+
+pub fn adjudicate_claim_ix(
+    registry: Pubkey,
+    claim: Pubkey,
+    payee: Pubkey,
+    insurer: Pubkey,
+    approve: bool,
+    reason_code: u16,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::AdjudicateClaim {
+            registry,
+            claim,
+            escrow: zk_healthcare::ClaimEscrow::address(&claim),
+            payee,
+            insurer,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::AdjudicateClaim { approve, reason_code }.data(),
+    }
+}
+
+pub fn register_provider_ix(authority: Pubkey, registry: Pubkey, provider: Pubkey) -> Instruction {
+    register_eth_provider_ix(authority, registry, provider, None)
+}
+
+/// `register_provider_ix`, also naming the provider's Ethereum address
+pub fn register_eth_provider_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    provider: Pubkey,
+    eth_address: Option<[u8; 20]>,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RegisterProvider {
+            registry,
+            registration: zk_healthcare::ProviderRegistration::address(&registry, &provider),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RegisterProvider { provider, eth_address }.data(),
+    }
+}
+
+pub fn set_provider_active_ix(authority: Pubkey, registry: Pubkey, provider: Pubkey, active: bool) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetProviderActive {
+            registry,
+            registration: zk_healthcare::ProviderRegistration::address(&registry, &provider),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::SetProviderActive { active }.data(),
+    }
+}
+
+pub fn set_provider_quota_ix(authority: Pubkey, registry: Pubkey, provider: Pubkey, daily_quota: u32) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetProviderQuota {
+            registry,
+            registration: zk_healthcare::ProviderRegistration::address(&registry, &provider),
+            authority,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::SetProviderQuota { daily_quota }.data(),
+    }
+}
+
+/// `patient` pins `ipfs_cid` at `IpfsPinRecord::address(patient, data_hash)`
+pub fn pin_medical_data_ix(registry: Pubkey, patient: Pubkey, ipfs_cid: &str, data_hash: [u8; 32]) -> Instruction {
+    pin_medical_data_paid_ix(registry, patient, ipfs_cid, data_hash, 0, None)
+}
+
+/// `pin_medical_data_ix` paying `storage_lamports` to `storage_treasury`
+pub fn pin_medical_data_paid_ix(
+    registry: Pubkey,
+    patient: Pubkey,
+    ipfs_cid: &str,
+    data_hash: [u8; 32],
+    storage_lamports: u64,
+    storage_treasury: Option<Pubkey>,
+) -> Instruction {
+    pin_ix(registry, patient, ipfs_cid, data_hash, storage_lamports, storage_treasury, 0, DataCategory::Other, None)
+}
+
+/// `pin_medical_data_ix` escrowing `pin_bounty_lamports` for its pinner
+pub fn pin_medical_data_with_bounty_ix(
+    registry: Pubkey,
+    patient: Pubkey,
+    ipfs_cid: &str,
+    data_hash: [u8; 32],
+    pin_bounty_lamports: u64,
+) -> Instruction {
+    pin_ix(registry, patient, ipfs_cid, data_hash, 0, None, pin_bounty_lamports, DataCategory::Other, None)
+}
+
+/// `pin_medical_data_ix` filing the pin under `category` and `tag`
+pub fn pin_medical_data_tagged_ix(
+    registry: Pubkey,
+    patient: Pubkey,
+    ipfs_cid: &str,
+    data_hash: [u8; 32],
+    category: DataCategory,
+    tag: Option<[u8; 8]>,
+) -> Instruction {
+    pin_ix(registry, patient, ipfs_cid, data_hash, 0, None, 0, category, tag)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pin_ix(
+    registry: Pubkey,
+    patient: Pubkey,
+    ipfs_cid: &str,
+    data_hash: [u8; 32],
+    storage_lamports: u64,
+    storage_treasury: Option<Pubkey>,
+    pin_bounty_lamports: u64,
+    category: DataCategory,
+    tag: Option<[u8; 8]>,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::PinMedicalData {
+            registry,
+            pin_record: zk_healthcare::IpfsPinRecord::address(&patient, &data_hash),
+            content_claim: zk_healthcare::PinContentClaim::address(&patient, &data_hash),
+            patient_index: patient_index_address(&patient),
+            patient,
+            system_program: system_program::ID,
+            storage_treasury,
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::PinMedicalData {
+            ipfs_cid: ipfs_cid.to_string(),
+            data_hash,
+            storage_lamports,
+            pin_bounty_lamports,
+            category,
+            tag,
+        }
+        .data(),
+    }
+}
+
+/// `patient` pins `storage_id` on `backend` at `IpfsPinRecord::address(patient,
+/// data_hash)`
+pub fn pin_medical_data_on_ix(
+    registry: Pubkey,
+    patient: Pubkey,
+    backend: zk_healthcare::StorageBackend,
+    storage_id: &[u8],
+    data_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::PinMedicalDataOn {
+            registry,
+            pin_record: zk_healthcare::IpfsPinRecord::address(&patient, &data_hash),
+            content_claim: zk_healthcare::PinContentClaim::address(&patient, &data_hash),
+            patient_index: patient_index_address(&patient),
+            patient,
+            system_program: system_program::ID,
+            storage_treasury: None,
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::PinMedicalDataOn {
+            backend,
+            storage_id: storage_id.to_vec(),
+            data_hash,
+            storage_lamports: 0,
+            pin_bounty_lamports: 0,
+            category: DataCategory::Other,
+            tag: None,
+        }
+        .data(),
+    }
+}
+
+/// `patient` pins each of `entries`, passing their pin record and content
+/// claim PDAs in order
+pub fn pin_medical_data_bulk_ix(registry: Pubkey, patient: Pubkey, entries: &[zk_healthcare::PinEntry]) -> Instruction {
+    let mut accounts = zk_healthcare::accounts::PinMedicalDataBulk {
+        registry,
+        patient_index: patient_index_address(&patient),
+        patient,
+        system_program: system_program::ID,
+        storage_treasury: None,
+        event_authority: zk_healthcare::event_authority_address(),
+        program: zk_healthcare::ID,
+    }
+    .to_account_metas(None);
+    for entry in entries {
+        let pin_record = zk_healthcare::IpfsPinRecord::address(&patient, &entry.data_hash);
+        let content_claim = zk_healthcare::PinContentClaim::address(&patient, &entry.data_hash);
+        accounts.extend([AccountMeta::new(pin_record, false), AccountMeta::new(content_claim, false)]);
+    }
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts,
+        data: zk_healthcare::instruction::PinMedicalDataBulk {
+            entries: entries.to_vec(),
+            storage_lamports: 0,
+        }
+        .data(),
+    }
+}
+
+/// `pin_medical_data_bulk_ix` of the payer's as a v0 transaction, with every
+/// account but the payer and the program in a lookup table
+pub async fn pin_medical_data_bulk_v0(
+    ctx: &mut ProgramTestContext,
+    registry: Pubkey,
+    entries: &[zk_healthcare::PinEntry],
+) -> VersionedTransaction {
+    let patient = ctx.payer.pubkey();
+    let ix = pin_medical_data_bulk_ix(registry, patient, entries);
+    let addresses =
+        ix.accounts.iter().map(|meta| meta.pubkey).filter(|key| *key != patient && *key != zk_healthcare::ID).collect();
+    let table = lookup_table(ctx, addresses).await;
+    v0_transaction(ctx, &[ix], &table).await
+}
+
+/// `patient` closes `pin_record` of `data_hash`, stored under `pin_patient`
+/// or a key that rotated to `patient`, refunding its rent to `refund_to`, its
+/// `rent_payer` or the key it rotated to
+pub fn unpin_medical_data_ix(
+    registry: Pubkey,
+    pin_record: Pubkey,
+    data_hash: [u8; 32],
+    pin_patient: Pubkey,
+    patient: Pubkey,
+    rent_payer: Pubkey,
+    refund_to: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::UnpinMedicalData {
+            registry,
+            pin_record,
+            patient_rotation: zk_healthcare::KeyRotation::address(&pin_patient),
+            rent_payer_rotation: zk_healthcare::KeyRotation::address(&rent_payer),
+            rent_payer: refund_to,
+            patient,
+            patient_index: patient_index_address(&pin_patient),
+            content_claim: zk_healthcare::PinContentClaim::address(&pin_patient, &data_hash),
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::UnpinMedicalData {}.data(),
+    }
+}
+
+/// A `verify_access_control` of `fixture` by `patient` for a pass to `resource`
+pub fn verify_access_control_ix(
+    registry: Pubkey,
+    circuit_id: &str,
+    patient: Pubkey,
+    fixture: &Fixture,
+    resource: Pubkey,
+    valid_slots: u64,
+    uses: u16,
+) -> Instruction {
+    let format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyAccessControl {
+            registry,
+            access_pass: zk_healthcare::AccessPass::address(&patient, &resource),
+            verifying_key: vk_address(circuit_id),
+            nullifier: nullifier_address(&fixture.proof, format, &fixture.public_inputs),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            patient_index: patient_index_address(&patient),
+            pin_record: None,
+            previous_record: None,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyAccessControl {
+            proof: fixture.proof.clone(),
+            proof_format: format,
+            public_inputs: fixture.public_inputs.clone(),
+            circuit_id: circuit_id.to_string(),
+            resource,
+            valid_slots,
+            uses,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+/// `resource_signer` presents `access_pass` as `resource`
+pub fn consume_access_pass_ix(
+    registry: Pubkey,
+    access_pass: Pubkey,
+    resource: Pubkey,
+    resource_signer: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ConsumeAccessPass {
+            registry,
+            access_pass,
+            resource_signer,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ConsumeAccessPass { resource }.data(),
+    }
+}
+
+/// `ix` from `verify_access_control_ix` passing its resource as the pin record
+/// it is, in place of the program id that marks the account as omitted
+pub fn with_pin_record(mut ix: Instruction, pin_record: Pubkey) -> Instruction {
+    let slot = ix.accounts.len() - 2;
+    ix.accounts[slot] = AccountMeta::new(pin_record, false);
+    ix
+}
+
+/// `ix` from `verify_prescription_ix`, `verify_diagnosis_ix`,
+/// `verify_lab_result_ix`, `verify_immunization_ix` or
+/// `verify_access_control_ix` passing the patient's `previous_record` of its
+/// type, in place of the program id that marks the account as omitted
+pub fn with_previous_record(mut ix: Instruction, previous_record: Pubkey) -> Instruction {
+    let slot = ix.accounts.len() - 1;
+    ix.accounts[slot] = AccountMeta::new(previous_record, false);
+    ix
+}
+
+/// `patient`'s latest record of `verification_type`, their
+/// `last_record_by_type`, if they have one
+pub async fn latest_record(
+    ctx: &mut ProgramTestContext,
+    patient: Pubkey,
+    verification_type: zk_healthcare::VerificationType,
+) -> Option<Pubkey> {
+    let account = ctx.banks_client.get_account(patient_index_address(&patient)).await.unwrap()?;
+    let index = zk_healthcare::PatientIndex::try_deserialize(&mut &account.data[..]).unwrap();
+    Some(index.last_record_by_type[verification_type as usize]).filter(|record| *record != Pubkey::default())
+}
+
+/// `ix` from a typed verify builder passing `patient`'s latest record of
+/// `verification_type`, if they have one
+pub async fn after_latest_record(
+    ctx: &mut ProgramTestContext,
+    ix: Instruction,
+    patient: Pubkey,
+    verification_type: zk_healthcare::VerificationType,
+) -> Instruction {
+    match latest_record(ctx, patient, verification_type).await {
+        Some(previous_record) => with_previous_record(ix, previous_record),
+        None => ix,
+    }
+}
+
+/// `accessor` reads `pin_record` on its `AccessPass` to the pin, or on none
+pub fn record_access_ix(registry: Pubkey, pin_record: Pubkey, accessor: Pubkey, with_pass: bool) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RecordAccess {
+            registry,
+            pin_record,
+            access_pass: with_pass.then(|| zk_healthcare::AccessPass::address(&accessor, &pin_record)),
+            accessor,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RecordAccess {}.data(),
+    }
+}
+
+pub fn confirm_pin_ix(
+    registry: Pubkey,
+    pin_record: Pubkey,
+    oracle: Pubkey,
+    success: bool,
+    provider_id: &str,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ConfirmPin {
+            registry,
+            pin_record,
+            oracle,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ConfirmPin {
+            success,
+            provider_id: provider_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+pub fn retry_pin_ix(registry: Pubkey, pin_record: Pubkey, patient: Pubkey, new_cid: Option<&str>) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RetryPin {
+            registry,
+            pin_record,
+            patient,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RetryPin {
+            new_cid: new_cid.map(str::to_string),
+        }
+        .data(),
+    }
+}
+
+/// `patient` files `pin_record`, pinned by `pin_patient`, under `category`
+pub fn retag_pin_ix(
+    registry: Pubkey,
+    pin_record: Pubkey,
+    pin_patient: Pubkey,
+    patient: Pubkey,
+    category: DataCategory,
+    tag: Option<[u8; 8]>,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RetagPin {
+            registry,
+            pin_record,
+            patient_index: patient_index_address(&pin_patient),
+            patient,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RetagPin { category, tag }.data(),
+    }
+}
+
+/// `patient` updates `pin_record`, holding `data_hash`, to `new_cid`
+pub fn update_pin_ix(
+    registry: Pubkey,
+    pin_record: Pubkey,
+    data_hash: [u8; 32],
+    patient: Pubkey,
+    new_cid: &str,
+    new_data_hash: [u8; 32],
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::UpdatePin {
+            registry,
+            pin_record,
+            new_content_pin: zk_healthcare::IpfsPinRecord::address(&patient, &new_data_hash),
+            content_claim: zk_healthcare::PinContentClaim::address(&patient, &data_hash),
+            new_content_claim: zk_healthcare::PinContentClaim::address(&patient, &new_data_hash),
+            patient,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::UpdatePin {
+            new_cid: new_cid.to_string(),
+            new_data_hash,
+        }
+        .data(),
+    }
+}
+
+/// `cranker` abandons the failed `pin_record` of `patient` and `data_hash`,
+/// refunding its `rent_payer`, neither of them rotated
+pub fn abandon_failed_pin_ix(
+    registry: Pubkey,
+    pin_record: Pubkey,
+    data_hash: [u8; 32],
+    patient: Pubkey,
+    rent_payer: Pubkey,
+    cranker: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::AbandonFailedPin {
+            registry,
+            pin_record,
+            patient_rotation: zk_healthcare::KeyRotation::address(&patient),
+            rent_payer_rotation: zk_healthcare::KeyRotation::address(&rent_payer),
+            rent_payer,
+            cranker,
+            patient_index: patient_index_address(&patient),
+            patient,
+            content_claim: zk_healthcare::PinContentClaim::address(&patient, &data_hash),
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::AbandonFailedPin {}.data(),
+    }
+}
+
+pub fn renew_pin_ix(
+    registry: Pubkey,
+    pin_record: Pubkey,
+    payer: Pubkey,
+    storage_treasury: Pubkey,
+    days: u32,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RenewPin {
+            registry,
+            pin_record,
+            payer,
+            storage_treasury,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RenewPin { days }.data(),
+    }
+}
+
+pub fn expire_pin_ix(registry: Pubkey, pin_record: Pubkey, cranker: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ExpirePin {
+            registry,
+            pin_record,
+            cranker,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ExpirePin {}.data(),
+    }
+}
+
+pub fn report_unpin_ix(registry: Pubkey, pin_record: Pubkey, oracle: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ReportUnpin {
+            registry,
+            pin_record,
+            oracle,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ReportUnpin {}.data(),
+    }
+}
+
+pub fn claim_pin_bounty_ix(registry: Pubkey, pin_record: Pubkey, oracle: Pubkey, provider_id: &str) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ClaimPinBounty {
+            registry,
+            pin_record,
+            oracle,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ClaimPinBounty {
+            provider_id: provider_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+pub fn set_storage_deal_ix(
+    registry: Pubkey,
+    pin_record: Pubkey,
+    oracle: Pubkey,
+    deal_id: u64,
+    provider: &str,
+    deal_expiry: i64,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::SetStorageDeal {
+            registry,
+            pin_record,
+            oracle,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::SetStorageDeal {
+            deal_id,
+            provider: provider.to_string(),
+            deal_expiry,
+        }
+        .data(),
+    }
+}
+
+pub fn deal_expiring_soon_ix(registry: Pubkey, pin_record: Pubkey, cranker: Pubkey, deal_id: u64) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::DealExpiringSoon {
+            registry,
+            pin_record,
+            cranker,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::DealExpiringSoon { deal_id }.data(),
+    }
+}
+
+/// Confirm `pin_record` pinned with the payer, the registry's authority, as
+/// its oracle, listing the payer first if it isn't yet
+pub async fn confirm_pin(ctx: &mut ProgramTestContext, registry: Pubkey, pin_record: Pubkey) {
+    let payer = ctx.payer.pubkey();
+    let state: zk_healthcare::HealthcareRegistry = fetch(ctx, registry).await;
+    if !state.pinning_oracles.contains(&payer) {
+        send(ctx, &[add_pinning_oracle_ix(payer, registry, payer)], &[]).await.unwrap();
+    }
+    send(ctx, &[confirm_pin_ix(registry, pin_record, payer, true, "test-cluster")], &[]).await.unwrap();
+}
+
+pub fn credential_nullifier_address(circuit_id: &str, credential: &[u8; 32], epoch: u64) -> Pubkey {
+    let (vk, epoch) = (vk_address(circuit_id), epoch.to_le_bytes());
+    Pubkey::find_program_address(&[b"credential", vk.as_ref(), credential, &epoch], &zk_healthcare::ID).0
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn verify_eligibility_anonymous_ix(
+    registry: Pubkey,
+    verification: Pubkey,
+    circuit_id: &str,
+    relayer: Pubkey,
+    proof: Vec<u8>,
+    public_inputs: Vec<u8>,
+    credential: [u8; 32],
+    epoch: u64,
+) -> Instruction {
+    let proof_format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyEligibilityAnonymous {
+            registry,
+            verification,
+            verifying_key: vk_address(circuit_id),
+            nullifier: nullifier_address(&proof, proof_format, &public_inputs),
+            credential_nullifier: credential_nullifier_address(circuit_id, &credential, epoch),
+            previous_credential_nullifier: epoch
+                .checked_sub(1)
+                .map(|previous| credential_nullifier_address(circuit_id, &credential, previous)),
+            relayer,
+            system_program: system_program::ID,
+            fee_recipient: None,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyEligibilityAnonymous {
+            proof_format,
+            submission: zk_healthcare::ProofSubmission {
+                proof,
+                public_inputs,
+                ipfs_hash: "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string(),
+            },
+            circuit_id: circuit_id.to_string(),
+            credential,
+            epoch,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+        }
+        .data(),
+    }
+}
+
+/// `ix` from `verify_eligibility_ix`, `verify_eligibility_batch_ix` or
+/// `complete_verification_ix`, which all take `hash_algo` just ahead of
+/// `force_new` but for `verify_eligibility`'s `mint_token` and unset
+/// `attestation` between them, with the proof hash computed by `hash_algo`
+/// instead of keccak
+pub fn with_hash_algo(mut ix: Instruction, hash_algo: zk_healthcare::HashAlgo) -> Instruction {
+    let trailing = if is_verify_eligibility(&ix) { 3 } else { 1 };
+    let at = ix.data.len() - 1 - trailing;
+    ix.data[at] = hash_algo as u8;
+    ix
+}
+
+fn is_verify_eligibility(ix: &Instruction) -> bool {
+    use anchor_lang::Discriminator;
+    ix.data.starts_with(&zk_healthcare::instruction::VerifyEligibility::DISCRIMINATOR)
+}
+
+/// `ix` from one of the same builders, paying the circuit's fee to `fee_recipient`
+/// in place of the program id that marks the account as omitted
+pub fn with_fee_recipient(mut ix: Instruction, fee_recipient: Pubkey) -> Instruction {
+    let slot = ix
+        .accounts
+        .iter()
+        .position(|meta| meta.pubkey == zk_healthcare::ID)
+        .expect("fee recipient slot");
+    ix.accounts[slot] = AccountMeta::new(fee_recipient, false);
+    ix
+}
+
+/// `ix` from `verify_eligibility_ix` with the proof's cache account filled in
+pub fn with_cache(mut ix: Instruction, proof_hash: [u8; 32]) -> Instruction {
+    ix.accounts[7] = AccountMeta::new(cache_address(&proof_hash), false);
+    ix
+}
+
+/// `ix` from `verify_eligibility_ix` revealing `submitter`'s commitment with `salt`
+pub fn with_commitment(mut ix: Instruction, submitter: Pubkey, salt: [u8; 32]) -> Instruction {
+    ix.accounts[8] = AccountMeta::new(commitment_address(&submitter), false);
+    // `salt` is the `None` just ahead of the one-byte `hash_algo`, `mint_token`,
+    // unset `attestation` and `force_new`
+    let tail = ix.data.split_off(ix.data.len() - 4);
+    assert_eq!(ix.data.pop(), Some(0));
+    ix.data.push(1);
+    ix.data.extend_from_slice(&salt);
+    ix.data.extend_from_slice(&tail);
+    ix
+}
+
+/// `ix` from `verify_eligibility_ix` co-signed by `provider`, passed with its
+/// registration under `registry`
+pub fn with_provider(mut ix: Instruction, registry: Pubkey, provider: Pubkey) -> Instruction {
+    let accounts = ix.accounts.len() - EVENT_CPI_ACCOUNTS;
+    ix.accounts[accounts - 7] = AccountMeta::new_readonly(provider, true);
+    let registration = zk_healthcare::ProviderRegistration::address(&registry, &provider);
+    ix.accounts[accounts - 6] = AccountMeta::new(registration, false);
+    ix
+}
+
+/// `ix` from `verify_eligibility_ix` carrying `attestation`, signed by its
+/// provider in the transaction's instruction at `attestation.sig_index`. Apply it
+/// after the other `with_` helpers, which expect the attestation unset.
+pub fn with_attestation(
+    mut ix: Instruction,
+    registry: Pubkey,
+    attestation: zk_healthcare::ProviderAttestation,
+) -> Instruction {
+    let accounts = ix.accounts.len() - EVENT_CPI_ACCOUNTS;
+    let registration = zk_healthcare::ProviderRegistration::address(&registry, &attestation.provider);
+    ix.accounts[accounts - 6] = AccountMeta::new(registration, false);
+    ix.accounts[accounts - 5] = AccountMeta::new_readonly(solana_sdk::sysvar::instructions::ID, false);
+    let force_new = ix.data.pop().unwrap();
+    assert_eq!(ix.data.pop(), Some(0));
+    Some(attestation).serialize(&mut ix.data).unwrap();
+    ix.data.push(force_new);
+    ix
+}
+
+/// An `ed25519_program` instruction checking `signer`'s signature over
+/// `message`, with the key, signature and message all inside it
+pub fn ed25519_ix(signer: &Keypair, message: &[u8]) -> Instruction {
+    const HEADER_LEN: u16 = 2 + 14;
+    let (key_at, signature_at) = (HEADER_LEN, HEADER_LEN + 32);
+    let message_at = signature_at + 64;
+    let mut data = vec![1, 0];
+    for offset in [signature_at, u16::MAX, key_at, u16::MAX, message_at, message.len() as u16, u16::MAX] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: solana_sdk::ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// `ix` from `verify_eligibility_ix` minting the patient a token of `registry`'s
+/// verification mint
+pub fn with_verification_token(mut ix: Instruction, registry: Pubkey, patient: Pubkey) -> Instruction {
+    let event_cpi = ix.accounts.split_off(ix.accounts.len() - EVENT_CPI_ACCOUNTS);
+    let accounts = ix.accounts.len();
+    ix.accounts.truncate(accounts - 4);
+    ix.accounts.extend([
+        AccountMeta::new(verification_mint_address(&registry), false),
+        AccountMeta::new(verification_token_account(&registry, &patient), false),
+        AccountMeta::new_readonly(anchor_spl::token_2022::ID, false),
+        AccountMeta::new_readonly(anchor_spl::associated_token::ID, false),
+    ]);
+    ix.accounts.extend(event_cpi);
+    let mint_token = ix.data.len() - 3;
+    ix.data[mint_token] = 1;
+    ix
+}
+
+/// `ix` from `verify_eligibility_ix`, `verify_eligibility_for_ward_ix`,
+/// `verify_eligibility_batch_ix`, `complete_verification_ix` or a typed verify
+/// builder with `force_new` set, retiring the patient's previous record of the
+/// type if it is still active
+pub fn with_force_new(mut ix: Instruction) -> Instruction {
+    *ix.data.last_mut().unwrap() = 1;
+    ix
+}
+
+/// Ethereum address of `secret_key`: the last 20 bytes of the keccak of its
+/// uncompressed public key
+pub fn eth_address(secret_key: &libsecp256k1::SecretKey) -> [u8; 20] {
+    let public_key = libsecp256k1::PublicKey::from_secret_key(secret_key).serialize();
+    keccak::hash(&public_key[1..]).0[12..].try_into().unwrap()
+}
+
+/// A `secp256k1_program` instruction, at `index` in its transaction, checking
+/// `secret_key`'s signature over `message` with the address, signature and
+/// message all inside it. With `high_s` the signature is swapped for its
+/// malleated twin, which recovers the same address.
+pub fn secp256k1_ix(secret_key: &libsecp256k1::SecretKey, message: &[u8], index: u8, high_s: bool) -> Instruction {
+    let digest = libsecp256k1::Message::parse(&keccak::hash(message).0);
+    let (mut signature, mut recovery_id) = libsecp256k1::sign(&digest, secret_key);
+    if high_s {
+        signature.s = -signature.s;
+        recovery_id = libsecp256k1::RecoveryId::parse(recovery_id.serialize() ^ 1).unwrap();
+    }
+    const HEADER_LEN: u16 = 1 + 11;
+    let (address_at, signature_at) = (HEADER_LEN, HEADER_LEN + 20);
+    let message_at = signature_at + 65;
+    let mut data = vec![1];
+    data.extend_from_slice(&signature_at.to_le_bytes());
+    data.push(index);
+    data.extend_from_slice(&address_at.to_le_bytes());
+    data.push(index);
+    data.extend_from_slice(&message_at.to_le_bytes());
+    data.extend_from_slice(&(message.len() as u16).to_le_bytes());
+    data.push(index);
+    data.extend_from_slice(&eth_address(secret_key));
+    data.extend_from_slice(&signature.serialize());
+    data.push(recovery_id.serialize());
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: solana_sdk::secp256k1_program::ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+pub fn verification_mint_address(registry: &Pubkey) -> Pubkey {
+    zk_healthcare::derive_verification_mint(registry).0
+}
+
+/// `patient`'s Token-2022 associated token account of `registry`'s verification mint
+pub fn verification_token_account(registry: &Pubkey, patient: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address_with_program_id(
+        patient,
+        &verification_mint_address(registry),
+        &anchor_spl::token_2022::ID,
+    )
+}
+
+pub fn initialize_verification_mint_ix(authority: Pubkey, registry: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::InitializeVerificationMint {
+            registry,
+            verification_mint: verification_mint_address(&registry),
+            authority,
+            token_program: anchor_spl::token_2022::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::InitializeVerificationMint {}.data(),
+    }
+}
+
+/// Burn `patient`'s token for the revoked `verification`
+pub fn burn_verification_token_ix(registry: Pubkey, verification: Pubkey, patient: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::BurnVerificationToken {
+            registry,
+            verification,
+            verification_mint: verification_mint_address(&registry),
+            patient_token_account: verification_token_account(&registry, &patient),
+            token_program: anchor_spl::token_2022::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::BurnVerificationToken {}.data(),
+    }
+}
+
+pub fn rotate_patient_key_ix(registry: Pubkey, patient: Pubkey, new_key: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RotatePatientKey {
+            registry,
+            rotation: zk_healthcare::KeyRotation::address(&patient),
+            new_key_rotation: zk_healthcare::KeyRotation::address(&new_key),
+            patient,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RotatePatientKey { new_key }.data(),
+    }
+}
+
+/// `new_patient` claims `old_key`'s `records`, each given with the address it
+/// moves to and the record after it in the old key's chain, if any; the first
+/// goes in the named accounts and the rest after them
+pub fn claim_record_ix(
+    registry: Pubkey,
+    old_key: Pubkey,
+    new_patient: Pubkey,
+    records: &[(Pubkey, Pubkey, Option<Pubkey>)],
+) -> Instruction {
+    let mut accounts = zk_healthcare::accounts::ClaimRotatedRecord {
+        registry,
+        rotation: zk_healthcare::KeyRotation::address(&old_key),
+        record: records[0].0,
+        new_record: records[0].1,
+        old_index: patient_index_address(&old_key),
+        new_index: patient_index_address(&new_patient),
+        new_patient,
+        system_program: system_program::ID,
+        next_record: records[0].2,
+    }
+    .to_account_metas(None);
+    for (record, new_record, next_record) in &records[1..] {
+        accounts.push(AccountMeta::new(*record, false));
+        accounts.push(AccountMeta::new(*new_record, false));
+        accounts.push(match next_record {
+            Some(next_record) => AccountMeta::new(*next_record, false),
+            None => AccountMeta::new_readonly(zk_healthcare::ID, false),
+        });
+    }
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts,
+        data: zk_healthcare::instruction::ClaimRecord {}.data(),
+    }
+}
+
+pub fn claim_access_pass_ix(old_key: Pubkey, new_patient: Pubkey, resource: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ClaimAccessPass {
+            rotation: zk_healthcare::KeyRotation::address(&old_key),
+            access_pass: zk_healthcare::AccessPass::address(&old_key, &resource),
+            new_access_pass: zk_healthcare::AccessPass::address(&new_patient, &resource),
+            new_patient,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ClaimAccessPass {}.data(),
+    }
+}
+
+/// `new_patient` claims `pin_record`, holding `data_hash`, from `old_key`
+pub fn claim_pin_record_ix(
+    old_key: Pubkey,
+    pin_record: Pubkey,
+    data_hash: [u8; 32],
+    new_patient: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ClaimPinRecord {
+            rotation: zk_healthcare::KeyRotation::address(&old_key),
+            pin_record,
+            old_patient_index: patient_index_address(&old_key),
+            new_patient_index: patient_index_address(&new_patient),
+            content_claim: zk_healthcare::PinContentClaim::address(&old_key, &data_hash),
+            new_content_claim: zk_healthcare::PinContentClaim::address(&new_patient, &data_hash),
+            new_patient,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ClaimPinRecord {}.data(),
+    }
+}
+
+/// `granter` lets `guardian` act for `ward`; a provider granter passes its
+/// registration with `as_provider`
+pub fn grant_guardian_consent_ix(
+    registry: Pubkey,
+    granter: Pubkey,
+    guardian: Pubkey,
+    ward: Pubkey,
+    scopes: u8,
+    expires_at: i64,
+    as_provider: bool,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::GrantGuardianConsent {
+            registry,
+            consent: zk_healthcare::GuardianConsent::address(&registry, &guardian, &ward),
+            granter,
+            provider_registration: as_provider
+                .then(|| zk_healthcare::ProviderRegistration::address(&registry, &granter)),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::GrantGuardianConsent { guardian, ward, scopes, expires_at }.data(),
+    }
+}
+
+pub fn revoke_guardian_consent_ix(registry: Pubkey, consent: Pubkey, revoker: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RevokeGuardianConsent { registry, consent, revoker }.to_account_metas(None),
+        data: zk_healthcare::instruction::RevokeGuardianConsent {}.data(),
+    }
+}
+
+/// `verify_eligibility_ix` signed by `guardian` for its `ward`
+pub fn verify_eligibility_for_ward_ix(
+    registry: Pubkey,
+    record_nonce: u64,
+    circuit_id: &str,
+    guardian: Pubkey,
+    ward: Pubkey,
+    fixture: &Fixture,
+    ipfs_hash: &str,
+) -> Instruction {
+    let proof_format = zk_healthcare::ProofFormat::Uncompressed;
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyEligibilityForWard {
+            registry,
+            consent: zk_healthcare::GuardianConsent::address(&registry, &guardian, &ward),
+            verification: verification_address(&ward, record_nonce),
+            verifying_key: vk_address(circuit_id),
+            nullifier: nullifier_address(&fixture.proof, proof_format, &fixture.public_inputs),
+            patient_index: patient_index_address(&ward),
+            previous_record: previous_record_address(&ward, record_nonce),
+            guardian,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyEligibilityForWard {
+            proof: fixture.proof.clone(),
+            proof_format,
+            public_inputs: fixture.public_inputs.clone(),
+            ipfs_hash: ipfs_hash.to_string(),
+            circuit_id: circuit_id.to_string(),
+            record_nonce,
+            hash_algo: zk_healthcare::HashAlgo::Keccak,
+            force_new: false,
+        }
+        .data(),
+    }
+}
+
+/// `guardian` pins `ipfs_cid` for `ward`, at the ward's
+/// `IpfsPinRecord::address`
+pub fn pin_medical_data_for_ward_ix(
+    registry: Pubkey,
+    guardian: Pubkey,
+    ward: Pubkey,
+    ipfs_cid: &str,
+    data_hash: [u8; 32],
+) -> Instruction {
+    pin_medical_data_for_ward_with_bounty_ix(registry, guardian, ward, ipfs_cid, data_hash, 0)
+}
+
+/// `pin_medical_data_for_ward_ix` escrowing `pin_bounty_lamports` of the
+/// guardian's
+pub fn pin_medical_data_for_ward_with_bounty_ix(
+    registry: Pubkey,
+    guardian: Pubkey,
+    ward: Pubkey,
+    ipfs_cid: &str,
+    data_hash: [u8; 32],
+    pin_bounty_lamports: u64,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::PinMedicalDataForWard {
+            registry,
+            consent: zk_healthcare::GuardianConsent::address(&registry, &guardian, &ward),
+            pin_record: zk_healthcare::IpfsPinRecord::address(&ward, &data_hash),
+            content_claim: zk_healthcare::PinContentClaim::address(&ward, &data_hash),
+            patient_index: patient_index_address(&ward),
+            guardian,
+            system_program: system_program::ID,
+            storage_treasury: None,
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::PinMedicalDataForWard {
+            ipfs_cid: ipfs_cid.to_string(),
+            data_hash,
+            storage_lamports: 0,
+            pin_bounty_lamports,
+            category: DataCategory::Other,
+            tag: None,
+        }
+        .data(),
+    }
+}
+
+pub fn patient_index_address(patient: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"patient", patient.as_ref()], &zk_healthcare::ID).0
+}
+
+pub fn commitment_address(submitter: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"commitment", submitter.as_ref()], &zk_healthcare::ID).0
+}
+
+pub fn commit_verification_ix(registry: Pubkey, submitter: Pubkey, commitment: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CommitVerification {
+            registry,
+            pending: commitment_address(&submitter),
+            submitter,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CommitVerification { commitment }.data(),
+    }
+}
+
+pub fn reclaim_commitment_ix(commitment: Pubkey, submitter: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ReclaimCommitment { commitment, submitter }.to_account_metas(None),
+        data: zk_healthcare::instruction::ReclaimCommitment {}.data(),
+    }
+}
+
+pub fn cache_address(proof_hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"cache", proof_hash], &zk_healthcare::ID).0
+}
+
+pub fn migrate_verification_record_ix(registry: Pubkey, verification: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::MigrateVerificationRecord {
+            registry,
+            verification,
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::MigrateVerificationRecord {}.data(),
+    }
+}
+
+/// Migrate `pin_record` of `patient`, counting it in their `PatientIndex`
+pub fn migrate_pin_record_ix(registry: Pubkey, pin_record: Pubkey, patient: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::MigratePinRecord {
+            registry,
+            pin_record,
+            patient_index: patient_index_address(&patient),
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::MigratePinRecord {}.data(),
+    }
+}
+
+pub fn check_verification_ix(registry: Pubkey, verification: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CheckVerification { registry, verification }.to_account_metas(None),
+        data: zk_healthcare::instruction::CheckVerification {}.data(),
+    }
+}
+
+/// Where `patient` stands for `verification_type`, reading `record` as their latest of it
+pub fn check_verification_status_ix(
+    registry: Pubkey,
+    patient: Pubkey,
+    verification_type: zk_healthcare::VerificationType,
+    record: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CheckVerificationStatus {
+            registry,
+            patient_index: patient_index_address(&patient),
+            record,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CheckVerificationStatus { patient, verification_type }.data(),
+    }
+}
+
+/// The registry authority holds `verification` under the order hashed to `order_hash`
+pub fn place_hold_ix(authority: Pubkey, registry: Pubkey, verification: Pubkey, order_hash: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::PlaceHold { registry, verification, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::PlaceHold { order_hash }.data(),
+    }
+}
+
+pub fn release_hold_ix(authority: Pubkey, registry: Pubkey, verification: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ReleaseHold { registry, verification, authority }.to_account_metas(None),
+        data: zk_healthcare::instruction::ReleaseHold {}.data(),
+    }
+}
+
+/// The `registry` authority lets `verification` move to `destination` until `expires_at`
+pub fn export_record_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    verification: Pubkey,
+    destination: Pubkey,
+    expires_at: i64,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ExportRecord {
+            registry,
+            verification,
+            destination,
+            ticket: zk_healthcare::MigrationTicket::address(&verification),
+            authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ExportRecord { expires_at }.data(),
+    }
+}
+
+/// Import `patient`'s `verification`, exported from `source_registry`, into
+/// `registry` as the eligibility record of `record_nonce`
+pub fn import_record_ix(
+    authority: Pubkey,
+    registry: Pubkey,
+    source_authority: Pubkey,
+    source_registry: Pubkey,
+    verification: Pubkey,
+    patient: Pubkey,
+    record_nonce: u64,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ImportRecord {
+            registry,
+            source_registry,
+            verification,
+            ticket: zk_healthcare::MigrationTicket::address(&verification),
+            patient_index: patient_index_address(&patient),
+            new_record: verification_address(&patient, record_nonce),
+            authority,
+            source_authority,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::ImportRecord {}.data(),
+    }
+}
+
+pub fn mark_expired_ix(registry: Pubkey, verification: Pubkey, patient: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::MarkExpired {
+            registry,
+            verification,
+            patient_index: patient_index_address(&patient),
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::MarkExpired {}.data(),
+    }
+}
+
+/// Report that `proof` fails `circuit_id` over `public_inputs`
+pub fn record_failed_proof_ix(registry: Pubkey, circuit_id: &str, proof: &[u8], public_inputs: &[u8]) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::RecordFailedProof {
+            registry,
+            verifying_key: vk_address(circuit_id),
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::RecordFailedProof {
+            proof: proof.to_vec(),
+            proof_format: zk_healthcare::ProofFormat::Uncompressed,
+            public_inputs: public_inputs.to_vec(),
+            circuit_id: circuit_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+pub fn check_cached_ix(proof_hash: [u8; 32]) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::CheckCached {
+            cache: cache_address(&proof_hash),
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::CheckCached { proof_hash }.data(),
+    }
+}
+
+pub fn reclaim_cache_ix(cache: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::ReclaimCache { cache, payer }.to_account_metas(None),
+        data: zk_healthcare::instruction::ReclaimCache {}.data(),
+    }
+}
+
+pub fn verify_proof_readonly_ix(
+    registry: Pubkey,
+    circuit_id: &str,
+    proof: Vec<u8>,
+    proof_format: zk_healthcare::ProofFormat,
+    public_inputs: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: zk_healthcare::accounts::VerifyProofReadonly {
+            registry,
+            verifying_key: vk_address(circuit_id),
+        }
+        .to_account_metas(None),
+        data: zk_healthcare::instruction::VerifyProofReadonly {
+            proof,
+            proof_format,
+            public_inputs,
+            circuit_id: circuit_id.to_string(),
+        }
+        .data(),
+    }
+}
+
+/// `Fr::from_le_bytes_mod_order(keccak(patient))`, public input 0 of patient-bound circuits
+pub fn patient_input(patient: &Pubkey) -> Fr {
+    Fr::from_le_bytes_mod_order(&keccak::hash(patient.as_ref()).to_bytes())
+}
+
+/// The registry's domain separator as a field element, read back from the account
+pub async fn domain_input(ctx: &mut ProgramTestContext, registry: Pubkey) -> Fr {
+    let registry: zk_healthcare::HealthcareRegistry = fetch(ctx, registry).await;
+    Fr::from_be_bytes_mod_order(&registry.domain)
+}
+
+/// Proves knowledge of `x` such that `x * x == y` for public `y`. `bound` holds
+/// extra public inputs allocated ahead of `y`, e.g. a patient binding, and
+/// `trailing` ones allocated after it, e.g. a timestamp. Generic over the
+/// scalar field, so the same circuit runs on BN254 and BLS12-381.
+#[derive(Clone)]
+struct SquareCircuit<F> {
+    x: Option<F>,
+    y: Option<F>,
+    bound: Vec<Option<F>>,
+    trailing: Vec<Option<F>>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for SquareCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> std::result::Result<(), SynthesisError> {
+        for value in self.bound {
+            cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing))?;
+        }
+        let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
+        let y = cs.new_input_variable(|| self.y.ok_or(SynthesisError::AssignmentMissing))?;
+        for value in self.trailing {
+            cs.new_input_variable(|| value.ok_or(SynthesisError::AssignmentMissing))?;
+        }
+        cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)?;
+        Ok(())
+    }
+}
+
+pub struct Fixture {
+    proving_key: ProvingKey<Bn254>,
+    x: Fr,
+    bound: Vec<Fr>,
+    trailing: Vec<Fr>,
+    pub vk_bytes: Vec<u8>,
+    pub proof: Vec<u8>,
+    pub compressed_proof: Vec<u8>,
+    pub public_inputs: Vec<u8>,
+}
+
+pub fn square_fixture(seed: u64) -> Fixture {
+    bound_square_fixture(seed, Vec::new())
+}
+
+/// A square circuit whose leading public inputs are `bound`
+pub fn bound_square_fixture(seed: u64, bound: Vec<Fr>) -> Fixture {
+    square_fixture_with(seed, bound, Vec::new())
+}
+
+/// `n` fixtures proving distinct statements under one verifying key, for batches
+pub fn batch_fixtures(n: u64) -> Vec<Fixture> {
+    (0..n).map(|index| bound_square_fixture(1, vec![Fr::from(index)])).collect()
+}
+
+/// The keccak `proof_hash` a record of the fixture's proof carries
+pub fn verification_hash(fixture: &Fixture) -> [u8; 32] {
+    let inputs = zk_healthcare::parse_public_inputs(&fixture.public_inputs, zk_healthcare::ProofFormat::Uncompressed);
+    zk_healthcare::compute_verification_hash(&fixture.compressed_proof, &inputs.unwrap())
+}
+
+/// A batch entry for the fixture's uncompressed proof
+pub fn submission(fixture: &Fixture, ipfs_hash: &str) -> zk_healthcare::ProofSubmission {
+    zk_healthcare::ProofSubmission {
+        proof: fixture.proof.clone(),
+        public_inputs: fixture.public_inputs.clone(),
+        ipfs_hash: ipfs_hash.to_string(),
+    }
+}
+
+/// A square circuit whose last public input is the unix timestamp `timestamp`
+pub fn timestamped_square_fixture(seed: u64, timestamp: i64) -> Fixture {
+    square_fixture_with(seed, Vec::new(), vec![Fr::from(timestamp as u64)])
+}
+
+fn square_fixture_with(seed: u64, bound: Vec<Fr>, trailing: Vec<Fr>) -> Fixture {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let x = Fr::from(seed + 3);
+    let y = x * x;
+    let setup = SquareCircuit {
+        x: None,
+        y: None,
+        bound: vec![None; bound.len()],
+        trailing: vec![None; trailing.len()],
+    };
+    let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(setup, &mut rng).unwrap();
+    let circuit = SquareCircuit {
+        x: Some(x),
+        y: Some(y),
+        bound: bound.iter().copied().map(Some).collect(),
+        trailing: trailing.iter().copied().map(Some).collect(),
+    };
+    let proof = Groth16::<Bn254>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let mut vk_bytes = Vec::new();
+    vk.serialize_uncompressed(&mut vk_bytes).unwrap();
+    let mut proof_bytes = Vec::new();
+    proof.serialize_uncompressed(&mut proof_bytes).unwrap();
+    let mut compressed_proof = Vec::new();
+    proof.serialize_compressed(&mut compressed_proof).unwrap();
+    let mut public_inputs = Vec::new();
+    for input in bound.iter().chain([&y]).chain(&trailing) {
+        input.serialize_uncompressed(&mut public_inputs).unwrap();
+    }
+    Fixture {
+        proving_key: pk,
+        x,
+        bound,
+        trailing,
+        vk_bytes,
+        proof: proof_bytes,
+        compressed_proof,
+        public_inputs,
+    }
+}
+
+/// A square circuit over BLS12-381, for circuits registered with
+/// `CurveId::Bls12_381`
+pub struct BlsFixture {
+    pub vk_bytes: Vec<u8>,
+    /// arkworks compressed, the only format BLS12-381 circuits take
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<u8>,
+}
+
+pub fn bls_square_fixture(seed: u64) -> BlsFixture {
+    use ark_bls12_381::{Bls12_381, Fr};
+    let mut rng = StdRng::seed_from_u64(seed);
+    let x = Fr::from(seed + 3);
+    let setup = SquareCircuit { x: None, y: None, bound: Vec::new(), trailing: Vec::new() };
+    let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(setup, &mut rng).unwrap();
+    let circuit = SquareCircuit { x: Some(x), y: Some(x * x), bound: Vec::new(), trailing: Vec::new() };
+    let proof = Groth16::<Bls12_381>::prove(&pk, circuit, &mut rng).unwrap();
+
+    let mut fixture = BlsFixture { vk_bytes: Vec::new(), proof: Vec::new(), public_inputs: Vec::new() };
+    vk.serialize_uncompressed(&mut fixture.vk_bytes).unwrap();
+    proof.serialize_compressed(&mut fixture.proof).unwrap();
+    (x * x).serialize_uncompressed(&mut fixture.public_inputs).unwrap();
+    fixture
+}
+
+impl Fixture {
+    /// A fresh uncompressed proof of the same statement under the same key.
+    /// Groth16 proofs are randomized, so every seed yields different bytes.
+    pub fn reprove(&self, seed: u64) -> Vec<u8> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let circuit = SquareCircuit {
+            x: Some(self.x),
+            y: Some(self.x * self.x),
+            bound: self.bound.iter().copied().map(Some).collect(),
+            trailing: self.trailing.iter().copied().map(Some).collect(),
+        };
+        let proof = Groth16::<Bn254>::prove(&self.proving_key, circuit, &mut rng).unwrap();
+        let mut bytes = Vec::new();
+        proof.serialize_uncompressed(&mut bytes).unwrap();
+        bytes
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+//! A small PLONK prover producing what snarkjs would for the square circuit, so
+//! `PlonkVerifier` can be tested without a circom toolchain. Nothing is blinded:
+//! the proofs are sound but not zero-knowledge, which the verifier can't tell.
+
+use anchor_lang::solana_program::keccak;
+use ark_bn254::{Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, EvaluationDomain, Polynomial, Radix2EvaluationDomain};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::UniformRand;
+use zk_healthcare::offchain::{encode_plonk_verifying_key, g1_syscall_bytes};
+
+/// Eight rows: the public inputs, one multiplication gate, and padding
+const POWER: u8 = 3;
+
+type Poly = DensePolynomial<Fr>;
+
+pub struct PlonkFixture {
+    pub vk_bytes: Vec<u8>,
+    pub proof: Vec<u8>,
+    /// Big-endian, as `ProofFormat::SnarkJs` submits them
+    pub public_inputs: Vec<u8>,
+}
+
+/// Proves knowledge of `x` such that `x * x == y` for public `y`, under a setup
+/// drawn from `seed`
+pub fn plonk_square_fixture(seed: u64) -> PlonkFixture {
+    bound_plonk_square_fixture(seed, Vec::new())
+}
+
+/// A PLONK square circuit whose leading public inputs are `bound`
+pub fn bound_plonk_square_fixture(seed: u64, bound: Vec<Fr>) -> PlonkFixture {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tau = Fr::rand(&mut rng);
+    let x = Fr::from(seed + 3);
+    let y = x * x;
+    let public: Vec<Fr> = bound.into_iter().chain([y]).collect();
+
+    let domain = Radix2EvaluationDomain::<Fr>::new(1 << POWER).unwrap();
+    let n = domain.size();
+    let omega = domain.group_gen;
+    let (k1, k2) = (Fr::from(2u64), Fr::from(3u64));
+    let srs: Vec<G1Affine> = (0..3 * n + 3)
+        .scan(Fr::one(), |power, _| {
+            let point = (G1Affine::generator() * *power).into_affine();
+            *power *= tau;
+            Some(point)
+        })
+        .collect();
+    let commit = |poly: &Poly| -> [u8; 64] {
+        let sum: G1Projective = poly.coeffs.iter().zip(&srs).map(|(coeff, point)| *point * coeff).sum();
+        g1_syscall_bytes(&sum.into_affine())
+    };
+
+    // One row per public input with qL = 1, then a * b - c = 0 on (x, x, y)
+    let mul = public.len();
+    let zeros = vec![Fr::zero(); n];
+    let (mut a, mut b, mut c) = (zeros.clone(), zeros.clone(), zeros.clone());
+    let (mut qm, mut ql, qr, mut qo, qc) = (zeros.clone(), zeros.clone(), zeros.clone(), zeros.clone(), zeros.clone());
+    let mut pi = zeros.clone();
+    for (row, input) in public.iter().enumerate() {
+        a[row] = *input;
+        ql[row] = Fr::one();
+        pi[row] = -*input;
+    }
+    (a[mul], b[mul], c[mul]) = (x, x, y);
+    (qm[mul], qo[mul]) = (Fr::one(), -Fr::one());
+
+    // Wire labels are omega^i, k1 omega^i, k2 omega^i. Copy constraints tie y's
+    // public row to the gate output and both gate inputs together.
+    let ids: Vec<Fr> = domain.elements().collect();
+    let mut sigma = [ids.clone(), ids.iter().map(|id| k1 * id).collect(), ids.iter().map(|id| k2 * id).collect()];
+    let labels = sigma.clone();
+    for ((col_p, row_p), (col_q, row_q)) in [((0, mul - 1), (2, mul)), ((0, mul), (1, mul))] {
+        sigma[col_p][row_p] = labels[col_q][row_q];
+        sigma[col_q][row_q] = labels[col_p][row_p];
+    }
+
+    let interpolate = |evals: &[Fr]| Poly::from_coefficients_vec(domain.ifft(evals));
+    let [qm_p, ql_p, qr_p, qo_p, qc_p] = [&qm, &ql, &qr, &qo, &qc].map(|evals| interpolate(evals));
+    let [s1, s2, s3] = [&sigma[0], &sigma[1], &sigma[2]].map(|evals| interpolate(evals));
+    let (a_p, b_p, c_p, pi_p) = (interpolate(&a), interpolate(&b), interpolate(&c), interpolate(&pi));
+    let selectors = [&qm_p, &ql_p, &qr_p, &qo_p, &qc_p, &s1, &s2, &s3];
+    let commitments = selectors.map(|poly| (G1Affine::generator() * poly.evaluate(&tau)).into_affine());
+    let x_2 = (G2Affine::generator() * tau).into_affine();
+    let vk_bytes = encode_plonk_verifying_key(&commitments, &x_2, k1, k2, omega, POWER);
+
+    let mut transcript = Transcript::default();
+    commitments.iter().for_each(|point| transcript.absorb(&g1_syscall_bytes(point)));
+    public.iter().for_each(|input| transcript.absorb_scalar(input));
+    let [a_c, b_c, c_c] = [&a_p, &b_p, &c_p].map(commit);
+    [&a_c, &b_c, &c_c].into_iter().for_each(|point| transcript.absorb(point));
+    let beta = transcript.challenge();
+    transcript.absorb_scalar(&beta);
+    let gamma = transcript.challenge();
+
+    // Grand product over the rows
+    let mut z = vec![Fr::one(); n];
+    for i in 0..n - 1 {
+        let wires = [a[i], b[i], c[i]];
+        let num: Fr = (0..3).map(|col| wires[col] + beta * labels[col][i] + gamma).product();
+        let den: Fr = (0..3).map(|col| wires[col] + beta * sigma[col][i] + gamma).product();
+        z[i + 1] = z[i] * num * den.inverse().unwrap();
+    }
+    let z_p = interpolate(&z);
+    let z_c = commit(&z_p);
+    transcript.absorb_scalar(&beta);
+    transcript.absorb_scalar(&gamma);
+    transcript.absorb(&z_c);
+    let alpha = transcript.challenge();
+
+    let constant = |value: Fr| Poly::from_coefficients_vec(vec![value]);
+    let linear = |c0: Fr, c1: Fr| Poly::from_coefficients_vec(vec![c0, c1]);
+    let mut l1_evals = zeros.clone();
+    l1_evals[0] = Fr::one();
+    let l1 = interpolate(&l1_evals);
+    let z_shifted = Poly::from_coefficients_vec(
+        z_p.coeffs.iter().zip(domain.elements()).map(|(coeff, power)| *coeff * power).collect(),
+    );
+
+    let gate = &(&(&(&(&(&(&qm_p * &a_p) * &b_p) + &(&ql_p * &a_p)) + &(&qr_p * &b_p)) + &(&qo_p * &c_p)) + &qc_p) + &pi_p;
+    let perm_num = &(&(&(&a_p + &linear(gamma, beta)) * &(&b_p + &linear(gamma, beta * k1)))
+        * &(&c_p + &linear(gamma, beta * k2)))
+        * &z_p;
+    let perm_den = &(&(&(&a_p + &(&(&s1 * beta) + &constant(gamma))) * &(&b_p + &(&(&s2 * beta) + &constant(gamma))))
+        * &(&c_p + &(&(&s3 * beta) + &constant(gamma))))
+        * &z_shifted;
+    let boundary = &(&z_p - &constant(Fr::one())) * &l1;
+    let numerator = &(&gate + &(&(&perm_num - &perm_den) * alpha)) + &(&boundary * alpha.square());
+    let (t, remainder) = numerator.divide_by_vanishing_poly(domain).unwrap();
+    assert!(remainder.is_zero(), "witness doesn't satisfy the circuit");
+    let mut t_coeffs = t.coeffs.clone();
+    t_coeffs.resize(3 * n, Fr::zero());
+    let [t1, t2, t3] = [0, 1, 2].map(|i| Poly::from_coefficients_slice(&t_coeffs[i * n..(i + 1) * n]));
+    let [t1_c, t2_c, t3_c] = [&t1, &t2, &t3].map(commit);
+    transcript.absorb_scalar(&alpha);
+    [&t1_c, &t2_c, &t3_c].into_iter().for_each(|point| transcript.absorb(point));
+    let xi = transcript.challenge();
+
+    let [e_a, e_b, e_c, e_s1, e_s2] = [&a_p, &b_p, &c_p, &s1, &s2].map(|poly| poly.evaluate(&xi));
+    let e_zw = z_p.evaluate(&(xi * omega));
+    transcript.absorb_scalar(&xi);
+    [e_a, e_b, e_c, e_s1, e_s2, e_zw].iter().for_each(|eval| transcript.absorb_scalar(eval));
+    let v = transcript.challenge();
+
+    // Linearization: every polynomial the verifier can't evaluate on its own
+    // stays symbolic, so R(xi) = 0 exactly when the proof is honest
+    let xin = xi.pow([n as u64]);
+    let zh = xin - Fr::one();
+    let l1_xi = l1.evaluate(&xi);
+    let perm_z = alpha * (e_a + beta * xi + gamma) * (e_b + beta * k1 * xi + gamma) * (e_c + beta * k2 * xi + gamma);
+    let perm_s3 = alpha * (e_a + beta * e_s1 + gamma) * (e_b + beta * e_s2 + gamma) * e_zw;
+    let r = [
+        &qm_p * (e_a * e_b),
+        &ql_p * e_a,
+        &qr_p * e_b,
+        &qo_p * e_c,
+        qc_p.clone(),
+        constant(pi_p.evaluate(&xi)),
+        &z_p * (perm_z + l1_xi * alpha.square()),
+        -(&(&(&s3 * beta) + &constant(e_c + gamma)) * perm_s3),
+        constant(-l1_xi * alpha.square()),
+        -(&(&(&t1 + &(&t2 * xin)) + &(&t3 * xin.square())) * zh),
+    ]
+    .iter()
+    .fold(Poly::zero(), |sum, term| &sum + term);
+    assert!(r.evaluate(&xi).is_zero());
+
+    let mut opened = r;
+    let mut power = Fr::one();
+    for (poly, eval) in [(&a_p, e_a), (&b_p, e_b), (&c_p, e_c), (&s1, e_s1), (&s2, e_s2)] {
+        power *= v;
+        opened = &opened + &(&(poly - &constant(eval)) * power);
+    }
+    let wxi_c = commit(&divide_by_root(&opened, xi));
+    let wxiw_c = commit(&divide_by_root(&(&z_p - &constant(e_zw)), xi * omega));
+
+    let mut proof = Vec::new();
+    for point in [a_c, b_c, c_c, z_c, t1_c, t2_c, t3_c, wxi_c, wxiw_c] {
+        proof.extend_from_slice(&point);
+    }
+    for eval in [e_a, e_b, e_c, e_s1, e_s2, e_zw] {
+        proof.extend_from_slice(&scalar_bytes(&eval));
+    }
+    PlonkFixture {
+        vk_bytes,
+        proof,
+        public_inputs: public.iter().flat_map(scalar_bytes).collect(),
+    }
+}
+
+/// `poly / (X - root)` by synthetic division; `root` must be a root of `poly`
+fn divide_by_root(poly: &Poly, root: Fr) -> Poly {
+    let mut quotient = vec![Fr::zero(); poly.coeffs.len().saturating_sub(1)];
+    let mut carry = Fr::zero();
+    for (i, coeff) in poly.coeffs.iter().enumerate().rev() {
+        let value = *coeff + carry * root;
+        if i == 0 {
+            assert!(value.is_zero(), "not a root");
+        } else {
+            quotient[i - 1] = value;
+        }
+        carry = value;
+    }
+    Poly::from_coefficients_vec(quotient)
+}
+
+fn scalar_bytes(scalar: &Fr) -> Vec<u8> {
+    scalar.into_bigint().to_bytes_be()
+}
+
+/// Mirrors the verifier's keccak transcript
+#[derive(Default)]
+struct Transcript(Vec<u8>);
+
+impl Transcript {
+    fn absorb(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn absorb_scalar(&mut self, scalar: &Fr) {
+        self.0.extend(scalar_bytes(scalar));
+    }
+
+    fn challenge(&mut self) -> Fr {
+        let challenge = Fr::from_be_bytes_mod_order(&keccak::hash(&self.0).to_bytes());
+        self.0.clear();
+        challenge
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, VerificationRecord, RECORD_GC_GRACE_SECS};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+const ORDER: [u8; 32] = [0x0d; 32];
+
+/// A registry whose records expire after `VALIDITY_SECS`, the payer's record
+/// under it, and fixtures for more
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey, Vec<Fixture>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(2);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), VALIDITY_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+    let ix = verify(&registry, ctx.payer.pubkey(), 0, &fixtures[0]);
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, verification_address(&ctx.payer.pubkey(), 0), fixtures)
+}
+
+fn verify(registry: &Keypair, patient: Pubkey, nonce: u64, fixture: &Fixture) -> Instruction {
+    verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    )
+}
+
+#[tokio::test]
+async fn test_held_expired_record_is_not_collected() {
+    let mut ctx = start().await;
+    let (registry, record, _) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[place_hold_ix(patient, registry.pubkey(), record, ORDER)], &[]).await.unwrap();
+
+    // Past its expiry and grace period, the hold still stands
+    let held: VerificationRecord = fetch(&mut ctx, record).await;
+    warp_clock_to(&mut ctx, held.expires_at + RECORD_GC_GRACE_SECS).await;
+    let ix = close_expired_verification_ix(registry.pubkey(), record, patient, patient, None);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+    let ix = close_records_bulk_ix(registry.pubkey(), &[(record, patient, None)]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+    let ix = mark_expired_ix(registry.pubkey(), record, patient);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+
+    send(&mut ctx, &[release_hold_ix(patient, registry.pubkey(), record)], &[]).await.unwrap();
+    let ix = close_expired_verification_ix(registry.pubkey(), record, patient, patient, None);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(record).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_held_record_refuses_changes() {
+    let mut ctx = start().await;
+    let (registry, record, fixtures) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[place_hold_ix(patient, registry.pubkey(), record, ORDER)], &[]).await.unwrap();
+    let held: VerificationRecord = fetch(&mut ctx, record).await;
+    assert_eq!(held.hold.map(|hold| hold.order_hash), Some(ORDER));
+    assert!(held.is_verified());
+
+    warp_clock(&mut ctx, 0).await;
+    let ix = place_hold_ix(patient, registry.pubkey(), record, [0x0e; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+    let ix = revoke_verification_ix(patient, registry.pubkey(), record, patient, 1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+    let ix = update_metadata_ix(registry.pubkey(), record, patient, vec![1; 8]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+    let ix = submit_claim_ix(registry.pubkey(), record, patient, 0, 1_000, [0x5c; 32], [0x62; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+    // Nor can a new record force it out
+    let ix = with_force_new(verify(&registry, patient, 1, &fixtures[1]));
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordUnderHold);
+
+    send(&mut ctx, &[release_hold_ix(patient, registry.pubkey(), record)], &[]).await.unwrap();
+    let released: VerificationRecord = fetch(&mut ctx, record).await;
+    assert!(!released.is_held());
+    warp_clock(&mut ctx, 0).await;
+    let ix = release_hold_ix(patient, registry.pubkey(), record);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordNotHeld);
+    let ix = revoke_verification_ix(patient, registry.pubkey(), record, patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_only_authority_places_a_hold() {
+    let mut ctx = start().await;
+    let (registry, record, _) = setup(&mut ctx).await;
+    let intruder = Keypair::new();
+
+    let ix = place_hold_ix(intruder.pubkey(), registry.pubkey(), record, ORDER);
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+    let held: VerificationRecord = fetch(&mut ctx, record).await;
+    assert!(!held.is_held());
+
+    // nor releases one
+    let ix = place_hold_ix(ctx.payer.pubkey(), registry.pubkey(), record, ORDER);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = release_hold_ix(intruder.pubkey(), registry.pubkey(), record);
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+}
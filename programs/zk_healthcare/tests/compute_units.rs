@@ -0,0 +1,235 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Compute-unit benchmarks. Native builtins are not metered by program-test, so
+// these run against the SBF artifact:
+//
+//     anchor build && SBF_OUT_DIR=target/deploy cargo test --test compute_units -- --ignored --nocapture
+
+mod common;
+
+use anchor_lang::solana_program::keccak;
+use ark_bn254::Fr;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{fixtures, DataCategory, PinEntry, ProofFormat, VkConfig, MAX_BULK_PINS};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// Per-instruction ceilings. Syscall prices move a little between Solana
+/// releases, so each gets 5% of headroom before the assertion fails.
+const INITIALIZE_BUDGET: u64 = 20_000;
+const REGISTER_VK_BUDGET: u64 = 30_000;
+const VERIFY_BUDGET: u64 = 400_000;
+/// A full `pin_medical_data_bulk` has to land without a compute budget
+/// instruction, inside the 200K CU a transaction gets by default
+const BULK_PIN_BUDGET: u64 = 200_000;
+
+fn assert_within_budget(name: &str, units: u64, budget: u64) {
+    assert!(units <= budget + budget / 20, "{name} used {units} CU, budget {budget}");
+}
+
+/// `instructions` behind a raised compute unit limit, so the measurement is the
+/// cost and not the default cap
+fn metered(instructions: Vec<Instruction>) -> Vec<Instruction> {
+    let mut metered = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+    metered.extend(instructions);
+    metered
+}
+
+async fn verify_units(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    circuit_id: &str,
+    proof: &[u8],
+    public_inputs: &[u8],
+) -> u64 {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verify = metered(vec![verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        circuit_id,
+        patient,
+        proof.to_vec(),
+        ProofFormat::Uncompressed,
+        public_inputs.to_vec(),
+        CID,
+    )]);
+    simulate_units(ctx, &verify, &[]).await
+}
+
+#[tokio::test]
+#[ignore = "requires the SBF build of zk_healthcare"]
+async fn bench_instruction_budgets() {
+    let mut ctx = start_sbf().await;
+    let authority = ctx.payer.pubkey();
+
+    let registry = Keypair::new();
+    let initialize = metered(vec![initialize_ix(authority, registry.pubkey(), true)]);
+    let initialize_units = simulate_units(&mut ctx, &initialize, &[&registry]).await;
+    send(&mut ctx, &initialize, &[&registry]).await.unwrap();
+
+    let vk_bytes = fixtures::vk_bytes();
+    let config = VkConfig { n_public: fixtures::N_PUBLIC, ..VkConfig::default() };
+    let vk_hash = keccak::hash(&vk_bytes).to_bytes();
+    let register = metered(vec![register_vk_ix(
+        authority,
+        registry.pubkey(),
+        "preimage_v1",
+        vk_bytes.len() as u32,
+        config,
+        vk_hash,
+    )]);
+    let register_units = simulate_units(&mut ctx, &register, &[]).await;
+
+    upload_vk(&mut ctx, registry.pubkey(), "preimage_v1", &vk_bytes).await;
+    let verify_units =
+        verify_units(&mut ctx, &registry, "preimage_v1", &fixtures::valid_proof(), &fixtures::public_inputs()).await;
+
+    println!("initialize:             {initialize_units} CU");
+    println!("register_verifying_key: {register_units} CU");
+    println!("verify_eligibility:     {verify_units} CU");
+    assert_within_budget("initialize", initialize_units, INITIALIZE_BUDGET);
+    assert_within_budget("register_verifying_key", register_units, REGISTER_VK_BUDGET);
+    assert_within_budget("verify_eligibility", verify_units, VERIFY_BUDGET);
+}
+
+#[tokio::test]
+#[ignore = "requires the SBF build of zk_healthcare"]
+async fn bench_public_input_scaling() {
+    let mut ctx = start_sbf().await;
+    let registry = initialize_registry(&mut ctx).await;
+
+    println!("public inputs | verify_eligibility CU");
+    println!("--------------|----------------------");
+    for n_public in [1u64, 2, 4, 8] {
+        let fixture = bound_square_fixture(1, (1..n_public).map(Fr::from).collect());
+        let circuit_id = format!("scaling_{n_public}");
+        upload_vk(&mut ctx, registry.pubkey(), &circuit_id, &fixture.vk_bytes).await;
+        let units = verify_units(&mut ctx, &registry, &circuit_id, &fixture.proof, &fixture.public_inputs).await;
+        println!("{n_public:>13} | {units:>21}");
+        assert_within_budget("verify_eligibility", units, VERIFY_BUDGET);
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires the SBF build of zk_healthcare"]
+async fn bench_prepared_vk_cache() {
+    let mut ctx = start_sbf().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let verify = [
+        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        verify_eligibility_ix(
+            registry.pubkey(),
+            nonce,
+            CIRCUIT,
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+            CID,
+        ),
+    ];
+
+    let unprepared = simulate_units(&mut ctx, &verify, &[]).await;
+    let authority = ctx.payer.pubkey();
+    let prepare = [
+        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        prepare_vk_ix(authority, CIRCUIT),
+    ];
+    send(&mut ctx, &prepare, &[]).await.unwrap();
+    let prepared = simulate_units(&mut ctx, &verify, &[]).await;
+
+    println!("verify_eligibility without cached VK: {unprepared} CU");
+    println!("verify_eligibility with cached VK:    {prepared} CU");
+    println!("saved:                                {} CU", unprepared.saturating_sub(prepared));
+    assert!(prepared < unprepared);
+    // Pairing through the alt_bn128 syscalls keeps verification well inside the
+    // ~450K CU the program logs as its estimate
+    assert!(prepared < 450_000, "verification used {prepared} CU");
+}
+
+#[tokio::test]
+#[ignore = "requires the SBF build of zk_healthcare"]
+async fn bench_batch_verification() {
+    let mut ctx = start_sbf().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(8);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let prepare = [
+        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        prepare_vk_ix(authority, CIRCUIT),
+    ];
+    send(&mut ctx, &prepare, &[]).await.unwrap();
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let single = [
+        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+        verify_eligibility_ix(
+            registry.pubkey(),
+            nonce,
+            CIRCUIT,
+            patient,
+            fixtures[0].proof.clone(),
+            ProofFormat::Uncompressed,
+            fixtures[0].public_inputs.clone(),
+            CID,
+        ),
+    ];
+    let per_proof = simulate_units(&mut ctx, &single, &[]).await;
+    println!("verify_eligibility:             {per_proof} CU per proof");
+
+    // The 1232-byte packet limit keeps real batches smaller than this; banks
+    // doesn't enforce it, so the curve cost can still be measured
+    for size in [2, 4, 8] {
+        let submissions = fixtures[..size].iter().map(|fixture| submission(fixture, CID)).collect();
+        let batch = [
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+            verify_eligibility_batch_ix(
+                registry.pubkey(),
+                0,
+                CIRCUIT,
+                ctx.payer.pubkey(),
+                ProofFormat::Uncompressed,
+                submissions,
+            ),
+        ];
+        let units = simulate_units(&mut ctx, &batch, &[]).await;
+        let average = units / size as u64;
+        println!("verify_eligibility_batch x{size}:    {units} CU, {average} CU per proof");
+        assert!(average < per_proof, "batch of {size} cost {average} CU per proof");
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires the SBF build of zk_healthcare"]
+async fn bench_full_bulk_pin() {
+    let mut ctx = start_sbf().await;
+    let registry = initialize_registry(&mut ctx).await;
+
+    // The longest CIDs, through the lookup table a full batch needs
+    let entries: Vec<PinEntry> = (0..MAX_BULK_PINS)
+        .map(|index| PinEntry {
+            ipfs_cid: "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm".to_string(),
+            data_hash: [index as u8 + 1; 32],
+            category: DataCategory::Other,
+            tag: None,
+        })
+        .collect();
+    let tx = pin_medical_data_bulk_v0(&mut ctx, registry.pubkey(), &entries).await;
+    let units = simulate_transaction_units(&mut ctx, tx).await;
+
+    println!("pin_medical_data_bulk of {MAX_BULK_PINS}: {units} CU");
+    assert!(units <= BULK_PIN_BUDGET, "pin_medical_data_bulk used {units} CU");
+}
@@ -0,0 +1,122 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, PatientIndex, ProofFormat, VerificationType};
+
+const CIRCUIT: &str = "eligibility_v1";
+const ACCESS_CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const COOLDOWN_SECS: i64 = 60 * 60;
+
+/// A registry making patients wait `COOLDOWN_SECS` between verifications
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = set_verification_cooldown_ix(ctx.payer.pubkey(), registry.pubkey(), COOLDOWN_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+    registry
+}
+
+/// Verify `fixture` as the payer, expiring their record before it
+async fn verify(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+) -> (Result<(), BanksClientError>, Vec<String>) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send_logged(ctx, &[with_force_new(ix)], &[]).await
+}
+
+async fn last_verified_at(ctx: &mut ProgramTestContext, verification_type: VerificationType) -> i64 {
+    let index: PatientIndex = fetch(ctx, patient_index_address(&ctx.payer.pubkey())).await;
+    index.last_verified_at_by_type[verification_type as usize]
+}
+
+#[tokio::test]
+async fn test_verification_waits_out_the_cooldown() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(3);
+    let registry = setup(&mut ctx, &fixtures[0]).await;
+    verify(&mut ctx, &registry, &fixtures[0]).await.0.unwrap();
+    let verified_at = last_verified_at(&mut ctx, VerificationType::Eligibility).await;
+
+    warp_clock_to(&mut ctx, verified_at + 600).await;
+    let (result, logs) = verify(&mut ctx, &registry, &fixtures[1]).await;
+    assert_error(result, HealthcareError::VerificationCooldownActive);
+    assert!(logs.iter().any(|log| log.contains("remaining_secs: 3000")));
+
+    warp_clock_to(&mut ctx, verified_at + COOLDOWN_SECS).await;
+    verify(&mut ctx, &registry, &fixtures[1]).await.0.unwrap();
+    assert_eq!(last_verified_at(&mut ctx, VerificationType::Eligibility).await, verified_at + COOLDOWN_SECS);
+
+    // Zero lifts the cooldown
+    let ix = set_verification_cooldown_ix(ctx.payer.pubkey(), registry.pubkey(), 0);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    verify(&mut ctx, &registry, &fixtures[2]).await.0.unwrap();
+    let ix = set_verification_cooldown_ix(ctx.payer.pubkey(), registry.pubkey(), -1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidVerificationCooldown);
+}
+
+#[tokio::test]
+async fn test_types_cool_down_apart() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let registry = setup(&mut ctx, &fixtures[0]).await;
+    let patient = ctx.payer.pubkey();
+    let access = square_fixture(2);
+    upload_vk(&mut ctx, registry.pubkey(), ACCESS_CIRCUIT, &access.vk_bytes).await;
+    // The upload took over `Eligibility`, so hand it back
+    for (circuit_id, verification_type) in
+        [(ACCESS_CIRCUIT, VerificationType::AccessControl), (CIRCUIT, VerificationType::Eligibility)]
+    {
+        let ix = set_type_circuit_ix(patient, registry.pubkey(), circuit_id, verification_type);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+
+    verify(&mut ctx, &registry, &fixtures[0]).await.0.unwrap();
+    // An access check straight after an eligibility check is another type
+    let resource = Keypair::new().pubkey();
+    let ix = verify_access_control_ix(registry.pubkey(), ACCESS_CIRCUIT, patient, &access, resource, 100, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let eligibility = last_verified_at(&mut ctx, VerificationType::Eligibility).await;
+    assert_eq!(last_verified_at(&mut ctx, VerificationType::AccessControl).await, eligibility);
+
+    let (result, _) = verify(&mut ctx, &registry, &fixtures[1]).await;
+    assert_error(result, HealthcareError::VerificationCooldownActive);
+}
+
+#[tokio::test]
+async fn test_batch_is_held_to_one_cooldown() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(3);
+    let registry = setup(&mut ctx, &fixtures[0]).await;
+    let patient = ctx.payer.pubkey();
+
+    // The entries of one batch share a timestamp, so they don't cool each other down
+    let format = ProofFormat::Uncompressed;
+    let submissions = fixtures[..2].iter().map(|fixture| submission(fixture, CID)).collect();
+    let ix = verify_eligibility_batch_ix(registry.pubkey(), 0, CIRCUIT, patient, format, submissions);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.type_counts[VerificationType::Eligibility as usize], 2);
+
+    // A later batch still waits out the cooldown
+    let submissions = vec![submission(&fixtures[2], CID)];
+    let ix = verify_eligibility_batch_ix(registry.pubkey(), 2, CIRCUIT, patient, format, submissions);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VerificationCooldownActive);
+}
@@ -0,0 +1,181 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// A claims program that verifies a patient's proof through CPI before paying
+// out, written the way an integrator would against the `cpi` feature.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::get_return_data;
+use anchor_lang::solana_program::program_error::ProgramError;
+use common::*;
+use solana_program_test::{processor, ProgramTestContext};
+use solana_sdk::signature::Signer;
+use solana_sdk::system_program;
+use zk_healthcare::{HashAlgo, HealthcareRegistry, ProofFormat, VerificationRecord, VerificationResult};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct ClaimArgs {
+    proof: Vec<u8>,
+    public_inputs: Vec<u8>,
+    record_nonce: u64,
+}
+
+/// Accounts: zk_healthcare, then `VerifyEligibility`'s accounts in order
+fn process_claim(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let args = ClaimArgs::try_from_slice(data)?;
+    // The circuit is free, nothing is cached or committed, no provider co-signs
+    // and no token is minted, so the three slots before the patient index and the
+    // seven after the previous record hold the program id
+    let [
+        zk_program, registry, verification, verifying_key, nullifier, patient, system_program, _, _, _,
+        patient_index, previous_record, _, _, _, _, _, _, _, event_authority, program, ..
+    ] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    // As does the previous record's slot for a patient's first record
+    let previous_record = (*previous_record.key != zk_healthcare::ID).then(|| previous_record.clone());
+    let cpi_accounts = zk_healthcare::cpi::accounts::VerifyEligibility {
+        registry: registry.clone(),
+        verification: verification.clone(),
+        verifying_key: verifying_key.clone(),
+        nullifier: nullifier.clone(),
+        patient: patient.clone(),
+        system_program: system_program.clone(),
+        fee_recipient: None,
+        cache: None,
+        commitment: None,
+        patient_index: patient_index.clone(),
+        previous_record,
+        provider: None,
+        provider_registration: None,
+        instructions: None,
+        verification_mint: None,
+        patient_token_account: None,
+        token_program: None,
+        associated_token_program: None,
+        event_authority: event_authority.clone(),
+        program: program.clone(),
+    };
+    zk_healthcare::cpi::verify_eligibility(
+        CpiContext::new(zk_program.clone(), cpi_accounts),
+        args.proof,
+        ProofFormat::Uncompressed,
+        args.public_inputs,
+        CID.to_string(),
+        CIRCUIT.to_string(),
+        args.record_nonce,
+        None,
+        HashAlgo::Keccak,
+        false,
+        None,
+        false,
+    )?;
+
+    let Some((program_id, result)) = get_return_data() else {
+        return Err(ProgramError::InvalidAccountData);
+    };
+    if program_id != zk_healthcare::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let result = VerificationResult::try_from_slice(&result)?;
+    if !result.verified || result.record != *verification.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    msg!("Claim approved against record {}", result.record);
+    Ok(())
+}
+
+async fn start_with_claims() -> (ProgramTestContext, Pubkey) {
+    let claims_id = Pubkey::new_unique();
+    let mut program = program_test();
+    program.add_program("claims", claims_id, processor!(process_claim));
+    (program.start_with_context().await, claims_id)
+}
+
+fn claim_ix(claims_id: Pubkey, registry: Pubkey, patient: Pubkey, args: ClaimArgs) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(zk_healthcare::ID, false)];
+    accounts.extend(
+        zk_healthcare::accounts::VerifyEligibility {
+            registry,
+            verification: verification_address(&patient, args.record_nonce),
+            verifying_key: vk_address(CIRCUIT),
+            nullifier: nullifier_address(&args.proof, ProofFormat::Uncompressed, &args.public_inputs),
+            patient,
+            system_program: system_program::ID,
+            fee_recipient: None,
+            cache: None,
+            commitment: None,
+            patient_index: patient_index_address(&patient),
+            previous_record: previous_record_address(&patient, args.record_nonce),
+            provider: None,
+            provider_registration: None,
+            instructions: None,
+            verification_mint: None,
+            patient_token_account: None,
+            token_program: None,
+            associated_token_program: None,
+            event_authority: zk_healthcare::event_authority_address(),
+            program: zk_healthcare::ID,
+        }
+        .to_account_metas(None),
+    );
+    Instruction {
+        program_id: claims_id,
+        accounts,
+        data: args.try_to_vec().unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn test_caller_reads_verification_result() {
+    let (mut ctx, claims_id) = start_with_claims().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let patient = ctx.payer.pubkey();
+    let args = ClaimArgs {
+        proof: fixture.proof.clone(),
+        public_inputs: fixture.public_inputs.clone(),
+        record_nonce: next_record_nonce(&mut ctx, patient).await,
+    };
+    let verification = verification_address(&patient, args.record_nonce);
+    let ix = claim_ix(claims_id, registry.pubkey(), patient, args);
+    let answer = simulate_return_data(&mut ctx, std::slice::from_ref(&ix), &[]).await;
+    assert_eq!(answer.program_id, zk_healthcare::ID);
+    let result = VerificationResult::try_from_slice(&answer.data).unwrap();
+    assert!(result.verified);
+    assert_eq!(result.record, verification);
+
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.proof_hash, result.proof_hash);
+    let registry: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(registry.total_verifications, 1);
+}
+
+#[tokio::test]
+async fn test_failed_proof_aborts_caller() {
+    let (mut ctx, claims_id) = start_with_claims().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let args = ClaimArgs {
+        proof: fixture.proof.clone(),
+        public_inputs: square_fixture(2).public_inputs,
+        record_nonce: 0,
+    };
+    let ix = claim_ix(claims_id, registry.pubkey(), ctx.payer.pubkey(), args);
+    assert_error(
+        send(&mut ctx, &[ix], &[]).await,
+        zk_healthcare::HealthcareError::PairingCheckFailed,
+    );
+}
@@ -0,0 +1,77 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Events duplicated through self-CPI, compared against their logged copies.
+// Captures events by swapping the process-wide syscall stubs, so this binary
+// holds a single test.
+
+mod common;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::Discriminator;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{DataPinned, EligibilityVerified, ProofFormat, VerificationRevoked};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// Run `ix`, returning the events it logged and those it emitted through
+/// self-CPI, discriminator included
+async fn run(ctx: &mut ProgramTestContext, ix: Instruction, signers: &[&Keypair]) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    let (result, logs) = send_logged(ctx, &[ix], signers).await;
+    result.unwrap();
+    let cpi_events = invoke_data(&logs)
+        .into_iter()
+        .filter(|(program, _)| *program == zk_healthcare::ID)
+        .filter_map(|(_, data)| data.strip_prefix(&EVENT_IX_TAG_LE[..]).map(<[u8]>::to_vec))
+        .collect();
+    (event_data(&logs), cpi_events)
+}
+
+/// The events of type `T` in `events`
+fn only<T: Discriminator>(events: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    events.iter().filter(|event| event.starts_with(&T::DISCRIMINATOR)).cloned().collect()
+}
+
+#[tokio::test]
+async fn test_cpi_events_match_logged_events() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let verify = |nonce: usize| {
+        verify_eligibility_ix(
+            registry.pubkey(),
+            nonce as u64,
+            CIRCUIT,
+            patient,
+            fixtures[nonce].proof.clone(),
+            ProofFormat::Uncompressed,
+            fixtures[nonce].public_inputs.clone(),
+            CID,
+        )
+    };
+
+    // Off by default: logs only
+    let (logged, cpi) = run(&mut ctx, verify(0), &[]).await;
+    assert_eq!(only::<EligibilityVerified>(&logged).len(), 1);
+    assert!(cpi.is_empty());
+
+    send(&mut ctx, &[set_cpi_events_ix(patient, registry.pubkey(), true)], &[]).await.unwrap();
+    let (logged, cpi) = run(&mut ctx, with_force_new(verify(1)), &[]).await;
+    assert_eq!(only::<EligibilityVerified>(&logged).len(), 1);
+    assert_eq!(cpi, only::<EligibilityVerified>(&logged));
+
+    let ix = pin_medical_data_ix(registry.pubkey(), patient, CID, [7; 32]);
+    let (logged, cpi) = run(&mut ctx, ix, &[]).await;
+    assert_eq!(only::<DataPinned>(&logged).len(), 1);
+    assert_eq!(cpi, only::<DataPinned>(&logged));
+
+    let ix = revoke_verification_ix(patient, registry.pubkey(), verification_address(&patient, 1), patient, 1);
+    let (logged, cpi) = run(&mut ctx, ix, &[]).await;
+    assert_eq!(only::<VerificationRevoked>(&logged).len(), 1);
+    assert_eq!(cpi, only::<VerificationRevoked>(&logged));
+}
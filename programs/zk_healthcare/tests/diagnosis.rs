@@ -0,0 +1,132 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{DiagnosisRecord, HealthcareError, IpfsPinRecord, PatientIndex, ProofFormat, VerificationType};
+
+const CIRCUIT: &str = "diagnosis_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+/// Stands in for a salted hash of an ICD-10 code
+const ICD10_COMMITMENT: [u8; 32] = [
+    0x4a, 0x10, 0x9c, 0x02, 0xee, 0x31, 0x7b, 0x55, 0x08, 0xd2, 0x6f, 0x93, 0x1c, 0xa7, 0x40, 0x2e, 0xb9, 0x05, 0x77,
+    0x13, 0xc8, 0x6a, 0xf1, 0x29, 0x3d, 0x84, 0x5e, 0x0b, 0x92, 0xfa, 0x61, 0x37,
+];
+
+/// A registry with `CIRCUIT` approved for diagnoses and one registered provider
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> (Keypair, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let ix = set_type_circuit_ix(authority, registry.pubkey(), CIRCUIT, VerificationType::Diagnosis);
+    send(ctx, &[ix], &[]).await.unwrap();
+    let provider = Keypair::new();
+    let ix = register_provider_ix(authority, registry.pubkey(), provider.pubkey());
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, provider)
+}
+
+fn diagnosis_address(fixture: &Fixture) -> Pubkey {
+    DiagnosisRecord::address(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs)
+}
+
+#[tokio::test]
+async fn test_diagnosis_stores_the_commitment_verbatim() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let (registry, provider) = setup(&mut ctx, &fixture).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, CID, [1; 32])], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient, &[1; 32]);
+    confirm_pin(&mut ctx, registry.pubkey(), pin_record).await;
+
+    let ix = verify_diagnosis_ix(
+        registry.pubkey(),
+        CIRCUIT,
+        patient,
+        &fixture,
+        ICD10_COMMITMENT,
+        provider.pubkey(),
+        Some(pin_record),
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let address = diagnosis_address(&fixture);
+    let diagnosis: DiagnosisRecord = fetch(&mut ctx, address).await;
+    assert_eq!(diagnosis.icd10_commitment, ICD10_COMMITMENT);
+    assert_eq!((diagnosis.patient, diagnosis.provider), (patient, provider.pubkey()));
+    assert_eq!(diagnosis.pin_record, Some(pin_record));
+    assert_eq!((diagnosis.ipfs_cid.as_str(), diagnosis.revoked_at), (CID, 0));
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.type_counts[VerificationType::Diagnosis as usize], 1);
+    assert_eq!(index.last_verification, address);
+}
+
+#[tokio::test]
+async fn test_unregistered_or_inactive_provider_rejected() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let (registry, provider) = setup(&mut ctx, &fixture).await;
+    let patient = ctx.payer.pubkey();
+
+    let stranger = Pubkey::new_unique();
+    let ix = verify_diagnosis_ix(registry.pubkey(), CIRCUIT, patient, &fixture, ICD10_COMMITMENT, stranger, None);
+    let err = send(&mut ctx, &[ix], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::AccountNotInitialized as u32);
+
+    let ix = set_provider_active_ix(patient, registry.pubkey(), provider.pubkey(), false);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let diagnose = |fixture: &Fixture| {
+        verify_diagnosis_ix(registry.pubkey(), CIRCUIT, patient, fixture, ICD10_COMMITMENT, provider.pubkey(), None)
+    };
+    assert_error(send(&mut ctx, &[diagnose(&fixture)], &[]).await, HealthcareError::ProviderInactive);
+
+    let ix = set_provider_active_ix(patient, registry.pubkey(), provider.pubkey(), true);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Past the slot, so the repeated diagnosis isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[diagnose(&fixture)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_only_the_provider_or_authority_revokes() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let (registry, provider) = setup(&mut ctx, &fixtures[0]).await;
+    let patient = ctx.payer.pubkey();
+    for fixture in &fixtures {
+        let provider = provider.pubkey();
+        let ix = verify_diagnosis_ix(registry.pubkey(), CIRCUIT, patient, fixture, ICD10_COMMITMENT, provider, None);
+        // The second supersedes the first, which stays unrevoked
+        let ix = after_latest_record(&mut ctx, with_force_new(ix), patient, VerificationType::Diagnosis).await;
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    let (first, second) = (diagnosis_address(&fixtures[0]), diagnosis_address(&fixtures[1]));
+
+    // Registered, but not the provider who made the diagnosis
+    let other = Keypair::new();
+    let ix = register_provider_ix(patient, registry.pubkey(), other.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = revoke_diagnosis_ix(other.pubkey(), registry.pubkey(), first, patient, 1);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::NotAuthorizedToRevoke);
+
+    let ix = revoke_diagnosis_ix(provider.pubkey(), registry.pubkey(), first, patient, 1);
+    send(&mut ctx, &[ix], &[&provider]).await.unwrap();
+    let diagnosis: DiagnosisRecord = fetch(&mut ctx, first).await;
+    assert_eq!(diagnosis.revoked_by, provider.pubkey());
+    assert!(diagnosis.revoked_at > 0);
+    // Past the slot, so the repeated revocation isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    let ix = revoke_diagnosis_ix(provider.pubkey(), registry.pubkey(), first, patient, 1);
+    assert_error(send(&mut ctx, &[ix], &[&provider]).await, HealthcareError::AlreadyRevoked);
+
+    // The registry authority may revoke any diagnosis
+    let ix = revoke_diagnosis_ix(patient, registry.pubkey(), second, patient, 2);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!((index.verification_count, index.active_count), (2, 0));
+}
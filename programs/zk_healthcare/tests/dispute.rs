@@ -0,0 +1,125 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{
+    HealthcareError, PatientIndex, ProofFormat, RecordStatus, VerificationDispute, VerificationRecord,
+    DEFAULT_DISPUTE_WINDOW_SECS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const EVIDENCE: [u8; 32] = [0xe5; 32];
+
+/// A registry and the payer's record verified under it
+async fn verified(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, verification_address(&patient, 0))
+}
+
+/// A funded keypair, registered as an insurer when `registered` is set
+async fn insurer(ctx: &mut ProgramTestContext, registry: Pubkey, registered: bool) -> Keypair {
+    let insurer = Keypair::new();
+    let payer = ctx.payer.pubkey();
+    let mut ixs = vec![system_instruction::transfer(&payer, &insurer.pubkey(), 1_000_000_000)];
+    if registered {
+        ixs.push(register_insurer_ix(payer, registry, insurer.pubkey()));
+    }
+    send(ctx, &ixs, &[]).await.unwrap();
+    insurer
+}
+
+#[tokio::test]
+async fn test_dismissed_dispute_restores_the_record() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+
+    let stranger = insurer(&mut ctx, registry.pubkey(), false).await;
+    let ix = dispute_verification_ix(registry.pubkey(), verification, patient, stranger.pubkey(), false, EVIDENCE);
+    assert_error(send(&mut ctx, &[ix], &[&stranger]).await, HealthcareError::NotAuthorizedToDispute);
+
+    let insurer = insurer(&mut ctx, registry.pubkey(), true).await;
+    let ix = dispute_verification_ix(registry.pubkey(), verification, patient, insurer.pubkey(), true, EVIDENCE);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.status, RecordStatus::Disputed);
+    let dispute: VerificationDispute = fetch(&mut ctx, VerificationDispute::address(&verification)).await;
+    assert_eq!((dispute.record, dispute.disputer), (verification, insurer.pubkey()));
+    assert_eq!(dispute.evidence_hash, EVIDENCE);
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.active_count, 0);
+    let ix = check_verification_ix(registry.pubkey(), verification);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordDisputed);
+
+    let ix = resolve_dispute_ix(patient, registry.pubkey(), verification, patient, insurer.pubkey(), false);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.status, RecordStatus::Verified);
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.active_count, 1);
+    let dispute = ctx.banks_client.get_account(VerificationDispute::address(&verification)).await.unwrap();
+    assert!(dispute.is_none());
+    // Past the slot, so the repeated check isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    let ix = check_verification_ix(registry.pubkey(), verification);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_upheld_dispute_revokes_the_record() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let patient = authority;
+
+    let ix = dispute_verification_ix(registry.pubkey(), verification, patient, authority, false, EVIDENCE);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = resolve_dispute_ix(authority, registry.pubkey(), verification, patient, authority, true);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!((record.status, record.revoked_by), (RecordStatus::Revoked, authority));
+    assert_ne!(record.revoked_at, 0);
+    let ix = check_verification_ix(registry.pubkey(), verification);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordRevoked);
+}
+
+#[tokio::test]
+async fn test_dispute_after_the_window_fails() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let insurer = insurer(&mut ctx, registry.pubkey(), true).await;
+
+    warp_clock(&mut ctx, DEFAULT_DISPUTE_WINDOW_SECS).await;
+    let ix = dispute_verification_ix(registry.pubkey(), verification, patient, insurer.pubkey(), true, EVIDENCE);
+    assert_error(send(&mut ctx, &[ix], &[&insurer]).await, HealthcareError::DisputeWindowClosed);
+
+    // A longer window reopens it for records already written
+    let ix = set_dispute_window_ix(patient, registry.pubkey(), 2 * DEFAULT_DISPUTE_WINDOW_SECS);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Past the slot, so the repeated dispute isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    let ix = dispute_verification_ix(registry.pubkey(), verification, patient, insurer.pubkey(), true, EVIDENCE);
+    send(&mut ctx, &[ix], &[&insurer]).await.unwrap();
+}
@@ -0,0 +1,106 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{CurveId, HealthcareError, HealthcareRegistry, ProofFormat, ProvingScheme, VkConfig};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    circuit_id: &str,
+    fixture: &Fixture,
+) -> Result<(), BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        circuit_id,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[ix], &[]).await
+}
+
+#[tokio::test]
+async fn test_initialize_stores_registry_domain() {
+    let mut ctx = start().await;
+    let registry_a = initialize_registry(&mut ctx).await;
+    let registry_b = initialize_registry(&mut ctx).await;
+
+    let a: HealthcareRegistry = fetch(&mut ctx, registry_a.pubkey()).await;
+    let b: HealthcareRegistry = fetch(&mut ctx, registry_b.pubkey()).await;
+    assert_eq!(a.domain, zk_healthcare::registry_domain(&registry_a.pubkey()));
+    assert_ne!(a.domain, b.domain);
+}
+
+#[tokio::test]
+async fn test_proof_bound_to_registry_a_rejected_by_registry_b() {
+    let mut ctx = start().await;
+    let registry_a = initialize_registry(&mut ctx).await;
+    let registry_b = initialize_registry(&mut ctx).await;
+
+    let domain_a = domain_input(&mut ctx, registry_a.pubkey()).await;
+    let fixture = bound_square_fixture(1, vec![domain_a]);
+    let config = VkConfig { domain_input: Some(0), ..VkConfig::default() };
+    upload_vk_with(&mut ctx, registry_a.pubkey(), "circuit_a", &fixture.vk_bytes, config).await;
+    upload_vk_with(&mut ctx, registry_b.pubkey(), "circuit_b", &fixture.vk_bytes, config).await;
+
+    // Same circuit deployed under B: the domain slot carries A's value
+    assert_error(
+        submit(&mut ctx, &registry_b, "circuit_b", &fixture).await,
+        HealthcareError::PublicInputDomainMismatch,
+    );
+    // A's key routed through B's registry account is not B's circuit
+    assert_error(
+        submit(&mut ctx, &registry_b, "circuit_a", &fixture).await,
+        HealthcareError::WrongCircuitForType,
+    );
+    // and even once B approves it, the domain slot still carries A's value
+    let ix = set_type_circuit_ix(
+        ctx.payer.pubkey(),
+        registry_b.pubkey(),
+        "circuit_a",
+        zk_healthcare::VerificationType::Eligibility,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Past the slot, so the repeated submission isn't taken for the refused one
+    warp_clock(&mut ctx, 0).await;
+    assert_error(
+        submit(&mut ctx, &registry_b, "circuit_a", &fixture).await,
+        HealthcareError::PublicInputDomainMismatch,
+    );
+
+    submit(&mut ctx, &registry_a, "circuit_a", &fixture).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_domain_slot_must_be_a_free_public_input() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = bound_square_fixture(1, vec![patient_input(&ctx.payer.pubkey())]);
+    let authority = ctx.payer.pubkey();
+    let total_len = fixture.vk_bytes.len() as u32;
+
+    for (binds_patient, slot) in [(false, 2), (true, 0)] {
+        let config = VkConfig {
+            n_public: 2,
+            binds_patient,
+            domain_input: Some(slot),
+            freshness_window_secs: None,
+            curve: CurveId::Bn254,
+            scheme: ProvingScheme::Groth16,
+            ..VkConfig::default()
+        };
+        let ix = register_vk_ix(authority, registry.pubkey(), "circuit", total_len, config, [0; 32]);
+        assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidDomainInput);
+    }
+}
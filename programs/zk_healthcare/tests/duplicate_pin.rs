@@ -0,0 +1,64 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_sdk::signature::Signer;
+use zk_healthcare::{HealthcareError, HealthcareRegistry, IpfsPinRecord};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const V1_CID: &str = "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm";
+
+#[tokio::test]
+async fn test_same_content_is_pinned_once_per_patient() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, CID, [7; 32])], &[]).await.unwrap();
+    let address = IpfsPinRecord::address(&patient, &[7; 32]);
+    let pinned: IpfsPinRecord = fetch(&mut ctx, address).await;
+
+    // Under another CID too: the content is what counts. Past the slot, so the
+    // repeated pin isn't taken for the first.
+    for cid in [CID, V1_CID] {
+        warp_clock(&mut ctx, 0).await;
+        let ix = pin_medical_data_ix(registry.pubkey(), patient, cid, [7; 32]);
+        let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+        assert_error(result, HealthcareError::AlreadyPinned);
+        assert!(logs.iter().any(|log| log.ends_with(&format!("Content already pinned at {address}"))), "{logs:?}");
+    }
+    let pin: IpfsPinRecord = fetch(&mut ctx, address).await;
+    assert_eq!((pin.pinned_at, pin.cid_string()), (pinned.pinned_at, CID.to_string()));
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.ipfs_pin_count, 1);
+
+    // Once unpinned it may be pinned again
+    let ix = unpin_medical_data_ix(registry.pubkey(), address, [7; 32], patient, patient, patient, patient);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, V1_CID, [7; 32])], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, address).await;
+    assert_eq!(pin.cid_string(), V1_CID);
+}
+
+#[tokio::test]
+async fn test_other_patients_and_other_content_are_pinned() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, CID, [7; 32])], &[]).await.unwrap();
+
+    // Another patient pinning the same content gets a pin of their own
+    let other = funded(&mut ctx).await;
+    let ix = pin_medical_data_ix(registry.pubkey(), other.pubkey(), CID, [7; 32]);
+    send(&mut ctx, &[ix], &[&other]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&other.pubkey(), &[7; 32])).await;
+    assert_eq!(pin.patient, other.pubkey());
+
+    // and so does other content of the first patient's, under the same CID
+    send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, CID, [8; 32])], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &[8; 32])).await;
+    assert_eq!((pin.patient, pin.data_hash), (patient, [8; 32]));
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.ipfs_pin_count, 3);
+}
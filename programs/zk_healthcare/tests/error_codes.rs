@@ -0,0 +1,116 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{fixtures, HealthcareError, ProofFormat, VkConfig};
+
+const CIRCUIT: &str = "preimage_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn setup(ctx: &mut ProgramTestContext) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes()).await;
+    registry
+}
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    proof: Vec<u8>,
+    public_inputs: Vec<u8>,
+) -> (Result<(), BanksClientError>, Vec<String>) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        proof,
+        ProofFormat::Uncompressed,
+        public_inputs,
+        CID,
+    );
+    send_logged(ctx, &[ix], &[]).await
+}
+
+fn logged(logs: &[String], needle: &str) -> bool {
+    logs.iter().any(|log| log.contains(needle))
+}
+
+#[tokio::test]
+async fn test_invalid_proof_encoding_names_offset() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+
+    // B's first coordinate pushed past the field modulus
+    let mut proof = fixtures::valid_proof();
+    proof[64..96].fill(0xff);
+    let (result, logs) = submit(&mut ctx, &registry, proof, fixtures::public_inputs()).await;
+    assert_error(result, HealthcareError::InvalidProofEncoding);
+    assert!(logged(&logs, "Proof point at byte 64 is not a valid encoding"));
+}
+
+#[tokio::test]
+async fn test_invalid_public_input_encoding() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+
+    // One element and a stray byte
+    let mut inputs = fixtures::public_inputs();
+    inputs.push(0);
+    let (result, logs) = submit(&mut ctx, &registry, fixtures::valid_proof(), inputs).await;
+    assert_error(result, HealthcareError::InvalidPublicInputEncoding);
+    assert!(logged(&logs, "Public inputs are 33 bytes"));
+}
+
+#[tokio::test]
+async fn test_public_input_count_mismatch() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+
+    let inputs = [fixtures::public_inputs(), fixtures::public_inputs()].concat();
+    let (result, logs) = submit(&mut ctx, &registry, fixtures::valid_proof(), inputs).await;
+    assert_error(result, HealthcareError::PublicInputCountMismatch);
+    assert!(logged(&logs, "Expected 1 public inputs, got 2"));
+}
+
+#[tokio::test]
+async fn test_verifying_key_deserialize_failed_names_offset() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+
+    // The first IC point follows alpha, beta, gamma, delta and the 8-byte IC length
+    let mut vk_bytes = fixtures::vk_bytes();
+    vk_bytes[456..488].fill(0xff);
+    let config = VkConfig { n_public: fixtures::N_PUBLIC, ..VkConfig::default() };
+    let vk_hash = keccak::hash(&vk_bytes).to_bytes();
+    let ix = register_vk_ix(authority, registry.pubkey(), CIRCUIT, vk_bytes.len() as u32, config, vk_hash);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    grow_vk_account(&mut ctx, CIRCUIT, vk_bytes.len() as u32).await;
+    for (i, chunk) in vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_chunk_ix(authority, CIRCUIT, (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+
+    let (result, logs) = send_logged(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await;
+    assert_error(result, HealthcareError::VerifyingKeyDeserializeFailed);
+    assert!(logged(&logs, "Verifying key is malformed at byte 456"));
+}
+
+#[tokio::test]
+async fn test_pairing_check_failed() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+
+    // A well-formed proof of a different statement
+    let mut inputs = fixtures::public_inputs();
+    inputs[0] ^= 1;
+    let (result, _) = submit(&mut ctx, &registry, fixtures::valid_proof(), inputs).await;
+    assert_error(result, HealthcareError::PairingCheckFailed);
+}
@@ -0,0 +1,77 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// The registry numbering its events across instructions, read from logs alone.
+// Captures events by swapping the process-wide syscall stubs, so this binary
+// holds a single test.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::client::event_header;
+use zk_healthcare::{CircuitStatus, HealthcareError, HealthcareRegistry, ProofFormat};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn replay(ctx: &mut ProgramTestContext, ix: Instruction, signers: &[&Keypair], logs: &mut Vec<String>) {
+    let (result, tx_logs) = send_logged(ctx, &[ix], signers).await;
+    result.unwrap();
+    logs.extend(tx_logs);
+}
+
+#[tokio::test]
+async fn test_events_are_numbered_without_gaps() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let first = fetch::<HealthcareRegistry>(&mut ctx, registry.pubkey()).await.event_seq;
+    let verify = |nonce: usize| {
+        verify_eligibility_ix(
+            registry.pubkey(),
+            nonce as u64,
+            CIRCUIT,
+            patient,
+            fixtures[nonce].proof.clone(),
+            ProofFormat::Uncompressed,
+            fixtures[nonce].public_inputs.clone(),
+            CID,
+        )
+    };
+    let records = [verification_address(&patient, 0), verification_address(&patient, 1)];
+
+    let mut logs = Vec::new();
+    replay(&mut ctx, verify(0), &[], &mut logs).await;
+    let ix = update_metadata_ix(registry.pubkey(), records[0], patient, vec![1; 8]);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+    let ix = pin_medical_data_ix(registry.pubkey(), patient, CID, [7; 32]);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+    let (proof, inputs) = (fixtures[1].proof.clone(), fixtures[1].public_inputs.clone());
+    let ix = verify_proof_readonly_ix(registry.pubkey(), CIRCUIT, proof, ProofFormat::Uncompressed, inputs);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+    replay(&mut ctx, with_force_new(verify(1)), &[], &mut logs).await;
+    let revoke = revoke_verification_ix(patient, registry.pubkey(), records[1], patient, 1);
+    replay(&mut ctx, revoke.clone(), &[], &mut logs).await;
+
+    // A refused instruction takes no number
+    warp_clock(&mut ctx, 0).await;
+    assert_error(send(&mut ctx, &[revoke], &[]).await, HealthcareError::AlreadyRevoked);
+
+    let ix = commit_checkpoint_ix(patient, registry.pubkey(), [7; 32], 0, first);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+    let ix = set_circuit_status_ix(patient, registry.pubkey(), CIRCUIT, CircuitStatus::Deprecated, 1);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+
+    // `ProofChecked`, from the read-only check, is the one event without a number
+    let headers: Vec<_> = event_data(&logs).iter().filter_map(|event| event_header(event)).collect();
+    assert_eq!(headers.len(), event_data(&logs).len() - 1);
+    let end = first + headers.len() as u64;
+    assert_eq!(headers.iter().map(|(seq, _)| *seq).collect::<Vec<_>>(), (first..end).collect::<Vec<_>>());
+    assert!(headers.iter().all(|(_, from)| *from == registry.pubkey()));
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.event_seq, end);
+}
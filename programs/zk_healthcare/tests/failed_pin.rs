@@ -0,0 +1,126 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, HealthcareRegistry, IpfsPinRecord, PinAbandoned, PinContentClaim, PinRetried, PinStatus,
+    DEFAULT_FAILED_PIN_TIMEOUT_SECS,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const V1_CID: &str = "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm";
+
+/// A registry with the payer as its pinning oracle, and a pin of a funded
+/// patient's under it that the oracle reported failed
+async fn failed_pin(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    let patient = funded(ctx).await;
+    let ix = pin_medical_data_ix(registry.pubkey(), patient.pubkey(), CID, [7; 32]);
+    send(ctx, &[ix], &[&patient]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient.pubkey(), &[7; 32]);
+
+    let oracle = ctx.payer.pubkey();
+    send(ctx, &[add_pinning_oracle_ix(oracle, registry.pubkey(), oracle)], &[]).await.unwrap();
+    send(ctx, &[confirm_pin_ix(registry.pubkey(), pin_record, oracle, false, "cluster")], &[]).await.unwrap();
+    (registry, pin_record, patient)
+}
+
+#[tokio::test]
+async fn test_retry_then_confirm() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, patient) = failed_pin(&mut ctx).await;
+    let oracle = ctx.payer.pubkey();
+
+    // Only the patient retries
+    let ix = retry_pin_ix(registry.pubkey(), pin_record, oracle, None);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinRecordPatientMismatch);
+
+    // under the CID the content was re-added as
+    let ix = retry_pin_ix(registry.pubkey(), pin_record, patient.pubkey(), Some(V1_CID));
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&patient]).await;
+    result.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle, pin.settled_at), (PinStatus::Requested, Pubkey::default(), 0));
+    assert_eq!((pin.cid_string(), pin.data_hash), (V1_CID.to_string(), [7; 32]));
+    let event = &events::<PinRetried>(&logs)[0];
+    assert_eq!((event.pin_record, event.patient), (pin_record, patient.pubkey()));
+    assert_eq!((event.ipfs_cid.as_str(), event.cid_changed), (V1_CID, true));
+
+    // A requested pin has nothing to retry
+    warp_clock(&mut ctx, 0).await;
+    let ix = retry_pin_ix(registry.pubkey(), pin_record, patient.pubkey(), None);
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::PinNotFailed);
+
+    send(&mut ctx, &[confirm_pin_ix(registry.pubkey(), pin_record, oracle, true, "cluster")], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle), (PinStatus::Confirmed, oracle));
+    let ix = record_access_ix(registry.pubkey(), pin_record, patient.pubkey(), false);
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+
+    // and a confirmed one can't be abandoned
+    let patient_key = patient.pubkey();
+    let ix = abandon_failed_pin_ix(registry.pubkey(), pin_record, [7; 32], patient_key, patient_key, patient_key);
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::PinNotFailed);
+}
+
+#[tokio::test]
+async fn test_patient_abandons_for_a_refund() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, patient) = failed_pin(&mut ctx).await;
+    let patient_key = patient.pubkey();
+    let content_claim = PinContentClaim::address(&patient_key, &[7; 32]);
+    let (rent, before) = (balance(&mut ctx, pin_record).await, balance(&mut ctx, patient_key).await);
+    let claim_rent = balance(&mut ctx, content_claim).await;
+    let pins: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+
+    // The payer covers the fee, so the patient's balance moves by the rent
+    // alone, the content claim's with the pin's
+    let ix = abandon_failed_pin_ix(registry.pubkey(), pin_record, [7; 32], patient_key, patient_key, patient_key);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&patient]).await;
+    result.unwrap();
+    assert!(ctx.banks_client.get_account(pin_record).await.unwrap().is_none());
+    assert!(ctx.banks_client.get_account(content_claim).await.unwrap().is_none());
+    assert_eq!(balance(&mut ctx, patient_key).await, before + rent + claim_rent);
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.ipfs_pin_count, pins.ipfs_pin_count - 1);
+    let event = &events::<PinAbandoned>(&logs)[0];
+    assert_eq!((event.pin_record, event.rent_payer, event.cranker), (pin_record, patient_key, patient_key));
+    assert_eq!(event.lamports, rent);
+}
+
+#[tokio::test]
+async fn test_anyone_cleans_up_after_the_timeout() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, patient) = failed_pin(&mut ctx).await;
+    let (patient_key, cranker) = (patient.pubkey(), ctx.payer.pubkey());
+    let failed_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.settled_at;
+    let abandon = || abandon_failed_pin_ix(registry.pubkey(), pin_record, [7; 32], patient_key, patient_key, cranker);
+
+    warp_clock_to(&mut ctx, failed_at + DEFAULT_FAILED_PIN_TIMEOUT_SECS - 1).await;
+    assert_error(send(&mut ctx, &[abandon()], &[]).await, HealthcareError::FailedPinNotAbandonable);
+
+    // A zero timeout leaves the pin to its patient however long it waits
+    let ix = set_failed_pin_timeout_ix(cranker, registry.pubkey(), -1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidFailedPinTimeout);
+    send(&mut ctx, &[set_failed_pin_timeout_ix(cranker, registry.pubkey(), 0)], &[]).await.unwrap();
+    warp_clock(&mut ctx, DEFAULT_FAILED_PIN_TIMEOUT_SECS).await;
+    assert_error(send(&mut ctx, &[abandon()], &[]).await, HealthcareError::FailedPinNotAbandonable);
+
+    // Past the timeout anyone closes it, and the rent still goes to the payer
+    let ix = set_failed_pin_timeout_ix(cranker, registry.pubkey(), DEFAULT_FAILED_PIN_TIMEOUT_SECS);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    let (rent, before) = (balance(&mut ctx, pin_record).await, balance(&mut ctx, patient_key).await);
+    let claim_rent = balance(&mut ctx, PinContentClaim::address(&patient_key, &[7; 32])).await;
+    let (result, logs) = send_logged(&mut ctx, &[abandon()], &[]).await;
+    result.unwrap();
+    assert_eq!(balance(&mut ctx, patient_key).await, before + rent + claim_rent);
+    let event = &events::<PinAbandoned>(&logs)[0];
+    assert_eq!((event.rent_payer, event.cranker), (patient_key, cranker));
+}
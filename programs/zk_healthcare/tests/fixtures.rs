@@ -0,0 +1,60 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{fixtures, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "preimage_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn submit(ctx: &mut ProgramTestContext, registry: &Keypair, proof: Vec<u8>) -> Result<Pubkey, BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        proof,
+        ProofFormat::Uncompressed,
+        fixtures::public_inputs(),
+        CID,
+    );
+    send(ctx, &[ix], &[]).await?;
+    Ok(verification)
+}
+
+#[tokio::test]
+async fn test_fixture_proof_verifies() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes()).await;
+
+    let verification = submit(&mut ctx, &registry, fixtures::valid_proof()).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+    assert_eq!(record.circuit_id, CIRCUIT);
+}
+
+#[tokio::test]
+async fn test_mutated_fixture_proof_fails() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes()).await;
+
+    // Low byte of C's x coordinate
+    let mut proof = fixtures::valid_proof();
+    proof[192] ^= 1;
+    let check = |proof: Vec<u8>| {
+        let inputs = fixtures::public_inputs();
+        verify_proof_readonly_ix(registry.pubkey(), CIRCUIT, proof, ProofFormat::Uncompressed, inputs)
+    };
+    assert_eq!(simulate_return_data(&mut ctx, &[check(proof.clone())], &[]).await.data, [0]);
+    assert!(submit(&mut ctx, &registry, proof).await.is_err());
+    assert_eq!(simulate_return_data(&mut ctx, &[check(fixtures::valid_proof())], &[]).await.data, [1]);
+}
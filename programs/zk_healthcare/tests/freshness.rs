@@ -0,0 +1,146 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    CurveId, HealthcareError, ProofFormat, ProvingScheme, VerificationRecord, VkConfig, MAX_CLOCK_SKEW_SECS,
+};
+
+const CIRCUIT: &str = "eligibility_fresh";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const WINDOW_SECS: i64 = 600;
+
+/// A registry with a timestamped circuit registered; returns the bank's clock.
+/// Every `timestamped_square_fixture(1, _)` proves under the registered key.
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Clock) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = timestamped_square_fixture(1, 0);
+    upload_vk_with(
+        ctx,
+        registry.pubkey(),
+        CIRCUIT,
+        &fixture.vk_bytes,
+        VkConfig {
+            freshness_window_secs: Some(WINDOW_SECS),
+            ..VkConfig::default()
+        },
+    )
+    .await;
+    let clock = ctx.banks_client.get_sysvar().await.unwrap();
+    (registry, clock)
+}
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+) -> (Pubkey, Result<(), BanksClientError>) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let result = send(ctx, &[ix], &[]).await;
+    (verification, result)
+}
+
+#[tokio::test]
+async fn test_fresh_proof_accepted_and_slot_recorded() {
+    let mut ctx = start().await;
+    let (registry, clock) = setup(&mut ctx).await;
+
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp);
+    let (verification, result) = submit(&mut ctx, &registry, &fixture).await;
+    result.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+    assert_eq!(record.timestamp, clock.unix_timestamp);
+    assert_eq!(record.slot, clock.slot);
+}
+
+#[tokio::test]
+async fn test_proof_at_window_boundary_accepted() {
+    let mut ctx = start().await;
+    let (registry, clock) = setup(&mut ctx).await;
+
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp - WINDOW_SECS);
+    submit(&mut ctx, &registry, &fixture).await.1.unwrap();
+
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp - WINDOW_SECS - 1);
+    assert_error(submit(&mut ctx, &registry, &fixture).await.1, HealthcareError::ProofStale);
+}
+
+#[tokio::test]
+async fn test_day_old_proof_rejected() {
+    let mut ctx = start().await;
+    let (registry, clock) = setup(&mut ctx).await;
+
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp - 86_400);
+    assert_error(submit(&mut ctx, &registry, &fixture).await.1, HealthcareError::ProofStale);
+}
+
+#[tokio::test]
+async fn test_proof_goes_stale_as_clock_advances() {
+    let mut ctx = start().await;
+    let (registry, clock) = setup(&mut ctx).await;
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp);
+
+    warp_clock(&mut ctx, WINDOW_SECS + 1).await;
+    assert_error(submit(&mut ctx, &registry, &fixture).await.1, HealthcareError::ProofStale);
+}
+
+#[tokio::test]
+async fn test_future_timestamp_beyond_skew_rejected() {
+    let mut ctx = start().await;
+    let (registry, clock) = setup(&mut ctx).await;
+
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp + MAX_CLOCK_SKEW_SECS + 1);
+    assert_error(submit(&mut ctx, &registry, &fixture).await.1, HealthcareError::ProofTimestampInFuture);
+
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp + MAX_CLOCK_SKEW_SECS);
+    submit(&mut ctx, &registry, &fixture).await.1.unwrap();
+}
+
+#[tokio::test]
+async fn test_timestamp_slot_must_be_free() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = timestamped_square_fixture(1, 0);
+    let authority = ctx.payer.pubkey();
+    let total_len = fixture.vk_bytes.len() as u32;
+
+    // Slot 1, the timestamp, can't also carry the registry domain
+    let config = VkConfig {
+        n_public: 2,
+        binds_patient: false,
+        domain_input: Some(1),
+        freshness_window_secs: Some(WINDOW_SECS),
+        curve: CurveId::Bn254,
+        scheme: ProvingScheme::Groth16,
+        ..VkConfig::default()
+    };
+    let ix = register_vk_ix(authority, registry.pubkey(), CIRCUIT, total_len, config, [0; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidDomainInput);
+
+    let config = VkConfig {
+        domain_input: None,
+        freshness_window_secs: Some(0),
+        ..config
+    };
+    let ix = register_vk_ix(authority, registry.pubkey(), CIRCUIT, total_len, config, [0; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidFreshnessWindow);
+}
@@ -0,0 +1,169 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    DataPinned, EligibilityVerified, GuardianConsent, GuardianScope, HealthcareError, IpfsPinRecord, PatientIndex,
+    VerificationRecord,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with `CIRCUIT` uploaded, a funded guardian and a ward without a
+/// wallet of their own
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Keypair, Pubkey) {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &square_fixture(1).vk_bytes).await;
+    let guardian = funded(ctx).await;
+    (registry, guardian, Pubkey::new_unique())
+}
+
+/// The payer, as registry authority, grants `scopes` that never expire
+async fn grant(ctx: &mut ProgramTestContext, registry: &Keypair, guardian: &Keypair, ward: Pubkey, scopes: u8) {
+    let authority = ctx.payer.pubkey();
+    let ix = grant_guardian_consent_ix(registry.pubkey(), authority, guardian.pubkey(), ward, scopes, 0, false);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_guardian_verifies_for_ward() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, guardian, ward) = setup(&mut ctx).await;
+    grant(&mut ctx, &registry, &guardian, ward, GuardianScope::Verify.bit()).await;
+
+    let ix = verify_eligibility_for_ward_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        guardian.pubkey(),
+        ward,
+        &square_fixture(1),
+        CID,
+    );
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&guardian]).await;
+    result.unwrap();
+    let events = events::<EligibilityVerified>(&logs);
+    assert_eq!((events[0].patient, events[0].guardian), (ward, Some(guardian.pubkey())));
+
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&ward, 0)).await;
+    assert_eq!((record.patient_pubkey, record.rent_payer), (ward, guardian.pubkey()));
+    assert!(record.is_verified());
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&ward)).await;
+    assert_eq!((index.verification_count, index.next_record_nonce), (1, 1));
+}
+
+#[tokio::test]
+async fn test_provider_grants_pin_consent() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, guardian, ward) = setup(&mut ctx).await;
+    let provider = funded(&mut ctx).await;
+    let ix = register_provider_ix(ctx.payer.pubkey(), registry.pubkey(), provider.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let pin = GuardianScope::Pin.bit();
+    let ix = grant_guardian_consent_ix(registry.pubkey(), provider.pubkey(), guardian.pubkey(), ward, pin, 0, true);
+    send(&mut ctx, &[ix], &[&provider]).await.unwrap();
+    let consent: GuardianConsent =
+        fetch(&mut ctx, GuardianConsent::address(&registry.pubkey(), &guardian.pubkey(), &ward)).await;
+    assert_eq!((consent.granted_by, consent.scopes), (provider.pubkey(), pin));
+
+    let ix = pin_medical_data_for_ward_ix(registry.pubkey(), guardian.pubkey(), ward, CID, [5; 32]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&guardian]).await;
+    result.unwrap();
+    let events = events::<DataPinned>(&logs);
+    assert_eq!((events[0].patient, events[0].guardian), (ward, Some(guardian.pubkey())));
+    let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&ward, &[5; 32])).await;
+    assert_eq!(pin.patient, ward);
+}
+
+#[tokio::test]
+async fn test_out_of_scope_action_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, guardian, ward) = setup(&mut ctx).await;
+    grant(&mut ctx, &registry, &guardian, ward, GuardianScope::Pin.bit()).await;
+
+    let ix = verify_eligibility_for_ward_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        guardian.pubkey(),
+        ward,
+        &square_fixture(1),
+        CID,
+    );
+    assert_error(send(&mut ctx, &[ix], &[&guardian]).await, HealthcareError::GuardianScopeMissing);
+    assert!(ctx.banks_client.get_account(verification_address(&ward, 0)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_action_after_revocation_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, guardian, ward) = setup(&mut ctx).await;
+    grant(&mut ctx, &registry, &guardian, ward, GuardianScope::ALL).await;
+    let ix = verify_eligibility_for_ward_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        guardian.pubkey(),
+        ward,
+        &square_fixture(1),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[&guardian]).await.unwrap();
+
+    let consent = GuardianConsent::address(&registry.pubkey(), &guardian.pubkey(), &ward);
+    let outsider = Keypair::new();
+    let ix = revoke_guardian_consent_ix(registry.pubkey(), consent, outsider.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&outsider]).await, HealthcareError::NotAuthorizedToRevokeConsent);
+    let ix = revoke_guardian_consent_ix(registry.pubkey(), consent, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = pin_medical_data_for_ward_ix(registry.pubkey(), guardian.pubkey(), ward, CID, [5; 32]);
+    assert_error(send(&mut ctx, &[ix], &[&guardian]).await, HealthcareError::GuardianConsentRevoked);
+    // What the guardian did before stands
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&ward, 0)).await;
+    assert!(record.is_verified());
+}
+
+#[tokio::test]
+async fn test_consent_lapses_at_its_expiry() {
+    let mut ctx = start().await;
+    let (registry, guardian, ward) = setup(&mut ctx).await;
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let authority = ctx.payer.pubkey();
+    let scopes = GuardianScope::Pin.bit();
+    let expires_at = clock.unix_timestamp + 60;
+    let ix = grant_guardian_consent_ix(
+        registry.pubkey(),
+        authority,
+        guardian.pubkey(),
+        ward,
+        scopes,
+        expires_at,
+        false,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    warp_clock(&mut ctx, 60).await;
+    let ix = pin_medical_data_for_ward_ix(registry.pubkey(), guardian.pubkey(), ward, CID, [5; 32]);
+    assert_error(send(&mut ctx, &[ix], &[&guardian]).await, HealthcareError::GuardianConsentExpired);
+}
+
+#[tokio::test]
+async fn test_only_authority_or_provider_grants_consent() {
+    let mut ctx = start().await;
+    let (registry, guardian, ward) = setup(&mut ctx).await;
+    let stranger = funded(&mut ctx).await;
+    let scopes = GuardianScope::Verify.bit();
+    let ix = grant_guardian_consent_ix(registry.pubkey(), stranger.pubkey(), guardian.pubkey(), ward, scopes, 0, false);
+    assert_error(send(&mut ctx, &[ix], &[&stranger]).await, HealthcareError::NotAuthorizedToGrantConsent);
+
+    let authority = ctx.payer.pubkey();
+    let ix = grant_guardian_consent_ix(registry.pubkey(), authority, guardian.pubkey(), ward, 1 << 5, 0, false);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidGuardianConsent);
+}
@@ -0,0 +1,97 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Heap frame tests. The allocator only exists in the SBF build (native
+// program-test runs on the host's), so these run against the artifact:
+//
+//     anchor build && SBF_OUT_DIR=target/deploy cargo test --test heap -- --ignored
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use ark_bn254::Fr;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use zk_healthcare::heap::HEAP_FRAME_BYTES;
+use zk_healthcare::{ProofFormat, VerificationRecord, MAX_BATCH_SIZE};
+
+const CIRCUIT: &str = "eligibility_wide";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// `instructions` under the full compute limit and, if `heap_frame` is set, the
+/// largest heap frame
+fn budgeted(instructions: Vec<Instruction>, heap_frame: bool) -> Vec<Instruction> {
+    let mut budgeted = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+    if heap_frame {
+        budgeted.push(ComputeBudgetInstruction::request_heap_frame(HEAP_FRAME_BYTES as u32));
+    }
+    budgeted.extend(instructions);
+    budgeted
+}
+
+/// A proof of statement `statement` for a circuit with `n_public` inputs; every
+/// statement shares one key per input count
+fn wide_fixture(n_public: u64, statement: u64) -> Fixture {
+    bound_square_fixture(1, (1..n_public).map(|input| Fr::from(statement * 1_000 + input)).collect())
+}
+
+async fn setup(ctx: &mut ProgramTestContext, n_public: u64) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &wide_fixture(n_public, 0).vk_bytes).await;
+    registry
+}
+
+/// A batch of the most proofs an instruction takes, each with 24 public inputs:
+/// their submissions, parsed inputs, records and the batch transcript hold more
+/// than the 32KB default heap
+fn wide_batch(registry: &Keypair, patient: Pubkey) -> Instruction {
+    let submissions = (0..MAX_BATCH_SIZE as u64).map(|statement| submission(&wide_fixture(24, statement), CID));
+    let format = ProofFormat::Uncompressed;
+    verify_eligibility_batch_ix(registry.pubkey(), 0, CIRCUIT, patient, format, submissions.collect())
+}
+
+#[tokio::test]
+#[ignore = "requires the SBF build of zk_healthcare"]
+async fn test_eight_inputs_verify_in_requested_heap_frame() {
+    let mut ctx = start_sbf().await;
+    let registry = setup(&mut ctx, 8).await;
+    let fixture = wide_fixture(8, 1);
+    assert_eq!(n_public(&fixture.vk_bytes), 8);
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &budgeted(vec![ix], true), &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+}
+
+#[tokio::test]
+#[ignore = "requires the SBF build of zk_healthcare"]
+async fn test_default_heap_frame_regression() {
+    let mut ctx = start_sbf().await;
+    let registry = setup(&mut ctx, 24).await;
+    let patient = ctx.payer.pubkey();
+
+    // The stock allocator stopped at 32KB whatever frame was requested, so this
+    // batch aborted with or without one. It still does without.
+    let err = send(&mut ctx, &budgeted(vec![wide_batch(&registry, patient)], false), &[]).await.unwrap_err();
+    assert_eq!(
+        err.unwrap(),
+        TransactionError::InstructionError(1, InstructionError::ProgramFailedToComplete)
+    );
+    send(&mut ctx, &budgeted(vec![wide_batch(&registry, patient)], true), &[]).await.unwrap();
+}
@@ -0,0 +1,91 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, PatientIndex, ProofFormat, RecordStatus, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with two proofs under its one circuit, the first already
+/// verified by the payer at nonce 0
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Fixture) {
+    let registry = initialize_registry(ctx).await;
+    let mut fixtures = batch_fixtures(2);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let ix = verify_ix(ctx, &registry, 0, &fixtures[0]);
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, fixtures.pop().unwrap())
+}
+
+fn verify_ix(ctx: &ProgramTestContext, registry: &Keypair, record_nonce: u64, fixture: &Fixture) -> Instruction {
+    verify_eligibility_ix(
+        registry.pubkey(),
+        record_nonce,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    )
+}
+
+#[tokio::test]
+async fn test_second_active_verification_rejected() {
+    let mut ctx = start().await;
+    let (registry, fixture) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let existing = verification_address(&patient, 0);
+
+    let ix = verify_ix(&ctx, &registry, 1, &fixture);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::ActiveVerificationExists);
+    // The error names the record standing in the way
+    assert!(logs.iter().any(|log| log.contains(&existing.to_string())));
+
+    assert!(ctx.banks_client.get_account(verification_address(&patient, 1)).await.unwrap().is_none());
+    let record: VerificationRecord = fetch(&mut ctx, existing).await;
+    assert!(record.is_verified());
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!((index.verification_count, index.active_count, index.next_record_nonce), (1, 1, 1));
+}
+
+#[tokio::test]
+async fn test_force_new_expires_the_previous_record() {
+    let mut ctx = start().await;
+    let (registry, fixture) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+
+    let ix = with_force_new(verify_ix(&ctx, &registry, 1, &fixture));
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let previous: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 0)).await;
+    assert_eq!(previous.status, RecordStatus::Expired);
+    assert!(!previous.is_valid);
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 1)).await;
+    assert!(record.is_verified());
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!((index.verification_count, index.active_count), (2, 1));
+}
+
+#[tokio::test]
+async fn test_revoked_record_does_not_block() {
+    let mut ctx = start().await;
+    let (registry, fixture) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let previous = verification_address(&patient, 0);
+    let ix = revoke_verification_ix(patient, registry.pubkey(), previous, patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = verify_ix(&ctx, &registry, 1, &fixture);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, previous).await;
+    assert_eq!(record.status, RecordStatus::Revoked);
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 1)).await;
+    assert!(record.is_verified());
+}
@@ -0,0 +1,125 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{fixtures, HealthcareError, IpfsPinRecord, ProofFormat, VerificationRecord, MAX_IPFS_CID_LEN};
+
+const CIRCUIT: &str = "preimage_v1";
+const V0_CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+/// raw codec, sha2-256 digest
+const V1_CID: &str = "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm";
+/// raw codec, a 35-byte blake3 digest: the longest CID a record holds, and
+/// longer than a pin's multihash
+const LONGEST_CID: &str = "bafkr4i7f2h3os4dkkdgeftu4tdpzbp5eluaa4sqtlacgf6i472uidgzna6rnkoi";
+
+async fn submit(ctx: &mut ProgramTestContext, registry: &Keypair, ipfs_hash: &str) -> Result<(), BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixtures::valid_proof(),
+        ProofFormat::Uncompressed,
+        fixtures::public_inputs(),
+        ipfs_hash,
+    );
+    send(ctx, &[ix], &[]).await
+}
+
+#[tokio::test]
+async fn test_long_cid_is_refused() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes()).await;
+
+    let long_cid = "b".repeat(100);
+    assert_error(submit(&mut ctx, &registry, &long_cid).await, HealthcareError::IpfsCidTooLong);
+
+    // A CID of the full length fills the record's account exactly
+    let cid = LONGEST_CID.to_string();
+    assert_eq!(cid.len(), MAX_IPFS_CID_LEN);
+    submit(&mut ctx, &registry, &cid).await.unwrap();
+    let address = verification_address(&ctx.payer.pubkey(), 0);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.ipfs_hash, cid);
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE);
+}
+
+#[tokio::test]
+async fn test_long_pin_cid_is_refused() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+
+    let long_cid = "b".repeat(100);
+    let ix = pin_medical_data_ix(registry.pubkey(), patient, &long_cid, [7; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::IpfsCidTooLong);
+
+    // A record's longest CID carries more multihash than a pin keeps
+    let ix = pin_medical_data_ix(registry.pubkey(), patient, LONGEST_CID, [7; 32]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::InvalidCid);
+    assert!(logs.iter().any(|log| log.ends_with("Invalid CID: multihash longer than a pin holds")), "{logs:?}");
+}
+
+#[tokio::test]
+async fn test_cids_of_either_version_are_accepted() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes()).await;
+    let patient = ctx.payer.pubkey();
+
+    submit(&mut ctx, &registry, V1_CID).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 0)).await;
+    assert_eq!(record.ipfs_hash, V1_CID);
+    for (cid, data_hash) in [(V0_CID, [7; 32]), (V1_CID, [8; 32])] {
+        send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, cid, data_hash)], &[]).await.unwrap();
+        let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &data_hash)).await;
+        assert_eq!(pin.cid_string(), cid);
+    }
+}
+
+#[tokio::test]
+async fn test_pin_stores_the_cid_decoded() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+
+    for (cid, cid_version, multicodec) in [(V0_CID, 0, 0x70), (V1_CID, 1, 0x55)] {
+        let data_hash = [cid_version + 7; 32];
+        send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), patient, cid, data_hash)], &[]).await.unwrap();
+        let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &data_hash)).await;
+        assert_eq!((pin.version, pin.cid_version, pin.multicodec), (IpfsPinRecord::VERSION, cid_version, multicodec));
+        // A sha2-256 multihash either way
+        assert_eq!(pin.multihash[..2], [0x12, 0x20]);
+        assert_eq!(pin.cid_string().as_bytes(), cid.as_bytes());
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_cids_are_refused() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures::vk_bytes()).await;
+    let patient = ctx.payer.pubkey();
+    let truncated = &V1_CID[..V1_CID.len() - 2];
+    let bad_character = V0_CID.replace('w', "0");
+
+    for (cid, rule) in [
+        (truncated, "multihash digest length"),
+        (bad_character.as_str(), "base58btc alphabet"),
+        ("", "empty"),
+    ] {
+        assert_error(submit(&mut ctx, &registry, cid).await, HealthcareError::InvalidCid);
+        let ix = pin_medical_data_ix(registry.pubkey(), patient, cid, [7; 32]);
+        let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+        assert_error(result, HealthcareError::InvalidCid);
+        assert!(logs.iter().any(|log| log.ends_with(&format!("Invalid CID: {rule}"))), "{logs:?}");
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    AccessPass, HealthcareError, IpfsPinRecord, KeyRotation, PatientIndex, PinContentClaim, ProofFormat,
+    VerificationRecord, VerificationType,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const ACCESS_CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry and two records verified under it for the payer, the second
+/// forced past the first, which is left expired
+async fn two_records(ctx: &mut ProgramTestContext) -> (Keypair, [Pubkey; 2]) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(2);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    for (nonce, fixture) in fixtures.iter().enumerate() {
+        let ix = verify_eligibility_ix(
+            registry.pubkey(),
+            nonce as u64,
+            CIRCUIT,
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+            CID,
+        );
+        let ix = if nonce == 0 { ix } else { with_force_new(ix) };
+        send(ctx, &[ix], &[]).await.unwrap();
+    }
+    (registry, [verification_address(&patient, 0), verification_address(&patient, 1)])
+}
+
+#[tokio::test]
+async fn test_rotated_key_claims_two_records() {
+    let mut ctx = start().await;
+    let (registry, records) = two_records(&mut ctx).await;
+    let old_key = ctx.payer.pubkey();
+    let before: [VerificationRecord; 2] = [fetch(&mut ctx, records[0]).await, fetch(&mut ctx, records[1]).await];
+    let new_key = funded(&mut ctx).await;
+
+    send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), old_key, new_key.pubkey())], &[]).await.unwrap();
+    let rotation: KeyRotation = fetch(&mut ctx, KeyRotation::address(&old_key)).await;
+    assert_eq!((rotation.old_key, rotation.new_key), (old_key, new_key.pubkey()));
+
+    let moved = [verification_address(&new_key.pubkey(), 0), verification_address(&new_key.pubkey(), 1)];
+    let ix = claim_record_ix(
+        registry.pubkey(),
+        old_key,
+        new_key.pubkey(),
+        &[(records[0], moved[0], Some(records[1])), (records[1], moved[1], None)],
+    );
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+
+    for (old, (address, new)) in before.iter().zip(records.iter().zip(moved)) {
+        assert!(ctx.banks_client.get_account(*address).await.unwrap().is_none());
+        let record: VerificationRecord = fetch(&mut ctx, new).await;
+        assert_eq!((record.patient_pubkey, record.rent_payer), (new_key.pubkey(), new_key.pubkey()));
+        assert_eq!((record.proof_hash, record.verification_id), (old.proof_hash, old.verification_id));
+        assert_eq!(record.status, old.status);
+    }
+    let old_index: PatientIndex = fetch(&mut ctx, patient_index_address(&old_key)).await;
+    assert_eq!((old_index.verification_count, old_index.active_count), (0, 0));
+    assert_eq!(old_index.type_counts[VerificationType::Eligibility as usize], 0);
+    let new_index: PatientIndex = fetch(&mut ctx, patient_index_address(&new_key.pubkey())).await;
+    assert_eq!((new_index.verification_count, new_index.active_count), (2, 1));
+    assert_eq!(new_index.type_counts[VerificationType::Eligibility as usize], 2);
+    assert_eq!(new_index.next_record_nonce, 2);
+    // The chain moves over in claim order
+    assert_eq!((old_index.last_record, new_index.last_record), (Pubkey::default(), moved[1]));
+    let newest: VerificationRecord = fetch(&mut ctx, moved[1]).await;
+    assert_eq!(newest.previous_record, Some(moved[0]));
+}
+
+#[tokio::test]
+async fn test_unrelated_key_cannot_claim() {
+    let mut ctx = start().await;
+    let (registry, records) = two_records(&mut ctx).await;
+    let old_key = ctx.payer.pubkey();
+    let new_key = funded(&mut ctx).await;
+    send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), old_key, new_key.pubkey())], &[]).await.unwrap();
+
+    let intruder = funded(&mut ctx).await;
+    let moved = verification_address(&intruder.pubkey(), 0);
+    let ix = claim_record_ix(registry.pubkey(), old_key, intruder.pubkey(), &[(records[0], moved, Some(records[1]))]);
+    assert_error(send(&mut ctx, &[ix], &[&intruder]).await, HealthcareError::RotationTargetMismatch);
+    let record: VerificationRecord = fetch(&mut ctx, records[0]).await;
+    assert_eq!(record.patient_pubkey, old_key);
+}
+
+#[tokio::test]
+async fn test_claim_must_use_the_next_record_address() {
+    let mut ctx = start().await;
+    let (registry, records) = two_records(&mut ctx).await;
+    let old_key = ctx.payer.pubkey();
+    let new_key = funded(&mut ctx).await;
+    send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), old_key, new_key.pubkey())], &[]).await.unwrap();
+
+    let skipped = verification_address(&new_key.pubkey(), 1);
+    let ix = claim_record_ix(registry.pubkey(), old_key, new_key.pubkey(), &[(records[0], skipped, Some(records[1]))]);
+    assert_error(send(&mut ctx, &[ix], &[&new_key]).await, HealthcareError::ClaimAccountMismatch);
+}
+
+#[tokio::test]
+async fn test_key_rotates_away_only_once() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let old_key = ctx.payer.pubkey();
+    let new_key = funded(&mut ctx).await;
+    send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), old_key, new_key.pubkey())], &[]).await.unwrap();
+
+    // The rotation account already exists
+    let other = Keypair::new();
+    let result = send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), old_key, other.pubkey())], &[]).await;
+    assert!(result.is_err());
+    let rotation: KeyRotation = fetch(&mut ctx, KeyRotation::address(&old_key)).await;
+    assert_eq!(rotation.new_key, new_key.pubkey());
+}
+
+#[tokio::test]
+async fn test_rotation_cannot_close_a_cycle() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let old_key = ctx.payer.pubkey();
+    let new_key = funded(&mut ctx).await;
+    let ix = rotate_patient_key_ix(registry.pubkey(), old_key, old_key);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RotationCycle);
+    send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), old_key, new_key.pubkey())], &[]).await.unwrap();
+
+    let ix = rotate_patient_key_ix(registry.pubkey(), new_key.pubkey(), old_key);
+    assert_error(send(&mut ctx, &[ix], &[&new_key]).await, HealthcareError::RotationCycle);
+    // Onwards to a third key is a chain, not a cycle
+    let third = Keypair::new();
+    let ix = rotate_patient_key_ix(registry.pubkey(), new_key.pubkey(), third.pubkey());
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_access_pass_and_pin_are_claimed() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), ACCESS_CIRCUIT, &fixture.vk_bytes).await;
+    let old_key = ctx.payer.pubkey();
+    let ix = set_type_circuit_ix(old_key, registry.pubkey(), ACCESS_CIRCUIT, VerificationType::AccessControl);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    send(&mut ctx, &[pin_medical_data_ix(registry.pubkey(), old_key, CID, [3; 32])], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&old_key, &[3; 32]);
+    confirm_pin(&mut ctx, registry.pubkey(), pin_record).await;
+    let resource = pin_record;
+    let ix = verify_access_control_ix(registry.pubkey(), ACCESS_CIRCUIT, old_key, &fixture, resource, 100, 3);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let new_key = funded(&mut ctx).await;
+    send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), old_key, new_key.pubkey())], &[]).await.unwrap();
+
+    let ix = claim_access_pass_ix(old_key, new_key.pubkey(), resource);
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+    let ix = claim_pin_record_ix(old_key, pin_record, [3; 32], new_key.pubkey());
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+
+    let old_pass = AccessPass::address(&old_key, &resource);
+    assert!(ctx.banks_client.get_account(old_pass).await.unwrap().is_none());
+    let pass: AccessPass = fetch(&mut ctx, AccessPass::address(&new_key.pubkey(), &resource)).await;
+    assert_eq!((pass.patient, pass.uses_remaining), (new_key.pubkey(), 3));
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.patient, new_key.pubkey());
+
+    // The content claim moved with the pin, so the new key can't pin the
+    // content again beside it
+    let old_claim = PinContentClaim::address(&old_key, &[3; 32]);
+    assert!(ctx.banks_client.get_account(old_claim).await.unwrap().is_none());
+    let claim: PinContentClaim = fetch(&mut ctx, PinContentClaim::address(&new_key.pubkey(), &[3; 32])).await;
+    assert_eq!(claim.pin_record, pin_record);
+    let ix = pin_medical_data_ix(registry.pubkey(), new_key.pubkey(), CID, [3; 32]);
+    assert_error(send(&mut ctx, &[ix], &[&new_key]).await, HealthcareError::AlreadyPinned);
+
+    // The new key now spends the pass on the pin
+    let ix = record_access_ix(registry.pubkey(), pin_record, new_key.pubkey(), true);
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.access_count, 1);
+}
@@ -0,0 +1,133 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, HealthcareRegistry, ImmunizationRecord, LabResultRecord, PatientIndex, ProofFormat,
+    VerificationRecord, VerificationType,
+};
+
+const ELIGIBILITY_CIRCUIT: &str = "eligibility_v1";
+const LAB_CIRCUIT: &str = "hba1c_v1";
+const IMMUNIZATION_CIRCUIT: &str = "immunization_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+/// Stands in for a salted hash of "HbA1c below 6.5%"
+const RESULT_COMMITMENT: [u8; 32] = [0x3c; 32];
+/// Stands in for a salted hash of a vaccine and dose
+const STATUS_COMMITMENT: [u8; 32] = [0x5e; 32];
+
+/// A registry with a circuit approved for each of eligibility, lab results and
+/// immunizations, and one registered provider
+async fn setup(ctx: &mut ProgramTestContext, fixtures: &[Fixture]) -> (Keypair, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    let authority = ctx.payer.pubkey();
+    let circuits = [
+        (LAB_CIRCUIT, VerificationType::LabResult),
+        (IMMUNIZATION_CIRCUIT, VerificationType::Immunization),
+        (ELIGIBILITY_CIRCUIT, VerificationType::Eligibility),
+    ];
+    // Each upload takes over `Eligibility`, so the eligibility circuit goes last
+    for ((circuit_id, verification_type), fixture) in circuits.into_iter().zip(fixtures) {
+        upload_vk(ctx, registry.pubkey(), circuit_id, &fixture.vk_bytes).await;
+        let ix = set_type_circuit_ix(authority, registry.pubkey(), circuit_id, verification_type);
+        send(ctx, &[ix], &[]).await.unwrap();
+    }
+    let provider = Keypair::new();
+    let ix = register_provider_ix(authority, registry.pubkey(), provider.pubkey());
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, provider)
+}
+
+#[tokio::test]
+async fn test_lab_result_and_immunization_are_recorded() {
+    let mut ctx = start().await;
+    let fixtures = [square_fixture(1), square_fixture(2), square_fixture(3)];
+    let (registry, provider) = setup(&mut ctx, &fixtures).await;
+    let patient = ctx.payer.pubkey();
+
+    // An eligibility record written before the others
+    let eligibility = &fixtures[2];
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        ELIGIBILITY_CIRCUIT,
+        patient,
+        eligibility.proof.clone(),
+        ProofFormat::Uncompressed,
+        eligibility.public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let lab = &fixtures[0];
+    let ix = verify_lab_result_ix(registry.pubkey(), LAB_CIRCUIT, patient, lab, RESULT_COMMITMENT, provider.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let address = LabResultRecord::address(&lab.proof, ProofFormat::Uncompressed, &lab.public_inputs);
+    let lab_result: LabResultRecord = fetch(&mut ctx, address).await;
+    assert_eq!((lab_result.patient, lab_result.lab), (patient, provider.pubkey()));
+    assert_eq!(lab_result.result_commitment, RESULT_COMMITMENT);
+    assert_eq!(lab_result.circuit_id, LAB_CIRCUIT);
+
+    let immunization = &fixtures[1];
+    let ix = verify_immunization_ix(
+        registry.pubkey(),
+        IMMUNIZATION_CIRCUIT,
+        patient,
+        immunization,
+        STATUS_COMMITMENT,
+        provider.pubkey(),
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let format = ProofFormat::Uncompressed;
+    let address = ImmunizationRecord::address(&immunization.proof, format, &immunization.public_inputs);
+    let record: ImmunizationRecord = fetch(&mut ctx, address).await;
+    assert_eq!((record.patient, record.provider), (patient, provider.pubkey()));
+    assert_eq!(record.status_commitment, STATUS_COMMITMENT);
+
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.type_counts, [1, 0, 0, 0, 1, 1]);
+    assert_eq!(index.last_verification, address);
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.verifications_by_type, [1, 0, 0, 0, 1, 1]);
+
+    // The new variants sit after the old ones, which decode as before
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 0)).await;
+    assert_eq!(record.verification_type, VerificationType::Eligibility);
+    for (index, verification_type) in [
+        VerificationType::Eligibility,
+        VerificationType::Prescription,
+        VerificationType::Diagnosis,
+        VerificationType::AccessControl,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        assert_eq!(VerificationType::try_from_slice(&[index as u8]).unwrap(), verification_type);
+    }
+}
+
+#[tokio::test]
+async fn test_each_type_takes_only_its_circuit() {
+    let mut ctx = start().await;
+    let fixtures = [square_fixture(1), square_fixture(2), square_fixture(3)];
+    let (registry, provider) = setup(&mut ctx, &fixtures).await;
+    let patient = ctx.payer.pubkey();
+
+    let ix = verify_immunization_ix(
+        registry.pubkey(),
+        LAB_CIRCUIT,
+        patient,
+        &fixtures[0],
+        STATUS_COMMITMENT,
+        provider.pubkey(),
+    );
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::WrongCircuitForType);
+
+    let (lab, provider) = (&fixtures[1], provider.pubkey());
+    let ix = verify_lab_result_ix(registry.pubkey(), IMMUNIZATION_CIRCUIT, patient, lab, RESULT_COMMITMENT, provider);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::WrongCircuitForType);
+}
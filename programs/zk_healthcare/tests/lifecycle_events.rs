@@ -0,0 +1,149 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// An indexer's set of live records, kept from events alone. Captures events by
+// swapping the process-wide syscall stubs, so this binary holds a single test.
+
+mod common;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::BTreeSet;
+use zk_healthcare::{
+    DisputeResolved, ProofFormat, RecordStatus, RecordStatusChanged, VerificationClosed, VerificationDisputed,
+    VerificationExpired, VerificationRecord, VerificationRevoked, VerificationType, RECORD_GC_GRACE_SECS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const EVIDENCE: [u8; 32] = [0xe5; 32];
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+
+async fn replay(ctx: &mut ProgramTestContext, ix: Instruction, signers: &[&Keypair], logs: &mut Vec<String>) {
+    let (result, tx_logs) = send_logged(ctx, &[ix], signers).await;
+    result.unwrap();
+    logs.extend(tx_logs);
+}
+
+/// `patient`'s next record, proving `fixture` and forcing out the one before it
+async fn verify(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    patient: &Keypair,
+    fixture: &Fixture,
+    logs: &mut Vec<String>,
+) -> Pubkey {
+    let nonce = next_record_nonce(ctx, patient.pubkey()).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    replay(ctx, with_force_new(ix), &[patient], logs).await;
+    verification_address(&patient.pubkey(), nonce)
+}
+
+fn decode<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    data.strip_prefix(&T::DISCRIMINATOR[..]).map(|fields| T::try_from_slice(fields).unwrap())
+}
+
+#[tokio::test]
+async fn test_live_records_follow_from_events() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(6);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let patients = [
+        funded(&mut ctx).await,
+        funded(&mut ctx).await,
+        funded(&mut ctx).await,
+        funded(&mut ctx).await,
+        funded(&mut ctx).await,
+    ];
+    let mut logs = Vec::new();
+
+    // One record runs out its validity, is marked expired and closed
+    let ix = set_default_validity_ix(authority, registry.pubkey(), VALIDITY_SECS);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let expired = verify(&mut ctx, &registry, &patients[0], &fixtures[0], &mut logs).await;
+    let ix = set_default_validity_ix(authority, registry.pubkey(), 0);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, expired).await;
+    warp_clock_to(&mut ctx, record.expires_at + RECORD_GC_GRACE_SECS).await;
+    let ix = mark_expired_ix(registry.pubkey(), expired, patients[0].pubkey());
+    replay(&mut ctx, ix, &[], &mut logs).await;
+    let ix = close_expired_verification_ix(registry.pubkey(), expired, patients[0].pubkey(), authority, None);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+
+    // One forced out by its successor
+    let forced_out = verify(&mut ctx, &registry, &patients[1], &fixtures[1], &mut logs).await;
+    let successor = verify(&mut ctx, &registry, &patients[1], &fixtures[2], &mut logs).await;
+
+    // One revoked and closed in bulk
+    let revoked = verify(&mut ctx, &registry, &patients[2], &fixtures[3], &mut logs).await;
+    let ix = revoke_verification_ix(authority, registry.pubkey(), revoked, patients[2].pubkey(), 1);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+    let ix = close_records_bulk_ix(registry.pubkey(), &[(revoked, patients[2].pubkey(), None)]);
+    replay(&mut ctx, ix, &[], &mut logs).await;
+
+    // One disputed and restored, one disputed and revoked
+    let mut disputed = Vec::new();
+    for (patient, fixture, upheld) in [(&patients[3], &fixtures[4], false), (&patients[4], &fixtures[5], true)] {
+        let record = verify(&mut ctx, &registry, patient, fixture, &mut logs).await;
+        let ix = dispute_verification_ix(registry.pubkey(), record, patient.pubkey(), authority, false, EVIDENCE);
+        replay(&mut ctx, ix, &[], &mut logs).await;
+        let ix = resolve_dispute_ix(authority, registry.pubkey(), record, patient.pubkey(), authority, upheld);
+        replay(&mut ctx, ix, &[], &mut logs).await;
+        disputed.push(record);
+    }
+
+    let mut live = BTreeSet::new();
+    let mut closed = Vec::new();
+    for data in event_data(&logs) {
+        if let Some(event) = decode::<RecordStatusChanged>(&data) {
+            if event.new_status == RecordStatus::Verified {
+                live.insert(event.record);
+            }
+        } else if let Some(event) = decode::<VerificationRevoked>(&data) {
+            assert_eq!((event.revoked_by, event.reason_code), (authority, 1));
+            assert!(live.remove(&event.record));
+        } else if let Some(event) = decode::<VerificationExpired>(&data) {
+            assert_eq!(event.verification_type, VerificationType::Eligibility);
+            assert!(live.remove(&event.record));
+        } else if let Some(event) = decode::<VerificationDisputed>(&data) {
+            assert!(live.remove(&event.record));
+        } else if let Some(event) = decode::<DisputeResolved>(&data) {
+            if !event.upheld {
+                live.insert(event.record);
+            }
+        } else if let Some(event) = decode::<VerificationClosed>(&data) {
+            live.remove(&event.record);
+            closed.push(event);
+        }
+    }
+    assert_eq!(live, BTreeSet::from([successor, disputed[0]]));
+
+    // Each closure names where the rent went
+    assert_eq!(closed.len(), 2);
+    assert_eq!((closed[0].record, closed[0].rent_payer), (expired, patients[0].pubkey()));
+    assert_eq!((closed[0].cranker, closed[0].patient), (Some(authority), patients[0].pubkey()));
+    assert_eq!((closed[1].record, closed[1].rent_payer), (revoked, patients[2].pubkey()));
+    assert_eq!((closed[1].cranker, closed[1].bounty), (None, 0));
+    assert!(closed.iter().all(|event| event.lamports > 0));
+
+    // and agrees with the records themselves
+    for record in [expired, forced_out, successor, revoked, disputed[0], disputed[1]] {
+        let account = ctx.banks_client.get_account(record).await.unwrap();
+        let verified = account.is_some() && fetch::<VerificationRecord>(&mut ctx, record).await.is_verified();
+        assert_eq!(verified, live.contains(&record), "{record}");
+    }
+}
@@ -0,0 +1,488 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::{AnchorSerialize, Discriminator, Space};
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Signer;
+use zk_healthcare::migrations::{LegacyIpfsPinRecord, LegacyVerificationRecord};
+use zk_healthcare::{
+    DataCategory, DealInfo, HashAlgo, HealthcareError, HoldInfo, IpfsPinRecord, PatientIndex, PinStatus, RecordStatus,
+    StorageBackend, VerificationRecord, VerificationType,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A record `registry` wrote in an earlier layout
+fn legacy_record(registry: &Pubkey, revision: u16) -> LegacyVerificationRecord {
+    let patient = Pubkey::new_unique();
+    LegacyVerificationRecord {
+        patient_pubkey: patient,
+        proof_hash: [1; 32],
+        ipfs_hash: CID.to_string(),
+        timestamp: 1_700_000_000,
+        is_valid: true,
+        verification_type: VerificationType::Eligibility,
+        slot: 42,
+        circuit_id: "eligibility_v1".to_string(),
+        circuit_version: 3,
+        vk_hash: [2; 32],
+        hash_algo: HashAlgo::Keccak,
+        public_inputs_hash: [3; 32],
+        verification_id: zk_healthcare::derive_verification_id(registry, &patient, "eligibility_v1", &[1; 32]),
+        revoked_at: 0,
+        revoked_by: Pubkey::default(),
+        expires_at: 1_800_000_000,
+        status: RecordStatus::Verified,
+        rent_payer: Pubkey::new_unique(),
+        bump: 254,
+        revision,
+        previous_proof_hash: [revision as u8; 32],
+        token_mint: Pubkey::default(),
+        attesting_provider: None,
+        previous_record: None,
+        event_seq: 0,
+        hold: None,
+        metadata: Vec::new(),
+    }
+}
+
+/// Write `record` to a fresh program-owned account of `len` bytes, zero-padded
+/// the way the v1 `init` left it, or cut short when `len` is below its size
+async fn install(ctx: &mut ProgramTestContext, record: &LegacyVerificationRecord, len: usize) -> Pubkey {
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    record.serialize(&mut data).unwrap();
+    data.resize(len, 0);
+    install_data(ctx, data).await
+}
+
+async fn install_data(ctx: &mut ProgramTestContext, data: Vec<u8>) -> Pubkey {
+    let len = data.len();
+    let address = Pubkey::new_unique();
+    let account = Account {
+        lamports: Rent::default().minimum_balance(len),
+        data,
+        owner: zk_healthcare::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    ctx.set_account(&address, &account.into());
+    address
+}
+
+#[tokio::test]
+async fn test_v1_record_is_migrated() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 2);
+    let address = install(&mut ctx, &legacy, LegacyVerificationRecord::SPACE).await;
+
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE);
+    assert!(account.lamports >= Rent::default().minimum_balance(VerificationRecord::SPACE));
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.circuit_id.as_str(), record.circuit_version), ("eligibility_v1", 3));
+    assert_eq!((record.status, record.verification_id), (RecordStatus::Verified, legacy.verification_id));
+    assert_eq!((record.patient_pubkey, record.bump), (legacy.patient_pubkey, 254));
+    assert_eq!((record.revision, record.previous_proof_hash), (2, [2; 32]));
+    assert_eq!((record.ipfs_hash.as_str(), record.expires_at), (CID, 1_800_000_000));
+
+    // Migrated once; the account is now as long as a current record
+    warp_clock(&mut ctx, 0).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordNotLegacy);
+}
+
+#[tokio::test]
+async fn test_v2_record_is_migrated() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 1);
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    data.push(2);
+    legacy.serialize(&mut data).unwrap();
+    // Bytes a renewal to a shorter IPFS hash left behind the record
+    data.resize(LegacyVerificationRecord::V2_SPACE, 0xff);
+    let address = install_data(&mut ctx, data).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.patient_pubkey, record.verification_id), (legacy.patient_pubkey, legacy.verification_id));
+    assert_eq!((record.revision, record.previous_proof_hash), (1, [1; 32]));
+    assert_eq!(record.token_mint, Pubkey::default());
+}
+
+#[tokio::test]
+async fn test_v3_record_keeps_its_token_mint() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 0);
+    let token_mint = Pubkey::new_unique();
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    data.push(3);
+    legacy.serialize(&mut data).unwrap();
+    data.extend_from_slice(token_mint.as_ref());
+    data.resize(LegacyVerificationRecord::V3_SPACE, 0xff);
+    let address = install_data(&mut ctx, data).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.token_mint, record.verification_id), (token_mint, legacy.verification_id));
+    assert!(record.metadata.is_empty());
+}
+
+#[tokio::test]
+async fn test_v4_record_keeps_its_metadata() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 0);
+    let token_mint = Pubkey::new_unique();
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    data.push(4);
+    legacy.serialize(&mut data).unwrap();
+    data.extend_from_slice(token_mint.as_ref());
+    vec![9u8; 100].serialize(&mut data).unwrap();
+    data.resize(LegacyVerificationRecord::V4_SPACE + 100, 0xff);
+    let address = install_data(&mut ctx, data).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE + 100);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.token_mint, record.attesting_provider), (token_mint, None));
+    assert_eq!(record.metadata, vec![9; 100]);
+}
+
+#[tokio::test]
+async fn test_v5_record_keeps_its_provider_and_leaves_the_chain() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 0);
+    let provider = Pubkey::new_unique();
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    data.push(5);
+    legacy.serialize(&mut data).unwrap();
+    data.extend_from_slice(Pubkey::default().as_ref());
+    Some(provider).serialize(&mut data).unwrap();
+    vec![9u8; 10].serialize(&mut data).unwrap();
+    data.resize(LegacyVerificationRecord::V5_SPACE + 32 + 10, 0xff);
+    let address = install_data(&mut ctx, data).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE + 10);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.attesting_provider, record.previous_record), (Some(provider), None));
+    assert_eq!(record.metadata, vec![9; 10]);
+}
+
+#[tokio::test]
+async fn test_v6_record_keeps_its_place_in_the_chain() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 0);
+    let previous_record = Pubkey::new_unique();
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    data.push(6);
+    legacy.serialize(&mut data).unwrap();
+    data.extend_from_slice(Pubkey::default().as_ref());
+    None::<Pubkey>.serialize(&mut data).unwrap();
+    Some(previous_record).serialize(&mut data).unwrap();
+    Vec::<u8>::new().serialize(&mut data).unwrap();
+    data.resize(LegacyVerificationRecord::V6_SPACE + 32, 0xff);
+    let address = install_data(&mut ctx, data).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.previous_record, record.event_seq), (Some(previous_record), 0));
+}
+
+#[tokio::test]
+async fn test_v7_record_keeps_its_seq_and_holds_nothing() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 0);
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    data.push(7);
+    legacy.serialize(&mut data).unwrap();
+    data.extend_from_slice(Pubkey::default().as_ref());
+    None::<Pubkey>.serialize(&mut data).unwrap();
+    None::<Pubkey>.serialize(&mut data).unwrap();
+    41u64.serialize(&mut data).unwrap();
+    vec![9u8; 3].serialize(&mut data).unwrap();
+    data.resize(LegacyVerificationRecord::V7_SPACE + 3, 0xff);
+    let address = install_data(&mut ctx, data).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE + 3);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.event_seq, record.hold, record.metadata), (41, None, vec![9; 3]));
+}
+
+#[tokio::test]
+async fn test_v8_record_keeps_its_hold_and_was_migrated_from_nowhere() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 0);
+    let hold = HoldInfo { placed_at: 1_700_000_000, order_hash: [0x0d; 32] };
+    let mut data = VerificationRecord::DISCRIMINATOR.to_vec();
+    data.push(8);
+    legacy.serialize(&mut data).unwrap();
+    data.extend_from_slice(Pubkey::default().as_ref());
+    None::<Pubkey>.serialize(&mut data).unwrap();
+    None::<Pubkey>.serialize(&mut data).unwrap();
+    41u64.serialize(&mut data).unwrap();
+    Some(hold).serialize(&mut data).unwrap();
+    vec![9u8; 3].serialize(&mut data).unwrap();
+    data.resize(LegacyVerificationRecord::V8_SPACE + 40 + 3, 0xff);
+    let address = install_data(&mut ctx, data).await;
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    assert_eq!((record.event_seq, record.hold, record.metadata), (41, Some(hold), vec![9; 3]));
+    assert_eq!(record.migrated_from, Pubkey::default());
+}
+
+#[tokio::test]
+async fn test_record_ending_before_the_renewal_fields_gets_defaults() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let legacy = legacy_record(&registry, 0);
+    let mut bytes = Vec::new();
+    legacy.serialize(&mut bytes).unwrap();
+    // Account cut right after `bump`, as a long IPFS hash could leave one
+    let address = install(&mut ctx, &legacy, 8 + bytes.len() - 34).await;
+
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!((record.version, record.revision), (VerificationRecord::VERSION, 0));
+    assert_eq!(record.previous_proof_hash, [0; 32]);
+    assert_eq!(record.verification_id, legacy.verification_id);
+}
+
+#[tokio::test]
+async fn test_current_record_is_not_migrated() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), "eligibility_v1", &fixture.vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        "eligibility_v1",
+        patient,
+        fixture.proof.clone(),
+        zk_healthcare::ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let address = verification_address(&patient, 0);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!(record.version, VerificationRecord::VERSION);
+
+    let ix = migrate_verification_record_ix(registry.pubkey(), address, patient);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordNotLegacy);
+}
+
+#[tokio::test]
+async fn test_record_is_migrated_under_its_own_registry() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let other = initialize_registry(&mut ctx).await.pubkey();
+    let address = install(&mut ctx, &legacy_record(&registry, 0), LegacyVerificationRecord::SPACE).await;
+
+    let ix = migrate_verification_record_ix(other, address, ctx.payer.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordRegistryMismatch);
+    let ix = migrate_verification_record_ix(registry, address, ctx.payer.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
+
+/// A v1 pin of `cid` under `registry`, installed zero-padded to its full length
+async fn install_pin(ctx: &mut ProgramTestContext, registry: &Pubkey, cid: &str) -> (LegacyIpfsPinRecord, Pubkey) {
+    let legacy = LegacyIpfsPinRecord {
+        patient: Pubkey::new_unique(),
+        ipfs_cid: cid.to_string(),
+        data_hash: [4; 32],
+        pinned_at: 1_700_000_000,
+        access_count: 3,
+        slot: 42,
+        registry: *registry,
+        rent_payer: Pubkey::new_unique(),
+    };
+    let mut data = IpfsPinRecord::DISCRIMINATOR.to_vec();
+    legacy.serialize(&mut data).unwrap();
+    data.resize(LegacyIpfsPinRecord::SPACE, 0);
+    let address = install_data(ctx, data).await;
+    (legacy, address)
+}
+
+#[tokio::test]
+async fn test_v1_pin_is_migrated_to_its_decoded_cid() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let v1_cid = "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm";
+
+    for cid in [CID, v1_cid] {
+        let (legacy, address) = install_pin(&mut ctx, &registry, cid).await;
+        let payer = ctx.payer.pubkey();
+        send(&mut ctx, &[migrate_pin_record_ix(registry, address, legacy.patient, payer)], &[]).await.unwrap();
+
+        // Since v5 the current layout outgrew the v1 one, so the payer tops up
+        // its rent
+        let rent = Rent::default().minimum_balance(IpfsPinRecord::SPACE);
+        let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+        assert_eq!((account.data.len(), account.lamports), (IpfsPinRecord::SPACE, rent));
+        let pin: IpfsPinRecord = fetch(&mut ctx, address).await;
+        assert_eq!(pin.version, IpfsPinRecord::VERSION);
+        assert_eq!(pin.cid_string(), cid);
+        assert_eq!((pin.patient, pin.rent_payer, pin.registry), (legacy.patient, legacy.rent_payer, registry));
+        assert_eq!((pin.data_hash, pin.access_count, pin.slot), (legacy.data_hash, 3, 42));
+        // The patient's index, created for it, counts the pin as `Other`
+        let index: PatientIndex = fetch(&mut ctx, patient_index_address(&legacy.patient)).await;
+        assert_eq!(index.pin_category_counts, [0, 0, 0, 0, 0, 1]);
+
+        warp_clock(&mut ctx, 0).await;
+        let ix = migrate_pin_record_ix(registry, address, legacy.patient, payer);
+        assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinRecordNotLegacy);
+    }
+}
+
+#[tokio::test]
+async fn test_v2_to_v10_pins_are_migrated_with_new_fields_zeroed() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let (legacy, address) = install_pin(&mut ctx, &registry, CID).await;
+    let payer = ctx.payer.pubkey();
+    send(&mut ctx, &[migrate_pin_record_ix(registry, address, legacy.patient, payer)], &[]).await.unwrap();
+    let current = ctx.banks_client.get_account(address).await.unwrap().unwrap().data;
+    let patient = fetch::<IpfsPinRecord>(&mut ctx, address).await.patient;
+
+    // The same pin as each version wrote it: its version byte, and none of the
+    // fields later ones appended. A v3 pin keeps its grant slot, a v4 one its
+    // last access too, and every one comes out waiting for an oracle.
+    for (version, len, grants_expire_at_slot, last_accessed_at) in [
+        (2, LegacyIpfsPinRecord::V2_SPACE, 0, 0),
+        (3, LegacyIpfsPinRecord::V3_SPACE, 9, 0),
+        (4, LegacyIpfsPinRecord::V4_SPACE, 9, 5),
+        (5, LegacyIpfsPinRecord::V5_SPACE, 9, 5),
+        (6, LegacyIpfsPinRecord::V6_SPACE, 9, 5),
+        (7, LegacyIpfsPinRecord::V7_SPACE, 9, 5),
+        (8, LegacyIpfsPinRecord::V8_SPACE, 9, 5),
+        (9, LegacyIpfsPinRecord::V9_SPACE, 9, 5),
+        (10, LegacyIpfsPinRecord::V10_SPACE, 9, 5),
+    ] {
+        let mut data = current.clone();
+        data[IpfsPinRecord::DISCRIMINATOR.len()] = version;
+        data[LegacyIpfsPinRecord::V2_SPACE..LegacyIpfsPinRecord::V3_SPACE].copy_from_slice(&9u64.to_le_bytes());
+        data[LegacyIpfsPinRecord::V3_SPACE..LegacyIpfsPinRecord::V4_SPACE].copy_from_slice(&5i64.to_le_bytes());
+        data.truncate(len);
+        let address = install_data(&mut ctx, data).await;
+        send(&mut ctx, &[migrate_pin_record_ix(registry, address, patient, payer)], &[]).await.unwrap();
+
+        let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), IpfsPinRecord::SPACE);
+        let pin: IpfsPinRecord = fetch(&mut ctx, address).await;
+        assert_eq!(pin.version, IpfsPinRecord::VERSION);
+        assert_eq!((pin.grants_expire_at_slot, pin.last_accessed_at), (grants_expire_at_slot, last_accessed_at));
+        assert_eq!((pin.status, pin.oracle, pin.settled_at), (PinStatus::Requested, Pubkey::default(), 0));
+        assert_eq!((pin.paid_through, pin.bounty_lamports, pin.bounty_periods), (0, 0, 0));
+        assert_eq!((pin.replication_target, pin.replication_achieved()), (0, 0));
+        assert_eq!((pin.backend, pin.deals.len()), (StorageBackend::Ipfs, 0));
+        assert_eq!((pin.category, pin.tag, pin.revision, pin.history.len()), (DataCategory::Other, None, 0, 0));
+        assert_eq!((pin.cid_string(), pin.patient, pin.access_count), (CID.to_string(), patient, 3));
+    }
+}
+
+#[tokio::test]
+async fn test_v11_and_v12_pins_keep_their_deals() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let payer = ctx.payer.pubkey();
+    let tag = Some(*b"scan-001");
+    let ix = pin_medical_data_tagged_ix(registry, payer, CID, [7; 32], DataCategory::Imaging, tag);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&payer, &[7; 32]);
+    send(&mut ctx, &[add_pinning_oracle_ix(payer, registry, payer)], &[]).await.unwrap();
+    let deal_expiry = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.pinned_at + 86_400;
+    for deal_id in [1001, 1002] {
+        let ix = set_storage_deal_ix(registry, pin_record, payer, deal_id, "f01234", deal_expiry);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    let current = ctx.banks_client.get_account(pin_record).await.unwrap().unwrap().data;
+
+    // The same pin as each version wrote it: a v11 one has no category, tag,
+    // revision, revision time or history between its backend and its deals,
+    // a v12 one none of those after its tag
+    let category = LegacyIpfsPinRecord::V9_SPACE + 1;
+    let revision = category + 1 + 9;
+    for (version, removed, len, category, tag) in [
+        (11, category..revision + 16, LegacyIpfsPinRecord::V11_SPACE, DataCategory::Other, None),
+        (12, revision..revision + 16, LegacyIpfsPinRecord::V12_SPACE, DataCategory::Imaging, tag),
+    ] {
+        let mut data = current.clone();
+        data[IpfsPinRecord::DISCRIMINATOR.len()] = version;
+        data.drain(removed);
+        assert_eq!(data.len(), len + 2 * DealInfo::INIT_SPACE);
+        let address = install_data(&mut ctx, data).await;
+        send(&mut ctx, &[migrate_pin_record_ix(registry, address, payer, payer)], &[]).await.unwrap();
+
+        let account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), IpfsPinRecord::space_with(0, 2));
+        let pin: IpfsPinRecord = fetch(&mut ctx, address).await;
+        assert_eq!((pin.version, pin.category, pin.tag), (IpfsPinRecord::VERSION, category, tag));
+        assert_eq!((pin.revision, pin.history.len()), (0, 0));
+        let deals: Vec<_> =
+            pin.deals.iter().map(|deal| (deal.deal_id, deal.provider.as_str(), deal.deal_expiry)).collect();
+        assert_eq!(deals, [(1001, "f01234", deal_expiry), (1002, "f01234", deal_expiry)]);
+        assert_eq!((pin.cid_string(), pin.patient, pin.backend), (CID.to_string(), payer, StorageBackend::Ipfs));
+    }
+    // The v11 pin is counted as it is migrated, the v12 one already was
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&payer)).await;
+    assert_eq!(index.pin_category_counts, [1, 0, 0, 0, 0, 1]);
+}
+
+#[tokio::test]
+async fn test_pin_is_migrated_under_its_own_registry() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let other = initialize_registry(&mut ctx).await.pubkey();
+    let (legacy, address) = install_pin(&mut ctx, &registry, CID).await;
+
+    let ix = migrate_pin_record_ix(other, address, legacy.patient, ctx.payer.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinRecordRegistryMismatch);
+
+    // and only with a CID it can decode
+    let (legacy, address) = install_pin(&mut ctx, &registry, "not-a-cid").await;
+    let ix = migrate_pin_record_ix(registry, address, legacy.patient, ctx.payer.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidCid);
+}
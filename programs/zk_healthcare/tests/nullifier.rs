@@ -0,0 +1,115 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, ProofNullifier, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// Submit as the payer, expiring their record before this one
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    proof: &[u8],
+    format: ProofFormat,
+    public_inputs: &[u8],
+) -> (Pubkey, Result<(), solana_program_test::BanksClientError>) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        proof.to_vec(),
+        format,
+        public_inputs.to_vec(),
+        CID,
+    );
+    let result = send(ctx, &[with_force_new(ix)], &[]).await;
+    (verification, result)
+}
+
+#[tokio::test]
+async fn test_same_proof_twice_rejected() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let (first, result) = submit(
+        &mut ctx,
+        &registry,
+        &fixture.proof,
+        ProofFormat::Uncompressed,
+        &fixture.public_inputs,
+    )
+    .await;
+    result.unwrap();
+
+    let address = nullifier_address(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs);
+    let nullifier: ProofNullifier = fetch(&mut ctx, address).await;
+    let record: VerificationRecord = fetch(&mut ctx, first).await;
+    assert_eq!(nullifier.verification, first);
+    assert_eq!(nullifier.used_at, record.timestamp);
+
+    let (_, result) = submit(
+        &mut ctx,
+        &registry,
+        &fixture.proof,
+        ProofFormat::Uncompressed,
+        &fixture.public_inputs,
+    )
+    .await;
+    assert_error(result, HealthcareError::ProofAlreadyUsed);
+
+    // Re-encoding the spent proof does not produce a fresh nullifier
+    let (_, result) = submit(
+        &mut ctx,
+        &registry,
+        &fixture.compressed_proof,
+        ProofFormat::Compressed,
+        &fixture.public_inputs,
+    )
+    .await;
+    assert_error(result, HealthcareError::ProofAlreadyUsed);
+}
+
+#[tokio::test]
+async fn test_different_proof_accepted() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let (_, result) = submit(
+        &mut ctx,
+        &registry,
+        &fixture.proof,
+        ProofFormat::Uncompressed,
+        &fixture.public_inputs,
+    )
+    .await;
+    result.unwrap();
+
+    let other_proof = fixture.reprove(99);
+    assert_ne!(other_proof, fixture.proof);
+    let (verification, result) = submit(
+        &mut ctx,
+        &registry,
+        &other_proof,
+        ProofFormat::Uncompressed,
+        &fixture.public_inputs,
+    )
+    .await;
+    result.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+}
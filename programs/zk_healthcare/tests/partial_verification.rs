@@ -0,0 +1,162 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use ark_bn254::Fr;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{
+    derive_verification_pda, HealthcareError, PartialVerification, ProofFormat, VerificationRecord, VerificationType,
+    INPUTS_PER_ADVANCE, PARTIAL_VERIFICATION_TTL_SLOTS,
+};
+
+const CIRCUIT: &str = "eligibility_wide";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn funded_patient(ctx: &mut ProgramTestContext) -> Keypair {
+    let patient = Keypair::new();
+    let ix = system_instruction::transfer(&ctx.payer.pubkey(), &patient.pubkey(), 1_000_000_000);
+    send(ctx, &[ix], &[]).await.unwrap();
+    patient
+}
+
+async fn begin(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    patient: &Keypair,
+    fixture: &Fixture,
+    format: ProofFormat,
+) -> Keypair {
+    let partial = Keypair::new();
+    let proof = match format {
+        ProofFormat::Compressed => fixture.compressed_proof.clone(),
+        _ => fixture.proof.clone(),
+    };
+    let ix = begin_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        patient.pubkey(),
+        proof,
+        format,
+        fixture.public_inputs.clone(),
+    );
+    send(ctx, &[ix], &[&partial, patient]).await.unwrap();
+    partial
+}
+
+/// Complete `partial` into the patient's first record
+fn complete_ix(registry: &Keypair, partial: &Keypair, patient: &Keypair, fixture: &Fixture) -> Instruction {
+    complete_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        0,
+        nullifier_address(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs),
+        patient.pubkey(),
+        CID,
+    )
+}
+
+#[tokio::test]
+async fn test_verification_completes_across_three_transactions() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = funded_patient(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let partial = begin(&mut ctx, &registry, &patient, &fixture, ProofFormat::Uncompressed).await;
+    let ix = advance_verification_ix(partial.pubkey(), CIRCUIT, patient.pubkey());
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+    let ix = complete_ix(&registry, &partial, &patient, &fixture);
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+
+    let (verification, bump) = derive_verification_pda(&patient.pubkey(), VerificationType::Eligibility, 0);
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+    assert_eq!(record.bump, bump);
+    assert_eq!(record.patient_pubkey, patient.pubkey());
+    assert_eq!(record.version, VerificationRecord::VERSION);
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    record.assert_active(&clock).unwrap();
+    assert!(ctx.banks_client.get_account(partial.pubkey()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_wide_circuit_folds_inputs_over_several_advances() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = funded_patient(&mut ctx).await;
+    let bound = (0..INPUTS_PER_ADVANCE as u64 + 4).map(Fr::from).collect();
+    let fixture = bound_square_fixture(1, bound);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let partial = begin(&mut ctx, &registry, &patient, &fixture, ProofFormat::Compressed).await;
+    let ix = complete_ix(&registry, &partial, &patient, &fixture);
+    assert_error(
+        send(&mut ctx, &[ix], &[&patient]).await,
+        HealthcareError::PartialVerificationIncomplete,
+    );
+
+    for _ in 0..2 {
+        let ix = advance_verification_ix(partial.pubkey(), CIRCUIT, patient.pubkey());
+        send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+        // Identical advances in one slot would share a signature
+        warp_clock(&mut ctx, 0).await;
+    }
+    let state: PartialVerification = fetch(&mut ctx, partial.pubkey()).await;
+    assert_eq!(state.next_input as usize, state.public_inputs.len());
+
+    let ix = complete_ix(&registry, &partial, &patient, &fixture);
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient.pubkey(), 0)).await;
+    assert!(record.is_valid);
+}
+
+#[tokio::test]
+async fn test_steps_require_original_patient() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = funded_patient(&mut ctx).await;
+    let intruder = funded_patient(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let partial = begin(&mut ctx, &registry, &patient, &fixture, ProofFormat::Uncompressed).await;
+    let ix = advance_verification_ix(partial.pubkey(), CIRCUIT, intruder.pubkey());
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+}
+
+#[tokio::test]
+async fn test_abandoned_verification_reclaimed_after_expiry() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = funded_patient(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let balance = ctx.banks_client.get_balance(patient.pubkey()).await.unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+
+    let partial = begin(&mut ctx, &registry, &patient, &fixture, ProofFormat::Uncompressed).await;
+    let ix = reclaim_verification_ix(partial.pubkey(), patient.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PartialVerificationNotExpired);
+
+    let state: PartialVerification = fetch(&mut ctx, partial.pubkey()).await;
+    assert_eq!(state.expires_at_slot, clock.slot + PARTIAL_VERIFICATION_TTL_SLOTS);
+    ctx.warp_to_slot(state.expires_at_slot + 1).unwrap();
+    let ix = advance_verification_ix(partial.pubkey(), CIRCUIT, patient.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::PartialVerificationExpired);
+
+    // Anyone may crank the cleanup; the rent goes back to the patient
+    let ix = reclaim_verification_ix(partial.pubkey(), patient.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(partial.pubkey()).await.unwrap().is_none());
+    assert_eq!(ctx.banks_client.get_balance(patient.pubkey()).await.unwrap(), balance);
+}
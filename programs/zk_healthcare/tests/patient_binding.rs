@@ -0,0 +1,86 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{HealthcareError, ProofFormat, VerificationRecord, VkConfig};
+
+const CIRCUIT: &str = "eligibility_bound";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn funded_patient(ctx: &mut ProgramTestContext) -> Keypair {
+    let patient = Keypair::new();
+    let ix = system_instruction::transfer(&ctx.payer.pubkey(), &patient.pubkey(), 1_000_000_000);
+    send(ctx, &[ix], &[]).await.unwrap();
+    patient
+}
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    patient: &Keypair,
+    fixture: &Fixture,
+) -> (Pubkey, Result<(), BanksClientError>) {
+    let nonce = next_record_nonce(ctx, patient.pubkey()).await;
+    let verification = verification_address(&patient.pubkey(), nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let result = send(ctx, &[ix], &[patient]).await;
+    (verification, result)
+}
+
+#[tokio::test]
+async fn test_bound_proof_accepted_for_its_patient() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient = funded_patient(&mut ctx).await;
+    let fixture = bound_square_fixture(1, vec![patient_input(&patient.pubkey())]);
+    let config = VkConfig { binds_patient: true, ..VkConfig::default() };
+    upload_vk_with(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes, config).await;
+
+    let (verification, result) = submit(&mut ctx, &registry, &patient, &fixture).await;
+    result.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.patient_pubkey, patient.pubkey());
+}
+
+#[tokio::test]
+async fn test_bound_proof_rejected_for_other_signer() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient_a = funded_patient(&mut ctx).await;
+    let patient_b = funded_patient(&mut ctx).await;
+    let fixture = bound_square_fixture(1, vec![patient_input(&patient_a.pubkey())]);
+    let config = VkConfig { binds_patient: true, ..VkConfig::default() };
+    upload_vk_with(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes, config).await;
+
+    let (_, result) = submit(&mut ctx, &registry, &patient_b, &fixture).await;
+    assert_error(result, HealthcareError::PublicInputPatientMismatch);
+}
+
+#[tokio::test]
+async fn test_unbound_circuit_accepts_any_signer() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let patient_a = funded_patient(&mut ctx).await;
+    let patient_b = funded_patient(&mut ctx).await;
+    // Same circuit shape, but registered without the binding flag
+    let fixture = bound_square_fixture(1, vec![patient_input(&patient_a.pubkey())]);
+    upload_vk_with(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes, VkConfig::default()).await;
+
+    let (_, result) = submit(&mut ctx, &registry, &patient_b, &fixture).await;
+    result.unwrap();
+}
@@ -0,0 +1,92 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{PatientIndex, ProofFormat, RecordStatus, VerificationRecord, VerificationType};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+#[tokio::test]
+async fn test_index_follows_each_verification_path() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(3);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    assert!(ctx.banks_client.get_account(patient_index_address(&patient)).await.unwrap().is_none());
+
+    // Single verify, which also creates the index
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let single = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixtures[0].proof.clone(),
+        ProofFormat::Uncompressed,
+        fixtures[0].public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    // Batch of one, replacing the single verify's record
+    let ix = verify_eligibility_batch_ix(
+        registry.pubkey(),
+        nonce + 1,
+        CIRCUIT,
+        patient,
+        ProofFormat::Uncompressed,
+        vec![submission(&fixtures[1], CID)],
+    );
+    send(&mut ctx, &[with_force_new(ix)], &[]).await.unwrap();
+
+    // Two-phase begin / advance / complete
+    let partial = Keypair::new();
+    let (proof, inputs) = (&fixtures[2].proof, &fixtures[2].public_inputs);
+    let ix = begin_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        patient,
+        proof.clone(),
+        ProofFormat::Uncompressed,
+        inputs.clone(),
+    );
+    send(&mut ctx, &[ix], &[&partial]).await.unwrap();
+    let ix = advance_verification_ix(partial.pubkey(), CIRCUIT, patient);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = complete_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        nonce + 2,
+        nullifier_address(proof, ProofFormat::Uncompressed, inputs),
+        patient,
+        CID,
+    );
+    send(&mut ctx, &[with_force_new(ix)], &[]).await.unwrap();
+
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    let verification = verification_address(&patient, nonce + 2);
+    let last: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(index.verification_count, 3);
+    assert_eq!(index.type_counts[VerificationType::Eligibility as usize], 3);
+    assert_eq!(index.type_counts.iter().sum::<u32>(), 3);
+    // Each path expired the record before it, leaving only its own active
+    assert_eq!(index.active_count, 1);
+    assert_eq!(index.last_verification, verification);
+    assert_eq!(index.last_verified_at, last.timestamp);
+    // Every eligibility record draws on the record nonce
+    assert_eq!(index.next_record_nonce, 3);
+
+    let expired: VerificationRecord = fetch(&mut ctx, single).await;
+    assert_eq!(expired.status, RecordStatus::Expired);
+    let ix = revoke_verification_ix(patient, registry.pubkey(), verification, patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!((index.verification_count, index.active_count), (3, 0));
+}
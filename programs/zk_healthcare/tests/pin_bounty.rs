@@ -0,0 +1,277 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    DataUnpinned, GuardianScope, HealthcareError, IpfsPinRecord, PinBountyClaimed, PinContentClaim, SECS_PER_DAY,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const PROVIDER_ID: &str = "cluster-eu-1";
+const BOUNTY: u64 = 4_000_000;
+/// Days a quarter of the bounty is released for
+const PERIODS: u16 = 4;
+
+/// A funded oracle added to `registry` that reported `pin_record` pinned
+async fn confirming_oracle(ctx: &mut ProgramTestContext, registry: &Keypair, pin_record: Pubkey) -> Keypair {
+    let oracle = funded(ctx).await;
+    send(ctx, &[add_pinning_oracle_ix(ctx.payer.pubkey(), registry.pubkey(), oracle.pubkey())], &[]).await.unwrap();
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), true, PROVIDER_ID);
+    send(ctx, &[ix], &[&oracle]).await.unwrap();
+    oracle
+}
+
+/// A registry releasing bounties a day at a time over `PERIODS` days, a funded
+/// patient's pin under it carrying `BOUNTY`, and the funded oracle that
+/// confirmed it
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey, Keypair, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    let authority = ctx.payer.pubkey();
+    send(ctx, &[set_pin_bounty_terms_ix(authority, registry.pubkey(), SECS_PER_DAY, PERIODS)], &[]).await.unwrap();
+    let patient = funded(ctx).await;
+    let ix = pin_medical_data_with_bounty_ix(registry.pubkey(), patient.pubkey(), CID, [7; 32], BOUNTY);
+    send(ctx, &[ix], &[&patient]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient.pubkey(), &[7; 32]);
+    let oracle = confirming_oracle(ctx, &registry, pin_record).await;
+    (registry, pin_record, patient, oracle)
+}
+
+#[tokio::test]
+async fn test_single_pinner_claims_over_two_periods() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, _, oracle) = setup(&mut ctx).await;
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.bounty_lamports, pin.bounty_period_secs, pin.bounty_periods), (BOUNTY, SECS_PER_DAY, PERIODS));
+    let claim = || claim_pin_bounty_ix(registry.pubkey(), pin_record, oracle.pubkey(), PROVIDER_ID);
+
+    // A day hosted releases a quarter
+    warp_clock_to(&mut ctx, pin.settled_at + SECS_PER_DAY).await;
+    let before = balance(&mut ctx, oracle.pubkey()).await;
+    let (result, logs) = send_logged(&mut ctx, &[claim()], &[&oracle]).await;
+    result.unwrap();
+    assert_eq!(balance(&mut ctx, oracle.pubkey()).await, before + BOUNTY / 4);
+    let event = &events::<PinBountyClaimed>(&logs)[0];
+    assert_eq!((event.pin_record, event.oracle), (pin_record, oracle.pubkey()));
+    assert_eq!(event.provider_id, PROVIDER_ID);
+    assert_eq!((event.lamports, event.periods, event.remaining), (BOUNTY / 4, 1, 3 * BOUNTY / 4));
+
+    // and two more days the next two quarters, less what was claimed
+    warp_clock(&mut ctx, 2 * SECS_PER_DAY).await;
+    let (result, logs) = send_logged(&mut ctx, &[claim()], &[&oracle]).await;
+    result.unwrap();
+    let event = &events::<PinBountyClaimed>(&logs)[0];
+    assert_eq!((event.lamports, event.periods, event.remaining), (BOUNTY / 2, 3, BOUNTY / 4));
+
+    // Hosting past the last period releases no more than the bounty
+    warp_clock(&mut ctx, 10 * SECS_PER_DAY).await;
+    let (result, logs) = send_logged(&mut ctx, &[claim()], &[&oracle]).await;
+    result.unwrap();
+    let event = &events::<PinBountyClaimed>(&logs)[0];
+    assert_eq!((event.lamports, event.periods, event.remaining), (BOUNTY / 4, PERIODS, 0));
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.bounty_released, BOUNTY);
+    warp_clock(&mut ctx, SECS_PER_DAY).await;
+    assert_error(send(&mut ctx, &[claim()], &[&oracle]).await, HealthcareError::PinBountyNotDue);
+}
+
+#[tokio::test]
+async fn test_early_claim_rejected() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, _, oracle) = setup(&mut ctx).await;
+    let settled_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.settled_at;
+    let claim = || claim_pin_bounty_ix(registry.pubkey(), pin_record, oracle.pubkey(), PROVIDER_ID);
+
+    warp_clock_to(&mut ctx, settled_at + SECS_PER_DAY - 1).await;
+    assert_error(send(&mut ctx, &[claim()], &[&oracle]).await, HealthcareError::PinBountyNotDue);
+
+    // Once claimed, a period pays out once
+    warp_clock(&mut ctx, 1).await;
+    send(&mut ctx, &[claim()], &[&oracle]).await.unwrap();
+    warp_clock(&mut ctx, SECS_PER_DAY - 1).await;
+    assert_error(send(&mut ctx, &[claim()], &[&oracle]).await, HealthcareError::PinBountyNotDue);
+
+    // Only an oracle that held the pin is owed anything, listed or not
+    let other = funded(&mut ctx).await;
+    let ix = claim_pin_bounty_ix(registry.pubkey(), pin_record, other.pubkey(), PROVIDER_ID);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::NoPinBountyShare);
+    let ix = add_pinning_oracle_ix(ctx.payer.pubkey(), registry.pubkey(), other.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    let ix = claim_pin_bounty_ix(registry.pubkey(), pin_record, other.pubkey(), PROVIDER_ID);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::NoPinBountyShare);
+
+    // nor is an oracle of another pin
+    let patient = funded(&mut ctx).await;
+    let ix = pin_medical_data_with_bounty_ix(registry.pubkey(), patient.pubkey(), CID, [8; 32], BOUNTY);
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+    let waiting = IpfsPinRecord::address(&patient.pubkey(), &[8; 32]);
+    warp_clock(&mut ctx, 2 * SECS_PER_DAY).await;
+    let ix = claim_pin_bounty_ix(registry.pubkey(), waiting, oracle.pubkey(), PROVIDER_ID);
+    assert_error(send(&mut ctx, &[ix], &[&oracle]).await, HealthcareError::NoPinBountyShare);
+
+    // An oracle removed from the registry still claims what it earned
+    let ix = remove_pinning_oracle_ix(ctx.payer.pubkey(), registry.pubkey(), oracle.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    send(&mut ctx, &[claim()], &[&oracle]).await.unwrap();
+
+    // and terms need a period and a count
+    let ix = set_pin_bounty_terms_ix(ctx.payer.pubkey(), registry.pubkey(), 0, PERIODS);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidPinBountyTerms);
+    let ix = set_pin_bounty_terms_ix(ctx.payer.pubkey(), registry.pubkey(), SECS_PER_DAY, 0);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidPinBountyTerms);
+}
+
+#[tokio::test]
+async fn test_unpin_refunds_the_remainder() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, patient, oracle) = setup(&mut ctx).await;
+    let patient_key = patient.pubkey();
+    let settled_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.settled_at;
+    let rent = balance(&mut ctx, pin_record).await - BOUNTY;
+    let claim_rent = balance(&mut ctx, PinContentClaim::address(&patient_key, &[7; 32])).await;
+
+    warp_clock_to(&mut ctx, settled_at + SECS_PER_DAY).await;
+    let ix = claim_pin_bounty_ix(registry.pubkey(), pin_record, oracle.pubkey(), PROVIDER_ID);
+    send(&mut ctx, &[ix], &[&oracle]).await.unwrap();
+
+    // The second day is the oracle's, unclaimed or not, so unpinning pays it
+    warp_clock_to(&mut ctx, settled_at + 2 * SECS_PER_DAY).await;
+    let unpin = unpin_medical_data_ix(
+        registry.pubkey(),
+        pin_record,
+        [7; 32],
+        patient_key,
+        patient_key,
+        patient_key,
+        patient_key,
+    );
+    let result = send(&mut ctx, std::slice::from_ref(&unpin), &[&patient]).await;
+    assert_error(result, HealthcareError::PinBountyOwed);
+
+    // The payer covers the fee, so the patient's balance moves by the refund
+    // and the rent alone
+    let (before, oracle_before) = (balance(&mut ctx, patient_key).await, balance(&mut ctx, oracle.pubkey()).await);
+    let mut ix = unpin;
+    ix.accounts.push(AccountMeta::new(oracle.pubkey(), false));
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&patient]).await;
+    result.unwrap();
+    assert!(ctx.banks_client.get_account(pin_record).await.unwrap().is_none());
+    assert_eq!(balance(&mut ctx, oracle.pubkey()).await, oracle_before + BOUNTY / 4);
+    assert_eq!(balance(&mut ctx, patient_key).await, before + rent + claim_rent + BOUNTY / 2);
+    let event = &events::<DataUnpinned>(&logs)[0];
+    assert_eq!((event.lamports, event.bounty_refunded), (rent, BOUNTY / 2));
+}
+
+#[tokio::test]
+async fn test_unpin_refunds_a_wards_remainder_to_the_ward() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let (clinic, ward) = (funded(&mut ctx).await, Keypair::new());
+    let (payer, ward_key) = (clinic.pubkey(), ward.pubkey());
+    let (authority, scope) = (ctx.payer.pubkey(), GuardianScope::Pin.bit());
+    let ix = grant_guardian_consent_ix(registry.pubkey(), authority, payer, ward_key, scope, 0, false);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = pin_medical_data_for_ward_with_bounty_ix(registry.pubkey(), payer, ward_key, CID, [7; 32], BOUNTY);
+    send(&mut ctx, &[ix], &[&clinic]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&ward_key, &[7; 32]);
+    let rent = balance(&mut ctx, pin_record).await - BOUNTY;
+    let claim_rent = balance(&mut ctx, PinContentClaim::address(&ward_key, &[7; 32])).await;
+
+    // The clinic paid both, but only the rent goes back to it; the escrow
+    // was the ward's
+    let before = balance(&mut ctx, payer).await;
+    let ix = unpin_medical_data_ix(registry.pubkey(), pin_record, [7; 32], ward_key, ward_key, payer, payer);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&ward]).await;
+    result.unwrap();
+    assert_eq!(balance(&mut ctx, payer).await, before + rent + claim_rent);
+    assert_eq!(balance(&mut ctx, ward_key).await, BOUNTY);
+    let event = &events::<DataUnpinned>(&logs)[0];
+    assert_eq!((event.rent_payer, event.lamports, event.bounty_refunded), (payer, rent, BOUNTY));
+}
+
+#[tokio::test]
+async fn test_replicas_split_each_share() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, _, first) = setup(&mut ctx).await;
+    let second = confirming_oracle(&mut ctx, &registry, pin_record).await;
+    let settled_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.settled_at;
+    let claim = |oracle: &Keypair| claim_pin_bounty_ix(registry.pubkey(), pin_record, oracle.pubkey(), PROVIDER_ID);
+
+    // Two oracles hosting for two days take half of two quarters each
+    warp_clock_to(&mut ctx, settled_at + 2 * SECS_PER_DAY).await;
+    let (result, logs) = send_logged(&mut ctx, &[claim(&first)], &[&first]).await;
+    result.unwrap();
+    let event = &events::<PinBountyClaimed>(&logs)[0];
+    assert_eq!((event.lamports, event.periods, event.remaining), (BOUNTY / 4, 2, BOUNTY / 2));
+
+    // A third joining then shares only the periods that end after it did
+    let third = confirming_oracle(&mut ctx, &registry, pin_record).await;
+    warp_clock(&mut ctx, 2 * SECS_PER_DAY).await;
+    let part = BOUNTY / 2 / 3;
+    for (oracle, lamports) in [(&first, part), (&second, BOUNTY / 4 + part), (&third, part)] {
+        let before = balance(&mut ctx, oracle.pubkey()).await;
+        let (result, logs) = send_logged(&mut ctx, &[claim(oracle)], &[oracle]).await;
+        result.unwrap();
+        assert_eq!(balance(&mut ctx, oracle.pubkey()).await, before + lamports);
+        let event = &events::<PinBountyClaimed>(&logs)[0];
+        assert_eq!((event.oracle, event.lamports, event.periods), (oracle.pubkey(), lamports, PERIODS));
+        // The lamports the three-way split leaves over stay unreleased
+        assert_eq!(event.remaining, BOUNTY / 2 - 3 * part);
+    }
+    warp_clock(&mut ctx, 0).await;
+    assert_error(send(&mut ctx, &[claim(&third)], &[&third]).await, HealthcareError::PinBountyNotDue);
+}
+
+#[tokio::test]
+async fn test_update_resumes_the_bounty_on_reconfirmation() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, patient, oracle) = setup(&mut ctx).await;
+    let settled_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.settled_at;
+
+    // A day hosted before the update is settled to the oracle holding the pin
+    warp_clock_to(&mut ctx, settled_at + SECS_PER_DAY).await;
+    let ix = update_pin_ix(registry.pubkey(), pin_record, [7; 32], patient.pubkey(), CID, [8; 32]);
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.bounty_periods_settled, pin.bounty_released), (1, BOUNTY / 4));
+
+    // Time spent waiting for the update to be confirmed counts for nothing,
+    // and the periods left resume from the confirmation
+    warp_clock(&mut ctx, SECS_PER_DAY / 2).await;
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), true, PROVIDER_ID);
+    send(&mut ctx, &[ix], &[&oracle]).await.unwrap();
+    let confirmed_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.settled_at;
+    warp_clock_to(&mut ctx, confirmed_at + SECS_PER_DAY).await;
+    let ix = claim_pin_bounty_ix(registry.pubkey(), pin_record, oracle.pubkey(), PROVIDER_ID);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&oracle]).await;
+    result.unwrap();
+    let event = &events::<PinBountyClaimed>(&logs)[0];
+    assert_eq!((event.lamports, event.periods, event.remaining), (BOUNTY / 2, 2, BOUNTY / 2));
+}
+
+#[tokio::test]
+async fn test_periods_no_replica_held_stay_in_escrow() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, _, first) = setup(&mut ctx).await;
+    let settled_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.settled_at;
+    send(&mut ctx, &[report_unpin_ix(registry.pubkey(), pin_record, first.pubkey())], &[&first]).await.unwrap();
+
+    // Two days held by no one release nothing to the oracle joining after
+    warp_clock_to(&mut ctx, settled_at + 2 * SECS_PER_DAY).await;
+    let second = confirming_oracle(&mut ctx, &registry, pin_record).await;
+    warp_clock_to(&mut ctx, settled_at + 3 * SECS_PER_DAY).await;
+    let ix = claim_pin_bounty_ix(registry.pubkey(), pin_record, second.pubkey(), PROVIDER_ID);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&second]).await;
+    result.unwrap();
+    let event = &events::<PinBountyClaimed>(&logs)[0];
+    assert_eq!((event.lamports, event.periods, event.remaining), (BOUNTY / 4, 3, 3 * BOUNTY / 4));
+    let ix = claim_pin_bounty_ix(registry.pubkey(), pin_record, first.pubkey(), PROVIDER_ID);
+    assert_error(send(&mut ctx, &[ix], &[&first]).await, HealthcareError::NoPinBountyShare);
+}
@@ -0,0 +1,108 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use zk_healthcare::{
+    DataCategory, DataPinned, HealthcareError, IpfsPinRecord, PatientIndex, PinRetagged, DATA_CATEGORY_COUNT,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn counts(ctx: &mut ProgramTestContext, patient: &Pubkey) -> [u32; DATA_CATEGORY_COUNT] {
+    fetch::<PatientIndex>(ctx, patient_index_address(patient)).await.pin_category_counts
+}
+
+#[tokio::test]
+async fn test_pins_are_counted_per_category() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+
+    for (byte, category, tag) in [
+        (1, DataCategory::Imaging, Some(*b"mri-2025")),
+        (2, DataCategory::Imaging, None),
+        (3, DataCategory::LabReport, Some(*b"cbc-0001")),
+        (4, DataCategory::Consent, None),
+    ] {
+        let ix = pin_medical_data_tagged_ix(registry, patient, CID, [byte; 32], category, tag);
+        let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+        result.unwrap();
+        assert_eq!(events::<DataPinned>(&logs)[0].category, category);
+        let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &[byte; 32])).await;
+        assert_eq!((pin.category, pin.tag), (category, tag));
+    }
+    assert_eq!(counts(&mut ctx, &patient).await, [2, 1, 0, 0, 1, 0]);
+
+    // An untagged pin is filed under Other
+    send(&mut ctx, &[pin_medical_data_ix(registry, patient, CID, [5; 32])], &[]).await.unwrap();
+    assert_eq!(counts(&mut ctx, &patient).await, [2, 1, 0, 0, 1, 1]);
+}
+
+#[tokio::test]
+async fn test_patient_retags_a_pin() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    let ix = pin_medical_data_tagged_ix(registry, patient, CID, [1; 32], DataCategory::ClinicalNote, None);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient, &[1; 32]);
+
+    // Retagging moves the pin's count to its new category
+    let tag = Some(*b"rx-00042");
+    let ix = retag_pin_ix(registry, pin_record, patient, patient, DataCategory::Prescription, tag);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    let event = &events::<PinRetagged>(&logs)[0];
+    assert_eq!((event.registry, event.pin_record, event.patient), (registry, pin_record, patient));
+    assert_eq!((event.old_category, event.category), (DataCategory::ClinicalNote, DataCategory::Prescription));
+    assert_eq!(event.tag, tag);
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.category, pin.tag), (DataCategory::Prescription, tag));
+    assert_eq!(counts(&mut ctx, &patient).await, [0, 0, 0, 1, 0, 0]);
+
+    // Only the patient retags their pin
+    let other = funded(&mut ctx).await;
+    let ix = retag_pin_ix(registry, pin_record, patient, other.pubkey(), DataCategory::Other, None);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::PinRecordPatientMismatch);
+}
+
+#[tokio::test]
+async fn test_unpinned_and_claimed_pins_leave_their_counts() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let old_key = ctx.payer.pubkey();
+    for (byte, category) in [(1, DataCategory::Imaging), (2, DataCategory::LabReport)] {
+        let ix = pin_medical_data_tagged_ix(registry, old_key, CID, [byte; 32], category, None);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+
+    // Unpinning stops counting the pin
+    let pin_record = IpfsPinRecord::address(&old_key, &[2; 32]);
+    let ix = unpin_medical_data_ix(registry, pin_record, [2; 32], old_key, old_key, old_key, old_key);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert_eq!(counts(&mut ctx, &old_key).await, [1, 0, 0, 0, 0, 0]);
+
+    // A claimed pin moves to the new key's index, which then retags it
+    let new_key = funded(&mut ctx).await;
+    send(&mut ctx, &[rotate_patient_key_ix(registry, old_key, new_key.pubkey())], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&old_key, &[1; 32]);
+    let ix = claim_pin_record_ix(old_key, pin_record, [1; 32], new_key.pubkey());
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+    assert_eq!(counts(&mut ctx, &old_key).await, [0; DATA_CATEGORY_COUNT]);
+    assert_eq!(counts(&mut ctx, &new_key.pubkey()).await, [1, 0, 0, 0, 0, 0]);
+    let ix = retag_pin_ix(registry, pin_record, new_key.pubkey(), new_key.pubkey(), DataCategory::Consent, None);
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+    assert_eq!(counts(&mut ctx, &new_key.pubkey()).await, [0, 0, 0, 0, 1, 0]);
+
+    // and the new key unpins it from there
+    let new = new_key.pubkey();
+    let ix = unpin_medical_data_ix(registry, pin_record, [1; 32], new, new, old_key, new);
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+    assert_eq!(counts(&mut ctx, &new).await, [0; DATA_CATEGORY_COUNT]);
+}
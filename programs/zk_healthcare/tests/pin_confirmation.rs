@@ -0,0 +1,152 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, HealthcareRegistry, IpfsPinRecord, PinConfirmed, PinFailed, PinStatus, VerificationType,
+    MAX_PINNING_ORACLES, MAX_PIN_PROVIDER_ID_LEN,
+};
+
+const CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const PROVIDER_ID: &str = "cluster-eu-1";
+
+/// A registry with `CIRCUIT` approved for access control, a pin of the payer's
+/// waiting under it, and a funded oracle the registry lists
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> (Keypair, Pubkey, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let ix = set_type_circuit_ix(patient, registry.pubkey(), CIRCUIT, VerificationType::AccessControl);
+    send(ctx, &[ix], &[]).await.unwrap();
+    send(ctx, &[pin_medical_data_ix(registry.pubkey(), patient, CID, [7; 32])], &[]).await.unwrap();
+
+    let oracle = funded(ctx).await;
+    send(ctx, &[add_pinning_oracle_ix(patient, registry.pubkey(), oracle.pubkey())], &[]).await.unwrap();
+    (registry, IpfsPinRecord::address(&patient, &[7; 32]), oracle)
+}
+
+#[tokio::test]
+async fn test_oracle_confirms_the_pin() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, oracle) = setup(&mut ctx, &square_fixture(1)).await;
+    let patient = ctx.payer.pubkey();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle), (PinStatus::Requested, Pubkey::default()));
+
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), true, PROVIDER_ID);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&oracle]).await;
+    result.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle), (PinStatus::Confirmed, oracle.pubkey()));
+    let event = &events::<PinConfirmed>(&logs)[0];
+    assert_eq!((event.pin_record, event.patient, event.oracle), (pin_record, patient, oracle.pubkey()));
+    assert_eq!((event.registry, event.provider_id.as_str()), (registry.pubkey(), PROVIDER_ID));
+    assert!(events::<PinFailed>(&logs).is_empty());
+
+    // The confirmed pin can now be read, and is settled for good
+    send(&mut ctx, &[record_access_ix(registry.pubkey(), pin_record, patient, false)], &[]).await.unwrap();
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), false, PROVIDER_ID);
+    assert_error(send(&mut ctx, &[ix], &[&oracle]).await, HealthcareError::PinAlreadySettled);
+}
+
+#[tokio::test]
+async fn test_non_oracle_is_rejected() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, oracle) = setup(&mut ctx, &square_fixture(1)).await;
+    let authority = ctx.payer.pubkey();
+
+    let stranger = funded(&mut ctx).await;
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, stranger.pubkey(), true, PROVIDER_ID);
+    assert_error(send(&mut ctx, &[ix], &[&stranger]).await, HealthcareError::NotPinningOracle);
+
+    // Only the authority lists oracles
+    let ix = add_pinning_oracle_ix(stranger.pubkey(), registry.pubkey(), stranger.pubkey());
+    let err = send(&mut ctx, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+
+    // and an oracle it removed reports nothing more
+    send(&mut ctx, &[remove_pinning_oracle_ix(authority, registry.pubkey(), oracle.pubkey())], &[]).await.unwrap();
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), true, PROVIDER_ID);
+    assert_error(send(&mut ctx, &[ix], &[&oracle]).await, HealthcareError::NotPinningOracle);
+    // Past the slot, so the repeated removal isn't taken for the first
+    warp_clock(&mut ctx, 0).await;
+    let ix = remove_pinning_oracle_ix(authority, registry.pubkey(), oracle.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::NotPinningOracle);
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.status, PinStatus::Requested);
+}
+
+#[tokio::test]
+async fn test_failure_path_sets_failed() {
+    let mut ctx = start_with_event_logs().await;
+    let fixture = square_fixture(1);
+    let (registry, pin_record, oracle) = setup(&mut ctx, &fixture).await;
+    let patient = ctx.payer.pubkey();
+
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), false, PROVIDER_ID);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&oracle]).await;
+    result.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle), (PinStatus::Failed, oracle.pubkey()));
+    let event = &events::<PinFailed>(&logs)[0];
+    assert_eq!((event.pin_record, event.oracle), (pin_record, oracle.pubkey()));
+    assert_eq!(event.provider_id, PROVIDER_ID);
+    assert!(events::<PinConfirmed>(&logs).is_empty());
+
+    // A failed pin is neither read nor granted
+    let ix = record_access_ix(registry.pubkey(), pin_record, patient, false);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::IpfsPinningFailed);
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, patient, &fixture, pin_record, 100, 1);
+    let result = send(&mut ctx, &[with_pin_record(ix, pin_record)], &[]).await;
+    assert_error(result, HealthcareError::IpfsPinningFailed);
+}
+
+#[tokio::test]
+async fn test_unconfirmed_pin_is_refused_downstream() {
+    let mut ctx = start_with_event_logs().await;
+    let fixture = square_fixture(1);
+    let (registry, pin_record, oracle) = setup(&mut ctx, &fixture).await;
+    let patient = ctx.payer.pubkey();
+
+    let ix = record_access_ix(registry.pubkey(), pin_record, patient, false);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinNotConfirmed);
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, patient, &fixture, pin_record, 100, 1);
+    let result = send(&mut ctx, &[with_pin_record(ix, pin_record)], &[]).await;
+    assert_error(result, HealthcareError::PinNotConfirmed);
+
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), true, PROVIDER_ID);
+    send(&mut ctx, &[ix], &[&oracle]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[record_access_ix(registry.pubkey(), pin_record, patient, false)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_oracle_list_and_provider_id_are_bounded() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, oracle) = setup(&mut ctx, &square_fixture(1)).await;
+    let authority = ctx.payer.pubkey();
+
+    warp_clock(&mut ctx, 0).await;
+    let ix = add_pinning_oracle_ix(authority, registry.pubkey(), oracle.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinningOracleAlreadyAdded);
+    for _ in 1..MAX_PINNING_ORACLES {
+        let ix = add_pinning_oracle_ix(authority, registry.pubkey(), Pubkey::new_unique());
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.pinning_oracles.len(), MAX_PINNING_ORACLES);
+    let ix = add_pinning_oracle_ix(authority, registry.pubkey(), Pubkey::new_unique());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::TooManyPinningOracles);
+
+    let provider_id = "p".repeat(MAX_PIN_PROVIDER_ID_LEN + 1);
+    let ix = confirm_pin_ix(registry.pubkey(), pin_record, oracle.pubkey(), true, &provider_id);
+    assert_error(send(&mut ctx, &[ix], &[&oracle]).await, HealthcareError::PinProviderIdTooLong);
+}
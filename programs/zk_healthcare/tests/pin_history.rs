@@ -0,0 +1,221 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Signer;
+use zk_healthcare::{
+    AccessPass, DataUnpinned, HealthcareError, IpfsPinRecord, PinContentClaim, PinStatus, PinUpdated,
+    VerificationType, MAX_PIN_REPLICAS, MAX_PIN_VERSIONS,
+};
+
+const CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const V1_CID: &str = "bafkreic5e6aqntcokfdxmhmhdkdvr2fszjzxtjqgzj5mebygwnlpwsrpcm";
+
+/// A registry and a confirmed pin of the payer's under it, of data hash
+/// `[1; 32]`
+async fn setup(ctx: &mut ProgramTestContext) -> (Pubkey, Pubkey) {
+    let registry = initialize_registry(ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    send(ctx, &[pin_medical_data_ix(registry, patient, CID, [1; 32])], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient, &[1; 32]);
+    confirm_pin(ctx, registry, pin_record).await;
+    (registry, pin_record)
+}
+
+#[tokio::test]
+async fn test_three_updates_keep_their_history_in_order() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let first = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await;
+
+    let mut pinned_at = vec![first.pinned_at];
+    for (revision, (cid, byte)) in [(V1_CID, 2), (CID, 3), (V1_CID, 4)].into_iter().enumerate() {
+        warp_clock(&mut ctx, 60).await;
+        let rent = balance(&mut ctx, pin_record).await;
+        let ix = update_pin_ix(registry, pin_record, [byte - 1; 32], patient, cid, [byte; 32]);
+        let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+        result.unwrap();
+        let event = &events::<PinUpdated>(&logs)[0];
+        assert_eq!((event.registry, event.pin_record, event.patient), (registry, pin_record, patient));
+        assert_eq!((event.old_hash, event.new_hash), ([byte - 1; 32], [byte; 32]));
+        assert_eq!((event.ipfs_cid.as_str(), event.version), (cid, revision as u32 + 1));
+
+        // The pin grows by a version, at the patient's expense
+        let account = ctx.banks_client.get_account(pin_record).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), IpfsPinRecord::space_with(revision + 1, 0));
+        assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+        assert!(account.lamports > rent);
+        let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+        assert_eq!((pin.data_hash, pin.cid_string(), pin.revised_at), ([byte; 32], cid.to_string(), event.timestamp));
+        assert_eq!((pin.pinned_at, pin.slot), (first.pinned_at, first.slot));
+        pinned_at.push(pin.revised_at);
+    }
+
+    // Oldest first, each version as the pin held it
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.revision, 3);
+    let hashes: Vec<_> = pin.history.iter().map(|version| version.data_hash).collect();
+    assert_eq!(hashes, [[1; 32], [2; 32], [3; 32]]);
+    let times: Vec<_> = pin.history.iter().map(|version| version.pinned_at).collect();
+    assert_eq!(times, pinned_at[..3]);
+    assert_eq!((pin.history[0].cid_version, pin.history[0].multihash), (first.cid_version, first.multihash));
+    assert_eq!(pin.history[1].cid_version, 1);
+
+    // Only the patient updates the pin, to content it doesn't already hold
+    let ix = update_pin_ix(registry, pin_record, [4; 32], patient, CID, [4; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinContentUnchanged);
+    let other = funded(&mut ctx).await;
+    let ix = update_pin_ix(registry, pin_record, [4; 32], other.pubkey(), CID, [5; 32]);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::PinRecordPatientMismatch);
+}
+
+#[tokio::test]
+async fn test_oldest_version_evicted_past_the_cap() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let updates = MAX_PIN_VERSIONS as u8 + 2;
+
+    for byte in 2..=updates + 1 {
+        let ix = update_pin_ix(registry, pin_record, [byte - 1; 32], patient, CID, [byte; 32]);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.revision, pin.data_hash), (u32::from(updates), [updates + 1; 32]));
+    let hashes: Vec<_> = pin.history.iter().map(|version| version.data_hash[0]).collect();
+    assert_eq!(hashes, (3..=updates).collect::<Vec<_>>());
+
+    // A full history no longer grows the account
+    let len = ctx.banks_client.get_account(pin_record).await.unwrap().unwrap().data.len();
+    assert_eq!(len, IpfsPinRecord::space_with(MAX_PIN_VERSIONS, 0));
+}
+
+#[tokio::test]
+async fn test_update_is_confirmed_again_and_keeps_its_grants() {
+    let mut ctx = start_with_event_logs().await;
+    let fixture = square_fixture(1);
+    let (registry, pin_record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    upload_vk(&mut ctx, registry, CIRCUIT, &fixture.vk_bytes).await;
+    let ix = set_type_circuit_ix(patient, registry, CIRCUIT, VerificationType::AccessControl);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let provider = funded(&mut ctx).await;
+    let ix = verify_access_control_ix(registry, CIRCUIT, provider.pubkey(), &fixture, pin_record, 100, 2);
+    send(&mut ctx, &[ix], &[&provider]).await.unwrap();
+    let read = || record_access_ix(registry, pin_record, provider.pubkey(), true);
+    send(&mut ctx, &[read()], &[&provider]).await.unwrap();
+
+    // The update sends the pin back to the oracles
+    let ix = update_pin_ix(registry, pin_record, [1; 32], patient, V1_CID, [2; 32]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle, pin.settled_at), (PinStatus::Requested, Pubkey::default(), 0));
+    assert_eq!((pin.replicas, pin.replication_achieved()), ([Pubkey::default(); MAX_PIN_REPLICAS], 0));
+    warp_clock(&mut ctx, 0).await;
+    assert_error(send(&mut ctx, &[read()], &[&provider]).await, HealthcareError::PinNotConfirmed);
+
+    // and once they report it pinned, the provider's pass opens it again
+    confirm_pin(&mut ctx, registry, pin_record).await;
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle), (PinStatus::Confirmed, patient));
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[read()], &[&provider]).await.unwrap();
+    let pass: AccessPass = fetch(&mut ctx, AccessPass::address(&provider.pubkey(), &pin_record)).await;
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pass.uses_remaining, pin.access_count), (0, 2));
+}
+
+#[tokio::test]
+async fn test_update_to_content_pinned_elsewhere_rejected() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[pin_medical_data_ix(registry, patient, CID, [9; 32])], &[]).await.unwrap();
+    let other_pin = IpfsPinRecord::address(&patient, &[9; 32]);
+
+    // Content another pin of the patient's holds isn't pinned a second time
+    let ix = update_pin_ix(registry, pin_record, [1; 32], patient, CID, [9; 32]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::AlreadyPinned);
+    assert!(logs.iter().any(|log| log.ends_with(&format!("Content already pinned at {other_pin}"))), "{logs:?}");
+
+    // The pin holds its address once it grows a history, so the content it
+    // was first pinned with isn't pinned beside it
+    let ix = update_pin_ix(registry, pin_record, [1; 32], patient, V1_CID, [2; 32]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = pin_medical_data_ix(registry, patient, V1_CID, [1; 32]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::AlreadyPinned);
+    assert!(logs.iter().any(|log| log.ends_with(&format!("Content already pinned at {pin_record}"))), "{logs:?}");
+
+    // but the pin itself may go back to it
+    send(&mut ctx, &[update_pin_ix(registry, pin_record, [2; 32], patient, CID, [1; 32])], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.data_hash, pin.revision), ([1; 32], 2));
+
+    // Content the pin no longer holds is free to pin at its own address
+    send(&mut ctx, &[pin_medical_data_ix(registry, patient, CID, [2; 32])], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &[2; 32])).await;
+    assert_eq!((pin.data_hash, pin.revision), ([2; 32], 0));
+}
+
+#[tokio::test]
+async fn test_updated_content_is_held_by_the_pin() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    send(&mut ctx, &[pin_medical_data_ix(registry, patient, CID, [3; 32])], &[]).await.unwrap();
+    let other_pin = IpfsPinRecord::address(&patient, &[3; 32]);
+
+    // The update moves the pin's content claim to what it now holds
+    send(&mut ctx, &[update_pin_ix(registry, pin_record, [1; 32], patient, V1_CID, [2; 32])], &[]).await.unwrap();
+    let claim: PinContentClaim = fetch(&mut ctx, PinContentClaim::address(&patient, &[2; 32])).await;
+    assert_eq!(claim.pin_record, pin_record);
+    let old_claim = PinContentClaim::address(&patient, &[1; 32]);
+    assert!(ctx.banks_client.get_account(old_claim).await.unwrap().is_none());
+
+    // so the content isn't pinned again at its own address
+    let ix = pin_medical_data_ix(registry, patient, V1_CID, [2; 32]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::AlreadyPinned);
+    assert!(logs.iter().any(|log| log.ends_with(&format!("Content already pinned at {pin_record}"))), "{logs:?}");
+
+    // nor does another pin update to it
+    let ix = update_pin_ix(registry, other_pin, [3; 32], patient, V1_CID, [2; 32]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    assert_error(result, HealthcareError::AlreadyPinned);
+    assert!(logs.iter().any(|log| log.ends_with(&format!("Content already pinned at {pin_record}"))), "{logs:?}");
+    let pin: IpfsPinRecord = fetch(&mut ctx, other_pin).await;
+    assert_eq!((pin.data_hash, pin.revision), ([3; 32], 0));
+}
+
+#[tokio::test]
+async fn test_unpin_after_update_reports_the_whole_lifetime() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let pinned_at = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.pinned_at;
+
+    warp_clock_to(&mut ctx, pinned_at + 600).await;
+    send(&mut ctx, &[update_pin_ix(registry, pin_record, [1; 32], patient, V1_CID, [2; 32])], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.pinned_at, pin.revised_at), (pinned_at, pinned_at + 600));
+
+    // The duration runs from the pin, not the update, and the unpin releases
+    // the content for pinning again
+    warp_clock_to(&mut ctx, pinned_at + 900).await;
+    let ix = unpin_medical_data_ix(registry, pin_record, [2; 32], patient, patient, patient, patient);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    assert_eq!(events::<DataUnpinned>(&logs)[0].pinned_duration_secs, 900);
+    assert!(ctx.banks_client.get_account(PinContentClaim::address(&patient, &[2; 32])).await.unwrap().is_none());
+    send(&mut ctx, &[pin_medical_data_ix(registry, patient, V1_CID, [2; 32])], &[]).await.unwrap();
+}
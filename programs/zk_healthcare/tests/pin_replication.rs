@@ -0,0 +1,129 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, IpfsPinRecord, PinConfirmed, PinReplicaConfirmed, PinStatus, ReplicationDegraded,
+    MAX_PIN_REPLICAS,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const PROVIDER_ID: &str = "cluster-eu-1";
+
+/// A registry asking `target` replicas of its pins, a pin of the payer's under
+/// it, and `oracles` funded oracles the registry lists
+async fn setup(ctx: &mut ProgramTestContext, target: u8, oracles: usize) -> (Keypair, Pubkey, Vec<Keypair>) {
+    let registry = initialize_registry(ctx).await;
+    let authority = ctx.payer.pubkey();
+    send(ctx, &[set_pin_replication_target_ix(authority, registry.pubkey(), target)], &[]).await.unwrap();
+    send(ctx, &[pin_medical_data_ix(registry.pubkey(), authority, CID, [7; 32])], &[]).await.unwrap();
+
+    let mut keypairs = Vec::new();
+    for _ in 0..oracles {
+        let oracle = funded(ctx).await;
+        send(ctx, &[add_pinning_oracle_ix(authority, registry.pubkey(), oracle.pubkey())], &[]).await.unwrap();
+        keypairs.push(oracle);
+    }
+    (registry, IpfsPinRecord::address(&authority, &[7; 32]), keypairs)
+}
+
+async fn confirm(ctx: &mut ProgramTestContext, registry: Pubkey, pin_record: Pubkey, oracle: &Keypair) -> Vec<String> {
+    let ix = confirm_pin_ix(registry, pin_record, oracle.pubkey(), true, PROVIDER_ID);
+    let (result, logs) = send_logged(ctx, &[ix], &[oracle]).await;
+    result.unwrap();
+    logs
+}
+
+#[tokio::test]
+async fn test_target_reached_with_three_oracles() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, oracles) = setup(&mut ctx, 3, 3).await;
+    let patient = ctx.payer.pubkey();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.replication_target, pin.replication_achieved()), (3, 0));
+
+    // Short of the target the pin waits, and can't be read
+    for (count, oracle) in oracles[..2].iter().enumerate() {
+        let logs = confirm(&mut ctx, registry.pubkey(), pin_record, oracle).await;
+        let event = &events::<PinReplicaConfirmed>(&logs)[0];
+        assert_eq!((event.pin_record, event.oracle), (pin_record, oracle.pubkey()));
+        assert_eq!((event.replicas, event.replication_target), (count as u8 + 1, 3));
+        assert!(events::<PinConfirmed>(&logs).is_empty());
+    }
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.replication_achieved()), (PinStatus::Requested, 2));
+    let ix = record_access_ix(registry.pubkey(), pin_record, patient, false);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinNotConfirmed);
+
+    // The third confirmation meets it
+    let logs = confirm(&mut ctx, registry.pubkey(), pin_record, &oracles[2]).await;
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.oracle, pin.replication_achieved()), (PinStatus::Confirmed, oracles[2].pubkey(), 3));
+    assert_eq!(&pin.replicas[..3], &oracles.iter().map(|oracle| oracle.pubkey()).collect::<Vec<_>>()[..]);
+    assert!(pin.replica_confirmed_at[..3].iter().all(|at| *at > 0 && *at <= pin.settled_at));
+    let event = &events::<PinConfirmed>(&logs)[0];
+    assert_eq!((event.pin_record, event.oracle), (pin_record, oracles[2].pubkey()));
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[record_access_ix(registry.pubkey(), pin_record, patient, false)], &[]).await.unwrap();
+
+    // and the target is bounded by the replicas a pin counts
+    for target in [0, MAX_PIN_REPLICAS as u8 + 1] {
+        let ix = set_pin_replication_target_ix(patient, registry.pubkey(), target);
+        assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidReplicationTarget);
+    }
+}
+
+#[tokio::test]
+async fn test_duplicate_confirmation_ignored() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, oracles) = setup(&mut ctx, 2, 2).await;
+
+    confirm(&mut ctx, registry.pubkey(), pin_record, &oracles[0]).await;
+    // Past the slot, so the repeated report isn't taken for the first
+    warp_clock(&mut ctx, 0).await;
+    let logs = confirm(&mut ctx, registry.pubkey(), pin_record, &oracles[0]).await;
+    assert!(events::<PinReplicaConfirmed>(&logs).is_empty());
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.replication_achieved()), (PinStatus::Requested, 1));
+
+    confirm(&mut ctx, registry.pubkey(), pin_record, &oracles[1]).await;
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.status, pin.replication_achieved()), (PinStatus::Confirmed, 2));
+}
+
+#[tokio::test]
+async fn test_degradation_drops_below_target() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, oracles) = setup(&mut ctx, 2, 3).await;
+    confirm(&mut ctx, registry.pubkey(), pin_record, &oracles[0]).await;
+    confirm(&mut ctx, registry.pubkey(), pin_record, &oracles[1]).await;
+    let report = |oracle: &Keypair| report_unpin_ix(registry.pubkey(), pin_record, oracle.pubkey());
+
+    let (result, logs) = send_logged(&mut ctx, &[report(&oracles[1])], &[&oracles[1]]).await;
+    result.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    // Still readable from the replica left
+    assert_eq!((pin.status, pin.replication_achieved()), (PinStatus::Confirmed, 1));
+    assert!(!pin.replicas.contains(&oracles[1].pubkey()));
+    let event = &events::<ReplicationDegraded>(&logs)[0];
+    assert_eq!((event.pin_record, event.patient, event.oracle), (pin_record, ctx.payer.pubkey(), oracles[1].pubkey()));
+    assert_eq!((event.replicas, event.replication_target), (1, 2));
+
+    // Only an oracle counted reports dropping it
+    warp_clock(&mut ctx, 0).await;
+    assert_error(send(&mut ctx, &[report(&oracles[1])], &[&oracles[1]]).await, HealthcareError::NotPinReplica);
+    assert_error(send(&mut ctx, &[report(&oracles[2])], &[&oracles[2]]).await, HealthcareError::NotPinReplica);
+
+    // Another oracle restores the replica without settling the pin again
+    let logs = confirm(&mut ctx, registry.pubkey(), pin_record, &oracles[2]).await;
+    assert_eq!(events::<PinReplicaConfirmed>(&logs)[0].replicas, 2);
+    assert!(events::<PinConfirmed>(&logs).is_empty());
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.replication_achieved(), pin.oracle), (2, oracles[1].pubkey()));
+}
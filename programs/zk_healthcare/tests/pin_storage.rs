@@ -0,0 +1,163 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{
+    DataPinned, HealthcareError, IpfsPinRecord, PinExpired, PinRenewed, PinStatus, PIN_EXPIRY_GRACE_SECS,
+    SECS_PER_DAY,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+/// Enough that a day's payment leaves a fresh treasury rent exempt
+const PRICE: u64 = 1_000_000;
+
+/// A registry charging `PRICE` a day into a fresh treasury, and a funded patient
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    let treasury = Pubkey::new_unique();
+    let ix = set_pin_storage_price_ix(ctx.payer.pubkey(), registry.pubkey(), PRICE, treasury);
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, treasury, funded(ctx).await)
+}
+
+/// Pin `CID` for `patient`, paying `storage_lamports`; the pin's address
+async fn pin_paid(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    treasury: Pubkey,
+    patient: &Keypair,
+    storage_lamports: u64,
+) -> Pubkey {
+    let (registry, patient_key) = (registry.pubkey(), patient.pubkey());
+    let ix = pin_medical_data_paid_ix(registry, patient_key, CID, [7; 32], storage_lamports, Some(treasury));
+    send(ctx, &[ix], &[patient]).await.unwrap();
+    IpfsPinRecord::address(&patient_key, &[7; 32])
+}
+
+#[tokio::test]
+async fn test_initial_period_computed_from_lamports() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, treasury, patient) = setup(&mut ctx).await;
+    let before = balance(&mut ctx, patient.pubkey()).await;
+
+    // Three and a half days' worth buys three whole days
+    let (registry_key, patient_key) = (registry.pubkey(), patient.pubkey());
+    let ix = pin_medical_data_paid_ix(registry_key, patient_key, CID, [7; 32], 3 * PRICE + PRICE / 2, Some(treasury));
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&patient]).await;
+    result.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient_key, &[7; 32]);
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.paid_through, pin.pinned_at + 3 * SECS_PER_DAY);
+    assert_eq!(events::<DataPinned>(&logs)[0].paid_through, pin.paid_through);
+    assert_eq!(balance(&mut ctx, treasury).await, 3 * PRICE);
+    let rent = balance(&mut ctx, pin_record).await + balance(&mut ctx, patient_index_address(&patient_key)).await;
+    assert_eq!(balance(&mut ctx, patient_key).await, before - rent - 3 * PRICE);
+
+    // While storage is free pins carry no paid-through date
+    let ix = set_pin_storage_price_ix(ctx.payer.pubkey(), registry_key, 0, treasury);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    send(&mut ctx, &[pin_medical_data_ix(registry_key, patient_key, CID, [8; 32])], &[&patient]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient_key, &[8; 32])).await;
+    assert_eq!(pin.paid_through, 0);
+}
+
+#[tokio::test]
+async fn test_underpayment_rejected() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, treasury, patient) = setup(&mut ctx).await;
+    let (registry_key, patient_key) = (registry.pubkey(), patient.pubkey());
+
+    let ix = pin_medical_data_paid_ix(registry_key, patient_key, CID, [7; 32], PRICE - 1, Some(treasury));
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::InsufficientStoragePayment);
+    let ix = pin_medical_data_paid_ix(registry_key, patient_key, CID, [7; 32], PRICE, None);
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::InvalidStorageTreasury);
+    let ix = pin_medical_data_paid_ix(registry_key, patient_key, CID, [7; 32], PRICE, Some(patient_key));
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::InvalidStorageTreasury);
+
+    let pin_record = pin_paid(&mut ctx, &registry, treasury, &patient, PRICE).await;
+    let ix = renew_pin_ix(registry_key, pin_record, patient_key, treasury, 0);
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::InvalidRenewalDays);
+
+    // A payer short of the renewal is refused
+    let broke = Keypair::new();
+    let ix = system_instruction::transfer(&ctx.payer.pubkey(), &broke.pubkey(), PRICE);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = renew_pin_ix(registry_key, pin_record, broke.pubkey(), treasury, 2);
+    assert_error(send(&mut ctx, &[ix], &[&broke]).await, HealthcareError::InsufficientStoragePayment);
+}
+
+#[tokio::test]
+async fn test_renewal_extends_paid_through() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, treasury, patient) = setup(&mut ctx).await;
+    let pin_record = pin_paid(&mut ctx, &registry, treasury, &patient, 2 * PRICE).await;
+    let paid_through = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.paid_through;
+
+    // Anyone may pay for more, from where the pin is paid through
+    let sponsor = funded(&mut ctx).await;
+    let ix = renew_pin_ix(registry.pubkey(), pin_record, sponsor.pubkey(), treasury, 5);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&sponsor]).await;
+    result.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.paid_through, paid_through + 5 * SECS_PER_DAY);
+    assert_eq!(balance(&mut ctx, treasury).await, 7 * PRICE);
+    let event = &events::<PinRenewed>(&logs)[0];
+    assert_eq!((event.payer, event.days, event.lamports), (sponsor.pubkey(), 5, 5 * PRICE));
+    assert_eq!(event.paid_through, pin.paid_through);
+
+    // A new price leaves the pin's date alone and applies to the next renewal
+    let ix = set_pin_storage_price_ix(ctx.payer.pubkey(), registry.pubkey(), 2 * PRICE, treasury);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert_eq!(fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.paid_through, pin.paid_through);
+
+    // and a lapsed pin is renewed from now
+    warp_clock_to(&mut ctx, pin.paid_through + SECS_PER_DAY).await;
+    let ix = renew_pin_ix(registry.pubkey(), pin_record, sponsor.pubkey(), treasury, 1);
+    send(&mut ctx, &[ix], &[&sponsor]).await.unwrap();
+    let renewed: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(renewed.paid_through, pin.paid_through + 2 * SECS_PER_DAY);
+    assert_eq!(balance(&mut ctx, treasury).await, 9 * PRICE);
+}
+
+#[tokio::test]
+async fn test_expiry_after_grace() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, treasury, patient) = setup(&mut ctx).await;
+    let pin_record = pin_paid(&mut ctx, &registry, treasury, &patient, PRICE).await;
+    let paid_through = fetch::<IpfsPinRecord>(&mut ctx, pin_record).await.paid_through;
+    let cranker = funded(&mut ctx).await;
+    let expire = || expire_pin_ix(registry.pubkey(), pin_record, cranker.pubkey());
+
+    warp_clock_to(&mut ctx, paid_through + PIN_EXPIRY_GRACE_SECS - 1).await;
+    assert_error(send(&mut ctx, &[expire()], &[&cranker]).await, HealthcareError::PinNotExpired);
+
+    warp_clock(&mut ctx, 1).await;
+    let (result, logs) = send_logged(&mut ctx, &[expire()], &[&cranker]).await;
+    result.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.status, PinStatus::Expired);
+    let event = &events::<PinExpired>(&logs)[0];
+    assert_eq!((event.pin_record, event.patient, event.paid_through), (pin_record, patient.pubkey(), paid_through));
+
+    // Expired for good: neither renewed nor read
+    let ix = renew_pin_ix(registry.pubkey(), pin_record, patient.pubkey(), treasury, 1);
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::PinAlreadyExpired);
+    let ix = record_access_ix(registry.pubkey(), pin_record, patient.pubkey(), false);
+    assert_error(send(&mut ctx, &[ix], &[&patient]).await, HealthcareError::PinNotConfirmed);
+
+    // A pin stored for free never expires
+    let ix = set_pin_storage_price_ix(ctx.payer.pubkey(), registry.pubkey(), 0, treasury);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let free = funded(&mut ctx).await;
+    let free_pin = pin_paid(&mut ctx, &registry, treasury, &free, 0).await;
+    warp_clock(&mut ctx, 10 * PIN_EXPIRY_GRACE_SECS).await;
+    let ix = expire_pin_ix(registry.pubkey(), free_pin, cranker.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&cranker]).await, HealthcareError::PinNotExpired);
+}
@@ -0,0 +1,144 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, ProvingScheme, VerificationRecord, VerifyingKey};
+
+const PLONK: &str = "eligibility_plonk_v1";
+const GROTH16: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    circuit_id: &str,
+    proof: &[u8],
+    format: ProofFormat,
+    public_inputs: &[u8],
+) -> Result<Pubkey, BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        circuit_id,
+        patient,
+        proof.to_vec(),
+        format,
+        public_inputs.to_vec(),
+        CID,
+    );
+    send(ctx, &[ix], &[]).await?;
+    Ok(verification)
+}
+
+#[tokio::test]
+async fn test_plonk_proof_verifies() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = plonk_square_fixture(1);
+    upload_plonk_vk(&mut ctx, registry.pubkey(), PLONK, &fixture).await;
+
+    let data = fetch_vk_data(&mut ctx, PLONK).await;
+    assert_eq!(VerifyingKey::new(&data).unwrap().scheme(), ProvingScheme::Plonk);
+
+    let verification = submit(&mut ctx, &registry, PLONK, &fixture.proof, ProofFormat::SnarkJs, &fixture.public_inputs)
+        .await
+        .unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+}
+
+#[tokio::test]
+async fn test_plonk_readonly_verdicts() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = plonk_square_fixture(1);
+    upload_plonk_vk(&mut ctx, registry.pubkey(), PLONK, &fixture).await;
+    let check = |proof: &[u8], inputs: &[u8]| {
+        verify_proof_readonly_ix(registry.pubkey(), PLONK, proof.to_vec(), ProofFormat::SnarkJs, inputs.to_vec())
+    };
+
+    let ix = check(&fixture.proof, &fixture.public_inputs);
+    assert_eq!(simulate_return_data(&mut ctx, &[ix], &[]).await.data, [1]);
+
+    let other = plonk_square_fixture(2);
+    let ix = check(&fixture.proof, &other.public_inputs);
+    assert_eq!(simulate_return_data(&mut ctx, &[ix], &[]).await.data, [0]);
+
+    // One opened evaluation changed: the quotient identity no longer holds
+    let mut tampered = fixture.proof.clone();
+    tampered[9 * 64 + 31] ^= 1;
+    let ix = check(&tampered, &fixture.public_inputs);
+    assert_eq!(simulate_return_data(&mut ctx, &[ix], &[]).await.data, [0]);
+}
+
+#[tokio::test]
+async fn test_wrong_public_input_rejected() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = plonk_square_fixture(1);
+    let other = plonk_square_fixture(2);
+    upload_plonk_vk(&mut ctx, registry.pubkey(), PLONK, &fixture).await;
+
+    let result = submit(&mut ctx, &registry, PLONK, &fixture.proof, ProofFormat::SnarkJs, &other.public_inputs).await;
+    assert_error(result.map(drop), HealthcareError::PairingCheckFailed);
+}
+
+#[tokio::test]
+async fn test_proofs_checked_against_their_own_scheme() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let plonk = plonk_square_fixture(1);
+    let groth16 = square_fixture(1);
+    upload_plonk_vk(&mut ctx, registry.pubkey(), PLONK, &plonk).await;
+
+    // snarkjs PLONK proofs have one encoding; the Groth16 layouts don't apply
+    for format in [ProofFormat::Uncompressed, ProofFormat::Compressed] {
+        let result = submit(&mut ctx, &registry, PLONK, &plonk.proof, format, &plonk.public_inputs).await;
+        assert_error(result.map(drop), HealthcareError::UnsupportedProofFormat);
+    }
+    let result = submit(&mut ctx, &registry, PLONK, &groth16.proof, ProofFormat::SnarkJs, &plonk.public_inputs).await;
+    assert_error(result.map(drop), HealthcareError::InvalidProofLength);
+
+    upload_vk(&mut ctx, registry.pubkey(), GROTH16, &groth16.vk_bytes).await;
+    let result = submit(&mut ctx, &registry, GROTH16, &plonk.proof, ProofFormat::SnarkJs, &plonk.public_inputs).await;
+    assert_error(result.map(drop), HealthcareError::InvalidProofLength);
+}
+
+#[tokio::test]
+async fn test_multi_transaction_paths_refuse_plonk() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = plonk_square_fixture(1);
+    let patient = ctx.payer.pubkey();
+    upload_plonk_vk(&mut ctx, registry.pubkey(), PLONK, &fixture).await;
+
+    let partial = Keypair::new();
+    let ix = begin_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        PLONK,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::SnarkJs,
+        fixture.public_inputs.clone(),
+    );
+    assert_error(send(&mut ctx, &[ix], &[&partial]).await, HealthcareError::UnsupportedProvingScheme);
+
+    let submissions = vec![zk_healthcare::ProofSubmission {
+        proof: fixture.proof.clone(),
+        public_inputs: fixture.public_inputs.clone(),
+        ipfs_hash: CID.to_string(),
+    }];
+    let ix = verify_eligibility_batch_ix(registry.pubkey(), 0, PLONK, patient, ProofFormat::SnarkJs, submissions);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::UnsupportedProvingScheme);
+
+    let ix = prepare_vk_ix(patient, PLONK);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::UnsupportedProvingScheme);
+}
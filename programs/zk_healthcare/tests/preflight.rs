@@ -0,0 +1,117 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use ark_bn254::Fr;
+use ark_std::rand::{rngs::StdRng, Rng, SeedableRng};
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::BTreeSet;
+use zk_healthcare::client::{self, PreflightError};
+use zk_healthcare::fixtures::{self, PreimageCircuit};
+use zk_healthcare::ProofFormat;
+
+const CIRCUIT: &str = "preimage_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const FORMATS: [ProofFormat; 3] = [ProofFormat::Uncompressed, ProofFormat::Compressed, ProofFormat::SnarkJs];
+const MUTATIONS_PER_FORMAT: usize = 40;
+
+/// The fixture proof of `preimage` and its public inputs, encoded for `format`
+fn submission(preimage: u64, format: ProofFormat) -> (Vec<u8>, Vec<u8>) {
+    let preimage = Fr::from(preimage);
+    let proof = client::encode_proof(&fixtures::prove(preimage), format);
+    let mut inputs = client::encode_public_inputs(&[PreimageCircuit::hash(preimage)]);
+    if format.big_endian_inputs() {
+        inputs.chunks_exact_mut(32).for_each(|scalar| scalar.reverse());
+    }
+    (proof, inputs)
+}
+
+/// The on-chain outcome of a submission, as the error code it failed with
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    proof: Vec<u8>,
+    format: ProofFormat,
+    inputs: Vec<u8>,
+) -> Result<(), u32> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        proof,
+        format,
+        inputs,
+        CID,
+    );
+    send(ctx, &[ix], &[]).await.map_err(error_code)
+}
+
+/// Flip one to three random bytes, or occasionally drop or append one
+fn mutate(rng: &mut StdRng, bytes: &mut Vec<u8>) {
+    match rng.gen_range(0..10) {
+        0 => {
+            bytes.pop();
+        }
+        1 => bytes.push(rng.gen()),
+        _ => {
+            for _ in 0..rng.gen_range(1..=3) {
+                let index = rng.gen_range(0..bytes.len());
+                bytes[index] ^= rng.gen_range(1..=255u8);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_preflight_accepts_valid_submissions() {
+    let vk_bytes = fixtures::vk_bytes();
+    for format in FORMATS {
+        let (proof, inputs) = submission(fixtures::PREIMAGE, format);
+        assert_eq!(client::preflight_verify(&vk_bytes, &proof, &inputs, format), Ok(()));
+    }
+
+    let (proof, inputs) = submission(fixtures::PREIMAGE, ProofFormat::Uncompressed);
+    let (_, other) = submission(fixtures::PREIMAGE + 1, ProofFormat::Uncompressed);
+    let result = client::preflight_verify(&vk_bytes, &proof, &other, ProofFormat::Uncompressed);
+    assert_eq!(result, Err(PreflightError::PairingCheckFailed));
+
+    let mut vk_bytes = vk_bytes;
+    vk_bytes[0..32].fill(0xff);
+    let err = client::preflight_verify(&vk_bytes, &proof, &inputs, ProofFormat::Uncompressed).unwrap_err();
+    assert_eq!(err, PreflightError::VerifyingKeyDeserializeFailed { offset: 0 });
+}
+
+#[tokio::test]
+async fn test_preflight_agrees_with_program_on_mutations() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk_bytes = fixtures::vk_bytes();
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &vk_bytes).await;
+
+    let mut rng = StdRng::seed_from_u64(0x9e1f);
+    let mut outcomes = BTreeSet::new();
+    for (index, format) in FORMATS.into_iter().enumerate() {
+        let (proof, inputs) = submission(fixtures::PREIMAGE + index as u64, format);
+        for _ in 0..MUTATIONS_PER_FORMAT {
+            let (mut proof, mut inputs) = (proof.clone(), inputs.clone());
+            // Mostly the proof, since it has the most stages to get wrong
+            if rng.gen_bool(0.75) {
+                mutate(&mut rng, &mut proof);
+            } else {
+                mutate(&mut rng, &mut inputs);
+            }
+
+            let local = client::preflight_verify(&vk_bytes, &proof, &inputs, format).map_err(|err| err.code().into());
+            let onchain = submit(&mut ctx, &registry, proof.clone(), format, inputs.clone()).await;
+            assert_eq!(local, onchain, "{:?} proof {:02x?} inputs {:02x?}", format, proof, inputs);
+            outcomes.insert(onchain);
+        }
+    }
+    // Enough distinct failures that the mutations reach past the first check
+    assert!(outcomes.len() >= 4, "only saw {:?}", outcomes);
+}
@@ -0,0 +1,165 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction::SystemError;
+use zk_healthcare::{HealthcareError, PatientIndex, PrescriptionRecord, ProofFormat, VerificationType};
+
+const CIRCUIT: &str = "prescription_v1";
+const DRUG: [u8; 32] = [7; 32];
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// A registry with `CIRCUIT` approved for prescriptions and one registered pharmacy
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> (Keypair, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let ix = set_type_circuit_ix(authority, registry.pubkey(), CIRCUIT, VerificationType::Prescription);
+    send(ctx, &[ix], &[]).await.unwrap();
+    let pharmacy = Keypair::new();
+    let ix = register_pharmacy_ix(authority, registry.pubkey(), pharmacy.pubkey());
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, pharmacy)
+}
+
+async fn now(ctx: &mut ProgramTestContext) -> i64 {
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+/// Prescribe `fixture` to the payer with `refills` refills for a day
+async fn prescribe(ctx: &mut ProgramTestContext, registry: &Keypair, fixture: &Fixture, refills: u8) -> Pubkey {
+    let now = now(ctx).await;
+    let patient = ctx.payer.pubkey();
+    let ix = verify_prescription_ix(registry.pubkey(), CIRCUIT, patient, fixture, DRUG, refills, now + DAY_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+    PrescriptionRecord::address(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs)
+}
+
+#[tokio::test]
+async fn test_prescription_is_recorded_and_refilled() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let (registry, pharmacy) = setup(&mut ctx, &fixture).await;
+    let address = prescribe(&mut ctx, &registry, &fixture, 2).await;
+
+    let patient = ctx.payer.pubkey();
+    let prescription: PrescriptionRecord = fetch(&mut ctx, address).await;
+    assert_eq!(prescription.patient, patient);
+    assert_eq!(prescription.registry, registry.pubkey());
+    assert_eq!(prescription.drug_commitment, DRUG);
+    assert_eq!((prescription.refills_allowed, prescription.refills_remaining), (2, 2));
+    assert_eq!(prescription.valid_until, prescription.verified_at + DAY_SECS);
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.type_counts[VerificationType::Prescription as usize], 1);
+    assert_eq!(index.last_verification, address);
+
+    let ix = record_refill_ix(registry.pubkey(), address, pharmacy.pubkey());
+    send(&mut ctx, &[ix], &[&pharmacy]).await.unwrap();
+    let prescription: PrescriptionRecord = fetch(&mut ctx, address).await;
+    assert_eq!(prescription.refills_remaining, 1);
+
+    // The same proof can't be prescribed twice
+    warp_clock(&mut ctx, 0).await;
+    let now = now(&mut ctx).await;
+    let ix = verify_prescription_ix(registry.pubkey(), CIRCUIT, patient, &fixture, DRUG, 2, now + DAY_SECS);
+    let err = send(&mut ctx, &[ix], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), SystemError::AccountAlreadyInUse as u32);
+}
+
+#[tokio::test]
+async fn test_prescription_needs_its_own_circuit_and_a_future_expiry() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let (patient, now) = (ctx.payer.pubkey(), now(&mut ctx).await);
+
+    // Approved for eligibility only
+    let ix = verify_prescription_ix(registry.pubkey(), CIRCUIT, patient, &fixture, DRUG, 1, now + DAY_SECS);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::WrongCircuitForType);
+
+    let ix = set_type_circuit_ix(patient, registry.pubkey(), CIRCUIT, VerificationType::Prescription);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = verify_prescription_ix(registry.pubkey(), CIRCUIT, patient, &fixture, DRUG, 1, now);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidPrescriptionExpiry);
+}
+
+#[tokio::test]
+async fn test_refills_run_out() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let (registry, pharmacy) = setup(&mut ctx, &fixture).await;
+    let address = prescribe(&mut ctx, &registry, &fixture, 2).await;
+
+    for _ in 0..2 {
+        // Past the slot, so the repeated refill isn't taken for the last one
+        warp_clock(&mut ctx, 0).await;
+        let ix = record_refill_ix(registry.pubkey(), address, pharmacy.pubkey());
+        send(&mut ctx, &[ix], &[&pharmacy]).await.unwrap();
+    }
+    warp_clock(&mut ctx, 0).await;
+    let ix = record_refill_ix(registry.pubkey(), address, pharmacy.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&pharmacy]).await, HealthcareError::NoRefillsRemaining);
+    let prescription: PrescriptionRecord = fetch(&mut ctx, address).await;
+    assert_eq!(prescription.refills_remaining, 0);
+}
+
+#[tokio::test]
+async fn test_no_refill_after_expiry() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let (registry, pharmacy) = setup(&mut ctx, &fixture).await;
+    let address = prescribe(&mut ctx, &registry, &fixture, 3).await;
+
+    let prescription: PrescriptionRecord = fetch(&mut ctx, address).await;
+    warp_clock_to(&mut ctx, prescription.valid_until).await;
+    let ix = record_refill_ix(registry.pubkey(), address, pharmacy.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&pharmacy]).await, HealthcareError::PrescriptionExpired);
+    let prescription: PrescriptionRecord = fetch(&mut ctx, address).await;
+    assert_eq!(prescription.refills_remaining, 3);
+}
+
+#[tokio::test]
+async fn test_only_registered_pharmacies_refill() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let (registry, _) = setup(&mut ctx, &fixture).await;
+    let address = prescribe(&mut ctx, &registry, &fixture, 1).await;
+
+    let stranger = Keypair::new();
+    let ix = record_refill_ix(registry.pubkey(), address, stranger.pubkey());
+    let err = send(&mut ctx, &[ix], &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::AccountNotInitialized as u32);
+}
+
+#[tokio::test]
+async fn test_new_prescription_must_replace_the_current_one() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let (registry, pharmacy) = setup(&mut ctx, &fixtures[0]).await;
+    let first = prescribe(&mut ctx, &registry, &fixtures[0], 1).await;
+    let (patient, valid_until) = (ctx.payer.pubkey(), now(&mut ctx).await + DAY_SECS);
+
+    // Refills left on the first, so a second is refused unless it replaces it
+    let ix = verify_prescription_ix(registry.pubkey(), CIRCUIT, patient, &fixtures[1], DRUG, 1, valid_until);
+    let result = send(&mut ctx, &[ix.clone()], &[]).await;
+    assert_error(result, HealthcareError::PreviousRecordMismatch);
+    let ix = with_previous_record(ix, first);
+    assert_error(send(&mut ctx, &[ix.clone()], &[]).await, HealthcareError::ActiveVerificationExists);
+    send(&mut ctx, &[with_force_new(ix)], &[]).await.unwrap();
+
+    let prescription: PrescriptionRecord = fetch(&mut ctx, first).await;
+    assert!(prescription.valid_until <= now(&mut ctx).await);
+    let ix = record_refill_ix(registry.pubkey(), first, pharmacy.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&pharmacy]).await, HealthcareError::PrescriptionExpired);
+    let (proof, inputs) = (&fixtures[1].proof, &fixtures[1].public_inputs);
+    let second = PrescriptionRecord::address(proof, ProofFormat::Uncompressed, inputs);
+    assert_eq!(latest_record(&mut ctx, patient, VerificationType::Prescription).await, Some(second));
+}
@@ -0,0 +1,130 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_sdk::signature::Signer;
+use zk_healthcare::{HealthcareError, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+#[tokio::test]
+async fn test_compressed_and_uncompressed_proofs_share_hash() {
+    let fixture = square_fixture(1);
+
+    // Separate banks: the second encoding would otherwise hit the nullifier
+    let mut hashes = Vec::new();
+    for (proof, format) in [
+        (fixture.proof.clone(), ProofFormat::Uncompressed),
+        (fixture.compressed_proof.clone(), ProofFormat::Compressed),
+    ] {
+        let mut ctx = start().await;
+        let registry = initialize_registry(&mut ctx).await;
+        upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+        let patient = ctx.payer.pubkey();
+        let nonce = next_record_nonce(&mut ctx, patient).await;
+        let verification = verification_address(&patient, nonce);
+        let ix = verify_eligibility_ix(
+            registry.pubkey(),
+            nonce,
+            CIRCUIT,
+            patient,
+            proof,
+            format,
+            fixture.public_inputs.clone(),
+            CID,
+        );
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+
+        let record: VerificationRecord = fetch(&mut ctx, verification).await;
+        assert!(record.is_valid);
+        hashes.push(record.proof_hash);
+    }
+    assert_eq!(hashes[0], hashes[1]);
+}
+
+#[tokio::test]
+async fn test_proof_format_mismatch_rejected() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.compressed_proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        CID,
+    );
+    assert_error(
+        send(&mut ctx, &[ix], &[]).await,
+        HealthcareError::InvalidProofLength,
+    );
+}
+
+#[tokio::test]
+async fn test_identity_a_rejected_before_pairing() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    // arkworks marks the point at infinity with a flag bit in the last byte of y
+    let mut proof = fixture.proof;
+    proof[..64].fill(0);
+    proof[63] = 0x40;
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        CID,
+    );
+    assert_error(
+        send(&mut ctx, &[ix], &[]).await,
+        HealthcareError::InvalidProofPoint,
+    );
+}
+
+#[tokio::test]
+async fn test_flipped_sign_flag_rejected() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    // Same A point to a lenient decoder, different bytes to keccak
+    let mut proof = fixture.proof;
+    proof[63] ^= 0x80;
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        CID,
+    );
+    assert_error(
+        send(&mut ctx, &[ix], &[]).await,
+        HealthcareError::NonCanonicalProofEncoding,
+    );
+}
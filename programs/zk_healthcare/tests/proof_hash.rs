@@ -0,0 +1,120 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    compute_public_inputs_hash, compute_verification_hash, parse_public_inputs, HashAlgo, HealthcareError, ProofFormat,
+    VerificationRecord, VerificationResult,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// The payer's next record and the instruction proving `fixture` into it
+async fn verify_ix(ctx: &mut ProgramTestContext, registry: &Keypair, fixture: &Fixture) -> (Pubkey, Instruction) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    (verification_address(&patient, nonce), ix)
+}
+
+fn poseidon_of(fixture: &Fixture) -> [u8; 32] {
+    let inputs = parse_public_inputs(&fixture.public_inputs, ProofFormat::Uncompressed).unwrap();
+    HashAlgo::Poseidon.proof_hash([0; 32], &inputs).unwrap()
+}
+
+#[tokio::test]
+async fn test_helpers_match_recorded_hashes() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let (verification, ix) = verify_ix(&mut ctx, &registry, &fixture).await;
+    let answer = simulate_return_data(&mut ctx, std::slice::from_ref(&ix), &[]).await;
+    let result = VerificationResult::try_from_slice(&answer.data).unwrap();
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let inputs = parse_public_inputs(&fixture.public_inputs, ProofFormat::Uncompressed).unwrap();
+    let expected = compute_verification_hash(&fixture.compressed_proof, &inputs);
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.proof_hash, expected);
+    assert_eq!(result.proof_hash, expected);
+    assert_eq!(record.public_inputs_hash, compute_public_inputs_hash(&inputs));
+
+    // The same proof under another statement or another program's tag hashes apart
+    let mut other = inputs.clone();
+    other[0][31] ^= 1;
+    assert_ne!(compute_verification_hash(&fixture.compressed_proof, &other), expected);
+    let untagged = anchor_lang::solana_program::keccak::hashv(&[&fixture.compressed_proof, &inputs[0]]);
+    assert_ne!(untagged.to_bytes(), expected);
+}
+
+#[tokio::test]
+async fn test_poseidon_proof_hash_recorded() {
+    let mut ctx = start().await;
+    let registry = initialize_registry_with(&mut ctx, false).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let (verification, ix) = verify_ix(&mut ctx, &registry, &fixture).await;
+    let ix = with_hash_algo(ix, HashAlgo::Poseidon);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.hash_algo, HashAlgo::Poseidon);
+    assert_eq!(record.proof_hash, poseidon_of(&fixture));
+}
+
+#[tokio::test]
+async fn test_nist_registry_refuses_poseidon() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    let (verification, ix) = verify_ix(&mut ctx, &registry, &fixture).await;
+    let ix = with_hash_algo(ix, HashAlgo::Poseidon);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::HashAlgoNotCompliant);
+
+    let ix = verify_ix(&mut ctx, &registry, &fixture).await.1;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.hash_algo, HashAlgo::Keccak);
+    assert_eq!(record.proof_hash, verification_hash(&fixture));
+}
+
+#[tokio::test]
+async fn test_batch_records_poseidon_per_statement() {
+    let mut ctx = start().await;
+    let registry = initialize_registry_with(&mut ctx, false).await;
+    let fixtures = batch_fixtures(2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+
+    let submissions = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+    let patient = ctx.payer.pubkey();
+    let format = ProofFormat::Uncompressed;
+    let ix = verify_eligibility_batch_ix(registry.pubkey(), 0, CIRCUIT, patient, format, submissions);
+    send(&mut ctx, &[with_hash_algo(ix, HashAlgo::Poseidon)], &[]).await.unwrap();
+
+    for (fixture, nonce) in fixtures.iter().zip(0..) {
+        let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, nonce)).await;
+        assert_eq!(record.hash_algo, HashAlgo::Poseidon);
+        assert_eq!(record.proof_hash, poseidon_of(fixture));
+    }
+}
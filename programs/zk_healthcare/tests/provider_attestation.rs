@@ -0,0 +1,258 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    attestation_message, eth_signed_message, AttestationScheme, HealthcareError, ProofFormat, ProviderAttestation,
+    VerificationRecord, VkConfig,
+};
+
+const CIRCUIT: &str = "eligibility_attested";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with a registered provider and a circuit that may require one
+async fn setup(ctx: &mut ProgramTestContext, requires_provider: bool) -> (Keypair, Keypair, Fixture) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    let config = VkConfig { requires_provider, ..VkConfig::default() };
+    upload_vk_with(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes, config).await;
+    let provider = Keypair::new();
+    let ix = register_provider_ix(ctx.payer.pubkey(), registry.pubkey(), provider.pubkey());
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, provider, fixture)
+}
+
+fn verify_ix(ctx: &ProgramTestContext, registry: &Keypair, fixture: &Fixture) -> Instruction {
+    verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    )
+}
+
+#[tokio::test]
+async fn test_circuit_requiring_a_provider_fails_without_one() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = setup(&mut ctx, true).await;
+
+    let ix = verify_ix(&ctx, &registry, &fixture);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::ProviderRequired);
+
+    // Nor can the batch path, which takes no provider, record it
+    let ix = verify_eligibility_batch_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        ProofFormat::Uncompressed,
+        vec![submission(&fixture, CID)],
+    );
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::ProviderRequired);
+
+    let ix = with_provider(verify_ix(&ctx, &registry, &fixture), registry.pubkey(), provider.pubkey());
+    send(&mut ctx, &[ix], &[&provider]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_inactive_provider_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = setup(&mut ctx, true).await;
+    let ix = set_provider_active_ix(ctx.payer.pubkey(), registry.pubkey(), provider.pubkey(), false);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = with_provider(verify_ix(&ctx, &registry, &fixture), registry.pubkey(), provider.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&provider]).await, HealthcareError::ProviderInactive);
+
+    // A provider registered under another registry doesn't count here
+    let other = initialize_registry(&mut ctx).await;
+    let ix = register_provider_ix(ctx.payer.pubkey(), other.pubkey(), provider.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = with_provider(verify_ix(&ctx, &registry, &fixture), other.pubkey(), provider.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[&provider]).await, HealthcareError::ProviderNotRegistered);
+}
+
+#[tokio::test]
+async fn test_record_stores_the_provider() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = setup(&mut ctx, false).await;
+    let patient = ctx.payer.pubkey();
+
+    let ix = with_provider(verify_ix(&ctx, &registry, &fixture), registry.pubkey(), provider.pubkey());
+    send(&mut ctx, &[ix], &[&provider]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 0)).await;
+    assert_eq!(record.attesting_provider, Some(provider.pubkey()));
+    assert!(record.is_verified());
+}
+
+/// `verify_ix` attested offline by `provider` over `message`, behind the
+/// `ed25519_program` instruction checking its signature
+fn attested_ixs(
+    ctx: &ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+    provider: &Keypair,
+    message: &[u8],
+) -> [Instruction; 2] {
+    let attestation = ProviderAttestation {
+        provider: provider.pubkey(),
+        msg_hash: keccak::hash(message).0,
+        sig_index: 0,
+        scheme: AttestationScheme::Ed25519,
+    };
+    let verify = with_attestation(verify_ix(ctx, registry, fixture), registry.pubkey(), attestation);
+    [ed25519_ix(provider, message), verify]
+}
+
+async fn now(ctx: &mut ProgramTestContext) -> i64 {
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+#[tokio::test]
+async fn test_offline_attestation_stores_the_provider() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = setup(&mut ctx, true).await;
+    let patient = ctx.payer.pubkey();
+
+    let expires_at = now(&mut ctx).await + 3600;
+    let message = attestation_message(&registry.pubkey(), &patient, CIRCUIT, expires_at);
+    let ixs = attested_ixs(&ctx, &registry, &fixture, &provider, &message);
+    // The provider signs nothing in the transaction itself
+    send(&mut ctx, &ixs, &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 0)).await;
+    assert_eq!(record.attesting_provider, Some(provider.pubkey()));
+}
+
+#[tokio::test]
+async fn test_tampered_attestation_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = setup(&mut ctx, true).await;
+    let patient = ctx.payer.pubkey();
+    let expires_at = now(&mut ctx).await + 3600;
+    let message = attestation_message(&registry.pubkey(), &patient, CIRCUIT, expires_at);
+
+    // A message changed after signing fails the signature check
+    let mut ixs = attested_ixs(&ctx, &registry, &fixture, &provider, &message);
+    *ixs[0].data.last_mut().unwrap() ^= 1;
+    assert!(send(&mut ctx, &ixs, &[]).await.is_err());
+
+    // A good signature over another patient's attestation
+    let other = attestation_message(&registry.pubkey(), &Pubkey::new_unique(), CIRCUIT, expires_at);
+    let ixs = attested_ixs(&ctx, &registry, &fixture, &provider, &other);
+    assert_error(send(&mut ctx, &ixs, &[]).await, HealthcareError::AttestationMismatch);
+
+    // A `msg_hash` other than the signed message's
+    let mut ixs = attested_ixs(&ctx, &registry, &fixture, &provider, &message);
+    ixs[0] = ed25519_ix(&provider, &attestation_message(&registry.pubkey(), &patient, CIRCUIT, expires_at + 1));
+    assert_error(send(&mut ctx, &ixs, &[]).await, HealthcareError::AttestationMismatch);
+
+    // Signed by someone other than the named provider
+    let mut ixs = attested_ixs(&ctx, &registry, &fixture, &provider, &message);
+    ixs[0] = ed25519_ix(&Keypair::new(), &message);
+    assert_error(send(&mut ctx, &ixs, &[]).await, HealthcareError::AttestationMismatch);
+    assert!(ctx.banks_client.get_account(verification_address(&patient, 0)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_expired_attestation_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = setup(&mut ctx, true).await;
+    let patient = ctx.payer.pubkey();
+
+    let expires_at = now(&mut ctx).await;
+    let message = attestation_message(&registry.pubkey(), &patient, CIRCUIT, expires_at);
+    let ixs = attested_ixs(&ctx, &registry, &fixture, &provider, &message);
+    assert_error(send(&mut ctx, &ixs, &[]).await, HealthcareError::AttestationExpired);
+}
+
+/// The private key of the web3.js `accounts.privateKeyToAccount` example, and
+/// the address it documents for it
+const ETH_KEY: [u8; 32] = [
+    0x4c, 0x08, 0x83, 0xa6, 0x91, 0x02, 0x93, 0x7d, 0x62, 0x31, 0x47, 0x1b, 0x5d, 0xbb, 0x62, 0x04, 0xfe, 0x51, 0x29,
+    0x61, 0x70, 0x82, 0x79, 0x2a, 0xe4, 0x68, 0xd0, 0x1a, 0x3f, 0x36, 0x23, 0x18,
+];
+const ETH_ADDRESS: [u8; 20] = [
+    0x2c, 0x75, 0x36, 0xe3, 0x60, 0x5d, 0x9c, 0x16, 0xa7, 0xa3, 0xd7, 0xb1, 0x89, 0x8e, 0x52, 0x93, 0x96, 0xa6, 0x5c,
+    0x23,
+];
+
+/// A registry whose circuit requires a provider, and a provider registered
+/// with `ETH_ADDRESS`
+async fn eth_setup(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey, Fixture) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    let config = VkConfig { requires_provider: true, ..VkConfig::default() };
+    upload_vk_with(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes, config).await;
+    let provider = Pubkey::new_unique();
+    let ix = register_eth_provider_ix(ctx.payer.pubkey(), registry.pubkey(), provider, Some(ETH_ADDRESS));
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, provider, fixture)
+}
+
+/// `verify_ix` attested by `provider` through `secret_key`'s secp256k1
+/// signature over `eth_signed_message(message)`
+async fn send_eth_attested(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+    provider: Pubkey,
+    secret_key: &libsecp256k1::SecretKey,
+    high_s: bool,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let expires_at = now(ctx).await + 3600;
+    let message = attestation_message(&registry.pubkey(), &ctx.payer.pubkey(), CIRCUIT, expires_at);
+    let signed = eth_signed_message(&message);
+    let attestation = ProviderAttestation {
+        provider,
+        msg_hash: keccak::hash(&signed).0,
+        sig_index: 0,
+        scheme: AttestationScheme::Secp256k1,
+    };
+    let verify = with_attestation(verify_ix(ctx, registry, fixture), registry.pubkey(), attestation);
+    send(ctx, &[secp256k1_ix(secret_key, &signed, 0, high_s), verify], &[]).await
+}
+
+#[tokio::test]
+async fn test_secp256k1_attestation_stores_the_provider() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = eth_setup(&mut ctx).await;
+    let secret_key = libsecp256k1::SecretKey::parse(&ETH_KEY).unwrap();
+    assert_eq!(eth_address(&secret_key), ETH_ADDRESS);
+
+    send_eth_attested(&mut ctx, &registry, &fixture, provider, &secret_key, false).await.unwrap();
+    let verification = verification_address(&ctx.payer.pubkey(), 0);
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.attesting_provider, Some(provider));
+}
+
+#[tokio::test]
+async fn test_secp256k1_signature_recovering_another_address_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = eth_setup(&mut ctx).await;
+
+    let stranger = libsecp256k1::SecretKey::parse(&[7; 32]).unwrap();
+    let result = send_eth_attested(&mut ctx, &registry, &fixture, provider, &stranger, false).await;
+    assert_error(result, HealthcareError::AttestationMismatch);
+}
+
+#[tokio::test]
+async fn test_secp256k1_high_s_signature_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, provider, fixture) = eth_setup(&mut ctx).await;
+    let secret_key = libsecp256k1::SecretKey::parse(&ETH_KEY).unwrap();
+
+    let result = send_eth_attested(&mut ctx, &registry, &fixture, provider, &secret_key, true).await;
+    assert_error(result, HealthcareError::AttestationSignatureMalleable);
+}
@@ -0,0 +1,104 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, ProviderRegistration, SECS_PER_DAY};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with a registered provider capped at `daily_quota`
+async fn setup(ctx: &mut ProgramTestContext, daily_quota: u32) -> (Keypair, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &batch_fixtures(1)[0].vk_bytes).await;
+    let provider = Keypair::new();
+    let authority = ctx.payer.pubkey();
+    let ix = register_provider_ix(authority, registry.pubkey(), provider.pubkey());
+    send(ctx, &[ix], &[]).await.unwrap();
+    let ix = set_provider_quota_ix(authority, registry.pubkey(), provider.pubkey(), daily_quota);
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, provider)
+}
+
+/// Verify `fixture` as the payer, attested by `provider` when passed
+async fn verify(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    provider: Option<&Keypair>,
+    fixture: &Fixture,
+) -> Result<(), BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = with_force_new(verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    ));
+    match provider {
+        Some(provider) => send(ctx, &[with_provider(ix, registry.pubkey(), provider.pubkey())], &[provider]).await,
+        None => send(ctx, &[ix], &[]).await,
+    }
+}
+
+#[tokio::test]
+async fn test_quota_resets_at_the_day_boundary() {
+    let mut ctx = start().await;
+    let (registry, provider) = setup(&mut ctx, 2).await;
+    let fixtures = batch_fixtures(5);
+    let registration = ProviderRegistration::address(&registry.pubkey(), &provider.pubkey());
+
+    // A minute before midnight UTC
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let midnight = (clock.unix_timestamp.div_euclid(SECS_PER_DAY) + 1) * SECS_PER_DAY;
+    warp_clock_to(&mut ctx, midnight - 60).await;
+    verify(&mut ctx, &registry, Some(&provider), &fixtures[0]).await.unwrap();
+    verify(&mut ctx, &registry, Some(&provider), &fixtures[1]).await.unwrap();
+    let result = verify(&mut ctx, &registry, Some(&provider), &fixtures[2]).await;
+    assert_error(result, HealthcareError::ProviderQuotaExceeded);
+    let account: ProviderRegistration = fetch(&mut ctx, registration).await;
+    assert_eq!((account.used_today, account.quota_epoch_day), (2, midnight / SECS_PER_DAY - 1));
+
+    // Verifications the provider doesn't attest aren't counted
+    verify(&mut ctx, &registry, None, &fixtures[3]).await.unwrap();
+
+    warp_clock_to(&mut ctx, midnight).await;
+    verify(&mut ctx, &registry, Some(&provider), &fixtures[2]).await.unwrap();
+    let account: ProviderRegistration = fetch(&mut ctx, registration).await;
+    assert_eq!((account.used_today, account.quota_epoch_day), (1, midnight / SECS_PER_DAY));
+}
+
+#[tokio::test]
+async fn test_only_authority_sets_quota() {
+    let mut ctx = start().await;
+    let (registry, provider) = setup(&mut ctx, 1).await;
+    let fixtures = batch_fixtures(3);
+    verify(&mut ctx, &registry, Some(&provider), &fixtures[0]).await.unwrap();
+    let result = verify(&mut ctx, &registry, Some(&provider), &fixtures[1]).await;
+    assert_error(result, HealthcareError::ProviderQuotaExceeded);
+
+    // The provider can't raise its own cap
+    let ix = set_provider_quota_ix(provider.pubkey(), registry.pubkey(), provider.pubkey(), 0);
+    let err = send(&mut ctx, &[ix], &[&provider]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+
+    // Zero lifts the cap
+    let ix = set_provider_quota_ix(ctx.payer.pubkey(), registry.pubkey(), provider.pubkey(), 0);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Past the slot, so the repeated verification isn't taken for the refused one
+    warp_clock(&mut ctx, 0).await;
+    verify(&mut ctx, &registry, Some(&provider), &fixtures[1]).await.unwrap();
+    verify(&mut ctx, &registry, Some(&provider), &fixtures[2]).await.unwrap();
+    let account: ProviderRegistration =
+        fetch(&mut ctx, ProviderRegistration::address(&registry.pubkey(), &provider.pubkey())).await;
+    assert_eq!((account.daily_quota, account.used_today), (0, 3));
+}
@@ -0,0 +1,174 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, HealthcareRegistry, ProofFormat, DEFAULT_CACHE_TTL_SLOTS, DEFAULT_MAX_PUBLIC_INPUTS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry whose circuit takes two public inputs, and fixtures proving
+/// distinct statements under it
+async fn setup(ctx: &mut ProgramTestContext, n: u64) -> (Keypair, Vec<Fixture>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(n);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    (registry, fixtures)
+}
+
+async fn set_limit(ctx: &mut ProgramTestContext, registry: &Keypair, max_public_inputs: u8) {
+    let ix = update_config_ix(ctx.payer.pubkey(), registry.pubkey(), max_public_inputs, DEFAULT_CACHE_TTL_SLOTS, 0);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+/// Submit as the payer, expiring their record before this one
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+    public_inputs: Vec<u8>,
+) -> Result<(), BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        public_inputs,
+        CID,
+    );
+    send(ctx, &[with_force_new(ix)], &[]).await
+}
+
+#[tokio::test]
+async fn test_limit_admits_exactly_max_public_inputs() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 1).await;
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.max_public_inputs, DEFAULT_MAX_PUBLIC_INPUTS);
+
+    set_limit(&mut ctx, &registry, 1).await;
+    let result = submit(&mut ctx, &registry, &fixtures[0], fixtures[0].public_inputs.clone()).await;
+    assert_error(result, HealthcareError::TooManyPublicInputs);
+
+    set_limit(&mut ctx, &registry, 2).await;
+    // Past the slot, so the retried submission isn't taken for the refused one
+    warp_clock(&mut ctx, 0).await;
+    submit(&mut ctx, &registry, &fixtures[0], fixtures[0].public_inputs.clone()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_limit_applies_to_every_verify_instruction() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 2).await;
+    set_limit(&mut ctx, &registry, 1).await;
+
+    let submissions = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+    let ix = verify_eligibility_batch_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        ProofFormat::Uncompressed,
+        submissions,
+    );
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::TooManyPublicInputs);
+
+    let partial = Keypair::new();
+    let ix = begin_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        fixtures[0].proof.clone(),
+        ProofFormat::Uncompressed,
+        fixtures[0].public_inputs.clone(),
+    );
+    assert_error(send(&mut ctx, &[ix], &[&partial]).await, HealthcareError::TooManyPublicInputs);
+}
+
+#[tokio::test]
+async fn test_empty_public_inputs_rejected() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 1).await;
+
+    let result = submit(&mut ctx, &registry, &fixtures[0], Vec::new()).await;
+    assert_error(result, HealthcareError::EmptyPublicInputs);
+
+    let mut entry = submission(&fixtures[0], CID);
+    entry.public_inputs.clear();
+    let ix = verify_eligibility_batch_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        ProofFormat::Uncompressed,
+        vec![entry],
+    );
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::EmptyPublicInputs);
+}
+
+#[tokio::test]
+async fn test_limit_changed_mid_flight() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, 3).await;
+    let patient = ctx.payer.pubkey();
+
+    // A verification begun under the old limit finishes under the new one
+    let partial = Keypair::new();
+    let ix = begin_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        patient,
+        fixtures[0].proof.clone(),
+        ProofFormat::Uncompressed,
+        fixtures[0].public_inputs.clone(),
+    );
+    send(&mut ctx, &[ix], &[&partial]).await.unwrap();
+    submit(&mut ctx, &registry, &fixtures[1], fixtures[1].public_inputs.clone()).await.unwrap();
+
+    set_limit(&mut ctx, &registry, 1).await;
+    let ix = advance_verification_ix(partial.pubkey(), CIRCUIT, patient);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record_nonce = next_record_nonce(&mut ctx, patient).await;
+    let nullifier = nullifier_address(&fixtures[0].proof, ProofFormat::Uncompressed, &fixtures[0].public_inputs);
+    let ix = complete_verification_ix(
+        registry.pubkey(),
+        partial.pubkey(),
+        CIRCUIT,
+        record_nonce,
+        nullifier,
+        patient,
+        CID,
+    );
+    send(&mut ctx, &[with_force_new(ix)], &[]).await.unwrap();
+
+    // New submissions are held to it
+    let result = submit(&mut ctx, &registry, &fixtures[2], fixtures[2].public_inputs.clone()).await;
+    assert_error(result, HealthcareError::TooManyPublicInputs);
+    set_limit(&mut ctx, &registry, 2).await;
+    // Past the slot, so the repeated submission isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    submit(&mut ctx, &registry, &fixtures[2], fixtures[2].public_inputs.clone()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_only_authority_updates_config() {
+    let mut ctx = start().await;
+    let (registry, _) = setup(&mut ctx, 1).await;
+
+    let intruder = Keypair::new();
+    let ix = update_config_ix(intruder.pubkey(), registry.pubkey(), u8::MAX, DEFAULT_CACHE_TTL_SLOTS, 0);
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+}
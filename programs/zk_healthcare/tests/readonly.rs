@@ -0,0 +1,129 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::{get_return_data, invoke, set_return_data};
+use anchor_lang::solana_program::program_error::ProgramError;
+use common::*;
+use solana_program_test::processor;
+use solana_sdk::signature::Signer;
+use zk_healthcare::{HealthcareRegistry, ProofFormat};
+
+const CIRCUIT: &str = "eligibility_v1";
+
+/// Stands in for an integrator's program: forwards its instruction data to
+/// `verify_proof_readonly` and republishes the answer it reads back
+fn relay(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let ix = Instruction {
+        program_id: zk_healthcare::ID,
+        accounts: accounts[1..]
+            .iter()
+            .map(|account| AccountMeta::new_readonly(*account.key, false))
+            .collect(),
+        data: data.to_vec(),
+    };
+    invoke(&ix, accounts)?;
+    match get_return_data() {
+        Some((program_id, answer)) if program_id == zk_healthcare::ID => {
+            set_return_data(&answer);
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+#[tokio::test]
+async fn test_simulation_returns_verdict() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let check = |proof: &[u8], format, inputs: &[u8]| {
+        verify_proof_readonly_ix(registry.pubkey(), CIRCUIT, proof.to_vec(), format, inputs.to_vec())
+    };
+
+    let ix = check(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs);
+    let answer = simulate_return_data(&mut ctx, &[ix], &[]).await;
+    assert_eq!(answer.program_id, zk_healthcare::ID);
+    assert_eq!(answer.data, [1]);
+    let ix = check(&fixture.compressed_proof, ProofFormat::Compressed, &fixture.public_inputs);
+    assert_eq!(simulate_return_data(&mut ctx, &[ix], &[]).await.data, [1]);
+
+    // A wrong statement and a malformed proof are answers, not failed transactions
+    let other = square_fixture(2);
+    let ix = check(&fixture.proof, ProofFormat::Uncompressed, &other.public_inputs);
+    assert_eq!(simulate_return_data(&mut ctx, &[ix], &[]).await.data, [0]);
+    let ix = check(&fixture.proof[..128], ProofFormat::Uncompressed, &fixture.public_inputs);
+    assert_eq!(simulate_return_data(&mut ctx, &[ix], &[]).await.data, [0]);
+}
+
+#[tokio::test]
+async fn test_check_writes_no_state() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let before: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+
+    let ix = verify_proof_readonly_ix(
+        registry.pubkey(),
+        CIRCUIT,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.total_verifications, 0);
+    assert_eq!(state.ipfs_pin_count, 0);
+    assert_eq!(state.event_seq, before.event_seq);
+    let nullifier = nullifier_address(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs);
+    assert!(ctx.banks_client.get_account(nullifier).await.unwrap().is_none());
+
+    // The proof can still be recorded afterwards
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cpi_caller_reads_return_data() {
+    let relay_id = Pubkey::new_unique();
+    let mut program = program_test();
+    program.add_program("relay", relay_id, processor!(relay));
+    let mut ctx = program.start_with_context().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+
+    for (inputs, expected) in [(fixture.public_inputs.clone(), 1), (square_fixture(2).public_inputs, 0)] {
+        let proof = fixture.proof.clone();
+        let check = verify_proof_readonly_ix(registry.pubkey(), CIRCUIT, proof, ProofFormat::Uncompressed, inputs);
+        let ix = Instruction {
+            program_id: relay_id,
+            accounts: vec![
+                AccountMeta::new_readonly(zk_healthcare::ID, false),
+                AccountMeta::new_readonly(registry.pubkey(), false),
+                AccountMeta::new_readonly(vk_address(CIRCUIT), false),
+            ],
+            data: check.data,
+        };
+        let answer = simulate_return_data(&mut ctx, &[ix], &[]).await;
+        assert_eq!(answer.program_id, relay_id);
+        assert_eq!(answer.data, [expected]);
+    }
+}
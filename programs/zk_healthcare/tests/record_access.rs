@@ -0,0 +1,165 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use anchor_lang::AccountSerialize;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{
+    AccessDenied, AccessPass, DataAccessed, HealthcareError, HealthcareRegistry, IpfsPinRecord, VerificationType,
+};
+
+const CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with `CIRCUIT` approved for access control, a confirmed pin of
+/// the payer's under it, and a funded provider
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> (Keypair, Pubkey, Keypair) {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let ix = set_type_circuit_ix(patient, registry.pubkey(), CIRCUIT, VerificationType::AccessControl);
+    send(ctx, &[ix], &[]).await.unwrap();
+    send(ctx, &[pin_medical_data_ix(registry.pubkey(), patient, CID, [7; 32])], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient, &[7; 32]);
+    confirm_pin(ctx, registry.pubkey(), pin_record).await;
+
+    let provider = Keypair::new();
+    let ix = system_instruction::transfer(&patient, &provider.pubkey(), 1_000_000_000);
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, pin_record, provider)
+}
+
+/// Issue `provider` a pass to `pin_record` and return its address
+async fn grant(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+    pin_record: Pubkey,
+    provider: &Keypair,
+) -> Pubkey {
+    let holder = provider.pubkey();
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, holder, fixture, pin_record, 100, 2);
+    send(ctx, &[ix], &[provider]).await.unwrap();
+    AccessPass::address(&holder, &pin_record)
+}
+
+/// `ix` from `record_access_ix` presenting `access_pass`, whoever holds it
+fn with_access_pass(mut ix: Instruction, access_pass: Pubkey) -> Instruction {
+    ix.accounts[2] = AccountMeta::new(access_pass, false);
+    ix
+}
+
+#[tokio::test]
+async fn test_patient_reads_their_own_pin() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, _) = setup(&mut ctx, &square_fixture(1)).await;
+    let patient = ctx.payer.pubkey();
+
+    warp_clock(&mut ctx, 30).await;
+    let ix = record_access_ix(registry.pubkey(), pin_record, patient, false);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.access_count, pin.last_accessed_at), (1, clock.unix_timestamp));
+    // Without a pass, nothing holds the pin
+    assert_eq!(pin.grants_expire_at_slot, 0);
+    let event = &events::<DataAccessed>(&logs)[0];
+    assert_eq!((event.pin_record, event.accessor, event.patient), (pin_record, patient, patient));
+    assert_eq!((event.registry, event.timestamp), (registry.pubkey(), clock.unix_timestamp));
+}
+
+#[tokio::test]
+async fn test_provider_reads_on_its_pass() {
+    let mut ctx = start_with_event_logs().await;
+    let fixture = square_fixture(1);
+    let (registry, pin_record, provider) = setup(&mut ctx, &fixture).await;
+    let access_pass = grant(&mut ctx, &registry, &fixture, pin_record, &provider).await;
+
+    let ix = record_access_ix(registry.pubkey(), pin_record, provider.pubkey(), true);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&provider]).await;
+    result.unwrap();
+    let event = &events::<DataAccessed>(&logs)[0];
+    assert_eq!((event.accessor, event.patient), (provider.pubkey(), ctx.payer.pubkey()));
+    let pass: AccessPass = fetch(&mut ctx, access_pass).await;
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pass.uses_remaining, pin.access_count), (1, 1));
+    assert_eq!(pin.grants_expire_at_slot, pass.expires_at_slot);
+
+    // The pass doesn't outlive its slots
+    ctx.warp_to_slot(pass.expires_at_slot + 1).unwrap();
+    let ix = record_access_ix(registry.pubkey(), pin_record, provider.pubkey(), true);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&provider]).await;
+    assert_error(result, HealthcareError::AccessDenied);
+    assert!(logs.iter().any(|log| log.ends_with("Access denied: access pass expired")), "{logs:?}");
+}
+
+#[tokio::test]
+async fn test_third_party_without_a_grant_is_denied() {
+    let mut ctx = start_with_event_logs().await;
+    let fixture = square_fixture(1);
+    let (registry, pin_record, provider) = setup(&mut ctx, &fixture).await;
+    let access_pass = grant(&mut ctx, &registry, &fixture, pin_record, &provider).await;
+    let stranger = Keypair::new();
+
+    // Denied events are off by default
+    let ix = record_access_ix(registry.pubkey(), pin_record, stranger.pubkey(), false);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&stranger]).await;
+    assert_error(result, HealthcareError::AccessDenied);
+    assert!(events::<AccessDenied>(&logs).is_empty());
+    assert!(logs.iter().any(|log| log.ends_with("Access denied: no access pass")), "{logs:?}");
+
+    let ix = set_denied_access_events_ix(ctx.payer.pubkey(), registry.pubkey(), true);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    let event_seq = fetch::<HealthcareRegistry>(&mut ctx, registry.pubkey()).await.event_seq;
+    let ix = record_access_ix(registry.pubkey(), pin_record, stranger.pubkey(), false);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&stranger]).await;
+    assert_error(result, HealthcareError::AccessDenied);
+    let event = &events::<AccessDenied>(&logs)[0];
+    assert_eq!((event.pin_record, event.accessor), (pin_record, stranger.pubkey()));
+    assert_eq!(event.patient, ctx.payer.pubkey());
+    // The denial takes no seq, which its failed transaction couldn't keep
+    assert_eq!(fetch::<HealthcareRegistry>(&mut ctx, registry.pubkey()).await.event_seq, event_seq);
+
+    // nor does presenting someone else's pass help
+    let ix = with_access_pass(record_access_ix(registry.pubkey(), pin_record, stranger.pubkey(), true), access_pass);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[&stranger]).await;
+    assert_error(result, HealthcareError::AccessDenied);
+    assert!(logs.iter().any(|log| log.ends_with("Access denied: access pass held by another key")), "{logs:?}");
+    assert_eq!(events::<AccessDenied>(&logs).len(), 1);
+
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    let pass: AccessPass = fetch(&mut ctx, access_pass).await;
+    assert_eq!((pin.access_count, pass.uses_remaining), (0, 2));
+}
+
+#[tokio::test]
+async fn test_access_count_does_not_wrap() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, _) = setup(&mut ctx, &square_fixture(1)).await;
+    let patient = ctx.payer.pubkey();
+
+    let mut pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    pin.access_count = u32::MAX - 1;
+    let mut account = ctx.banks_client.get_account(pin_record).await.unwrap().unwrap();
+    pin.try_serialize(&mut &mut account.data[..]).unwrap();
+    ctx.set_account(&pin_record, &account.into());
+
+    send(&mut ctx, &[record_access_ix(registry.pubkey(), pin_record, patient, false)], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.access_count, u32::MAX);
+    warp_clock(&mut ctx, 0).await;
+    let ix = record_access_ix(registry.pubkey(), pin_record, patient, false);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CounterOverflow);
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.access_count, u32::MAX);
+}
@@ -0,0 +1,123 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, PatientIndex, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// `n` records the payer verified one after another, oldest first
+async fn setup(ctx: &mut ProgramTestContext, n: u64) -> (Keypair, Vec<Pubkey>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(n);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let mut records = Vec::new();
+    for (nonce, fixture) in fixtures.iter().enumerate() {
+        let ix = verify_eligibility_ix(
+            registry.pubkey(),
+            nonce as u64,
+            CIRCUIT,
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+            CID,
+        );
+        let ix = if nonce == 0 { ix } else { with_force_new(ix) };
+        send(ctx, &[ix], &[]).await.unwrap();
+        records.push(verification_address(&patient, nonce as u64));
+    }
+    (registry, records)
+}
+
+/// `patient`'s records newest first, walked from their index along
+/// `previous_record` the way a client without the nonces would
+async fn walk(ctx: &mut ProgramTestContext, patient: &Pubkey) -> Vec<Pubkey> {
+    let index: PatientIndex = fetch(ctx, patient_index_address(patient)).await;
+    let mut records = Vec::new();
+    let mut at = Some(index.last_record).filter(|record| *record != Pubkey::default());
+    while let Some(address) = at {
+        if ctx.banks_client.get_account(address).await.unwrap().is_none() {
+            break;
+        }
+        let record: VerificationRecord = fetch(ctx, address).await;
+        records.push(address);
+        at = record.previous_record;
+    }
+    records
+}
+
+async fn revoke(ctx: &mut ProgramTestContext, registry: &Keypair, record: Pubkey) {
+    let patient = ctx.payer.pubkey();
+    let ix = revoke_verification_ix(patient, registry.pubkey(), record, patient, 1);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_walk_five_records_across_a_closure() {
+    let mut ctx = start().await;
+    let (registry, records) = setup(&mut ctx, 5).await;
+    let patient = ctx.payer.pubkey();
+
+    let newest_first: Vec<_> = records.iter().rev().copied().collect();
+    assert_eq!(walk(&mut ctx, &patient).await, newest_first);
+    let first: VerificationRecord = fetch(&mut ctx, records[0]).await;
+    assert_eq!(first.previous_record, None);
+
+    // Closing the middle record relinks the one after it past it
+    revoke(&mut ctx, &registry, records[2]).await;
+    let ix = close_records_bulk_ix(registry.pubkey(), &[(records[2], patient, Some(records[3]))]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(records[2]).await.unwrap().is_none());
+    assert_eq!(walk(&mut ctx, &patient).await, [records[4], records[3], records[1], records[0]]);
+
+    // and closing the newest moves the index back to the one before it
+    revoke(&mut ctx, &registry, records[4]).await;
+    let ix = close_records_bulk_ix(registry.pubkey(), &[(records[4], patient, None)]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert_eq!(walk(&mut ctx, &patient).await, [records[3], records[1], records[0]]);
+
+    // A record written afterwards joins at the front
+    let fixture = &batch_fixtures(6)[5];
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        5,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let newest = verification_address(&patient, 5);
+    assert_eq!(walk(&mut ctx, &patient).await, [newest, records[3], records[1], records[0]]);
+}
+
+#[tokio::test]
+async fn test_closing_a_linked_record_needs_its_successor() {
+    let mut ctx = start().await;
+    let (registry, records) = setup(&mut ctx, 3).await;
+    let patient = ctx.payer.pubkey();
+    revoke(&mut ctx, &registry, records[1]).await;
+
+    for next_record in [None, Some(records[0])] {
+        let ix = close_records_bulk_ix(registry.pubkey(), &[(records[1], patient, next_record)]);
+        assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordSuccessorMismatch);
+    }
+    assert_eq!(walk(&mut ctx, &patient).await, [records[2], records[1], records[0]]);
+
+    // The oldest record has nothing before it to lose
+    revoke(&mut ctx, &registry, records[0]).await;
+    let ix = close_records_bulk_ix(registry.pubkey(), &[(records[0], patient, Some(records[1]))]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let second: VerificationRecord = fetch(&mut ctx, records[1]).await;
+    assert_eq!(second.previous_record, None);
+    assert_eq!(walk(&mut ctx, &patient).await, [records[2], records[1]]);
+}
@@ -0,0 +1,136 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, RecordStatus, VerificationRecord, VerificationResult, VkConfig};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const PLAN_YEAR_SECS: i64 = 365 * 24 * 60 * 60;
+const CIRCUIT_VALIDITY_SECS: i64 = 30 * 24 * 60 * 60;
+
+async fn setup(ctx: &mut ProgramTestContext, validity_secs: Option<i64>) -> (Keypair, Vec<Fixture>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(2);
+    let config = VkConfig { validity_secs, ..VkConfig::default() };
+    upload_vk_with(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes, config).await;
+    (registry, fixtures)
+}
+
+async fn set_default_validity(ctx: &mut ProgramTestContext, registry: &Keypair, validity_secs: i64) {
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), validity_secs);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+/// Record `fixture`, expiring the record before it, and return the record's
+/// address alongside it
+async fn verify(ctx: &mut ProgramTestContext, registry: &Keypair, fixture: &Fixture) -> (Pubkey, VerificationRecord) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[with_force_new(ix)], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(ctx, verification).await;
+    assert_eq!(record.status, RecordStatus::Verified);
+    (verification, record)
+}
+
+/// Consume the record at `address` with `check_verification`
+async fn consume(ctx: &mut ProgramTestContext, registry: &Keypair, address: Pubkey) -> Result<(), BanksClientError> {
+    send(ctx, &[check_verification_ix(registry.pubkey(), address)], &[]).await
+}
+
+#[tokio::test]
+async fn test_record_consumed_around_its_expiry() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, None).await;
+    set_default_validity(&mut ctx, &registry, PLAN_YEAR_SECS).await;
+    let (address, record) = verify(&mut ctx, &registry, &fixtures[0]).await;
+    assert_eq!(record.expires_at, record.timestamp + PLAN_YEAR_SECS);
+    let patient = ctx.payer.pubkey();
+
+    // The last second of the plan year
+    warp_clock_to(&mut ctx, record.expires_at - 1).await;
+    let answer = simulate_return_data(&mut ctx, &[check_verification_ix(registry.pubkey(), address)], &[]).await;
+    let result = VerificationResult::try_from_slice(&answer.data).unwrap();
+    assert_eq!((result.record, result.verification_id), (address, record.verification_id));
+    consume(&mut ctx, &registry, address).await.unwrap();
+    let early = send(&mut ctx, &[mark_expired_ix(registry.pubkey(), address, patient)], &[]).await;
+    assert_error(early, HealthcareError::RecordNotExpired);
+
+    // The record stops counting at its deadline, before anyone marks it
+    warp_clock_to(&mut ctx, record.expires_at).await;
+    assert_error(consume(&mut ctx, &registry, address).await, HealthcareError::RecordExpired);
+    send(&mut ctx, &[mark_expired_ix(registry.pubkey(), address, patient)], &[]).await.unwrap();
+    let expired: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!((expired.status, expired.is_valid), (RecordStatus::Expired, false));
+    // Past the slot, so the repeated mark isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    assert_error(
+        send(&mut ctx, &[mark_expired_ix(registry.pubkey(), address, patient)], &[]).await,
+        HealthcareError::RecordExpired,
+    );
+}
+
+#[tokio::test]
+async fn test_circuit_validity_overrides_registry_default() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, Some(CIRCUIT_VALIDITY_SECS)).await;
+    let (_, record) = verify(&mut ctx, &registry, &fixtures[0]).await;
+    assert_eq!(record.expires_at, record.timestamp + CIRCUIT_VALIDITY_SECS);
+
+    set_default_validity(&mut ctx, &registry, PLAN_YEAR_SECS).await;
+    let (_, record) = verify(&mut ctx, &registry, &fixtures[1]).await;
+    assert_eq!(record.expires_at, record.timestamp + CIRCUIT_VALIDITY_SECS);
+}
+
+#[tokio::test]
+async fn test_record_without_validity_never_expires() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx, None).await;
+    let (address, record) = verify(&mut ctx, &registry, &fixtures[0]).await;
+    assert_eq!(record.expires_at, 0);
+    let patient = ctx.payer.pubkey();
+
+    warp_clock(&mut ctx, 10 * PLAN_YEAR_SECS).await;
+    consume(&mut ctx, &registry, address).await.unwrap();
+    assert_error(
+        send(&mut ctx, &[mark_expired_ix(registry.pubkey(), address, patient)], &[]).await,
+        HealthcareError::RecordNotExpired,
+    );
+
+    // Revoked records are refused however long they had left
+    let ix = revoke_verification_ix(patient, registry.pubkey(), address, patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Past the slot, so the repeated check isn't taken for the one that passed
+    warp_clock(&mut ctx, 0).await;
+    assert_error(consume(&mut ctx, &registry, address).await, HealthcareError::RecordRevoked);
+}
+
+#[tokio::test]
+async fn test_validity_periods_must_be_positive() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), -1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidValidityPeriod);
+
+    let vk_bytes = square_fixture(1).vk_bytes;
+    let config = VkConfig { n_public: n_public(&vk_bytes), validity_secs: Some(0), ..VkConfig::default() };
+    let vk_hash = anchor_lang::solana_program::keccak::hash(&vk_bytes).to_bytes();
+    let ix = register_vk_ix(ctx.payer.pubkey(), registry.pubkey(), CIRCUIT, vk_bytes.len() as u32, config, vk_hash);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidValidityPeriod);
+}
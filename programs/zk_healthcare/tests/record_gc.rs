@@ -0,0 +1,106 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use zk_healthcare::{HealthcareError, HealthcareRegistry, ProofFormat, VerificationRecord, RECORD_GC_GRACE_SECS};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+const BOUNTY: u64 = 100_000;
+
+/// A registry whose records expire after `VALIDITY_SECS`, and a record `patient`
+/// paid for
+async fn setup(ctx: &mut ProgramTestContext, patient: &Keypair) -> (Keypair, Pubkey, VerificationRecord) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), VALIDITY_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+
+    let nonce = next_record_nonce(ctx, patient.pubkey()).await;
+    let verification = verification_address(&patient.pubkey(), nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[ix], &[patient]).await.unwrap();
+    let record: VerificationRecord = fetch(ctx, verification).await;
+    assert_eq!(record.rent_payer, patient.pubkey());
+    (registry, verification, record)
+}
+
+/// Send `ix` paid for and signed by `payer` alone
+async fn send_as(ctx: &mut ProgramTestContext, payer: &Keypair, ix: Instruction) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+#[tokio::test]
+async fn test_close_before_grace_period_rejected() {
+    let mut ctx = start().await;
+    let patient = funded(&mut ctx).await;
+    let (registry, address, record) = setup(&mut ctx, &patient).await;
+    let cranker = ctx.payer.pubkey();
+    let ix = close_expired_verification_ix(registry.pubkey(), address, patient.pubkey(), cranker, None);
+
+    warp_clock_to(&mut ctx, record.expires_at - 1).await;
+    assert_error(send(&mut ctx, std::slice::from_ref(&ix), &[]).await, HealthcareError::RecordNotExpired);
+    warp_clock_to(&mut ctx, record.expires_at + RECORD_GC_GRACE_SECS - 1).await;
+    assert_error(send(&mut ctx, std::slice::from_ref(&ix), &[]).await, HealthcareError::GcGracePeriodActive);
+
+    // The rent only ever goes back to the payer
+    warp_clock_to(&mut ctx, record.expires_at + RECORD_GC_GRACE_SECS).await;
+    let payer = patient.pubkey();
+    let ix = close_expired_verification_refunding_ix(registry.pubkey(), address, payer, payer, cranker, cranker, None);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RentRefundMismatch);
+    assert!(ctx.banks_client.get_account(address).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_close_after_grace_period_pays_bounty() {
+    let mut ctx = start().await;
+    let patient = funded(&mut ctx).await;
+    let (registry, address, record) = setup(&mut ctx, &patient).await;
+    let ix = set_gc_bounty_ix(ctx.payer.pubkey(), registry.pubkey(), BOUNTY);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    warp_clock_to(&mut ctx, record.expires_at + RECORD_GC_GRACE_SECS).await;
+    let cranker = funded(&mut ctx).await;
+    let rent = balance(&mut ctx, address).await;
+    let payer_before = balance(&mut ctx, patient.pubkey()).await;
+    let cranker_before = balance(&mut ctx, cranker.pubkey()).await;
+    let ix = close_expired_verification_ix(registry.pubkey(), address, patient.pubkey(), cranker.pubkey(), None);
+    send_as(&mut ctx, &cranker, ix).await.unwrap();
+
+    assert!(ctx.banks_client.get_account(address).await.unwrap().is_none());
+    assert_eq!(balance(&mut ctx, patient.pubkey()).await, payer_before + rent - BOUNTY);
+    assert_eq!(balance(&mut ctx, cranker.pubkey()).await, cranker_before + BOUNTY - 5000);
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!((state.total_verifications, state.closed_verifications), (1, 1));
+}
+
+#[tokio::test]
+async fn test_bounty_capped_at_record_rent() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let rent = Rent::default().minimum_balance(VerificationRecord::SPACE);
+    let ix = set_gc_bounty_ix(ctx.payer.pubkey(), registry.pubkey(), rent + 1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::GcBountyTooHigh);
+    let ix = set_gc_bounty_ix(ctx.payer.pubkey(), registry.pubkey(), rent);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
@@ -0,0 +1,103 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry and the payer's record verified under it
+async fn verified(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, verification_address(&patient, 0))
+}
+
+#[tokio::test]
+async fn test_metadata_grows_the_record() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+
+    let ix = update_metadata_ix(registry.pubkey(), verification, patient, vec![7; 300]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let account = ctx.banks_client.get_account(verification).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), VerificationRecord::SPACE + 300);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(VerificationRecord::SPACE + 300));
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.metadata, vec![7; 300]);
+    assert!(record.is_verified());
+}
+
+#[tokio::test]
+async fn test_smaller_metadata_refunds_rent() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let ix = update_metadata_ix(registry.pubkey(), verification, patient, vec![7; 400]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let before = balance(&mut ctx, patient).await;
+    let ix = update_metadata_ix(registry.pubkey(), verification, patient, vec![9; 10]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let rent = Rent::default();
+    let (large, small) = (VerificationRecord::SPACE + 400, VerificationRecord::SPACE + 10);
+    let refund = rent.minimum_balance(large) - rent.minimum_balance(small);
+    // Less the transaction's one signature fee
+    assert_eq!(balance(&mut ctx, patient).await, before + refund - 5_000);
+    let account = ctx.banks_client.get_account(verification).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), small);
+    assert_eq!(account.lamports, rent.minimum_balance(small));
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.metadata, vec![9; 10]);
+}
+
+#[tokio::test]
+async fn test_metadata_over_the_registry_limit_is_rejected() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let ix = update_metadata_ix(registry.pubkey(), verification, patient, vec![7; 513]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::MetadataTooLong);
+
+    let ix = set_max_metadata_len_ix(patient, registry.pubkey(), 16);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = update_metadata_ix(registry.pubkey(), verification, patient, vec![7; 17]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::MetadataTooLong);
+    let ix = update_metadata_ix(registry.pubkey(), verification, patient, vec![7; 16]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = set_max_metadata_len_ix(patient, registry.pubkey(), 10 * 1024 + 1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::MetadataLimitTooHigh);
+}
+
+#[tokio::test]
+async fn test_revoked_record_metadata_is_frozen() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let ix = revoke_verification_ix(patient, registry.pubkey(), verification, patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let ix = update_metadata_ix(registry.pubkey(), verification, patient, vec![7; 8]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordRevoked);
+}
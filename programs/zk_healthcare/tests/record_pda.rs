@@ -0,0 +1,76 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction::SystemError;
+use zk_healthcare::{
+    derive_verification_pda, HealthcareError, PatientIndex, ProofFormat, VerificationRecord, VerificationType,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    registry
+}
+
+/// Verify `fixture` as the payer into its record at `record_nonce`, expiring
+/// the record before it
+async fn verify(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    record_nonce: u64,
+    fixture: &Fixture,
+) -> Result<(), BanksClientError> {
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        record_nonce,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[with_force_new(ix)], &[]).await
+}
+
+#[tokio::test]
+async fn test_records_land_at_the_patients_nonces() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let registry = setup(&mut ctx, &fixtures[0]).await;
+    let patient = ctx.payer.pubkey();
+
+    for (nonce, fixture) in (0..).zip(&fixtures) {
+        verify(&mut ctx, &registry, nonce, fixture).await.unwrap();
+        let (address, bump) = derive_verification_pda(&patient, VerificationType::Eligibility, nonce);
+        let record: VerificationRecord = fetch(&mut ctx, address).await;
+        assert_eq!((record.patient_pubkey, record.bump), (patient, bump));
+        assert_eq!(record.proof_hash, verification_hash(fixture));
+    }
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient)).await;
+    assert_eq!(index.next_record_nonce, 2);
+}
+
+#[tokio::test]
+async fn test_reused_nonce_fails_at_init() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let registry = setup(&mut ctx, &fixtures[0]).await;
+    verify(&mut ctx, &registry, 0, &fixtures[0]).await.unwrap();
+
+    // The record at nonce 0 exists, so creating it again fails before the handler runs
+    let err = verify(&mut ctx, &registry, 0, &fixtures[1]).await.unwrap_err();
+    assert_eq!(error_code(err), SystemError::AccountAlreadyInUse as u32);
+
+    // A nonce past the patient's next one is refused too, so records stay consecutive
+    let result = verify(&mut ctx, &registry, 2, &fixtures[1]).await;
+    assert_error(result, HealthcareError::RecordNonceMismatch);
+    verify(&mut ctx, &registry, 1, &fixtures[1]).await.unwrap();
+}
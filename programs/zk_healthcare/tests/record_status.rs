@@ -0,0 +1,80 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// A record's status through its lifecycle, as `RecordStatusChanged` reports it.
+// Captures events by swapping the process-wide syscall stubs, so this binary
+// holds a single test.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use zk_healthcare::{HealthcareError, ProofFormat, RecordStatus, RecordStatusChanged, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+
+/// Send `ix` and return the `(old, new)` status of each `RecordStatusChanged`
+/// for `record`
+async fn transitions(
+    ctx: &mut ProgramTestContext,
+    ix: Instruction,
+    record: Pubkey,
+) -> Vec<(RecordStatus, RecordStatus)> {
+    let (result, logs) = send_logged(ctx, &[ix], &[]).await;
+    result.unwrap();
+    events::<RecordStatusChanged>(&logs)
+        .into_iter()
+        .inspect(|event| assert_eq!(event.record, record))
+        .map(|event| (event.old_status, event.new_status))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_record_lifecycle_events() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), VALIDITY_SECS);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    // Written pending, then verified in the same instruction
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let address = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let changes = transitions(&mut ctx, ix, address).await;
+    assert_eq!(changes, [(RecordStatus::Pending, RecordStatus::Verified)]);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert!(record.is_verified() && record.is_valid);
+
+    warp_clock_to(&mut ctx, record.expires_at).await;
+    let changes = transitions(&mut ctx, mark_expired_ix(registry.pubkey(), address, patient), address).await;
+    assert_eq!(changes, [(RecordStatus::Verified, RecordStatus::Expired)]);
+
+    // An expired record may still be revoked, which is final
+    let ix = revoke_verification_ix(patient, registry.pubkey(), address, patient, 1);
+    let changes = transitions(&mut ctx, ix, address).await;
+    assert_eq!(changes, [(RecordStatus::Expired, RecordStatus::Revoked)]);
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!((record.status, record.is_valid), (RecordStatus::Revoked, false));
+    // Past the slot, so the repeated mark isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    assert_error(
+        send(&mut ctx, &[mark_expired_ix(registry.pubkey(), address, patient)], &[]).await,
+        HealthcareError::InvalidStatusTransition,
+    );
+}
@@ -0,0 +1,151 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{HealthcareError, MigrationTicket, ProofFormat, RecordStatus, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const TICKET_SECS: i64 = 60 * 60;
+
+/// A source registry with the payer's first record under it, and a destination
+/// registry under its own authority
+struct Merger {
+    source: Keypair,
+    destination: Keypair,
+    destination_authority: Keypair,
+    record: Pubkey,
+}
+
+async fn setup(ctx: &mut ProgramTestContext) -> Merger {
+    let source = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(ctx, source.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = verify_eligibility_ix(
+        source.pubkey(),
+        0,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        fixture.proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        CID,
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+
+    let (destination, destination_authority) = (Keypair::new(), Keypair::new());
+    let fund = system_instruction::transfer(&ctx.payer.pubkey(), &destination_authority.pubkey(), 1_000_000_000);
+    let ix = initialize_ix(destination_authority.pubkey(), destination.pubkey(), true);
+    send(ctx, &[fund, ix], &[&destination_authority, &destination]).await.unwrap();
+    let record = verification_address(&ctx.payer.pubkey(), 0);
+    Merger { source, destination, destination_authority, record }
+}
+
+impl Merger {
+    /// A ticket for the record until `TICKET_SECS` after it was verified; its expiry
+    async fn export(&self, ctx: &mut ProgramTestContext) -> i64 {
+        let record: VerificationRecord = fetch(ctx, self.record).await;
+        let expires_at = record.timestamp + TICKET_SECS;
+        let (source, destination) = (self.source.pubkey(), self.destination.pubkey());
+        let ix = export_record_ix(ctx.payer.pubkey(), source, self.record, destination, expires_at);
+        send(ctx, &[ix], &[]).await.unwrap();
+        expires_at
+    }
+
+    async fn import(
+        &self,
+        ctx: &mut ProgramTestContext,
+        record_nonce: u64,
+    ) -> Result<(), solana_program_test::BanksClientError> {
+        let patient = ctx.payer.pubkey();
+        let ix = import_record_ix(
+            self.destination_authority.pubkey(),
+            self.destination.pubkey(),
+            patient,
+            self.source.pubkey(),
+            self.record,
+            patient,
+            record_nonce,
+        );
+        send(ctx, &[ix], &[&self.destination_authority]).await
+    }
+}
+
+#[tokio::test]
+async fn test_record_moves_to_destination_once() {
+    let mut ctx = start().await;
+    let merger = setup(&mut ctx).await;
+    merger.export(&mut ctx).await;
+    let ticket: MigrationTicket = fetch(&mut ctx, MigrationTicket::address(&merger.record)).await;
+    assert_eq!((ticket.source_registry, ticket.destination), (merger.source.pubkey(), merger.destination.pubkey()));
+    assert_eq!(ticket.imported_record, None);
+
+    merger.import(&mut ctx, 1).await.unwrap();
+    let imported_at = verification_address(&ctx.payer.pubkey(), 1);
+    let original: VerificationRecord = fetch(&mut ctx, merger.record).await;
+    let imported: VerificationRecord = fetch(&mut ctx, imported_at).await;
+    assert_eq!(original.status, RecordStatus::Migrated);
+    assert!(imported.is_verified());
+    assert!(imported.is_in_registry(&merger.destination.pubkey()));
+    assert!(!imported.is_in_registry(&merger.source.pubkey()));
+    assert_eq!((imported.migrated_from, imported.previous_record), (merger.record, Some(merger.record)));
+    assert_eq!((imported.proof_hash, imported.expires_at), (original.proof_hash, original.expires_at));
+    assert_eq!(imported.rent_payer, merger.destination_authority.pubkey());
+    let ticket: MigrationTicket = fetch(&mut ctx, MigrationTicket::address(&merger.record)).await;
+    assert_eq!(ticket.imported_record, Some(imported_at));
+
+    // The ticket is spent, and the original can't be exported again
+    assert_error(merger.import(&mut ctx, 2).await, HealthcareError::RecordAlreadyMigrated);
+    let (source, destination) = (merger.source.pubkey(), merger.destination.pubkey());
+    let ix = export_record_ix(ctx.payer.pubkey(), source, merger.record, destination, original.timestamp + TICKET_SECS);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordAlreadyMigrated);
+}
+
+#[tokio::test]
+async fn test_expired_ticket_is_not_imported() {
+    let mut ctx = start().await;
+    let merger = setup(&mut ctx).await;
+    let expires_at = merger.export(&mut ctx).await;
+
+    warp_clock_to(&mut ctx, expires_at).await;
+    assert_error(merger.import(&mut ctx, 1).await, HealthcareError::MigrationTicketExpired);
+    let original: VerificationRecord = fetch(&mut ctx, merger.record).await;
+    assert!(original.is_verified());
+
+    // A fresh ticket lets it through
+    let (source, destination) = (merger.source.pubkey(), merger.destination.pubkey());
+    let ix = export_record_ix(ctx.payer.pubkey(), source, merger.record, destination, expires_at + TICKET_SECS);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    warp_clock(&mut ctx, 0).await;
+    merger.import(&mut ctx, 1).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_import_needs_the_record_as_exported_and_both_authorities() {
+    let mut ctx = start().await;
+    let merger = setup(&mut ctx).await;
+    let expires_at = merger.export(&mut ctx).await;
+    let (source, destination) = (merger.source.pubkey(), merger.destination.pubkey());
+
+    // A ticket still running can't be replaced
+    warp_clock(&mut ctx, 0).await;
+    let ix = export_record_ix(ctx.payer.pubkey(), source, merger.record, destination, expires_at + 1);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::MigrationTicketOutstanding);
+
+    // The destination can't release the record on its own
+    let patient = ctx.payer.pubkey();
+    let authority = merger.destination_authority.pubkey();
+    let ix = import_record_ix(authority, destination, authority, source, merger.record, patient, 1);
+    let result = send(&mut ctx, &[ix], &[&merger.destination_authority]).await;
+    assert_error(result, HealthcareError::NotSourceAuthority);
+
+    // and the record must be as it was exported
+    let ix = update_metadata_ix(source, merger.record, patient, vec![1; 8]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert_error(merger.import(&mut ctx, 1).await, HealthcareError::RecordSnapshotMismatch);
+}
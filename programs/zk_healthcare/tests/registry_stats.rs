@@ -0,0 +1,85 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::AccountSerialize;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, HealthcareRegistry, ProofFormat, VerificationType};
+
+const CIRCUIT: &str = "eligibility_v1";
+const ACCESS_CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// Verify `fixture` as the payer, expiring their record before it
+async fn verify(ctx: &mut ProgramTestContext, registry: &Keypair, fixture: &Fixture) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[with_force_new(ix)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_counters_follow_the_records() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(2);
+    let access = square_fixture(2);
+    let patient = ctx.payer.pubkey();
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    upload_vk(&mut ctx, registry.pubkey(), ACCESS_CIRCUIT, &access.vk_bytes).await;
+    // The second upload took over `Eligibility`, so hand it back
+    for (circuit_id, verification_type) in
+        [(ACCESS_CIRCUIT, VerificationType::AccessControl), (CIRCUIT, VerificationType::Eligibility)]
+    {
+        let ix = set_type_circuit_ix(patient, registry.pubkey(), circuit_id, verification_type);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+
+    // The second eligibility verification expires the first
+    verify(&mut ctx, &registry, &fixtures[0]).await;
+    verify(&mut ctx, &registry, &fixtures[1]).await;
+    let resource = Keypair::new().pubkey();
+    let ix = verify_access_control_ix(registry.pubkey(), ACCESS_CIRCUIT, patient, &access, resource, 100, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = revoke_verification_ix(patient, registry.pubkey(), verification_address(&patient, 1), patient, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    // A proof checked against another proof's inputs fails
+    let ix = record_failed_proof_ix(registry.pubkey(), CIRCUIT, &fixtures[1].proof, &fixtures[0].public_inputs);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = record_failed_proof_ix(registry.pubkey(), CIRCUIT, &fixtures[1].proof, &fixtures[1].public_inputs);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::ProofVerified);
+
+    let account: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(account.total_verifications, 3);
+    assert_eq!(account.verifications_by_type, [2, 0, 0, 1, 0, 0]);
+    assert_eq!((account.revoked_count, account.expired_count, account.failed_proof_count), (1, 1, 1));
+}
+
+#[tokio::test]
+async fn test_counter_overflow_is_refused() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+
+    let mut stats: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    stats.failed_proof_count = u64::MAX;
+    let mut account = ctx.banks_client.get_account(registry.pubkey()).await.unwrap().unwrap();
+    stats.try_serialize(&mut &mut account.data[..]).unwrap();
+    ctx.set_account(&registry.pubkey(), &account.into());
+
+    let ix = record_failed_proof_ix(registry.pubkey(), CIRCUIT, &fixtures[1].proof, &fixtures[0].public_inputs);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CounterOverflow);
+}
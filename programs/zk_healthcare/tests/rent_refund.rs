@@ -0,0 +1,185 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    GuardianScope, HealthcareError, IpfsPinRecord, PinContentClaim, ProofFormat, VerificationRecord,
+    RECORD_GC_GRACE_SECS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+
+/// A registry whose records expire after `VALIDITY_SECS`
+async fn setup(ctx: &mut ProgramTestContext) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &square_fixture(1).vk_bytes).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), VALIDITY_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+    registry
+}
+
+/// A record and a pin `patient` paid for
+async fn verify_and_pin(ctx: &mut ProgramTestContext, registry: &Keypair, patient: &Keypair) -> (Pubkey, Pubkey) {
+    let fixture = square_fixture(1);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        patient.pubkey(),
+        fixture.proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        CID,
+    );
+    send(ctx, &[ix], &[patient]).await.unwrap();
+    let ix = pin_medical_data_ix(registry.pubkey(), patient.pubkey(), CID, [3; 32]);
+    send(ctx, &[ix], &[patient]).await.unwrap();
+    (verification_address(&patient.pubkey(), 0), IpfsPinRecord::address(&patient.pubkey(), &[3; 32]))
+}
+
+/// Past the grace period of `record`
+async fn warp_past_grace(ctx: &mut ProgramTestContext, record: Pubkey) {
+    let record: VerificationRecord = fetch(ctx, record).await;
+    warp_clock_to(ctx, record.expires_at + RECORD_GC_GRACE_SECS).await;
+}
+
+#[tokio::test]
+async fn test_clinic_funded_record_closes_back_to_clinic() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let (clinic, ward) = (funded(&mut ctx).await, Keypair::new());
+    let (authority, scopes) = (ctx.payer.pubkey(), GuardianScope::Verify.bit() | GuardianScope::Pin.bit());
+    let ix = grant_guardian_consent_ix(registry.pubkey(), authority, clinic.pubkey(), ward.pubkey(), scopes, 0, false);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = verify_eligibility_for_ward_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        clinic.pubkey(),
+        ward.pubkey(),
+        &square_fixture(1),
+        CID,
+    );
+    send(&mut ctx, &[ix], &[&clinic]).await.unwrap();
+    let ix = pin_medical_data_for_ward_ix(registry.pubkey(), clinic.pubkey(), ward.pubkey(), CID, [5; 32]);
+    send(&mut ctx, &[ix], &[&clinic]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&ward.pubkey(), &[5; 32]);
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.patient, pin.rent_payer, pin.registry), (ward.pubkey(), clinic.pubkey(), registry.pubkey()));
+
+    // The ward unpins, and the clinic gets the rent back, the content
+    // claim's with the pin's
+    let content_claim = PinContentClaim::address(&ward.pubkey(), &[5; 32]);
+    let rent = balance(&mut ctx, pin_record).await + balance(&mut ctx, content_claim).await;
+    let before = balance(&mut ctx, clinic.pubkey()).await;
+    let payer = clinic.pubkey();
+    let ix = unpin_medical_data_ix(registry.pubkey(), pin_record, [5; 32], ward.pubkey(), ward.pubkey(), payer, payer);
+    send(&mut ctx, &[ix], &[&ward]).await.unwrap();
+    assert!(ctx.banks_client.get_account(pin_record).await.unwrap().is_none());
+    assert_eq!(balance(&mut ctx, clinic.pubkey()).await, before + rent);
+
+    // as it does when the ward's record is closed
+    let record = verification_address(&ward.pubkey(), 0);
+    warp_past_grace(&mut ctx, record).await;
+    let rent = balance(&mut ctx, record).await;
+    let before = balance(&mut ctx, clinic.pubkey()).await;
+    let ix = close_expired_verification_refunding_ix(
+        registry.pubkey(),
+        record,
+        ward.pubkey(),
+        clinic.pubkey(),
+        clinic.pubkey(),
+        authority,
+        None,
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert_eq!(balance(&mut ctx, clinic.pubkey()).await, before + rent);
+}
+
+#[tokio::test]
+async fn test_rotated_payer_receives_at_new_key() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let patient = funded(&mut ctx).await;
+    let (record, pin_record) = verify_and_pin(&mut ctx, &registry, &patient).await;
+    let new_key = Keypair::new();
+    let (old_key, authority) = (patient.pubkey(), ctx.payer.pubkey());
+    let ix = rotate_patient_key_ix(registry.pubkey(), old_key, new_key.pubkey());
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+
+    // Once the payer rotated, the rent no longer goes to the key it left
+    let ix = revoke_verification_ix(authority, registry.pubkey(), record, old_key, 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = close_records_bulk_ix(registry.pubkey(), &[(record, old_key, None)]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RentRefundMismatch);
+    let rent = balance(&mut ctx, record).await;
+    let ix = close_records_bulk_refunding_ix(registry.pubkey(), &[(record, old_key, old_key, new_key.pubkey(), None)]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert_eq!(balance(&mut ctx, new_key.pubkey()).await, rent);
+
+    // nor does a pin's, once the new key has claimed it
+    let ix = claim_pin_record_ix(old_key, pin_record, [3; 32], new_key.pubkey());
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+    let (registry, new_key_pubkey) = (registry.pubkey(), new_key.pubkey());
+    let unpin = |refund_to| {
+        unpin_medical_data_ix(registry, pin_record, [3; 32], new_key_pubkey, new_key_pubkey, old_key, refund_to)
+    };
+    assert_error(send(&mut ctx, &[unpin(old_key)], &[&new_key]).await, HealthcareError::RentRefundMismatch);
+    let (pin_rent, before) = (balance(&mut ctx, pin_record).await, balance(&mut ctx, new_key_pubkey).await);
+    let claim_rent = balance(&mut ctx, PinContentClaim::address(&new_key_pubkey, &[3; 32])).await;
+    send(&mut ctx, &[unpin(new_key_pubkey)], &[&new_key]).await.unwrap();
+    assert_eq!(balance(&mut ctx, new_key.pubkey()).await, before + pin_rent + claim_rent);
+}
+
+#[tokio::test]
+async fn test_closer_never_receives_the_principal() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx).await;
+    let patient = funded(&mut ctx).await;
+    let (record, pin_record) = verify_and_pin(&mut ctx, &registry, &patient).await;
+    let cranker = funded(&mut ctx).await;
+    warp_past_grace(&mut ctx, record).await;
+    let (patient_key, cranker_key) = (patient.pubkey(), cranker.pubkey());
+
+    let ix = close_expired_verification_refunding_ix(
+        registry.pubkey(),
+        record,
+        patient_key,
+        patient_key,
+        cranker_key,
+        cranker_key,
+        None,
+    );
+    assert_error(send(&mut ctx, &[ix], &[&cranker]).await, HealthcareError::RentRefundMismatch);
+    let to_cranker = (record, patient_key, patient_key, cranker_key, None);
+    let ix = close_records_bulk_refunding_ix(registry.pubkey(), &[to_cranker]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RentRefundMismatch);
+    // Naming the cranker as the payer points at the cranker's rotation, not the payer's
+    let as_payer = (record, patient_key, cranker_key, cranker_key, None);
+    let ix = close_records_bulk_refunding_ix(registry.pubkey(), &[as_payer]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::BulkCloseAccountMismatch);
+
+    let (rent, pin_rent) = (balance(&mut ctx, record).await, balance(&mut ctx, pin_record).await);
+    let claim_rent = balance(&mut ctx, PinContentClaim::address(&patient_key, &[3; 32])).await;
+    let (patient_before, cranker_before) = (balance(&mut ctx, patient_key).await, balance(&mut ctx, cranker_key).await);
+    let ix = close_expired_verification_ix(registry.pubkey(), record, patient_key, cranker_key, None);
+    send(&mut ctx, &[ix], &[&cranker]).await.unwrap();
+    let ix = unpin_medical_data_ix(
+        registry.pubkey(),
+        pin_record,
+        [3; 32],
+        patient_key,
+        patient_key,
+        patient_key,
+        patient_key,
+    );
+    send(&mut ctx, &[ix], &[&patient]).await.unwrap();
+    assert_eq!(balance(&mut ctx, patient_key).await, patient_before + rent + pin_rent + claim_rent);
+    assert_eq!(balance(&mut ctx, cranker_key).await, cranker_before);
+}
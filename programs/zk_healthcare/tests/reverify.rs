@@ -0,0 +1,125 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::{
+    HealthcareError, PatientIndex, ProofFormat, RecordStatus, VerificationRecord, RENEWAL_WINDOW_SECS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const RENEWED_CID: &str = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+const MONTH_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// A registry whose records last a month, and fixtures to prove with
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Vec<Fixture>) {
+    let registry = initialize_registry(ctx).await;
+    let fixtures = batch_fixtures(3);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), MONTH_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, fixtures)
+}
+
+/// Record `fixture` for `patient`, who signs, and return the record's address
+async fn verify(ctx: &mut ProgramTestContext, registry: &Keypair, patient: &Keypair, fixture: &Fixture) -> Pubkey {
+    let nonce = next_record_nonce(ctx, patient.pubkey()).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[ix], &[patient]).await.unwrap();
+    verification_address(&patient.pubkey(), nonce)
+}
+
+fn payer(ctx: &ProgramTestContext) -> Keypair {
+    Keypair::from_bytes(&ctx.payer.to_bytes()).unwrap()
+}
+
+#[tokio::test]
+async fn test_expired_record_is_renewed_in_place() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx).await;
+    let patient = payer(&ctx);
+    let address = verify(&mut ctx, &registry, &patient, &fixtures[0]).await;
+    let original: VerificationRecord = fetch(&mut ctx, address).await;
+
+    // Too early: a week and a second before expiry
+    warp_clock_to(&mut ctx, original.expires_at - RENEWAL_WINDOW_SECS - 1).await;
+    let ix = reverify_eligibility_ix(registry.pubkey(), address, CIRCUIT, patient.pubkey(), &fixtures[1], RENEWED_CID);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordNotRenewable);
+
+    warp_clock_to(&mut ctx, original.expires_at).await;
+    send(&mut ctx, &[mark_expired_ix(registry.pubkey(), address, patient.pubkey())], &[]).await.unwrap();
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient.pubkey())).await;
+    assert_eq!(index.active_count, 0);
+
+    let ix = reverify_eligibility_ix(registry.pubkey(), address, CIRCUIT, patient.pubkey(), &fixtures[1], RENEWED_CID);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let renewed: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!((renewed.status, renewed.is_valid), (RecordStatus::Verified, true));
+    assert_eq!(renewed.revision, 1);
+    assert_eq!(renewed.previous_proof_hash, original.proof_hash);
+    assert_eq!(renewed.proof_hash, verification_hash(&fixtures[1]));
+    assert_eq!(renewed.ipfs_hash, RENEWED_CID);
+    assert_eq!(renewed.expires_at, renewed.timestamp + MONTH_SECS);
+    assert!(renewed.is_in_registry(&registry.pubkey()));
+    send(&mut ctx, &[check_verification_ix(registry.pubkey(), address)], &[]).await.unwrap();
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient.pubkey())).await;
+    assert_eq!((index.verification_count, index.active_count), (1, 1));
+    assert_eq!(index.next_record_nonce, 1);
+
+    // Near its new expiry it renews again, though not with a spent proof
+    warp_clock_to(&mut ctx, renewed.expires_at - 1).await;
+    let ix = reverify_eligibility_ix(registry.pubkey(), address, CIRCUIT, patient.pubkey(), &fixtures[1], RENEWED_CID);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::ProofAlreadyUsed);
+    let ix = reverify_eligibility_ix(registry.pubkey(), address, CIRCUIT, patient.pubkey(), &fixtures[2], CID);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let again: VerificationRecord = fetch(&mut ctx, address).await;
+    assert_eq!((again.revision, again.previous_proof_hash), (2, renewed.proof_hash));
+    let index: PatientIndex = fetch(&mut ctx, patient_index_address(&patient.pubkey())).await;
+    assert_eq!(index.active_count, 1);
+}
+
+#[tokio::test]
+async fn test_someone_elses_record_is_refused() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx).await;
+    let patient = payer(&ctx);
+    let address = verify(&mut ctx, &registry, &patient, &fixtures[0]).await;
+    let other = Keypair::new();
+    let ix = system_instruction::transfer(&patient.pubkey(), &other.pubkey(), 1_000_000_000);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    verify(&mut ctx, &registry, &other, &fixtures[1]).await;
+
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    warp_clock_to(&mut ctx, record.expires_at).await;
+    let ix = reverify_eligibility_ix(registry.pubkey(), address, CIRCUIT, other.pubkey(), &fixtures[2], CID);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::NotRecordPatient);
+}
+
+#[tokio::test]
+async fn test_revoked_record_is_not_renewed() {
+    let mut ctx = start().await;
+    let (registry, fixtures) = setup(&mut ctx).await;
+    let patient = payer(&ctx);
+    let address = verify(&mut ctx, &registry, &patient, &fixtures[0]).await;
+    let ix = revoke_verification_ix(patient.pubkey(), registry.pubkey(), address, patient.pubkey(), 1);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, address).await;
+    warp_clock_to(&mut ctx, record.expires_at).await;
+    let ix = reverify_eligibility_ix(registry.pubkey(), address, CIRCUIT, patient.pubkey(), &fixtures[1], RENEWED_CID);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordRevoked);
+}
@@ -0,0 +1,101 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Revoking verification records, as the audit trail records it. Captures events
+// by swapping the process-wide syscall stubs, so this binary holds a single test.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, VerificationRecord, VerificationRevoked};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const COVERAGE_LOST: u16 = 1;
+const FALSIFIED_DATA: u16 = 2;
+
+/// The record of `patient` proving `fixture`, which expires the record before it
+async fn verify(ctx: &mut ProgramTestContext, registry: &Keypair, patient: &Keypair, fixture: &Fixture) -> Pubkey {
+    let nonce = next_record_nonce(ctx, patient.pubkey()).await;
+    let verification = verification_address(&patient.pubkey(), nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[with_force_new(ix)], &[patient]).await.unwrap();
+    verification
+}
+
+/// Send `ix` signed by `revoker` and return the `VerificationRevoked` events
+async fn revoke(ctx: &mut ProgramTestContext, ix: Instruction, revoker: &Keypair) -> Vec<VerificationRevoked> {
+    let (result, logs) = send_logged(ctx, &[ix], &[revoker]).await;
+    result.unwrap();
+    events(&logs)
+}
+
+#[tokio::test]
+async fn test_revocation_by_authority_and_patient() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = funded(&mut ctx).await;
+    let records = [
+        verify(&mut ctx, &registry, &patient, &fixtures[0]).await,
+        verify(&mut ctx, &registry, &patient, &fixtures[1]).await,
+    ];
+
+    // Neither a third party nor another registry's authority may revoke
+    let intruder = funded(&mut ctx).await;
+    let ix = revoke_verification_ix(intruder.pubkey(), registry.pubkey(), records[0], patient.pubkey(), COVERAGE_LOST);
+    assert_error(send(&mut ctx, &[ix], &[&intruder]).await, HealthcareError::NotAuthorizedToRevoke);
+    let other_registry = initialize_registry(&mut ctx).await;
+    let ix = revoke_verification_ix(
+        ctx.payer.pubkey(),
+        other_registry.pubkey(),
+        records[0],
+        patient.pubkey(),
+        COVERAGE_LOST,
+    );
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::RecordRegistryMismatch);
+
+    // The patient withdraws one record themselves
+    let ix = revoke_verification_ix(patient.pubkey(), registry.pubkey(), records[0], patient.pubkey(), COVERAGE_LOST);
+    let events = revoke(&mut ctx, ix, &patient).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        (events[0].record, events[0].patient, events[0].reason_code, events[0].revoked_by),
+        (records[0], patient.pubkey(), COVERAGE_LOST, patient.pubkey())
+    );
+    let record: VerificationRecord = fetch(&mut ctx, records[0]).await;
+    assert!(!record.is_valid);
+    assert!(record.revoked_at > 0);
+    assert_eq!(record.revoked_by, patient.pubkey());
+
+    // The authority revokes the other, which can't then be revoked again
+    let authority = Keypair::from_bytes(&ctx.payer.to_bytes()).unwrap();
+    let ix = revoke_verification_ix(
+        authority.pubkey(),
+        registry.pubkey(),
+        records[1],
+        patient.pubkey(),
+        FALSIFIED_DATA,
+    );
+    let events = revoke(&mut ctx, ix.clone(), &authority).await;
+    assert_eq!((events[0].reason_code, events[0].revoked_by), (FALSIFIED_DATA, authority.pubkey()));
+    let record: VerificationRecord = fetch(&mut ctx, records[1]).await;
+    assert_eq!((record.is_valid, record.revoked_by), (false, authority.pubkey()));
+
+    // Past the slot, so the repeated revocation isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::AlreadyRevoked);
+}
@@ -0,0 +1,163 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    CurveId, HashAlgo, HealthcareError, HealthcareRegistry, ProofFormat, ProvingScheme, SecurityPolicy,
+    VerificationRecord, VkConfig,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const WINDOW_SECS: i64 = 600;
+
+/// Groth16 over BN254 with keccak records, for proofs at most `WINDOW_SECS` old
+fn compliant_policy() -> SecurityPolicy {
+    SecurityPolicy {
+        allowed_schemes: ProvingScheme::Groth16.bit(),
+        allowed_curves: CurveId::Bn254.bit(),
+        allowed_hash_algos: HashAlgo::Keccak.bit(),
+        max_freshness_window_secs: WINDOW_SECS,
+    }
+}
+
+async fn set_policy(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    policy: SecurityPolicy,
+) -> Result<(), BanksClientError> {
+    let ix = set_security_policy_ix(ctx.payer.pubkey(), registry.pubkey(), policy);
+    send(ctx, &[ix], &[]).await
+}
+
+async fn register(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+    config: VkConfig,
+) -> Result<(), BanksClientError> {
+    let vk_bytes = &fixture.vk_bytes;
+    let config = VkConfig { n_public: n_public(vk_bytes), ..config };
+    let (total_len, vk_hash) = (vk_bytes.len() as u32, keccak::hash(vk_bytes).to_bytes());
+    let ix = register_vk_ix(ctx.payer.pubkey(), registry.pubkey(), CIRCUIT, total_len, config, vk_hash);
+    send(ctx, &[ix], &[]).await
+}
+
+/// Submit as the payer, expiring their record before this one
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+    hash_algo: HashAlgo,
+) -> (Pubkey, Result<(), BanksClientError>) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let result = send(ctx, &[with_force_new(with_hash_algo(ix, hash_algo))], &[]).await;
+    (verification, result)
+}
+
+#[tokio::test]
+async fn test_disallowed_curve_rejected_at_registration() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let bls_only = SecurityPolicy {
+        allowed_curves: CurveId::Bls12_381.bit(),
+        ..SecurityPolicy::default_for(true)
+    };
+    set_policy(&mut ctx, &registry, bls_only).await.unwrap();
+
+    let fixture = square_fixture(1);
+    let result = register(&mut ctx, &registry, &fixture, VkConfig::default()).await;
+    assert_error(result, HealthcareError::CircuitNotAllowedByPolicy);
+
+    // Only the authority sets the policy, and it must allow something
+    let intruder = Keypair::new();
+    let ix = set_security_policy_ix(intruder.pubkey(), registry.pubkey(), SecurityPolicy::default_for(true));
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+    let empty = SecurityPolicy { allowed_schemes: 0, ..compliant_policy() };
+    assert_error(set_policy(&mut ctx, &registry, empty).await, HealthcareError::InvalidSecurityPolicy);
+
+    set_policy(&mut ctx, &registry, SecurityPolicy::default_for(true)).await.unwrap();
+    // Past the slot, so the retried registration isn't taken for the refused one
+    warp_clock(&mut ctx, 0).await;
+    register(&mut ctx, &registry, &fixture, VkConfig::default()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tightened_policy_stops_existing_circuit() {
+    let mut ctx = start().await;
+    let registry = initialize_registry_with(&mut ctx, false).await;
+    let fixture = batch_fixtures(3);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture[0].vk_bytes).await;
+    submit(&mut ctx, &registry, &fixture[0], HashAlgo::Poseidon).await.1.unwrap();
+
+    // The circuit registered without a freshness check, which the policy now needs
+    let fresh_only = SecurityPolicy {
+        max_freshness_window_secs: WINDOW_SECS,
+        ..SecurityPolicy::default_for(false)
+    };
+    set_policy(&mut ctx, &registry, fresh_only).await.unwrap();
+    let (_, result) = submit(&mut ctx, &registry, &fixture[1], HashAlgo::Keccak).await;
+    assert_error(result, HealthcareError::CircuitNotAllowedByPolicy);
+
+    let keccak_only = SecurityPolicy {
+        allowed_hash_algos: HashAlgo::Keccak.bit(),
+        ..SecurityPolicy::default_for(false)
+    };
+    set_policy(&mut ctx, &registry, keccak_only).await.unwrap();
+    let (_, result) = submit(&mut ctx, &registry, &fixture[1], HashAlgo::Poseidon).await;
+    assert_error(result, HealthcareError::HashAlgoNotAllowed);
+    submit(&mut ctx, &registry, &fixture[2], HashAlgo::Keccak).await.1.unwrap();
+}
+
+#[tokio::test]
+async fn test_compliant_flow_end_to_end() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+
+    // A NIST-compliant registry can't be given a policy allowing Poseidon
+    let poseidon = SecurityPolicy {
+        allowed_hash_algos: HashAlgo::Keccak.bit() | HashAlgo::Poseidon.bit(),
+        ..compliant_policy()
+    };
+    assert_error(set_policy(&mut ctx, &registry, poseidon).await, HealthcareError::PolicyNotNistCompliant);
+    set_policy(&mut ctx, &registry, compliant_policy()).await.unwrap();
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(state.security_policy, compliant_policy());
+
+    // Circuits must check freshness within the policy's window
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let fixture = timestamped_square_fixture(1, clock.unix_timestamp);
+    let stale_window = VkConfig { freshness_window_secs: Some(WINDOW_SECS + 1), ..VkConfig::default() };
+    for config in [VkConfig::default(), stale_window] {
+        let result = register(&mut ctx, &registry, &fixture, config).await;
+        assert_error(result, HealthcareError::CircuitNotAllowedByPolicy);
+    }
+    let config = VkConfig { freshness_window_secs: Some(WINDOW_SECS), ..VkConfig::default() };
+    upload_vk_with(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes, config).await;
+
+    let (verification, result) = submit(&mut ctx, &registry, &fixture, HashAlgo::Keccak).await;
+    result.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+    assert_eq!(record.hash_algo, HashAlgo::Keccak);
+}
@@ -0,0 +1,137 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Golden vectors in the snarkjs JSON layout (`verification_key.json`,
+// `proof.json`, `public.json`) for a circuit proving `a * b == c` with public
+// signals `[c, b]`.
+
+mod common;
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::VerifyingKey;
+use ark_serialize::CanonicalSerialize;
+use common::*;
+use serde_json::Value;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use std::str::FromStr;
+use zk_healthcare::{HealthcareError, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "snarkjs_mul";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+const VERIFICATION_KEY_JSON: &str = include_str!("fixtures/snarkjs/verification_key.json");
+const PROOF_JSON: &str = include_str!("fixtures/snarkjs/proof.json");
+const PUBLIC_JSON: &str = include_str!("fixtures/snarkjs/public.json");
+
+fn decimal(value: &Value) -> &str {
+    value.as_str().expect("snarkjs encodes field elements as decimal strings")
+}
+
+fn fq(value: &Value) -> Fq {
+    Fq::from_str(decimal(value)).unwrap()
+}
+
+fn be_bytes<F: PrimeField>(value: F) -> Vec<u8> {
+    value.into_bigint().to_bytes_be()
+}
+
+fn g1(point: &Value) -> G1Affine {
+    G1Affine::new(fq(&point[0]), fq(&point[1]))
+}
+
+fn g2(point: &Value) -> G2Affine {
+    let x = Fq2::new(fq(&point[0][0]), fq(&point[0][1]));
+    let y = Fq2::new(fq(&point[1][0]), fq(&point[1][1]));
+    G2Affine::new(x, y)
+}
+
+/// The verifying key uploaded on-chain is always the arkworks encoding
+fn vk_bytes(json: &Value) -> Vec<u8> {
+    let vk = VerifyingKey::<Bn254> {
+        alpha_g1: g1(&json["vk_alpha_1"]),
+        beta_g2: g2(&json["vk_beta_2"]),
+        gamma_g2: g2(&json["vk_gamma_2"]),
+        delta_g2: g2(&json["vk_delta_2"]),
+        gamma_abc_g1: json["IC"].as_array().unwrap().iter().map(g1).collect(),
+    };
+    let mut bytes = Vec::new();
+    vk.serialize_uncompressed(&mut bytes).unwrap();
+    bytes
+}
+
+/// pi_a | pi_b | pi_c as big-endian coordinates, keeping snarkjs' `[c0, c1]`
+/// pairs swapped to c1 before c0 the way the Solidity verifier expects them
+fn snarkjs_proof_bytes(json: &Value) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(256);
+    for coordinate in &json["pi_a"].as_array().unwrap()[..2] {
+        bytes.extend(be_bytes(fq(coordinate)));
+    }
+    for pair in &json["pi_b"].as_array().unwrap()[..2] {
+        bytes.extend(be_bytes(fq(&pair[1])));
+        bytes.extend(be_bytes(fq(&pair[0])));
+    }
+    for coordinate in &json["pi_c"].as_array().unwrap()[..2] {
+        bytes.extend(be_bytes(fq(coordinate)));
+    }
+    bytes
+}
+
+fn snarkjs_public_bytes(json: &Value) -> Vec<u8> {
+    json.as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|signal| be_bytes(Fr::from_str(decimal(signal)).unwrap()))
+        .collect()
+}
+
+fn verify_ix(registry: Pubkey, record_nonce: u64, patient: Pubkey, public_inputs: Vec<u8>) -> Instruction {
+    let proof: Value = serde_json::from_str(PROOF_JSON).unwrap();
+    verify_eligibility_ix(
+        registry,
+        record_nonce,
+        CIRCUIT,
+        patient,
+        snarkjs_proof_bytes(&proof),
+        ProofFormat::SnarkJs,
+        public_inputs,
+        CID,
+    )
+}
+
+#[tokio::test]
+async fn test_snarkjs_golden_vector_verifies() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk: Value = serde_json::from_str(VERIFICATION_KEY_JSON).unwrap();
+    assert_eq!(vk["nPublic"], 2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &vk_bytes(&vk)).await;
+
+    let public: Value = serde_json::from_str(PUBLIC_JSON).unwrap();
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_ix(registry.pubkey(), nonce, patient, snarkjs_public_bytes(&public));
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, nonce)).await;
+    assert!(record.is_valid);
+}
+
+#[tokio::test]
+async fn test_snarkjs_golden_vector_wrong_signals_rejected() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk: Value = serde_json::from_str(VERIFICATION_KEY_JSON).unwrap();
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &vk_bytes(&vk)).await;
+
+    // 11 * 13 == 143, so claiming 144 must fail
+    let public: Value = serde_json::json!(["144", "13"]);
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_ix(registry.pubkey(), nonce, patient, snarkjs_public_bytes(&public));
+    assert_error(
+        send(&mut ctx, &[ix], &[]).await,
+        HealthcareError::PairingCheckFailed,
+    );
+}
@@ -0,0 +1,97 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_sdk::hash::hash;
+use solana_sdk::signature::Signer;
+use zk_healthcare::{
+    DataPinned, DataUnpinned, HealthcareError, IpfsPinRecord, StorageBackend, MAX_CUSTOM_STORAGE_ID_LEN,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const ARWEAVE_TX_ID: [u8; 32] = [0xab; 32];
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[tokio::test]
+async fn test_one_pin_per_backend() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    let shadow_url_hash = hash(b"https://shdw-drive.genesysgo.net/bucket/scan.pdf").to_bytes();
+    let custom_id = [7u8; 20];
+
+    for (index, (backend, storage_id, id_string)) in [
+        (StorageBackend::Ipfs, CID.as_bytes(), CID.to_string()),
+        (StorageBackend::Arweave, &ARWEAVE_TX_ID[..], hex(&ARWEAVE_TX_ID)),
+        (StorageBackend::ShadowDrive, &shadow_url_hash[..], hex(&shadow_url_hash)),
+        (StorageBackend::Custom(7), &custom_id[..], hex(&custom_id)),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let data_hash = [index as u8 + 1; 32];
+        let ix = pin_medical_data_on_ix(registry, patient, backend, storage_id, data_hash);
+        let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+        result.unwrap();
+        let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &data_hash)).await;
+        assert_eq!((pin.backend, pin.cid_string()), (backend, id_string.clone()));
+
+        // Workers learn where to pin from the event
+        let event = &events::<DataPinned>(&logs)[0];
+        assert_eq!((event.backend, &event.ipfs_cid, event.data_hash), (backend, &id_string, data_hash));
+    }
+
+    // and where to release it from
+    let pin_record = IpfsPinRecord::address(&patient, &[2; 32]);
+    confirm_pin(&mut ctx, registry, pin_record).await;
+    let ix = unpin_medical_data_ix(registry, pin_record, [2; 32], patient, patient, patient, patient);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    let event = &events::<DataUnpinned>(&logs)[0];
+    assert_eq!((event.backend, event.ipfs_cid.as_str()), (StorageBackend::Arweave, hex(&ARWEAVE_TX_ID).as_str()));
+
+    // Pins written the IPFS way stay on IPFS
+    send(&mut ctx, &[pin_medical_data_ix(registry, patient, CID, [9; 32])], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, IpfsPinRecord::address(&patient, &[9; 32])).await;
+    assert_eq!(pin.backend, StorageBackend::Ipfs);
+}
+
+#[tokio::test]
+async fn test_id_must_fit_its_backend() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    let pin = |backend, storage_id: &[u8]| pin_medical_data_on_ix(registry, patient, backend, storage_id, [1; 32]);
+
+    // An Arweave transaction id is no CID
+    let ix = pin(StorageBackend::Ipfs, &ARWEAVE_TX_ID);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidCid);
+    let ix = pin(StorageBackend::Ipfs, b"QmNotACid");
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidCid);
+
+    for (backend, len) in [
+        (StorageBackend::Arweave, 31),
+        (StorageBackend::Arweave, CID.len()),
+        (StorageBackend::ShadowDrive, 33),
+        (StorageBackend::Custom(7), 0),
+        (StorageBackend::Custom(7), MAX_CUSTOM_STORAGE_ID_LEN + 1),
+    ] {
+        let ix = pin(backend, &vec![0xab; len]);
+        assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidStorageId);
+    }
+    send(&mut ctx, &[pin(StorageBackend::Custom(7), &[0xab; MAX_CUSTOM_STORAGE_ID_LEN])], &[]).await.unwrap();
+
+    // Only an IPFS pin is retried under a new CID
+    let pin_record = IpfsPinRecord::address(&patient, &[1; 32]);
+    send(&mut ctx, &[add_pinning_oracle_ix(patient, registry, patient)], &[]).await.unwrap();
+    send(&mut ctx, &[confirm_pin_ix(registry, pin_record, patient, false, "cluster")], &[]).await.unwrap();
+    let ix = retry_pin_ix(registry, pin_record, patient, Some(CID));
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidStorageId);
+    send(&mut ctx, &[retry_pin_ix(registry, pin_record, patient, None)], &[]).await.unwrap();
+}
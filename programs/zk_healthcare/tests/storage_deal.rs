@@ -0,0 +1,154 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Reads events from the logs, so every test starts with event logging.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, IpfsPinRecord, StorageBackend, StorageDealExpiring, StorageDealRecorded, MAX_STORAGE_DEALS,
+    SECS_PER_DAY,
+};
+
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const PROVIDER: &str = "f01234";
+
+/// A registry, a pin of the payer's under it, when it was pinned, and a
+/// funded oracle the registry lists
+async fn setup(ctx: &mut ProgramTestContext) -> (Pubkey, Pubkey, i64, Keypair) {
+    let registry = initialize_registry(ctx).await.pubkey();
+    let patient = ctx.payer.pubkey();
+    send(ctx, &[pin_medical_data_ix(registry, patient, CID, [7; 32])], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&patient, &[7; 32]);
+    let pinned_at = fetch::<IpfsPinRecord>(ctx, pin_record).await.pinned_at;
+    let oracle = funded(ctx).await;
+    send(ctx, &[add_pinning_oracle_ix(patient, registry, oracle.pubkey())], &[]).await.unwrap();
+    (registry, pin_record, pinned_at, oracle)
+}
+
+#[tokio::test]
+async fn test_oracle_records_two_deals() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, now, oracle) = setup(&mut ctx).await;
+    let deal = |deal_id, provider: &str, days| {
+        set_storage_deal_ix(registry, pin_record, oracle.pubkey(), deal_id, provider, now + days * SECS_PER_DAY)
+    };
+
+    // Each deal grows the pin, at the oracle's expense
+    for (count, (deal_id, provider)) in [(1001, PROVIDER), (1002, "f05678")].into_iter().enumerate() {
+        let (before, pin_before) = (balance(&mut ctx, oracle.pubkey()).await, balance(&mut ctx, pin_record).await);
+        let (result, logs) = send_logged(&mut ctx, &[deal(deal_id, provider, 180)], &[&oracle]).await;
+        result.unwrap();
+        let account = ctx.banks_client.get_account(pin_record).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), IpfsPinRecord::space_with(0, count + 1));
+        assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+        assert_eq!(balance(&mut ctx, oracle.pubkey()).await, before - (account.lamports - pin_before));
+
+        let event = &events::<StorageDealRecorded>(&logs)[0];
+        assert_eq!((event.pin_record, event.patient, event.oracle), (pin_record, ctx.payer.pubkey(), oracle.pubkey()));
+        assert_eq!((event.deal_id, event.provider.as_str()), (deal_id, provider));
+        assert_eq!((event.deal_expiry, event.deals), (now + 180 * SECS_PER_DAY, count as u8 + 1));
+    }
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    let ids: Vec<u64> = pin.deals.iter().map(|deal| deal.deal_id).collect();
+    assert_eq!(ids, [1001, 1002]);
+    assert!(pin.deals.iter().all(|deal| deal.oracle == oracle.pubkey() && deal.recorded_at >= now));
+
+    // Recording a deal again extends it in place
+    send(&mut ctx, &[deal(1001, PROVIDER, 365)], &[&oracle]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.deals.len(), pin.deals[0].deal_expiry), (2, now + 365 * SECS_PER_DAY));
+    let len = ctx.banks_client.get_account(pin_record).await.unwrap().unwrap().data.len();
+    assert_eq!(len, IpfsPinRecord::space_with(0, 2));
+
+    // Only a listed oracle records deals, each with a provider and a future end
+    let other = funded(&mut ctx).await;
+    let ix = set_storage_deal_ix(registry, pin_record, other.pubkey(), 1003, PROVIDER, now + SECS_PER_DAY);
+    assert_error(send(&mut ctx, &[ix], &[&other]).await, HealthcareError::NotPinningOracle);
+    for ix in [deal(1003, "", 180), deal(1003, &"f0".repeat(12), 180), deal(1003, PROVIDER, -1)] {
+        assert_error(send(&mut ctx, &[ix], &[&oracle]).await, HealthcareError::InvalidStorageDeal);
+    }
+}
+
+#[tokio::test]
+async fn test_fifth_deal_rejected() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, now, oracle) = setup(&mut ctx).await;
+    let (oracle_key, deal_expiry) = (oracle.pubkey(), now + SECS_PER_DAY);
+    let deal = |deal_id| set_storage_deal_ix(registry, pin_record, oracle_key, deal_id, PROVIDER, deal_expiry);
+
+    for deal_id in 0..MAX_STORAGE_DEALS as u64 {
+        send(&mut ctx, &[deal(deal_id)], &[&oracle]).await.unwrap();
+    }
+    let ix = deal(MAX_STORAGE_DEALS as u64);
+    assert_error(send(&mut ctx, &[ix], &[&oracle]).await, HealthcareError::TooManyStorageDeals);
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.deals.len(), MAX_STORAGE_DEALS);
+
+    // A full pin still takes an extended deal, and unpins with the deals' rent
+    let ix = set_storage_deal_ix(registry, pin_record, oracle_key, 0, PROVIDER, deal_expiry + SECS_PER_DAY);
+    send(&mut ctx, &[ix], &[&oracle]).await.unwrap();
+    confirm_pin(&mut ctx, registry, pin_record).await;
+    let patient = ctx.payer.pubkey();
+    let rent = balance(&mut ctx, pin_record).await;
+    assert_eq!(rent, Rent::default().minimum_balance(IpfsPinRecord::space_with(0, MAX_STORAGE_DEALS)));
+    let ix = unpin_medical_data_ix(registry, pin_record, [7; 32], patient, patient, patient, patient);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(pin_record).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_expiry_warning_inside_window_only() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, now, oracle) = setup(&mut ctx).await;
+    let cranker = funded(&mut ctx).await;
+    let ix = set_deal_expiry_warning_ix(ctx.payer.pubkey(), registry, SECS_PER_DAY);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let deal_expiry = now + 3 * SECS_PER_DAY;
+    let ix = set_storage_deal_ix(registry, pin_record, oracle.pubkey(), 1001, PROVIDER, deal_expiry);
+    send(&mut ctx, &[ix], &[&oracle]).await.unwrap();
+    let flag = |deal_id| deal_expiring_soon_ix(registry, pin_record, cranker.pubkey(), deal_id);
+
+    // Outside the window the deal isn't flagged
+    warp_clock_to(&mut ctx, deal_expiry - SECS_PER_DAY - 1).await;
+    assert_error(send(&mut ctx, &[flag(1001)], &[&cranker]).await, HealthcareError::DealNotExpiringSoon);
+
+    // and inside it anyone flags it
+    warp_clock(&mut ctx, 1).await;
+    let (result, logs) = send_logged(&mut ctx, &[flag(1001)], &[&cranker]).await;
+    result.unwrap();
+    let event = &events::<StorageDealExpiring>(&logs)[0];
+    assert_eq!((event.pin_record, event.patient, event.deal_id), (pin_record, ctx.payer.pubkey(), 1001));
+    assert_eq!((event.provider.as_str(), event.deal_expiry), (PROVIDER, deal_expiry));
+    assert_eq!(event.timestamp, deal_expiry - SECS_PER_DAY);
+
+    // A deal the pin doesn't hold can't be flagged
+    assert_error(send(&mut ctx, &[flag(1002)], &[&cranker]).await, HealthcareError::StorageDealNotFound);
+    let ix = set_deal_expiry_warning_ix(ctx.payer.pubkey(), registry, 0);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidDealExpiryWarning);
+}
+
+#[tokio::test]
+async fn test_pin_holding_a_deal_is_not_pinned_again() {
+    let mut ctx = start_with_event_logs().await;
+    let (registry, pin_record, now, oracle) = setup(&mut ctx).await;
+    let ix = set_storage_deal_ix(registry, pin_record, oracle.pubkey(), 1001, PROVIDER, now + SECS_PER_DAY);
+    send(&mut ctx, &[ix], &[&oracle]).await.unwrap();
+
+    // The deal grew the account past a new pin's length, which changes nothing
+    let patient = ctx.payer.pubkey();
+    for ix in [
+        pin_medical_data_ix(registry, patient, CID, [7; 32]),
+        pin_medical_data_on_ix(registry, patient, StorageBackend::Arweave, &[9; 32], [7; 32]),
+    ] {
+        let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+        assert_error(result, HealthcareError::AlreadyPinned);
+        assert!(logs.iter().any(|log| log.ends_with(&format!("Content already pinned at {pin_record}"))), "{logs:?}");
+    }
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!((pin.cid_string(), pin.deals.len()), (CID.to_string(), 1));
+}
@@ -0,0 +1,162 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::clock::Clock;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, HealthcareRegistry, ProofFormat, TimeSource, VerificationRecord, VkConfig, NOMINAL_SLOT_MS,
+    RECORD_GC_GRACE_SECS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const WINDOW_SECS: i64 = 1_000;
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+
+/// Slots in `secs` at `NOMINAL_SLOT_MS`
+fn slots(secs: i64) -> u64 {
+    (secs * 1000 / NOMINAL_SLOT_MS) as u64
+}
+
+/// Move `count` slots ahead while the bank's unix timestamp stands still
+async fn warp_slots(ctx: &mut ProgramTestContext, count: u64) {
+    let before: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    ctx.warp_to_slot(before.slot + count).unwrap();
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = before.unix_timestamp;
+    ctx.set_sysvar(&clock);
+}
+
+/// A registry whose records expire after `VALIDITY_SECS` and may be disputed
+/// for `WINDOW_SECS`, and the payer's record verified under it
+async fn verified(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let ixs = [
+        set_default_validity_ix(authority, registry.pubkey(), VALIDITY_SECS),
+        set_dispute_window_ix(authority, registry.pubkey(), WINDOW_SECS),
+    ];
+    send(ctx, &ixs, &[]).await.unwrap();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        authority,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, verification_address(&authority, 0))
+}
+
+#[tokio::test]
+async fn test_slot_dispute_window_ignores_a_skewed_timestamp() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let dispute = dispute_verification_ix(registry.pubkey(), verification, authority, authority, false, [1; 32]);
+
+    // The timestamp jumps past the window in a single slot
+    warp_clock(&mut ctx, WINDOW_SECS).await;
+    let result = send(&mut ctx, std::slice::from_ref(&dispute), &[]).await;
+    assert_error(result, HealthcareError::DisputeWindowClosed);
+
+    let ix = set_time_source_ix(authority, registry.pubkey(), TimeSource::Slot);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let stored: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(stored.time_source, TimeSource::Slot);
+    // Past the slot, so the repeated dispute isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[dispute], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_slot_dispute_window_closes_on_slots() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let ix = set_time_source_ix(authority, registry.pubkey(), TimeSource::Slot);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+
+    // Enough slots for the window, though the timestamp hasn't moved
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    warp_slots(&mut ctx, record.slot + slots(WINDOW_SECS) - clock.slot).await;
+    let ix = dispute_verification_ix(registry.pubkey(), verification, authority, authority, false, [1; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::DisputeWindowClosed);
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    assert!(clock.unix_timestamp < record.timestamp + WINDOW_SECS);
+}
+
+#[tokio::test]
+async fn test_slot_gc_grace_counts_slots() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let ix = set_time_source_ix(authority, registry.pubkey(), TimeSource::Slot);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    let close = close_expired_verification_ix(registry.pubkey(), verification, authority, authority, None);
+
+    // By the timestamp the grace period is long over, but not a slot has passed
+    warp_clock_to(&mut ctx, record.expires_at + RECORD_GC_GRACE_SECS).await;
+    let result = send(&mut ctx, std::slice::from_ref(&close), &[]).await;
+    assert_error(result, HealthcareError::RecordNotExpired);
+
+    let expiry = record.slot + slots(VALIDITY_SECS);
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    warp_slots(&mut ctx, expiry - clock.slot).await;
+    let result = send(&mut ctx, std::slice::from_ref(&close), &[]).await;
+    assert_error(result, HealthcareError::GcGracePeriodActive);
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    warp_slots(&mut ctx, expiry + slots(RECORD_GC_GRACE_SECS) - clock.slot).await;
+    send(&mut ctx, &[close], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(verification).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_slot_freshness_reads_a_slot_input() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = timestamped_square_fixture(1, 0);
+    let config = VkConfig {
+        freshness_window_secs: Some(WINDOW_SECS),
+        ..VkConfig::default()
+    };
+    upload_vk_with(&mut ctx, registry.pubkey(), "eligibility_fresh", &fixture.vk_bytes, config).await;
+    let authority = ctx.payer.pubkey();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let fixture = timestamped_square_fixture(1, clock.slot as i64);
+    let submit = |nonce| {
+        verify_eligibility_ix(
+            registry.pubkey(),
+            nonce,
+            "eligibility_fresh",
+            authority,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+            CID,
+        )
+    };
+
+    // Read as a unix timestamp, a slot number is decades old
+    assert_error(send(&mut ctx, &[submit(0)], &[]).await, HealthcareError::ProofStale);
+
+    let ix = set_time_source_ix(authority, registry.pubkey(), TimeSource::Slot);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // A skewed timestamp doesn't matter once the input is a slot
+    warp_clock(&mut ctx, 10 * WINDOW_SECS).await;
+    send(&mut ctx, &[submit(0)], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&authority, 0)).await;
+    assert!(record.is_verified());
+}
@@ -0,0 +1,110 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, HealthcareRegistry, ProofFormat, VerificationRecord, VerificationType, VkConfig};
+
+const ELIGIBILITY: &str = "eligibility_v1";
+const ACCESS: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// The payer's next record and the instruction proving `fixture` into it
+async fn verify_ix(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    circuit_id: &str,
+    fixture: &Fixture,
+) -> (Pubkey, Instruction) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        circuit_id,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    (verification_address(&patient, nonce), ix)
+}
+
+#[tokio::test]
+async fn test_mapped_circuit_verifies() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), ELIGIBILITY, &fixture.vk_bytes).await;
+
+    let state: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert!(state.is_circuit_for(VerificationType::Eligibility, &vk_address(ELIGIBILITY)));
+    assert_eq!(state.circuit_for_type[VerificationType::AccessControl as usize], None);
+
+    let (verification, ix) = verify_ix(&mut ctx, &registry, ELIGIBILITY, &fixture).await;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+}
+
+#[tokio::test]
+async fn test_unmapped_circuit_refused() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let fixture = square_fixture(1);
+
+    // Registered and finalized, but never approved for a verification type
+    let config = VkConfig { n_public: n_public(&fixture.vk_bytes), ..VkConfig::default() };
+    let vk_hash = keccak::hash(&fixture.vk_bytes).to_bytes();
+    let total_len = fixture.vk_bytes.len() as u32;
+    let ix = register_vk_ix(authority, registry.pubkey(), ELIGIBILITY, total_len, config, vk_hash);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    for (i, chunk) in fixture.vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_chunk_ix(authority, ELIGIBILITY, (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    send(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), ELIGIBILITY)], &[]).await.unwrap();
+
+    let (_, ix) = verify_ix(&mut ctx, &registry, ELIGIBILITY, &fixture).await;
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::WrongCircuitForType);
+}
+
+#[tokio::test]
+async fn test_circuit_for_another_type_refused() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let (eligibility, access) = (square_fixture(1), square_fixture(2));
+    upload_vk(&mut ctx, registry.pubkey(), ACCESS, &access.vk_bytes).await;
+    upload_vk(&mut ctx, registry.pubkey(), ELIGIBILITY, &eligibility.vk_bytes).await;
+    let ix = set_type_circuit_ix(authority, registry.pubkey(), ACCESS, VerificationType::AccessControl);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    // A valid proof under the access-control circuit can't be recorded as eligibility
+    let (_, ix) = verify_ix(&mut ctx, &registry, ACCESS, &access).await;
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::WrongCircuitForType);
+
+    let ix = verify_ix(&mut ctx, &registry, ELIGIBILITY, &eligibility).await.1;
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_only_authority_maps_circuits() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), ELIGIBILITY, &fixture.vk_bytes).await;
+
+    let intruder = Keypair::new();
+    let ix = set_type_circuit_ix(intruder.pubkey(), registry.pubkey(), ELIGIBILITY, VerificationType::Prescription);
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+}
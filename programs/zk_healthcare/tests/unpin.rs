@@ -0,0 +1,135 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{AccessPass, HealthcareError, HealthcareRegistry, IpfsPinRecord, VerificationType};
+
+const CIRCUIT: &str = "access_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with `CIRCUIT` approved for access control, and a confirmed pin
+/// of the payer's under it
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> (Keypair, Pubkey) {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let ix = set_type_circuit_ix(authority, registry.pubkey(), CIRCUIT, VerificationType::AccessControl);
+    send(ctx, &[ix], &[]).await.unwrap();
+    send(ctx, &[pin_medical_data_ix(registry.pubkey(), authority, CID, [7; 32])], &[]).await.unwrap();
+    let pin_record = IpfsPinRecord::address(&authority, &[7; 32]);
+    confirm_pin(ctx, registry.pubkey(), pin_record).await;
+    (registry, pin_record)
+}
+
+/// The payer's unpin of its own `pin_record`
+fn unpin_ix(registry: &Keypair, pin_record: Pubkey, patient: Pubkey) -> Instruction {
+    unpin_medical_data_ix(registry.pubkey(), pin_record, [7; 32], patient, patient, patient, patient)
+}
+
+async fn unpin(ctx: &mut ProgramTestContext, registry: &Keypair, pin_record: Pubkey) -> Result<(), BanksClientError> {
+    let ix = unpin_ix(registry, pin_record, ctx.payer.pubkey());
+    send(ctx, &[ix], &[]).await
+}
+
+#[tokio::test]
+async fn test_unpin_closes_the_pin_and_counts_it() {
+    let mut ctx = start().await;
+    let (registry, pin_record) = setup(&mut ctx, &square_fixture(1)).await;
+    let before: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+
+    warp_clock(&mut ctx, 90).await;
+    let ix = unpin_ix(&registry, pin_record, ctx.payer.pubkey());
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    assert!(logs.iter().any(|log| log.ends_with(&format!("Pin record {CID} closed after 90s pinned"))));
+    assert!(ctx.banks_client.get_account(pin_record).await.unwrap().is_none());
+    let after: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(after.ipfs_pin_count, before.ipfs_pin_count - 1);
+}
+
+#[tokio::test]
+async fn test_live_pass_holds_the_pin() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let (registry, pin_record) = setup(&mut ctx, &fixtures[0]).await;
+    let patient = ctx.payer.pubkey();
+
+    // A pass issued against the pin
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, patient, &fixtures[0], pin_record, 10, 5);
+    send(&mut ctx, &[with_pin_record(ix, pin_record)], &[]).await.unwrap();
+    let pass: AccessPass = fetch(&mut ctx, AccessPass::address(&patient, &pin_record)).await;
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.grants_expire_at_slot, pass.expires_at_slot);
+    assert_error(unpin(&mut ctx, &registry, pin_record).await, HealthcareError::ActiveGrantsExist);
+
+    // is still counted through its last slot
+    ctx.warp_to_slot(pass.expires_at_slot).unwrap();
+    assert_error(unpin(&mut ctx, &registry, pin_record).await, HealthcareError::ActiveGrantsExist);
+    ctx.warp_to_slot(pass.expires_at_slot + 1).unwrap();
+    unpin(&mut ctx, &registry, pin_record).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_pass_used_on_the_pin_holds_it() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let (registry, pin_record) = setup(&mut ctx, &fixture).await;
+    let patient = ctx.payer.pubkey();
+
+    // Issued without the pin, the pass only shows once it is used
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, patient, &fixture, pin_record, 10, 5);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.grants_expire_at_slot, 0);
+    send(&mut ctx, &[record_access_ix(registry.pubkey(), pin_record, patient, true)], &[]).await.unwrap();
+    assert_error(unpin(&mut ctx, &registry, pin_record).await, HealthcareError::ActiveGrantsExist);
+
+    let pass: AccessPass = fetch(&mut ctx, AccessPass::address(&patient, &pin_record)).await;
+    ctx.warp_to_slot(pass.expires_at_slot + 1).unwrap();
+    unpin(&mut ctx, &registry, pin_record).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_third_party_pass_cannot_hold_the_pin() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let (registry, pin_record) = setup(&mut ctx, &fixtures[0]).await;
+    let stranger = funded(&mut ctx).await;
+
+    // A pass of someone else's can't be issued against the pin, only to it
+    let ix = verify_access_control_ix(registry.pubkey(), CIRCUIT, stranger.pubkey(), &fixtures[1], pin_record, 100, 5);
+    let result = send(&mut ctx, &[with_pin_record(ix.clone(), pin_record)], &[&stranger]).await;
+    assert_error(result, HealthcareError::PinRecordPatientMismatch);
+    send(&mut ctx, &[ix], &[&stranger]).await.unwrap();
+    let pin: IpfsPinRecord = fetch(&mut ctx, pin_record).await;
+    assert_eq!(pin.grants_expire_at_slot, 0);
+    unpin(&mut ctx, &registry, pin_record).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_only_the_owner_or_its_new_key_unpins() {
+    let mut ctx = start().await;
+    let (registry, pin_record) = setup(&mut ctx, &square_fixture(1)).await;
+    let patient = ctx.payer.pubkey();
+
+    let intruder = Keypair::new();
+    let intruder_key = intruder.pubkey();
+    let ix = unpin_medical_data_ix(registry.pubkey(), pin_record, [7; 32], patient, intruder_key, patient, patient);
+    assert_error(send(&mut ctx, &[ix], &[&intruder]).await, HealthcareError::PinRecordPatientMismatch);
+
+    // After a rotation the old key is refused and the new one unpins without
+    // claiming the pin first
+    let new_key = Keypair::new();
+    send(&mut ctx, &[rotate_patient_key_ix(registry.pubkey(), patient, new_key.pubkey())], &[]).await.unwrap();
+    let new_patient = new_key.pubkey();
+    let ix = unpin_medical_data_ix(registry.pubkey(), pin_record, [7; 32], patient, patient, patient, new_patient);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PinRecordPatientMismatch);
+    let ix = unpin_medical_data_ix(registry.pubkey(), pin_record, [7; 32], patient, new_patient, patient, new_patient);
+    send(&mut ctx, &[ix], &[&new_key]).await.unwrap();
+    assert!(ctx.banks_client.get_account(pin_record).await.unwrap().is_none());
+}
@@ -0,0 +1,198 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use zk_healthcare::client::derive_verification_id;
+use zk_healthcare::{
+    HealthcareError, HealthcareRegistry, ProofFormat, VerificationCache, VerificationRecord, VerificationResult,
+    DEFAULT_MAX_PUBLIC_INPUTS,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const TTL_SLOTS: u64 = 20;
+
+async fn setup(ctx: &mut ProgramTestContext, fixture: &Fixture) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = update_config_ix(ctx.payer.pubkey(), registry.pubkey(), DEFAULT_MAX_PUBLIC_INPUTS, TTL_SLOTS, 0);
+    send(ctx, &[ix], &[]).await.unwrap();
+    registry
+}
+
+async fn funded_patient(ctx: &mut ProgramTestContext) -> Keypair {
+    let patient = Keypair::new();
+    let ix = system_instruction::transfer(&ctx.payer.pubkey(), &patient.pubkey(), 1_000_000_000);
+    send(ctx, &[ix], &[]).await.unwrap();
+    patient
+}
+
+/// `verify_eligibility` of the fixture's proof by `patient` into its record at
+/// `record_nonce`, with its cache account when `cached`
+fn verify_ix(
+    registry: &Keypair,
+    record_nonce: u64,
+    patient: &Keypair,
+    fixture: &Fixture,
+    cached: bool,
+) -> Instruction {
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        record_nonce,
+        CIRCUIT,
+        patient.pubkey(),
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    if cached {
+        with_cache(ix, verification_hash(fixture))
+    } else {
+        ix
+    }
+}
+
+async fn verify(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    patient: &Keypair,
+    fixture: &Fixture,
+    cached: bool,
+) -> (Pubkey, Result<(), BanksClientError>) {
+    let nonce = next_record_nonce(ctx, patient.pubkey()).await;
+    let ix = verify_ix(registry, nonce, patient, fixture, cached);
+    let result = send(ctx, &[ix], &[patient]).await;
+    (verification_address(&patient.pubkey(), nonce), result)
+}
+
+async fn check_cached(ctx: &mut ProgramTestContext, proof_hash: [u8; 32]) -> Result<(), BanksClientError> {
+    send(ctx, &[check_cached_ix(proof_hash)], &[]).await
+}
+
+#[tokio::test]
+async fn test_cache_hit_skips_the_pairing() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let patient = funded_patient(&mut ctx).await;
+    let proof_hash = verification_hash(&fixture);
+
+    let (verification, result) = verify(&mut ctx, &registry, &patient, &fixture, true).await;
+    result.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.proof_hash, proof_hash);
+    let entry: VerificationCache = fetch(&mut ctx, cache_address(&proof_hash)).await;
+    let expected = VerificationResult {
+        verified: true,
+        proof_hash,
+        record: verification,
+        verification_id: derive_verification_id(&registry.pubkey(), &patient.pubkey(), CIRCUIT, &proof_hash),
+    };
+    assert_eq!(entry.result, expected);
+    assert_eq!((entry.circuit_id.as_str(), entry.payer), (CIRCUIT, patient.pubkey()));
+
+    let answer = simulate_return_data(&mut ctx, &[check_cached_ix(proof_hash)], &[]).await;
+    assert_eq!(VerificationResult::try_from_slice(&answer.data).unwrap(), expected);
+
+    // Resubmitting the proof is answered from the cache instead of failing as a replay
+    let before = balance(&mut ctx, patient.pubkey()).await;
+    let nonce = next_record_nonce(&mut ctx, patient.pubkey()).await;
+    let ix = verify_ix(&registry, nonce, &patient, &fixture, true);
+    let answer = simulate_return_data(&mut ctx, std::slice::from_ref(&ix), &[&patient]).await;
+    assert_eq!(VerificationResult::try_from_slice(&answer.data).unwrap(), expected);
+    let (logs_result, logs) = send_logged(&mut ctx, &[ix], &[&patient]).await;
+    logs_result.unwrap();
+    assert!(logs.iter().any(|log| log.contains("Verification served from cache")));
+    let again = verification_address(&patient.pubkey(), nonce);
+    assert!(ctx.banks_client.get_account(again).await.unwrap().is_none());
+    assert_eq!(next_record_nonce(&mut ctx, patient.pubkey()).await, nonce);
+    assert_eq!(balance(&mut ctx, patient.pubkey()).await, before);
+
+    let registry: HealthcareRegistry = fetch(&mut ctx, registry.pubkey()).await;
+    assert_eq!(registry.total_verifications, 1);
+}
+
+#[tokio::test]
+async fn test_cache_miss() {
+    let mut ctx = start().await;
+    let fixtures = batch_fixtures(2);
+    let (fixture, other) = (&fixtures[0], &fixtures[1]);
+    let registry = setup(&mut ctx, fixture).await;
+    let patient = funded_patient(&mut ctx).await;
+    let proof_hash = verification_hash(fixture);
+
+    // Nothing is cached for a proof never verified, or verified without its cache
+    let err = check_cached(&mut ctx, proof_hash).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::AccountNotInitialized as u32);
+    verify(&mut ctx, &registry, &patient, fixture, false).await.1.unwrap();
+    let err = check_cached(&mut ctx, proof_hash).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::AccountNotInitialized as u32);
+
+    // So a resubmission is still a replay, cache account or not
+    let (_, result) = verify(&mut ctx, &registry, &patient, fixture, true).await;
+    assert_error(result, HealthcareError::ProofAlreadyUsed);
+
+    // A cache account at the wrong address is refused rather than filled
+    let nonce = next_record_nonce(&mut ctx, patient.pubkey()).await;
+    let ix = with_force_new(with_cache(verify_ix(&registry, nonce, &patient, other, false), proof_hash));
+    assert_error(
+        send(&mut ctx, &[ix], &[&patient]).await,
+        HealthcareError::CacheAccountMismatch,
+    );
+}
+
+#[tokio::test]
+async fn test_cache_entry_expires() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let patient = funded_patient(&mut ctx).await;
+    let proof_hash = verification_hash(&fixture);
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    verify(&mut ctx, &registry, &patient, &fixture, true).await.1.unwrap();
+    let entry: VerificationCache = fetch(&mut ctx, cache_address(&proof_hash)).await;
+    assert!(entry.expires_at_slot >= clock.slot + TTL_SLOTS);
+    check_cached(&mut ctx, proof_hash).await.unwrap();
+
+    ctx.warp_to_slot(entry.expires_at_slot + 1).unwrap();
+    assert_error(check_cached(&mut ctx, proof_hash).await, HealthcareError::CacheEntryExpired);
+    let (_, result) = verify(&mut ctx, &registry, &patient, &fixture, true).await;
+    assert_error(result, HealthcareError::ProofAlreadyUsed);
+}
+
+#[tokio::test]
+async fn test_expired_entry_refunds_its_payer() {
+    let mut ctx = start().await;
+    let fixture = square_fixture(1);
+    let registry = setup(&mut ctx, &fixture).await;
+    let patient = funded_patient(&mut ctx).await;
+    let cache = cache_address(&verification_hash(&fixture));
+
+    verify(&mut ctx, &registry, &patient, &fixture, true).await.1.unwrap();
+    let ix = reclaim_cache_ix(cache, patient.pubkey());
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::CacheEntryNotExpired);
+
+    // Anyone may crank it, but the rent only goes back to the payer
+    let entry: VerificationCache = fetch(&mut ctx, cache).await;
+    ctx.warp_to_slot(entry.expires_at_slot + 1).unwrap();
+    let ix = reclaim_cache_ix(cache, ctx.payer.pubkey());
+    let err = send(&mut ctx, &[ix], &[]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+
+    let rent = balance(&mut ctx, cache).await;
+    let before = balance(&mut ctx, patient.pubkey()).await;
+    send(&mut ctx, &[reclaim_cache_ix(cache, patient.pubkey())], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(cache).await.unwrap().is_none());
+    assert_eq!(balance(&mut ctx, patient.pubkey()).await, before + rent);
+}
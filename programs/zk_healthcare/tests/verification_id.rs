@@ -0,0 +1,71 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// The verification ID as a job queue sees it: computed before submitting, then
+// matched against the record, the event and the return data. Captures events by
+// swapping the process-wide syscall stubs, so this binary holds a single test.
+
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use solana_sdk::signature::Signer;
+use zk_healthcare::client::derive_verification_id;
+use zk_healthcare::{EligibilityVerified, ProofFormat, VerificationRecord, VerificationResult};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+#[tokio::test]
+async fn test_verification_id_agrees_everywhere() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixtures = batch_fixtures(3);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixtures[0].vk_bytes).await;
+    let patient = ctx.payer.pubkey();
+    let expected: Vec<_> = fixtures
+        .iter()
+        .map(|fixture| derive_verification_id(&registry.pubkey(), &patient, CIRCUIT, &verification_hash(fixture)))
+        .collect();
+    assert_ne!(expected[0], expected[1]);
+
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let fixture = &fixtures[0];
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let answer = simulate_return_data(&mut ctx, std::slice::from_ref(&ix), &[]).await;
+    let result = VerificationResult::try_from_slice(&answer.data).unwrap();
+    assert_eq!(result.verification_id, expected[0]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    let event_ids: Vec<_> = events::<EligibilityVerified>(&logs).iter().map(|event| event.verification_id).collect();
+    assert_eq!(event_ids, [expected[0]]);
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.verification_id, expected[0]);
+
+    // A batch gives each submission its own ID, in submission order
+    let submissions = fixtures[1..].iter().map(|fixture| submission(fixture, CID)).collect();
+    let format = ProofFormat::Uncompressed;
+    let ix = verify_eligibility_batch_ix(registry.pubkey(), nonce + 1, CIRCUIT, patient, format, submissions);
+    let ix = with_force_new(ix);
+    let answer = simulate_return_data(&mut ctx, std::slice::from_ref(&ix), &[]).await;
+    let results = Vec::<VerificationResult>::try_from_slice(&answer.data).unwrap();
+    let result_ids: Vec<_> = results.iter().map(|result| result.verification_id).collect();
+    assert_eq!(result_ids, expected[1..]);
+    let (result, logs) = send_logged(&mut ctx, &[ix], &[]).await;
+    result.unwrap();
+    let event_ids: Vec<_> = events::<EligibilityVerified>(&logs).iter().map(|event| event.verification_id).collect();
+    assert_eq!(event_ids, expected[1..]);
+    for (id, record_nonce) in expected[1..].iter().zip(nonce + 1..) {
+        let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, record_nonce)).await;
+        assert_eq!(record.verification_id, *id);
+    }
+}
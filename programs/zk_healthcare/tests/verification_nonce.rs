@@ -0,0 +1,177 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::solana_program::keccak;
+use ark_bn254::Fr;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, PatientIndex, ProofFormat, VerificationRecord, VkConfig};
+
+const CIRCUIT: &str = "eligibility_nonce";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A proof of statement `statement` carrying verification nonce `nonce` at
+/// input 0; every statement shares one key
+fn fixture(nonce: u64, statement: u64) -> Fixture {
+    bound_square_fixture(1, vec![Fr::from(nonce), Fr::from(statement)])
+}
+
+fn nonce_config() -> VkConfig {
+    VkConfig {
+        nonce_input: Some(0),
+        ..VkConfig::default()
+    }
+}
+
+async fn setup(ctx: &mut ProgramTestContext, config: VkConfig) -> Keypair {
+    let registry = initialize_registry(ctx).await;
+    upload_vk_with(ctx, registry.pubkey(), CIRCUIT, &fixture(0, 0).vk_bytes, config).await;
+    registry
+}
+
+/// Submit `fixture` as the payer, expiring their record before it
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    fixture: &Fixture,
+) -> (Pubkey, Result<(), BanksClientError>) {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let result = send(ctx, &[with_force_new(ix)], &[]).await;
+    (verification, result)
+}
+
+async fn nonce(ctx: &mut ProgramTestContext) -> u64 {
+    let index: PatientIndex = fetch(ctx, patient_index_address(&ctx.payer.pubkey())).await;
+    index.verification_nonce
+}
+
+#[tokio::test]
+async fn test_stale_nonce_rejected() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, nonce_config()).await;
+
+    let (verification, result) = submit(&mut ctx, &registry, &fixture(0, 1)).await;
+    result.unwrap();
+    assert_eq!(nonce(&mut ctx).await, 1);
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.patient_pubkey, ctx.payer.pubkey());
+
+    // Another statement proven against the same nonce is as spent as the first
+    let (_, result) = submit(&mut ctx, &registry, &fixture(0, 2)).await;
+    assert_error(result, HealthcareError::StaleNonce);
+    let (_, result) = submit(&mut ctx, &registry, &fixture(2, 2)).await;
+    assert_error(result, HealthcareError::StaleNonce);
+    assert_eq!(nonce(&mut ctx).await, 1);
+
+    submit(&mut ctx, &registry, &fixture(1, 2)).await.1.unwrap();
+    assert_eq!(nonce(&mut ctx).await, 2);
+}
+
+#[tokio::test]
+async fn test_circuit_without_nonce_ignores_the_index() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, VkConfig::default()).await;
+    submit(&mut ctx, &registry, &fixture(0, 1)).await.1.unwrap();
+    submit(&mut ctx, &registry, &fixture(0, 2)).await.1.unwrap();
+    assert_eq!(nonce(&mut ctx).await, 0);
+}
+
+#[tokio::test]
+async fn test_batch_spends_consecutive_nonces() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, nonce_config()).await;
+    let patient = ctx.payer.pubkey();
+    let batch = |fixtures: &[Fixture]| {
+        let submissions = fixtures.iter().map(|fixture| submission(fixture, CID)).collect();
+        let format = ProofFormat::Uncompressed;
+        verify_eligibility_batch_ix(registry.pubkey(), 0, CIRCUIT, patient, format, submissions)
+    };
+
+    let repeated = [fixture(0, 1), fixture(0, 2)];
+    assert_error(send(&mut ctx, &[batch(&repeated)], &[]).await, HealthcareError::StaleNonce);
+    send(&mut ctx, &[batch(&[fixture(0, 1), fixture(1, 2)])], &[]).await.unwrap();
+    assert_eq!(nonce(&mut ctx).await, 2);
+}
+
+#[tokio::test]
+async fn test_interleaved_partial_verifications_spend_one_nonce() {
+    let mut ctx = start().await;
+    let registry = setup(&mut ctx, nonce_config()).await;
+    let patient = ctx.payer.pubkey();
+
+    // Both begin against nonce 0; only the first to complete gets it
+    let mut completions = Vec::new();
+    for fixture in [fixture(0, 1), fixture(0, 2)] {
+        let partial = Keypair::new();
+        let ix = begin_verification_ix(
+            registry.pubkey(),
+            partial.pubkey(),
+            CIRCUIT,
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            fixture.public_inputs.clone(),
+        );
+        send(&mut ctx, &[ix], &[&partial]).await.unwrap();
+        let ix = advance_verification_ix(partial.pubkey(), CIRCUIT, patient);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+        completions.push((partial, fixture));
+    }
+    for (index, (partial, fixture)) in completions.iter().enumerate() {
+        let record_nonce = next_record_nonce(&mut ctx, patient).await;
+        let nullifier = nullifier_address(&fixture.proof, ProofFormat::Uncompressed, &fixture.public_inputs);
+        let ix = complete_verification_ix(
+            registry.pubkey(),
+            partial.pubkey(),
+            CIRCUIT,
+            record_nonce,
+            nullifier,
+            patient,
+            CID,
+        );
+        let result = send(&mut ctx, &[ix], &[]).await;
+        if index == 0 {
+            result.unwrap();
+        } else {
+            assert_error(result, HealthcareError::StaleNonce);
+        }
+    }
+    assert_eq!(nonce(&mut ctx).await, 1);
+}
+
+#[tokio::test]
+async fn test_nonce_slot_validated_at_registration() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk_bytes = fixture(0, 0).vk_bytes;
+    let vk_hash = keccak::hash(&vk_bytes).to_bytes();
+    let config = VkConfig {
+        n_public: n_public(&vk_bytes),
+        nullifier_input: Some(1),
+        ..VkConfig::default()
+    };
+    for nonce_input in [1, config.n_public] {
+        let config = VkConfig {
+            nonce_input: Some(nonce_input),
+            ..config
+        };
+        let total_len = vk_bytes.len() as u32;
+        let ix = register_vk_ix(ctx.payer.pubkey(), registry.pubkey(), CIRCUIT, total_len, config, vk_hash);
+        assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidNonceInput);
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::AnchorDeserialize;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    HealthcareError, ProofFormat, RecordStatus, VerificationRecord, VerificationStatusView, VerificationType,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const VALIDITY_SECS: i64 = 24 * 60 * 60;
+
+/// A registry whose records expire after `VALIDITY_SECS`, and the payer's first
+/// record under it
+async fn setup(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let ix = set_default_validity_ix(ctx.payer.pubkey(), registry.pubkey(), VALIDITY_SECS);
+    send(ctx, &[ix], &[]).await.unwrap();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        ctx.payer.pubkey(),
+        fixture.proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        CID,
+    );
+    send(ctx, &[ix], &[]).await.unwrap();
+    (registry, verification_address(&ctx.payer.pubkey(), 0))
+}
+
+async fn status(
+    ctx: &mut ProgramTestContext,
+    registry: Pubkey,
+    patient: Pubkey,
+    verification_type: VerificationType,
+    record: Pubkey,
+) -> VerificationStatusView {
+    let ix = check_verification_status_ix(registry, patient, verification_type, record);
+    let answer = simulate_return_data(ctx, &[ix], &[]).await;
+    assert_eq!(answer.program_id, zk_healthcare::ID);
+    VerificationStatusView::try_from_slice(&answer.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_active_record_is_verified() {
+    let mut ctx = start().await;
+    let (registry, record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+
+    let view = status(&mut ctx, registry.pubkey(), patient, VerificationType::Eligibility, record).await;
+    let written: VerificationRecord = fetch(&mut ctx, record).await;
+    assert!(view.exists && !view.held);
+    assert_eq!((view.status, view.expires_at, view.record), (RecordStatus::Verified, written.expires_at, record));
+
+    // A hold shows, and leaves the record verified
+    send(&mut ctx, &[place_hold_ix(patient, registry.pubkey(), record, [0x0d; 32])], &[]).await.unwrap();
+    let view = status(&mut ctx, registry.pubkey(), patient, VerificationType::Eligibility, record).await;
+    assert_eq!((view.status, view.held), (RecordStatus::Verified, true));
+
+    // Only the latest record answers
+    let ix = check_verification_status_ix(registry.pubkey(), patient, VerificationType::Eligibility, patient);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::NotLatestRecord);
+}
+
+#[tokio::test]
+async fn test_record_past_expiry_is_expired() {
+    let mut ctx = start().await;
+    let (registry, record) = setup(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let written: VerificationRecord = fetch(&mut ctx, record).await;
+
+    // Before anyone marks it
+    warp_clock_to(&mut ctx, written.expires_at).await;
+    let view = status(&mut ctx, registry.pubkey(), patient, VerificationType::Eligibility, record).await;
+    assert!(view.exists);
+    assert_eq!((view.status, view.expires_at), (RecordStatus::Expired, written.expires_at));
+    let record_now: VerificationRecord = fetch(&mut ctx, record).await;
+    assert_eq!(record_now.status, RecordStatus::Verified);
+}
+
+#[tokio::test]
+async fn test_missing_record_does_not_exist() {
+    let mut ctx = start().await;
+    let (registry, record) = setup(&mut ctx).await;
+    let none = VerificationStatusView {
+        exists: false,
+        status: RecordStatus::Pending,
+        expires_at: 0,
+        record: Pubkey::default(),
+        held: false,
+    };
+
+    // A patient without an index, or without a record of the type
+    let stranger = Keypair::new().pubkey();
+    let view = status(&mut ctx, registry.pubkey(), stranger, VerificationType::Eligibility, stranger).await;
+    assert_eq!(view, none);
+    let patient = ctx.payer.pubkey();
+    let view = status(&mut ctx, registry.pubkey(), patient, VerificationType::Diagnosis, patient).await;
+    assert_eq!(view, none);
+
+    // nor under another registry
+    let other = initialize_registry(&mut ctx).await;
+    let view = status(&mut ctx, other.pubkey(), patient, VerificationType::Eligibility, record).await;
+    assert_eq!(view, none);
+}
@@ -0,0 +1,152 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account;
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use anchor_spl::token_2022::spl_token_2022::{self, state::Account as TokenAccount};
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, VerificationRecord};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// A registry with its verification mint and the circuit uploaded, and the
+/// record of a `verify_eligibility` that minted the payer a token
+async fn verified_with_token(ctx: &mut ProgramTestContext) -> (Keypair, Pubkey) {
+    let registry = initialize_registry(ctx).await;
+    let fixture = &batch_fixtures(1)[0];
+    upload_vk(ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    let ix = initialize_verification_mint_ix(authority, registry.pubkey());
+    send(ctx, &[ix], &[]).await.unwrap();
+
+    let patient = ctx.payer.pubkey();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[with_verification_token(ix, registry.pubkey(), patient)], &[]).await.unwrap();
+    (registry, verification_address(&patient, 0))
+}
+
+async fn token_balance(ctx: &mut ProgramTestContext, token_account: Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(token_account).await.unwrap().unwrap();
+    StateWithExtensions::<TokenAccount>::unpack(&account.data).unwrap().base.amount
+}
+
+#[tokio::test]
+async fn test_token_minted_on_verify() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified_with_token(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.token_mint, verification_mint_address(&registry.pubkey()));
+    let token_account = verification_token_account(&registry.pubkey(), &patient);
+    assert_eq!(token_balance(&mut ctx, token_account).await, 1);
+
+    // Without `mint_token` the record holds no token and none is minted
+    let fixture = &batch_fixtures(2)[1];
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        1,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(&mut ctx, &[with_force_new(ix)], &[]).await.unwrap();
+    let record: VerificationRecord = fetch(&mut ctx, verification_address(&patient, 1)).await;
+    assert_eq!(record.token_mint, Pubkey::default());
+    assert_eq!(token_balance(&mut ctx, token_account).await, 1);
+}
+
+#[tokio::test]
+async fn test_token_cannot_be_transferred() {
+    let mut ctx = start().await;
+    let (registry, _) = verified_with_token(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let mint = verification_mint_address(&registry.pubkey());
+    let source = verification_token_account(&registry.pubkey(), &patient);
+
+    let recipient = Pubkey::new_unique();
+    let ix = create_associated_token_account(&patient, &recipient, &mint, &spl_token_2022::ID);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let destination = verification_token_account(&registry.pubkey(), &recipient);
+    let ix = spl_token_2022::instruction::transfer_checked(
+        &spl_token_2022::ID,
+        &source,
+        &mint,
+        &destination,
+        &patient,
+        &[],
+        1,
+        0,
+    )
+    .unwrap();
+    assert!(send(&mut ctx, &[ix], &[]).await.is_err());
+    assert_eq!(token_balance(&mut ctx, source).await, 1);
+    assert_eq!(token_balance(&mut ctx, destination).await, 0);
+}
+
+#[tokio::test]
+async fn test_token_burned_on_revoke() {
+    let mut ctx = start().await;
+    let (registry, verification) = verified_with_token(&mut ctx).await;
+    let patient = ctx.payer.pubkey();
+    let token_account = verification_token_account(&registry.pubkey(), &patient);
+
+    let burn = burn_verification_token_ix(registry.pubkey(), verification, patient);
+    assert_error(send(&mut ctx, std::slice::from_ref(&burn), &[]).await, HealthcareError::RecordNotRevoked);
+
+    let revoke = revoke_verification_ix(patient, registry.pubkey(), verification, patient, 1);
+    send(&mut ctx, &[revoke, burn.clone()], &[]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, token_account).await, 0);
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert_eq!(record.token_mint, Pubkey::default());
+
+    // Past the slot, so the repeated burn isn't taken for the first one
+    warp_clock(&mut ctx, 0).await;
+    assert_error(send(&mut ctx, &[burn], &[]).await, HealthcareError::NoVerificationToken);
+}
+
+#[tokio::test]
+async fn test_minting_needs_the_registry_mint() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &fixture.vk_bytes).await;
+    let other = initialize_registry(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let ix = initialize_verification_mint_ix(authority, other.pubkey());
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let patient = ctx.payer.pubkey();
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        0,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    let mut ix = with_verification_token(ix, registry.pubkey(), patient);
+    let accounts = ix.accounts.len() - EVENT_CPI_ACCOUNTS;
+    ix.accounts[accounts - 4].pubkey = verification_mint_address(&other.pubkey());
+    ix.accounts[accounts - 3].pubkey = verification_token_account(&other.pubkey(), &patient);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidVerificationMint);
+}
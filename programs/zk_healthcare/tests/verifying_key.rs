@@ -0,0 +1,312 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    CurveId, HealthcareError, ProofFormat, ProvingScheme, VerificationRecord, VerifyingKey, VerifyingKeyPDA, VkConfig,
+};
+
+#[tokio::test]
+async fn test_chunked_upload_then_verify() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), "eligibility_v1", &fixture.vk_bytes).await;
+
+    let data = fetch_vk_data(&mut ctx, "eligibility_v1").await;
+    let vk = VerifyingKey::new(&data).unwrap();
+    assert!(vk.is_finalized());
+    assert_eq!(vk.vk_bytes(), fixture.vk_bytes);
+    assert_eq!(vk.vk_hash, keccak::hash(&fixture.vk_bytes).to_bytes());
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        "eligibility_v1",
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let record: VerificationRecord = fetch(&mut ctx, verification).await;
+    assert!(record.is_valid);
+}
+
+#[tokio::test]
+async fn test_verify_rejects_unfinalized_vk() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    let authority = ctx.payer.pubkey();
+
+    let config = VkConfig { n_public: 1, ..VkConfig::default() };
+    let (total_len, vk_hash) = (fixture.vk_bytes.len() as u32, keccak::hash(&fixture.vk_bytes).to_bytes());
+    let ix = register_vk_ix(authority, registry.pubkey(), "eligibility_v1", total_len, config, vk_hash);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let ix = write_vk_chunk_ix(authority, "eligibility_v1", 0, &fixture.vk_bytes[..VK_CHUNK_SIZE]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let nonce = next_record_nonce(&mut ctx, authority).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        "eligibility_v1",
+        authority,
+        fixture.proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+    );
+    assert_error(
+        send(&mut ctx, &[ix], &[]).await,
+        HealthcareError::VerifyingKeyNotFinalized,
+    );
+}
+
+#[tokio::test]
+async fn test_finalize_with_missing_bytes_rejected() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    let authority = ctx.payer.pubkey();
+
+    let config = VkConfig { n_public: 1, ..VkConfig::default() };
+    let (total_len, vk_hash) = (fixture.vk_bytes.len() as u32, keccak::hash(&fixture.vk_bytes).to_bytes());
+    let ix = register_vk_ix(authority, registry.pubkey(), "eligibility_v1", total_len, config, vk_hash);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Upload everything except the first chunk, last chunk first
+    for (i, chunk) in fixture.vk_bytes.chunks(VK_CHUNK_SIZE).enumerate().skip(1).rev() {
+        let ix = write_vk_chunk_ix(authority, "eligibility_v1", (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    assert_error(
+        send(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), "eligibility_v1")], &[]).await,
+        HealthcareError::VerifyingKeyIncomplete,
+    );
+
+    let ix = write_vk_chunk_ix(authority, "eligibility_v1", 0, &fixture.vk_bytes[..VK_CHUNK_SIZE]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    // Past the slot, so the repeated finalize isn't taken for the refused one
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), "eligibility_v1")], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_chunk_write_requires_authority() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let config = VkConfig { n_public: 1, ..VkConfig::default() };
+    let ix = register_vk_ix(authority, registry.pubkey(), "eligibility_v1", 584, config, [0; 32]);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    let intruder = Keypair::new();
+    let ix = write_vk_chunk_ix(intruder.pubkey(), "eligibility_v1", 0, &[0; 584]);
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), anchor_lang::error::ErrorCode::ConstraintHasOne as u32);
+}
+
+#[tokio::test]
+async fn test_prepared_vk_cached_and_used() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), "eligibility_v1", &fixture.vk_bytes).await;
+
+    let authority = ctx.payer.pubkey();
+    send(&mut ctx, &[prepare_vk_ix(authority, "eligibility_v1")], &[]).await.unwrap();
+    let data = fetch_vk_data(&mut ctx, "eligibility_v1").await;
+    let vk = VerifyingKey::new(&data).unwrap();
+    assert_eq!(vk.prepared_vk_bytes().len(), VerifyingKeyPDA::prepared_len(vk.total_len));
+
+    let nonce = next_record_nonce(&mut ctx, authority).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        "eligibility_v1",
+        authority,
+        fixture.proof,
+        ProofFormat::Uncompressed,
+        fixture.public_inputs,
+        "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+    );
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_public_input_count_mismatch() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), "eligibility_v1", &fixture.vk_bytes).await;
+
+    let too_many = [fixture.public_inputs.clone(), fixture.public_inputs.clone()].concat();
+    let cases = [
+        (Vec::new(), HealthcareError::EmptyPublicInputs),
+        (too_many, HealthcareError::PublicInputCountMismatch),
+    ];
+    for (public_inputs, expected) in cases {
+        let patient = ctx.payer.pubkey();
+        let nonce = next_record_nonce(&mut ctx, patient).await;
+        let ix = verify_eligibility_ix(
+            registry.pubkey(),
+            nonce,
+            "eligibility_v1",
+            patient,
+            fixture.proof.clone(),
+            ProofFormat::Uncompressed,
+            public_inputs,
+            "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+        );
+        assert_error(send(&mut ctx, &[ix], &[]).await, expected);
+    }
+}
+
+#[tokio::test]
+async fn test_register_rejects_length_inconsistent_with_n_public() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    let authority = ctx.payer.pubkey();
+
+    let config = VkConfig { n_public: 2, ..VkConfig::default() };
+    let total_len = fixture.vk_bytes.len() as u32;
+    let ix = register_vk_ix(authority, registry.pubkey(), "eligibility_v1", total_len, config, [0; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::PublicInputCountMismatch);
+}
+
+#[tokio::test]
+async fn test_register_records_curve_and_refuses_unsupported() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    let authority = ctx.payer.pubkey();
+    let total_len = fixture.vk_bytes.len() as u32;
+
+    // BLS12-381 has a Groth16 verifier only
+    let config = VkConfig { n_public: 1, curve: CurveId::Bls12_381, scheme: ProvingScheme::Plonk, ..VkConfig::default() };
+    let ix = register_vk_ix(authority, registry.pubkey(), "eligibility_bls", total_len, config, [0; 32]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::UnsupportedCurve);
+
+    upload_vk(&mut ctx, registry.pubkey(), "eligibility_v1", &fixture.vk_bytes).await;
+    let data = fetch_vk_data(&mut ctx, "eligibility_v1").await;
+    assert_eq!(VerifyingKey::new(&data).unwrap().curve(), CurveId::Bn254);
+}
+
+#[tokio::test]
+async fn test_corrupted_vk_refused() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    upload_vk(&mut ctx, registry.pubkey(), "eligibility_v1", &fixture.vk_bytes).await;
+
+    // Flip a byte of alpha_g1, just past the header
+    let address = vk_address("eligibility_v1");
+    let mut account = ctx.banks_client.get_account(address).await.unwrap().unwrap();
+    account.data[VerifyingKeyPDA::HEADER_LEN + 10] ^= 1;
+    ctx.set_account(&address, &account.into());
+
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(&mut ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        "eligibility_v1",
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+    );
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VerifyingKeyCorrupted);
+
+    let ix = verify_proof_readonly_ix(
+        registry.pubkey(),
+        "eligibility_v1",
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+    );
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VerifyingKeyCorrupted);
+}
+
+#[tokio::test]
+async fn test_finalize_rejects_bytes_not_matching_declared_hash() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let fixture = square_fixture(1);
+    let other = square_fixture(2);
+    let authority = ctx.payer.pubkey();
+
+    let config = VkConfig { n_public: 1, ..VkConfig::default() };
+    let (total_len, vk_hash) = (fixture.vk_bytes.len() as u32, keccak::hash(&other.vk_bytes).to_bytes());
+    let ix = register_vk_ix(authority, registry.pubkey(), "eligibility_v1", total_len, config, vk_hash);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    for (i, chunk) in fixture.vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_chunk_ix(authority, "eligibility_v1", (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    let ix = finalize_vk_ix(authority, registry.pubkey(), "eligibility_v1");
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VerifyingKeyCorrupted);
+}
+
+#[tokio::test]
+async fn test_vk_larger_than_one_allocation() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let bound = (0..159).map(ark_bn254::Fr::from).collect();
+    let fixture = bound_square_fixture(1, bound);
+    let authority = ctx.payer.pubkey();
+    let total_len = fixture.vk_bytes.len() as u32;
+    assert!(total_len > 10 * 1024);
+
+    let config = VkConfig { n_public: n_public(&fixture.vk_bytes), ..VkConfig::default() };
+    let vk_hash = keccak::hash(&fixture.vk_bytes).to_bytes();
+    let ix = register_vk_ix(authority, registry.pubkey(), "eligibility_large", total_len, config, vk_hash);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+
+    // Registered small: the tail of the key has nowhere to go yet
+    let ix = write_vk_chunk_ix(authority, "eligibility_large", 0, &fixture.vk_bytes[..VK_CHUNK_SIZE]);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VerifyingKeyNotAllocated);
+
+    let space = VerifyingKeyPDA::space(total_len);
+    let initial = VerifyingKeyPDA::initial_space(total_len);
+    // One instruction can only grow an account by MAX_PERMITTED_DATA_INCREASE
+    let ix = resize_vk_account_ix(authority, "eligibility_large", space as u32);
+    let code = error_code(send(&mut ctx, &[ix], &[]).await.unwrap_err());
+    assert_eq!(code, u32::from(anchor_lang::error::ErrorCode::AccountReallocExceedsLimit));
+
+    // Past the slot, so a growth step to the same length isn't taken for the refused one
+    warp_clock(&mut ctx, 0).await;
+    grow_vk_account(&mut ctx, "eligibility_large", total_len).await;
+    let account = ctx.banks_client.get_account(vk_address("eligibility_large")).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), space);
+    assert!(space > 2 * initial);
+    let ix = resize_vk_account_ix(authority, "eligibility_large", (space + 1) as u32);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::InvalidVkAccountSize);
+
+    for (i, chunk) in fixture.vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_chunk_ix(authority, "eligibility_large", (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+    send(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), "eligibility_large")], &[]).await.unwrap();
+    let ix = resize_vk_account_ix(authority, "eligibility_large", (space - 1) as u32);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VerifyingKeyAlreadyFinalized);
+
+    // 160 public inputs don't fit a transaction, so check the stored key in place
+    let data = fetch_vk_data(&mut ctx, "eligibility_large").await;
+    let vk = VerifyingKey::new(&data).unwrap();
+    assert!(vk.is_finalized() && vk.is_intact());
+    assert_eq!(vk.vk_bytes(), fixture.vk_bytes);
+    let proof = zk_healthcare::Groth16Proof::decode(&fixture.proof, ProofFormat::Uncompressed).unwrap();
+    assert!(vk.verify(&proof, &fixture.public_inputs, ProofFormat::Uncompressed).unwrap());
+}
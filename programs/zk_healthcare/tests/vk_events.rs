@@ -0,0 +1,103 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+//
+// Verifying key lifecycle as an indexer sees it. Captures events by swapping the
+// process-wide syscall stubs, so this binary holds a single test.
+
+mod common;
+
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{
+    ProofFormat, VerificationRecord, VerificationType, VerifyingKeyFinalized, VerifyingKeyRegistered,
+    VerifyingKeyUpdated, VkConfig,
+};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+async fn logged(ctx: &mut ProgramTestContext, ix: Instruction, log: &mut Vec<String>) {
+    let (result, logs) = send_logged(ctx, &[ix], &[]).await;
+    result.unwrap();
+    log.extend(logs);
+}
+
+/// Verify as the payer, expiring their record before this one
+async fn verify(ctx: &mut ProgramTestContext, registry: &Keypair, fixture: &Fixture) -> VerificationRecord {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let verification = verification_address(&patient, nonce);
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        fixture.proof.clone(),
+        ProofFormat::Uncompressed,
+        fixture.public_inputs.clone(),
+        CID,
+    );
+    send(ctx, &[with_force_new(ix)], &[]).await.unwrap();
+    fetch(ctx, verification).await
+}
+
+/// The key in force after each lifecycle event, replayed from the logs alone
+fn key_history(logs: &[String]) -> Vec<[u8; 32]> {
+    let mut declared = None;
+    let mut history = Vec::new();
+    for line in logs {
+        let line = std::slice::from_ref(line);
+        if let Some(event) = events::<VerifyingKeyRegistered>(line).pop() {
+            assert_eq!(event.circuit_id, CIRCUIT);
+            declared = Some(event.vk_hash);
+        } else if let Some(event) = events::<VerifyingKeyFinalized>(line).pop() {
+            assert_eq!(Some(event.vk_hash), declared, "finalized a key that wasn't registered");
+            history.push(event.vk_hash);
+        } else if let Some(event) = events::<VerifyingKeyUpdated>(line).pop() {
+            assert_eq!(history.last(), Some(&event.old_hash), "update from a key that wasn't active");
+            history.push(event.new_hash);
+        }
+    }
+    history
+}
+
+#[tokio::test]
+async fn test_indexer_reconstructs_key_history() {
+    let mut ctx = start_with_event_logs().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let authority = ctx.payer.pubkey();
+    let (old, new) = (square_fixture(1), square_fixture(2));
+    let mut log = Vec::new();
+
+    let config = VkConfig { n_public: 1, ..VkConfig::default() };
+    let (total_len, vk_hash) = (old.vk_bytes.len() as u32, keccak::hash(&old.vk_bytes).to_bytes());
+    let ix = register_vk_ix(authority, registry.pubkey(), CIRCUIT, total_len, config, vk_hash);
+    logged(&mut ctx, ix, &mut log).await;
+    for (i, chunk) in old.vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_chunk_ix(authority, CIRCUIT, (i * VK_CHUNK_SIZE) as u32, chunk);
+        logged(&mut ctx, ix, &mut log).await;
+    }
+    logged(&mut ctx, finalize_vk_ix(authority, registry.pubkey(), CIRCUIT), &mut log).await;
+    let ix = set_type_circuit_ix(authority, registry.pubkey(), CIRCUIT, VerificationType::Eligibility);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let first = verify(&mut ctx, &registry, &old).await;
+
+    let new_hash = keccak::hash(&new.vk_bytes).to_bytes();
+    let ix = propose_vk_update_ix(authority, registry.pubkey(), CIRCUIT, new_hash, new.vk_bytes.len() as u32);
+    logged(&mut ctx, ix, &mut log).await;
+    for (i, chunk) in new.vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_update_chunk_ix(authority, CIRCUIT, (i * VK_CHUNK_SIZE) as u32, chunk);
+        logged(&mut ctx, ix, &mut log).await;
+    }
+    warp_clock(&mut ctx, VK_UPDATE_DELAY_SECS).await;
+    logged(&mut ctx, activate_vk_update_ix(authority, registry.pubkey(), CIRCUIT), &mut log).await;
+    let second = verify(&mut ctx, &registry, &new).await;
+
+    let history = key_history(&log);
+    assert_eq!(history, [vk_hash, new_hash]);
+    // Each record names the key that accepted it
+    assert_eq!(first.vk_hash, history[0]);
+    assert_eq!(second.vk_hash, history[1]);
+}
@@ -0,0 +1,113 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::solana_program::keccak;
+use common::*;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::signature::{Keypair, Signer};
+use zk_healthcare::{HealthcareError, ProofFormat, VerifyingKey};
+
+const CIRCUIT: &str = "eligibility_v1";
+const CID: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// Submit as the payer, expiring their record before this one
+async fn submit(
+    ctx: &mut ProgramTestContext,
+    registry: &Keypair,
+    proof: &[u8],
+    public_inputs: &[u8],
+) -> Result<(), solana_program_test::BanksClientError> {
+    let patient = ctx.payer.pubkey();
+    let nonce = next_record_nonce(ctx, patient).await;
+    let ix = verify_eligibility_ix(
+        registry.pubkey(),
+        nonce,
+        CIRCUIT,
+        patient,
+        proof.to_vec(),
+        ProofFormat::Uncompressed,
+        public_inputs.to_vec(),
+        CID,
+    );
+    send(ctx, &[with_force_new(ix)], &[]).await
+}
+
+async fn stage_update(ctx: &mut ProgramTestContext, registry: &Keypair, vk_bytes: &[u8]) {
+    let authority = ctx.payer.pubkey();
+    let hash = keccak::hash(vk_bytes).to_bytes();
+    let ix = propose_vk_update_ix(authority, registry.pubkey(), CIRCUIT, hash, vk_bytes.len() as u32);
+    send(ctx, &[ix], &[]).await.unwrap();
+    for (i, chunk) in vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_update_chunk_ix(authority, CIRCUIT, (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(ctx, &[ix], &[]).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_update_waits_for_timelock() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let old = square_fixture(1);
+    let new = square_fixture(2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &old.vk_bytes).await;
+    let authority = ctx.payer.pubkey();
+    send(&mut ctx, &[prepare_vk_ix(authority, CIRCUIT)], &[]).await.unwrap();
+    stage_update(&mut ctx, &registry, &new.vk_bytes).await;
+
+    // The old key stays in force while the update is pending
+    submit(&mut ctx, &registry, &old.proof, &old.public_inputs).await.unwrap();
+    assert_error(
+        submit(&mut ctx, &registry, &new.proof, &new.public_inputs).await,
+        HealthcareError::PairingCheckFailed,
+    );
+
+    assert_error(
+        send(&mut ctx, &[activate_vk_update_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await,
+        HealthcareError::VkUpdateTimelockActive,
+    );
+
+    warp_clock(&mut ctx, VK_UPDATE_DELAY_SECS).await;
+    send(&mut ctx, &[activate_vk_update_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await.unwrap();
+
+    let data = fetch_vk_data(&mut ctx, CIRCUIT).await;
+    let vk = VerifyingKey::new(&data).unwrap();
+    assert_eq!(vk.vk_bytes(), new.vk_bytes);
+    // The cached pairing for the old key is dropped and must be re-prepared
+    assert!(vk.prepared_vk_bytes().is_empty());
+    assert!(ctx.banks_client.get_account(vk_update_address(CIRCUIT)).await.unwrap().is_none());
+
+    submit(&mut ctx, &registry, &new.proof, &new.public_inputs).await.unwrap();
+    // A fresh proof under the old key, since the first one is already nullified
+    assert_error(
+        submit(&mut ctx, &registry, &old.reprove(7), &old.public_inputs).await,
+        HealthcareError::PairingCheckFailed,
+    );
+}
+
+#[tokio::test]
+async fn test_update_rejects_bytes_not_matching_proposal() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let old = square_fixture(1);
+    let new = square_fixture(2);
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &old.vk_bytes).await;
+
+    let authority = ctx.payer.pubkey();
+    let ix = propose_vk_update_ix(authority, registry.pubkey(), CIRCUIT, [7; 32], new.vk_bytes.len() as u32);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    for (i, chunk) in new.vk_bytes.chunks(VK_CHUNK_SIZE).enumerate() {
+        let ix = write_vk_update_chunk_ix(authority, CIRCUIT, (i * VK_CHUNK_SIZE) as u32, chunk);
+        send(&mut ctx, &[ix], &[]).await.unwrap();
+    }
+
+    warp_clock(&mut ctx, VK_UPDATE_DELAY_SECS).await;
+    assert_error(
+        send(&mut ctx, &[activate_vk_update_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await,
+        HealthcareError::VkUpdateHashMismatch,
+    );
+
+    // A mistaken proposal can be withdrawn and re-proposed
+    send(&mut ctx, &[cancel_vk_update_ix(authority, CIRCUIT)], &[]).await.unwrap();
+    stage_update(&mut ctx, &registry, &new.vk_bytes).await;
+}
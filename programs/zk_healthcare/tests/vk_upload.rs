@@ -0,0 +1,180 @@
+// Copyright 2025 Raza Ahmad. Licensed under Apache 2.0.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::keccak;
+use ark_bn254::Fr;
+use common::*;
+use solana_program_test::{BanksClientError, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use zk_healthcare::{HealthcareError, HealthcareRegistry, UploadStatus, VerifyingKey, VkConfig, VK_UPLOAD_TIMEOUT_SECS};
+
+const CIRCUIT: &str = "eligibility_v1";
+
+/// A key spanning three upload chunks
+fn vk_bytes() -> Vec<u8> {
+    let vk_bytes = bound_square_fixture(1, (0..8).map(Fr::from).collect()).vk_bytes;
+    assert_eq!(vk_bytes.len().div_ceil(VK_CHUNK_SIZE), 3);
+    vk_bytes
+}
+
+/// Register `vk_bytes` and write the chunks at `chunks`, leaving it unfinalized
+async fn start_upload(ctx: &mut ProgramTestContext, registry: &Keypair, vk_bytes: &[u8], chunks: &[usize]) {
+    let authority = ctx.payer.pubkey();
+    let config = VkConfig { n_public: n_public(vk_bytes), ..VkConfig::default() };
+    let (total_len, vk_hash) = (vk_bytes.len() as u32, keccak::hash(vk_bytes).to_bytes());
+    let ix = register_vk_ix(authority, registry.pubkey(), CIRCUIT, total_len, config, vk_hash);
+    send(ctx, &[ix], &[]).await.unwrap();
+    grow_vk_account(ctx, CIRCUIT, total_len).await;
+    for &index in chunks {
+        write_chunk_at(ctx, vk_bytes, index * VK_CHUNK_SIZE).await;
+    }
+}
+
+/// Write the upload chunk of `vk_bytes` starting at `offset`
+async fn write_chunk_at(ctx: &mut ProgramTestContext, vk_bytes: &[u8], offset: usize) {
+    let chunk = &vk_bytes[offset..(offset + VK_CHUNK_SIZE).min(vk_bytes.len())];
+    let ix = write_vk_chunk_ix(ctx.payer.pubkey(), CIRCUIT, offset as u32, chunk);
+    send(ctx, &[ix], &[]).await.unwrap();
+}
+
+async fn upload_status(ctx: &mut ProgramTestContext) -> UploadStatus {
+    let data = fetch_vk_data(ctx, CIRCUIT).await;
+    VerifyingKey::new(&data).unwrap().upload_status()
+}
+
+/// Send `ix` paid for and signed by `payer` alone
+async fn send_as(ctx: &mut ProgramTestContext, payer: &Keypair, ix: Instruction) -> Result<(), BanksClientError> {
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+async fn registered_circuits(ctx: &mut ProgramTestContext, registry: &Keypair) -> u16 {
+    let registry: HealthcareRegistry = fetch(ctx, registry.pubkey()).await;
+    registry.registered_circuits
+}
+
+#[tokio::test]
+async fn test_upload_resumes_after_interruption() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk_bytes = vk_bytes();
+    start_upload(&mut ctx, &registry, &vk_bytes, &[0]).await;
+
+    // The uploader goes away for a while, then picks up where the account says
+    warp_clock(&mut ctx, VK_UPLOAD_TIMEOUT_SECS / 2).await;
+    let UploadStatus::InProgress { written } = upload_status(&mut ctx).await else {
+        panic!("upload should still be in progress");
+    };
+    assert_eq!(written as usize, VK_CHUNK_SIZE);
+    for offset in (written as usize..vk_bytes.len()).step_by(VK_CHUNK_SIZE) {
+        write_chunk_at(&mut ctx, &vk_bytes, offset).await;
+    }
+    let total_len = vk_bytes.len() as u32;
+    assert_eq!(upload_status(&mut ctx).await, UploadStatus::InProgress { written: total_len });
+
+    let authority = ctx.payer.pubkey();
+    send(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await.unwrap();
+    assert_eq!(upload_status(&mut ctx).await, UploadStatus::Finalized);
+}
+
+#[tokio::test]
+async fn test_finalize_with_gap_rejected() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk_bytes = vk_bytes();
+    start_upload(&mut ctx, &registry, &vk_bytes, &[2, 0]).await;
+
+    // Bytes past the gap don't count towards the resume offset
+    let written = VK_CHUNK_SIZE as u32;
+    assert_eq!(upload_status(&mut ctx).await, UploadStatus::InProgress { written });
+    let authority = ctx.payer.pubkey();
+    assert_error(
+        send(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await,
+        HealthcareError::VerifyingKeyIncomplete,
+    );
+
+    write_chunk_at(&mut ctx, &vk_bytes, VK_CHUNK_SIZE).await;
+    let total_len = vk_bytes.len() as u32;
+    assert_eq!(upload_status(&mut ctx).await, UploadStatus::InProgress { written: total_len });
+    // Past the slot, so the retried finalize isn't taken for the failed one
+    warp_clock(&mut ctx, 0).await;
+    send(&mut ctx, &[finalize_vk_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_authority_aborts_upload() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk_bytes = vk_bytes();
+    start_upload(&mut ctx, &registry, &vk_bytes, &[0]).await;
+    let authority = ctx.payer.pubkey();
+
+    let intruder = Keypair::new();
+    let ix = abort_vk_upload_ix(intruder.pubkey(), registry.pubkey(), CIRCUIT);
+    let err = send(&mut ctx, &[ix], &[&intruder]).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+
+    let rent = ctx.banks_client.get_balance(vk_address(CIRCUIT)).await.unwrap();
+    let before = ctx.banks_client.get_balance(authority).await.unwrap();
+    send(&mut ctx, &[abort_vk_upload_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await.unwrap();
+    assert!(ctx.banks_client.get_account(vk_address(CIRCUIT)).await.unwrap().is_none());
+    assert_eq!(ctx.banks_client.get_balance(authority).await.unwrap(), before + rent - 5000);
+    assert_eq!(registered_circuits(&mut ctx, &registry).await, 0);
+
+    // The circuit ID is free again, and a finished upload can't be aborted
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &vk_bytes).await;
+    assert_error(
+        send(&mut ctx, &[abort_vk_upload_ix(authority, registry.pubkey(), CIRCUIT)], &[]).await,
+        HealthcareError::VerifyingKeyAlreadyFinalized,
+    );
+}
+
+#[tokio::test]
+async fn test_stale_upload_collected_by_anyone_after_timeout() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    let vk_bytes = vk_bytes();
+    start_upload(&mut ctx, &registry, &vk_bytes, &[0]).await;
+    let authority = ctx.payer.pubkey();
+
+    let collector = Keypair::new();
+    let ix = system_instruction::transfer(&authority, &collector.pubkey(), 1_000_000_000);
+    send(&mut ctx, &[ix], &[]).await.unwrap();
+    let gc = gc_stale_upload_ix(authority, registry.pubkey(), CIRCUIT);
+
+    // A write restarts the clock
+    warp_clock(&mut ctx, VK_UPLOAD_TIMEOUT_SECS - 60).await;
+    write_chunk_at(&mut ctx, &vk_bytes, VK_CHUNK_SIZE).await;
+    warp_clock(&mut ctx, 120).await;
+    assert_error(send_as(&mut ctx, &collector, gc.clone()).await, HealthcareError::VkUploadNotStale);
+
+    // The rent only ever goes back to the authority
+    warp_clock(&mut ctx, VK_UPLOAD_TIMEOUT_SECS).await;
+    let ix = gc_stale_upload_ix(collector.pubkey(), registry.pubkey(), CIRCUIT);
+    let err = send_as(&mut ctx, &collector, ix).await.unwrap_err();
+    assert_eq!(error_code(err), ErrorCode::ConstraintHasOne as u32);
+
+    let rent = ctx.banks_client.get_balance(vk_address(CIRCUIT)).await.unwrap();
+    let before = ctx.banks_client.get_balance(authority).await.unwrap();
+    send_as(&mut ctx, &collector, gc).await.unwrap();
+    assert!(ctx.banks_client.get_account(vk_address(CIRCUIT)).await.unwrap().is_none());
+    assert_eq!(ctx.banks_client.get_balance(authority).await.unwrap(), before + rent);
+    assert_eq!(registered_circuits(&mut ctx, &registry).await, 0);
+}
+
+#[tokio::test]
+async fn test_finalized_key_is_never_stale() {
+    let mut ctx = start().await;
+    let registry = initialize_registry(&mut ctx).await;
+    upload_vk(&mut ctx, registry.pubkey(), CIRCUIT, &vk_bytes()).await;
+
+    warp_clock(&mut ctx, VK_UPLOAD_TIMEOUT_SECS).await;
+    let ix = gc_stale_upload_ix(ctx.payer.pubkey(), registry.pubkey(), CIRCUIT);
+    assert_error(send(&mut ctx, &[ix], &[]).await, HealthcareError::VerifyingKeyAlreadyFinalized);
+}